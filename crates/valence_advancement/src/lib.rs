@@ -3,6 +3,7 @@
 
 pub mod event;
 pub mod packet;
+pub mod progress;
 
 use std::borrow::Cow;
 use std::io::Write;
@@ -17,6 +18,7 @@ pub use bevy_hierarchy;
 use bevy_hierarchy::{Children, Parent};
 use event::{handle_advancement_tab_change, AdvancementTabChange};
 use packet::SelectAdvancementTabS2c;
+use progress::{update_advancement_progress, AdvancementDone, AdvancementProgress};
 use rustc_hash::FxHashMap;
 use valence_client::{Client, FlushPacketsSet, SpawnClientsSet};
 use valence_core::ident::Ident;
@@ -46,6 +48,7 @@ impl Plugin for AdvancementPlugin {
                 .before(WriteAdvancementPacketToClientsSet),
         ))
         .add_event::<AdvancementTabChange>()
+        .add_event::<AdvancementDone>()
         .add_system(
             add_advancement_update_component_to_new_clients
                 .after(SpawnClientsSet)
@@ -53,6 +56,11 @@ impl Plugin for AdvancementPlugin {
         )
         .add_system(handle_advancement_tab_change.in_base_set(CoreSet::PreUpdate))
         .add_system(update_advancement_cached_bytes.in_set(WriteAdvancementToCacheSet))
+        .add_system(
+            update_advancement_progress
+                .in_base_set(CoreSet::PostUpdate)
+                .before(WriteAdvancementPacketToClientsSet),
+        )
         .add_system(send_advancement_update_packet.in_set(WriteAdvancementPacketToClientsSet));
     }
 }
@@ -70,12 +78,15 @@ pub struct AdvancementBundle {
 
 fn add_advancement_update_component_to_new_clients(
     mut commands: Commands,
-    query: Query<Entity, Added<Client>>,
+    query: Query<(Entity, Option<&AdvancementProgress>), Added<Client>>,
 ) {
-    for client in query.iter() {
-        commands
-            .entity(client)
-            .insert(AdvancementClientUpdate::default());
+    for (client, progress) in query.iter() {
+        let mut client = commands.entity(client);
+        client.insert(AdvancementClientUpdate::default());
+
+        if progress.is_none() {
+            client.insert(AdvancementProgress::default());
+        }
     }
 }
 
@@ -462,21 +473,25 @@ impl AdvancementClientUpdate {
         });
     }
 
-    /// Marks criteria as done
+    /// Marks criteria as done. This doesn't change the client's
+    /// [`AdvancementProgress`](progress::AdvancementProgress), prefer
+    /// [`AdvancementProgress::grant`](progress::AdvancementProgress::grant).
     pub fn criteria_done(&mut self, criteria: Entity) {
-        self.progress.push((
-            criteria,
-            Some(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as i64,
-            ),
-        ))
+        self.progress.push((criteria, Some(now_millis())))
     }
 
-    /// Marks criteria as undone
+    /// Marks criteria as undone. This doesn't change the client's
+    /// [`AdvancementProgress`](progress::AdvancementProgress), prefer
+    /// [`AdvancementProgress::revoke`](progress::AdvancementProgress::revoke).
     pub fn criteria_undone(&mut self, criteria: Entity) {
         self.progress.push((criteria, None))
     }
 }
+
+/// Milliseconds since the Unix epoch, used as the time criteria are obtained.
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_hierarchy::{Children, Parent};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use valence_core::ident::Ident;
+
+use crate::{
+    now_millis, Advancement, AdvancementClientUpdate, AdvancementCriteria, AdvancementRequirements,
+};
+
+/// Which criteria a client has obtained. Clients are given this component when
+/// they join.
+///
+/// Granting and revoking criteria with this component sends the progress to
+/// the client, which shows a toast when an advancement with
+/// [`AdvancementDisplay::show_toast`] is completed. The progress of
+/// advancements is also sent when they are sent to the client with
+/// [`AdvancementClientUpdate::send_advancements`].
+///
+/// [`AdvancementDisplay::show_toast`]: crate::AdvancementDisplay::show_toast
+#[derive(Component, Default, Debug)]
+pub struct AdvancementProgress {
+    /// Obtained criteria and the time they were obtained at in milliseconds
+    /// since the Unix epoch.
+    criteria: FxHashMap<Entity, i64>,
+    /// Completed advancements.
+    completed: FxHashSet<Entity>,
+    /// Criteria that have been granted or revoked since the last update.
+    changed: Vec<Entity>,
+}
+
+impl AdvancementProgress {
+    /// Grants a criteria. Returns `false` if it was already obtained.
+    pub fn grant(&mut self, criteria: Entity) -> bool {
+        self.grant_at(criteria, now_millis())
+    }
+
+    /// Grants a criteria obtained at `time` milliseconds since the Unix
+    /// epoch. Returns `false` if it was already obtained.
+    pub fn grant_at(&mut self, criteria: Entity, time: i64) -> bool {
+        if self.criteria.insert(criteria, time).is_some() {
+            return false;
+        }
+
+        self.changed.push(criteria);
+        true
+    }
+
+    /// Revokes a criteria. Returns `false` if it wasn't obtained.
+    pub fn revoke(&mut self, criteria: Entity) -> bool {
+        if self.criteria.remove(&criteria).is_none() {
+            return false;
+        }
+
+        self.changed.push(criteria);
+        true
+    }
+
+    pub fn is_obtained(&self, criteria: Entity) -> bool {
+        self.criteria.contains_key(&criteria)
+    }
+
+    /// Returns when a criteria was obtained in milliseconds since the Unix
+    /// epoch.
+    pub fn obtained_at(&self, criteria: Entity) -> Option<i64> {
+        self.criteria.get(&criteria).copied()
+    }
+
+    /// Returns if all requirements of an advancement are met. This is updated
+    /// at the end of the tick.
+    pub fn is_completed(&self, advancement: Entity) -> bool {
+        self.completed.contains(&advancement)
+    }
+
+    /// Returns the obtained criteria and when they were obtained.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, i64)> + '_ {
+        self.criteria.iter().map(|(&c, &time)| (c, time))
+    }
+}
+
+/// Sent when a client completes all requirements of an advancement.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AdvancementDone {
+    pub client: Entity,
+    pub advancement: Entity,
+}
+
+/// The [`AdvancementProgress`] of a client in a form that can be stored with
+/// the rest of the client's data, since entities change between runs.
+///
+/// Maps advancement identifiers to the obtained criteria and the times they
+/// were obtained at.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct SavedAdvancementProgress(pub BTreeMap<Ident<String>, BTreeMap<Ident<String>, i64>>);
+
+/// Converts [`AdvancementProgress`] to and from [`SavedAdvancementProgress`].
+#[derive(SystemParam)]
+pub struct AdvancementProgressStorage<'w, 's> {
+    advancements: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Advancement,
+            &'static AdvancementRequirements,
+            Option<&'static Children>,
+        ),
+    >,
+    criteria: Query<'w, 's, (&'static AdvancementCriteria, &'static Parent)>,
+}
+
+impl AdvancementProgressStorage<'_, '_> {
+    pub fn save(&self, progress: &AdvancementProgress) -> SavedAdvancementProgress {
+        let mut saved = SavedAdvancementProgress::default();
+
+        for (criteria, time) in progress.iter() {
+            let Ok((c_identifier, parent)) = self.criteria.get(criteria) else {
+                continue
+            };
+
+            let Ok((_, a_identifier, ..)) = self.advancements.get(parent.get()) else {
+                continue
+            };
+
+            saved
+                .0
+                .entry(a_identifier.get().to_string_ident())
+                .or_default()
+                .insert(c_identifier.get().to_string_ident(), time);
+        }
+
+        saved
+    }
+
+    /// Restores saved progress. Advancements and criteria that don't exist
+    /// anymore are ignored.
+    ///
+    /// No [`AdvancementDone`] events are sent for advancements that were
+    /// already completed.
+    pub fn load(&self, saved: &SavedAdvancementProgress) -> AdvancementProgress {
+        let mut progress = AdvancementProgress::default();
+
+        for (advancement, a_identifier, requirements, children) in &self.advancements {
+            let (Some(saved_criteria), Some(children)) =
+                (saved.0.get(a_identifier.get().as_str()), children) else {
+                continue
+            };
+
+            for &child in children {
+                let Ok((c_identifier, _)) = self.criteria.get(child) else {
+                    continue
+                };
+
+                if let Some(&time) = saved_criteria.get(c_identifier.get().as_str()) {
+                    progress.grant_at(child, time);
+                }
+            }
+
+            if is_done(&progress, requirements) {
+                progress.completed.insert(advancement);
+            }
+        }
+
+        progress
+    }
+}
+
+pub(crate) fn update_advancement_progress(
+    mut clients: Query<
+        (
+            Entity,
+            &mut AdvancementProgress,
+            &mut AdvancementClientUpdate,
+        ),
+        Or<(
+            Changed<AdvancementProgress>,
+            Changed<AdvancementClientUpdate>,
+        )>,
+    >,
+    advancements: Query<(&AdvancementRequirements, Option<&Children>), With<Advancement>>,
+    criteria_query: Query<&Parent, With<AdvancementCriteria>>,
+    mut events: EventWriter<AdvancementDone>,
+) {
+    for (client, mut progress, mut client_update) in &mut clients {
+        let progress = progress.bypass_change_detection();
+
+        let mut sent = FxHashSet::default();
+        let mut affected = FxHashSet::default();
+
+        for criteria in std::mem::take(&mut progress.changed) {
+            if !sent.insert(criteria) {
+                continue;
+            }
+
+            client_update
+                .progress
+                .push((criteria, progress.obtained_at(criteria)));
+
+            if let Ok(parent) = criteria_query.get(criteria) {
+                affected.insert(parent.get());
+            }
+        }
+
+        // Newly sent advancements need their progress as well.
+        let new_advancements = client_update.new_advancements.clone();
+
+        for advancement in new_advancements {
+            let Ok((_, Some(children))) = advancements.get(advancement) else {
+                continue
+            };
+
+            for &child in children {
+                if let Some(time) = progress.obtained_at(child) {
+                    if sent.insert(child) {
+                        client_update.progress.push((child, Some(time)));
+                    }
+                }
+            }
+        }
+
+        for advancement in affected {
+            let Ok((requirements, _)) = advancements.get(advancement) else {
+                continue
+            };
+
+            if is_done(progress, requirements) {
+                if progress.completed.insert(advancement) {
+                    events.send(AdvancementDone {
+                        client,
+                        advancement,
+                    });
+                }
+            } else {
+                progress.completed.remove(&advancement);
+            }
+        }
+    }
+}
+
+fn is_done(progress: &AdvancementProgress, requirements: &AdvancementRequirements) -> bool {
+    !requirements.0.is_empty()
+        && requirements
+            .0
+            .iter()
+            .all(|column| column.iter().any(|&c| progress.is_obtained(c)))
+}
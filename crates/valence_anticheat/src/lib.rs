@@ -0,0 +1,350 @@
+//! # Anticheat
+//! Validates the movement of clients against the blocks of their instance.
+//!
+//! By default, the server accepts every position sent by a client. With the
+//! [`AnticheatPlugin`] added, each [`MovementEvent`] is checked for
+//!
+//! - moving further horizontally than [`AnticheatSettings::max_speed`],
+//! - moving up faster than [`AnticheatSettings::max_ascent`] or staying in the
+//!   air for longer than [`AnticheatSettings::max_air_ticks`], and
+//! - moving through the collision shapes of blocks.
+//!
+//! Clients that fail a check are moved back to their previous position, which
+//! sends a position sync to the client. A [`MovementViolationEvent`] is sent
+//! with the amount of the violation and the client's total
+//! [`ViolationScore`], which decays over time. What to do with clients that
+//! keep failing checks, such as kicking them, is left to the server.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_anticheat::MovementViolationEvent;
+//! # use valence_core::despawn::Despawned;
+//! fn disconnect_cheaters(
+//!     mut events: EventReader<MovementViolationEvent>,
+//!     mut commands: Commands,
+//! ) {
+//!     for event in events.iter() {
+//!         if event.score > 20.0 {
+//!             commands.entity(event.client).insert(Despawned);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Clients in spectator mode and clients riding a vehicle are not checked.
+//! Clients in creative mode and clients flying with
+//! [`PlayerAbilities::allow_flying`] are only checked for speed and moving
+//! through blocks.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::{DVec3, Vec3Swizzles};
+use valence_block::{BlockKind, PropName, PropValue};
+use valence_client::abilities::PlayerAbilities;
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_client::movement::MovementEvent;
+use valence_client::vehicle::Vehicle;
+use valence_client::Client;
+use valence_core::aabb::Aabb;
+use valence_core::game_mode::GameMode;
+use valence_entity::effects::{ActiveEffects, StatusEffect};
+use valence_entity::entity::Pose;
+use valence_entity::hitbox::HitboxShape;
+use valence_entity::{Location, Position};
+use valence_instance::Instance;
+
+/// The step height of players.
+const STEP_HEIGHT: f64 = 0.6;
+
+pub struct AnticheatPlugin;
+
+/// When client movement is validated. Systems that read [`MovementEvent`]s
+/// and only want to see valid movement should run _after_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ValidateMovementSet;
+
+impl Plugin for AnticheatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnticheatSettings>()
+            .add_event::<MovementViolationEvent>()
+            .add_system(
+                validate_movement
+                    .in_schedule(EventLoopSchedule)
+                    .in_base_set(EventLoopSet::Update)
+                    .in_set(ValidateMovementSet),
+            )
+            .add_system(add_validation_state.in_base_set(CoreSet::PreUpdate))
+            .add_system(decay_violation_scores.in_base_set(CoreSet::PostUpdate));
+    }
+}
+
+/// Limits used to validate client movement.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct AnticheatSettings {
+    /// The greatest horizontal distance a client may move in one movement
+    /// packet, in blocks. This is increased by the speed effect.
+    pub max_speed: f64,
+    /// The greatest distance a client may move up in one movement packet, in
+    /// blocks. This is increased by the jump boost effect.
+    pub max_ascent: f64,
+    /// The number of consecutive movement packets a client may stay in the
+    /// air without falling, including packets that don't change the position.
+    /// Clients on ladders and vines or in fluids are not in the air.
+    pub max_air_ticks: u32,
+    /// If clients are prevented from moving through blocks.
+    pub check_no_clip: bool,
+    /// The amount subtracted from every [`ViolationScore`] each tick.
+    pub score_decay: f64,
+}
+
+impl Default for AnticheatSettings {
+    fn default() -> Self {
+        Self {
+            max_speed: 1.0,
+            max_ascent: 0.8,
+            max_air_ticks: 40,
+            check_no_clip: true,
+            score_decay: 0.05,
+        }
+    }
+}
+
+/// The kind of check a client failed.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ViolationKind {
+    /// The client moved too far horizontally.
+    Speed,
+    /// The client moved up too fast or stayed in the air for too long.
+    Fly,
+    /// The client moved through a block.
+    NoClip,
+}
+
+/// Sent when a client fails a movement check and is moved back.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MovementViolationEvent {
+    pub client: Entity,
+    pub kind: ViolationKind,
+    /// How far over the limit the client went. The unit depends on the kind of
+    /// violation.
+    pub amount: f64,
+    /// The [`ViolationScore`] of the client after adding this violation.
+    pub score: f64,
+}
+
+/// The sum of the violations of a client, minus
+/// [`AnticheatSettings::score_decay`] for every tick since.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct ViolationScore(pub f64);
+
+/// Movement state of a client tracked between movement packets.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct MovementValidationState {
+    /// Consecutive movement packets where the client was in the air without
+    /// falling.
+    air_ticks: u32,
+}
+
+impl MovementValidationState {
+    pub fn air_ticks(&self) -> u32 {
+        self.air_ticks
+    }
+}
+
+fn add_validation_state(
+    clients: Query<Entity, (Added<Client>, Without<MovementValidationState>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert((
+            MovementValidationState::default(),
+            ViolationScore::default(),
+        ));
+    }
+}
+
+fn validate_movement(
+    mut movements: EventReader<MovementEvent>,
+    mut clients: Query<
+        (
+            &mut Position,
+            &Location,
+            &GameMode,
+            &PlayerAbilities,
+            &mut MovementValidationState,
+            &mut ViolationScore,
+            Option<&HitboxShape>,
+            Option<&Pose>,
+            Option<&ActiveEffects>,
+        ),
+        Without<Vehicle>,
+    >,
+    instances: Query<&Instance>,
+    settings: Res<AnticheatSettings>,
+    mut violations: EventWriter<MovementViolationEvent>,
+) {
+    for mov in movements.iter() {
+        let Ok((mut pos, loc, game_mode, abilities, mut state, mut score, shape, pose, effects)) =
+            clients.get_mut(mov.client) else {
+            continue
+        };
+
+        if *game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        let effect_level = |effect| {
+            effects
+                .and_then(|e| e.get(effect))
+                .map_or(0.0, |e| e.amplifier as f64 + 1.0)
+        };
+
+        let delta = mov.position - mov.old_position;
+        let gliding = pose.is_some_and(|p| p.0 == valence_entity::Pose::FallFlying);
+        let exempt_from_fly = *game_mode == GameMode::Creative
+            || (abilities.flying && abilities.allow_flying)
+            || gliding
+            || effects.is_some_and(|e| {
+                e.contains(StatusEffect::Levitation) || e.contains(StatusEffect::SlowFalling)
+            });
+
+        let hitbox = match shape {
+            Some(shape) => shape.get(),
+            None => Aabb::from_bottom_size(DVec3::ZERO, [0.6, 1.8, 0.6]),
+        };
+
+        let instance = instances.get(loc.0).ok();
+
+        let mut violation = None;
+
+        // Speed check.
+        let max_speed = settings.max_speed * (1.0 + 0.2 * effect_level(StatusEffect::Speed));
+        let horizontal = delta.xz().length();
+
+        // Gliding with an elytra and fireworks is much faster than walking.
+        if !gliding && horizontal > max_speed {
+            violation = Some((ViolationKind::Speed, horizontal - max_speed));
+        }
+
+        // Fly checks.
+        if violation.is_none() && !exempt_from_fly {
+            let max_ascent = settings.max_ascent + 0.1 * effect_level(StatusEffect::JumpBoost);
+
+            if delta.y > max_ascent {
+                violation = Some((ViolationKind::Fly, delta.y - max_ascent));
+            } else if let Some(instance) = instance {
+                let new_hitbox = hitbox + mov.position;
+
+                // Movements that don't change the position count too, so
+                // clients can't hover in place.
+                if delta.y >= 0.0
+                    && !instance.is_on_ground(new_hitbox)
+                    && !touches_climbable(instance, new_hitbox)
+                {
+                    state.air_ticks += 1;
+
+                    if state.air_ticks > settings.max_air_ticks {
+                        violation = Some((ViolationKind::Fly, 1.0));
+                    }
+                } else {
+                    state.air_ticks = 0;
+                }
+            }
+        }
+
+        // No-clip check.
+        if let (None, true, Some(instance)) = (violation, settings.check_no_clip, instance) {
+            let resolved = instance.move_entity(hitbox + mov.old_position, delta, STEP_HEIGHT);
+            let blocked = (delta - resolved.delta).length();
+
+            // Allow some error, since the client's collision isn't exactly the same.
+            if blocked > 0.1 {
+                violation = Some((ViolationKind::NoClip, blocked));
+            }
+        }
+
+        if let Some((kind, amount)) = violation {
+            // Move the client back. This is sent to the client as a teleport.
+            pos.0 = mov.old_position;
+            score.0 += amount;
+
+            violations.send(MovementViolationEvent {
+                client: mov.client,
+                kind,
+                amount,
+                score: score.0,
+            });
+        }
+    }
+}
+
+/// Returns `true` if `aabb` touches a block that clients can move up in
+/// without jumping, like ladders, vines and fluids.
+fn touches_climbable(instance: &Instance, aabb: Aabb) -> bool {
+    let min = aabb.min.floor().as_ivec3();
+    let max = aabb.max.floor().as_ivec3();
+
+    for y in min.y..=max.y {
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let Some(block) = instance.block([x, y, z]) else {
+                    continue
+                };
+
+                let state = block.state();
+
+                let climbable = matches!(
+                    state.to_kind(),
+                    BlockKind::Ladder
+                        | BlockKind::Vine
+                        | BlockKind::Scaffolding
+                        | BlockKind::WeepingVines
+                        | BlockKind::WeepingVinesPlant
+                        | BlockKind::TwistingVines
+                        | BlockKind::TwistingVinesPlant
+                        | BlockKind::CaveVines
+                        | BlockKind::CaveVinesPlant
+                        | BlockKind::BubbleColumn
+                );
+
+                if climbable
+                    || state.is_liquid()
+                    || state.get(PropName::Waterlogged) == Some(PropValue::True)
+                {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+fn decay_violation_scores(
+    mut scores: Query<&mut ViolationScore>,
+    settings: Res<AnticheatSettings>,
+) {
+    for mut score in &mut scores {
+        if score.0 > 0.0 {
+            score.0 = (score.0 - settings.score_decay).max(0.0);
+        }
+    }
+}
@@ -59,6 +59,8 @@ pub(crate) enum ParseChunkError {
     UnknownBlockEntityIdent(String),
     #[error("invalid block entity position")]
     InvalidBlockEntityPosition,
+    #[error("invalid scheduled block tick")]
+    InvalidBlockTick,
 }
 
 /// Takes an Anvil chunk in NBT form and writes its data to a Valence [`Chunk`].
@@ -301,6 +303,35 @@ where
         }
     }
 
+    // Block ticks are optional since they are absent from older chunks.
+    if let Some(Value::List(List::Compound(block_ticks))) = nbt.get("block_ticks") {
+        for comp in block_ticks {
+            let (
+                Some(Value::Int(x)),
+                Some(Value::Int(y)),
+                Some(Value::Int(z)),
+                Some(Value::Int(delay)),
+                Some(Value::Int(priority)),
+            ) = (comp.get("x"), comp.get("y"), comp.get("z"), comp.get("t"), comp.get("p")) else {
+                return Err(ParseChunkError::InvalidBlockTick);
+            };
+
+            // Skip ticks outside of the sections we're writing.
+            let y = match usize::try_from(y + sect_offset * 16) {
+                Ok(y) if y < chunk.section_count() * 16 => y,
+                _ => continue,
+            };
+
+            chunk.schedule_tick(
+                x.mod_floor(&16) as usize,
+                y,
+                z.mod_floor(&16) as usize,
+                (*delay).max(0) as u32,
+                *priority,
+            );
+        }
+    }
+
     Ok(())
 }
 
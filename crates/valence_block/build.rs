@@ -23,6 +23,11 @@ struct Block {
     properties: Vec<Property>,
     default_state_id: u16,
     states: Vec<State>,
+    /// Missing from block data extracted before hardness was added.
+    #[serde(default)]
+    hardness: Option<f32>,
+    #[serde(default)]
+    requires_tool: bool,
 }
 
 impl Block {
@@ -408,6 +413,30 @@ fn build() -> anyhow::Result<TokenStream> {
         })
         .collect::<TokenStream>();
 
+    let block_kind_hardness_arms = blocks
+        .iter()
+        .filter_map(|block| {
+            let name = ident(block.name.replace('.', "_").to_pascal_case());
+            let hardness = block.hardness?;
+
+            Some(quote! {
+                BlockKind::#name => Some(#hardness),
+            })
+        })
+        .collect::<TokenStream>();
+
+    let block_kind_requires_tool_arms = blocks
+        .iter()
+        .filter(|block| block.requires_tool)
+        .map(|block| {
+            let name = ident(block.name.replace('.', "_").to_pascal_case());
+
+            quote! {
+                BlockKind::#name => true,
+            }
+        })
+        .collect::<TokenStream>();
+
     let block_kind_from_raw_arms = blocks
         .iter()
         .map(|block| {
@@ -788,6 +817,30 @@ fn build() -> anyhow::Result<TokenStream> {
                 }
             }
 
+            /// Returns the hardness of this block kind, which determines how
+            /// long it takes to break. Blocks that can't be broken, such as
+            /// bedrock, have a negative hardness.
+            ///
+            /// Returns `None` if the hardness is missing from the extracted
+            /// block data.
+            pub const fn hardness(self) -> Option<f32> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #block_kind_hardness_arms
+                    _ => None,
+                }
+            }
+
+            /// Returns `true` if this block kind only drops items when broken
+            /// with the correct tool, such as stone with a pickaxe.
+            pub const fn requires_tool(self) -> bool {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #block_kind_requires_tool_arms
+                    _ => false,
+                }
+            }
+
             /// Constructs a block kind from a raw block kind ID.
             ///
             /// If the given ID is invalid, `None` is returned.
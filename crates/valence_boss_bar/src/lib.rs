@@ -0,0 +1,284 @@
+//! # Boss bars
+//! The bars shown at the top of the screen during boss fights, which are also
+//! useful for timers and announcements.
+//!
+//! A boss bar is an entity made from a [`BossBarBundle`]. Clients see the bar
+//! once they are added to its [`BossBarViewers`].
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_boss_bar::packet::{BossBarColor, BossBarDivision};
+//! # use valence_boss_bar::{BossBarBundle, BossBarHealth};
+//! fn spawn_timer(mut commands: Commands, client: Entity) -> Entity {
+//!     let mut bundle =
+//!         BossBarBundle::new("Time left", BossBarColor::Blue, BossBarDivision::TenNotches);
+//!     bundle.viewers.insert(client);
+//!
+//!     commands.spawn(bundle).id()
+//! }
+//!
+//! fn tick_timer(mut bars: Query<&mut BossBarHealth>) {
+//!     for mut health in &mut bars {
+//!         health.0 = (health.0 - 0.01).max(0.0);
+//!     }
+//! }
+//! ```
+//!
+//! Changes to the bar are sent to its viewers. Despawning the bar removes it
+//! for all viewers, and disconnected clients are removed from the viewers.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+pub mod packet;
+
+use std::collections::BTreeSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use packet::{BossBarAction, BossBarColor, BossBarDivision, BossBarFlags, BossBarS2c};
+use valence_client::{Client, FlushPacketsSet};
+use valence_core::despawn::Despawned;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::text::Text;
+use valence_core::uuid::UniqueId;
+
+pub struct BossBarPlugin;
+
+impl Plugin for BossBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                remove_disconnected_viewers,
+                update_boss_bars.after(remove_disconnected_viewers),
+                remove_despawned_boss_bars,
+            )
+                .in_base_set(CoreSet::PostUpdate)
+                .before(FlushPacketsSet),
+        );
+    }
+}
+
+#[derive(Bundle)]
+pub struct BossBarBundle {
+    pub id: UniqueId,
+    pub title: BossBarTitle,
+    pub health: BossBarHealth,
+    pub style: BossBarStyle,
+    pub flags: BossBarFlags,
+    pub viewers: BossBarViewers,
+}
+
+impl BossBarBundle {
+    /// Creates a full boss bar without flags or viewers.
+    pub fn new(title: impl Into<Text>, color: BossBarColor, division: BossBarDivision) -> Self {
+        Self {
+            id: UniqueId::default(),
+            title: BossBarTitle(title.into()),
+            health: BossBarHealth(1.0),
+            style: BossBarStyle { color, division },
+            flags: BossBarFlags::new(),
+            viewers: BossBarViewers::default(),
+        }
+    }
+}
+
+/// The text shown above a boss bar.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct BossBarTitle(pub Text);
+
+/// How full a boss bar is, from `0.0` to `1.0`.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct BossBarHealth(pub f32);
+
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BossBarStyle {
+    pub color: BossBarColor,
+    pub division: BossBarDivision,
+}
+
+/// The clients that see a boss bar.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct BossBarViewers {
+    viewers: BTreeSet<Entity>,
+    /// The viewers the boss bar was sent to.
+    sent: BTreeSet<Entity>,
+}
+
+impl BossBarViewers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the boss bar to a client. Returns `false` if the client already
+    /// sees it.
+    pub fn insert(&mut self, client: Entity) -> bool {
+        self.viewers.insert(client)
+    }
+
+    /// Hides the boss bar from a client. Returns `false` if the client didn't
+    /// see it.
+    pub fn remove(&mut self, client: Entity) -> bool {
+        self.viewers.remove(&client)
+    }
+
+    pub fn contains(&self, client: Entity) -> bool {
+        self.viewers.contains(&client)
+    }
+
+    pub fn clear(&mut self) {
+        self.viewers.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.viewers.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.viewers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.viewers.is_empty()
+    }
+}
+
+fn remove_disconnected_viewers(
+    mut disconnected: RemovedComponents<Client>,
+    mut boss_bars: Query<&mut BossBarViewers>,
+) {
+    let disconnected: Vec<_> = disconnected.iter().collect();
+
+    if disconnected.is_empty() {
+        return;
+    }
+
+    for mut viewers in &mut boss_bars {
+        for &client in &disconnected {
+            if viewers.contains(client) {
+                viewers.remove(client);
+            }
+        }
+    }
+}
+
+fn update_boss_bars(
+    mut boss_bars: Query<
+        (
+            &UniqueId,
+            Ref<BossBarTitle>,
+            Ref<BossBarHealth>,
+            Ref<BossBarStyle>,
+            Ref<BossBarFlags>,
+            &mut BossBarViewers,
+        ),
+        (
+            Or<(
+                Changed<BossBarTitle>,
+                Changed<BossBarHealth>,
+                Changed<BossBarStyle>,
+                Changed<BossBarFlags>,
+                Changed<BossBarViewers>,
+            )>,
+            Without<Despawned>,
+        ),
+    >,
+    mut clients: Query<&mut Client>,
+) {
+    for (id, title, health, style, flags, mut viewers) in &mut boss_bars {
+        let mut updates = vec![];
+
+        if title.is_changed() {
+            updates.push(BossBarAction::UpdateTitle(title.0.clone()));
+        }
+
+        if health.is_changed() {
+            updates.push(BossBarAction::UpdateHealth(health.0));
+        }
+
+        if style.is_changed() {
+            updates.push(BossBarAction::UpdateStyle(style.color, style.division));
+        }
+
+        if flags.is_changed() {
+            updates.push(BossBarAction::UpdateFlags(*flags));
+        }
+
+        // Viewers that already see the boss bar get the changes.
+        if !updates.is_empty() {
+            for &viewer in viewers.sent.intersection(&viewers.viewers) {
+                if let Ok(mut client) = clients.get_mut(viewer) {
+                    for action in &updates {
+                        client.write_packet(&BossBarS2c {
+                            id: id.0,
+                            action: action.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if !viewers.is_changed() {
+            continue;
+        }
+
+        let viewers = viewers.bypass_change_detection();
+
+        for &viewer in viewers.sent.difference(&viewers.viewers) {
+            if let Ok(mut client) = clients.get_mut(viewer) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: BossBarAction::Remove,
+                });
+            }
+        }
+
+        for &viewer in viewers.viewers.difference(&viewers.sent) {
+            if let Ok(mut client) = clients.get_mut(viewer) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: BossBarAction::Add {
+                        title: title.0.clone(),
+                        health: health.0,
+                        color: style.color,
+                        division: style.division,
+                        flags: *flags,
+                    },
+                });
+            }
+        }
+
+        viewers.sent = viewers.viewers.clone();
+    }
+}
+
+fn remove_despawned_boss_bars(
+    boss_bars: Query<(&UniqueId, &BossBarViewers), Added<Despawned>>,
+    mut clients: Query<&mut Client>,
+) {
+    for (id, viewers) in &boss_bars {
+        for &viewer in &viewers.sent {
+            if let Ok(mut client) = clients.get_mut(viewer) {
+                client.write_packet(&BossBarS2c {
+                    id: id.0,
+                    action: BossBarAction::Remove,
+                });
+            }
+        }
+    }
+}
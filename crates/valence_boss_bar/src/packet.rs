@@ -0,0 +1,59 @@
+use bevy_ecs::prelude::*;
+use bitfield_struct::bitfield;
+use uuid::Uuid;
+use valence_core::protocol::{packet_id, Decode, Encode, Packet};
+use valence_core::text::Text;
+
+#[derive(Clone, Debug, Encode, Decode, Packet)]
+#[packet(id = packet_id::BOSS_BAR_S2C)]
+pub struct BossBarS2c {
+    pub id: Uuid,
+    pub action: BossBarAction,
+}
+
+#[derive(Clone, PartialEq, Debug, Encode, Decode)]
+pub enum BossBarAction {
+    Add {
+        title: Text,
+        health: f32,
+        color: BossBarColor,
+        division: BossBarDivision,
+        flags: BossBarFlags,
+    },
+    Remove,
+    UpdateHealth(f32),
+    UpdateTitle(Text),
+    UpdateStyle(BossBarColor, BossBarDivision),
+    UpdateFlags(BossBarFlags),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum BossBarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub enum BossBarDivision {
+    NoDivision,
+    SixNotches,
+    TenNotches,
+    TwelveNotches,
+    TwentyNotches,
+}
+
+/// The flags of a boss bar, which is also a component of boss bar entities.
+#[bitfield(u8)]
+#[derive(Component, PartialEq, Eq, Encode, Decode)]
+pub struct BossBarFlags {
+    pub darken_sky: bool,
+    pub dragon_bar: bool,
+    pub create_fog: bool,
+    #[bits(5)]
+    _pad: u8,
+}
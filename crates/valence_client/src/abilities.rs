@@ -0,0 +1,170 @@
+//! Player abilities such as flying and instant block breaking.
+//!
+//! The [`PlayerAbilities`] of a client are sent to it whenever they change.
+//! They are reset to the defaults for the client's [`GameMode`] when the game
+//! mode changes, like in vanilla.
+
+use super::*;
+use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
+use crate::packet::{PlayerAbilitiesFlags, PlayerAbilitiesS2c, UpdatePlayerAbilitiesC2s};
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<ToggleFlyEvent>()
+        .add_systems(
+            (
+                reset_abilities_on_game_mode_change,
+                update_player_abilities
+                    .after(initial_join)
+                    .after(respawn)
+                    .after(reset_abilities_on_game_mode_change),
+            )
+                .in_set(UpdateClientsSet),
+        )
+        .add_system(
+            handle_update_player_abilities
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::PreUpdate),
+        );
+}
+
+/// The abilities of a client.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct PlayerAbilities {
+    /// If the client is invulnerable. This only affects the client's
+    /// prediction of damage.
+    pub invulnerable: bool,
+    /// If the client is currently flying. Clients update this when they start
+    /// or stop flying.
+    pub flying: bool,
+    /// If the client is allowed to fly.
+    pub allow_flying: bool,
+    /// If the client breaks blocks instantly, like in creative mode.
+    pub instant_break: bool,
+    /// The flying speed of the client. Defaults to `0.05`.
+    pub flying_speed: f32,
+    /// The walking speed of the client, which is used by the client to change
+    /// its field of view as it speeds up or slows down. This is also known as
+    /// the FOV modifier and defaults to `0.1`.
+    ///
+    /// This doesn't change how fast the client walks. Use the movement speed
+    /// attribute for that.
+    pub fov_modifier: f32,
+}
+
+impl PlayerAbilities {
+    /// Returns the abilities of a client in `game_mode`.
+    pub fn for_game_mode(game_mode: GameMode) -> Self {
+        let mut abilities = Self::default();
+
+        match game_mode {
+            GameMode::Creative => {
+                abilities.allow_flying = true;
+                abilities.instant_break = true;
+                abilities.invulnerable = true;
+            }
+            GameMode::Spectator => {
+                abilities.allow_flying = true;
+                abilities.flying = true;
+                abilities.invulnerable = true;
+            }
+            GameMode::Survival | GameMode::Adventure => {}
+        }
+
+        abilities
+    }
+}
+
+impl Default for PlayerAbilities {
+    fn default() -> Self {
+        Self {
+            invulnerable: false,
+            flying: false,
+            allow_flying: false,
+            instant_break: false,
+            flying_speed: 0.05,
+            fov_modifier: 0.1,
+        }
+    }
+}
+
+/// Sent when a client starts or stops flying.
+///
+/// Clients that aren't allowed to fly are stopped automatically and no event
+/// is sent.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ToggleFlyEvent {
+    pub client: Entity,
+    pub flying: bool,
+}
+
+fn reset_abilities_on_game_mode_change(
+    mut clients: Query<(&GameMode, &mut PlayerAbilities), Changed<GameMode>>,
+) {
+    for (game_mode, mut abilities) in &mut clients {
+        let new = PlayerAbilities {
+            flying_speed: abilities.flying_speed,
+            fov_modifier: abilities.fov_modifier,
+            ..PlayerAbilities::for_game_mode(*game_mode)
+        };
+
+        if *abilities != new {
+            *abilities = new;
+        }
+    }
+}
+
+/// Sends the abilities to clients when they change. Clients reset their
+/// abilities when respawning, so they are also sent when the location changes.
+#[allow(clippy::type_complexity)]
+fn update_player_abilities(
+    mut clients: Query<
+        (&mut Client, &PlayerAbilities),
+        Or<(Changed<PlayerAbilities>, Changed<Location>)>,
+    >,
+) {
+    for (mut client, abilities) in &mut clients {
+        client.write_packet(&PlayerAbilitiesS2c {
+            flags: PlayerAbilitiesFlags::new()
+                .with_invulnerable(abilities.invulnerable)
+                .with_flying(abilities.flying)
+                .with_allow_flying(abilities.allow_flying)
+                .with_instant_break(abilities.instant_break),
+            flying_speed: abilities.flying_speed,
+            fov_modifier: abilities.fov_modifier,
+        });
+    }
+}
+
+fn handle_update_player_abilities(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut PlayerAbilities>,
+    mut events: EventWriter<ToggleFlyEvent>,
+) {
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<UpdatePlayerAbilitiesC2s>() else {
+            continue
+        };
+
+        let Ok(mut abilities) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let flying = matches!(pkt, UpdatePlayerAbilitiesC2s::StartFlying);
+
+        if flying && !abilities.allow_flying {
+            // Sends the abilities again to stop the client from flying.
+            abilities.set_changed();
+            continue;
+        }
+
+        if abilities.flying != flying {
+            // The client already knows it's flying, so it isn't sent again.
+            abilities.bypass_change_detection().flying = flying;
+
+            events.send(ToggleFlyEvent {
+                client: packet.client,
+                flying,
+            });
+        }
+    }
+}
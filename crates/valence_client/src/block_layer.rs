@@ -0,0 +1,212 @@
+//! Per-client block overrides.
+//!
+//! A [`BlockLayer`] attached to a client changes the blocks that client sees
+//! without modifying the client's [`Instance`]. Other clients in the instance
+//! continue to see the blocks of the instance. This is useful for things like
+//! per-player ore highlights, build previews, or doors that only open for one
+//! player.
+//!
+//! Overridden blocks are resent whenever the client loads the chunk containing
+//! them or the chunk is modified in the instance, so the override stays in
+//! place until it is removed from the layer.
+//!
+//! Only block states can be overridden. Block entities, collision, and other
+//! server-side behavior are still determined by the instance.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+use valence_block::BlockState;
+use valence_core::protocol::var_long::VarLong;
+use valence_instance::packet::{BlockUpdateS2c, ChunkDeltaUpdateS2c};
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_system(
+        update_block_layers
+            .after(update_view)
+            .after(respawn)
+            .before(update_old_view_dist)
+            .in_set(UpdateClientsSet),
+    );
+}
+
+/// A set of blocks that replace the blocks of the client's [`Instance`] for
+/// that client only. See the [module-level documentation](self) for more.
+#[derive(Component, Clone, Default, Debug)]
+pub struct BlockLayer {
+    chunks: BTreeMap<ChunkPos, HashMap<BlockPos, BlockState>>,
+    /// Positions that were added or removed since the last tick.
+    modified: Vec<BlockPos>,
+}
+
+impl BlockLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the overriding block state at the given position, if any.
+    pub fn block(&self, pos: impl Into<BlockPos>) -> Option<BlockState> {
+        let pos = pos.into();
+
+        self.chunks
+            .get(&ChunkPos::from_block_pos(pos))
+            .and_then(|blocks| blocks.get(&pos))
+            .copied()
+    }
+
+    /// Overrides the block at the given position with `state`. The previous
+    /// overriding block state is returned.
+    pub fn set_block(&mut self, pos: impl Into<BlockPos>, state: BlockState) -> Option<BlockState> {
+        let pos = pos.into();
+
+        let old = self
+            .chunks
+            .entry(ChunkPos::from_block_pos(pos))
+            .or_default()
+            .insert(pos, state);
+
+        if old != Some(state) {
+            self.modified.push(pos);
+        }
+
+        old
+    }
+
+    /// Removes the override at the given position, so that the client sees
+    /// the block from the instance again. The previous overriding block state
+    /// is returned.
+    pub fn remove_block(&mut self, pos: impl Into<BlockPos>) -> Option<BlockState> {
+        let pos = pos.into();
+        let chunk_pos = ChunkPos::from_block_pos(pos);
+
+        let blocks = self.chunks.get_mut(&chunk_pos)?;
+        let old = blocks.remove(&pos)?;
+
+        if blocks.is_empty() {
+            self.chunks.remove(&chunk_pos);
+        }
+
+        self.modified.push(pos);
+
+        Some(old)
+    }
+
+    /// Removes all overrides from this layer.
+    pub fn clear(&mut self) {
+        for (_, blocks) in mem::take(&mut self.chunks) {
+            self.modified.extend(blocks.into_keys());
+        }
+    }
+
+    /// Returns an iterator over all overridden blocks in this layer. The order
+    /// of the blocks is unspecified.
+    pub fn iter(&self) -> impl Iterator<Item = (BlockPos, BlockState)> + '_ {
+        self.chunks
+            .values()
+            .flat_map(|blocks| blocks.iter().map(|(&pos, &state)| (pos, state)))
+    }
+
+    /// Returns the number of overridden blocks in this layer.
+    pub fn len(&self) -> usize {
+        self.chunks.values().map(|blocks| blocks.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+fn update_block_layers(
+    mut clients: Query<(
+        &mut Client,
+        &mut BlockLayer,
+        View,
+        OldView,
+        &Location,
+        &OldLocation,
+    )>,
+    instances: Query<&Instance>,
+) {
+    for (mut client, mut layer, view, old_view, loc, old_loc) in &mut clients {
+        let Ok(instance) = instances.get(loc.0) else {
+            continue
+        };
+
+        let view = view.get();
+        let old_view = old_view.get();
+        let layer = layer.bypass_change_detection();
+
+        // Send the blocks that were overridden or restored since the last tick.
+        for pos in mem::take(&mut layer.modified) {
+            let chunk_pos = ChunkPos::from_block_pos(pos);
+
+            if !view.contains(chunk_pos) {
+                continue;
+            }
+
+            let state = match layer.block(pos) {
+                Some(state) => state,
+                None => match instance.block(pos) {
+                    Some(block) => block.state(),
+                    None => continue,
+                },
+            };
+
+            client.write_packet(&BlockUpdateS2c {
+                position: pos,
+                block_id: VarInt(state.to_raw() as i32),
+            });
+        }
+
+        // Overrides are lost when the client receives the chunk again or the
+        // blocks in the chunk are changed, so those need to be resent.
+        for (&chunk_pos, blocks) in &layer.chunks {
+            if !view.contains(chunk_pos) {
+                continue;
+            }
+
+            let Some(chunk) = instance.chunk(chunk_pos) else {
+                continue
+            };
+
+            if loc.0 == old_loc.get() && old_view.contains(chunk_pos) && !chunk.is_modified() {
+                continue;
+            }
+
+            write_overrides(&mut client, chunk_pos, blocks);
+        }
+    }
+}
+
+fn write_overrides(
+    client: &mut Client,
+    chunk_pos: ChunkPos,
+    blocks: &HashMap<BlockPos, BlockState>,
+) {
+    let mut sections: BTreeMap<i32, Vec<VarLong>> = BTreeMap::new();
+
+    for (pos, state) in blocks {
+        let packed = (state.to_raw() as i64) << 12
+            | (pos.x.rem_euclid(16) as i64) << 8
+            | (pos.z.rem_euclid(16) as i64) << 4
+            | pos.y.rem_euclid(16) as i64;
+
+        sections
+            .entry(pos.y.div_euclid(16))
+            .or_default()
+            .push(VarLong(packed));
+    }
+
+    for (sect_y, blocks) in sections {
+        let chunk_section_position = (chunk_pos.x as i64) << 42
+            | (chunk_pos.z as i64 & 0x3fffff) << 20
+            | sect_y as i64 & 0xfffff;
+
+        client.write_packet(&ChunkDeltaUpdateS2c {
+            chunk_section_position,
+            blocks: Cow::Owned(blocks),
+        });
+    }
+}
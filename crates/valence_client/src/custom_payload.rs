@@ -1,3 +1,17 @@
+//! Plugin messages sent on custom payload channels.
+//!
+//! Every payload a client sends is turned into a [`CustomPayloadEvent`], which
+//! can be decoded into any [`Decode`] type. The channels the server listens on
+//! are registered in the [`PluginChannels`] resource and announced to clients
+//! with `minecraft:register` when they join, which some client mods wait for
+//! before they send anything. The `minecraft:brand`, `minecraft:register`,
+//! and `minecraft:unregister` channels of clients are handled here and kept in
+//! the [`ClientChannels`] component.
+
+use std::collections::BTreeSet;
+
+use anyhow::ensure;
+use valence_core::ident;
 use valence_core::protocol::raw::RawBytes;
 use valence_core::protocol::{packet_id, Decode, Encode};
 
@@ -5,13 +19,29 @@ use super::*;
 use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
 
 pub(super) fn build(app: &mut App) {
-    app.add_event::<CustomPayloadEvent>().add_system(
-        handle_custom_payload
-            .in_schedule(EventLoopSchedule)
-            .in_base_set(EventLoopSet::PreUpdate),
-    );
+    app.init_resource::<PluginChannels>()
+        .add_event::<CustomPayloadEvent>()
+        .add_event::<ChannelRegistrationEvent>()
+        .add_system(
+            handle_custom_payload
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::PreUpdate),
+        )
+        .add_systems(
+            (send_brand, register_channels)
+                .chain()
+                .in_set(UpdateClientsSet),
+        );
 }
 
+const BRAND: Ident<&str> = ident!("minecraft:brand");
+const REGISTER: Ident<&str> = ident!("minecraft:register");
+const UNREGISTER: Ident<&str> = ident!("minecraft:unregister");
+
+/// The most channels a client can register. Registrations beyond this are
+/// ignored, so clients can't fill the memory of the server with channels.
+pub const MAX_CLIENT_CHANNELS: usize = 128;
+
 #[derive(Clone, Debug)]
 pub struct CustomPayloadEvent {
     pub client: Entity,
@@ -19,6 +49,106 @@ pub struct CustomPayloadEvent {
     pub data: Box<[u8]>,
 }
 
+impl CustomPayloadEvent {
+    /// Decodes the payload as a `T`. Fails if the payload is invalid or has
+    /// data left after the `T`.
+    pub fn decode<'a, T: Decode<'a>>(&'a self) -> anyhow::Result<T> {
+        let mut r = &self.data[..];
+        let value = T::decode(&mut r)?;
+
+        ensure!(
+            r.is_empty(),
+            "{} bytes left over after decoding payload on channel `{}`",
+            r.len(),
+            self.channel
+        );
+
+        Ok(value)
+    }
+}
+
+/// Sent when a client registers or unregisters a channel it listens on.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChannelRegistrationEvent {
+    pub client: Entity,
+    pub channel: Ident<String>,
+    /// If the channel was registered rather than unregistered.
+    pub registered: bool,
+}
+
+/// The channels the server listens on and the brand of the server.
+///
+/// The channels are announced to clients with `minecraft:register` when they
+/// join and when the channels change.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct PluginChannels {
+    channels: BTreeSet<Ident<String>>,
+    brand: String,
+}
+
+impl PluginChannels {
+    /// Adds a channel the server listens on. Returns `false` if the channel
+    /// was already registered.
+    pub fn register(&mut self, channel: Ident<String>) -> bool {
+        self.channels.insert(channel)
+    }
+
+    pub fn unregister(&mut self, channel: Ident<&str>) -> bool {
+        self.channels.remove(channel.as_str())
+    }
+
+    pub fn is_registered(&self, channel: Ident<&str>) -> bool {
+        self.channels.contains(channel.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Ident<&str>> + '_ {
+        self.channels.iter().map(|c| c.as_str_ident())
+    }
+
+    /// The brand sent to clients when they join, shown in the debug screen.
+    pub fn brand(&self) -> &str {
+        &self.brand
+    }
+
+    /// Sets the brand of the server. Only clients that join afterwards see the
+    /// new brand.
+    pub fn set_brand(&mut self, brand: impl Into<String>) {
+        self.brand = brand.into();
+    }
+}
+
+impl Default for PluginChannels {
+    fn default() -> Self {
+        Self {
+            channels: BTreeSet::new(),
+            brand: "Valence".into(),
+        }
+    }
+}
+
+/// The channels a client listens on and the brand of the client, like
+/// `vanilla` or `fabric`. At most [`MAX_CLIENT_CHANNELS`] channels are kept.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ClientChannels {
+    channels: BTreeSet<Ident<String>>,
+    brand: Option<String>,
+}
+
+impl ClientChannels {
+    pub fn is_registered(&self, channel: Ident<&str>) -> bool {
+        self.channels.contains(channel.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Ident<&str>> + '_ {
+        self.channels.iter().map(|c| c.as_str_ident())
+    }
+
+    /// The brand the client sent, or `None` if it hasn't sent one yet.
+    pub fn brand(&self) -> Option<&str> {
+        self.brand.as_deref()
+    }
+}
+
 impl Client {
     pub fn send_custom_payload(&mut self, channel: Ident<&str>, data: &[u8]) {
         self.write_packet(&CustomPayloadS2c {
@@ -26,19 +156,98 @@ impl Client {
             data: data.into(),
         });
     }
+
+    /// Encodes `payload` and sends it on `channel`.
+    pub fn send_plugin_message(&mut self, channel: Ident<&str>, payload: &impl Encode) {
+        let mut data = vec![];
+
+        match payload.encode(&mut data) {
+            Ok(()) => self.send_custom_payload(channel, &data),
+            Err(e) => warn!("failed to encode payload on channel `{channel}`: {e:#}"),
+        }
+    }
 }
 
 fn handle_custom_payload(
     mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut ClientChannels>,
     mut events: EventWriter<CustomPayloadEvent>,
+    mut registration_events: EventWriter<ChannelRegistrationEvent>,
 ) {
     for packet in packets.iter() {
-        if let Some(pkt) = packet.decode::<CustomPayloadC2s>() {
-            events.send(CustomPayloadEvent {
-                client: packet.client,
-                channel: pkt.channel.into(),
-                data: pkt.data.0.into(),
-            })
+        let Some(pkt) = packet.decode::<CustomPayloadC2s>() else {
+            continue
+        };
+
+        if let Ok(mut channels) = clients.get_mut(packet.client) {
+            if pkt.channel == BRAND {
+                if let Ok(brand) = <&str>::decode(&mut &pkt.data.0[..]) {
+                    channels.brand = Some(brand.to_owned());
+                }
+            } else if pkt.channel == REGISTER || pkt.channel == UNREGISTER {
+                let registered = pkt.channel == REGISTER;
+
+                // The payload is a list of channels separated by null bytes.
+                for channel in pkt.data.0.split(|&b| b == 0).filter(|c| !c.is_empty()) {
+                    let Ok(channel) = std::str::from_utf8(channel) else {
+                        continue
+                    };
+
+                    let Ok(channel) = Ident::<String>::try_from(channel) else {
+                        continue
+                    };
+
+                    let changed = if registered {
+                        channels.channels.len() < MAX_CLIENT_CHANNELS
+                            && channels.channels.insert(channel.clone())
+                    } else {
+                        channels.channels.remove(&channel)
+                    };
+
+                    if changed {
+                        registration_events.send(ChannelRegistrationEvent {
+                            client: packet.client,
+                            channel,
+                            registered,
+                        });
+                    }
+                }
+            }
+        }
+
+        events.send(CustomPayloadEvent {
+            client: packet.client,
+            channel: pkt.channel.into(),
+            data: pkt.data.0.into(),
+        })
+    }
+}
+
+fn send_brand(mut clients: Query<&mut Client, Added<Client>>, channels: Res<PluginChannels>) {
+    for mut client in &mut clients {
+        client.send_plugin_message(BRAND, &channels.brand);
+    }
+}
+
+/// Announces the channels of the server to new clients, or to all clients
+/// when the channels change.
+fn register_channels(mut clients: Query<&mut Client>, channels: Res<PluginChannels>) {
+    if channels.channels.is_empty() {
+        return;
+    }
+
+    let changed = channels.is_changed();
+
+    let data = channels
+        .channels
+        .iter()
+        .map(|c| c.as_str())
+        .collect::<Vec<_>>()
+        .join("\0");
+
+    for mut client in &mut clients {
+        if changed || client.is_added() {
+            client.send_custom_payload(REGISTER, data.as_bytes());
         }
     }
 }
@@ -0,0 +1,85 @@
+//! Game profiles and skins.
+//!
+//! The game profile of a client or player entity is made up of its
+//! [`Username`], [`UniqueId`], and [`Properties`]. The [`GameProfile`] query
+//! gives access to all three at once.
+//!
+//! The skin and cape are stored in the signed `textures` property. When the
+//! [`Properties`] of a player entity change, the player list entry is re-added
+//! and the entity is respawned for every client that can see it, so the new
+//! skin is shown immediately. A client does not see changes to its own skin
+//! until it respawns.
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_system(
+        respawn_changed_skins
+            .before(resend_entities)
+            .in_set(UpdateClientsSet),
+    );
+}
+
+/// A query for the game profile of a client or player entity.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct GameProfile {
+    pub username: &'static mut Username,
+    pub uuid: &'static UniqueId,
+    pub properties: &'static mut Properties,
+}
+
+impl GameProfileItem<'_> {
+    /// Returns the `textures` property, which contains the skin and cape.
+    pub fn textures(&self) -> Option<&Property> {
+        self.properties.textures()
+    }
+
+    /// Replaces the skin and cape with the given `textures` property. See
+    /// [`Properties::set_textures`].
+    pub fn set_textures(&mut self, value: impl Into<String>, signature: Option<String>) {
+        self.properties.set_textures(value, signature);
+    }
+}
+
+impl Properties {
+    /// Replaces the `textures` property, or adds it if it doesn't exist.
+    ///
+    /// The value is the base64 encoded texture data. Clients will only
+    /// display the textures if the property has a valid signature from Mojang.
+    pub fn set_textures(&mut self, value: impl Into<String>, signature: Option<String>) {
+        let value = value.into();
+
+        match self.textures_mut() {
+            Some(prop) => {
+                prop.value = value;
+                prop.signature = signature;
+            }
+            None => self.0.push(Property {
+                name: "textures".into(),
+                value,
+                signature,
+            }),
+        }
+    }
+
+    /// Removes the `textures` property, which resets the skin to one of the
+    /// defaults.
+    pub fn remove_textures(&mut self) -> Option<Property> {
+        let idx = self.0.iter().position(|prop| prop.name == "textures")?;
+        Some(self.0.remove(idx))
+    }
+}
+
+/// Clients only look up the skin of a player entity when it is spawned, so
+/// player entities are respawned for viewers when their properties change.
+fn respawn_changed_skins(
+    players: Query<(Entity, &EntityKind, Ref<Properties>), Changed<Properties>>,
+    mut events: EventWriter<ResendEntityEvent>,
+) {
+    for (player, kind, props) in &players {
+        if !props.is_added() && *kind == EntityKind::PLAYER {
+            events.send(ResendEntityEvent { entity: player });
+        }
+    }
+}
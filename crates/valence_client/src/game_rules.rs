@@ -0,0 +1,106 @@
+//! Game rules of instances.
+//!
+//! [`GameRules`] may be attached to instances. Clients in an instance with
+//! game rules have their [`ReducedDebugInfo`] and [`HasRespawnScreen`] set
+//! from the rules when they join or enter the instance, and whenever the rules
+//! change. Changes to those components are sent to clients immediately.
+//!
+//! Rules that only concern the server, like [`GameRules::keep_inventory`], are
+//! read by the plugins implementing them.
+
+use valence_entity::packet::EntityStatusS2c;
+
+use super::*;
+use crate::packet::{GameEventKind, GameStateChangeS2c};
+
+pub(super) fn build(app: &mut App) {
+    app.add_systems(
+        (
+            apply_game_rules.before(initial_join),
+            update_reduced_debug_info
+                .after(apply_game_rules)
+                .after(respawn),
+            update_respawn_screen.after(apply_game_rules).after(respawn),
+        )
+            .in_set(UpdateClientsSet),
+    );
+}
+
+/// The game rules of an instance. Mutating the rules at runtime updates the
+/// clients in the instance.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct GameRules {
+    /// If the debug screen of clients only shows reduced information, like
+    /// the `reducedDebugInfo` rule.
+    pub reduced_debug_info: bool,
+    /// If clients respawn immediately when they die instead of showing the
+    /// death screen, like the `doImmediateRespawn` rule.
+    pub immediate_respawn: bool,
+    /// If the time of day of the instance advances, like the `doDaylightCycle`
+    /// rule. Setting this to `false` has the same effect as
+    /// [`FreezeTime`](crate::time::FreezeTime).
+    pub daylight_cycle: bool,
+    /// If players keep their inventory and experience when they die, like the
+    /// `keepInventory` rule.
+    pub keep_inventory: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            reduced_debug_info: false,
+            immediate_respawn: false,
+            daylight_cycle: true,
+            keep_inventory: false,
+        }
+    }
+}
+
+/// Copies the game rules of instances to the clients in them.
+fn apply_game_rules(
+    mut clients: Query<(Ref<Location>, &mut ReducedDebugInfo, &mut HasRespawnScreen)>,
+    rules: Query<Ref<GameRules>, With<Instance>>,
+) {
+    for (loc, mut reduced_debug_info, mut has_respawn_screen) in &mut clients {
+        let Ok(rules) = rules.get(loc.0) else {
+            continue
+        };
+
+        if loc.is_changed() || rules.is_changed() {
+            reduced_debug_info.set_if_neq(ReducedDebugInfo(rules.reduced_debug_info));
+            has_respawn_screen.set_if_neq(HasRespawnScreen(!rules.immediate_respawn));
+        }
+    }
+}
+
+fn update_reduced_debug_info(
+    mut clients: Query<(&mut Client, &ReducedDebugInfo), Changed<ReducedDebugInfo>>,
+) {
+    for (mut client, reduced_debug_info) in &mut clients {
+        if client.is_added() {
+            // The game join packet includes the reduced debug info.
+            continue;
+        }
+
+        client.write_packet(&EntityStatusS2c {
+            entity_id: 0,
+            entity_status: if reduced_debug_info.0 { 22 } else { 23 },
+        });
+    }
+}
+
+fn update_respawn_screen(
+    mut clients: Query<(&mut Client, &HasRespawnScreen), Changed<HasRespawnScreen>>,
+) {
+    for (mut client, has_respawn_screen) in &mut clients {
+        if client.is_added() {
+            // The game join packet includes whether the respawn screen is enabled.
+            continue;
+        }
+
+        client.write_packet(&GameStateChangeS2c {
+            kind: GameEventKind::EnableRespawnScreen,
+            value: if has_respawn_screen.0 { 0.0 } else { 1.0 },
+        });
+    }
+}
@@ -1,11 +1,24 @@
+//! Keepalives and latency.
+//!
+//! The server regularly sends a keepalive to every client, which the client
+//! answers with the same ID. The round trip time updates the [`Ping`] of the
+//! client, which is shown in the tab list. Clients that are slow to answer
+//! cause a [`ClientLagging`] event, and clients that don't answer within
+//! [`KeepaliveSettings::timeout`] are disconnected after a [`ClientTimedOut`]
+//! event.
+
+use std::time::Duration;
+
 use valence_core::protocol::{packet_id, Decode, Encode, Packet};
-use valence_core::CoreSettings;
 
 use super::*;
 use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
 
 pub(super) fn build(app: &mut App) {
-    app.add_system(send_keepalive.in_set(UpdateClientsSet))
+    app.init_resource::<KeepaliveSettings>()
+        .add_event::<ClientLagging>()
+        .add_event::<ClientTimedOut>()
+        .add_system(send_keepalive.in_set(UpdateClientsSet))
         .add_system(
             handle_keepalive_response
                 .in_base_set(EventLoopSet::PreUpdate)
@@ -13,11 +26,57 @@ pub(super) fn build(app: &mut App) {
         );
 }
 
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct KeepaliveSettings {
+    /// The time between sending a keepalive to a client and sending the next
+    /// one. The next keepalive isn't sent until the client answered the last
+    /// one.
+    pub period: Duration,
+    /// How long a client may take to answer a keepalive before a
+    /// [`ClientLagging`] event is sent.
+    pub lag_threshold: Duration,
+    /// How long a client may take to answer a keepalive before it is
+    /// disconnected.
+    pub timeout: Duration,
+}
+
+impl Default for KeepaliveSettings {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(10),
+            lag_threshold: Duration::from_secs(1),
+            timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+/// Sent when a client takes longer than [`KeepaliveSettings::lag_threshold`]
+/// to answer a keepalive. Sent at most once per keepalive, either while the
+/// server is still waiting for the answer or when the late answer arrives.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ClientLagging {
+    pub client: Entity,
+    /// The time since the keepalive was sent.
+    pub delay: Duration,
+}
+
+/// Sent when a client doesn't answer a keepalive within
+/// [`KeepaliveSettings::timeout`]. The client is disconnected in the same
+/// tick.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ClientTimedOut {
+    pub client: Entity,
+    /// The time since the keepalive was sent.
+    pub delay: Duration,
+}
+
 #[derive(Component, Debug)]
 pub struct KeepaliveState {
     got_keepalive: bool,
     last_keepalive_id: u64,
     keepalive_sent_time: Instant,
+    /// If a [`ClientLagging`] event was sent for the current keepalive.
+    reported_lag: bool,
 }
 
 impl KeepaliveState {
@@ -26,32 +85,56 @@ impl KeepaliveState {
             got_keepalive: true,
             last_keepalive_id: 0,
             keepalive_sent_time: Instant::now(),
+            reported_lag: false,
         }
     }
+
+    /// The time since the keepalive the client hasn't answered yet was sent,
+    /// or `None` if the client answered the last keepalive.
+    pub fn pending_time(&self) -> Option<Duration> {
+        (!self.got_keepalive).then(|| self.keepalive_sent_time.elapsed())
+    }
 }
 
 fn send_keepalive(
     mut clients: Query<(Entity, &mut Client, &mut KeepaliveState)>,
-    server: Res<Server>,
-    settings: Res<CoreSettings>,
+    settings: Res<KeepaliveSettings>,
+    mut lagging_events: EventWriter<ClientLagging>,
+    mut timed_out_events: EventWriter<ClientTimedOut>,
     mut commands: Commands,
 ) {
-    if server.current_tick() % (settings.tick_rate.get() * 10) as i64 == 0 {
-        let mut rng = rand::thread_rng();
-        let now = Instant::now();
+    let mut rng = rand::thread_rng();
+    let now = Instant::now();
+
+    for (entity, mut client, mut state) in &mut clients {
+        let delay = now.saturating_duration_since(state.keepalive_sent_time);
 
-        for (entity, mut client, mut state) in &mut clients {
-            if state.got_keepalive {
+        if state.got_keepalive {
+            if delay >= settings.period {
                 let id = rng.gen();
                 client.write_packet(&KeepAliveS2c { id });
 
                 state.got_keepalive = false;
                 state.last_keepalive_id = id;
                 state.keepalive_sent_time = now;
-            } else {
-                warn!("Client {entity:?} timed out (no keepalive response)");
-                commands.entity(entity).remove::<Client>();
+                state.reported_lag = false;
             }
+        } else if delay >= settings.timeout {
+            warn!("Client {entity:?} timed out (no keepalive response)");
+
+            timed_out_events.send(ClientTimedOut {
+                client: entity,
+                delay,
+            });
+
+            commands.entity(entity).remove::<Client>();
+        } else if !state.reported_lag && delay >= settings.lag_threshold {
+            state.reported_lag = true;
+
+            lagging_events.send(ClientLagging {
+                client: entity,
+                delay,
+            });
         }
     }
 }
@@ -59,6 +142,8 @@ fn send_keepalive(
 fn handle_keepalive_response(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(Entity, &mut KeepaliveState, &mut Ping)>,
+    settings: Res<KeepaliveSettings>,
+    mut lagging_events: EventWriter<ClientLagging>,
     mut commands: Commands,
 ) {
     for packet in packets.iter() {
@@ -74,8 +159,29 @@ fn handle_keepalive_response(
                     );
                     commands.entity(client).remove::<Client>();
                 } else {
+                    let delay = packet
+                        .timestamp
+                        .saturating_duration_since(state.keepalive_sent_time);
+
                     state.got_keepalive = true;
-                    ping.0 = state.keepalive_sent_time.elapsed().as_millis() as i32;
+
+                    if !state.reported_lag && delay >= settings.lag_threshold {
+                        state.reported_lag = true;
+
+                        lagging_events.send(ClientLagging { client, delay });
+                    }
+
+                    // Smooth the ping like the vanilla server so a single slow
+                    // round trip doesn't make the tab list jump around.
+                    let millis = delay.as_millis().min(i32::MAX as u128) as i64;
+
+                    let new_ping = if ping.0 < 0 {
+                        millis
+                    } else {
+                        (ping.0 as i64 * 3 + millis) / 4
+                    };
+
+                    ping.set_if_neq(Ping(new_ping as i32));
                 }
             }
         }
@@ -47,15 +47,20 @@ use valence_core::property::Property;
 use valence_core::protocol::byte_angle::ByteAngle;
 use valence_core::protocol::encode::{PacketEncoder, WritePacket};
 use valence_core::protocol::global_pos::GlobalPos;
-use valence_core::protocol::packet::sound::{PlaySoundS2c, Sound, SoundCategory};
+use valence_core::protocol::packet::sound::{
+    PlaySoundFromEntityS2c, PlaySoundS2c, SoundCategory, SoundId, StopSoundS2c,
+};
 use valence_core::protocol::var_int::VarInt;
 use valence_core::protocol::{Encode, Packet};
 use valence_core::scratch::ScratchBuf;
 use valence_core::text::Text;
 use valence_core::uuid::UniqueId;
 use valence_core::Server;
+use valence_entity::attributes::Attributes;
+use valence_entity::effects::ActiveEffects;
+use valence_entity::equipment::Equipment;
 use valence_entity::packet::{
-    EntitiesDestroyS2c, EntitySetHeadYawS2c, EntitySpawnS2c, EntityStatusS2c,
+    EntitiesDestroyS2c, EntityAttributesS2c, EntitySetHeadYawS2c, EntitySpawnS2c, EntityStatusS2c,
     EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, ExperienceOrbSpawnS2c,
 };
 use valence_entity::player::PlayerEntityBundle;
@@ -71,10 +76,14 @@ use valence_registry::codec::RegistryCodec;
 use valence_registry::tags::TagsRegistry;
 use valence_registry::RegistrySet;
 
+pub mod abilities;
 pub mod action;
+pub mod block_layer;
 pub mod command;
 pub mod custom_payload;
 pub mod event_loop;
+pub mod game_profile;
+pub mod game_rules;
 pub mod hand_swing;
 pub mod interact_block;
 pub mod interact_entity;
@@ -86,9 +95,12 @@ pub mod op_level;
 pub mod packet;
 pub mod resource_pack;
 pub mod settings;
+pub mod spectator;
 pub mod status;
 pub mod teleport;
+pub mod time;
 pub mod title;
+pub mod vehicle;
 pub mod weather;
 
 pub struct ClientPlugin;
@@ -117,18 +129,35 @@ impl Plugin for ClientPlugin {
         app.add_systems(
             (
                 initial_join.after(RegistrySet),
+                resend_tags.after(initial_join),
                 update_chunk_load_dist,
                 read_data_in_old_view
                     .after(WriteUpdatePacketsToInstancesSet)
                     .after(update_chunk_load_dist),
-                update_view.after(initial_join).after(read_data_in_old_view),
-                respawn.after(update_view),
+                respawn.after(read_data_in_old_view),
+                update_view
+                    .after(initial_join)
+                    .after(read_data_in_old_view)
+                    .after(respawn),
                 remove_entities.after(update_view),
+                resend_entities.after(update_view),
                 update_spawn_position.after(update_view),
                 update_old_view_dist.after(update_view),
                 update_game_mode,
                 update_tracked_data.after(WriteUpdatePacketsToInstancesSet),
                 init_tracked_data.after(WriteUpdatePacketsToInstancesSet),
+                init_attributes.after(WriteUpdatePacketsToInstancesSet),
+                update_attributes.after(WriteUpdatePacketsToInstancesSet),
+            )
+                .in_set(UpdateClientsSet),
+        )
+        .add_systems(
+            (
+                init_effects
+                    .after(initial_join)
+                    .after(respawn)
+                    .after(WriteUpdatePacketsToInstancesSet),
+                update_effects.after(WriteUpdatePacketsToInstancesSet),
             )
                 .in_set(UpdateClientsSet),
         )
@@ -141,17 +170,25 @@ impl Plugin for ClientPlugin {
             FlushPacketsSet.in_base_set(CoreSet::PostUpdate),
             ClearInstanceChangesSet.after(FlushPacketsSet),
         ))
-        .add_system(flush_packets.in_set(FlushPacketsSet));
+        .add_system(flush_packets.in_set(FlushPacketsSet))
+        .add_event::<ResendEntityEvent>();
 
         event_loop::build(app);
+        abilities::build(app);
+        block_layer::build(app);
+        game_profile::build(app);
+        game_rules::build(app);
         movement::build(app);
         command::build(app);
         keepalive::build(app);
         interact_entity::build(app);
         settings::build(app);
+        spectator::build(app);
         action::build(app);
         teleport::build(app);
         weather::build(app);
+        time::build(app);
+        vehicle::build(app);
         message::build(app);
         custom_payload::build(app);
         hand_swing::build(app);
@@ -160,6 +197,7 @@ impl Plugin for ClientPlugin {
         op_level::build(app);
         resource_pack::build(app);
         status::build(app);
+        title::build(app);
     }
 }
 
@@ -175,7 +213,9 @@ pub struct ClientBundle {
     pub ip: Ip,
     pub properties: Properties,
     pub compass_pos: CompassPos,
+    pub compass_angle: CompassAngle,
     pub game_mode: GameMode,
+    pub abilities: abilities::PlayerAbilities,
     pub op_level: op_level::OpLevel,
     pub action_sequence: action::ActionSequence,
     pub view_distance: ViewDistance,
@@ -191,6 +231,10 @@ pub struct ClientBundle {
     pub is_debug: IsDebug,
     pub is_flat: IsFlat,
     pub teleport_state: teleport::TeleportState,
+    pub action_bar_queue: title::ActionBarQueue,
+    pub channels: custom_payload::ClientChannels,
+    pub camera: spectator::Camera,
+    pub hidden_entities: spectator::HiddenEntities,
     pub player: PlayerEntityBundle,
 }
 
@@ -208,7 +252,9 @@ impl ClientBundle {
             ip: Ip(args.ip),
             properties: Properties(args.properties),
             compass_pos: CompassPos::default(),
+            compass_angle: CompassAngle::default(),
             game_mode: GameMode::default(),
+            abilities: abilities::PlayerAbilities::default(),
             op_level: op_level::OpLevel::default(),
             action_sequence: action::ActionSequence::default(),
             view_distance: ViewDistance::default(),
@@ -217,6 +263,10 @@ impl ClientBundle {
             keepalive_state: keepalive::KeepaliveState::new(),
             ping: Ping::default(),
             teleport_state: teleport::TeleportState::new(),
+            action_bar_queue: title::ActionBarQueue::new(),
+            channels: custom_payload::ClientChannels::default(),
+            camera: spectator::Camera::default(),
+            hidden_entities: spectator::HiddenEntities::default(),
             is_hardcore: IsHardcore::default(),
             is_flat: IsFlat::default(),
             has_respawn_screen: HasRespawnScreen::default(),
@@ -350,24 +400,24 @@ impl Client {
     /// Puts a particle effect at the given position, only for this client.
     ///
     /// If you want to show a particle effect to all players, use
-    /// [`Instance::play_particle`]
+    /// [`Instance::spawn_particle`]. See it for the meaning of the arguments.
     ///
-    /// [`Instance::play_particle`]: Instance::play_particle
-    pub fn play_particle(
+    /// [`Instance::spawn_particle`]: Instance::spawn_particle
+    pub fn spawn_particle(
         &mut self,
         particle: &Particle,
-        long_distance: bool,
         position: impl Into<DVec3>,
         offset: impl Into<Vec3>,
-        max_speed: f32,
         count: i32,
+        speed: f32,
+        long_distance: bool,
     ) {
         self.write_packet(&ParticleS2c {
             particle: Cow::Borrowed(particle),
             long_distance,
             position: position.into(),
             offset: offset.into(),
-            max_speed,
+            max_speed: speed,
             count,
         })
     }
@@ -375,26 +425,60 @@ impl Client {
     /// Plays a sound effect at the given position, only for this client.
     ///
     /// If you want to play a sound effect to all players, use
-    /// [`Instance::play_sound`]
+    /// [`Instance::play_sound`]. See it for the meaning of the arguments.
     ///
     /// [`Instance::play_sound`]: Instance::play_sound
-    pub fn play_sound(
+    pub fn play_sound<'a>(
         &mut self,
-        sound: Sound,
-        category: SoundCategory,
         position: impl Into<DVec3>,
+        sound: impl Into<SoundId<'a>>,
+        category: SoundCategory,
         volume: f32,
         pitch: f32,
+        seed: i64,
     ) {
         let position = position.into();
 
         self.write_packet(&PlaySoundS2c {
-            id: sound.to_id(),
+            id: sound.into(),
             category,
             position: (position * 8.0).as_ivec3(),
             volume,
             pitch,
-            seed: rand::random(),
+            seed,
+        });
+    }
+
+    /// Plays a sound effect that follows an entity, only for this client. An
+    /// `entity_id` of `0` is the client's own entity.
+    ///
+    /// See [`Instance::play_sound_from_entity`] for the meaning of the
+    /// arguments.
+    pub fn play_sound_from_entity<'a>(
+        &mut self,
+        entity_id: i32,
+        sound: impl Into<SoundId<'a>>,
+        category: SoundCategory,
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) {
+        self.write_packet(&PlaySoundFromEntityS2c {
+            id: sound.into(),
+            category,
+            entity_id: VarInt(entity_id),
+            volume,
+            pitch,
+            seed,
+        });
+    }
+
+    /// Stops sound effects for this client. See [`Instance::stop_sound`] for
+    /// the meaning of the arguments.
+    pub fn stop_sound(&mut self, category: Option<SoundCategory>, sound: Option<Ident<&str>>) {
+        self.write_packet(&StopSoundS2c {
+            source: category,
+            sound: sound.map(Into::into),
         });
     }
 
@@ -536,6 +620,11 @@ pub struct Ip(pub IpAddr);
 #[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
 pub struct CompassPos(pub BlockPos);
 
+/// The angle sent along with the [`CompassPos`], in degrees. Clients face this
+/// direction when they spawn at the world spawn.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct CompassAngle(pub f32);
+
 #[derive(Component, Clone, PartialEq, Eq, Debug)]
 pub struct ViewDistance(u8);
 
@@ -608,6 +697,9 @@ impl OldViewItem<'_> {
 pub struct DeathLocation(pub Option<(Ident<String>, BlockPos)>);
 
 /// Delay measured in milliseconds. Negative values indicate absence.
+///
+/// The ping of clients is updated from keepalive round trips. See
+/// [`keepalive`].
 #[derive(Component, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct Ping(pub i32);
 
@@ -740,6 +832,19 @@ fn initial_join(
     }
 }
 
+/// Sends the tags to clients that have already joined when the
+/// [`TagsRegistry`] is modified.
+fn resend_tags(tags: Res<TagsRegistry>, mut clients: Query<&mut Client>) {
+    if tags.is_changed() && !tags.is_added() {
+        for mut client in &mut clients {
+            // Newly joined clients were sent the tags in `initial_join`.
+            if !client.is_added() {
+                client.enc.append_bytes(tags.sync_tags_packet());
+            }
+        }
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn respawn(
     mut clients: Query<
@@ -811,6 +916,7 @@ fn update_chunk_load_dist(
 
 #[derive(WorldQuery)]
 struct EntityInitQuery {
+    entity: Entity,
     entity_id: &'static EntityId,
     uuid: &'static UniqueId,
     kind: &'static EntityKind,
@@ -820,12 +926,21 @@ struct EntityInitQuery {
     object_data: &'static ObjectData,
     velocity: &'static Velocity,
     tracked_data: &'static TrackedData,
+    attributes: Option<&'static Attributes>,
+    effects: Option<&'static ActiveEffects>,
+    equipment: Option<&'static Equipment>,
+    vehicle: Option<&'static vehicle::Vehicle>,
 }
 
 impl EntityInitQueryItem<'_> {
     /// Writes the appropriate packets to initialize an entity. This will spawn
-    /// the entity and initialize tracked data.
-    fn write_init_packets(&self, pos: DVec3, mut writer: impl WritePacket) {
+    /// the entity, initialize tracked data, and mount passengers.
+    fn write_init_packets(
+        &self,
+        pos: DVec3,
+        passengers: &vehicle::PassengerLookup,
+        mut writer: impl WritePacket,
+    ) {
         match *self.kind {
             EntityKind::MARKER => {}
             EntityKind::EXPERIENCE_ORB => {
@@ -869,6 +984,33 @@ impl EntityInitQueryItem<'_> {
                 metadata: init_data.into(),
             });
         }
+
+        if let Some(attributes) = self.attributes {
+            writer.write_packet(&EntityAttributesS2c {
+                entity_id: self.entity_id.get().into(),
+                properties: attributes.init_properties(),
+            });
+        }
+
+        if let Some(effects) = self.effects {
+            effects.write_init_packets(self.entity_id.get().into(), &mut writer);
+        }
+
+        if let Some(equipment) = self.equipment {
+            equipment.write_init_packets(self.entity_id.get().into(), &mut writer);
+        }
+
+        // Passengers that haven't been spawned yet are ignored by the client, so
+        // the packet is sent for both the vehicle and its passengers.
+        if let Some(pkt) = passengers.passengers_packet(self.entity) {
+            writer.write_packet(&pkt);
+        }
+
+        if let Some(vehicle) = self.vehicle {
+            if let Some(pkt) = passengers.passengers_packet(vehicle.get()) {
+                writer.write_packet(&pkt);
+            }
+        }
     }
 }
 
@@ -883,13 +1025,26 @@ fn read_data_in_old_view(
         &OldPosition,
         &OldViewDistance,
         Option<&PacketByteRange>,
+        &spectator::HiddenEntities,
     )>,
     instances: Query<&Instance>,
     entities: Query<(EntityInitQuery, &OldPosition)>,
     entity_ids: Query<&EntityId>,
+    byte_ranges: Query<&PacketByteRange>,
+    passengers: vehicle::PassengerLookup,
 ) {
     clients.par_iter_mut().for_each_mut(
-        |(mut client, mut remove_buf, loc, old_loc, pos, old_pos, old_view_dist, byte_range)| {
+        |(
+            mut client,
+            mut remove_buf,
+            loc,
+            old_loc,
+            pos,
+            old_pos,
+            old_view_dist,
+            byte_range,
+            hidden,
+        )| {
             let Ok(instance) = instances.get(old_loc.get()) else {
                 return;
             };
@@ -917,7 +1072,9 @@ fn read_data_in_old_view(
 
                     // Send entity spawn packets for entities entering the client's view.
                     for &(id, src_pos) in &cell.incoming {
-                        if src_pos.map_or(true, |p| !view.contains(p)) {
+                        if src_pos.map_or(true, |p| !view.contains(p))
+                            && !hidden.is_hidden_for_client(id)
+                        {
                             // The incoming entity originated from outside the view distance, so it
                             // must be spawned.
                             if let Ok((entity, old_pos)) = entities.get(id) {
@@ -925,14 +1082,20 @@ fn read_data_in_old_view(
                                 // the current position. This is because the client could also
                                 // receive update packets for this entity this tick, which may
                                 // include a relative entity movement.
-                                entity.write_init_packets(old_pos.get(), &mut client.enc);
+                                entity.write_init_packets(
+                                    old_pos.get(),
+                                    &passengers,
+                                    &mut client.enc,
+                                );
                             }
                         }
                     }
 
                     // Send entity despawn packets for entities exiting the client's view.
                     for &(id, dest_pos) in &cell.outgoing {
-                        if dest_pos.map_or(true, |p| !view.contains(p)) {
+                        if dest_pos.map_or(true, |p| !view.contains(p))
+                            && !hidden.is_hidden_for_client(id)
+                        {
                             // The outgoing entity moved outside the view distance, so it must be
                             // despawned.
                             if let Ok(entity_id) = entity_ids.get(id) {
@@ -944,15 +1107,41 @@ fn read_data_in_old_view(
                     // Send all data in the chunk's packet buffer to this client. This will update
                     // entities in the cell, spawn or update the chunk in the cell, or send any
                     // other packet data that was added here by users.
-                    match byte_range {
+                    let own_range = match byte_range {
                         Some(byte_range) if pos == new_chunk_pos && loc == old_loc => {
-                            // Skip range of bytes for the client's own entity.
-                            client.write_packet_bytes(&cell.packet_buf[..byte_range.0.start]);
-                            client.write_packet_bytes(&cell.packet_buf[byte_range.0.end..]);
+                            Some(byte_range.0.clone())
                         }
-                        _ => {
-                            client.write_packet_bytes(&cell.packet_buf);
+                        _ => None,
+                    };
+
+                    if hidden.any_hidden_for_client() {
+                        // Skip the ranges of bytes for the client's own entity and the entities
+                        // hidden from the client.
+                        let mut skipped: Vec<_> = hidden
+                            .iter_hidden_for_client()
+                            .filter(|id| cell.entities.contains(id))
+                            .filter_map(|id| byte_ranges.get(id).ok())
+                            .map(|range| range.0.clone())
+                            .chain(own_range)
+                            .collect();
+
+                        skipped.sort_unstable_by_key(|range| range.start);
+                        skipped.dedup();
+
+                        let mut start = 0;
+
+                        for range in skipped {
+                            client.write_packet_bytes(&cell.packet_buf[start..range.start]);
+                            start = range.end;
                         }
+
+                        client.write_packet_bytes(&cell.packet_buf[start..]);
+                    } else if let Some(own_range) = own_range {
+                        // Skip range of bytes for the client's own entity.
+                        client.write_packet_bytes(&cell.packet_buf[..own_range.start]);
+                        client.write_packet_bytes(&cell.packet_buf[own_range.end..]);
+                    } else {
+                        client.write_packet_bytes(&cell.packet_buf);
                     }
                 }
             });
@@ -979,12 +1168,14 @@ fn update_view(
             &OldPosition,
             &ViewDistance,
             &OldViewDistance,
+            &spectator::HiddenEntities,
         ),
         Or<(Changed<Location>, Changed<Position>, Changed<ViewDistance>)>,
     >,
     instances: Query<&Instance>,
     entities: Query<(EntityInitQuery, &Position)>,
     entity_ids: Query<&EntityId>,
+    passengers: vehicle::PassengerLookup,
 ) {
     clients.par_iter_mut().for_each_mut(
         |(
@@ -998,14 +1189,15 @@ fn update_view(
             old_pos,
             view_dist,
             old_view_dist,
+            hidden,
         )| {
             // TODO: cache chunk pos?
             let view = ChunkView::new(ChunkPos::from_dvec3(pos.0), view_dist.0);
             let old_view = ChunkView::new(ChunkPos::from_dvec3(old_pos.get()), old_view_dist.0);
 
             // Make sure the center chunk is set before loading chunks! Otherwise the client
-            // may ignore the chunk.
-            if old_view.pos != view.pos {
+            // may ignore the chunk. Respawned clients also need it again.
+            if old_view.pos != view.pos || loc.0 != old_loc.get() {
                 client.write_packet(&ChunkRenderDistanceCenterS2c {
                     chunk_x: VarInt(view.pos.x),
                     chunk_z: VarInt(view.pos.z),
@@ -1028,8 +1220,8 @@ fn update_view(
 
                             // Unload all the entities in the cell.
                             for &id in &cell.entities {
-                                // Skip client's own entity.
-                                if id != entity {
+                                // Skip client's own entity and hidden entities.
+                                if id != entity && !hidden.is_hidden_for_client(id) {
                                     if let Ok(entity_id) = entity_ids.get(id) {
                                         remove_buf.push(entity_id.get());
                                     }
@@ -1057,10 +1249,14 @@ fn update_view(
 
                             // Load all the entities in this cell.
                             for &id in &cell.entities {
-                                // Skip client's own entity.
-                                if id != entity {
+                                // Skip client's own entity and hidden entities.
+                                if id != entity && !hidden.is_hidden_for_client(id) {
                                     if let Ok((entity, pos)) = entities.get(id) {
-                                        entity.write_init_packets(pos.get(), &mut client.enc);
+                                        entity.write_init_packets(
+                                            pos.get(),
+                                            &passengers,
+                                            &mut client.enc,
+                                        );
                                     }
                                 }
                             }
@@ -1085,6 +1281,10 @@ fn update_view(
 
                             // Unload all the entities in the cell.
                             for &id in &cell.entities {
+                                if hidden.is_hidden_for_client(id) {
+                                    continue;
+                                }
+
                                 if let Ok(entity_id) = entity_ids.get(id) {
                                     remove_buf.push(entity_id.get());
                                 }
@@ -1108,8 +1308,16 @@ fn update_view(
 
                             // Load all the entities in this cell.
                             for &id in &cell.entities {
+                                if hidden.is_hidden_for_client(id) {
+                                    continue;
+                                }
+
                                 if let Ok((entity, pos)) = entities.get(id) {
-                                    entity.write_init_packets(pos.get(), &mut client.enc);
+                                    entity.write_init_packets(
+                                        pos.get(),
+                                        &passengers,
+                                        &mut client.enc,
+                                    );
                                 }
                             }
                         }
@@ -1120,6 +1328,52 @@ fn update_view(
     );
 }
 
+/// Despawns an entity and spawns it again for every client that can see it.
+/// This resets what clients know about the entity, such as the death animation
+/// of a player that respawned or the skin of a player entity.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ResendEntityEvent {
+    pub entity: Entity,
+}
+
+fn resend_entities(
+    mut events: EventReader<ResendEntityEvent>,
+    entities: Query<(EntityInitQuery, &Position, &Location)>,
+    mut clients: Query<(
+        Entity,
+        &mut Client,
+        View,
+        &Location,
+        &spectator::HiddenEntities,
+    )>,
+    passengers: vehicle::PassengerLookup,
+) {
+    for event in events.iter() {
+        let Ok((init, pos, loc)) = entities.get(event.entity) else {
+            continue
+        };
+
+        let chunk_pos = pos.chunk_pos();
+
+        for (client_entity, mut client, view, client_loc, hidden) in &mut clients {
+            // The client's own entity is not spawned for itself.
+            if client_entity == event.entity
+                || client_loc.0 != loc.0
+                || !view.get().contains(chunk_pos)
+                || hidden.is_hidden_for_client(event.entity)
+            {
+                continue;
+            }
+
+            client.write_packet(&EntitiesDestroyS2c {
+                entity_ids: Cow::Borrowed(&[VarInt(init.entity_id.get())]),
+            });
+
+            init.write_init_packets(pos.0, &passengers, &mut client.enc);
+        }
+    }
+}
+
 /// Removes all the entities that are queued to be removed for each client.
 fn remove_entities(
     mut clients: Query<(&mut Client, &mut EntityRemoveBuf), Changed<EntityRemoveBuf>>,
@@ -1159,13 +1413,23 @@ fn update_old_view_dist(
 
 /// Sets the client's compass position.
 ///
-/// This also closes the "downloading terrain" screen when first joining, so
-/// it should happen after the initial chunks are written.
-fn update_spawn_position(mut clients: Query<(&mut Client, &CompassPos), Changed<CompassPos>>) {
-    for (mut client, compass_pos) in &mut clients {
+/// This also closes the "downloading terrain" screen when joining or
+/// respawning, so it should happen after the initial chunks are written.
+#[allow(clippy::type_complexity)]
+fn update_spawn_position(
+    mut clients: Query<
+        (&mut Client, &CompassPos, &CompassAngle),
+        Or<(
+            Changed<CompassPos>,
+            Changed<CompassAngle>,
+            Changed<Location>,
+        )>,
+    >,
+) {
+    for (mut client, compass_pos, compass_angle) in &mut clients {
         client.write_packet(&PlayerSpawnPositionS2c {
             position: compass_pos.0,
-            angle: 0.0, // TODO: does this do anything?
+            angle: compass_angle.0,
         });
     }
 }
@@ -1193,6 +1457,51 @@ fn init_tracked_data(mut clients: Query<(&mut Client, &TrackedData), Added<Track
     }
 }
 
+fn init_attributes(mut clients: Query<(&mut Client, &Attributes), Added<Attributes>>) {
+    for (mut client, attributes) in &mut clients {
+        client.write_packet(&EntityAttributesS2c {
+            entity_id: VarInt(0),
+            properties: attributes.init_properties(),
+        });
+    }
+}
+
+fn update_attributes(mut clients: Query<(&mut Client, Ref<Attributes>)>) {
+    for (mut client, attributes) in &mut clients {
+        if attributes.is_changed() && !attributes.is_added() {
+            let properties = attributes.update_properties();
+
+            if !properties.is_empty() {
+                client.write_packet(&EntityAttributesS2c {
+                    entity_id: VarInt(0),
+                    properties,
+                });
+            }
+        }
+    }
+}
+
+/// Sends the client its active effects when it joins or respawns.
+#[allow(clippy::type_complexity)]
+fn init_effects(
+    mut clients: Query<
+        (&mut Client, &ActiveEffects),
+        Or<(Added<ActiveEffects>, Changed<Location>)>,
+    >,
+) {
+    for (mut client, effects) in &mut clients {
+        effects.write_init_packets(VarInt(0), &mut client.enc);
+    }
+}
+
+fn update_effects(mut clients: Query<(&mut Client, Ref<ActiveEffects>)>) {
+    for (mut client, effects) in &mut clients {
+        if !effects.is_added() && effects.is_modified() {
+            effects.write_update_packets(VarInt(0), &mut client.enc);
+        }
+    }
+}
+
 fn update_tracked_data(mut clients: Query<(&mut Client, &TrackedData)>) {
     for (mut client, tracked_data) in &mut clients {
         if let Some(update_data) = tracked_data.update_data() {
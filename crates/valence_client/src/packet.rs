@@ -419,6 +419,7 @@ pub struct ServerMetadataS2c<'a> {
 #[packet(id = packet_id::SIGN_EDITOR_OPEN_S2C)]
 pub struct SignEditorOpenS2c {
     pub location: BlockPos,
+    pub is_front_text: bool,
 }
 
 #[derive(Clone, Debug, Encode, Decode, Packet)]
@@ -0,0 +1,336 @@
+//! Spectating entities and hiding entities from clients.
+//!
+//! The [`Camera`] of a client is the entity whose point of view the client
+//! sees. Locking it to another entity makes the client follow that entity
+//! around until the client presses the sneak key, or the entity is despawned
+//! or leaves the client's instance. Spectators lock their camera to an entity
+//! by attacking it, like in vanilla.
+//!
+//! Spectators that pick a player in the spectator menu are teleported to that
+//! player, and a [`SpectatorTeleportEvent`] is sent.
+//!
+//! Entities in the [`HiddenEntities`] of a client aren't spawned for that
+//! client, no packets about them are sent to it, and their player list entries
+//! are unlisted in its tab list. This can be used to vanish staff members from
+//! selected players.
+
+use std::collections::BTreeSet;
+
+use valence_entity::packet::SetCameraEntityS2c;
+use valence_entity::InitEntitiesSet;
+
+use super::*;
+use crate::command::{SneakEvent, SneakState};
+use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
+use crate::interact_entity::{EntityInteraction, InteractEntityEvent};
+use crate::packet::SpectatorTeleportC2s;
+use crate::teleport::TeleportState;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<SpectatorTeleportEvent>()
+        .add_system(
+            handle_spectator_teleport
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::PreUpdate),
+        )
+        .add_system(teleport_spectators)
+        .add_system(
+            follow_cameras
+                .in_base_set(CoreSet::PostUpdate)
+                .before(InitEntitiesSet),
+        )
+        .add_systems(
+            (
+                update_cameras.after(update_view),
+                update_hidden_entities
+                    .after(update_view)
+                    .before(remove_entities),
+            )
+                .in_set(UpdateClientsSet),
+        )
+        .add_system(remove_despawned_hidden_entities.in_base_set(CoreSet::PostUpdate));
+}
+
+/// The entity whose point of view a client sees. `None` is the client's own
+/// point of view.
+///
+/// The entity must be in view of the client. While the camera is locked to
+/// an entity, the client is moved along with it.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Camera(pub Option<Entity>);
+
+impl Camera {
+    /// Locks the camera to `entity`.
+    pub fn set(&mut self, entity: Entity) {
+        self.0 = Some(entity);
+    }
+
+    /// Goes back to the client's own point of view.
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn get(&self) -> Option<Entity> {
+        self.0
+    }
+}
+
+/// Sent when a spectator picks a player to teleport to in the spectator menu.
+/// The spectator is teleported to the target after the event is sent, in
+/// [`CoreSet::Update`], if it is still in spectator mode.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SpectatorTeleportEvent {
+    pub client: Entity,
+    /// The entity the client teleported to.
+    pub target: Entity,
+}
+
+/// The entities hidden from a client.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct HiddenEntities {
+    hidden: BTreeSet<Entity>,
+    /// The entities hidden from the client at the end of the previous tick.
+    sent_hidden: BTreeSet<Entity>,
+}
+
+impl HiddenEntities {
+    /// Hides an entity from this client. Returns `false` if the entity was
+    /// already hidden.
+    pub fn hide(&mut self, entity: Entity) -> bool {
+        self.hidden.insert(entity)
+    }
+
+    /// Shows a hidden entity to this client again. Returns `false` if the
+    /// entity wasn't hidden.
+    pub fn show(&mut self, entity: Entity) -> bool {
+        self.hidden.remove(&entity)
+    }
+
+    pub fn is_hidden(&self, entity: Entity) -> bool {
+        self.hidden.contains(&entity)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.hidden.iter().copied()
+    }
+
+    /// If the entity is currently hidden on the client's side. This lags
+    /// behind [`Self::is_hidden`] until the end of the tick.
+    pub(crate) fn is_hidden_for_client(&self, entity: Entity) -> bool {
+        self.sent_hidden.contains(&entity)
+    }
+
+    pub(crate) fn any_hidden_for_client(&self) -> bool {
+        !self.sent_hidden.is_empty()
+    }
+
+    pub(crate) fn iter_hidden_for_client(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.sent_hidden.iter().copied()
+    }
+}
+
+fn handle_spectator_teleport(
+    mut packets: EventReader<PacketEvent>,
+    entities: Query<(Entity, &UniqueId)>,
+    mut events: EventWriter<SpectatorTeleportEvent>,
+) {
+    for packet in packets.iter() {
+        if let Some(pkt) = packet.decode::<SpectatorTeleportC2s>() {
+            if let Some((target, _)) = entities.iter().find(|(_, uuid)| uuid.0 == pkt.target) {
+                events.send(SpectatorTeleportEvent {
+                    client: packet.client,
+                    target,
+                });
+            }
+        }
+    }
+}
+
+fn teleport_spectators(
+    mut events: EventReader<SpectatorTeleportEvent>,
+    game_modes: Query<&GameMode>,
+    mut entities: Query<(&mut Location, &mut Position)>,
+) {
+    for event in events.iter() {
+        if !matches!(game_modes.get(event.client), Ok(GameMode::Spectator)) {
+            continue;
+        }
+
+        let Ok((loc, pos)) = entities.get(event.target) else {
+            continue
+        };
+
+        let (loc, pos) = (*loc, *pos);
+
+        if let Ok((mut client_loc, mut client_pos)) = entities.get_mut(event.client) {
+            client_loc.set_if_neq(loc);
+            client_pos.set_if_neq(pos);
+        }
+    }
+}
+
+/// Locks the camera of spectators to the entities they attack and moves
+/// clients along with their camera.
+#[allow(clippy::type_complexity)]
+fn follow_cameras(
+    mut queries: ParamSet<(
+        Query<
+            (
+                Entity,
+                &mut Camera,
+                &GameMode,
+                &Location,
+                &mut Position,
+                &mut TeleportState,
+            ),
+            With<Client>,
+        >,
+        Query<(&Location, &Position), Without<Despawned>>,
+    )>,
+    mut interact_events: EventReader<InteractEntityEvent>,
+    mut sneak_events: EventReader<SneakEvent>,
+) {
+    let mut clients = queries.p0();
+
+    for event in interact_events.iter() {
+        if event.interact == EntityInteraction::Attack {
+            if let Ok((_, mut camera, game_mode, ..)) = clients.get_mut(event.client) {
+                if *game_mode == GameMode::Spectator {
+                    camera.set(event.entity);
+                }
+            }
+        }
+    }
+
+    for event in sneak_events.iter() {
+        if event.state == SneakState::Start {
+            if let Ok((_, mut camera, ..)) = clients.get_mut(event.client) {
+                if camera.0.is_some() {
+                    camera.reset();
+                }
+            }
+        }
+    }
+
+    let cameras: Vec<_> = clients
+        .iter()
+        .filter_map(|(client, camera, ..)| Some((client, camera.0?)))
+        .collect();
+
+    let targets = queries.p1();
+
+    let cameras: Vec<_> = cameras
+        .into_iter()
+        .map(|(client, target)| {
+            let target_pos = targets.get(target).ok().map(|(loc, pos)| (*loc, *pos));
+            (client, target, target_pos)
+        })
+        .collect();
+
+    let mut clients = queries.p0();
+
+    for (client, target, target_pos) in cameras {
+        let Ok((_, mut camera, _, loc, mut pos, mut teleport_state)) = clients.get_mut(client) else {
+            continue
+        };
+
+        match target_pos {
+            Some((target_loc, target_pos)) if target != client && target_loc == *loc => {
+                // Move the client without teleporting it, since the client is
+                // looking through the camera anyway.
+                if pos.set_if_neq(target_pos) {
+                    teleport_state.synced_pos = target_pos.0;
+                }
+            }
+            _ => {
+                camera.reset();
+
+                // Teleport the client to where its camera was, like vanilla.
+                teleport_state.request_teleport();
+            }
+        }
+    }
+}
+
+fn update_cameras(
+    mut clients: Query<(Entity, &mut Client, Ref<Camera>)>,
+    entity_ids: Query<&EntityId>,
+) {
+    for (entity, mut client, camera) in &mut clients {
+        if camera.is_changed() && !(camera.is_added() && camera.0.is_none()) {
+            // Clients know their own entity by the reserved ID 0.
+            let id = camera
+                .0
+                .filter(|&target| target != entity)
+                .and_then(|target| entity_ids.get(target).ok())
+                .map_or(0, |id| id.get());
+
+            client.write_packet(&SetCameraEntityS2c {
+                entity_id: VarInt(id),
+            });
+        }
+    }
+}
+
+/// Spawns and despawns entities for clients when they are shown or hidden.
+#[allow(clippy::type_complexity)]
+fn update_hidden_entities(
+    mut clients: Query<
+        (
+            Entity,
+            &mut Client,
+            &mut HiddenEntities,
+            &mut EntityRemoveBuf,
+            &Location,
+            View,
+        ),
+        Changed<HiddenEntities>,
+    >,
+    entities: Query<(EntityInitQuery, &Location, &Position)>,
+    passengers: vehicle::PassengerLookup,
+) {
+    for (client_entity, mut client, mut hidden, mut remove_buf, loc, view) in &mut clients {
+        let hidden = hidden.bypass_change_detection();
+        let view = view.get();
+
+        for &entity in hidden.hidden.symmetric_difference(&hidden.sent_hidden) {
+            // The client's own entity is not spawned for itself.
+            if entity == client_entity {
+                continue;
+            }
+
+            let Ok((init, entity_loc, entity_pos)) = entities.get(entity) else {
+                continue
+            };
+
+            if entity_loc != loc || !view.contains(entity_pos.chunk_pos()) {
+                continue;
+            }
+
+            if hidden.hidden.contains(&entity) {
+                remove_buf.push(init.entity_id.get());
+            } else {
+                init.write_init_packets(entity_pos.0, &passengers, &mut client.enc);
+            }
+        }
+
+        hidden.sent_hidden.clone_from(&hidden.hidden);
+    }
+}
+
+fn remove_despawned_hidden_entities(
+    entities: Query<Entity, Added<Despawned>>,
+    mut clients: Query<&mut HiddenEntities>,
+) {
+    for entity in &entities {
+        for mut hidden in &mut clients {
+            if hidden.is_hidden(entity) {
+                let hidden = hidden.bypass_change_detection();
+
+                // The client already removed the entity.
+                hidden.hidden.remove(&entity);
+                hidden.sent_hidden.remove(&entity);
+            }
+        }
+    }
+}
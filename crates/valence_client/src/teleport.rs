@@ -7,12 +7,17 @@ use super::*;
 use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
 
 pub(super) fn build(app: &mut App) {
-    app.add_system(teleport.after(update_view).in_set(UpdateClientsSet))
-        .add_system(
-            handle_teleport_confirmations
-                .in_schedule(EventLoopSchedule)
-                .in_base_set(EventLoopSet::PreUpdate),
-        );
+    app.add_system(
+        teleport
+            .after(update_view)
+            .after(respawn)
+            .in_set(UpdateClientsSet),
+    )
+    .add_system(
+        handle_teleport_confirmations
+            .in_schedule(EventLoopSchedule)
+            .in_base_set(EventLoopSet::PreUpdate),
+    );
 }
 
 #[derive(Component, Debug)]
@@ -23,6 +28,8 @@ pub struct TeleportState {
     /// confirmation. Inbound client position packets should be ignored while
     /// this is nonzero.
     pending_teleports: u32,
+    /// If the position is sent to the client even if it didn't change.
+    teleport_requested: bool,
     pub(super) synced_pos: DVec3,
     pub(super) synced_look: Look,
 }
@@ -32,6 +39,7 @@ impl TeleportState {
         Self {
             teleport_id_counter: 0,
             pending_teleports: 0,
+            teleport_requested: false,
             synced_pos: DVec3::ZERO,
             synced_look: Look {
                 // Client starts facing north.
@@ -48,6 +56,50 @@ impl TeleportState {
     pub fn pending_teleports(&self) -> u32 {
         self.pending_teleports
     }
+
+    /// Sends the position of the client to it at the end of the tick, even if
+    /// the position didn't change.
+    pub fn request_teleport(&mut self) {
+        self.teleport_requested = true;
+    }
+}
+
+/// A query for moving clients, possibly to another instance.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct Teleport {
+    pub location: &'static mut Location,
+    pub position: &'static mut Position,
+    pub look: &'static mut Look,
+    pub state: &'static mut TeleportState,
+}
+
+impl TeleportItem<'_> {
+    /// Teleports the client to `position` in `instance`.
+    ///
+    /// If `instance` is not the client's current instance, the client is
+    /// respawned in it. This sends the dimension type of the new instance,
+    /// resends the chunks and entities in view, despawns the client for
+    /// viewers in the old instance and spawns it for viewers in the new one.
+    /// The client's inventory, abilities, health, effects and weather are
+    /// synced again, since the client resets them on respawn.
+    ///
+    /// The position is always sent to the client, even if it is the same as
+    /// before, and movement packets are ignored until the client confirms the
+    /// teleport.
+    pub fn teleport_to(
+        &mut self,
+        instance: Entity,
+        position: impl Into<DVec3>,
+        yaw: f32,
+        pitch: f32,
+    ) {
+        self.location.set_if_neq(Location(instance));
+        self.position.0 = position.into();
+        self.look.yaw = yaw;
+        self.look.pitch = pitch;
+        self.state.request_teleport();
+    }
 }
 
 /// Syncs the client's position and look with the server.
@@ -57,16 +109,30 @@ impl TeleportState {
 #[allow(clippy::type_complexity)]
 fn teleport(
     mut clients: Query<
-        (&mut Client, &mut TeleportState, &Position, &Look),
-        Or<(Changed<Position>, Changed<Look>)>,
+        (
+            &mut Client,
+            &mut TeleportState,
+            Ref<Location>,
+            Ref<Position>,
+            &Look,
+        ),
+        Or<(
+            Changed<Location>,
+            Changed<Position>,
+            Changed<Look>,
+            Changed<TeleportState>,
+        )>,
     >,
 ) {
-    for (mut client, mut state, pos, look) in &mut clients {
-        let changed_pos = pos.0 != state.synced_pos;
-        let changed_yaw = look.yaw != state.synced_look.yaw;
-        let changed_pitch = look.pitch != state.synced_look.pitch;
+    for (mut client, mut state, loc, pos, look) in &mut clients {
+        // Respawned clients need their position even if it didn't change.
+        let respawned = loc.is_changed() && !loc.is_added();
+        let changed_pos = respawned || state.teleport_requested || pos.0 != state.synced_pos;
+        let changed_yaw = respawned || look.yaw != state.synced_look.yaw;
+        let changed_pitch = respawned || look.pitch != state.synced_look.pitch;
 
         if changed_pos || changed_yaw || changed_pitch {
+            state.teleport_requested = false;
             state.synced_pos = pos.0;
             state.synced_look = *look;
 
@@ -0,0 +1,138 @@
+//! The time of day system.
+//!
+//! This module contains the systems and components needed to handle the time
+//! of day.
+//!
+//! # Components
+//!
+//! The components may be attached to clients or instances. Components on a
+//! client take precedence over the components on the client's instance, which
+//! allows a client to have their own personal time of day.
+//!
+//! - [`TimeOfDay`]: The current time of day. It advances by one every tick
+//!   unless it is frozen. Changes are sent to the client immediately, and the
+//!   time is resynchronized every second.
+//! - [`FreezeTime`]: When set to `true`, the time of day does not advance and
+//!   the client stops moving the sun and moon. Instances with the
+//!   [`GameRules::daylight_cycle`] rule disabled are frozen too.
+//! - [`WorldAge`]: The age of an instance, which is sent along with the time of
+//!   day. Instances without a [`WorldAge`] use the current tick of the server.
+//!
+//! If neither a client nor its instance has a [`TimeOfDay`], no time update
+//! packets are sent.
+
+use valence_instance::packet::WorldTimeUpdateS2c;
+
+use super::*;
+use crate::game_rules::GameRules;
+
+pub(super) fn build(app: &mut App) {
+    app.add_systems(
+        (
+            advance_time_of_day,
+            send_time_of_day.after(UpdateClientsSet),
+        )
+            .chain()
+            .in_base_set(CoreSet::PostUpdate)
+            .before(FlushPacketsSet),
+    );
+}
+
+/// The time of day in ticks. 0 is sunrise, 6000 is noon, 12000 is sunset, and
+/// 18000 is midnight. Values larger than 24000 wrap around to the next day.
+///
+/// Valid values are non-negative.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct TimeOfDay(pub i64);
+
+/// Prevents [`TimeOfDay`] from advancing when set to `true`.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct FreezeTime(pub bool);
+
+/// The age of an instance in ticks. It advances by one every tick, even when
+/// the time of day is frozen. Changes are sent to clients immediately.
+///
+/// Valid values are non-negative.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct WorldAge(pub i64);
+
+/// How often the time of day is resynchronized with clients, in ticks.
+const TIME_SYNC_INTERVAL: i64 = 20;
+
+fn advance_time_of_day(
+    mut times: Query<(&mut TimeOfDay, Option<&FreezeTime>, Option<&GameRules>)>,
+    mut ages: Query<&mut WorldAge>,
+) {
+    for (mut time, freeze, rules) in &mut times {
+        let frozen =
+            matches!(freeze, Some(FreezeTime(true))) || rules.is_some_and(|r| !r.daylight_cycle);
+
+        if !frozen {
+            // Change detection is bypassed so that only changes made by users
+            // trigger an immediate time update.
+            time.bypass_change_detection().0 += 1;
+        }
+    }
+
+    for mut age in &mut ages {
+        age.bypass_change_detection().0 += 1;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn send_time_of_day(
+    server: Res<Server>,
+    mut clients: Query<(
+        &mut Client,
+        Ref<Location>,
+        Option<Ref<TimeOfDay>>,
+        Option<Ref<FreezeTime>>,
+    )>,
+    instances: Query<
+        (
+            Option<Ref<TimeOfDay>>,
+            Option<Ref<FreezeTime>>,
+            Option<Ref<WorldAge>>,
+            Option<Ref<GameRules>>,
+        ),
+        With<Instance>,
+    >,
+) {
+    let resync = server.current_tick() % TIME_SYNC_INTERVAL == 0;
+
+    for (mut client, loc, client_time, client_freeze) in &mut clients {
+        let Ok((instance_time, instance_freeze, age, rules)) = instances.get(loc.0) else {
+            continue
+        };
+
+        let (time, freeze, changed) = match (client_time, instance_time) {
+            (Some(time), _) => {
+                let changed =
+                    time.is_changed() || client_freeze.as_ref().is_some_and(|f| f.is_changed());
+
+                (time.0, client_freeze.is_some_and(|f| f.0), changed)
+            }
+            (None, Some(time)) => {
+                let changed = time.is_changed()
+                    || instance_freeze.as_ref().is_some_and(|f| f.is_changed())
+                    || rules.as_ref().is_some_and(|r| r.is_changed());
+
+                let freeze = instance_freeze.is_some_and(|f| f.0)
+                    || rules.is_some_and(|r| !r.daylight_cycle);
+
+                (time.0, freeze, changed)
+            }
+            (None, None) => continue,
+        };
+
+        let changed = changed || age.as_ref().is_some_and(|a| a.is_changed()) || loc.is_changed();
+
+        if changed || resync || client.is_added() {
+            client.write_packet(&WorldTimeUpdateS2c {
+                world_age: age.map_or(server.current_tick(), |a| a.0),
+                // A negative time of day stops the client from advancing it.
+                time_of_day: if freeze { -time.max(1) } else { time },
+            });
+        }
+    }
+}
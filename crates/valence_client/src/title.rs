@@ -1,34 +1,63 @@
+use std::collections::VecDeque;
+
 use valence_core::protocol::{packet_id, Decode, Encode};
 
 use super::*;
 
+pub(super) fn build(app: &mut App) {
+    app.add_system(update_action_bar_queues.in_set(UpdateClientsSet));
+}
+
 pub trait SetTitle {
     /// Displays a title to a client.
     ///
     /// A title is a large piece of text displayed in the center of the screen
-    /// which may also include a subtitle underneath it. The title can be
-    /// configured to fade in and out using
-    /// [`set_title_times`](Self::set_title_times).
-    fn set_title(&mut self, text: impl Into<Text>);
-
-    fn set_subtitle(&mut self, text: impl Into<Text>);
-
-    fn set_action_bar(&mut self, text: impl Into<Text>);
-
+    /// with a subtitle underneath it. Either one can be empty.
+    ///
     /// - `fade_in`: Ticks to spend fading in.
     /// - `stay`: Ticks to keep the title displayed.
     /// - `fade_out`: Ticks to spend fading out.
+    fn set_title(
+        &mut self,
+        title: impl Into<Text>,
+        subtitle: impl Into<Text>,
+        fade_in: i32,
+        stay: i32,
+        fade_out: i32,
+    );
+
+    /// Changes the subtitle of the title being displayed.
+    fn set_subtitle(&mut self, text: impl Into<Text>);
+
+    /// Displays text above the hotbar for a few seconds. For messages that
+    /// stay longer, see [`ActionBarQueue`].
+    fn action_bar(&mut self, text: impl Into<Text>);
+
+    /// Changes the times of the title being displayed and all titles after it.
+    /// See [`set_title`](Self::set_title) for the meaning of the arguments.
     fn set_title_times(&mut self, fade_in: i32, stay: i32, fade_out: i32);
 
+    /// Hides the title being displayed.
     fn clear_title(&mut self);
 
+    /// Hides the title being displayed and resets the subtitle and times.
     fn reset_title(&mut self);
 }
 
 impl<T: WritePacket> SetTitle for T {
-    fn set_title(&mut self, text: impl Into<Text>) {
+    fn set_title(
+        &mut self,
+        title: impl Into<Text>,
+        subtitle: impl Into<Text>,
+        fade_in: i32,
+        stay: i32,
+        fade_out: i32,
+    ) {
+        self.set_title_times(fade_in, stay, fade_out);
+        // The title packet displays the title, so the subtitle goes first.
+        self.set_subtitle(subtitle);
         self.write_packet(&TitleS2c {
-            title_text: text.into().into(),
+            title_text: title.into().into(),
         });
     }
 
@@ -38,7 +67,7 @@ impl<T: WritePacket> SetTitle for T {
         });
     }
 
-    fn set_action_bar(&mut self, text: impl Into<Text>) {
+    fn action_bar(&mut self, text: impl Into<Text>) {
         self.write_packet(&OverlayMessageS2c {
             action_bar_text: text.into().into(),
         });
@@ -61,6 +90,94 @@ impl<T: WritePacket> SetTitle for T {
     }
 }
 
+/// Action bar messages that are shown to a client one after another, each for
+/// a number of ticks.
+#[derive(Component, Default, Debug)]
+pub struct ActionBarQueue {
+    messages: VecDeque<(Text, u32)>,
+    /// The message being shown and the ticks it has left.
+    current: Option<(Text, u32)>,
+    /// Ticks since the current message was sent.
+    since_sent: u32,
+    /// If the current message was removed before its time ran out.
+    cleared: bool,
+}
+
+/// Action bar messages start fading out after this many ticks, so they are
+/// sent again before that.
+const ACTION_BAR_RESEND_TICKS: u32 = 40;
+
+impl ActionBarQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows a message for `ticks` ticks after the messages before it.
+    pub fn push(&mut self, text: impl Into<Text>, ticks: u32) {
+        self.messages.push_back((text.into(), ticks));
+    }
+
+    /// The message being shown, if any.
+    pub fn current(&self) -> Option<&Text> {
+        self.current.as_ref().map(|(text, _)| text)
+    }
+
+    /// Removes the message being shown and all messages after it.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+
+        if self.current.take().is_some() {
+            self.cleared = true;
+        }
+    }
+
+    /// The number of messages waiting to be shown.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+fn update_action_bar_queues(mut clients: Query<(&mut Client, &mut ActionBarQueue)>) {
+    for (mut client, mut queue) in &mut clients {
+        if queue.current.is_none() && queue.messages.is_empty() && !queue.cleared {
+            continue;
+        }
+
+        let queue = queue.bypass_change_detection();
+        let mut ended = std::mem::take(&mut queue.cleared);
+
+        if let Some((text, ticks_left)) = &mut queue.current {
+            queue.since_sent += 1;
+
+            if *ticks_left <= 1 {
+                queue.current = None;
+                ended = true;
+            } else {
+                *ticks_left -= 1;
+
+                if queue.since_sent >= ACTION_BAR_RESEND_TICKS {
+                    client.action_bar(text.clone());
+                    queue.since_sent = 0;
+                }
+            }
+        }
+
+        if queue.current.is_none() {
+            if let Some((text, ticks)) = queue.messages.pop_front() {
+                client.action_bar(text.clone());
+                queue.current = Some((text, ticks));
+                queue.since_sent = 0;
+            } else if ended {
+                client.action_bar(Text::default());
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Encode, Decode, Packet)]
 #[packet(id = packet_id::TITLE_S2C)]
 pub struct TitleS2c<'a> {
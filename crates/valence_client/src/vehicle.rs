@@ -0,0 +1,340 @@
+//! Entity passengers and vehicles.
+//!
+//! Any entity can carry other entities by adding them to its [`Passengers`]
+//! component. The [`Vehicle`] component is then inserted on each passenger
+//! automatically, and removed again once the passenger is no longer in the
+//! list. An entity can only ride one vehicle at a time, so adding an entity to
+//! a second vehicle removes it from the first.
+//!
+//! The [`Position`] and [`Location`] of passengers are kept in sync with their
+//! vehicle. Clients render passengers on top of the vehicle regardless.
+//!
+//! When a client is the first passenger of a vehicle, it controls the vehicle.
+//! Movement packets from the client are applied to the vehicle and a
+//! [`VehicleMoveEvent`] is sent. Changes to the vehicle's position made by the
+//! server are sent back to the controlling client.
+//!
+//! Clients dismount their vehicle when they press the sneak key. The
+//! [`SteerVehicleEvent`] is sent for every steering input, including
+//! dismounts.
+
+use bevy_ecs::system::SystemParam;
+use valence_entity::packet::EntityPassengersSetS2c;
+use valence_entity::{boat, InitEntitiesSet};
+
+use super::*;
+use crate::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
+use crate::movement::VehicleMoveC2s;
+use crate::packet::{BoatPaddleStateC2s, PlayerInputC2s, VehicleMoveS2c};
+use crate::teleport::TeleportState;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<VehicleMoveEvent>()
+        .add_event::<SteerVehicleEvent>()
+        .add_system(
+            handle_vehicle_packets
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::PreUpdate),
+        )
+        .add_systems(
+            (update_vehicles, sync_passengers)
+                .chain()
+                .in_base_set(CoreSet::PostUpdate)
+                .before(InitEntitiesSet),
+        )
+        .add_system(
+            write_passenger_updates
+                .in_base_set(CoreSet::PostUpdate)
+                .after(InitEntitiesSet)
+                .before(WriteUpdatePacketsToInstancesSet),
+        );
+}
+
+/// The entities riding on this entity. The first passenger controls the
+/// vehicle.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Passengers(pub Vec<Entity>);
+
+/// The entity this entity is riding on. This is managed automatically from the
+/// [`Passengers`] of the vehicle.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Vehicle {
+    entity: Entity,
+    /// The position of the vehicle last seen by this passenger, if it is a
+    /// controlling client.
+    synced_pos: DVec3,
+}
+
+impl Vehicle {
+    /// Returns the vehicle entity.
+    pub fn get(&self) -> Entity {
+        self.entity
+    }
+}
+
+/// Sent when a client moves the vehicle it is controlling.
+#[derive(Clone, Debug)]
+pub struct VehicleMoveEvent {
+    pub client: Entity,
+    pub vehicle: Entity,
+    pub position: DVec3,
+    pub old_position: DVec3,
+    pub look: Look,
+    pub old_look: Look,
+}
+
+/// Sent when a client riding a vehicle presses a movement key.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SteerVehicleEvent {
+    pub client: Entity,
+    /// Positive to the left of the client.
+    pub sideways: f32,
+    /// Positive forward.
+    pub forward: f32,
+    pub jump: bool,
+    /// If the client pressed the sneak key to dismount.
+    pub unmount: bool,
+}
+
+#[allow(clippy::type_complexity)]
+fn handle_vehicle_packets(
+    mut packets: EventReader<PacketEvent>,
+    mut riders: Query<&mut Vehicle>,
+    mut vehicles: Query<(
+        &mut Passengers,
+        &mut Position,
+        &mut Look,
+        Option<&mut boat::LeftPaddleMoving>,
+        Option<&mut boat::RightPaddleMoving>,
+    )>,
+    mut move_events: EventWriter<VehicleMoveEvent>,
+    mut steer_events: EventWriter<SteerVehicleEvent>,
+) {
+    for packet in packets.iter() {
+        let Ok(mut vehicle) = riders.get_mut(packet.client) else {
+            continue
+        };
+
+        let Ok((mut passengers, mut pos, mut look, left_paddle, right_paddle)) =
+            vehicles.get_mut(vehicle.entity) else {
+            continue
+        };
+
+        let controlling = passengers.0.first() == Some(&packet.client);
+
+        if let Some(pkt) = packet.decode::<PlayerInputC2s>() {
+            steer_events.send(SteerVehicleEvent {
+                client: packet.client,
+                sideways: pkt.sideways,
+                forward: pkt.forward,
+                jump: pkt.flags.jump(),
+                unmount: pkt.flags.unmount(),
+            });
+
+            if pkt.flags.unmount() {
+                passengers.0.retain(|&e| e != packet.client);
+            }
+        } else if let Some(pkt) = packet.decode::<VehicleMoveC2s>() {
+            if !controlling {
+                continue;
+            }
+
+            let new_look = Look::new(pkt.yaw, pkt.pitch);
+
+            move_events.send(VehicleMoveEvent {
+                client: packet.client,
+                vehicle: vehicle.entity,
+                position: pkt.position,
+                old_position: pos.0,
+                look: new_look,
+                old_look: *look,
+            });
+
+            pos.set_if_neq(Position(pkt.position));
+            look.set_if_neq(new_look);
+            vehicle.synced_pos = pkt.position;
+        } else if let Some(pkt) = packet.decode::<BoatPaddleStateC2s>() {
+            if !controlling {
+                continue;
+            }
+
+            if let Some(mut left) = left_paddle {
+                left.set_if_neq(boat::LeftPaddleMoving(pkt.left_paddle_turning));
+            }
+
+            if let Some(mut right) = right_paddle {
+                right.set_if_neq(boat::RightPaddleMoving(pkt.right_paddle_turning));
+            }
+        }
+    }
+}
+
+/// Inserts and removes [`Vehicle`] components to match the [`Passengers`] of
+/// every vehicle.
+fn update_vehicles(
+    mut vehicles: Query<(Entity, &mut Passengers, &Position, Option<&Despawned>)>,
+    riders: Query<(Entity, &Vehicle, Option<&Despawned>)>,
+    entities: Query<(), (With<EntityKind>, Without<Despawned>)>,
+    mut commands: Commands,
+) {
+    let mut mounted = vec![];
+    let mut removals = vec![];
+
+    for (vehicle, mut passengers, pos, despawned) in &mut vehicles {
+        if despawned.is_some() || !passengers.is_changed() {
+            continue;
+        }
+
+        // Remove duplicates, the vehicle itself, and entities that don't exist.
+        let mut i = 0;
+        while i < passengers.0.len() {
+            let passenger = passengers.0[i];
+
+            if passenger == vehicle
+                || !entities.contains(passenger)
+                || passengers.0[..i].contains(&passenger)
+            {
+                passengers.0.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        for &passenger in &passengers.0 {
+            let current = riders.get(passenger).ok().map(|(_, v, _)| v.entity);
+
+            if current != Some(vehicle) {
+                if let Some(current) = current {
+                    // An entity can only ride one vehicle at a time.
+                    removals.push((current, passenger));
+                }
+
+                commands.entity(passenger).insert(Vehicle {
+                    entity: vehicle,
+                    synced_pos: pos.0,
+                });
+
+                mounted.push(passenger);
+            }
+        }
+    }
+
+    for (vehicle, passenger) in removals {
+        if let Ok((_, mut passengers, _, _)) = vehicles.get_mut(vehicle) {
+            passengers.0.retain(|&e| e != passenger);
+        }
+    }
+
+    for (rider, vehicle, despawned) in &riders {
+        if mounted.contains(&rider) {
+            continue;
+        }
+
+        let rides = match vehicles.get_mut(vehicle.entity) {
+            Ok((_, mut passengers, _, None)) => {
+                if despawned.is_some() {
+                    passengers.0.retain(|&e| e != rider);
+                }
+
+                passengers.0.contains(&rider)
+            }
+            _ => false,
+        };
+
+        if !rides && despawned.is_none() {
+            commands.entity(rider).remove::<Vehicle>();
+        }
+    }
+}
+
+/// Moves passengers along with their vehicle. Passengers of passengers lag one
+/// tick behind for every level of nesting.
+#[allow(clippy::type_complexity)]
+fn sync_passengers(
+    mut entities: Query<(
+        &mut Position,
+        &mut Location,
+        &Look,
+        Option<&Passengers>,
+        Option<&mut Vehicle>,
+        Option<&mut Client>,
+        Option<&mut TeleportState>,
+    )>,
+    mut moves: Local<Vec<(Entity, bool, Position, Location, Look)>>,
+) {
+    for (pos, loc, look, passengers, ..) in &entities {
+        if let Some(passengers) = passengers {
+            for (i, &passenger) in passengers.0.iter().enumerate() {
+                moves.push((passenger, i == 0, *pos, *loc, *look));
+            }
+        }
+    }
+
+    for (passenger, controlling, vehicle_pos, vehicle_loc, vehicle_look) in moves.drain(..) {
+        let Ok((mut pos, mut loc, _, _, vehicle, client, teleport_state)) = entities.get_mut(passenger) else {
+            continue
+        };
+
+        pos.set_if_neq(vehicle_pos);
+        loc.set_if_neq(vehicle_loc);
+
+        // Clients are moved with the vehicle on their end, so a teleport isn't
+        // necessary.
+        if let Some(mut state) = teleport_state {
+            state.synced_pos = vehicle_pos.0;
+        }
+
+        // Send changes made by the server to the controlling client.
+        if let (true, Some(mut vehicle), Some(mut client)) = (controlling, vehicle, client) {
+            if vehicle.synced_pos != vehicle_pos.0 {
+                vehicle.synced_pos = vehicle_pos.0;
+
+                client.write_packet(&VehicleMoveS2c {
+                    position: vehicle_pos.0,
+                    yaw: vehicle_look.yaw,
+                    pitch: vehicle_look.pitch,
+                });
+            }
+        }
+    }
+}
+
+fn write_passenger_updates(
+    vehicles: Query<(Entity, &Location, &Position), Changed<Passengers>>,
+    lookup: PassengerLookup,
+    mut instances: Query<&mut Instance>,
+) {
+    for (vehicle, loc, pos) in &vehicles {
+        if let Ok(mut instance) = instances.get_mut(loc.0) {
+            if let Some(pkt) = lookup.passengers_packet(vehicle) {
+                instance.write_packet_at(&pkt, pos.chunk_pos());
+            }
+        }
+    }
+}
+
+/// Used to create the packets that mount entities on their vehicles.
+#[derive(SystemParam)]
+pub(crate) struct PassengerLookup<'w, 's> {
+    entities: Query<'w, 's, (&'static EntityId, Option<&'static Passengers>)>,
+}
+
+impl PassengerLookup<'_, '_> {
+    /// Returns the packet setting the passengers of `vehicle`, if it has a
+    /// [`Passengers`] component.
+    pub(crate) fn passengers_packet(&self, vehicle: Entity) -> Option<EntityPassengersSetS2c> {
+        let Ok((id, Some(passengers))) = self.entities.get(vehicle) else {
+            return None
+        };
+
+        Some(EntityPassengersSetS2c {
+            entity_id: VarInt(id.get()),
+            passengers: passengers
+                .0
+                .iter()
+                .filter_map(|&e| self.entities.get(e).ok())
+                .map(|(id, _)| VarInt(id.get()))
+                .collect(),
+        })
+    }
+}
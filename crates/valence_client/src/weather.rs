@@ -12,8 +12,8 @@
 //! - [`Thunder`]: When attached, thunder level set event is emitted. When
 //!   removed, the thunder level set to zero event is emitted.
 //!
-//! New joined players are handled, so that they are get weather events from
-//! the instance.
+//! New joined players and players changing instances are handled, so that
+//! they are get weather events from the instance.
 
 use super::*;
 use crate::packet::{GameEventKind, GameStateChangeS2c};
@@ -60,6 +60,7 @@ pub(super) fn build(app: &mut App) {
     )
     .add_system(
         handle_weather_for_joined_player
+            .after(UpdateClientsSet)
             .before(UpdateWeatherPerClientSet)
             .in_base_set(CoreSet::PostUpdate),
     );
@@ -77,12 +78,32 @@ pub struct Rain(pub f32);
 #[derive(Component)]
 pub struct Thunder(pub f32);
 
+/// Sends the weather of the instance to clients that joined or changed
+/// instances.
+#[allow(clippy::type_complexity)]
 fn handle_weather_for_joined_player(
-    mut clients: Query<(&mut Client, &Location), Added<Client>>,
+    mut clients: Query<(&mut Client, &Location), Or<(Added<Client>, Changed<Location>)>>,
     weathers: Query<(Option<&Rain>, Option<&Thunder>), With<Instance>>,
 ) {
     for (mut client, loc) in &mut clients {
+        let joined = client.is_added();
+
         if let Ok((rain, thunder)) = weathers.get(loc.0) {
+            if rain.is_none() && !joined {
+                // The client may still have the weather of its previous instance.
+                client.write_packet(&GameStateChangeS2c {
+                    kind: GameEventKind::EndRaining,
+                    value: 0.0,
+                });
+            }
+
+            if thunder.is_none() && !joined {
+                client.write_packet(&GameStateChangeS2c {
+                    kind: GameEventKind::ThunderLevelChange,
+                    value: 0.0,
+                });
+            }
+
             if let Some(level) = rain {
                 client.write_packet(&GameStateChangeS2c {
                     kind: GameEventKind::BeginRaining,
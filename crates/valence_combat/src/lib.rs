@@ -0,0 +1,518 @@
+//! # Combat
+//! Vanilla-style melee combat between living entities.
+//!
+//! Every [`AttackEntityEvent`] against a living entity becomes an attack. The
+//! damage of an attack depends on the attacker's attack damage attribute, the
+//! held weapon and its sharpness, and how far the attack cooldown has
+//! recovered. Falling attackers deal critical hits, and sprinting attackers
+//! deal extra knockback and stop sprinting.
+//!
+//! Attacks are applied in three steps in [`CoreSet::Update`]:
+//!
+//! 1. In [`PrepareAttacksSet`], the attacks made this tick are added to the
+//!    [`PreAttackEvents`] resource.
+//! 2. In [`ModifyAttacksSet`], plugins can change or cancel them.
+//! 3. In [`ApplyAttacksSet`], a [`DamageEvent`] is sent for each attack, the
+//!    target is knocked back, and a [`PostAttackEvent`] is sent.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_combat::PreAttackEvents;
+//! fn double_crits(mut attacks: ResMut<PreAttackEvents>) {
+//!     for attack in attacks.iter_mut() {
+//!         if attack.critical {
+//!             attack.damage *= 2.0;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Entities that were hurt recently are invulnerable for
+//! [`CombatSettings::invulnerability_ticks`]. During that time, only attacks
+//! stronger than the last one deal damage, and only the difference.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::{Vec2, Vec3};
+use tracing::warn;
+use valence_client::{Client, Username};
+use valence_core::item::ItemKind;
+use valence_core::{ident, Server, DEFAULT_TPS};
+use valence_entity::attributes::{Attributes, EntityAttribute};
+use valence_entity::entity::Flags;
+use valence_entity::{
+    EntityAnimation, EntityAnimations, Look, OldPosition, OnGround, Position, Velocity,
+};
+use valence_health::{DamageEvent, DamageTypeRegistry, Health};
+use valence_interaction::AttackEntityEvent;
+use valence_inventory::{HeldItem, Inventory};
+
+pub struct CombatPlugin;
+
+/// When the attacks made this tick are added to [`PreAttackEvents`].
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PrepareAttacksSet;
+
+/// Systems that change or cancel [`PreAttackEvents`] should run in this set.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ModifyAttacksSet;
+
+/// When attacks are applied and [`PostAttackEvent`]s are sent.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ApplyAttacksSet;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatSettings>()
+            .init_resource::<PreAttackEvents>()
+            .add_event::<PostAttackEvent>()
+            .configure_sets((
+                PrepareAttacksSet.in_base_set(CoreSet::Update),
+                ModifyAttacksSet
+                    .after(PrepareAttacksSet)
+                    .in_base_set(CoreSet::Update),
+                ApplyAttacksSet
+                    .after(ModifyAttacksSet)
+                    .in_base_set(CoreSet::Update),
+            ))
+            .add_system(init_combat_state.in_base_set(CoreSet::PreUpdate))
+            .add_system(prepare_attacks.in_set(PrepareAttacksSet))
+            .add_system(apply_attacks.in_set(ApplyAttacksSet));
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct CombatSettings {
+    /// The number of ticks after an entity is hurt during which it only takes
+    /// damage from stronger attacks.
+    pub invulnerability_ticks: i64,
+    /// Whether the damage of an attack depends on the time since the
+    /// attacker's last attack. When `false`, every attack deals full damage
+    /// like in versions before 1.9.
+    pub attack_cooldown: bool,
+}
+
+impl Default for CombatSettings {
+    fn default() -> Self {
+        Self {
+            invulnerability_ticks: 10,
+            attack_cooldown: true,
+        }
+    }
+}
+
+/// The combat timers of a living entity.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct CombatState {
+    last_attack_tick: Option<i64>,
+    last_hurt_tick: Option<i64>,
+    last_hurt_damage: f32,
+}
+
+impl CombatState {
+    /// The tick this entity last attacked another entity.
+    pub fn last_attack_tick(&self) -> Option<i64> {
+        self.last_attack_tick
+    }
+
+    /// The tick this entity was last hurt by an attack.
+    pub fn last_hurt_tick(&self) -> Option<i64> {
+        self.last_hurt_tick
+    }
+
+    /// How far the attack cooldown of this entity has recovered at
+    /// `current_tick`, from 0 to 1.
+    pub fn attack_strength(&self, current_tick: i64, attack_speed: f32) -> f32 {
+        let Some(last) = self.last_attack_tick else {
+            return 1.0
+        };
+
+        let cooldown_ticks = DEFAULT_TPS.get() as f32 / attack_speed;
+
+        (((current_tick - last) as f32 + 0.5) / cooldown_ticks).clamp(0.0, 1.0)
+    }
+}
+
+/// An attack that has not been applied yet.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PreAttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// The damage dealt to the target, including the critical hit bonus.
+    pub damage: f32,
+    /// The knockback every attack deals, pushing the target away from the
+    /// attacker.
+    pub base_knockback: f32,
+    /// Extra knockback in the direction the attacker is looking, such as from
+    /// sprinting or the knockback enchantment.
+    pub bonus_knockback: f32,
+    /// The recovery of the attacker's attack cooldown, from 0 to 1.
+    pub attack_strength: f32,
+    pub critical: bool,
+    pub sprinting: bool,
+    pub cancelled: bool,
+}
+
+impl PreAttackEvent {
+    /// Prevents the attack from being applied.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+}
+
+/// The attacks made this tick. Systems in [`ModifyAttacksSet`] can change them
+/// before they are applied.
+#[derive(Resource, Default, Debug)]
+pub struct PreAttackEvents {
+    events: Vec<PreAttackEvent>,
+}
+
+impl PreAttackEvents {
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &PreAttackEvent> + '_ {
+        self.events.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = &mut PreAttackEvent> + '_ {
+        self.events.iter_mut()
+    }
+
+    /// Adds an attack that was not made by a client, such as by a mob.
+    pub fn send(&mut self, event: PreAttackEvent) {
+        self.events.push(event);
+    }
+}
+
+/// Sent after an attack is applied.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PostAttackEvent {
+    pub attacker: Entity,
+    pub target: Entity,
+    /// The damage dealt to the target. This is less than the damage of the
+    /// attack if the target was still invulnerable from an earlier attack.
+    pub damage: f32,
+    /// The new velocity of the target in m/s, if it was knocked back.
+    pub knockback: Option<Vec3>,
+    pub critical: bool,
+}
+
+/// Returns the attack damage bonus and the attack speed of the weapon `item`,
+/// or `None` if it isn't a weapon.
+pub fn weapon_stats(item: ItemKind) -> Option<(f32, f32)> {
+    Some(match item {
+        ItemKind::WoodenSword | ItemKind::GoldenSword => (3.0, 1.6),
+        ItemKind::StoneSword => (4.0, 1.6),
+        ItemKind::IronSword => (5.0, 1.6),
+        ItemKind::DiamondSword => (6.0, 1.6),
+        ItemKind::NetheriteSword => (7.0, 1.6),
+        ItemKind::WoodenAxe => (6.0, 0.8),
+        ItemKind::GoldenAxe => (6.0, 1.0),
+        ItemKind::StoneAxe => (8.0, 0.8),
+        ItemKind::IronAxe => (8.0, 0.9),
+        ItemKind::DiamondAxe => (8.0, 1.0),
+        ItemKind::NetheriteAxe => (9.0, 1.0),
+        ItemKind::WoodenPickaxe | ItemKind::GoldenPickaxe => (1.0, 1.2),
+        ItemKind::StonePickaxe => (2.0, 1.2),
+        ItemKind::IronPickaxe => (3.0, 1.2),
+        ItemKind::DiamondPickaxe => (4.0, 1.2),
+        ItemKind::NetheritePickaxe => (5.0, 1.2),
+        ItemKind::WoodenShovel | ItemKind::GoldenShovel => (1.5, 1.0),
+        ItemKind::StoneShovel => (2.5, 1.0),
+        ItemKind::IronShovel => (3.5, 1.0),
+        ItemKind::DiamondShovel => (4.5, 1.0),
+        ItemKind::NetheriteShovel => (5.5, 1.0),
+        ItemKind::WoodenHoe | ItemKind::GoldenHoe => (0.0, 1.0),
+        ItemKind::StoneHoe => (0.0, 2.0),
+        ItemKind::IronHoe => (0.0, 3.0),
+        ItemKind::DiamondHoe | ItemKind::NetheriteHoe => (0.0, 4.0),
+        ItemKind::Trident => (8.0, 1.1),
+        _ => return None,
+    })
+}
+
+/// Applies knockback of `strength` away from the direction `(x, z)` to the
+/// velocity `vel` in blocks per tick, like vanilla does.
+fn knockback(vel: Vec3, strength: f32, x: f32, z: f32, on_ground: bool) -> Vec3 {
+    if strength <= 0.0 {
+        return vel;
+    }
+
+    let push = Vec2::new(x, z).normalize_or_zero() * strength;
+
+    Vec3::new(
+        vel.x / 2.0 - push.x,
+        if on_ground {
+            (vel.y / 2.0 + strength).min(0.4)
+        } else {
+            vel.y
+        },
+        vel.z / 2.0 - push.y,
+    )
+}
+
+fn init_combat_state(
+    entities: Query<Entity, (Added<Health>, Without<CombatState>)>,
+    mut commands: Commands,
+) {
+    for entity in &entities {
+        commands.entity(entity).insert(CombatState::default());
+    }
+}
+
+fn prepare_attacks(
+    mut attacks: EventReader<AttackEntityEvent>,
+    mut attackers: Query<(
+        &mut CombatState,
+        &Position,
+        Option<&OldPosition>,
+        Option<&OnGround>,
+        Option<&Flags>,
+        Option<&Attributes>,
+        Option<&Inventory>,
+        Option<&HeldItem>,
+    )>,
+    targets: Query<&Health>,
+    server: Res<Server>,
+    settings: Res<CombatSettings>,
+    mut events: ResMut<PreAttackEvents>,
+) {
+    for attack in attacks.iter() {
+        if !targets.contains(attack.target) {
+            continue;
+        }
+
+        let Ok((mut state, pos, old_pos, on_ground, flags, attributes, inventory, held)) =
+            attackers.get_mut(attack.client) else {
+            continue
+        };
+
+        let weapon = inventory
+            .zip(held)
+            .and_then(|(inv, held)| inv.slot(held.slot()));
+
+        let (weapon_damage, weapon_speed) = weapon
+            .and_then(|stack| weapon_stats(stack.item))
+            .unwrap_or((0.0, EntityAttribute::AttackSpeed.default_value() as f32));
+
+        let attribute =
+            |attr: EntityAttribute| attributes.map_or(attr.default_value(), |a| a.get(attr)) as f32;
+
+        // Weapons replace the base attack speed of 4.
+        let attack_speed = attribute(EntityAttribute::AttackSpeed) + weapon_speed - 4.0;
+
+        let attack_strength = if settings.attack_cooldown {
+            state.attack_strength(server.current_tick(), attack_speed)
+        } else {
+            1.0
+        };
+
+        let mut damage = attribute(EntityAttribute::AttackDamage) + weapon_damage;
+
+        let sharpness = weapon.map_or(0, |w| w.enchantment_level("minecraft:sharpness"));
+
+        let mut sharpness_damage = if sharpness > 0 {
+            sharpness as f32 * 0.5 + 0.5
+        } else {
+            0.0
+        };
+
+        damage *= 0.2 + attack_strength * attack_strength * 0.8;
+        sharpness_damage *= attack_strength;
+
+        let strong = attack_strength > 0.9;
+        let sprinting = flags.is_some_and(|f| f.sprinting());
+
+        let mut bonus_knockback =
+            weapon.map_or(0, |w| w.enchantment_level("minecraft:knockback")) as f32;
+
+        if sprinting && strong {
+            bonus_knockback += 1.0;
+        }
+
+        let falling =
+            !on_ground.is_some_and(|g| g.0) && old_pos.is_some_and(|old| pos.0.y < old.get().y);
+
+        let critical = strong && falling && !sprinting;
+
+        if critical {
+            damage *= 1.5;
+        }
+
+        state.last_attack_tick = Some(server.current_tick());
+
+        events.send(PreAttackEvent {
+            attacker: attack.client,
+            target: attack.target,
+            damage: damage + sharpness_damage,
+            base_knockback: 0.4,
+            bonus_knockback,
+            attack_strength,
+            critical,
+            sprinting,
+            cancelled: false,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_attacks(
+    mut events: ResMut<PreAttackEvents>,
+    mut targets: Query<(
+        &mut CombatState,
+        &Health,
+        &Position,
+        Option<&OnGround>,
+        Option<&Attributes>,
+        Option<&mut Velocity>,
+        Option<&mut Client>,
+        Option<&mut EntityAnimations>,
+    )>,
+    mut attackers: Query<(
+        &Position,
+        Option<&Look>,
+        Option<&Username>,
+        Option<&mut Flags>,
+    )>,
+    server: Res<Server>,
+    settings: Res<CombatSettings>,
+    damage_types: Res<DamageTypeRegistry>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut post_events: EventWriter<PostAttackEvent>,
+) {
+    let tps = DEFAULT_TPS.get() as f32;
+
+    for event in events.events.drain(..) {
+        if event.cancelled || event.damage <= 0.0 {
+            continue;
+        }
+
+        let Ok((mut state, health, pos, on_ground, attributes, velocity, client, animations)) =
+            targets.get_mut(event.target) else {
+            continue
+        };
+
+        let Ok((attacker_pos, attacker_look, attacker_username, attacker_flags)) =
+            attackers.get_mut(event.attacker) else {
+            continue
+        };
+
+        if health.0 <= 0.0 {
+            continue;
+        }
+
+        let now = server.current_tick();
+
+        let invulnerable = state
+            .last_hurt_tick
+            .is_some_and(|tick| now - tick < settings.invulnerability_ticks);
+
+        let damage = if invulnerable {
+            if event.damage <= state.last_hurt_damage {
+                continue;
+            }
+
+            event.damage - state.last_hurt_damage
+        } else {
+            state.last_hurt_tick = Some(now);
+            event.damage
+        };
+
+        state.last_hurt_damage = event.damage;
+
+        let damage_type_name = if attacker_username.is_some() {
+            ident!("player_attack")
+        } else {
+            ident!("mob_attack")
+        };
+
+        let Some(damage_type) = damage_types.index_of(damage_type_name) else {
+            warn!("missing damage type {damage_type_name}");
+            continue
+        };
+
+        damage_events.send(DamageEvent::new(event.target, damage, damage_type).by(event.attacker));
+
+        let mut new_velocity = None;
+
+        // Attacks on invulnerable entities don't knock them back.
+        if !invulnerable {
+            let resistance =
+                attributes.map_or(0.0, |a| a.get(EntityAttribute::KnockbackResistance)) as f32;
+            let on_ground = on_ground.is_some_and(|g| g.0);
+
+            // The velocity of clients isn't known to the server.
+            let mut vel = match (&client, &velocity) {
+                (None, Some(velocity)) => velocity.0 / tps,
+                _ => Vec3::ZERO,
+            };
+
+            let diff = (attacker_pos.0 - pos.0).as_vec3();
+
+            vel = knockback(
+                vel,
+                event.base_knockback * (1.0 - resistance),
+                diff.x,
+                diff.z,
+                on_ground,
+            );
+
+            let yaw = attacker_look.map_or(0.0, |l| l.yaw).to_radians();
+
+            vel = knockback(
+                vel,
+                event.bonus_knockback * 0.5 * (1.0 - resistance),
+                yaw.sin(),
+                -yaw.cos(),
+                on_ground,
+            );
+
+            let vel = vel * tps;
+
+            if let Some(mut client) = client {
+                client.set_velocity(vel);
+            } else if let Some(mut velocity) = velocity {
+                velocity.0 = vel;
+            }
+
+            new_velocity = Some(vel);
+
+            // Like in vanilla, extra knockback stops the attacker from sprinting.
+            if event.bonus_knockback > 0.0 {
+                if let Some(mut flags) = attacker_flags {
+                    flags.set_sprinting(false);
+                }
+            }
+        }
+
+        if event.critical {
+            if let Some(mut animations) = animations {
+                animations.trigger(EntityAnimation::Crit);
+            }
+        }
+
+        post_events.send(PostAttackEvent {
+            attacker: event.attacker,
+            target: event.target,
+            damage,
+            knockback: new_velocity,
+            critical: event.critical,
+        });
+    }
+}
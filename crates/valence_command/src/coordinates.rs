@@ -0,0 +1,221 @@
+//! Positions and rotations relative to the sender of a command, like `~ ~1 ~`
+//! and `^ ^ ^2`.
+
+use glam::DVec3;
+use valence_core::block_pos::BlockPos;
+use valence_core::text::Text;
+use valence_core::translation_key;
+
+use crate::parse::{parse_number, ParseError, StringReader, DOUBLE_ERRORS, INT_ERRORS};
+
+/// A coordinate that is either absolute or relative to the sender with `~`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct WorldCoordinate {
+    pub value: f64,
+    pub relative: bool,
+}
+
+impl WorldCoordinate {
+    /// Returns the coordinate, using `origin` as the sender's coordinate.
+    pub fn resolve(self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+/// A position in a command.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Coordinates {
+    /// `x y z`, where each coordinate can be relative to the position of the
+    /// sender.
+    World([WorldCoordinate; 3]),
+    /// `^left ^up ^forwards`, relative to the position of the sender and the
+    /// direction they are looking in.
+    Local([f64; 3]),
+}
+
+impl Coordinates {
+    /// Returns the position, given the position of the sender and the
+    /// direction they are looking in.
+    pub fn resolve(&self, origin: DVec3, yaw: f32, pitch: f32) -> DVec3 {
+        match self {
+            Coordinates::World([x, y, z]) => DVec3::new(
+                x.resolve(origin.x),
+                y.resolve(origin.y),
+                z.resolve(origin.z),
+            ),
+            Coordinates::Local([left, up, forwards]) => {
+                // The same math as vanilla.
+                let yaw = (yaw as f64 + 90.0).to_radians();
+                let pitch = pitch as f64;
+
+                let forward_dir = DVec3::new(
+                    yaw.cos() * (-pitch).to_radians().cos(),
+                    (-pitch).to_radians().sin(),
+                    yaw.sin() * (-pitch).to_radians().cos(),
+                );
+
+                let up_dir = DVec3::new(
+                    yaw.cos() * (90.0 - pitch).to_radians().cos(),
+                    (90.0 - pitch).to_radians().sin(),
+                    yaw.sin() * (90.0 - pitch).to_radians().cos(),
+                );
+
+                let left_dir = -forward_dir.cross(up_dir);
+
+                origin + forward_dir * *forwards + up_dir * *up + left_dir * *left
+            }
+        }
+    }
+
+    /// Returns the block at the position, given the position of the sender
+    /// and the direction they are looking in.
+    pub fn resolve_block_pos(&self, origin: DVec3, yaw: f32, pitch: f32) -> BlockPos {
+        BlockPos::at(self.resolve(origin, yaw, pitch))
+    }
+}
+
+/// Reads three world or local coordinates. Absolute coordinates must be
+/// integers if `integers` is set. Otherwise, absolute integer `x` and `z`
+/// coordinates are moved to the center of the block like in vanilla.
+pub(crate) fn parse_coordinates(
+    reader: &mut StringReader,
+    integers: bool,
+) -> Result<Coordinates, ParseError> {
+    let start = reader.pos();
+
+    if reader.peek() == Some('^') {
+        let mut coords = [0.0; 3];
+
+        for (i, coord) in coords.iter_mut().enumerate() {
+            if i > 0 {
+                expect_separator(reader, start, translation_key::ARGUMENT_POS3D_INCOMPLETE)?;
+            }
+
+            *coord = parse_local_coordinate(reader)?;
+        }
+
+        return Ok(Coordinates::Local(coords));
+    }
+
+    let x = parse_world_coordinate(reader, integers, !integers)?;
+    expect_separator(reader, start, translation_key::ARGUMENT_POS3D_INCOMPLETE)?;
+    let y = parse_world_coordinate(reader, integers, false)?;
+    expect_separator(reader, start, translation_key::ARGUMENT_POS3D_INCOMPLETE)?;
+    let z = parse_world_coordinate(reader, integers, !integers)?;
+
+    Ok(Coordinates::World([x, y, z]))
+}
+
+/// Reads two world coordinates, with the key of the error for when the second
+/// one is missing. Absolute integer coordinates are moved to the center of the
+/// block if `center` is set.
+pub(crate) fn parse_coordinates_2d(
+    reader: &mut StringReader,
+    integers: bool,
+    center: bool,
+    incomplete: &'static str,
+) -> Result<[WorldCoordinate; 2], ParseError> {
+    let start = reader.pos();
+
+    let a = parse_world_coordinate(reader, integers, center)?;
+    expect_separator(reader, start, incomplete)?;
+    let b = parse_world_coordinate(reader, integers, center)?;
+
+    Ok([a, b])
+}
+
+pub(crate) fn parse_angle(reader: &mut StringReader) -> Result<WorldCoordinate, ParseError> {
+    if !reader.can_read() {
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_ANGLE_INCOMPLETE,
+            [],
+        )));
+    }
+
+    parse_world_coordinate(reader, false, false)
+}
+
+fn expect_separator(
+    reader: &mut StringReader,
+    start: usize,
+    incomplete: &'static str,
+) -> Result<(), ParseError> {
+    if reader.peek() == Some(' ') {
+        reader.read_char();
+        Ok(())
+    } else {
+        reader.set_pos(start);
+        Err(reader.error(Text::translate(incomplete, [])))
+    }
+}
+
+fn parse_world_coordinate(
+    reader: &mut StringReader,
+    integer: bool,
+    center: bool,
+) -> Result<WorldCoordinate, ParseError> {
+    match reader.peek() {
+        Some('^') => {
+            return Err(reader.error(Text::translate(translation_key::ARGUMENT_POS_MIXED, [])))
+        }
+        Some(' ') | None => {
+            return Err(reader.error(Text::translate(
+                if integer {
+                    translation_key::ARGUMENT_POS_MISSING_INT
+                } else {
+                    translation_key::ARGUMENT_POS_MISSING_DOUBLE
+                },
+                [],
+            )))
+        }
+        _ => {}
+    }
+
+    let relative = reader.peek() == Some('~');
+
+    if relative {
+        reader.read_char();
+
+        // A lone `~` is the sender's coordinate.
+        if reader.peek().map_or(true, |c| c == ' ') {
+            return Ok(WorldCoordinate {
+                value: 0.0,
+                relative,
+            });
+        }
+    }
+
+    let start = reader.pos();
+
+    let value = if integer && !relative {
+        parse_number::<i32>(reader, None, None, INT_ERRORS)? as f64
+    } else {
+        let value = parse_number::<f64>(reader, None, None, DOUBLE_ERRORS)?;
+
+        if center && !relative && !reader.input()[start..reader.pos()].contains('.') {
+            value + 0.5
+        } else {
+            value
+        }
+    };
+
+    Ok(WorldCoordinate { value, relative })
+}
+
+fn parse_local_coordinate(reader: &mut StringReader) -> Result<f64, ParseError> {
+    if reader.peek() != Some('^') {
+        return Err(reader.error(Text::translate(translation_key::ARGUMENT_POS_MIXED, [])));
+    }
+
+    reader.read_char();
+
+    if reader.peek().map_or(true, |c| c == ' ') {
+        return Ok(0.0);
+    }
+
+    parse_number(reader, None, None, DOUBLE_ERRORS)
+}
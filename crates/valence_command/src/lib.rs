@@ -0,0 +1,589 @@
+//! # Commands
+//! Commands run by players with `/`, declared as a tree of nodes like in
+//! Minecraft's Brigadier library.
+//!
+//! A command starts with a [`literal`] node for its name. Child nodes are the
+//! subcommands and [`argument`]s that can follow, where each argument reads a
+//! typed value with a [`Parser`]. Nodes that can end a command are made
+//! executable with a handler. Commands are added to the [`CommandRegistry`],
+//! which is sent to clients so that they get syntax highlighting.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_client::message::SendMessage;
+//! # use valence_command::packet::Parser;
+//! # use valence_command::{argument, literal, CommandRegistry};
+//! fn register_commands(mut registry: ResMut<CommandRegistry>) {
+//!     registry.register(
+//!         literal("heal")
+//!             .executes(|_event, client, _commands| client.send_chat_message("Healed!"))
+//!             .then(
+//!                 argument(
+//!                     "amount",
+//!                     Parser::Integer {
+//!                         min: Some(1),
+//!                         max: None,
+//!                     },
+//!                 )
+//!                 .executes(|event, client, _commands| {
+//!                     let amount = event.args.get_i32("amount").unwrap();
+//!                     client.send_chat_message(format!("Healed {amount} hearts!"));
+//!                 }),
+//!             ),
+//!     );
+//! }
+//! ```
+//!
+//! Commands run by clients are parsed with the registry. Valid commands run
+//! the handler of their last node and send a [`CommandExecutionEvent`].
+//! Invalid commands are answered with an error in the chat, like in vanilla.
+//!
+//! Nodes can require a permission with [`CommandNode::requires`]. Clients
+//! without the permission don't see the node and can't use it, and their
+//! command tree is sent again when their [`Permissions`] change.
+//!
+//! Arguments can be given a suggestion provider with
+//! [`CommandNode::suggests_with`] to complete them while they are typed. See
+//! the [`suggestion`] module.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+pub mod coordinates;
+pub mod packet;
+pub mod parse;
+pub mod predicate;
+pub mod range;
+pub mod selector;
+pub mod suggestion;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_tasks::{AsyncComputeTaskPool, TaskPool};
+use packet::{CommandTreeS2c, Node, NodeData, Parser, Suggestion};
+use parse::{parse_arg, ArgValue, CommandArgs, ParseError, StringReader};
+use suggestion::{
+    handle_suggestion_requests, send_finished_suggestions, CommandSuggestion, SuggestionChannel,
+    SuggestionContext, SuggestionFuture, SuggestionProvider,
+};
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
+use valence_client::message::SendMessage;
+use valence_client::{Client, FlushPacketsSet, UpdateClientsSet};
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::packet::chat::CommandExecutionC2s;
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_core::translation_key;
+use valence_permission::{
+    PermissionManager, Permissions, PermissionsChangedEvent, UpdatePermissionsSet,
+};
+
+pub struct CommandPlugin;
+
+impl Plugin for CommandPlugin {
+    fn build(&self, app: &mut App) {
+        // Async suggestion providers are run on this pool.
+        AsyncComputeTaskPool::init(TaskPool::default);
+
+        app.init_resource::<CommandRegistry>()
+            .init_resource::<SuggestionChannel>()
+            .add_event::<CommandExecutionEvent>()
+            .add_systems(
+                (handle_command_execution, handle_suggestion_requests)
+                    .in_base_set(EventLoopSet::PreUpdate)
+                    .in_schedule(EventLoopSchedule),
+            )
+            .add_systems(
+                (send_command_tree, send_finished_suggestions)
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(UpdateClientsSet)
+                    .after(UpdatePermissionsSet)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+type CommandHandler = Arc<dyn Fn(&CommandExecutionEvent, &mut Client, &mut Commands) + Send + Sync>;
+
+/// All commands known to the server, by their name.
+///
+/// Changing the registry sends the commands to all clients again.
+#[derive(Resource, Default, Debug)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, CommandNode>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a command, returning the command with the same name it replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is not a [`literal`].
+    pub fn register(&mut self, command: CommandNode) -> Option<CommandNode> {
+        let NodeKind::Literal(name) = &command.kind else {
+            panic!("commands must start with a literal node")
+        };
+
+        self.commands.insert(name.clone(), command)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<CommandNode> {
+        self.commands.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CommandNode> {
+        self.commands.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CommandNode> + '_ {
+        self.commands.values()
+    }
+
+    /// Parses the input of a command, without the leading `/`. Returns the
+    /// executable node the command ends at, along with the arguments that
+    /// were read on the way there.
+    ///
+    /// The permissions of nodes are not checked. Use
+    /// [`parse_with_permissions`] for commands run by clients.
+    ///
+    /// [`parse_with_permissions`]: Self::parse_with_permissions
+    pub fn parse(&self, input: &str) -> Result<(&CommandNode, CommandArgs), ParseError> {
+        self.parse_filtered(input, &|_| true)
+    }
+
+    /// Parses the input of a command like [`parse`], skipping the nodes the
+    /// holder of `permissions` can't use.
+    ///
+    /// [`parse`]: Self::parse
+    pub fn parse_with_permissions(
+        &self,
+        input: &str,
+        permissions: &Permissions,
+        manager: &PermissionManager,
+    ) -> Result<(&CommandNode, CommandArgs), ParseError> {
+        self.parse_filtered(input, &|node| node.can_use(permissions, manager))
+    }
+
+    fn parse_filtered(
+        &self,
+        input: &str,
+        can_use: &dyn Fn(&CommandNode) -> bool,
+    ) -> Result<(&CommandNode, CommandArgs), ParseError> {
+        let mut reader = StringReader::new(input);
+
+        let command = self
+            .commands
+            .get(reader.read_word())
+            .filter(|command| can_use(command));
+
+        let Some(command) = command else {
+            reader.set_pos(0);
+            return Err(reader.error(Text::translate(
+                translation_key::COMMAND_UNKNOWN_COMMAND,
+                [],
+            )))
+        };
+
+        let mut args = vec![];
+        let node = parse_after(command, &mut reader, &mut args, can_use)?;
+
+        Ok((node, CommandArgs(args)))
+    }
+
+    /// Creates the command tree packet with the nodes that pass `can_use`.
+    fn to_packet(&self, can_use: &dyn Fn(&CommandNode) -> bool) -> CommandTreeS2c<'_> {
+        let mut nodes = vec![Node {
+            children: vec![],
+            data: NodeData::Root,
+            executable: false,
+            redirect_node: None,
+        }];
+
+        let children = self
+            .commands
+            .values()
+            .filter(|command| can_use(command))
+            .map(|command| command.write_nodes(&mut nodes, can_use))
+            .collect();
+
+        nodes[0].children = children;
+
+        CommandTreeS2c {
+            commands: nodes,
+            root_index: VarInt(0),
+        }
+    }
+}
+
+/// Creates a node that matches the given word exactly.
+pub fn literal(name: impl Into<String>) -> CommandNode {
+    CommandNode::new(NodeKind::Literal(name.into()))
+}
+
+/// Creates a node that reads an argument with a parser. The value is stored
+/// in the [`CommandArgs`] under `name`.
+pub fn argument(name: impl Into<String>, parser: Parser<'static>) -> CommandNode {
+    CommandNode::new(NodeKind::Argument {
+        name: name.into(),
+        parser,
+        suggestion: None,
+    })
+}
+
+/// A node in the tree of a command. Create nodes with [`literal`] and
+/// [`argument`].
+#[derive(Clone)]
+pub struct CommandNode {
+    kind: NodeKind,
+    children: Vec<CommandNode>,
+    executable: bool,
+    handler: Option<CommandHandler>,
+    provider: Option<SuggestionProvider>,
+    permission: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum NodeKind {
+    Literal(String),
+    Argument {
+        name: String,
+        parser: Parser<'static>,
+        suggestion: Option<Suggestion>,
+    },
+}
+
+impl CommandNode {
+    fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            children: vec![],
+            executable: false,
+            handler: None,
+            provider: None,
+            permission: None,
+        }
+    }
+
+    /// The name of the literal or argument.
+    pub fn name(&self) -> &str {
+        match &self.kind {
+            NodeKind::Literal(name) | NodeKind::Argument { name, .. } => name,
+        }
+    }
+
+    /// Adds a node that can follow this one. Children are tried in the order
+    /// they were added.
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Makes the command executable when it ends at this node. The handler is
+    /// run with the [`CommandExecutionEvent`] and the client that ran the
+    /// command.
+    pub fn executes(
+        mut self,
+        handler: impl Fn(&CommandExecutionEvent, &mut Client, &mut Commands) + Send + Sync + 'static,
+    ) -> Self {
+        self.executable = true;
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Makes the command executable when it ends at this node, without a
+    /// handler. Read the [`CommandExecutionEvent`]s to run the command.
+    pub fn executable(mut self) -> Self {
+        self.executable = true;
+        self
+    }
+
+    /// Sets the suggestions the client shows for an argument. Does nothing
+    /// for literals.
+    pub fn suggests(mut self, suggestion: Suggestion) -> Self {
+        if let NodeKind::Argument { suggestion: s, .. } = &mut self.kind {
+            *s = Some(suggestion);
+        }
+        self
+    }
+
+    /// Sets the provider of the suggestions for an argument, which the client
+    /// asks the server for while the argument is typed. Does nothing for
+    /// literals.
+    ///
+    /// The provider returns a future, which is run on the
+    /// [`AsyncComputeTaskPool`]. Suggestions that don't start with the part
+    /// of the argument that was typed are left out, ignoring case.
+    pub fn suggests_with<F, Fut>(mut self, provider: F) -> Self
+    where
+        F: Fn(SuggestionContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Vec<CommandSuggestion>> + Send + 'static,
+    {
+        if let NodeKind::Argument { suggestion, .. } = &mut self.kind {
+            *suggestion = Some(Suggestion::AskServer);
+            self.provider = Some(Arc::new(move |ctx| -> SuggestionFuture {
+                Box::pin(provider(ctx))
+            }));
+        }
+        self
+    }
+
+    /// Makes the node require a permission node. Clients without the
+    /// permission don't see the node and its children, and can't use them.
+    pub fn requires(mut self, permission: impl Into<String>) -> Self {
+        self.permission = Some(permission.into());
+        self
+    }
+
+    /// The permission required to use the node.
+    pub fn permission(&self) -> Option<&str> {
+        self.permission.as_deref()
+    }
+
+    /// If the holder of `permissions` can use this node. Parent nodes are not
+    /// checked.
+    pub fn can_use(&self, permissions: &Permissions, manager: &PermissionManager) -> bool {
+        self.permission.as_ref().map_or(true, |permission| {
+            manager.has_permission(permissions, permission)
+        })
+    }
+
+    pub fn children(&self) -> &[CommandNode] {
+        &self.children
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    /// Adds this node and its children that pass `can_use` to the nodes of a
+    /// command tree packet, returning the index of this node.
+    fn write_nodes<'a>(
+        &'a self,
+        nodes: &mut Vec<Node<'a>>,
+        can_use: &dyn Fn(&CommandNode) -> bool,
+    ) -> VarInt {
+        let index = nodes.len();
+
+        nodes.push(Node {
+            children: vec![],
+            data: match &self.kind {
+                NodeKind::Literal(name) => NodeData::Literal { name },
+                NodeKind::Argument {
+                    name,
+                    parser,
+                    suggestion,
+                } => NodeData::Argument {
+                    name,
+                    parser: parser.clone(),
+                    suggestion: *suggestion,
+                },
+            },
+            executable: self.executable,
+            redirect_node: None,
+        });
+
+        let children = self
+            .children
+            .iter()
+            .filter(|child| can_use(child))
+            .map(|child| child.write_nodes(nodes, can_use))
+            .collect();
+
+        nodes[index].children = children;
+
+        VarInt(index as i32)
+    }
+}
+
+impl fmt::Debug for CommandNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandNode")
+            .field("kind", &self.kind)
+            .field("children", &self.children)
+            .field("executable", &self.executable)
+            .field("handler", &self.handler.is_some())
+            .field("provider", &self.provider.is_some())
+            .field("permission", &self.permission)
+            .finish()
+    }
+}
+
+/// Reads the children of `node` after `node` was read, until the end of the
+/// input. Returns the last node.
+fn parse_after<'n>(
+    node: &'n CommandNode,
+    reader: &mut StringReader,
+    args: &mut Vec<(String, ArgValue)>,
+    can_use: &dyn Fn(&CommandNode) -> bool,
+) -> Result<&'n CommandNode, ParseError> {
+    if !reader.can_read() {
+        return if node.executable {
+            Ok(node)
+        } else {
+            Err(reader.error(Text::translate(
+                translation_key::COMMAND_UNKNOWN_COMMAND,
+                [],
+            )))
+        };
+    }
+
+    // Skip the space after the node.
+    reader.read_char();
+
+    let start = reader.pos();
+    let mut error: Option<ParseError> = None;
+
+    // Like in Brigadier, a literal that matches the next word is the only child
+    // that is tried.
+    let word = reader.remaining().split(' ').next().unwrap_or_default();
+    let children = match node.children.iter().find(|child| {
+        matches!(&child.kind, NodeKind::Literal(name) if name == word) && can_use(child)
+    }) {
+        Some(literal) => std::slice::from_ref(literal),
+        None => &node.children,
+    };
+
+    for child in children.iter().filter(|child| can_use(child)) {
+        reader.set_pos(start);
+        let arg_count = args.len();
+
+        let res = match &child.kind {
+            NodeKind::Literal(name) => {
+                if reader.read_word() != name.as_str() {
+                    continue;
+                }
+
+                parse_after(child, reader, args, can_use)
+            }
+            NodeKind::Argument { name, parser, .. } => parse_arg(parser, reader)
+                .and_then(|value| {
+                    if reader.peek().map_or(true, |c| c == ' ') {
+                        args.push((name.clone(), value));
+                        Ok(())
+                    } else {
+                        Err(reader.error(Text::translate(
+                            translation_key::COMMAND_EXPECTED_SEPARATOR,
+                            [],
+                        )))
+                    }
+                })
+                .and_then(|()| parse_after(child, reader, args, can_use)),
+        };
+
+        match res {
+            Ok(last) => return Ok(last),
+            Err(e) => {
+                args.truncate(arg_count);
+
+                // Report the error that got the furthest into the input.
+                if error.as_ref().map_or(true, |error| e.pos > error.pos) {
+                    error = Some(e);
+                }
+            }
+        }
+    }
+
+    reader.set_pos(start);
+
+    Err(error.unwrap_or_else(|| {
+        reader.error(Text::translate(
+            translation_key::COMMAND_UNKNOWN_ARGUMENT,
+            [],
+        ))
+    }))
+}
+
+/// Sent when a client runs a valid command.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommandExecutionEvent {
+    /// The client that ran the command.
+    pub client: Entity,
+    /// The name of the command.
+    pub command: String,
+    /// The whole input of the command, without the leading `/`.
+    pub input: String,
+    pub args: CommandArgs,
+}
+
+fn handle_command_execution(
+    mut packets: EventReader<PacketEvent>,
+    registry: Res<CommandRegistry>,
+    manager: Res<PermissionManager>,
+    mut clients: Query<(&mut Client, Option<&Permissions>)>,
+    mut events: EventWriter<CommandExecutionEvent>,
+    mut commands: Commands,
+) {
+    let no_permissions = Permissions::default();
+
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<CommandExecutionC2s>() else {
+            continue
+        };
+
+        let Ok((mut client, permissions)) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let permissions = permissions.unwrap_or(&no_permissions);
+
+        match registry.parse_with_permissions(pkt.command, permissions, &manager) {
+            Ok((node, args)) => {
+                let event = CommandExecutionEvent {
+                    client: packet.client,
+                    command: pkt.command.split(' ').next().unwrap_or_default().to_owned(),
+                    input: pkt.command.to_owned(),
+                    args,
+                };
+
+                if let Some(handler) = &node.handler {
+                    handler(&event, &mut client, &mut commands);
+                }
+
+                events.send(event);
+            }
+            Err(e) => client.send_chat_message(e.to_text(pkt.command)),
+        }
+    }
+}
+
+fn send_command_tree(
+    registry: Res<CommandRegistry>,
+    manager: Res<PermissionManager>,
+    mut permission_events: EventReader<PermissionsChangedEvent>,
+    mut clients: Query<(Entity, &mut Client, Option<&Permissions>)>,
+) {
+    let no_permissions = Permissions::default();
+    let changed: Vec<Entity> = permission_events.iter().map(|e| e.client).collect();
+
+    for (entity, mut client, permissions) in &mut clients {
+        if registry.is_changed() || client.is_added() || changed.contains(&entity) {
+            let permissions = permissions.unwrap_or(&no_permissions);
+
+            client.write_packet(&registry.to_packet(&|node| node.can_use(permissions, &manager)));
+        }
+    }
+}
@@ -0,0 +1,613 @@
+//! Reading typed arguments from command input.
+
+use valence_core::text::{Color, Text, TextFormat};
+use valence_core::translation_key;
+
+use crate::coordinates::{
+    parse_angle, parse_coordinates, parse_coordinates_2d, Coordinates, WorldCoordinate,
+};
+use crate::packet::{Parser, StringArg};
+use crate::predicate::{
+    parse_block_predicate, parse_block_state, parse_item_predicate, parse_item_stack,
+    BlockPredicate, BlockStateArg, ItemPredicate, ItemStackArg,
+};
+use crate::range::{parse_float_range, parse_int_range, FloatRange, IntRange};
+use crate::selector::{parse_selector, EntitySelector};
+
+/// A cursor over the input of a command.
+#[derive(Copy, Clone, Debug)]
+pub struct StringReader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// The whole input, including the part that was already read.
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// The byte offset of the cursor.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn set_pos(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// The part of the input after the cursor.
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.pos < self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    pub fn read_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Creates an error at the cursor.
+    pub fn error(&self, message: impl Into<Text>) -> ParseError {
+        ParseError {
+            pos: self.pos,
+            message: message.into(),
+        }
+    }
+
+    /// Reads up to the next space or the end of the input.
+    pub fn read_word(&mut self) -> &'a str {
+        let word = self.remaining().split(' ').next().unwrap_or_default();
+        self.pos += word.len();
+        word
+    }
+
+    /// Reads the characters allowed in unquoted strings, which are ASCII
+    /// letters, digits, and `_-.+`.
+    pub fn read_unquoted_string(&mut self) -> &'a str {
+        let rest = self.remaining();
+        let len = rest
+            .find(|c: char| !is_allowed_in_unquoted_string(c))
+            .unwrap_or(rest.len());
+
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Reads a string surrounded by `"` or `'`, where `\` escapes the quote
+    /// and itself.
+    pub fn read_quoted_string(&mut self) -> Result<String, ParseError> {
+        let Some(quote @ ('"' | '\'')) = self.peek() else {
+            return Err(self.error(Text::translate(
+                translation_key::PARSING_QUOTE_EXPECTED_START,
+                [],
+            )))
+        };
+
+        self.read_char();
+
+        let mut res = String::new();
+
+        loop {
+            match self.read_char() {
+                Some('\\') => match self.read_char() {
+                    Some(c) if c == quote || c == '\\' => res.push(c),
+                    Some(c) => {
+                        self.pos -= c.len_utf8();
+                        return Err(self.error(Text::translate(
+                            translation_key::PARSING_QUOTE_ESCAPE,
+                            [c.into()],
+                        )));
+                    }
+                    None => break,
+                },
+                Some(c) if c == quote => return Ok(res),
+                Some(c) => res.push(c),
+                None => break,
+            }
+        }
+
+        Err(self.error(Text::translate(
+            translation_key::PARSING_QUOTE_EXPECTED_END,
+            [],
+        )))
+    }
+
+    /// Reads a quoted string or an unquoted string.
+    pub fn read_string(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some('"' | '\'') => self.read_quoted_string(),
+            _ => Ok(self.read_unquoted_string().to_owned()),
+        }
+    }
+
+    /// Reads the characters allowed in resource locations like
+    /// `minecraft:stone`, and a leading `#` for tags.
+    pub fn read_resource_location(&mut self) -> &'a str {
+        let rest = self.remaining();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || matches!(c, '_' | '-' | '.' | ':' | '/')
+                    || (i == 0 && c == '#'))
+            })
+            .map_or(rest.len(), |(i, _)| i);
+
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Reads the rest of the input.
+    pub fn read_remaining(&mut self) -> &'a str {
+        let rest = self.remaining();
+        self.pos = self.input.len();
+        rest
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.peek() == Some(' ') {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads the given character, or returns an error if the next character is
+    /// something else.
+    pub fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(Text::translate(
+                translation_key::PARSING_EXPECTED,
+                [c.into()],
+            )))
+        }
+    }
+}
+
+fn is_allowed_in_unquoted_string(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+/// An error in the input of a command.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseError {
+    /// The byte offset in the input where the error is.
+    pub pos: usize,
+    pub message: Text,
+}
+
+impl ParseError {
+    /// The message shown to players for an error in `input`, in the format
+    /// of vanilla. The part of the input after the error is underlined.
+    pub fn to_text(&self, input: &str) -> Text {
+        let pos = self.pos.min(input.len());
+        let start = input[..pos]
+            .char_indices()
+            .rev()
+            .nth(9)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        let mut context = Text::default().color(Color::GRAY);
+
+        if start > 0 {
+            context += "...";
+        }
+
+        context += input[start..pos].to_owned();
+
+        if pos < input.len() {
+            context += input[pos..].to_owned().color(Color::RED).underlined();
+        }
+
+        context += Text::translate(translation_key::COMMAND_CONTEXT_HERE, [])
+            .color(Color::RED)
+            .italic();
+
+        self.message.clone().color(Color::RED) + "\n" + context
+    }
+}
+
+/// A parsed argument of a command.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ArgValue {
+    Bool(bool),
+    Float(f32),
+    Double(f64),
+    Integer(i32),
+    Long(i64),
+    String(String),
+    Entity(EntitySelector),
+    /// A player name or a selector that only selects players.
+    GameProfile(EntitySelector),
+    BlockPos(Coordinates),
+    Vec3(Coordinates),
+    ColumnPos([WorldCoordinate; 2]),
+    Vec2([WorldCoordinate; 2]),
+    /// The yaw and pitch of a rotation.
+    Rotation([WorldCoordinate; 2]),
+    Angle(WorldCoordinate),
+    BlockState(BlockStateArg),
+    BlockPredicate(BlockPredicate),
+    ItemStack(ItemStackArg),
+    ItemPredicate(ItemPredicate),
+    IntRange(IntRange),
+    FloatRange(FloatRange),
+    /// A duration in ticks.
+    Time(i32),
+    Component(Text),
+    /// The input of an argument without a parser on the server, which is read
+    /// up to the next space.
+    Raw(String),
+}
+
+/// The parsed arguments of a command, by name.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct CommandArgs(pub(crate) Vec<(String, ArgValue)>);
+
+impl CommandArgs {
+    pub fn get(&self, name: &str) -> Option<&ArgValue> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            ArgValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn get_f32(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            ArgValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            ArgValue::Double(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    pub fn get_i32(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            ArgValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            ArgValue::Long(l) => Some(*l),
+            _ => None,
+        }
+    }
+
+    /// Gets a string argument, or the input of an argument without a parser.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            ArgValue::String(s) | ArgValue::Raw(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets an entity selector, including the selectors of game profile
+    /// arguments.
+    pub fn get_selector(&self, name: &str) -> Option<&EntitySelector> {
+        match self.get(name)? {
+            ArgValue::Entity(s) | ArgValue::GameProfile(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets the coordinates of a block position or a 3D position.
+    pub fn get_coordinates(&self, name: &str) -> Option<&Coordinates> {
+        match self.get(name)? {
+            ArgValue::BlockPos(c) | ArgValue::Vec3(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Gets a time argument in ticks.
+    pub fn get_time(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            ArgValue::Time(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    pub fn get_text(&self, name: &str) -> Option<&Text> {
+        match self.get(name)? {
+            ArgValue::Component(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the names and values of the arguments in the order they
+    /// appear in the command.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ArgValue)> + '_ {
+        self.0.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Reads the argument of a parser at the cursor.
+pub fn parse_arg(parser: &Parser, reader: &mut StringReader) -> Result<ArgValue, ParseError> {
+    match parser {
+        Parser::Bool => {
+            let start = reader.pos();
+
+            match reader.read_unquoted_string() {
+                "true" => Ok(ArgValue::Bool(true)),
+                "false" => Ok(ArgValue::Bool(false)),
+                "" => {
+                    Err(reader.error(Text::translate(translation_key::PARSING_BOOL_EXPECTED, [])))
+                }
+                other => {
+                    let other = other.to_owned();
+                    reader.set_pos(start);
+                    Err(reader.error(Text::translate(
+                        translation_key::PARSING_BOOL_INVALID,
+                        [other.into()],
+                    )))
+                }
+            }
+        }
+        Parser::Float { min, max } => parse_number(
+            reader,
+            *min,
+            *max,
+            [
+                translation_key::PARSING_FLOAT_EXPECTED,
+                translation_key::PARSING_FLOAT_INVALID,
+                translation_key::ARGUMENT_FLOAT_LOW,
+                translation_key::ARGUMENT_FLOAT_BIG,
+            ],
+        )
+        .map(ArgValue::Float),
+        Parser::Double { min, max } => {
+            parse_number(reader, *min, *max, DOUBLE_ERRORS).map(ArgValue::Double)
+        }
+        Parser::Integer { min, max } => {
+            parse_number(reader, *min, *max, INT_ERRORS).map(ArgValue::Integer)
+        }
+        Parser::Long { min, max } => parse_number(
+            reader,
+            *min,
+            *max,
+            [
+                translation_key::PARSING_LONG_EXPECTED,
+                translation_key::PARSING_LONG_INVALID,
+                translation_key::ARGUMENT_LONG_LOW,
+                translation_key::ARGUMENT_LONG_BIG,
+            ],
+        )
+        .map(ArgValue::Long),
+        Parser::String(StringArg::SingleWord) => {
+            Ok(ArgValue::String(reader.read_unquoted_string().to_owned()))
+        }
+        Parser::String(StringArg::QuotablePhrase) => reader.read_string().map(ArgValue::String),
+        Parser::String(StringArg::GreedyPhrase) | Parser::Message => {
+            Ok(ArgValue::String(reader.read_remaining().to_owned()))
+        }
+        Parser::Entity {
+            single,
+            only_players,
+        } => {
+            let start = reader.pos();
+            let selector = parse_selector(reader)?;
+
+            if *single && !selector.is_single() {
+                reader.set_pos(start);
+                return Err(reader.error(Text::translate(
+                    if *only_players {
+                        translation_key::ARGUMENT_PLAYER_TOOMANY
+                    } else {
+                        translation_key::ARGUMENT_ENTITY_TOOMANY
+                    },
+                    [],
+                )));
+            }
+
+            if *only_players && !selector.only_players() {
+                reader.set_pos(start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_PLAYER_ENTITIES,
+                    [],
+                )));
+            }
+
+            Ok(ArgValue::Entity(selector))
+        }
+        Parser::GameProfile => {
+            let start = reader.pos();
+            let selector = parse_selector(reader)?;
+
+            if !selector.only_players() {
+                reader.set_pos(start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_PLAYER_ENTITIES,
+                    [],
+                )));
+            }
+
+            Ok(ArgValue::GameProfile(selector))
+        }
+        Parser::BlockPos => parse_coordinates(reader, true).map(ArgValue::BlockPos),
+        Parser::Vec3 => parse_coordinates(reader, false).map(ArgValue::Vec3),
+        Parser::ColumnPos => parse_coordinates_2d(
+            reader,
+            true,
+            false,
+            translation_key::ARGUMENT_POS2D_INCOMPLETE,
+        )
+        .map(ArgValue::ColumnPos),
+        Parser::Vec2 => parse_coordinates_2d(
+            reader,
+            false,
+            true,
+            translation_key::ARGUMENT_POS2D_INCOMPLETE,
+        )
+        .map(ArgValue::Vec2),
+        Parser::Rotation => parse_coordinates_2d(
+            reader,
+            false,
+            false,
+            translation_key::ARGUMENT_ROTATION_INCOMPLETE,
+        )
+        .map(ArgValue::Rotation),
+        Parser::Angle => parse_angle(reader).map(ArgValue::Angle),
+        Parser::BlockState => parse_block_state(reader).map(ArgValue::BlockState),
+        Parser::BlockPredicate => parse_block_predicate(reader).map(ArgValue::BlockPredicate),
+        Parser::ItemStack => parse_item_stack(reader).map(ArgValue::ItemStack),
+        Parser::ItemPredicate => parse_item_predicate(reader).map(ArgValue::ItemPredicate),
+        Parser::IntRange => parse_int_range(reader).map(ArgValue::IntRange),
+        Parser::FloatRange => parse_float_range(reader).map(ArgValue::FloatRange),
+        Parser::Time => parse_time(reader).map(ArgValue::Time),
+        Parser::Component => parse_component(reader).map(ArgValue::Component),
+        _ => Ok(ArgValue::Raw(reader.read_word().to_owned())),
+    }
+}
+
+/// Reads a duration like `5s`, `2d`, or `10t` and converts it to ticks.
+/// Numbers without a unit are in ticks.
+fn parse_time(reader: &mut StringReader) -> Result<i32, ParseError> {
+    let start = reader.pos();
+    let amount: f64 = parse_number(reader, None, None, DOUBLE_ERRORS)?;
+
+    let unit = match reader.peek() {
+        Some('d') => 24000.0,
+        Some('s') => 20.0,
+        Some('t') => 1.0,
+        Some(' ') | None => 1.0,
+        Some(_) => {
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_TIME_INVALID_UNIT,
+                [],
+            )))
+        }
+    };
+
+    if reader.peek().is_some_and(|c| c != ' ') {
+        reader.read_char();
+    }
+
+    let ticks = (amount * unit).round() as i32;
+
+    if ticks < 0 {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_TIME_TICK_COUNT_TOO_LOW,
+            ["0".into(), ticks.to_string().into()],
+        )));
+    }
+
+    Ok(ticks)
+}
+
+/// Reads a text component in JSON.
+fn parse_component(reader: &mut StringReader) -> Result<Text, ParseError> {
+    let mut stream = serde_json::Deserializer::from_str(reader.remaining()).into_iter::<Text>();
+
+    match stream.next() {
+        Some(Ok(text)) => {
+            reader.set_pos(reader.pos() + stream.byte_offset());
+            Ok(text)
+        }
+        Some(Err(e)) => Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_COMPONENT_INVALID,
+            [e.to_string().into()],
+        ))),
+        None => Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_COMPONENT_INVALID,
+            ["empty input".into()],
+        ))),
+    }
+}
+
+/// The keys of the errors for reading integers with [`parse_number`].
+pub(crate) const INT_ERRORS: [&str; 4] = [
+    translation_key::PARSING_INT_EXPECTED,
+    translation_key::PARSING_INT_INVALID,
+    translation_key::ARGUMENT_INTEGER_LOW,
+    translation_key::ARGUMENT_INTEGER_BIG,
+];
+
+/// The keys of the errors for reading doubles with [`parse_number`].
+pub(crate) const DOUBLE_ERRORS: [&str; 4] = [
+    translation_key::PARSING_DOUBLE_EXPECTED,
+    translation_key::PARSING_DOUBLE_INVALID,
+    translation_key::ARGUMENT_DOUBLE_LOW,
+    translation_key::ARGUMENT_DOUBLE_BIG,
+];
+
+/// Reads a number with the keys of the expected, invalid, too low, and too
+/// high errors.
+pub(crate) fn parse_number<T>(
+    reader: &mut StringReader,
+    min: Option<T>,
+    max: Option<T>,
+    [expected, invalid, low, big]: [&'static str; 4],
+) -> Result<T, ParseError>
+where
+    T: std::str::FromStr + PartialOrd + ToString + Copy,
+{
+    let start = reader.pos();
+    let rest = reader.remaining();
+    let len = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '.')))
+        .unwrap_or(rest.len());
+    let number = &rest[..len];
+
+    if number.is_empty() {
+        return Err(reader.error(Text::translate(expected, [])));
+    }
+
+    let Ok(value) = number.parse::<T>() else {
+        return Err(reader.error(Text::translate(invalid, [number.to_owned().into()])))
+    };
+
+    reader.set_pos(start + len);
+
+    if let Some(min) = min.filter(|&min| value < min) {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(
+            low,
+            [min.to_string().into(), value.to_string().into()],
+        )));
+    }
+
+    if let Some(max) = max.filter(|&max| value > max) {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(
+            big,
+            [max.to_string().into(), value.to_string().into()],
+        )));
+    }
+
+    Ok(value)
+}
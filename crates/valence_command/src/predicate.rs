@@ -0,0 +1,407 @@
+//! Blocks and items with their properties and NBT, like
+//! `oak_stairs[facing=east]` and `diamond_sword{Damage:10}`, and predicates
+//! that match them.
+
+use valence_block::{BlockKind, BlockState, PropName, PropValue};
+use valence_core::ident::Ident;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::text::Text;
+use valence_core::translation_key;
+use valence_nbt::snbt::SnbtReader;
+use valence_nbt::{Compound, Value};
+use valence_registry::tags::TagsRegistry;
+
+use crate::parse::{ParseError, StringReader};
+
+/// A block state with optional NBT for its block entity.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockStateArg {
+    pub state: BlockState,
+    pub nbt: Option<Compound>,
+}
+
+/// A block kind or a block tag.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BlockOrTag {
+    Block(BlockKind),
+    Tag(Ident<String>),
+}
+
+/// Matches blocks of a kind or tag, with the given properties and NBT.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockPredicate {
+    pub block: BlockOrTag,
+    /// The names and values of the properties the block must have. Properties
+    /// of tags can't be checked until the block is known.
+    pub properties: Vec<(String, String)>,
+    pub nbt: Option<Compound>,
+}
+
+impl BlockPredicate {
+    /// If a block with the given state and block entity NBT matches the
+    /// predicate. Block tags are looked up in `tags`.
+    pub fn matches(&self, state: BlockState, nbt: Option<&Compound>, tags: &TagsRegistry) -> bool {
+        let kind_matches = match &self.block {
+            BlockOrTag::Block(kind) => state.to_kind() == *kind,
+            BlockOrTag::Tag(tag) => tag_contains(
+                tags,
+                "minecraft:block",
+                tag,
+                state.to_kind().to_raw() as i32,
+            ),
+        };
+
+        kind_matches
+            && self.properties.iter().all(|(name, value)| {
+                PropName::from_str(name)
+                    .and_then(|name| state.get(name))
+                    .is_some_and(|v| v.to_str() == value)
+            })
+            && nbt_matches(self.nbt.as_ref(), nbt)
+    }
+}
+
+/// An item with optional NBT.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ItemStackArg {
+    pub item: ItemKind,
+    pub nbt: Option<Compound>,
+}
+
+impl ItemStackArg {
+    pub fn to_stack(&self, count: u8) -> ItemStack {
+        ItemStack::new(self.item, count, self.nbt.clone())
+    }
+}
+
+/// An item kind or an item tag.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ItemOrTag {
+    Item(ItemKind),
+    Tag(Ident<String>),
+}
+
+/// Matches items of a kind or tag with the given NBT.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ItemPredicate {
+    pub item: ItemOrTag,
+    pub nbt: Option<Compound>,
+}
+
+impl ItemPredicate {
+    /// If the item stack matches the predicate. Item tags are looked up in
+    /// `tags`.
+    pub fn matches(&self, stack: &ItemStack, tags: &TagsRegistry) -> bool {
+        let kind_matches = match &self.item {
+            ItemOrTag::Item(item) => stack.item == *item,
+            ItemOrTag::Tag(tag) => {
+                tag_contains(tags, "minecraft:item", tag, stack.item.to_raw() as i32)
+            }
+        };
+
+        kind_matches && nbt_matches(self.nbt.as_ref(), stack.nbt.as_ref())
+    }
+}
+
+fn tag_contains(tags: &TagsRegistry, registry: &str, tag: &Ident<String>, raw_id: i32) -> bool {
+    Ident::new(registry)
+        .ok()
+        .and_then(|registry| tags.tag(registry.as_str_ident(), tag.as_str_ident()))
+        .is_some_and(|tag| tag.entries.iter().any(|e| e.0 == raw_id))
+}
+
+/// If every value in `expected` is also in `actual`. Compounds only need to
+/// contain the expected keys, like in vanilla.
+fn nbt_matches(expected: Option<&Compound>, actual: Option<&Compound>) -> bool {
+    fn compound_matches(expected: &Compound, actual: &Compound) -> bool {
+        expected
+            .iter()
+            .all(|(key, expected)| match (expected, actual.get(key)) {
+                (Value::Compound(expected), Some(Value::Compound(actual))) => {
+                    compound_matches(expected, actual)
+                }
+                (expected, actual) => Some(expected) == actual,
+            })
+    }
+
+    match (expected, actual) {
+        (None, _) => true,
+        (Some(expected), None) => expected.is_empty(),
+        (Some(expected), Some(actual)) => compound_matches(expected, actual),
+    }
+}
+
+pub(crate) fn parse_block_state(reader: &mut StringReader) -> Result<BlockStateArg, ParseError> {
+    let start = reader.pos();
+
+    if reader.peek() == Some('#') {
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_BLOCK_TAG_DISALLOWED,
+            [],
+        )));
+    }
+
+    let id = read_ident(reader)?;
+
+    let Some(kind) = block_kind(&id) else {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_BLOCK_ID_INVALID,
+            [id.as_str().to_owned().into()],
+        )))
+    };
+
+    let mut state = BlockState::from_kind(kind);
+
+    for (name, value) in parse_properties(reader, id.as_str(), Some(kind))? {
+        // The properties were validated while parsing.
+        state = state.set(
+            PropName::from_str(&name).unwrap(),
+            PropValue::from_str(&value).unwrap(),
+        );
+    }
+
+    Ok(BlockStateArg {
+        state,
+        nbt: parse_nbt(reader)?,
+    })
+}
+
+pub(crate) fn parse_block_predicate(
+    reader: &mut StringReader,
+) -> Result<BlockPredicate, ParseError> {
+    let start = reader.pos();
+    let is_tag = reader.peek() == Some('#');
+
+    if is_tag {
+        reader.read_char();
+    }
+
+    let id = read_ident(reader)?;
+
+    let block = if is_tag {
+        BlockOrTag::Tag(id)
+    } else {
+        match block_kind(&id) {
+            Some(kind) => BlockOrTag::Block(kind),
+            None => {
+                reader.set_pos(start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_BLOCK_ID_INVALID,
+                    [id.as_str().to_owned().into()],
+                )));
+            }
+        }
+    };
+
+    let kind = match &block {
+        BlockOrTag::Block(kind) => Some(*kind),
+        BlockOrTag::Tag(_) => None,
+    };
+
+    let display_id = reader.input()[start..reader.pos()].to_owned();
+
+    Ok(BlockPredicate {
+        properties: parse_properties(reader, &display_id, kind)?,
+        block,
+        nbt: parse_nbt(reader)?,
+    })
+}
+
+pub(crate) fn parse_item_stack(reader: &mut StringReader) -> Result<ItemStackArg, ParseError> {
+    let start = reader.pos();
+
+    if reader.peek() == Some('#') {
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_ITEM_TAG_DISALLOWED,
+            [],
+        )));
+    }
+
+    let id = read_ident(reader)?;
+
+    let Some(item) = item_kind(&id) else {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_ITEM_ID_INVALID,
+            [id.as_str().to_owned().into()],
+        )))
+    };
+
+    Ok(ItemStackArg {
+        item,
+        nbt: parse_nbt(reader)?,
+    })
+}
+
+pub(crate) fn parse_item_predicate(reader: &mut StringReader) -> Result<ItemPredicate, ParseError> {
+    let start = reader.pos();
+    let is_tag = reader.peek() == Some('#');
+
+    if is_tag {
+        reader.read_char();
+    }
+
+    let id = read_ident(reader)?;
+
+    let item = if is_tag {
+        ItemOrTag::Tag(id)
+    } else {
+        match item_kind(&id) {
+            Some(item) => ItemOrTag::Item(item),
+            None => {
+                reader.set_pos(start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_ITEM_ID_INVALID,
+                    [id.as_str().to_owned().into()],
+                )));
+            }
+        }
+    };
+
+    Ok(ItemPredicate {
+        item,
+        nbt: parse_nbt(reader)?,
+    })
+}
+
+fn block_kind(id: &Ident<String>) -> Option<BlockKind> {
+    (id.namespace() == "minecraft")
+        .then(|| BlockKind::from_str(id.path()))
+        .flatten()
+}
+
+fn item_kind(id: &Ident<String>) -> Option<ItemKind> {
+    (id.namespace() == "minecraft")
+        .then(|| ItemKind::from_str(id.path()))
+        .flatten()
+}
+
+fn read_ident(reader: &mut StringReader) -> Result<Ident<String>, ParseError> {
+    let start = reader.pos();
+    let id = reader.read_resource_location();
+
+    match Ident::new(id) {
+        Ok(id) => Ok(id.to_string_ident()),
+        _ => {
+            reader.set_pos(start);
+            Err(reader.error(Text::translate(translation_key::ARGUMENT_ID_INVALID, [])))
+        }
+    }
+}
+
+/// Reads the properties of a block in `[]`, if there are any. The properties
+/// are validated if the block kind is known.
+fn parse_properties(
+    reader: &mut StringReader,
+    id: &str,
+    kind: Option<BlockKind>,
+) -> Result<Vec<(String, String)>, ParseError> {
+    let mut properties: Vec<(String, String)> = vec![];
+
+    if reader.peek() != Some('[') {
+        return Ok(properties);
+    }
+
+    reader.read_char();
+    reader.skip_whitespace();
+
+    while reader.can_read() && reader.peek() != Some(']') {
+        let name_start = reader.pos();
+        let name = reader.read_unquoted_string().to_owned();
+
+        if properties.iter().any(|(n, _)| *n == name) {
+            reader.set_pos(name_start);
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_BLOCK_PROPERTY_DUPLICATE,
+                [name.into(), id.to_owned().into()],
+            )));
+        }
+
+        let prop_name = PropName::from_str(&name);
+
+        if let Some(kind) = kind {
+            if prop_name.map_or(true, |n| BlockState::from_kind(kind).get(n).is_none()) {
+                reader.set_pos(name_start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_BLOCK_PROPERTY_UNKNOWN,
+                    [id.to_owned().into(), name.into()],
+                )));
+            }
+        }
+
+        reader.skip_whitespace();
+
+        if reader.peek() != Some('=') {
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_BLOCK_PROPERTY_NOVALUE,
+                [name.into(), id.to_owned().into()],
+            )));
+        }
+
+        reader.read_char();
+        reader.skip_whitespace();
+
+        let value_start = reader.pos();
+        let value = reader.read_unquoted_string().to_owned();
+
+        if let (Some(kind), Some(prop_name)) = (kind, prop_name) {
+            let valid = PropValue::from_str(&value).is_some_and(|v| {
+                BlockState::from_kind(kind).set(prop_name, v).get(prop_name) == Some(v)
+            });
+
+            if !valid {
+                reader.set_pos(value_start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_BLOCK_PROPERTY_INVALID,
+                    [id.to_owned().into(), value.into(), name.into()],
+                )));
+            }
+        }
+
+        properties.push((name, value));
+
+        reader.skip_whitespace();
+
+        match reader.peek() {
+            Some(',') => {
+                reader.read_char();
+                reader.skip_whitespace();
+            }
+            Some(']') => {}
+            _ => {
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_BLOCK_PROPERTY_UNCLOSED,
+                    [],
+                )))
+            }
+        }
+    }
+
+    if reader.read_char() != Some(']') {
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_BLOCK_PROPERTY_UNCLOSED,
+            [],
+        )));
+    }
+
+    Ok(properties)
+}
+
+/// Reads an SNBT compound, if there is one.
+fn parse_nbt(reader: &mut StringReader) -> Result<Option<Compound>, ParseError> {
+    if reader.peek() != Some('{') {
+        return Ok(None);
+    }
+
+    let mut snbt = SnbtReader::new(reader.remaining());
+
+    match snbt.parse_element() {
+        Ok(Value::Compound(compound)) => {
+            reader.set_pos(reader.pos() + snbt.bytes_read());
+            Ok(Some(compound))
+        }
+        Ok(_) => unreachable!("SNBT starting with `{{` must be a compound"),
+        Err(e) => Err(reader.error(e.to_string())),
+    }
+}
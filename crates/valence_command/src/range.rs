@@ -0,0 +1,116 @@
+//! Ranges of numbers like `1..5`, `..5`, and `3`.
+
+use std::str::FromStr;
+
+use valence_core::text::Text;
+use valence_core::translation_key;
+
+use crate::parse::{ParseError, StringReader};
+
+/// A range of integers with optional bounds, like `1..5` or `10..`. Both
+/// bounds are inclusive.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct IntRange {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+impl IntRange {
+    pub fn contains(&self, value: i32) -> bool {
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+    }
+}
+
+/// A range of numbers with optional bounds, like `..5.5` or `1..2`. Both
+/// bounds are inclusive.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct FloatRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl FloatRange {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+    }
+}
+
+pub(crate) fn parse_int_range(reader: &mut StringReader) -> Result<IntRange, ParseError> {
+    let (min, max) = parse_range(reader, translation_key::PARSING_INT_INVALID)?;
+    Ok(IntRange { min, max })
+}
+
+pub(crate) fn parse_float_range(reader: &mut StringReader) -> Result<FloatRange, ParseError> {
+    let (min, max) = parse_range(reader, translation_key::PARSING_DOUBLE_INVALID)?;
+    Ok(FloatRange { min, max })
+}
+
+/// Reads a single number or two optional bounds separated by `..`.
+fn parse_range<T>(
+    reader: &mut StringReader,
+    invalid: &'static str,
+) -> Result<(Option<T>, Option<T>), ParseError>
+where
+    T: FromStr + PartialOrd + Copy,
+{
+    let start = reader.pos();
+    let min = read_bound(reader, invalid)?;
+
+    let max = if reader.remaining().starts_with("..") {
+        reader.set_pos(reader.pos() + 2);
+        read_bound(reader, invalid)?
+    } else {
+        min
+    };
+
+    if min.is_none() && max.is_none() {
+        reader.set_pos(start);
+        return Err(reader.error(Text::translate(translation_key::ARGUMENT_RANGE_EMPTY, [])));
+    }
+
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            reader.set_pos(start);
+            return Err(reader.error(Text::translate(translation_key::ARGUMENT_RANGE_SWAPPED, [])));
+        }
+    }
+
+    Ok((min, max))
+}
+
+/// Reads a number up to the next `..`, or nothing if there is no number.
+fn read_bound<T: FromStr>(
+    reader: &mut StringReader,
+    invalid: &'static str,
+) -> Result<Option<T>, ParseError> {
+    let rest = reader.remaining();
+    let mut len = 0;
+
+    for (i, c) in rest.char_indices() {
+        if !(c.is_ascii_digit() || matches!(c, '-' | '.')) || rest[i..].starts_with("..") {
+            break;
+        }
+
+        len = i + 1;
+    }
+
+    let number = &rest[..len];
+
+    if number.is_empty() {
+        return Ok(None);
+    }
+
+    let Ok(value) = number.parse() else {
+        let key = if invalid == translation_key::PARSING_INT_INVALID && number.contains('.') {
+            translation_key::ARGUMENT_RANGE_INTS
+        } else {
+            invalid
+        };
+
+        return Err(reader.error(Text::translate(key, [number.to_owned().into()])))
+    };
+
+    reader.set_pos(reader.pos() + len);
+
+    Ok(Some(value))
+}
@@ -0,0 +1,512 @@
+//! Entity selectors like `@a[distance=..5]`, player names, and UUIDs.
+
+use uuid::Uuid;
+use valence_core::game_mode::GameMode;
+use valence_core::text::Text;
+use valence_core::translation_key;
+
+use crate::parse::{parse_number, ParseError, StringReader, DOUBLE_ERRORS, INT_ERRORS};
+use crate::range::{parse_float_range, parse_int_range, FloatRange, IntRange};
+
+/// The entities an entity argument refers to.
+#[derive(Clone, PartialEq, Debug)]
+pub enum EntitySelector {
+    /// The name of a player.
+    Name(String),
+    /// The UUID of an entity.
+    Uuid(Uuid),
+    /// A target selector like `@e[type=zombie]`.
+    Selector(Selector),
+}
+
+impl EntitySelector {
+    /// If this selects at most one entity.
+    pub fn is_single(&self) -> bool {
+        match self {
+            EntitySelector::Name(_) | EntitySelector::Uuid(_) => true,
+            EntitySelector::Selector(selector) => selector.max_results() == 1,
+        }
+    }
+
+    /// If this can only select players.
+    pub fn only_players(&self) -> bool {
+        match self {
+            EntitySelector::Name(_) => true,
+            EntitySelector::Uuid(_) => false,
+            EntitySelector::Selector(selector) => selector.only_players(),
+        }
+    }
+}
+
+/// The `@` variable of a target selector.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SelectorKind {
+    /// `@p`
+    NearestPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@r`
+    RandomPlayer,
+    /// `@e`
+    AllEntities,
+    /// `@s`
+    Executor,
+}
+
+/// How the entities of a selector are sorted before the limit is applied.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SelectorSort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+/// A value of a selector option that is inverted with `!`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Negatable<T> {
+    pub value: T,
+    pub negated: bool,
+}
+
+/// A target selector with its options.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Selector {
+    pub kind: SelectorKind,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub distance: Option<FloatRange>,
+    pub dx: Option<f64>,
+    pub dy: Option<f64>,
+    pub dz: Option<f64>,
+    pub x_rotation: Option<FloatRange>,
+    pub y_rotation: Option<FloatRange>,
+    pub level: Option<IntRange>,
+    pub limit: Option<usize>,
+    pub sort: Option<SelectorSort>,
+    pub game_modes: Vec<Negatable<GameMode>>,
+    pub names: Vec<Negatable<String>>,
+    /// Entity types like `minecraft:zombie`, or entity type tags starting
+    /// with `#`.
+    pub types: Vec<Negatable<String>>,
+    pub tags: Vec<Negatable<String>>,
+    pub teams: Vec<Negatable<String>>,
+    /// The options that are not interpreted, which are `scores`,
+    /// `advancements`, `nbt`, and `predicate`, along with their input.
+    pub other: Vec<(String, String)>,
+}
+
+impl Selector {
+    pub fn new(kind: SelectorKind) -> Self {
+        Self {
+            kind,
+            x: None,
+            y: None,
+            z: None,
+            distance: None,
+            dx: None,
+            dy: None,
+            dz: None,
+            x_rotation: None,
+            y_rotation: None,
+            level: None,
+            limit: None,
+            sort: None,
+            game_modes: vec![],
+            names: vec![],
+            types: vec![],
+            tags: vec![],
+            teams: vec![],
+            other: vec![],
+        }
+    }
+
+    /// The maximum number of entities this selects.
+    pub fn max_results(&self) -> usize {
+        self.limit.unwrap_or(match self.kind {
+            SelectorKind::NearestPlayer | SelectorKind::RandomPlayer | SelectorKind::Executor => 1,
+            SelectorKind::AllPlayers | SelectorKind::AllEntities => usize::MAX,
+        })
+    }
+
+    /// If this can only select players.
+    pub fn only_players(&self) -> bool {
+        match self.kind {
+            SelectorKind::NearestPlayer | SelectorKind::AllPlayers | SelectorKind::RandomPlayer => {
+                true
+            }
+            SelectorKind::AllEntities | SelectorKind::Executor => self.types.iter().any(|ty| {
+                !ty.negated && matches!(ty.value.as_str(), "player" | "minecraft:player")
+            }),
+        }
+    }
+
+    /// The sort used when none is given.
+    pub fn default_sort(&self) -> SelectorSort {
+        match self.kind {
+            SelectorKind::NearestPlayer => SelectorSort::Nearest,
+            SelectorKind::RandomPlayer => SelectorSort::Random,
+            _ => SelectorSort::Arbitrary,
+        }
+    }
+}
+
+pub(crate) fn parse_selector(reader: &mut StringReader) -> Result<EntitySelector, ParseError> {
+    let start = reader.pos();
+
+    if reader.peek() != Some('@') {
+        let name = reader.read_word();
+
+        if let Ok(uuid) = Uuid::parse_str(name) {
+            return Ok(EntitySelector::Uuid(uuid));
+        }
+
+        if name.is_empty() || name.len() > 16 {
+            reader.set_pos(start);
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_ENTITY_INVALID,
+                [],
+            )));
+        }
+
+        return Ok(EntitySelector::Name(name.to_owned()));
+    }
+
+    reader.read_char();
+
+    let kind = match reader.read_char() {
+        Some('p') => SelectorKind::NearestPlayer,
+        Some('a') => SelectorKind::AllPlayers,
+        Some('r') => SelectorKind::RandomPlayer,
+        Some('e') => SelectorKind::AllEntities,
+        Some('s') => SelectorKind::Executor,
+        Some(c) => {
+            reader.set_pos(start);
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_ENTITY_SELECTOR_UNKNOWN,
+                [format!("@{c}").into()],
+            )));
+        }
+        None => {
+            reader.set_pos(start);
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_ENTITY_SELECTOR_MISSING,
+                [],
+            )));
+        }
+    };
+
+    let mut selector = Selector::new(kind);
+
+    if reader.peek() == Some('[') {
+        reader.read_char();
+        parse_options(&mut selector, reader)?;
+    }
+
+    Ok(EntitySelector::Selector(selector))
+}
+
+/// Reads the options of a selector after the `[`.
+fn parse_options(selector: &mut Selector, reader: &mut StringReader) -> Result<(), ParseError> {
+    reader.skip_whitespace();
+
+    while reader.can_read() && reader.peek() != Some(']') {
+        let key_start = reader.pos();
+        let key = reader.read_unquoted_string();
+
+        reader.skip_whitespace();
+
+        if reader.peek() != Some('=') {
+            reader.set_pos(key_start);
+            return Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_ENTITY_OPTIONS_VALUELESS,
+                [key.to_owned().into()],
+            )));
+        }
+
+        reader.read_char();
+        reader.skip_whitespace();
+
+        let inapplicable = |reader: &mut StringReader| -> Result<(), ParseError> {
+            reader.set_pos(key_start);
+            Err(reader.error(Text::translate(
+                translation_key::ARGUMENT_ENTITY_OPTIONS_INAPPLICABLE,
+                [key.to_owned().into()],
+            )))
+        };
+
+        match key {
+            "x" | "y" | "z" | "dx" | "dy" | "dz" => {
+                let value = parse_number(reader, None, None, DOUBLE_ERRORS)?;
+
+                let field = match key {
+                    "x" => &mut selector.x,
+                    "y" => &mut selector.y,
+                    "z" => &mut selector.z,
+                    "dx" => &mut selector.dx,
+                    "dy" => &mut selector.dy,
+                    _ => &mut selector.dz,
+                };
+
+                if field.is_some() {
+                    return inapplicable(reader);
+                }
+
+                *field = Some(value);
+            }
+            "distance" => {
+                let value_start = reader.pos();
+                let range = parse_float_range(reader)?;
+
+                if range.min.is_some_and(|min| min < 0.0) || range.max.is_some_and(|max| max < 0.0)
+                {
+                    reader.set_pos(value_start);
+                    return Err(reader.error(Text::translate(
+                        translation_key::ARGUMENT_ENTITY_OPTIONS_DISTANCE_NEGATIVE,
+                        [],
+                    )));
+                }
+
+                if selector.distance.is_some() {
+                    return inapplicable(reader);
+                }
+
+                selector.distance = Some(range);
+            }
+            "x_rotation" | "y_rotation" => {
+                let range = parse_float_range(reader)?;
+
+                let field = if key == "x_rotation" {
+                    &mut selector.x_rotation
+                } else {
+                    &mut selector.y_rotation
+                };
+
+                if field.is_some() {
+                    return inapplicable(reader);
+                }
+
+                *field = Some(range);
+            }
+            "level" => {
+                let value_start = reader.pos();
+                let range = parse_int_range(reader)?;
+
+                if range.min.is_some_and(|min| min < 0) || range.max.is_some_and(|max| max < 0) {
+                    reader.set_pos(value_start);
+                    return Err(reader.error(Text::translate(
+                        translation_key::ARGUMENT_ENTITY_OPTIONS_LEVEL_NEGATIVE,
+                        [],
+                    )));
+                }
+
+                if selector.level.is_some() {
+                    return inapplicable(reader);
+                }
+
+                selector.level = Some(range);
+            }
+            "limit" => {
+                if selector.kind == SelectorKind::Executor || selector.limit.is_some() {
+                    return inapplicable(reader);
+                }
+
+                let value_start = reader.pos();
+                let limit: i32 = parse_number(reader, None, None, INT_ERRORS)?;
+
+                if limit < 1 {
+                    reader.set_pos(value_start);
+                    return Err(reader.error(Text::translate(
+                        translation_key::ARGUMENT_ENTITY_OPTIONS_LIMIT_TOOSMALL,
+                        [],
+                    )));
+                }
+
+                selector.limit = Some(limit as usize);
+            }
+            "sort" => {
+                if selector.kind == SelectorKind::Executor || selector.sort.is_some() {
+                    return inapplicable(reader);
+                }
+
+                let value_start = reader.pos();
+
+                selector.sort = Some(match reader.read_unquoted_string() {
+                    "nearest" => SelectorSort::Nearest,
+                    "furthest" => SelectorSort::Furthest,
+                    "random" => SelectorSort::Random,
+                    "arbitrary" => SelectorSort::Arbitrary,
+                    other => {
+                        let other = other.to_owned();
+                        reader.set_pos(value_start);
+                        return Err(reader.error(Text::translate(
+                            translation_key::ARGUMENT_ENTITY_OPTIONS_SORT_IRREVERSIBLE,
+                            [other.into()],
+                        )));
+                    }
+                });
+            }
+            "gamemode" => {
+                let negated = read_negation(reader);
+                let value_start = reader.pos();
+
+                let value = match reader.read_unquoted_string() {
+                    "survival" => GameMode::Survival,
+                    "creative" => GameMode::Creative,
+                    "adventure" => GameMode::Adventure,
+                    "spectator" => GameMode::Spectator,
+                    other => {
+                        let other = other.to_owned();
+                        reader.set_pos(value_start);
+                        return Err(reader.error(Text::translate(
+                            translation_key::ARGUMENT_ENTITY_OPTIONS_MODE_INVALID,
+                            [other.into()],
+                        )));
+                    }
+                };
+
+                if !negated && selector.game_modes.iter().any(|m| !m.negated) {
+                    return inapplicable(reader);
+                }
+
+                selector.game_modes.push(Negatable { value, negated });
+            }
+            "name" => {
+                let negated = read_negation(reader);
+                let value = reader.read_string()?;
+
+                if !negated && selector.names.iter().any(|n| !n.negated) {
+                    return inapplicable(reader);
+                }
+
+                selector.names.push(Negatable { value, negated });
+            }
+            "type" => {
+                let negated = read_negation(reader);
+                let value_start = reader.pos();
+                let value = reader.read_resource_location();
+
+                if value.is_empty() || value == "#" {
+                    reader.set_pos(value_start);
+                    return Err(reader.error(Text::translate(
+                        translation_key::ARGUMENT_ENTITY_OPTIONS_TYPE_INVALID,
+                        [value.to_owned().into()],
+                    )));
+                }
+
+                // Selectors of players and selectors with a type can't select other types.
+                let type_limited = matches!(
+                    selector.kind,
+                    SelectorKind::NearestPlayer
+                        | SelectorKind::AllPlayers
+                        | SelectorKind::RandomPlayer
+                ) || selector.types.iter().any(|t| !t.negated);
+
+                if type_limited {
+                    return inapplicable(reader);
+                }
+
+                selector.types.push(Negatable {
+                    value: value.to_owned(),
+                    negated,
+                });
+            }
+            "tag" | "team" => {
+                let negated = read_negation(reader);
+                let value = reader.read_unquoted_string().to_owned();
+
+                let list = if key == "tag" {
+                    &mut selector.tags
+                } else {
+                    &mut selector.teams
+                };
+
+                if key == "team" && !negated && list.iter().any(|t| !t.negated) {
+                    return inapplicable(reader);
+                }
+
+                list.push(Negatable { value, negated });
+            }
+            "scores" | "advancements" | "nbt" | "predicate" => {
+                let value = read_raw_value(reader)?;
+                selector.other.push((key.to_owned(), value.to_owned()));
+            }
+            _ => {
+                reader.set_pos(key_start);
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_ENTITY_OPTIONS_UNKNOWN,
+                    [key.to_owned().into()],
+                )));
+            }
+        }
+
+        reader.skip_whitespace();
+
+        match reader.peek() {
+            Some(',') => {
+                reader.read_char();
+                reader.skip_whitespace();
+            }
+            Some(']') => {}
+            _ => {
+                return Err(reader.error(Text::translate(
+                    translation_key::ARGUMENT_ENTITY_OPTIONS_UNTERMINATED,
+                    [],
+                )))
+            }
+        }
+    }
+
+    if reader.read_char() != Some(']') {
+        return Err(reader.error(Text::translate(
+            translation_key::ARGUMENT_ENTITY_OPTIONS_UNTERMINATED,
+            [],
+        )));
+    }
+
+    Ok(())
+}
+
+fn read_negation(reader: &mut StringReader) -> bool {
+    let negated = reader.peek() == Some('!');
+
+    if negated {
+        reader.read_char();
+        reader.skip_whitespace();
+    }
+
+    negated
+}
+
+/// Reads an option value up to the next `,` or `]` that isn't nested in
+/// brackets or quotes.
+fn read_raw_value<'a>(reader: &mut StringReader<'a>) -> Result<&'a str, ParseError> {
+    let rest = reader.remaining();
+    let mut depth = 0_usize;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{' | '[') => depth += 1,
+            (None, '}' | ']') if depth > 0 => depth -= 1,
+            (None, ',' | ']') if depth == 0 => {
+                reader.set_pos(reader.pos() + i);
+                return Ok(&rest[..i]);
+            }
+            _ => {}
+        }
+    }
+
+    reader.set_pos(reader.pos() + rest.len());
+    Err(reader.error(Text::translate(
+        translation_key::ARGUMENT_ENTITY_OPTIONS_UNTERMINATED,
+        [],
+    )))
+}
@@ -0,0 +1,336 @@
+//! Suggestions for the command a client is typing.
+//!
+//! Clients ask the server for suggestions for arguments that were given a
+//! provider with [`CommandNode::suggests_with`]. The suggestions are found by
+//! reading the input with the command tree, like when a command is run.
+//! Providers return a future, so they can take their time, like when looking
+//! up names in a database. The response is sent to the client once all
+//! providers are done.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use bevy_tasks::AsyncComputeTaskPool;
+use flume::{Receiver, Sender};
+use valence_client::event_loop::PacketEvent;
+use valence_client::Client;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::packet::chat::{
+    CommandSuggestionsMatch, CommandSuggestionsS2c, RequestCommandCompletionsC2s,
+};
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_permission::{PermissionManager, Permissions};
+
+use crate::packet::Parser;
+use crate::parse::{parse_arg, ArgValue, CommandArgs, StringReader};
+use crate::{CommandNode, CommandRegistry, NodeKind};
+
+pub(crate) type SuggestionFuture = Pin<Box<dyn Future<Output = Vec<CommandSuggestion>> + Send>>;
+
+pub(crate) type SuggestionProvider =
+    Arc<dyn Fn(SuggestionContext) -> SuggestionFuture + Send + Sync>;
+
+/// A value the client can complete an argument with.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommandSuggestion {
+    pub text: String,
+    /// Shown when hovering over the suggestion.
+    pub tooltip: Option<Text>,
+}
+
+impl CommandSuggestion {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tooltip: None,
+        }
+    }
+
+    pub fn with_tooltip(mut self, tooltip: impl Into<Text>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+}
+
+impl From<&str> for CommandSuggestion {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for CommandSuggestion {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// The argument a suggestion provider is asked to complete.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SuggestionContext {
+    /// The client that is typing the command.
+    pub client: Entity,
+    /// The whole input of the command, without the leading `/`.
+    pub input: String,
+    /// The arguments before the one being completed.
+    pub args: CommandArgs,
+    /// The part of the argument that was typed so far. Suggestions that don't
+    /// start with it are left out of the response.
+    pub partial: String,
+}
+
+/// Sends the suggestions of async providers from the task pool to the main
+/// thread.
+#[derive(Resource)]
+pub(crate) struct SuggestionChannel {
+    sender: Sender<FinishedSuggestions>,
+    receiver: Receiver<FinishedSuggestions>,
+}
+
+impl Default for SuggestionChannel {
+    fn default() -> Self {
+        let (sender, receiver) = flume::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+struct FinishedSuggestions {
+    client: Entity,
+    transaction_id: i32,
+    input: String,
+    offset: usize,
+    found: Vec<(usize, CommandSuggestion)>,
+}
+
+/// The suggestions for an input, by the position in the input they start at.
+#[derive(Default)]
+struct Completion {
+    found: Vec<(usize, CommandSuggestion)>,
+    pending: Vec<(usize, String, SuggestionFuture)>,
+}
+
+impl Completion {
+    fn add(&mut self, start: usize, partial: &str, suggestions: Vec<CommandSuggestion>) {
+        self.found.extend(
+            suggestions
+                .into_iter()
+                .filter(|s| starts_with_ignore_case(&s.text, partial))
+                .map(|s| (start, s)),
+        );
+    }
+}
+
+/// Finds the suggestions for the end of the input, skipping the nodes that
+/// don't pass `can_use`.
+fn complete(
+    registry: &CommandRegistry,
+    client: Entity,
+    input: &str,
+    can_use: &dyn Fn(&CommandNode) -> bool,
+) -> Completion {
+    let mut completion = Completion::default();
+    let mut reader = StringReader::new(input);
+    let name = reader.read_word();
+
+    if !reader.can_read() {
+        // The name of the command is still being typed.
+        let names = registry
+            .iter()
+            .filter(|c| can_use(c))
+            .map(|c| CommandSuggestion::new(c.name()))
+            .collect();
+        completion.add(0, name, names);
+    } else if let Some(command) = registry.get(name).filter(|c| can_use(c)) {
+        complete_after(
+            command,
+            &mut reader,
+            &mut vec![],
+            client,
+            can_use,
+            &mut completion,
+        );
+    }
+
+    completion
+}
+
+/// Finds the suggestions for the children of `node` after `node` was read.
+fn complete_after(
+    node: &CommandNode,
+    reader: &mut StringReader,
+    args: &mut Vec<(String, ArgValue)>,
+    client: Entity,
+    can_use: &dyn Fn(&CommandNode) -> bool,
+    completion: &mut Completion,
+) {
+    // Skip the space after the node.
+    reader.read_char();
+
+    let start = reader.pos();
+    let rest = reader.remaining();
+
+    for child in node.children.iter().filter(|child| can_use(child)) {
+        reader.set_pos(start);
+
+        match &child.kind {
+            NodeKind::Literal(name) => {
+                if !rest.contains(' ') {
+                    completion.add(start, rest, vec![CommandSuggestion::new(name)]);
+                } else if reader.read_word() == name.as_str() {
+                    complete_after(child, reader, args, client, can_use, completion);
+                }
+            }
+            NodeKind::Argument { name, parser, .. } => match parse_arg(parser, reader) {
+                Ok(value) if reader.peek() == Some(' ') => {
+                    args.push((name.clone(), value));
+                    complete_after(child, reader, args, client, can_use, completion);
+                    args.pop();
+                }
+                // The argument is still being typed.
+                _ => {
+                    if matches!(parser, Parser::Bool) {
+                        completion.add(start, rest, vec!["true".into(), "false".into()]);
+                    }
+
+                    if let Some(provider) = &child.provider {
+                        let future = provider(SuggestionContext {
+                            client,
+                            input: reader.input().to_owned(),
+                            args: CommandArgs(args.clone()),
+                            partial: rest.to_owned(),
+                        });
+
+                        completion.pending.push((start, rest.to_owned(), future));
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+pub(crate) fn handle_suggestion_requests(
+    mut packets: EventReader<PacketEvent>,
+    registry: Res<CommandRegistry>,
+    manager: Res<PermissionManager>,
+    channel: Res<SuggestionChannel>,
+    mut clients: Query<(&mut Client, Option<&Permissions>)>,
+) {
+    let no_permissions = Permissions::default();
+
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<RequestCommandCompletionsC2s>() else {
+            continue
+        };
+
+        let Ok((mut client, permissions)) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let permissions = permissions.unwrap_or(&no_permissions);
+
+        let input = pkt.text.strip_prefix('/').unwrap_or(pkt.text);
+        let offset = pkt.text.len() - input.len();
+        let mut completion = complete(&registry, packet.client, input, &|node| {
+            node.can_use(permissions, &manager)
+        });
+
+        if completion.pending.is_empty() {
+            write_suggestions(
+                &mut client,
+                pkt.transaction_id.0,
+                input,
+                offset,
+                completion.found,
+            );
+            continue;
+        }
+
+        let sender = channel.sender.clone();
+        let client = packet.client;
+        let transaction_id = pkt.transaction_id.0;
+        let input = input.to_owned();
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                for (start, partial, future) in std::mem::take(&mut completion.pending) {
+                    let suggestions = future.await;
+                    completion.add(start, &partial, suggestions);
+                }
+
+                let _ = sender.send(FinishedSuggestions {
+                    client,
+                    transaction_id,
+                    input,
+                    offset,
+                    found: completion.found,
+                });
+            })
+            .detach();
+    }
+}
+
+pub(crate) fn send_finished_suggestions(
+    channel: Res<SuggestionChannel>,
+    mut clients: Query<&mut Client>,
+) {
+    for finished in channel.receiver.try_iter() {
+        if let Ok(mut client) = clients.get_mut(finished.client) {
+            write_suggestions(
+                &mut client,
+                finished.transaction_id,
+                &finished.input,
+                finished.offset,
+                finished.found,
+            );
+        }
+    }
+}
+
+/// Writes the suggestions as one range that starts at the earliest suggestion
+/// and ends at the end of the input. `offset` is the length of the `/` before
+/// the input.
+fn write_suggestions(
+    client: &mut Client,
+    transaction_id: i32,
+    input: &str,
+    offset: usize,
+    found: Vec<(usize, CommandSuggestion)>,
+) {
+    let start = found
+        .iter()
+        .map(|(start, _)| *start)
+        .min()
+        .unwrap_or(input.len());
+
+    let mut suggestions: Vec<_> = found
+        .into_iter()
+        .map(|(s, suggestion)| CommandSuggestion {
+            text: format!("{}{}", &input[start..s], suggestion.text),
+            tooltip: suggestion.tooltip,
+        })
+        .collect();
+
+    suggestions.sort_by_key(|s| s.text.to_lowercase());
+    suggestions.dedup_by(|a, b| a.text == b.text);
+
+    // The client counts positions in UTF-16 code units.
+    client.write_packet(&CommandSuggestionsS2c {
+        id: VarInt(transaction_id),
+        start: VarInt((offset + input[..start].encode_utf16().count()) as i32),
+        length: VarInt(input[start..].encode_utf16().count() as i32),
+        matches: suggestions
+            .iter()
+            .map(|s| CommandSuggestionsMatch {
+                suggested_match: &s.text,
+                tooltip: s.tooltip.as_ref().map(Cow::Borrowed),
+            })
+            .collect(),
+    });
+}
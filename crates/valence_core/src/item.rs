@@ -6,6 +6,10 @@ use valence_nbt::Compound;
 use crate::protocol::var_int::VarInt;
 use crate::protocol::{Decode, Encode};
 
+mod data;
+
+pub use data::{AttributeModifier, AttributeOperation, Enchantment, EquipmentSlot, SkullProfile};
+
 include!(concat!(env!("OUT_DIR"), "/item.rs"));
 
 #[derive(Clone, PartialEq, Debug)]
@@ -0,0 +1,697 @@
+//! Typed access to the NBT data of item stacks.
+//!
+//! The NBT of an [`ItemStack`] remains the source of truth, so everything set
+//! here is sent to clients and read back from them like any other NBT. The
+//! raw NBT is still available through [`ItemStack::nbt`] for anything not
+//! covered here.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use uuid::Uuid;
+use valence_nbt::{compound, Compound, List, Value};
+
+use super::ItemStack;
+use crate::player_textures::PlayerTextures;
+use crate::property::Property;
+use crate::text::Text;
+
+/// An enchantment on an item stack.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Enchantment {
+    /// The ID of the enchantment, such as `minecraft:sharpness`.
+    pub id: String,
+    pub level: i16,
+}
+
+/// An attribute modifier that is applied to the entity holding or wearing an
+/// item stack.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AttributeModifier {
+    /// The name of the modified attribute, such as
+    /// `minecraft:generic.attack_damage`.
+    pub attribute: String,
+    pub name: String,
+    pub amount: f64,
+    pub operation: AttributeOperation,
+    pub uuid: Uuid,
+    /// The slot the item must be in for the modifier to apply. The modifier
+    /// applies in any slot if this is `None`.
+    pub slot: Option<EquipmentSlot>,
+}
+
+/// How an attribute modifier is applied to the base value of an attribute.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AttributeOperation {
+    Add,
+    MultiplyBase,
+    MultiplyTotal,
+}
+
+impl AttributeOperation {
+    pub fn to_raw(self) -> u8 {
+        match self {
+            Self::Add => 0,
+            Self::MultiplyBase => 1,
+            Self::MultiplyTotal => 2,
+        }
+    }
+
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Add),
+            1 => Some(Self::MultiplyBase),
+            2 => Some(Self::MultiplyTotal),
+            _ => None,
+        }
+    }
+}
+
+/// A slot an entity can hold or wear an item in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EquipmentSlot {
+    MainHand,
+    OffHand,
+    Feet,
+    Legs,
+    Chest,
+    Head,
+}
+
+impl EquipmentSlot {
+    /// Returns the name of the slot used in NBT, such as `"mainhand"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MainHand => "mainhand",
+            Self::OffHand => "offhand",
+            Self::Feet => "feet",
+            Self::Legs => "legs",
+            Self::Chest => "chest",
+            Self::Head => "head",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mainhand" => Some(Self::MainHand),
+            "offhand" => Some(Self::OffHand),
+            "feet" => Some(Self::Feet),
+            "legs" => Some(Self::Legs),
+            "chest" => Some(Self::Chest),
+            "head" => Some(Self::Head),
+            _ => None,
+        }
+    }
+}
+
+/// The game profile of a player head, which decides the skin shown on it.
+///
+/// This is stored in the `SkullOwner` tag of player head items and skull
+/// block entities.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SkullProfile {
+    pub uuid: Option<Uuid>,
+    pub name: Option<String>,
+    /// The base64 encoded `textures` property of the profile.
+    pub textures: Option<Property>,
+}
+
+impl SkullProfile {
+    /// Creates a profile that shows the given skin. Unlike the textures of a
+    /// player, the textures of a skull don't have to be signed.
+    pub fn from_textures(textures: &PlayerTextures) -> Self {
+        let value = textures.to_property_value();
+
+        // The client caches skins by profile ID, so heads with different skins need
+        // different IDs.
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let most = hasher.finish();
+        value.len().hash(&mut hasher);
+        let least = hasher.finish();
+
+        Self {
+            uuid: Some(Uuid::from_u64_pair(most, least)),
+            name: None,
+            textures: Some(Property {
+                name: "textures".into(),
+                value,
+                signature: None,
+            }),
+        }
+    }
+
+    /// Returns the skin and cape URLs in the textures of this profile.
+    pub fn player_textures(&self) -> Option<PlayerTextures> {
+        PlayerTextures::from_properties(self.textures.as_slice()).ok()
+    }
+
+    /// Reads a profile from a `SkullOwner` compound. Missing or malformed
+    /// entries are left empty.
+    pub fn from_compound(owner: &Compound) -> Self {
+        let mut profile = Self::default();
+
+        if let Some(Value::IntArray(id)) = owner.get("Id") {
+            if let [a, b, c, d] = id[..] {
+                let most = (a as u32 as u64) << 32 | b as u32 as u64;
+                let least = (c as u32 as u64) << 32 | d as u32 as u64;
+
+                profile.uuid = Some(Uuid::from_u64_pair(most, least));
+            }
+        }
+
+        if let Some(Value::String(name)) = owner.get("Name") {
+            profile.name = Some(name.clone());
+        }
+
+        if let Some(Value::Compound(props)) = owner.get("Properties") {
+            if let Some(Value::List(List::Compound(textures))) = props.get("textures") {
+                if let Some(Value::String(value)) = textures.first().and_then(|t| t.get("Value")) {
+                    let signature = match textures[0].get("Signature") {
+                        Some(Value::String(sig)) => Some(sig.clone()),
+                        _ => None,
+                    };
+
+                    profile.textures = Some(Property {
+                        name: "textures".into(),
+                        value: value.clone(),
+                        signature,
+                    });
+                }
+            }
+        }
+
+        profile
+    }
+
+    /// Writes this profile as a `SkullOwner` compound.
+    pub fn to_compound(&self) -> Compound {
+        let mut owner = Compound::new();
+
+        if let Some(uuid) = self.uuid {
+            owner.insert("Id", uuid);
+        }
+
+        if let Some(name) = &self.name {
+            owner.insert("Name", name.clone());
+        }
+
+        if let Some(textures) = &self.textures {
+            let mut entry = compound! {
+                "Value" => textures.value.clone(),
+            };
+
+            if let Some(sig) = &textures.signature {
+                entry.insert("Signature", sig.clone());
+            }
+
+            owner.insert(
+                "Properties",
+                compound! {
+                    "textures" => List::Compound(vec![entry]),
+                },
+            );
+        }
+
+        owner
+    }
+}
+
+fn text_to_json(text: Text) -> String {
+    serde_json::to_string(&text)
+        .unwrap_or_else(|err| panic!("failed to jsonify text {text:?}\n{err}"))
+}
+
+fn json_to_text(json: &str) -> Option<Text> {
+    serde_json::from_str(json).ok()
+}
+
+impl ItemStack {
+    /// Returns the NBT of this stack for modification, creating an empty
+    /// compound if the stack has none.
+    pub fn nbt_mut(&mut self) -> &mut Compound {
+        self.nbt.get_or_insert_with(Compound::new)
+    }
+
+    /// Removes a top level tag. The NBT is removed entirely once it is empty,
+    /// so that stacks without data compare equal to stacks that never had any.
+    fn remove_tag(&mut self, key: &str) -> Option<Value> {
+        let nbt = self.nbt.as_mut()?;
+        let removed = nbt.remove(key);
+
+        if nbt.is_empty() {
+            self.nbt = None;
+        }
+
+        removed
+    }
+
+    fn display(&self) -> Option<&Compound> {
+        match self.nbt.as_ref()?.get("display")? {
+            Value::Compound(display) => Some(display),
+            _ => None,
+        }
+    }
+
+    fn display_mut(&mut self) -> &mut Compound {
+        let nbt = self.nbt_mut();
+
+        if !matches!(nbt.get("display"), Some(Value::Compound(_))) {
+            nbt.insert("display", Compound::new());
+        }
+
+        match nbt.get_mut("display") {
+            Some(Value::Compound(display)) => display,
+            _ => unreachable!(),
+        }
+    }
+
+    fn remove_display_tag(&mut self, key: &str) {
+        let Some(Some(Value::Compound(display))) =
+            self.nbt.as_mut().map(|nbt| nbt.get_mut("display")) else {
+            return
+        };
+
+        display.remove(key);
+
+        if display.is_empty() {
+            self.remove_tag("display");
+        }
+    }
+
+    /// Returns the custom name of this stack, if it has one.
+    pub fn display_name(&self) -> Option<Text> {
+        match self.display()?.get("Name")? {
+            Value::String(json) => json_to_text(json),
+            _ => None,
+        }
+    }
+
+    /// Gives the stack a custom name. Note that clients show custom names in
+    /// italics unless the text says otherwise.
+    pub fn set_display_name(&mut self, name: impl Into<Text>) {
+        let json = text_to_json(name.into());
+        self.display_mut().insert("Name", json);
+    }
+
+    #[must_use]
+    pub fn with_display_name(mut self, name: impl Into<Text>) -> Self {
+        self.set_display_name(name);
+        self
+    }
+
+    pub fn remove_display_name(&mut self) {
+        self.remove_display_tag("Name");
+    }
+
+    /// Returns the lines of text shown below the name of this stack.
+    pub fn lore(&self) -> Vec<Text> {
+        match self.display().and_then(|display| display.get("Lore")) {
+            Some(Value::List(List::String(lines))) => {
+                lines.iter().filter_map(|line| json_to_text(line)).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Sets the lines of text shown below the name of this stack. Setting no
+    /// lines removes the lore.
+    pub fn set_lore(&mut self, lore: impl IntoIterator<Item = impl Into<Text>>) {
+        let lines: Vec<_> = lore
+            .into_iter()
+            .map(|line| text_to_json(line.into()))
+            .collect();
+
+        if lines.is_empty() {
+            self.remove_display_tag("Lore");
+        } else {
+            self.display_mut().insert("Lore", List::String(lines));
+        }
+    }
+
+    #[must_use]
+    pub fn with_lore(mut self, lore: impl IntoIterator<Item = impl Into<Text>>) -> Self {
+        self.set_lore(lore);
+        self
+    }
+
+    /// Returns the enchantments on this stack.
+    pub fn enchantments(&self) -> Vec<Enchantment> {
+        let Some(Value::List(List::Compound(enchantments))) =
+            self.nbt.as_ref().and_then(|nbt| nbt.get("Enchantments")) else {
+            return vec![]
+        };
+
+        enchantments
+            .iter()
+            .filter_map(|ench| {
+                let Some(Value::String(id)) = ench.get("id") else {
+                    return None
+                };
+
+                let level = match ench.get("lvl")? {
+                    Value::Short(lvl) => *lvl,
+                    Value::Int(lvl) => *lvl as i16,
+                    _ => return None,
+                };
+
+                Some(Enchantment {
+                    id: id.clone(),
+                    level,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the level of the enchantment `id` on this stack, such as
+    /// `minecraft:sharpness`, or zero if it isn't enchanted with it.
+    pub fn enchantment_level(&self, id: &str) -> i16 {
+        self.enchantments()
+            .into_iter()
+            .find(|ench| ench.id == id)
+            .map_or(0, |ench| ench.level)
+    }
+
+    /// Sets the level of the enchantment `id` on this stack, such as
+    /// `minecraft:sharpness`. A level of zero or less removes the
+    /// enchantment.
+    pub fn set_enchantment(&mut self, id: &str, level: i16) {
+        let mut enchantments = self.enchantments();
+        enchantments.retain(|ench| ench.id != id);
+
+        if level > 0 {
+            enchantments.push(Enchantment {
+                id: id.into(),
+                level,
+            });
+        }
+
+        self.set_enchantments(enchantments);
+    }
+
+    #[must_use]
+    pub fn with_enchantment(mut self, id: &str, level: i16) -> Self {
+        self.set_enchantment(id, level);
+        self
+    }
+
+    /// Replaces all enchantments on this stack.
+    pub fn set_enchantments(&mut self, enchantments: impl IntoIterator<Item = Enchantment>) {
+        let list: Vec<_> = enchantments
+            .into_iter()
+            .map(|ench| {
+                compound! {
+                    "id" => ench.id,
+                    "lvl" => ench.level,
+                }
+            })
+            .collect();
+
+        if list.is_empty() {
+            self.remove_tag("Enchantments");
+        } else {
+            self.nbt_mut().insert("Enchantments", List::Compound(list));
+        }
+    }
+
+    /// Returns the damage taken by this stack, which is zero for items that
+    /// can't be damaged.
+    pub fn damage(&self) -> i32 {
+        match self.nbt.as_ref().and_then(|nbt| nbt.get("Damage")) {
+            Some(Value::Int(damage)) => *damage,
+            _ => 0,
+        }
+    }
+
+    /// Sets the damage taken by this stack. A damage of zero removes the tag.
+    pub fn set_damage(&mut self, damage: i32) {
+        if damage == 0 {
+            self.remove_tag("Damage");
+        } else {
+            self.nbt_mut().insert("Damage", damage);
+        }
+    }
+
+    #[must_use]
+    pub fn with_damage(mut self, damage: i32) -> Self {
+        self.set_damage(damage);
+        self
+    }
+
+    /// Returns if this stack never takes damage.
+    pub fn is_unbreakable(&self) -> bool {
+        matches!(
+            self.nbt.as_ref().and_then(|nbt| nbt.get("Unbreakable")),
+            Some(Value::Byte(1..))
+        )
+    }
+
+    pub fn set_unbreakable(&mut self, unbreakable: bool) {
+        if unbreakable {
+            self.nbt_mut().insert("Unbreakable", true);
+        } else {
+            self.remove_tag("Unbreakable");
+        }
+    }
+
+    #[must_use]
+    pub fn with_unbreakable(mut self, unbreakable: bool) -> Self {
+        self.set_unbreakable(unbreakable);
+        self
+    }
+
+    /// Returns the custom model data of this stack, which resource packs use
+    /// to pick a different model for the item.
+    pub fn custom_model_data(&self) -> Option<i32> {
+        match self.nbt.as_ref()?.get("CustomModelData")? {
+            Value::Int(data) => Some(*data),
+            _ => None,
+        }
+    }
+
+    pub fn set_custom_model_data(&mut self, data: impl Into<Option<i32>>) {
+        match data.into() {
+            Some(data) => {
+                self.nbt_mut().insert("CustomModelData", data);
+            }
+            None => {
+                self.remove_tag("CustomModelData");
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn with_custom_model_data(mut self, data: impl Into<Option<i32>>) -> Self {
+        self.set_custom_model_data(data);
+        self
+    }
+
+    /// Returns the attribute modifiers of this stack. Modifiers that can't be
+    /// read are skipped.
+    pub fn attribute_modifiers(&self) -> Vec<AttributeModifier> {
+        let Some(Value::List(List::Compound(modifiers))) = self
+            .nbt
+            .as_ref()
+            .and_then(|nbt| nbt.get("AttributeModifiers")) else {
+            return vec![]
+        };
+
+        modifiers
+            .iter()
+            .filter_map(|modifier| {
+                let Some(Value::String(attribute)) = modifier.get("AttributeName") else {
+                    return None
+                };
+
+                let name = match modifier.get("Name") {
+                    Some(Value::String(name)) => name.clone(),
+                    _ => String::new(),
+                };
+
+                let Some(Value::Double(amount)) = modifier.get("Amount") else {
+                    return None
+                };
+
+                let operation = match modifier.get("Operation") {
+                    Some(Value::Int(op)) => AttributeOperation::from_raw((*op).try_into().ok()?)?,
+                    _ => AttributeOperation::Add,
+                };
+
+                let Some(Value::IntArray(uuid)) = modifier.get("UUID") else {
+                    return None
+                };
+
+                let [a, b, c, d] = uuid[..] else { return None };
+
+                let most = (a as u32 as u64) << 32 | b as u32 as u64;
+                let least = (c as u32 as u64) << 32 | d as u32 as u64;
+
+                let slot = match modifier.get("Slot") {
+                    Some(Value::String(slot)) => EquipmentSlot::from_name(slot),
+                    _ => None,
+                };
+
+                Some(AttributeModifier {
+                    attribute: attribute.clone(),
+                    name,
+                    amount: *amount,
+                    operation,
+                    uuid: Uuid::from_u64_pair(most, least),
+                    slot,
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces all attribute modifiers of this stack. Note that items lose
+    /// their default modifiers, like the attack damage of swords, once they
+    /// have any modifiers.
+    pub fn set_attribute_modifiers(
+        &mut self,
+        modifiers: impl IntoIterator<Item = AttributeModifier>,
+    ) {
+        let list: Vec<_> = modifiers
+            .into_iter()
+            .map(|modifier| {
+                let mut nbt = compound! {
+                    "AttributeName" => modifier.attribute,
+                    "Name" => modifier.name,
+                    "Amount" => modifier.amount,
+                    "Operation" => modifier.operation.to_raw() as i32,
+                    "UUID" => modifier.uuid,
+                };
+
+                if let Some(slot) = modifier.slot {
+                    nbt.insert("Slot", slot.name());
+                }
+
+                nbt
+            })
+            .collect();
+
+        if list.is_empty() {
+            self.remove_tag("AttributeModifiers");
+        } else {
+            self.nbt_mut()
+                .insert("AttributeModifiers", List::Compound(list));
+        }
+    }
+
+    pub fn add_attribute_modifier(&mut self, modifier: AttributeModifier) {
+        let mut modifiers = self.attribute_modifiers();
+        modifiers.push(modifier);
+        self.set_attribute_modifiers(modifiers);
+    }
+
+    #[must_use]
+    pub fn with_attribute_modifier(mut self, modifier: AttributeModifier) -> Self {
+        self.add_attribute_modifier(modifier);
+        self
+    }
+
+    /// Returns the profile of this player head, which decides its skin.
+    pub fn skull_profile(&self) -> Option<SkullProfile> {
+        match self.nbt.as_ref()?.get("SkullOwner")? {
+            Value::Compound(owner) => Some(SkullProfile::from_compound(owner)),
+            _ => None,
+        }
+    }
+
+    pub fn set_skull_profile(&mut self, profile: impl Into<Option<SkullProfile>>) {
+        match profile.into() {
+            Some(profile) => {
+                self.nbt_mut().insert("SkullOwner", profile.to_compound());
+            }
+            None => {
+                self.remove_tag("SkullOwner");
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn with_skull_profile(mut self, profile: impl Into<Option<SkullProfile>>) -> Self {
+        self.set_skull_profile(profile);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemKind;
+    use crate::protocol::{Decode, Encode};
+    use crate::text::{Color, TextFormat};
+
+    fn round_trip(stack: &ItemStack) -> ItemStack {
+        let mut buf = vec![];
+        Some(stack).encode(&mut buf).unwrap();
+
+        let mut r = buf.as_slice();
+        Option::<ItemStack>::decode(&mut r).unwrap().unwrap()
+    }
+
+    #[test]
+    fn typed_data_round_trips() {
+        let modifier = AttributeModifier {
+            attribute: "minecraft:generic.attack_damage".into(),
+            name: "Sharp".into(),
+            amount: 7.5,
+            operation: AttributeOperation::MultiplyBase,
+            uuid: Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0),
+            slot: Some(EquipmentSlot::MainHand),
+        };
+
+        let stack = ItemStack::new(ItemKind::DiamondSword, 1, None)
+            .with_display_name("Excalibur".color(Color::GOLD))
+            .with_lore(["Forged in a lake", "Very sharp"])
+            .with_enchantment("minecraft:sharpness", 5)
+            .with_damage(12)
+            .with_unbreakable(true)
+            .with_custom_model_data(42)
+            .with_attribute_modifier(modifier.clone());
+
+        let stack = round_trip(&stack);
+
+        assert_eq!(stack.display_name(), Some("Excalibur".color(Color::GOLD)));
+        assert_eq!(
+            stack.lore(),
+            vec![Text::from("Forged in a lake"), Text::from("Very sharp")]
+        );
+        assert_eq!(stack.enchantment_level("minecraft:sharpness"), 5);
+        assert_eq!(stack.damage(), 12);
+        assert!(stack.is_unbreakable());
+        assert_eq!(stack.custom_model_data(), Some(42));
+        assert_eq!(stack.attribute_modifiers(), vec![modifier]);
+    }
+
+    #[test]
+    fn removing_data_removes_nbt() {
+        let mut stack = ItemStack::new(ItemKind::Stick, 1, None)
+            .with_display_name("Stick")
+            .with_lore(["A stick"])
+            .with_enchantment("minecraft:knockback", 2)
+            .with_damage(3);
+
+        stack.remove_display_name();
+        stack.set_lore(Vec::<Text>::new());
+        stack.set_enchantment("minecraft:knockback", 0);
+        stack.set_damage(0);
+
+        assert_eq!(stack, ItemStack::new(ItemKind::Stick, 1, None));
+    }
+
+    #[test]
+    fn skull_profile_from_textures() {
+        let textures = PlayerTextures {
+            skin: "http://textures.minecraft.net/texture/abc".parse().unwrap(),
+            cape: None,
+        };
+
+        let stack = ItemStack::new(ItemKind::PlayerHead, 1, None)
+            .with_skull_profile(SkullProfile::from_textures(&textures));
+
+        let profile = round_trip(&stack).skull_profile().unwrap();
+        assert!(profile.uuid.is_some());
+        assert_eq!(profile.player_textures(), Some(textures));
+    }
+}
@@ -5,6 +5,7 @@ use anyhow::bail;
 use glam::{DVec3, Vec3};
 
 use crate::block_pos::BlockPos;
+use crate::ident::Ident;
 use crate::item::ItemStack;
 use crate::protocol::var_int::VarInt;
 use crate::protocol::{packet_id, Decode, Encode, Packet};
@@ -47,9 +48,7 @@ pub enum Particle {
     Firework,
     Fishing,
     Flame,
-    DrippingCherryLeaves,
-    FallingCherryLeaves,
-    LandingCherryLeaves,
+    CherryLeaves,
     SculkSoul,
     SculkCharge {
         roll: f32,
@@ -128,6 +127,7 @@ pub enum Particle {
     Shriek {
         delay: i32,
     },
+    EggCrack,
 }
 
 impl Particle {
@@ -162,74 +162,73 @@ impl Particle {
             Particle::Firework => 26,
             Particle::Fishing => 27,
             Particle::Flame => 28,
-            Particle::DrippingCherryLeaves => 29,
-            Particle::FallingCherryLeaves => 30,
-            Particle::LandingCherryLeaves => 31,
-            Particle::SculkSoul => 32,
-            Particle::SculkCharge { .. } => 33,
-            Particle::SculkChargePop => 34,
-            Particle::SoulFireFlame => 35,
-            Particle::Soul => 36,
-            Particle::Flash => 37,
-            Particle::HappyVillager => 38,
-            Particle::Composter => 39,
-            Particle::Heart => 40,
-            Particle::InstantEffect => 41,
-            Particle::Item { .. } => 42,
-            Particle::VibrationBlock { .. } => 43,
-            Particle::VibrationEntity { .. } => 43,
-            Particle::ItemSlime => 44,
-            Particle::ItemSnowball => 45,
-            Particle::LargeSmoke => 46,
-            Particle::Lava => 47,
-            Particle::Mycelium => 48,
-            Particle::Note => 49,
-            Particle::Poof => 50,
-            Particle::Portal => 51,
-            Particle::Rain => 52,
-            Particle::Smoke => 53,
-            Particle::Sneeze => 54,
-            Particle::Spit => 55,
-            Particle::SquidInk => 56,
-            Particle::SweepAttack => 57,
-            Particle::TotemOfUndying => 58,
-            Particle::Underwater => 59,
-            Particle::Splash => 60,
-            Particle::Witch => 61,
-            Particle::BubblePop => 62,
-            Particle::CurrentDown => 63,
-            Particle::BubbleColumnUp => 64,
-            Particle::Nautilus => 65,
-            Particle::Dolphin => 66,
-            Particle::CampfireCosySmoke => 67,
-            Particle::CampfireSignalSmoke => 68,
-            Particle::DrippingHoney => 69,
-            Particle::FallingHoney => 70,
-            Particle::LandingHoney => 71,
-            Particle::FallingNectar => 72,
-            Particle::FallingSporeBlossom => 73,
-            Particle::Ash => 74,
-            Particle::CrimsonSpore => 75,
-            Particle::WarpedSpore => 76,
-            Particle::SporeBlossomAir => 77,
-            Particle::DrippingObsidianTear => 78,
-            Particle::FallingObsidianTear => 79,
-            Particle::LandingObsidianTear => 80,
-            Particle::ReversePortal => 81,
-            Particle::WhiteAsh => 82,
-            Particle::SmallFlame => 83,
-            Particle::Snowflake => 84,
-            Particle::DrippingDripstoneLava => 85,
-            Particle::FallingDripstoneLava => 86,
-            Particle::DrippingDripstoneWater => 87,
-            Particle::FallingDripstoneWater => 88,
-            Particle::GlowSquidInk => 89,
-            Particle::Glow => 90,
-            Particle::WaxOn => 91,
-            Particle::WaxOff => 92,
-            Particle::ElectricSpark => 93,
-            Particle::Scrape => 94,
-            Particle::Shriek { .. } => 95,
+            Particle::CherryLeaves => 29,
+            Particle::SculkSoul => 30,
+            Particle::SculkCharge { .. } => 31,
+            Particle::SculkChargePop => 32,
+            Particle::SoulFireFlame => 33,
+            Particle::Soul => 34,
+            Particle::Flash => 35,
+            Particle::HappyVillager => 36,
+            Particle::Composter => 37,
+            Particle::Heart => 38,
+            Particle::InstantEffect => 39,
+            Particle::Item { .. } => 40,
+            Particle::VibrationBlock { .. } => 41,
+            Particle::VibrationEntity { .. } => 41,
+            Particle::ItemSlime => 42,
+            Particle::ItemSnowball => 43,
+            Particle::LargeSmoke => 44,
+            Particle::Lava => 45,
+            Particle::Mycelium => 46,
+            Particle::Note => 47,
+            Particle::Poof => 48,
+            Particle::Portal => 49,
+            Particle::Rain => 50,
+            Particle::Smoke => 51,
+            Particle::Sneeze => 52,
+            Particle::Spit => 53,
+            Particle::SquidInk => 54,
+            Particle::SweepAttack => 55,
+            Particle::TotemOfUndying => 56,
+            Particle::Underwater => 57,
+            Particle::Splash => 58,
+            Particle::Witch => 59,
+            Particle::BubblePop => 60,
+            Particle::CurrentDown => 61,
+            Particle::BubbleColumnUp => 62,
+            Particle::Nautilus => 63,
+            Particle::Dolphin => 64,
+            Particle::CampfireCosySmoke => 65,
+            Particle::CampfireSignalSmoke => 66,
+            Particle::DrippingHoney => 67,
+            Particle::FallingHoney => 68,
+            Particle::LandingHoney => 69,
+            Particle::FallingNectar => 70,
+            Particle::FallingSporeBlossom => 71,
+            Particle::Ash => 72,
+            Particle::CrimsonSpore => 73,
+            Particle::WarpedSpore => 74,
+            Particle::SporeBlossomAir => 75,
+            Particle::DrippingObsidianTear => 76,
+            Particle::FallingObsidianTear => 77,
+            Particle::LandingObsidianTear => 78,
+            Particle::ReversePortal => 79,
+            Particle::WhiteAsh => 80,
+            Particle::SmallFlame => 81,
+            Particle::Snowflake => 82,
+            Particle::DrippingDripstoneLava => 83,
+            Particle::FallingDripstoneLava => 84,
+            Particle::DrippingDripstoneWater => 85,
+            Particle::FallingDripstoneWater => 86,
+            Particle::GlowSquidInk => 87,
+            Particle::Glow => 88,
+            Particle::WaxOn => 89,
+            Particle::WaxOff => 90,
+            Particle::ElectricSpark => 91,
+            Particle::Scrape => 92,
+            Particle::Shriek { .. } => 93,
+            Particle::EggCrack => 94,
         }
     }
 
@@ -272,88 +271,87 @@ impl Particle {
             26 => Particle::Firework,
             27 => Particle::Fishing,
             28 => Particle::Flame,
-            29 => Particle::DrippingCherryLeaves,
-            30 => Particle::FallingCherryLeaves,
-            31 => Particle::LandingCherryLeaves,
-            32 => Particle::SculkSoul,
-            33 => Particle::SculkCharge {
+            29 => Particle::CherryLeaves,
+            30 => Particle::SculkSoul,
+            31 => Particle::SculkCharge {
                 roll: f32::decode(r)?,
             },
-            34 => Particle::SculkChargePop,
-            35 => Particle::SoulFireFlame,
-            36 => Particle::Soul,
-            37 => Particle::Flash,
-            38 => Particle::HappyVillager,
-            39 => Particle::Composter,
-            40 => Particle::Heart,
-            41 => Particle::InstantEffect,
-            42 => Particle::Item(Decode::decode(r)?),
-            43 => match <&str>::decode(r)? {
-                "block" => Particle::VibrationBlock {
+            32 => Particle::SculkChargePop,
+            33 => Particle::SoulFireFlame,
+            34 => Particle::Soul,
+            35 => Particle::Flash,
+            36 => Particle::HappyVillager,
+            37 => Particle::Composter,
+            38 => Particle::Heart,
+            39 => Particle::InstantEffect,
+            40 => Particle::Item(Decode::decode(r)?),
+            41 => match Ident::<Cow<str>>::decode(r)?.as_str() {
+                "minecraft:block" => Particle::VibrationBlock {
                     block_pos: BlockPos::decode(r)?,
                     ticks: VarInt::decode(r)?.0,
                 },
-                "entity" => Particle::VibrationEntity {
+                "minecraft:entity" => Particle::VibrationEntity {
                     entity_id: VarInt::decode(r)?.0,
                     entity_eye_height: f32::decode(r)?,
                     ticks: VarInt::decode(r)?.0,
                 },
                 invalid => bail!("invalid vibration position source of \"{invalid}\""),
             },
-            44 => Particle::ItemSlime,
-            45 => Particle::ItemSnowball,
-            46 => Particle::LargeSmoke,
-            47 => Particle::Lava,
-            48 => Particle::Mycelium,
-            49 => Particle::Note,
-            50 => Particle::Poof,
-            51 => Particle::Portal,
-            52 => Particle::Rain,
-            53 => Particle::Smoke,
-            54 => Particle::Sneeze,
-            55 => Particle::Spit,
-            56 => Particle::SquidInk,
-            57 => Particle::SweepAttack,
-            58 => Particle::TotemOfUndying,
-            59 => Particle::Underwater,
-            60 => Particle::Splash,
-            61 => Particle::Witch,
-            62 => Particle::BubblePop,
-            63 => Particle::CurrentDown,
-            64 => Particle::BubbleColumnUp,
-            65 => Particle::Nautilus,
-            66 => Particle::Dolphin,
-            67 => Particle::CampfireCosySmoke,
-            68 => Particle::CampfireSignalSmoke,
-            69 => Particle::DrippingHoney,
-            70 => Particle::FallingHoney,
-            71 => Particle::LandingHoney,
-            72 => Particle::FallingNectar,
-            73 => Particle::FallingSporeBlossom,
-            74 => Particle::Ash,
-            75 => Particle::CrimsonSpore,
-            76 => Particle::WarpedSpore,
-            77 => Particle::SporeBlossomAir,
-            78 => Particle::DrippingObsidianTear,
-            79 => Particle::FallingObsidianTear,
-            80 => Particle::LandingObsidianTear,
-            81 => Particle::ReversePortal,
-            82 => Particle::WhiteAsh,
-            83 => Particle::SmallFlame,
-            84 => Particle::Snowflake,
-            85 => Particle::DrippingDripstoneLava,
-            86 => Particle::FallingDripstoneLava,
-            87 => Particle::DrippingDripstoneWater,
-            88 => Particle::FallingDripstoneWater,
-            89 => Particle::GlowSquidInk,
-            90 => Particle::Glow,
-            91 => Particle::WaxOn,
-            92 => Particle::WaxOff,
-            93 => Particle::ElectricSpark,
-            94 => Particle::Scrape,
-            95 => Particle::Shriek {
+            42 => Particle::ItemSlime,
+            43 => Particle::ItemSnowball,
+            44 => Particle::LargeSmoke,
+            45 => Particle::Lava,
+            46 => Particle::Mycelium,
+            47 => Particle::Note,
+            48 => Particle::Poof,
+            49 => Particle::Portal,
+            50 => Particle::Rain,
+            51 => Particle::Smoke,
+            52 => Particle::Sneeze,
+            53 => Particle::Spit,
+            54 => Particle::SquidInk,
+            55 => Particle::SweepAttack,
+            56 => Particle::TotemOfUndying,
+            57 => Particle::Underwater,
+            58 => Particle::Splash,
+            59 => Particle::Witch,
+            60 => Particle::BubblePop,
+            61 => Particle::CurrentDown,
+            62 => Particle::BubbleColumnUp,
+            63 => Particle::Nautilus,
+            64 => Particle::Dolphin,
+            65 => Particle::CampfireCosySmoke,
+            66 => Particle::CampfireSignalSmoke,
+            67 => Particle::DrippingHoney,
+            68 => Particle::FallingHoney,
+            69 => Particle::LandingHoney,
+            70 => Particle::FallingNectar,
+            71 => Particle::FallingSporeBlossom,
+            72 => Particle::Ash,
+            73 => Particle::CrimsonSpore,
+            74 => Particle::WarpedSpore,
+            75 => Particle::SporeBlossomAir,
+            76 => Particle::DrippingObsidianTear,
+            77 => Particle::FallingObsidianTear,
+            78 => Particle::LandingObsidianTear,
+            79 => Particle::ReversePortal,
+            80 => Particle::WhiteAsh,
+            81 => Particle::SmallFlame,
+            82 => Particle::Snowflake,
+            83 => Particle::DrippingDripstoneLava,
+            84 => Particle::FallingDripstoneLava,
+            85 => Particle::DrippingDripstoneWater,
+            86 => Particle::FallingDripstoneWater,
+            87 => Particle::GlowSquidInk,
+            88 => Particle::Glow,
+            89 => Particle::WaxOn,
+            90 => Particle::WaxOff,
+            91 => Particle::ElectricSpark,
+            92 => Particle::Scrape,
+            93 => Particle::Shriek {
                 delay: VarInt::decode(r)?.0,
             },
+            94 => Particle::EggCrack,
             id => bail!("invalid particle ID of {id}"),
         })
     }
@@ -426,7 +424,7 @@ impl Encode for Particle {
             Particle::SculkCharge { roll } => roll.encode(w),
             Particle::Item(stack) => stack.encode(w),
             Particle::VibrationBlock { block_pos, ticks } => {
-                "block".encode(&mut w)?;
+                "minecraft:block".encode(&mut w)?;
                 block_pos.encode(&mut w)?;
                 VarInt(*ticks).encode(w)
             }
@@ -435,7 +433,7 @@ impl Encode for Particle {
                 entity_eye_height,
                 ticks,
             } => {
-                "entity".encode(&mut w)?;
+                "minecraft:entity".encode(&mut w)?;
                 VarInt(*entity_id).encode(&mut w)?;
                 entity_eye_height.encode(&mut w)?;
                 VarInt(*ticks).encode(w)
@@ -445,3 +443,48 @@ impl Encode for Particle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(particle: Particle) {
+        let mut buf = vec![];
+        particle.encode(&mut buf).unwrap();
+
+        let mut r = buf.as_slice();
+        assert_eq!(
+            Particle::decode_with_id(particle.id(), &mut r).unwrap(),
+            particle
+        );
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn particle_ids() {
+        assert_eq!(Particle::CherryLeaves.id(), 29);
+        assert_eq!(Particle::Shriek { delay: 0 }.id(), 93);
+        assert_eq!(Particle::EggCrack.id(), 94);
+    }
+
+    #[test]
+    fn particle_payloads() {
+        round_trip(Particle::Block(1));
+        round_trip(Particle::Dust {
+            rgb: Vec3::new(1.0, 0.5, 0.0),
+            scale: 2.0,
+        });
+        round_trip(Particle::Item(None));
+        round_trip(Particle::VibrationBlock {
+            block_pos: BlockPos::new(1, 2, 3),
+            ticks: 20,
+        });
+        round_trip(Particle::VibrationEntity {
+            entity_id: 5,
+            entity_eye_height: 1.62,
+            ticks: 20,
+        });
+        round_trip(Particle::Shriek { delay: 10 });
+        round_trip(Particle::EggCrack);
+    }
+}
@@ -3,6 +3,7 @@
 use anyhow::Context;
 use base64::prelude::*;
 use serde::Deserialize;
+use serde_json::json;
 use url::Url;
 
 use crate::property::Property;
@@ -51,4 +52,18 @@ impl PlayerTextures {
             cape: textures.cape.map(|t| t.url),
         })
     }
+
+    /// Encodes these textures as the base64 value of a `textures` property.
+    /// The value is unsigned, so clients only accept it for player heads.
+    pub fn to_property_value(&self) -> String {
+        let mut textures = json!({
+            "SKIN": { "url": self.skin },
+        });
+
+        if let Some(cape) = &self.cape {
+            textures["CAPE"] = json!({ "url": cape });
+        }
+
+        BASE64_STANDARD.encode(json!({ "textures": textures }).to_string())
+    }
 }
@@ -199,7 +199,7 @@ mod tests {
 
     use super::*;
     use crate::protocol::decode::PacketDecoder;
-    use crate::protocol::encode::PacketEncoder;
+    use crate::protocol::encode::{BufferPool, PacketEncoder};
 
     #[derive(Encode, Decode, Packet, Debug)]
     #[packet(id = 1)]
@@ -348,4 +348,36 @@ mod tests {
         check_test_packet(&mut dec, "fourth");
         check_test_packet(&mut dec, "third");
     }
+
+    #[test]
+    fn pooled_buffers_reused() {
+        let pool = BufferPool::new();
+        let mut enc = PacketEncoder::with_pool(pool.clone());
+
+        enc.append_packet(&TestPacket::new("first")).unwrap();
+        let first = enc.take();
+        let first_ptr = first.as_ptr();
+
+        pool.put(first);
+        assert_eq!(pool.len(), 1);
+
+        // The encoder took an empty buffer from the pool when the first packet
+        // was taken, so the buffer of the first packet is taken after this one.
+        enc.append_packet(&TestPacket::new("second")).unwrap();
+        let second = enc.take();
+        assert_ne!(second.as_ptr(), first_ptr);
+        assert!(pool.is_empty());
+
+        enc.append_packet(&TestPacket::new("third")).unwrap();
+        let third = enc.take();
+        assert_eq!(third.as_ptr(), first_ptr);
+
+        let mut dec = PacketDecoder::new();
+
+        dec.queue_bytes(second);
+        dec.queue_bytes(third);
+
+        check_test_packet(&mut dec, "second");
+        check_test_packet(&mut dec, "third");
+    }
 }
@@ -39,6 +39,10 @@ impl PacketDecoder {
             Err(VarIntDecodeError::TooLarge) => bail!("malformed packet length VarInt"),
         };
 
+        // The length may be padded, so its size isn't always the written size of
+        // the VarInt.
+        let packet_len_len = self.buf.len() - r.len();
+
         ensure!(
             (0..=MAX_PACKET_SIZE).contains(&packet_len),
             "packet length of {packet_len} is out of bounds"
@@ -49,8 +53,6 @@ impl PacketDecoder {
             return Ok(None);
         }
 
-        let packet_len_len = VarInt(packet_len).written_size();
-
         let mut data;
 
         #[cfg(feature = "compression")]
@@ -91,7 +93,7 @@ impl PacketDecoder {
                     "decompressed packet length is shorter than expected"
                 );
 
-                let total_packet_len = VarInt(packet_len).written_size() + packet_len as usize;
+                let total_packet_len = packet_len_len + packet_len as usize;
 
                 self.buf.advance(total_packet_len);
 
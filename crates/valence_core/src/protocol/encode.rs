@@ -1,10 +1,12 @@
 use std::io::Write;
+use std::mem;
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "encryption")]
 use aes::cipher::generic_array::GenericArray;
 #[cfg(feature = "encryption")]
 use aes::cipher::{BlockEncryptMut, BlockSizeUser, KeyIvInit};
-use anyhow::ensure;
+use anyhow::{bail, ensure};
 use bevy_ecs::world::Mut;
 use bytes::{BufMut, BytesMut};
 use tracing::warn;
@@ -17,6 +19,78 @@ use crate::protocol::{Encode, Packet, MAX_PACKET_SIZE};
 #[cfg(feature = "encryption")]
 type Cipher = cfb8::Encryptor<aes::Aes128>;
 
+/// The number of bytes reserved for the length of a packet before the packet
+/// is encoded, so that the packet doesn't have to be moved to make room for
+/// its length afterwards. Lengths shorter than this are padded with
+/// continuation bits. Three bytes fit every length below [`MAX_PACKET_SIZE`],
+/// which is also the most the vanilla client reads.
+const PACKET_LEN_PREFIX_SIZE: usize = 3;
+
+/// Writes `len` as a VarInt padded to [`PACKET_LEN_PREFIX_SIZE`] bytes at the
+/// start of `buf`.
+fn write_packet_len(buf: &mut [u8], len: usize) {
+    debug_assert!(len < MAX_PACKET_SIZE as usize);
+
+    for (i, byte) in buf[..PACKET_LEN_PREFIX_SIZE].iter_mut().enumerate() {
+        *byte = (len >> (i * 7)) as u8 & 0b01111111;
+
+        if i < PACKET_LEN_PREFIX_SIZE - 1 {
+            *byte |= 0b10000000;
+        }
+    }
+}
+
+/// A pool of buffers to encode packets into, shared by the encoders of
+/// clients and the tasks that write their packets to the network. Buffers are
+/// given back to the pool after they are written, so that their allocations
+/// are reused instead of freed.
+#[derive(Clone, Default, Debug)]
+pub struct BufferPool {
+    buffers: Arc<Mutex<Vec<BytesMut>>>,
+}
+
+impl BufferPool {
+    /// The maximum number of buffers kept in the pool.
+    const MAX_BUFFERS: usize = 1024;
+    /// Buffers with more capacity than this are freed instead of kept, so that
+    /// a few large packets don't keep memory in use.
+    const MAX_BUFFER_CAPACITY: usize = 1 << 20;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes an empty buffer from the pool, or creates one if the pool is
+    /// empty.
+    pub fn get(&self) -> BytesMut {
+        self.buffers.lock().unwrap().pop().unwrap_or_default()
+    }
+
+    /// Clears a buffer and gives it back to the pool.
+    pub fn put(&self, mut buf: BytesMut) {
+        if buf.capacity() == 0 || buf.capacity() > Self::MAX_BUFFER_CAPACITY {
+            return;
+        }
+
+        buf.clear();
+
+        let mut buffers = self.buffers.lock().unwrap();
+
+        if buffers.len() < Self::MAX_BUFFERS {
+            buffers.push(buf);
+        }
+    }
+
+    /// The number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Default)]
 pub struct PacketEncoder {
     buf: BytesMut,
@@ -26,6 +100,7 @@ pub struct PacketEncoder {
     compression_threshold: Option<u32>,
     #[cfg(feature = "encryption")]
     cipher: Option<Cipher>,
+    pool: Option<BufferPool>,
 }
 
 impl PacketEncoder {
@@ -33,6 +108,15 @@ impl PacketEncoder {
         Self::default()
     }
 
+    /// Creates an encoder that encodes packets into buffers taken from `pool`.
+    pub fn with_pool(pool: BufferPool) -> Self {
+        Self {
+            buf: pool.get(),
+            pool: Some(pool),
+            ..Default::default()
+        }
+    }
+
     #[inline]
     pub fn append_bytes(&mut self, bytes: &[u8]) {
         self.buf.extend_from_slice(bytes)
@@ -65,10 +149,6 @@ impl PacketEncoder {
     {
         let start_len = self.buf.len();
 
-        pkt.encode_with_id((&mut self.buf).writer())?;
-
-        let data_len = self.buf.len() - start_len;
-
         #[cfg(feature = "compression")]
         if let Some(threshold) = self.compression_threshold {
             use std::io::Read;
@@ -76,8 +156,22 @@ impl PacketEncoder {
             use flate2::bufread::ZlibEncoder;
             use flate2::Compression;
 
+            // The packet length, followed by a data length of zero in case the
+            // packet isn't compressed.
+            let prefix_len = PACKET_LEN_PREFIX_SIZE + 1;
+
+            self.buf.put_bytes(0, prefix_len);
+
+            if let Err(e) = pkt.encode_with_id((&mut self.buf).writer()) {
+                self.buf.truncate(start_len);
+                return Err(e);
+            }
+
+            let data_len = self.buf.len() - start_len - prefix_len;
+
             if data_len > threshold as usize {
-                let mut z = ZlibEncoder::new(&self.buf[start_len..], Compression::new(4));
+                let mut z =
+                    ZlibEncoder::new(&self.buf[start_len + prefix_len..], Compression::new(4));
 
                 self.compress_buf.clear();
 
@@ -85,68 +179,59 @@ impl PacketEncoder {
 
                 let packet_len = data_len_size + z.read_to_end(&mut self.compress_buf)?;
 
-                ensure!(
-                    packet_len <= MAX_PACKET_SIZE as usize,
-                    "packet exceeds maximum length"
-                );
-
                 drop(z);
 
                 self.buf.truncate(start_len);
 
+                ensure!(
+                    packet_len < MAX_PACKET_SIZE as usize,
+                    "packet exceeds maximum length"
+                );
+
                 let mut writer = (&mut self.buf).writer();
 
                 VarInt(packet_len as i32).encode(&mut writer)?;
                 VarInt(data_len as i32).encode(&mut writer)?;
                 self.buf.extend_from_slice(&self.compress_buf);
             } else {
-                let data_len_size = 1;
-                let packet_len = data_len_size + data_len;
+                let packet_len = 1 + data_len;
 
-                ensure!(
-                    packet_len <= MAX_PACKET_SIZE as usize,
-                    "packet exceeds maximum length"
-                );
+                if packet_len >= MAX_PACKET_SIZE as usize {
+                    self.buf.truncate(start_len);
+                    bail!("packet exceeds maximum length");
+                }
 
-                let packet_len_size = VarInt(packet_len as i32).written_size();
-
-                let data_prefix_len = packet_len_size + data_len_size;
-
-                self.buf.put_bytes(0, data_prefix_len);
-                self.buf
-                    .copy_within(start_len..start_len + data_len, start_len + data_prefix_len);
-
-                let mut front = &mut self.buf[start_len..];
-
-                VarInt(packet_len as i32).encode(&mut front)?;
-                // Zero for no compression on this packet.
-                VarInt(0).encode(front)?;
+                write_packet_len(&mut self.buf[start_len..], packet_len);
             }
 
             return Ok(());
         }
 
-        let packet_len = data_len;
+        self.buf.put_bytes(0, PACKET_LEN_PREFIX_SIZE);
 
-        ensure!(
-            packet_len <= MAX_PACKET_SIZE as usize,
-            "packet exceeds maximum length"
-        );
+        if let Err(e) = pkt.encode_with_id((&mut self.buf).writer()) {
+            self.buf.truncate(start_len);
+            return Err(e);
+        }
 
-        let packet_len_size = VarInt(packet_len as i32).written_size();
+        let packet_len = self.buf.len() - start_len - PACKET_LEN_PREFIX_SIZE;
 
-        self.buf.put_bytes(0, packet_len_size);
-        self.buf
-            .copy_within(start_len..start_len + data_len, start_len + packet_len_size);
+        if packet_len >= MAX_PACKET_SIZE as usize {
+            self.buf.truncate(start_len);
+            bail!("packet exceeds maximum length");
+        }
 
-        let front = &mut self.buf[start_len..];
-        VarInt(packet_len as i32).encode(front)?;
+        write_packet_len(&mut self.buf[start_len..], packet_len);
 
         Ok(())
     }
 
     /// Takes all the packets written so far and encrypts them if encryption is
     /// enabled.
+    ///
+    /// If the encoder has a [`BufferPool`], the returned buffer is owned by the
+    /// caller and a new buffer is taken from the pool. The returned buffer
+    /// should be given back to the pool once it is written.
     pub fn take(&mut self) -> BytesMut {
         #[cfg(feature = "encryption")]
         if let Some(cipher) = &mut self.cipher {
@@ -156,7 +241,18 @@ impl PacketEncoder {
             }
         }
 
-        self.buf.split()
+        match &self.pool {
+            // An empty buffer would be dropped by the caller, so the current
+            // buffer is kept instead of taking one from the pool.
+            Some(_) if self.buf.is_empty() => BytesMut::new(),
+            Some(pool) => mem::replace(&mut self.buf, pool.get()),
+            None => self.buf.split(),
+        }
+    }
+
+    /// The pool the buffers of this encoder are taken from, if any.
+    pub fn pool(&self) -> Option<&BufferPool> {
+        self.pool.as_ref()
     }
 
     pub fn clear(&mut self) {
@@ -283,25 +379,21 @@ where
 {
     let start_len = buf.len();
 
-    pkt.encode_with_id(&mut *buf)?;
-
-    let packet_len = buf.len() - start_len;
+    buf.put_bytes(0, PACKET_LEN_PREFIX_SIZE);
 
-    ensure!(
-        packet_len <= MAX_PACKET_SIZE as usize,
-        "packet exceeds maximum length"
-    );
+    if let Err(e) = pkt.encode_with_id(&mut *buf) {
+        buf.truncate(start_len);
+        return Err(e);
+    }
 
-    let packet_len_size = VarInt(packet_len as i32).written_size();
+    let packet_len = buf.len() - start_len - PACKET_LEN_PREFIX_SIZE;
 
-    buf.put_bytes(0, packet_len_size);
-    buf.copy_within(
-        start_len..start_len + packet_len,
-        start_len + packet_len_size,
-    );
+    if packet_len >= MAX_PACKET_SIZE as usize {
+        buf.truncate(start_len);
+        bail!("packet exceeds maximum length");
+    }
 
-    let front = &mut buf[start_len..];
-    VarInt(packet_len as i32).encode(front)?;
+    write_packet_len(&mut buf[start_len..], packet_len);
 
     Ok(())
 }
@@ -323,12 +415,21 @@ where
 
     let start_len = buf.len();
 
-    pkt.encode_with_id(&mut *buf)?;
+    // The packet length, followed by a data length of zero in case the packet
+    // isn't compressed.
+    let prefix_len = PACKET_LEN_PREFIX_SIZE + 1;
+
+    buf.put_bytes(0, prefix_len);
+
+    if let Err(e) = pkt.encode_with_id(&mut *buf) {
+        buf.truncate(start_len);
+        return Err(e);
+    }
 
-    let data_len = buf.len() - start_len;
+    let data_len = buf.len() - start_len - prefix_len;
 
     if data_len > threshold as usize {
-        let mut z = ZlibEncoder::new(&buf[start_len..], Compression::new(4));
+        let mut z = ZlibEncoder::new(&buf[start_len + prefix_len..], Compression::new(4));
 
         scratch.clear();
 
@@ -336,39 +437,27 @@ where
 
         let packet_len = data_len_size + z.read_to_end(scratch)?;
 
-        ensure!(
-            packet_len <= MAX_PACKET_SIZE as usize,
-            "packet exceeds maximum length"
-        );
-
         drop(z);
 
         buf.truncate(start_len);
 
-        VarInt(packet_len as i32).encode(&mut *buf)?;
-        VarInt(data_len as i32).encode(&mut *buf)?;
-        buf.extend_from_slice(scratch);
-    } else {
-        let data_len_size = 1;
-        let packet_len = data_len_size + data_len;
-
         ensure!(
-            packet_len <= MAX_PACKET_SIZE as usize,
+            packet_len < MAX_PACKET_SIZE as usize,
             "packet exceeds maximum length"
         );
 
-        let packet_len_size = VarInt(packet_len as i32).written_size();
-
-        let data_prefix_len = packet_len_size + data_len_size;
-
-        buf.put_bytes(0, data_prefix_len);
-        buf.copy_within(start_len..start_len + data_len, start_len + data_prefix_len);
+        VarInt(packet_len as i32).encode(&mut *buf)?;
+        VarInt(data_len as i32).encode(&mut *buf)?;
+        buf.extend_from_slice(scratch);
+    } else {
+        let packet_len = 1 + data_len;
 
-        let mut front = &mut buf[start_len..];
+        if packet_len >= MAX_PACKET_SIZE as usize {
+            buf.truncate(start_len);
+            bail!("packet exceeds maximum length");
+        }
 
-        VarInt(packet_len as i32).encode(&mut front)?;
-        // Zero for no compression on this packet.
-        VarInt(0).encode(front)?;
+        write_packet_len(&mut buf[start_len..], packet_len);
     }
 
     Ok(())
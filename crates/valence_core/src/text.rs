@@ -11,8 +11,12 @@ use uuid::Uuid;
 use valence_nbt::Value;
 
 use crate::ident::Ident;
+use crate::item::ItemStack;
 use crate::protocol::{Decode, Encode};
 
+mod legacy;
+mod mini_message;
+
 /// Represents formatted text in Minecraft's JSON text format.
 ///
 /// Text is used in various places such as chat, window titles,
@@ -265,11 +269,15 @@ enum HoverEvent {
     ShowText(Text),
     ShowItem {
         id: Ident<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         count: Option<i32>,
-        // TODO: tag
+        /// The NBT of the item as SNBT.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
     },
     ShowEntity {
-        name: Text,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<Text>,
         #[serde(rename = "type")]
         kind: Ident<String>,
         id: Uuid,
@@ -736,6 +744,37 @@ pub trait TextFormat: Into<Text> {
         t
     }
 
+    /// Shows the tooltip of an item when the text is hovered over.
+    fn on_hover_show_item(self, item: &ItemStack) -> Text {
+        let mut t = self.into();
+        t.0.hover_event = Some(HoverEvent::ShowItem {
+            id: Ident::new_unchecked(format!("minecraft:{}", item.item.to_str())),
+            count: Some(item.count().into()),
+            tag: item
+                .nbt
+                .as_ref()
+                .map(|nbt| valence_nbt::snbt::to_snbt_string(&Value::Compound(nbt.clone()))),
+        });
+        t
+    }
+
+    /// Shows the type, UUID, and name of an entity when the text is hovered
+    /// over.
+    fn on_hover_show_entity(
+        self,
+        kind: impl Into<Ident<String>>,
+        id: Uuid,
+        name: Option<Text>,
+    ) -> Text {
+        let mut t = self.into();
+        t.0.hover_event = Some(HoverEvent::ShowEntity {
+            name,
+            kind: kind.into(),
+            id,
+        });
+        t
+    }
+
     fn clear_hover_event(self) -> Text {
         let mut t = self.into();
         t.0.hover_event = None;
@@ -964,6 +1003,7 @@ fn color_from_str(s: &str) -> Option<Color> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::item::ItemKind;
     use crate::{ident, translation_key};
 
     #[test]
@@ -1088,4 +1128,24 @@ mod tests {
         assert_eq!(serialized, expected);
         assert_eq!(txt, deserialized);
     }
+
+    #[test]
+    fn hover_events() {
+        let mut nbt = valence_nbt::Compound::new();
+        nbt.insert("Damage", 1);
+
+        let txt = "item".on_hover_show_item(&ItemStack::new(ItemKind::Diamond, 2, Some(nbt)));
+        let serialized = serde_json::to_string(&txt).unwrap();
+        let deserialized: Text = serde_json::from_str(&serialized).unwrap();
+        let expected = r#"{"text":"item","hoverEvent":{"action":"show_item","contents":{"id":"minecraft:diamond","count":2,"tag":"{Damage:1}"}}}"#;
+        assert_eq!(serialized, expected);
+        assert_eq!(txt, deserialized);
+
+        let txt = "entity".on_hover_show_entity(ident!("pig"), Uuid::nil(), None);
+        let serialized = serde_json::to_string(&txt).unwrap();
+        let deserialized: Text = serde_json::from_str(&serialized).unwrap();
+        let expected = r#"{"text":"entity","hoverEvent":{"action":"show_entity","contents":{"type":"minecraft:pig","id":"00000000-0000-0000-0000-000000000000"}}}"#;
+        assert_eq!(serialized, expected);
+        assert_eq!(txt, deserialized);
+    }
 }
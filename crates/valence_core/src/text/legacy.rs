@@ -0,0 +1,155 @@
+use super::*;
+
+impl Text {
+    /// Parses a string formatted with legacy `§` codes.
+    ///
+    /// Color codes reset the formatting codes before them, like they do on
+    /// the client. Hex colors are written as `§x§r§r§g§g§b§b`. Unknown codes
+    /// are ignored.
+    ///
+    /// ```
+    /// use valence_core::text::{Color, Text, TextFormat};
+    ///
+    /// let txt = Text::from_legacy("§cRed §lbold§r plain");
+    ///
+    /// assert_eq!(
+    ///     txt,
+    ///     Text::default() + "Red ".color(Color::RED) + "bold".color(Color::RED).bold() + " plain"
+    /// );
+    /// ```
+    pub fn from_legacy(s: &str) -> Self {
+        let mut children = vec![];
+        let mut style = TextInner::default();
+        let mut buf = String::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '§' {
+                buf.push(c);
+                continue;
+            }
+
+            let Some(code) = chars.next() else { break };
+
+            let mut new_style = style.clone();
+
+            match code.to_ascii_lowercase() {
+                'k' => new_style.obfuscated = Some(true),
+                'l' => new_style.bold = Some(true),
+                'm' => new_style.strikethrough = Some(true),
+                'n' => new_style.underlined = Some(true),
+                'o' => new_style.italic = Some(true),
+                'r' => new_style = TextInner::default(),
+                'x' => {
+                    if let Some(color) = legacy_hex_color(chars.as_str()) {
+                        new_style = TextInner {
+                            color: Some(color),
+                            ..Default::default()
+                        };
+                        // Skip the six `§<digit>` pairs.
+                        chars.nth(11);
+                    }
+                }
+                code => {
+                    if let Some(color) = legacy_color(code) {
+                        new_style = TextInner {
+                            color: Some(color),
+                            ..Default::default()
+                        };
+                    }
+                }
+            }
+
+            if new_style != style {
+                if !buf.is_empty() {
+                    children.push(Text(Box::new(TextInner {
+                        content: TextContent::Text {
+                            text: std::mem::take(&mut buf).into(),
+                        },
+                        ..style
+                    })));
+                }
+
+                style = new_style;
+            }
+        }
+
+        if !buf.is_empty() {
+            children.push(Text(Box::new(TextInner {
+                content: TextContent::Text { text: buf.into() },
+                ..style
+            })));
+        }
+
+        if children.len() == 1 {
+            children.pop().unwrap()
+        } else {
+            Text(Box::new(TextInner {
+                extra: children,
+                ..Default::default()
+            }))
+        }
+    }
+}
+
+fn legacy_color(code: char) -> Option<Color> {
+    Some(match code {
+        '0' => Color::BLACK,
+        '1' => Color::DARK_BLUE,
+        '2' => Color::DARK_GREEN,
+        '3' => Color::DARK_AQUA,
+        '4' => Color::DARK_RED,
+        '5' => Color::DARK_PURPLE,
+        '6' => Color::GOLD,
+        '7' => Color::GRAY,
+        '8' => Color::DARK_GRAY,
+        '9' => Color::BLUE,
+        'a' => Color::GREEN,
+        'b' => Color::AQUA,
+        'c' => Color::RED,
+        'd' => Color::LIGHT_PURPLE,
+        'e' => Color::YELLOW,
+        'f' => Color::WHITE,
+        _ => return None,
+    })
+}
+
+/// Reads the `§r§r§g§g§b§b` part of a hex color.
+fn legacy_hex_color(s: &str) -> Option<Color> {
+    let mut hex = String::from("#");
+    let mut chars = s.chars();
+
+    for _ in 0..6 {
+        if chars.next()? != '§' {
+            return None;
+        }
+
+        hex.push(chars.next().filter(char::is_ascii_hexdigit)?);
+    }
+
+    color_from_str(&hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_codes() {
+        assert_eq!(Text::from_legacy("plain"), "plain".into());
+        assert_eq!(Text::from_legacy("§6gold"), "gold".color(Color::GOLD));
+        assert_eq!(
+            Text::from_legacy("§l§obold italic§4red"),
+            Text::default() + "bold italic".bold().italic() + "red".color(Color::DARK_RED)
+        );
+        assert_eq!(
+            Text::from_legacy("§x§1§2§3§a§b§chex§zunknown"),
+            "hexunknown".color(Color::new(0x12, 0x3a, 0xbc))
+        );
+        // Not a hex color, so the digits are read as color codes.
+        assert_eq!(
+            Text::from_legacy("§x§1§2broken"),
+            "broken".color(Color::DARK_GREEN)
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use super::*;
+
+impl Text {
+    /// Parses a string in a subset of the [MiniMessage] format.
+    ///
+    /// The supported tags are:
+    ///
+    /// - Colors: `<red>`, `<#ff5555>`, and `<color:red>` (or `colour`, `c`).
+    /// - Decorations: `<bold>` (or `b`), `<italic>` (or `i`, `em`),
+    ///   `<underlined>` (or `u`), `<strikethrough>` (or `st`), and
+    ///   `<obfuscated>` (or `obf`). Prefixing the name with `!` turns the
+    ///   decoration off.
+    /// - `<click:action:value>`, where the action is `open_url`, `run_command`,
+    ///   `suggest_command`, `change_page`, or `copy_to_clipboard`.
+    /// - `<hover:show_text:text>`, `<hover:show_item:id[:count[:snbt]]>`, and
+    ///   `<hover:show_entity:type:uuid[:name]>`.
+    /// - `<insert:text>` (or `insertion`) and `<font:name>`.
+    /// - `<key:keybind>`, `<lang:key[:args]...>` (or `tr`, `translate`),
+    ///   `<selector:selector[:separator]>` (or `sel`),
+    ///   `<score:name:objective>`, and `<newline>` (or `br`).
+    /// - `<reset>`, which closes all open tags.
+    ///
+    /// Tags are closed with `</name>`, which also closes any tags opened after
+    /// it. Arguments containing `:` or `>` can be quoted with `'` or `"`, and
+    /// the arguments of hover texts, translations, and separators are parsed
+    /// as MiniMessage themselves. Unknown tags are kept as plain text, and
+    /// `\<` escapes a `<`.
+    ///
+    /// ```
+    /// use valence_core::text::{Color, Text, TextFormat};
+    ///
+    /// let txt = Text::from_mini_message("<red>Click <b>here</b></red>!");
+    ///
+    /// assert_eq!(
+    ///     txt,
+    ///     Text::default() + "Click ".color(Color::RED) + "here".color(Color::RED).bold() + "!"
+    /// );
+    /// ```
+    ///
+    /// [MiniMessage]: https://docs.advntr.dev/minimessage/format.html
+    pub fn from_mini_message(s: &str) -> Self {
+        let mut parser = Parser::default();
+        let mut rest = s;
+
+        while let Some(i) = rest.find(['<', '\\']) {
+            parser.buf.push_str(&rest[..i]);
+            rest = &rest[i..];
+
+            if let Some(escaped) = rest.strip_prefix('\\') {
+                match escaped.chars().next() {
+                    Some(c @ ('<' | '\\')) => {
+                        parser.buf.push(c);
+                        rest = &escaped[1..];
+                    }
+                    _ => {
+                        parser.buf.push('\\');
+                        rest = escaped;
+                    }
+                }
+
+                continue;
+            }
+
+            let Some(len) = tag_len(rest) else {
+                parser.buf.push('<');
+                rest = &rest[1..];
+                continue;
+            };
+
+            if !parser.tag(&rest[1..len - 1]) {
+                parser.buf.push_str(&rest[..len]);
+            }
+
+            rest = &rest[len..];
+        }
+
+        parser.buf.push_str(rest);
+        parser.finish()
+    }
+}
+
+/// Returns the length of the tag at the start of the string, including the
+/// angle brackets, or `None` if the tag is not closed.
+fn tag_len(s: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut chars = s.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '<') => return None,
+            (None, '>') => return (i > 1).then_some(i + 1),
+            (None, _) => {}
+        }
+    }
+
+    None
+}
+
+/// Reads an identifier from the start of the arguments, returning it with the
+/// remaining arguments. The first two arguments are read as a namespace and
+/// path unless the second one is accepted by `is_next`.
+fn ident_arg(
+    args: &[String],
+    is_next: impl FnOnce(&str) -> bool,
+) -> Option<(Ident<String>, &[String])> {
+    let (ident, rest) = match args {
+        [namespace, path, rest @ ..] if !is_next(path) => (format!("{namespace}:{path}"), rest),
+        [ident, rest @ ..] => (ident.clone(), rest),
+        [] => return None,
+    };
+
+    Some((Ident::new(ident).ok()?.into(), rest))
+}
+
+/// Splits the inside of a tag into its name and arguments, removing quotes.
+fn split_args(tag: &str) -> Vec<String> {
+    let mut args = vec![String::new()];
+    let mut quote = None;
+    let mut chars = tag.chars();
+
+    while let Some(c) = chars.next() {
+        let arg = args.last_mut().unwrap();
+
+        match (quote, c) {
+            (Some(_), '\\') => match chars.next() {
+                Some(c @ ('\'' | '"' | '\\')) => arg.push(c),
+                Some(c) => {
+                    arg.push('\\');
+                    arg.push(c);
+                }
+                None => arg.push('\\'),
+            },
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => arg.push(c),
+            (None, '\'' | '"') if arg.is_empty() => quote = Some(c),
+            (None, ':') => args.push(String::new()),
+            (None, c) => arg.push(c),
+        }
+    }
+
+    args
+}
+
+#[derive(Default)]
+struct Parser {
+    children: Vec<Text>,
+    /// The open tags and the style inside each of them.
+    stack: Vec<(String, TextInner)>,
+    buf: String,
+}
+
+impl Parser {
+    fn style(&self) -> TextInner {
+        self.stack
+            .last()
+            .map(|(_, style)| style.clone())
+            .unwrap_or_default()
+    }
+
+    fn flush(&mut self) {
+        if !self.buf.is_empty() {
+            let text = std::mem::take(&mut self.buf);
+            self.push(TextContent::Text { text: text.into() });
+        }
+    }
+
+    fn push(&mut self, content: TextContent) {
+        self.children.push(Text(Box::new(TextInner {
+            content,
+            ..self.style()
+        })));
+    }
+
+    /// Handles the inside of a tag. Returns `false` if the tag is unknown.
+    fn tag(&mut self, tag: &str) -> bool {
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.split(':').next().unwrap_or_default().to_lowercase();
+
+            let Some(i) = self.stack.iter().rposition(|(n, _)| *n == name) else {
+                return false
+            };
+
+            self.flush();
+            self.stack.truncate(i);
+            return true;
+        }
+
+        let args = split_args(tag);
+        let name = args[0].to_lowercase();
+        // Values can contain unquoted `:`, such as in URLs.
+        let rest = || args[1..].join(":");
+
+        let mut style = self.style();
+
+        match (name.as_str(), args.len()) {
+            ("reset", 1) => {
+                self.flush();
+                self.stack.clear();
+                return true;
+            }
+            ("newline" | "br", 1) => {
+                self.flush();
+                self.push(TextContent::Text { text: "\n".into() });
+                return true;
+            }
+            ("key", 2..) => {
+                self.flush();
+                self.push(TextContent::Keybind {
+                    keybind: rest().into(),
+                });
+                return true;
+            }
+            ("lang" | "tr" | "translate", 2..) => {
+                self.flush();
+                self.push(TextContent::Translate {
+                    translate: args[1].clone().into(),
+                    with: args[2..]
+                        .iter()
+                        .map(|arg| Text::from_mini_message(arg))
+                        .collect(),
+                });
+                return true;
+            }
+            ("selector" | "sel", 2 | 3) => {
+                self.flush();
+                self.push(TextContent::EntityNames {
+                    selector: args[1].clone().into(),
+                    separator: args.get(2).map(|sep| Text::from_mini_message(sep)),
+                });
+                return true;
+            }
+            ("score", 3) => {
+                self.flush();
+                self.push(TextContent::ScoreboardValue {
+                    score: ScoreboardValueContent {
+                        name: args[1].clone().into(),
+                        objective: args[2].clone().into(),
+                        value: None,
+                    },
+                });
+                return true;
+            }
+            ("color" | "colour" | "c", 2) => {
+                let Some(color) = color_from_str(&args[1]) else {
+                    return false
+                };
+                style.color = Some(color);
+            }
+            ("click", 3..) => {
+                let value = args[2..].join(":");
+
+                style.click_event = Some(match args[1].as_str() {
+                    "open_url" => ClickEvent::OpenUrl(value.into()),
+                    "run_command" => ClickEvent::RunCommand(value.into()),
+                    "suggest_command" => ClickEvent::SuggestCommand(value.into()),
+                    "change_page" => match value.parse() {
+                        Ok(page) => ClickEvent::ChangePage(page),
+                        Err(_) => return false,
+                    },
+                    "copy_to_clipboard" => ClickEvent::CopyToClipboard(value.into()),
+                    _ => return false,
+                });
+            }
+            ("hover", 3..) => {
+                style.hover_event = Some(match args[1].as_str() {
+                    "show_text" => {
+                        HoverEvent::ShowText(Text::from_mini_message(&args[2..].join(":")))
+                    }
+                    "show_item" => {
+                        let Some((id, rest)) = ident_arg(&args[2..], |s| s.parse::<i32>().is_ok()) else {
+                            return false
+                        };
+
+                        let count = match rest.first().map(|count| count.parse()) {
+                            Some(Ok(count)) => Some(count),
+                            Some(Err(_)) => return false,
+                            None => None,
+                        };
+
+                        HoverEvent::ShowItem {
+                            id,
+                            count,
+                            tag: (rest.len() > 1).then(|| rest[1..].join(":")),
+                        }
+                    }
+                    "show_entity" => {
+                        let Some((kind, rest)) =
+                            ident_arg(&args[2..], |s| s.parse::<Uuid>().is_ok()) else {
+                            return false
+                        };
+
+                        let Some(Ok(id)) = rest.first().map(|id| id.parse()) else {
+                            return false
+                        };
+
+                        HoverEvent::ShowEntity {
+                            name: (rest.len() > 1)
+                                .then(|| Text::from_mini_message(&rest[1..].join(":"))),
+                            kind,
+                            id,
+                        }
+                    }
+                    _ => return false,
+                });
+            }
+            ("insert" | "insertion", 2..) => style.insertion = Some(rest().into()),
+            ("font", 2..) => style.font = Some(rest().into()),
+            (name, 1) => {
+                let (name, value) = match name.strip_prefix('!') {
+                    Some(name) => (name, false),
+                    None => (name, true),
+                };
+
+                match name {
+                    "bold" | "b" => style.bold = Some(value),
+                    "italic" | "i" | "em" => style.italic = Some(value),
+                    "underlined" | "u" => style.underlined = Some(value),
+                    "strikethrough" | "st" => style.strikethrough = Some(value),
+                    "obfuscated" | "obf" => style.obfuscated = Some(value),
+                    _ => match color_from_str(name) {
+                        Some(color) if value => style.color = Some(color),
+                        _ => return false,
+                    },
+                }
+            }
+            _ => return false,
+        }
+
+        self.flush();
+        self.stack.push((name, style));
+        true
+    }
+
+    fn finish(mut self) -> Text {
+        self.flush();
+
+        if self.children.len() == 1 {
+            self.children.pop().unwrap()
+        } else {
+            Text(Box::new(TextInner {
+                extra: self.children,
+                ..Default::default()
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident;
+
+    #[test]
+    fn mini_message_styles() {
+        assert_eq!(Text::from_mini_message("plain"), "plain".into());
+        assert_eq!(
+            Text::from_mini_message("<#123abc>hex</#123abc> <color:gold>gold"),
+            Text::default()
+                + "hex".color(Color::new(0x12, 0x3a, 0xbc))
+                + " "
+                + "gold".color(Color::GOLD)
+        );
+        assert_eq!(
+            Text::from_mini_message("<b><i>both</b>none<!u>off<reset>reset"),
+            Text::default() + "both".bold().italic() + "none" + "off".not_underlined() + "reset"
+        );
+        assert_eq!(
+            Text::from_mini_message("<font:minecraft:uniform><insert:hi>x"),
+            "x".font("minecraft:uniform").insertion("hi")
+        );
+    }
+
+    #[test]
+    fn mini_message_events() {
+        assert_eq!(
+            Text::from_mini_message("<click:open_url:https://example.com>link"),
+            "link".on_click_open_url("https://example.com")
+        );
+        assert_eq!(
+            Text::from_mini_message("<hover:show_text:'<red>red: text'>hover"),
+            "hover".on_hover_show_text("red: text".color(Color::RED))
+        );
+
+        let json = serde_json::to_string(&Text::from_mini_message(
+            "<hover:show_item:minecraft:diamond:2>item",
+        ))
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"text":"item","hoverEvent":{"action":"show_item","contents":{"id":"minecraft:diamond","count":2}}}"#
+        );
+    }
+
+    #[test]
+    fn mini_message_show_entity() {
+        let id = Uuid::from_u128(1);
+
+        assert_eq!(
+            Text::from_mini_message(&format!("<hover:show_entity:pig:{id}:'<b>Bob'>pig")),
+            "pig".on_hover_show_entity(ident!("minecraft:pig"), id, Some("Bob".bold()))
+        );
+        assert_eq!(
+            Text::from_mini_message(&format!("<hover:show_entity:minecraft:pig:{id}>pig")),
+            "pig".on_hover_show_entity(ident!("pig"), id, None)
+        );
+    }
+
+    #[test]
+    fn mini_message_content() {
+        assert_eq!(
+            Text::from_mini_message("<key:key.jump><br><lang:chat.type.text:'<b>a':b>"),
+            Text::default()
+                + Text::keybind("key.jump")
+                + "\n"
+                + Text::translate("chat.type.text", ["a".bold(), "b".into()])
+        );
+        assert_eq!(
+            Text::from_mini_message("<unknown>\\<red> </red><b"),
+            "<unknown><red> </red><b".into()
+        );
+    }
+}
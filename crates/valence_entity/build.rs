@@ -291,6 +291,8 @@ fn build() -> anyhow::Result<TokenStream> {
     let mut entity_kind_consts = TokenStream::new();
     let mut entity_kind_fmt_args = TokenStream::new();
     let mut translation_key_arms = TokenStream::new();
+    let mut to_str_arms = TokenStream::new();
+    let mut from_str_arms = TokenStream::new();
     let mut modules = TokenStream::new();
     let mut systems = TokenStream::new();
     let mut system_names = vec![];
@@ -341,6 +343,14 @@ fn build() -> anyhow::Result<TokenStream> {
                 EntityKind::#stripped_shouty_entity_name_ident => #translation_key_expr,
             }]);
 
+            to_str_arms.extend([quote! {
+                EntityKind::#stripped_shouty_entity_name_ident => Some(#entity_type),
+            }]);
+
+            from_str_arms.extend([quote! {
+                #entity_type => Some(EntityKind::#stripped_shouty_entity_name_ident),
+            }]);
+
             // Create bundle type.
             let mut bundle_fields = TokenStream::new();
             let mut bundle_init_fields = TokenStream::new();
@@ -476,19 +486,34 @@ fn build() -> anyhow::Result<TokenStream> {
             let data_type = field.default_value.type_id();
             let encodable_expr = field.default_value.encodable_expr(quote!(value.0));
 
+            // Clients restart the interpolation of displays when the delay is
+            // sent, so it is sent again even if it didn't change.
+            let (changed_ident, send_update) = if stripped_snake_entity_name == "display"
+                && snake_field_name == "start_interpolation"
+            {
+                (ident("_changed"), quote!(!tracked_data.is_added()))
+            } else {
+                (
+                    ident("changed"),
+                    quote!(changed && !tracked_data.is_added()),
+                )
+            };
+
             systems.extend([quote! {
                 #[allow(clippy::needless_borrow)]
                 fn #system_name_ident(
                     mut query: Query<(&#component_path, &mut TrackedData), Changed<#component_path>>
                 ) {
                     for (value, mut tracked_data) in &mut query {
-                        if *value == Default::default() {
-                            tracked_data.remove_init_value(#data_index);
+                        // Only send an update if the encoded value actually
+                        // changed, even if the component was mutably accessed.
+                        let #changed_ident = if *value == Default::default() {
+                            tracked_data.remove_init_value(#data_index)
                         } else {
-                            tracked_data.insert_init_value(#data_index, #data_type, #encodable_expr);
-                        }
+                            tracked_data.insert_init_value(#data_index, #data_type, #encodable_expr)
+                        };
 
-                        if !tracked_data.is_added() {
+                        if #send_update {
                             tracked_data.append_update_value(#data_index, #data_type, #encodable_expr);
                         }
                     }
@@ -571,6 +596,26 @@ fn build() -> anyhow::Result<TokenStream> {
                     _ => None,
                 }
             }
+
+            /// Get the snake_case name of this entity kind.
+            ///
+            /// Returns `None` if the entity kind is invalid.
+            pub const fn to_str(self) -> Option<&'static str> {
+                match self {
+                    #to_str_arms
+                    _ => None,
+                }
+            }
+
+            /// Construct an entity kind from its snake_case name.
+            ///
+            /// Returns `None` if the name is invalid.
+            pub fn from_str(name: &str) -> Option<Self> {
+                match name {
+                    #from_str_arms
+                    _ => None,
+                }
+            }
         }
 
         impl std::fmt::Debug for EntityKind {
@@ -0,0 +1,504 @@
+//! Entity attributes such as max health and movement speed.
+//!
+//! Living entities are given an [`Attributes`] component with the vanilla
+//! defaults for their kind when they are spawned, unless one is already
+//! present. Changes to the base values and modifiers are sent to clients
+//! automatically, including the client controlling the entity.
+//!
+//! Only the attributes known to this version of the game are available.
+//! Newer attributes such as `generic.scale` are ignored by the client.
+//!
+//! The value of an attribute is computed the same way as in vanilla:
+//!
+//! 1. All [`AttributeOperation::Add`] modifiers are added to the base value.
+//! 2. The result of step 1 multiplied by each
+//!    [`AttributeOperation::MultiplyBase`] modifier is added.
+//! 3. The result of step 2 is multiplied by one plus each
+//!    [`AttributeOperation::MultiplyTotal`] modifier.
+//!
+//! The final value is clamped to the range of the attribute.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+use valence_core::ident;
+use valence_core::ident::Ident;
+pub use valence_core::item::AttributeOperation;
+
+use crate::packet::{self, AttributeProperty};
+use crate::*;
+
+/// An attribute of a living entity.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EntityAttribute {
+    MaxHealth,
+    FollowRange,
+    KnockbackResistance,
+    MovementSpeed,
+    FlyingSpeed,
+    AttackDamage,
+    AttackKnockback,
+    AttackSpeed,
+    Armor,
+    ArmorToughness,
+    Luck,
+    HorseJumpStrength,
+    ZombieSpawnReinforcements,
+}
+
+impl EntityAttribute {
+    pub const ALL: [Self; 13] = [
+        Self::MaxHealth,
+        Self::FollowRange,
+        Self::KnockbackResistance,
+        Self::MovementSpeed,
+        Self::FlyingSpeed,
+        Self::AttackDamage,
+        Self::AttackKnockback,
+        Self::AttackSpeed,
+        Self::Armor,
+        Self::ArmorToughness,
+        Self::Luck,
+        Self::HorseJumpStrength,
+        Self::ZombieSpawnReinforcements,
+    ];
+
+    /// The resource identifier of the attribute used in packets.
+    pub fn name(self) -> Ident<&'static str> {
+        match self {
+            Self::MaxHealth => ident!("generic.max_health"),
+            Self::FollowRange => ident!("generic.follow_range"),
+            Self::KnockbackResistance => ident!("generic.knockback_resistance"),
+            Self::MovementSpeed => ident!("generic.movement_speed"),
+            Self::FlyingSpeed => ident!("generic.flying_speed"),
+            Self::AttackDamage => ident!("generic.attack_damage"),
+            Self::AttackKnockback => ident!("generic.attack_knockback"),
+            Self::AttackSpeed => ident!("generic.attack_speed"),
+            Self::Armor => ident!("generic.armor"),
+            Self::ArmorToughness => ident!("generic.armor_toughness"),
+            Self::Luck => ident!("generic.luck"),
+            Self::HorseJumpStrength => ident!("horse.jump_strength"),
+            Self::ZombieSpawnReinforcements => ident!("zombie.spawn_reinforcements"),
+        }
+    }
+
+    /// The base value of the attribute when the entity kind does not specify
+    /// one.
+    pub fn default_value(self) -> f64 {
+        match self {
+            Self::MaxHealth => 20.0,
+            Self::FollowRange => 32.0,
+            Self::KnockbackResistance => 0.0,
+            Self::MovementSpeed => 0.7,
+            Self::FlyingSpeed => 0.4,
+            Self::AttackDamage => 2.0,
+            Self::AttackKnockback => 0.0,
+            Self::AttackSpeed => 4.0,
+            Self::Armor => 0.0,
+            Self::ArmorToughness => 0.0,
+            Self::Luck => 0.0,
+            Self::HorseJumpStrength => 0.7,
+            Self::ZombieSpawnReinforcements => 0.0,
+        }
+    }
+
+    /// The minimum value of the attribute.
+    pub fn min_value(self) -> f64 {
+        match self {
+            Self::MaxHealth => 1.0,
+            Self::Luck => -1024.0,
+            _ => 0.0,
+        }
+    }
+
+    /// The maximum value of the attribute.
+    pub fn max_value(self) -> f64 {
+        match self {
+            Self::MaxHealth
+            | Self::MovementSpeed
+            | Self::FlyingSpeed
+            | Self::AttackSpeed
+            | Self::Luck => 1024.0,
+            Self::FollowRange | Self::AttackDamage => 2048.0,
+            Self::KnockbackResistance | Self::ZombieSpawnReinforcements => 1.0,
+            Self::AttackKnockback => 5.0,
+            Self::Armor => 30.0,
+            Self::ArmorToughness => 20.0,
+            Self::HorseJumpStrength => 2.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AttributeModifier {
+    pub amount: f64,
+    pub operation: AttributeOperation,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct AttributeValue {
+    base: f64,
+    /// Modifiers are identified by UUID so they can be removed again by
+    /// whatever added them.
+    modifiers: BTreeMap<Uuid, AttributeModifier>,
+}
+
+/// The attributes of a living entity. See the [module-level
+/// documentation](self) for more.
+#[derive(Component, Clone, Default, Debug)]
+pub struct Attributes {
+    attributes: BTreeMap<EntityAttribute, AttributeValue>,
+    /// Attributes that were changed since the last tick.
+    modified: BTreeSet<EntityAttribute>,
+}
+
+impl Attributes {
+    /// Creates an empty set of attributes. Every attribute has its
+    /// [default value](EntityAttribute::default_value).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the attributes vanilla gives to entities of the given kind.
+    pub fn for_kind(kind: EntityKind) -> Self {
+        use EntityAttribute::*;
+
+        let mut attrs = Self::new();
+
+        let mut set = |attr, value| {
+            attrs.attributes.insert(
+                attr,
+                AttributeValue {
+                    base: value,
+                    modifiers: BTreeMap::new(),
+                },
+            );
+        };
+
+        // Shared by all living entities.
+        set(MaxHealth, 20.0);
+        set(KnockbackResistance, 0.0);
+        set(MovementSpeed, 0.7);
+        set(Armor, 0.0);
+        set(ArmorToughness, 0.0);
+
+        match kind {
+            EntityKind::PLAYER => {
+                set(AttackDamage, 1.0);
+                set(MovementSpeed, 0.1);
+                set(AttackSpeed, 4.0);
+                set(Luck, 0.0);
+            }
+            EntityKind::ZOMBIE
+            | EntityKind::HUSK
+            | EntityKind::DROWNED
+            | EntityKind::ZOMBIE_VILLAGER
+            | EntityKind::ZOMBIFIED_PIGLIN => {
+                set(FollowRange, 35.0);
+                set(MovementSpeed, 0.23);
+                set(AttackDamage, 3.0);
+                set(Armor, 2.0);
+                set(ZombieSpawnReinforcements, 0.0);
+            }
+            EntityKind::SKELETON
+            | EntityKind::STRAY
+            | EntityKind::WITHER_SKELETON
+            | EntityKind::CREEPER => {
+                set(FollowRange, 16.0);
+                set(MovementSpeed, 0.25);
+                set(AttackDamage, 2.0);
+            }
+            EntityKind::SPIDER => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 16.0);
+                set(MovementSpeed, 0.3);
+                set(AttackDamage, 2.0);
+            }
+            EntityKind::CAVE_SPIDER => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 12.0);
+                set(MovementSpeed, 0.3);
+                set(AttackDamage, 2.0);
+            }
+            EntityKind::ENDERMAN => {
+                set(FollowRange, 64.0);
+                set(MaxHealth, 40.0);
+                set(MovementSpeed, 0.3);
+                set(AttackDamage, 7.0);
+            }
+            EntityKind::IRON_GOLEM => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 100.0);
+                set(MovementSpeed, 0.25);
+                set(KnockbackResistance, 1.0);
+                set(AttackDamage, 15.0);
+            }
+            EntityKind::VILLAGER | EntityKind::WANDERING_TRADER => {
+                set(FollowRange, 48.0);
+                set(MovementSpeed, 0.5);
+            }
+            EntityKind::WOLF => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 8.0);
+                set(MovementSpeed, 0.3);
+                set(AttackDamage, 2.0);
+            }
+            EntityKind::COW | EntityKind::MOOSHROOM => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 10.0);
+                set(MovementSpeed, 0.2);
+            }
+            EntityKind::PIG => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 10.0);
+                set(MovementSpeed, 0.25);
+            }
+            EntityKind::SHEEP => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 8.0);
+                set(MovementSpeed, 0.23);
+            }
+            EntityKind::CHICKEN => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 4.0);
+                set(MovementSpeed, 0.25);
+            }
+            EntityKind::HORSE | EntityKind::DONKEY | EntityKind::MULE => {
+                set(FollowRange, 16.0);
+                set(MaxHealth, 15.0);
+                set(MovementSpeed, 0.225);
+                set(HorseJumpStrength, 0.7);
+            }
+            _ => {}
+        }
+
+        attrs
+    }
+
+    /// Returns `true` if the attribute has a base value or modifiers in this
+    /// set.
+    pub fn contains(&self, attr: EntityAttribute) -> bool {
+        self.attributes.contains_key(&attr)
+    }
+
+    /// Returns the base value of the attribute, before modifiers are applied.
+    pub fn base(&self, attr: EntityAttribute) -> f64 {
+        self.attributes
+            .get(&attr)
+            .map_or(attr.default_value(), |value| value.base)
+    }
+
+    /// Sets the base value of the attribute.
+    pub fn set_base(&mut self, attr: EntityAttribute, base: f64) {
+        let entry = self.entry(attr);
+
+        if entry.base != base {
+            entry.base = base;
+            self.modified.insert(attr);
+        }
+    }
+
+    /// Returns the value of the attribute after modifiers are applied.
+    pub fn get(&self, attr: EntityAttribute) -> f64 {
+        let Some(value) = self.attributes.get(&attr) else {
+            return attr.default_value()
+        };
+
+        let mut added = value.base;
+
+        for modifier in value.modifiers.values() {
+            if modifier.operation == AttributeOperation::Add {
+                added += modifier.amount;
+            }
+        }
+
+        let mut total = added;
+
+        for modifier in value.modifiers.values() {
+            if modifier.operation == AttributeOperation::MultiplyBase {
+                total += added * modifier.amount;
+            }
+        }
+
+        for modifier in value.modifiers.values() {
+            if modifier.operation == AttributeOperation::MultiplyTotal {
+                total *= 1.0 + modifier.amount;
+            }
+        }
+
+        total.clamp(attr.min_value(), attr.max_value())
+    }
+
+    /// Returns the modifier of the attribute with the given UUID.
+    pub fn modifier(&self, attr: EntityAttribute, uuid: Uuid) -> Option<AttributeModifier> {
+        self.attributes.get(&attr)?.modifiers.get(&uuid).copied()
+    }
+
+    /// Returns an iterator over the modifiers of the attribute.
+    pub fn modifiers(
+        &self,
+        attr: EntityAttribute,
+    ) -> impl Iterator<Item = (Uuid, AttributeModifier)> + '_ {
+        self.attributes
+            .get(&attr)
+            .into_iter()
+            .flat_map(|value| value.modifiers.iter().map(|(&uuid, &m)| (uuid, m)))
+    }
+
+    /// Adds a modifier to the attribute, replacing the modifier with the same
+    /// UUID. The previous modifier is returned.
+    pub fn set_modifier(
+        &mut self,
+        attr: EntityAttribute,
+        uuid: Uuid,
+        amount: f64,
+        operation: AttributeOperation,
+    ) -> Option<AttributeModifier> {
+        let modifier = AttributeModifier { amount, operation };
+        let old = self.entry(attr).modifiers.insert(uuid, modifier);
+
+        if old != Some(modifier) {
+            self.modified.insert(attr);
+        }
+
+        old
+    }
+
+    /// Removes the modifier with the given UUID from the attribute.
+    pub fn remove_modifier(
+        &mut self,
+        attr: EntityAttribute,
+        uuid: Uuid,
+    ) -> Option<AttributeModifier> {
+        let old = self.attributes.get_mut(&attr)?.modifiers.remove(&uuid)?;
+        self.modified.insert(attr);
+        Some(old)
+    }
+
+    /// Removes all modifiers from the attribute.
+    pub fn clear_modifiers(&mut self, attr: EntityAttribute) {
+        if let Some(value) = self.attributes.get_mut(&attr) {
+            if !value.modifiers.is_empty() {
+                value.modifiers.clear();
+                self.modified.insert(attr);
+            }
+        }
+    }
+
+    /// Returns the properties for the
+    /// [`EntityAttributesS2c`](packet::EntityAttributesS2c) packet with every
+    /// attribute in this set. This is used when the entity enters the view
+    /// of a client.
+    pub fn init_properties(&self) -> Vec<AttributeProperty<'static>> {
+        self.attributes
+            .iter()
+            .map(|(&attr, value)| to_property(attr, value))
+            .collect()
+    }
+
+    /// Returns the properties for the
+    /// [`EntityAttributesS2c`](packet::EntityAttributesS2c) packet with the
+    /// attributes that were changed since the last tick.
+    pub fn update_properties(&self) -> Vec<AttributeProperty<'static>> {
+        self.modified
+            .iter()
+            .filter_map(|attr| Some(to_property(*attr, self.attributes.get(attr)?)))
+            .collect()
+    }
+
+    fn entry(&mut self, attr: EntityAttribute) -> &mut AttributeValue {
+        self.attributes
+            .entry(attr)
+            .or_insert_with(|| AttributeValue {
+                base: attr.default_value(),
+                modifiers: BTreeMap::new(),
+            })
+    }
+
+    pub(crate) fn clear_modified(&mut self) {
+        self.modified.clear();
+    }
+}
+
+fn to_property(attr: EntityAttribute, value: &AttributeValue) -> AttributeProperty<'static> {
+    AttributeProperty {
+        key: Ident::<Cow<str>>::from(attr.name()),
+        value: value.base,
+        modifiers: value
+            .modifiers
+            .iter()
+            .map(|(&uuid, m)| packet::AttributeModifier {
+                uuid,
+                amount: m.amount,
+                operation: m.operation.to_raw(),
+            })
+            .collect(),
+    }
+}
+
+/// Gives living entities the default attributes for their kind.
+#[allow(clippy::type_complexity)]
+pub(crate) fn add_attributes(
+    entities: Query<
+        (Entity, &EntityKind),
+        (
+            Added<EntityKind>,
+            With<living::LivingEntity>,
+            Without<Attributes>,
+        ),
+    >,
+    mut commands: Commands,
+) {
+    for (entity, kind) in &entities {
+        commands.entity(entity).insert(Attributes::for_kind(*kind));
+    }
+}
+
+pub(crate) fn clear_attribute_changes(mut attributes: Query<&mut Attributes, Changed<Attributes>>) {
+    for mut attributes in &mut attributes {
+        attributes.clear_modified();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attribute_value() {
+        let mut attrs = Attributes::for_kind(EntityKind::PLAYER);
+
+        assert_eq!(attrs.get(EntityAttribute::MovementSpeed), 0.1);
+
+        let a = Uuid::from_u128(1);
+        let b = Uuid::from_u128(2);
+        let c = Uuid::from_u128(3);
+
+        attrs.set_modifier(EntityAttribute::MaxHealth, a, 10.0, AttributeOperation::Add);
+        attrs.set_modifier(
+            EntityAttribute::MaxHealth,
+            b,
+            0.5,
+            AttributeOperation::MultiplyBase,
+        );
+        attrs.set_modifier(
+            EntityAttribute::MaxHealth,
+            c,
+            1.0,
+            AttributeOperation::MultiplyTotal,
+        );
+
+        // ((20 + 10) + 30 * 0.5) * 2
+        assert_eq!(attrs.get(EntityAttribute::MaxHealth), 90.0);
+
+        attrs.remove_modifier(EntityAttribute::MaxHealth, c);
+        assert_eq!(attrs.get(EntityAttribute::MaxHealth), 45.0);
+
+        attrs.set_base(EntityAttribute::MaxHealth, 2000.0);
+        assert_eq!(attrs.get(EntityAttribute::MaxHealth), 1024.0);
+
+        assert_eq!(attrs.update_properties().len(), 1);
+        attrs.clear_modified();
+        assert!(attrs.update_properties().is_empty());
+    }
+}
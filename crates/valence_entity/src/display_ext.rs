@@ -0,0 +1,228 @@
+//! Helpers for the tracked data of display entities.
+
+use bevy_ecs::query::WorldQuery;
+use glam::{Affine3A, Mat4, Quat};
+
+use crate::{display, item_display, text_display};
+
+impl display::Billboard {
+    /// The display does not rotate to face the viewer.
+    pub const FIXED: Self = Self(0);
+    /// The display pivots around its vertical axis to face the viewer.
+    pub const VERTICAL: Self = Self(1);
+    /// The display pivots around its horizontal axis to face the viewer.
+    pub const HORIZONTAL: Self = Self(2);
+    /// The display pivots around its center point to face the viewer.
+    pub const CENTER: Self = Self(3);
+}
+
+impl display::Brightness {
+    /// Use the light level of the block the display is in.
+    pub const NONE: Self = Self(-1);
+
+    /// Overrides the block and sky light levels used to render the display.
+    /// Both values are clamped to `0..=15`.
+    pub fn new(block_light: u8, sky_light: u8) -> Self {
+        Self((block_light.min(15) as i32) << 4 | (sky_light.min(15) as i32) << 20)
+    }
+}
+
+impl display::GlowColorOverride {
+    /// Use the color of the entity's team.
+    pub const NONE: Self = Self(-1);
+
+    /// Creates a glow color from an RGB value.
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self((r as i32) << 16 | (g as i32) << 8 | b as i32)
+    }
+}
+
+impl item_display::ItemDisplay {
+    pub const NONE: Self = Self(0);
+    pub const THIRD_PERSON_LEFT_HAND: Self = Self(1);
+    pub const THIRD_PERSON_RIGHT_HAND: Self = Self(2);
+    pub const FIRST_PERSON_LEFT_HAND: Self = Self(3);
+    pub const FIRST_PERSON_RIGHT_HAND: Self = Self(4);
+    pub const HEAD: Self = Self(5);
+    pub const GUI: Self = Self(6);
+    pub const GROUND: Self = Self(7);
+    pub const FIXED: Self = Self(8);
+}
+
+impl text_display::Background {
+    /// A fully transparent background.
+    pub const TRANSPARENT: Self = Self(0);
+
+    /// Creates a background color from an ARGB value.
+    pub fn argb(a: u8, r: u8, g: u8, b: u8) -> Self {
+        Self(i32::from_be_bytes([a, r, g, b]))
+    }
+}
+
+impl text_display::TextOpacity {
+    /// Creates a text opacity from an alpha value, where `255` is fully
+    /// opaque.
+    ///
+    /// Note that the client does not render text with an alpha value below
+    /// `26`.
+    pub fn new(alpha: u8) -> Self {
+        Self(alpha as i8)
+    }
+
+    /// Returns the alpha value of the text.
+    pub fn get(&self) -> u8 {
+        self.0 as u8
+    }
+}
+
+/// The horizontal alignment of the lines of a text display.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub enum TextAlignment {
+    #[default]
+    Center,
+    Left,
+    Right,
+}
+
+impl text_display::TextDisplayFlags {
+    const SHADOW: i8 = 0x01;
+    const SEE_THROUGH: i8 = 0x02;
+    const DEFAULT_BACKGROUND: i8 = 0x04;
+    const ALIGN_LEFT: i8 = 0x08;
+    const ALIGN_RIGHT: i8 = 0x10;
+
+    /// Whether the text is rendered with a shadow.
+    pub fn shadow(&self) -> bool {
+        self.0 & Self::SHADOW != 0
+    }
+
+    pub fn set_shadow(&mut self, shadow: bool) {
+        self.set_flag(Self::SHADOW, shadow);
+    }
+
+    /// Whether the text is visible through blocks.
+    pub fn see_through(&self) -> bool {
+        self.0 & Self::SEE_THROUGH != 0
+    }
+
+    pub fn set_see_through(&mut self, see_through: bool) {
+        self.set_flag(Self::SEE_THROUGH, see_through);
+    }
+
+    /// Whether the text uses the default chat background color instead of
+    /// [`Background`](text_display::Background).
+    pub fn default_background(&self) -> bool {
+        self.0 & Self::DEFAULT_BACKGROUND != 0
+    }
+
+    pub fn set_default_background(&mut self, default_background: bool) {
+        self.set_flag(Self::DEFAULT_BACKGROUND, default_background);
+    }
+
+    pub fn alignment(&self) -> TextAlignment {
+        if self.0 & Self::ALIGN_LEFT != 0 {
+            TextAlignment::Left
+        } else if self.0 & Self::ALIGN_RIGHT != 0 {
+            TextAlignment::Right
+        } else {
+            TextAlignment::Center
+        }
+    }
+
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        self.set_flag(Self::ALIGN_LEFT, alignment == TextAlignment::Left);
+        self.set_flag(Self::ALIGN_RIGHT, alignment == TextAlignment::Right);
+    }
+
+    fn set_flag(&mut self, flag: i8, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+}
+
+/// A query for the transformation and interpolation of a display entity.
+///
+/// The transformation is applied in the order right rotation, scale, left
+/// rotation, and then translation.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct DisplayTransformation {
+    pub translation: &'static mut display::Translation,
+    pub left_rotation: &'static mut display::LeftRotation,
+    pub scale: &'static mut display::Scale,
+    pub right_rotation: &'static mut display::RightRotation,
+    pub start_interpolation: &'static mut display::StartInterpolation,
+    pub interpolation_duration: &'static mut display::InterpolationDuration,
+}
+
+impl DisplayTransformationItem<'_> {
+    /// Sets the transformation of the display from an affine
+    /// transformation. The right rotation is reset to the identity.
+    pub fn set_affine(&mut self, transform: Affine3A) {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+
+        self.translation.0 = translation;
+        self.left_rotation.0 = rotation;
+        self.scale.0 = scale;
+        self.right_rotation.0 = Quat::IDENTITY;
+    }
+
+    /// Sets the transformation of the display from a transformation matrix.
+    /// The right rotation is reset to the identity.
+    pub fn set_matrix(&mut self, transform: Mat4) {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+
+        self.translation.0 = translation;
+        self.left_rotation.0 = rotation;
+        self.scale.0 = scale;
+        self.right_rotation.0 = Quat::IDENTITY;
+    }
+
+    /// Makes clients smoothly interpolate from the previous transformation to
+    /// the current one over `duration` ticks, starting after `delay` ticks.
+    ///
+    /// This should be called on the same tick the transformation is changed.
+    pub fn interpolate(&mut self, delay: i32, duration: i32) {
+        // Always mark the start as changed so clients restart the
+        // interpolation even if the delay is the same as before.
+        self.start_interpolation.0 = delay;
+        self.interpolation_duration.0 = duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_display_flags() {
+        let mut flags = text_display::TextDisplayFlags::default();
+
+        flags.set_shadow(true);
+        flags.set_alignment(TextAlignment::Right);
+        assert!(flags.shadow());
+        assert!(!flags.see_through());
+        assert_eq!(flags.alignment(), TextAlignment::Right);
+
+        flags.set_alignment(TextAlignment::Left);
+        assert_eq!(flags.alignment(), TextAlignment::Left);
+        assert_eq!(flags.0, 0x01 | 0x08);
+
+        flags.set_shadow(false);
+        flags.set_alignment(TextAlignment::Center);
+        assert_eq!(flags.0, 0);
+    }
+
+    #[test]
+    fn display_colors() {
+        assert_eq!(display::Brightness::new(15, 15).0, 15 << 4 | 15 << 20);
+        assert_eq!(
+            text_display::Background::argb(0x40, 0, 0, 0),
+            text_display::Background::default()
+        );
+        assert_eq!(text_display::TextOpacity::new(255).get(), 255);
+    }
+}
@@ -0,0 +1,323 @@
+//! Status effects, also known as potion effects.
+//!
+//! Insert an [`ActiveEffects`] component on a living entity to give it status
+//! effects. Added and removed effects are sent to clients automatically,
+//! including the client controlling the entity.
+//!
+//! Effect durations are counted down by the server. When an effect runs out,
+//! it is removed and an [`EffectExpiredEvent`] is sent.
+//!
+//! Particles around entities seen by other clients are determined by the
+//! [`PotionSwirlsColor`](crate::living::PotionSwirlsColor) and
+//! [`PotionSwirlsAmbient`](crate::living::PotionSwirlsAmbient) tracked data,
+//! which are not changed by this module.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use valence_core::protocol::encode::WritePacket;
+
+use crate::packet::{self, EntityStatusEffectS2c, RemoveEntityStatusEffectS2c};
+use crate::*;
+
+/// A status effect with its protocol ID.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum StatusEffect {
+    Speed = 1,
+    Slowness,
+    Haste,
+    MiningFatigue,
+    Strength,
+    InstantHealth,
+    InstantDamage,
+    JumpBoost,
+    Nausea,
+    Regeneration,
+    Resistance,
+    FireResistance,
+    WaterBreathing,
+    Invisibility,
+    Blindness,
+    NightVision,
+    Hunger,
+    Weakness,
+    Poison,
+    Wither,
+    HealthBoost,
+    Absorption,
+    Saturation,
+    Glowing,
+    Levitation,
+    Luck,
+    BadLuck,
+    SlowFalling,
+    ConduitPower,
+    DolphinsGrace,
+    BadOmen,
+    HeroOfTheVillage,
+    Darkness,
+}
+
+impl StatusEffect {
+    pub const ALL: [Self; 33] = [
+        Self::Speed,
+        Self::Slowness,
+        Self::Haste,
+        Self::MiningFatigue,
+        Self::Strength,
+        Self::InstantHealth,
+        Self::InstantDamage,
+        Self::JumpBoost,
+        Self::Nausea,
+        Self::Regeneration,
+        Self::Resistance,
+        Self::FireResistance,
+        Self::WaterBreathing,
+        Self::Invisibility,
+        Self::Blindness,
+        Self::NightVision,
+        Self::Hunger,
+        Self::Weakness,
+        Self::Poison,
+        Self::Wither,
+        Self::HealthBoost,
+        Self::Absorption,
+        Self::Saturation,
+        Self::Glowing,
+        Self::Levitation,
+        Self::Luck,
+        Self::BadLuck,
+        Self::SlowFalling,
+        Self::ConduitPower,
+        Self::DolphinsGrace,
+        Self::BadOmen,
+        Self::HeroOfTheVillage,
+        Self::Darkness,
+    ];
+
+    /// Returns the protocol ID of the effect.
+    pub fn to_raw(self) -> i32 {
+        self as i32
+    }
+
+    /// Returns the effect with the given protocol ID.
+    pub fn from_raw(id: i32) -> Option<Self> {
+        Self::ALL
+            .get(usize::try_from(id).ok()?.checked_sub(1)?)
+            .copied()
+    }
+
+    /// Returns `true` if the effect is applied once rather than over time.
+    pub fn is_instant(self) -> bool {
+        matches!(
+            self,
+            Self::InstantHealth | Self::InstantDamage | Self::Saturation
+        )
+    }
+}
+
+/// An instance of a status effect on an entity.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ActiveEffect {
+    /// The level of the effect minus one. An amplifier of `0` is level I.
+    pub amplifier: u8,
+    /// The remaining duration in ticks. `None` lasts forever.
+    pub duration: Option<u32>,
+    /// Ambient effects come from beacons and conduits. They have less visible
+    /// particles.
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+}
+
+impl ActiveEffect {
+    /// Creates an effect with visible particles and icon.
+    pub fn new(amplifier: u8, duration: Option<u32>) -> Self {
+        Self {
+            amplifier,
+            duration,
+            ambient: false,
+            show_particles: true,
+            show_icon: true,
+        }
+    }
+}
+
+/// The status effects of a living entity. See the [module-level
+/// documentation](self) for more.
+#[derive(Component, Clone, Default, Debug)]
+pub struct ActiveEffects {
+    effects: BTreeMap<StatusEffect, ActiveEffect>,
+    /// Effects that were added or replaced since the last tick.
+    added: BTreeSet<StatusEffect>,
+    /// Effects that were removed since the last tick.
+    removed: BTreeSet<StatusEffect>,
+}
+
+impl ActiveEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an effect, replacing the effect of the same type. The previous
+    /// effect is returned.
+    pub fn add(&mut self, effect: StatusEffect, active: ActiveEffect) -> Option<ActiveEffect> {
+        self.removed.remove(&effect);
+        self.added.insert(effect);
+        self.effects.insert(effect, active)
+    }
+
+    /// Removes an effect. The removed effect is returned.
+    pub fn remove(&mut self, effect: StatusEffect) -> Option<ActiveEffect> {
+        let old = self.effects.remove(&effect)?;
+        self.added.remove(&effect);
+        self.removed.insert(effect);
+        Some(old)
+    }
+
+    /// Removes all effects.
+    pub fn clear(&mut self) {
+        for effect in std::mem::take(&mut self.effects).into_keys() {
+            self.added.remove(&effect);
+            self.removed.insert(effect);
+        }
+    }
+
+    pub fn get(&self, effect: StatusEffect) -> Option<&ActiveEffect> {
+        self.effects.get(&effect)
+    }
+
+    pub fn contains(&self, effect: StatusEffect) -> bool {
+        self.effects.contains_key(&effect)
+    }
+
+    /// Returns an iterator over all active effects ordered by protocol ID.
+    pub fn iter(&self) -> impl Iterator<Item = (StatusEffect, &ActiveEffect)> + '_ {
+        self.effects
+            .iter()
+            .map(|(&effect, active)| (effect, active))
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Writes the packets for all active effects. This is used when the
+    /// entity enters the view of a client.
+    pub fn write_init_packets(&self, entity_id: VarInt, mut writer: impl WritePacket) {
+        for (&effect, active) in &self.effects {
+            writer.write_packet(&effect_packet(entity_id, effect, active));
+        }
+    }
+
+    /// Writes the packets for the effects that were added or removed since
+    /// the last tick.
+    pub fn write_update_packets(&self, entity_id: VarInt, mut writer: impl WritePacket) {
+        for effect in &self.removed {
+            writer.write_packet(&RemoveEntityStatusEffectS2c {
+                entity_id,
+                effect_id: VarInt(effect.to_raw()),
+            });
+        }
+
+        for effect in &self.added {
+            if let Some(active) = self.effects.get(effect) {
+                writer.write_packet(&effect_packet(entity_id, *effect, active));
+            }
+        }
+    }
+
+    /// Returns `true` if effects were added or removed since the last tick.
+    pub fn is_modified(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+fn effect_packet(
+    entity_id: VarInt,
+    effect: StatusEffect,
+    active: &ActiveEffect,
+) -> EntityStatusEffectS2c {
+    EntityStatusEffectS2c {
+        entity_id,
+        effect_id: VarInt(effect.to_raw()),
+        amplifier: active.amplifier,
+        duration: VarInt(
+            active
+                .duration
+                .map_or(-1, |d| d.min(i32::MAX as u32) as i32),
+        ),
+        flags: packet::Flags::new()
+            .with_is_ambient(active.ambient)
+            .with_show_particles(active.show_particles)
+            .with_show_icon(active.show_icon),
+        factor_codec: None,
+    }
+}
+
+/// Sent when the duration of a status effect runs out and it is removed from
+/// the entity.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EffectExpiredEvent {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+    /// The effect that expired.
+    pub active: ActiveEffect,
+}
+
+pub(crate) fn tick_effects(
+    mut entities: Query<(Entity, &mut ActiveEffects)>,
+    mut events: EventWriter<EffectExpiredEvent>,
+) {
+    for (entity, mut effects) in &mut entities {
+        let mut expired = vec![];
+
+        // Change detection is bypassed so that counting down doesn't mark the
+        // component as changed every tick.
+        for (&effect, active) in &mut effects.bypass_change_detection().effects {
+            if let Some(duration) = &mut active.duration {
+                *duration = duration.saturating_sub(1);
+
+                if *duration == 0 {
+                    expired.push(effect);
+                }
+            }
+        }
+
+        for effect in expired {
+            if let Some(active) = effects.remove(effect) {
+                events.send(EffectExpiredEvent {
+                    entity,
+                    effect,
+                    active,
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn clear_effect_changes(mut effects: Query<&mut ActiveEffects, Changed<ActiveEffects>>) {
+    for mut effects in &mut effects {
+        let effects = effects.bypass_change_detection();
+        effects.added.clear();
+        effects.removed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effect_ids() {
+        for effect in StatusEffect::ALL {
+            assert_eq!(StatusEffect::from_raw(effect.to_raw()), Some(effect));
+        }
+
+        assert_eq!(StatusEffect::Darkness.to_raw(), 33);
+        assert_eq!(StatusEffect::from_raw(0), None);
+    }
+}
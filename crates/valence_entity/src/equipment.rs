@@ -0,0 +1,224 @@
+//! The items held and worn by living entities.
+//!
+//! Living entities are given an empty [`Equipment`] component when they are
+//! spawned, unless one is already present. The equipment is shown to clients
+//! viewing the entity, and changes are sent to them automatically. Other
+//! entities have no equipment, because clients ignore it.
+//!
+//! The equipment of a client is what other clients see it holding and
+//! wearing. The client itself sees its inventory instead, so its equipment is
+//! never sent to it.
+
+use valence_core::hand::Hand;
+use valence_core::item::ItemStack;
+use valence_core::protocol::encode::WritePacket;
+
+use crate::packet::{EntityEquipmentUpdateS2c, EquipmentEntry};
+use crate::*;
+
+/// A slot of [`Equipment`] with its protocol ID.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EquipmentSlot {
+    MainHand,
+    OffHand,
+    Feet,
+    Legs,
+    Chest,
+    Head,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [Self; 6] = [
+        Self::MainHand,
+        Self::OffHand,
+        Self::Feet,
+        Self::Legs,
+        Self::Chest,
+        Self::Head,
+    ];
+
+    /// Returns the slot holding the item in `hand`.
+    pub const fn from_hand(hand: Hand) -> Self {
+        match hand {
+            Hand::Main => Self::MainHand,
+            Hand::Off => Self::OffHand,
+        }
+    }
+
+    pub const fn to_raw(self) -> i8 {
+        self as i8
+    }
+}
+
+/// The items in the hands and armor slots of a living entity.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct Equipment {
+    items: [Option<ItemStack>; 6],
+    /// Contains a set bit for each slot changed since the last tick.
+    changed: u8,
+}
+
+impl Equipment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: EquipmentSlot) -> Option<&ItemStack> {
+        self.items[slot as usize].as_ref()
+    }
+
+    /// Puts an item in a slot. Nothing is sent to viewers if the item is the
+    /// same as before.
+    pub fn set(&mut self, slot: EquipmentSlot, item: impl Into<Option<ItemStack>>) {
+        self.replace(slot, item);
+    }
+
+    /// Puts an item in a slot, returning the previous item.
+    pub fn replace(
+        &mut self,
+        slot: EquipmentSlot,
+        item: impl Into<Option<ItemStack>>,
+    ) -> Option<ItemStack> {
+        let item = item.into();
+
+        if self.items[slot as usize] == item {
+            return item;
+        }
+
+        self.changed |= 1 << slot as u8;
+        std::mem::replace(&mut self.items[slot as usize], item)
+    }
+
+    pub fn main_hand(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::MainHand)
+    }
+
+    pub fn off_hand(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::OffHand)
+    }
+
+    pub fn feet(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::Feet)
+    }
+
+    pub fn legs(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::Legs)
+    }
+
+    pub fn chest(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::Chest)
+    }
+
+    pub fn head(&self) -> Option<&ItemStack> {
+        self.get(EquipmentSlot::Head)
+    }
+
+    /// Swaps the items in the main and off hand.
+    pub fn swap_hands(&mut self) {
+        if self.items[0] != self.items[1] {
+            self.items.swap(0, 1);
+            self.changed |= 0b11;
+        }
+    }
+
+    /// Returns an iterator over the slots and the items in them, skipping
+    /// empty slots.
+    pub fn iter(&self) -> impl Iterator<Item = (EquipmentSlot, &ItemStack)> + '_ {
+        EquipmentSlot::ALL
+            .into_iter()
+            .filter_map(|slot| Some((slot, self.get(slot)?)))
+    }
+
+    /// Writes the packet with all the equipment, if there is any. This is used
+    /// when the entity enters the view of a client.
+    pub fn write_init_packets(&self, entity_id: VarInt, mut writer: impl WritePacket) {
+        let equipment: Vec<_> = self
+            .iter()
+            .map(|(slot, item)| EquipmentEntry {
+                slot: slot.to_raw(),
+                item: Some(item.clone()),
+            })
+            .collect();
+
+        if !equipment.is_empty() {
+            writer.write_packet(&EntityEquipmentUpdateS2c {
+                entity_id,
+                equipment,
+            });
+        }
+    }
+
+    /// Writes the packet with the slots that were changed since the last
+    /// tick.
+    pub fn write_update_packets(&self, entity_id: VarInt, mut writer: impl WritePacket) {
+        if !self.is_modified() {
+            return;
+        }
+
+        let equipment = EquipmentSlot::ALL
+            .into_iter()
+            .filter(|&slot| (self.changed >> slot as u8) & 1 == 1)
+            .map(|slot| EquipmentEntry {
+                slot: slot.to_raw(),
+                item: self.get(slot).cloned(),
+            })
+            .collect();
+
+        writer.write_packet(&EntityEquipmentUpdateS2c {
+            entity_id,
+            equipment,
+        });
+    }
+
+    /// Returns `true` if slots were changed since the last tick.
+    pub fn is_modified(&self) -> bool {
+        self.changed != 0
+    }
+}
+
+/// Gives living entities empty equipment.
+#[allow(clippy::type_complexity)]
+pub(crate) fn add_equipment(
+    entities: Query<
+        Entity,
+        (
+            Added<EntityKind>,
+            With<living::LivingEntity>,
+            Without<Equipment>,
+        ),
+    >,
+    mut commands: Commands,
+) {
+    for entity in &entities {
+        commands.entity(entity).insert(Equipment::default());
+    }
+}
+
+pub(crate) fn clear_equipment_changes(mut equipment: Query<&mut Equipment, Changed<Equipment>>) {
+    for mut equipment in &mut equipment {
+        equipment.bypass_change_detection().changed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_slots() {
+        let mut equipment = Equipment::new();
+        let sword = ItemStack::new(valence_core::item::ItemKind::DiamondSword, 1, None);
+
+        equipment.set(EquipmentSlot::MainHand, sword.clone());
+        assert_eq!(equipment.changed, 0b1);
+
+        equipment.changed = 0;
+        equipment.set(EquipmentSlot::MainHand, sword.clone());
+        assert!(!equipment.is_modified());
+
+        equipment.swap_hands();
+        assert_eq!(equipment.main_hand(), None);
+        assert_eq!(equipment.off_hand(), Some(&sword));
+        assert_eq!(equipment.changed, 0b11);
+    }
+}
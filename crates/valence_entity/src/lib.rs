@@ -17,6 +17,10 @@
     clippy::dbg_macro
 )]
 
+pub mod attributes;
+mod display_ext;
+pub mod effects;
+pub mod equipment;
 pub mod hitbox;
 pub mod packet;
 
@@ -25,6 +29,7 @@ use std::ops::Range;
 
 use bevy_app::{App, CoreSet, Plugin};
 use bevy_ecs::prelude::*;
+pub use display_ext::{DisplayTransformation, DisplayTransformationItem, TextAlignment};
 use glam::{DVec3, Vec3};
 use paste::paste;
 use rustc_hash::FxHashMap;
@@ -63,6 +68,7 @@ pub struct ClearEntityChangesSet;
 impl Plugin for EntityPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(EntityManager::new())
+            .add_event::<effects::EffectExpiredEvent>()
             .configure_sets((
                 InitEntitiesSet.in_base_set(CoreSet::PostUpdate),
                 UpdateTrackedDataSet.in_base_set(CoreSet::PostUpdate),
@@ -76,11 +82,19 @@ impl Plugin for EntityPlugin {
                     .chain()
                     .in_set(InitEntitiesSet),
             )
+            .add_system(attributes::add_attributes.in_set(InitEntitiesSet))
+            .add_system(equipment::add_equipment.in_set(InitEntitiesSet))
+            .add_system(add_entity_damages.in_set(InitEntitiesSet))
+            .add_system(effects::tick_effects.in_base_set(CoreSet::PreUpdate))
             .add_systems(
                 (
                     clear_status_changes,
                     clear_animation_changes,
+                    clear_damage_changes,
                     clear_tracked_data_changes,
+                    attributes::clear_attribute_changes,
+                    effects::clear_effect_changes,
+                    equipment::clear_equipment_changes,
                     update_old_position,
                     update_old_location,
                 )
@@ -164,6 +178,30 @@ fn clear_animation_changes(
     }
 }
 
+/// Gives living entities an empty list of damage.
+#[allow(clippy::type_complexity)]
+fn add_entity_damages(
+    entities: Query<
+        Entity,
+        (
+            Added<EntityKind>,
+            With<living::LivingEntity>,
+            Without<EntityDamages>,
+        ),
+    >,
+    mut commands: Commands,
+) {
+    for entity in &entities {
+        commands.entity(entity).insert(EntityDamages::default());
+    }
+}
+
+fn clear_damage_changes(mut damages: Query<&mut EntityDamages, Changed<EntityDamages>>) {
+    for mut damages in &mut damages {
+        damages.0.clear();
+    }
+}
+
 fn clear_tracked_data_changes(mut tracked_data: Query<&mut TrackedData, Changed<TrackedData>>) {
     for mut tracked_data in &mut tracked_data {
         tracked_data.clear_update_values();
@@ -383,6 +421,12 @@ impl EntityAnimations {
     }
 }
 
+/// The damage a living entity took this tick. Each damage plays the hurt
+/// animation and sound for the clients in view of the entity, except for the
+/// entity itself.
+#[derive(Component, Clone, Default, Debug)]
+pub struct EntityDamages(pub Vec<packet::EntityDamageS2c>);
+
 /// Extra integer data passed to the entity spawn packet. The meaning depends on
 /// the type of entity being spawned.
 ///
@@ -441,14 +485,15 @@ impl TrackedData {
         }
     }
 
-    pub fn insert_init_value(&mut self, index: u8, type_id: u8, value: impl Encode) {
+    /// Sets the initial value of the tracked data at `index`. Returns `false`
+    /// if the value was already set to the same encoded value, in which case
+    /// nothing changes and no update needs to be sent.
+    pub fn insert_init_value(&mut self, index: u8, type_id: u8, value: impl Encode) -> bool {
         debug_assert!(
             index != 0xff,
             "index of 0xff is reserved for the terminator"
         );
 
-        self.remove_init_value(index);
-
         self.init_data.pop(); // Remove terminator.
 
         // Append the new value to the end.
@@ -461,28 +506,51 @@ impl TrackedData {
 
         let len = self.init_data.len() - len_before;
 
+        if let Some((pos, range)) = self.find_init_entry(index) {
+            if self.init_data[range.clone()] == self.init_data[len_before..] {
+                // Same value as before. Undo the append.
+                self.init_data.truncate(len_before);
+                self.init_data.push(0xff);
+
+                return false;
+            }
+
+            self.init_data.drain(range);
+            self.init_entries.remove(pos);
+        }
+
         self.init_entries.push((index, len as u32));
 
         self.init_data.push(0xff); // Add terminator.
+
+        true
     }
 
     pub fn remove_init_value(&mut self, index: u8) -> bool {
+        if let Some((pos, range)) = self.find_init_entry(index) {
+            self.init_data.drain(range);
+            self.init_entries.remove(pos);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the position of the entry for `index` in `init_entries` and its
+    /// byte range in `init_data`.
+    fn find_init_entry(&self, index: u8) -> Option<(usize, Range<usize>)> {
         let mut start = 0;
 
         for (pos, &(idx, len)) in self.init_entries.iter().enumerate() {
             if idx == index {
-                let end = start + len as usize;
-
-                self.init_data.drain(start..end);
-                self.init_entries.remove(pos);
-
-                return true;
+                return Some((pos, start..start + len as usize));
             }
 
             start += len as usize;
         }
 
-        false
+        None
     }
 
     pub fn append_update_value(&mut self, index: u8, type_id: u8, value: impl Encode) {
@@ -896,6 +964,23 @@ mod tests {
         assert!(td.update_data.is_empty());
     }
 
+    #[test]
+    fn insert_same_init_tracked_data() {
+        let mut td = TrackedData::default();
+
+        assert!(td.insert_init_value(0, 3, "foo"));
+        assert!(td.insert_init_value(5, 9, "baz"));
+        assert!(!td.insert_init_value(0, 3, "foo"));
+        assert!(td.insert_init_value(0, 3, "bar"));
+        assert!(!td.insert_init_value(5, 9, "baz"));
+
+        let mut expected = TrackedData::default();
+        expected.insert_init_value(5, 9, "baz");
+        expected.insert_init_value(0, 3, "bar");
+
+        assert_eq!(td.init_data(), expected.init_data());
+    }
+
     #[test]
     fn get_set_flags() {
         let mut flags = entity::Flags(0);
@@ -0,0 +1,175 @@
+//! # Experience
+//! Experience levels for clients and experience orbs.
+//!
+//! Clients are given an [`Experience`] component when they join, which is sent
+//! to the client whenever it changes. The helpers in this crate convert between
+//! levels and experience points using the vanilla level curve.
+//!
+//! Experience orbs are spawned with an [`ExperienceOrbBundle`]. Orbs fly
+//! towards the nearest client within 8 blocks and give it their experience
+//! when picked up, sending an [`ExperiencePickupEvent`].
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use glam::DVec3;
+//! # use valence_experience::{split_experience, ExperienceOrbBundle};
+//! fn drop_experience(commands: &mut Commands, instance: Entity, pos: DVec3, amount: u32) {
+//!     for value in split_experience(amount) {
+//!         commands.spawn(ExperienceOrbBundle::new(instance, pos, value));
+//!     }
+//! }
+//! ```
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_client::packet::ExperienceBarUpdateS2c;
+use valence_client::{Client, FlushPacketsSet, UpdateClientsSet};
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::Location;
+
+mod orb;
+
+pub use orb::{split_experience, ExperienceOrb, ExperienceOrbBundle, ExperiencePickupEvent};
+
+pub struct ExperiencePlugin;
+
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(init_experience.in_base_set(CoreSet::PreUpdate))
+            .add_system(
+                update_experience
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(UpdateClientsSet)
+                    .before(FlushPacketsSet),
+            );
+
+        orb::build(app);
+    }
+}
+
+/// The experience of a client.
+///
+/// The level, progress and total are independent like in vanilla, so the
+/// level can be changed without changing the total points. Use
+/// [`Experience::from_total`] to derive the level from the total.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Experience {
+    /// The level shown above the experience bar.
+    pub level: u32,
+    /// The progress towards the next level, from 0 to 1.
+    pub progress: f32,
+    /// The total experience points, shown on the death screen.
+    pub total: u32,
+}
+
+impl Experience {
+    /// Returns the experience of a client with `total` points that were all
+    /// collected from level zero.
+    pub fn from_total(total: u32) -> Self {
+        let mut level = 0;
+        let mut remaining = total;
+
+        while remaining >= points_to_next_level(level) {
+            remaining -= points_to_next_level(level);
+            level += 1;
+        }
+
+        Self {
+            level,
+            progress: remaining as f32 / points_to_next_level(level) as f32,
+            total,
+        }
+    }
+
+    /// Returns the experience at the start of `level`.
+    pub fn from_level(level: u32) -> Self {
+        Self {
+            level,
+            progress: 0.0,
+            total: total_points_for_level(level),
+        }
+    }
+
+    /// Adds experience points, filling the experience bar and gaining levels
+    /// like picking up an experience orb does.
+    pub fn add_points(&mut self, points: u32) {
+        self.total = self.total.saturating_add(points);
+        self.progress += points as f32 / points_to_next_level(self.level) as f32;
+
+        while self.progress >= 1.0 {
+            self.progress = (self.progress - 1.0) * points_to_next_level(self.level) as f32;
+            self.level += 1;
+            self.progress /= points_to_next_level(self.level) as f32;
+        }
+    }
+}
+
+/// Returns the number of experience points needed to go from `level` to the
+/// next level.
+pub fn points_to_next_level(level: u32) -> u32 {
+    if level >= 30 {
+        112 + (level - 30) * 9
+    } else if level >= 15 {
+        37 + (level - 15) * 5
+    } else {
+        7 + level * 2
+    }
+}
+
+/// Returns the total number of experience points needed to reach `level` from
+/// level zero.
+pub fn total_points_for_level(level: u32) -> u32 {
+    let l = level as f64;
+
+    let total = if level <= 16 {
+        l * l + 6.0 * l
+    } else if level <= 31 {
+        2.5 * l * l - 40.5 * l + 360.0
+    } else {
+        4.5 * l * l - 162.5 * l + 2220.0
+    };
+
+    total as u32
+}
+
+fn init_experience(
+    clients: Query<Entity, (Added<Client>, Without<Experience>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(Experience::default());
+    }
+}
+
+/// Sends the experience to clients when it changes, or when the client
+/// respawned and forgot it.
+fn update_experience(
+    mut clients: Query<(&mut Client, &Experience), Or<(Changed<Experience>, Changed<Location>)>>,
+) {
+    for (mut client, experience) in &mut clients {
+        client.write_packet(&ExperienceBarUpdateS2c {
+            bar: experience.progress,
+            level: VarInt(experience.level as i32),
+            total_xp: VarInt(experience.total as i32),
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeSet;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use valence_client::Client;
+use valence_core::aabb::Aabb;
+use valence_core::despawn::Despawned;
+use valence_core::game_mode::GameMode;
+use valence_core::protocol::packet::sound::{Sound, SoundCategory};
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::entity::NoGravity;
+use valence_entity::experience_orb::ExperienceOrbEntityBundle;
+use valence_entity::hitbox::Hitbox;
+use valence_entity::packet::ItemPickupAnimationS2c;
+use valence_entity::{EntityId, Location, ObjectData, OnGround, Position};
+use valence_instance::Instance;
+
+use crate::Experience;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<ExperiencePickupEvent>()
+        .add_system(tick_experience_orbs.in_base_set(CoreSet::Update));
+}
+
+/// How close a client needs to be for an orb to fly towards it.
+const ATTRACT_DISTANCE: f64 = 8.0;
+
+/// Orbs despawn after existing for this many ticks.
+const MAX_AGE: u32 = 6000;
+
+/// The orb sizes used by [`split_experience`], from largest to smallest.
+const ORB_VALUES: [u32; 11] = [2477, 1237, 617, 307, 149, 73, 37, 17, 7, 3, 1];
+
+/// Makes an experience orb entity fly towards nearby clients and give them its
+/// experience when picked up.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct ExperienceOrb {
+    /// The experience points given to the client that picks up the orb.
+    pub value: u32,
+    /// The velocity of the orb in blocks per tick.
+    velocity: DVec3,
+    age: u32,
+}
+
+impl ExperienceOrb {
+    pub fn new(value: u32) -> Self {
+        Self {
+            value,
+            velocity: DVec3::ZERO,
+            age: 0,
+        }
+    }
+}
+
+/// The components needed to spawn an experience orb.
+#[derive(Bundle)]
+pub struct ExperienceOrbBundle {
+    pub orb: ExperienceOrb,
+    pub entity: ExperienceOrbEntityBundle,
+}
+
+impl ExperienceOrbBundle {
+    /// An orb worth `value` points in the instance `location`.
+    pub fn new(location: Entity, position: DVec3, value: u32) -> Self {
+        Self {
+            orb: ExperienceOrb::new(value),
+            entity: ExperienceOrbEntityBundle {
+                location: Location(location),
+                position: Position(position),
+                // Determines the size of the orb on the client.
+                object_data: ObjectData(value as i32),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Sent when a client picks up an experience orb. The orb's experience is
+/// added to the client's [`Experience`] automatically.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ExperiencePickupEvent {
+    pub client: Entity,
+    pub orb: Entity,
+    pub value: u32,
+}
+
+/// Splits `amount` experience points into the values of orbs like vanilla
+/// does when dropping experience.
+pub fn split_experience(mut amount: u32) -> impl Iterator<Item = u32> {
+    std::iter::from_fn(move || {
+        let value = *ORB_VALUES.iter().find(|&&v| v <= amount)?;
+        amount -= value;
+        Some(value)
+    })
+}
+
+fn tick_experience_orbs(
+    mut orbs: Query<(
+        Entity,
+        &mut ExperienceOrb,
+        &mut Position,
+        &mut OnGround,
+        &Location,
+        &EntityId,
+        Option<&NoGravity>,
+    )>,
+    mut clients: Query<
+        (
+            Entity,
+            &Position,
+            &Location,
+            &GameMode,
+            &EntityId,
+            Option<&Hitbox>,
+            &mut Experience,
+        ),
+        (With<Client>, Without<ExperienceOrb>),
+    >,
+    mut instances: Query<&mut Instance>,
+    mut commands: Commands,
+    mut events: EventWriter<ExperiencePickupEvent>,
+) {
+    // Clients pick up at most one orb per tick.
+    let mut collected = BTreeSet::new();
+
+    for (orb_ent, mut orb, mut pos, mut on_ground, loc, orb_id, no_gravity) in &mut orbs {
+        orb.age += 1;
+
+        if orb.age >= MAX_AGE {
+            commands.entity(orb_ent).insert(Despawned);
+            continue;
+        }
+
+        let Ok(mut instance) = instances.get_mut(loc.0) else {
+            continue
+        };
+
+        if !no_gravity.is_some_and(|g| g.0) {
+            orb.velocity.y -= 0.03;
+        }
+
+        let nearest = clients
+            .iter()
+            .filter(|(_, _, client_loc, game_mode, ..)| {
+                client_loc.0 == loc.0 && **game_mode != GameMode::Spectator
+            })
+            .map(|(client, client_pos, ..)| (client, client_pos.0.distance(pos.0)))
+            .filter(|&(_, dist)| dist < ATTRACT_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((client, _)) = nearest {
+            let (_, client_pos, ..) = clients.get(client).unwrap();
+
+            // Orbs fly towards the middle of the client.
+            let diff = client_pos.0 + DVec3::new(0.0, 0.81, 0.0) - pos.0;
+            let dist = diff.length();
+
+            if dist > 0.0 && dist < ATTRACT_DISTANCE {
+                let strength = 1.0 - dist / ATTRACT_DISTANCE;
+                orb.velocity += diff / dist * strength * strength * 0.1;
+            }
+        }
+
+        let aabb = Aabb::from_bottom_size(pos.0, [0.5, 0.5, 0.5]);
+        let movement = instance.move_entity(aabb, orb.velocity, 0.0);
+
+        pos.0 += movement.delta;
+        on_ground.0 = movement.on_ground;
+
+        if movement.collided.x {
+            orb.velocity.x = 0.0;
+        }
+        if movement.collided.y {
+            orb.velocity.y = 0.0;
+        }
+        if movement.collided.z {
+            orb.velocity.z = 0.0;
+        }
+
+        let friction = if movement.on_ground { 0.6 * 0.98 } else { 0.98 };
+
+        orb.velocity.x *= friction;
+        orb.velocity.y *= 0.98;
+        orb.velocity.z *= friction;
+
+        let Some((client, _)) = nearest else {
+            continue
+        };
+
+        if collected.contains(&client) {
+            continue;
+        }
+
+        let Ok((_, client_pos, _, _, client_id, hitbox, mut experience)) =
+            clients.get_mut(client) else {
+            continue
+        };
+
+        let client_aabb = hitbox.map_or_else(
+            || Aabb::from_bottom_size(client_pos.0, [0.6, 1.8, 0.6]),
+            |h| h.get(),
+        );
+
+        // The pickup area of players is larger than their hitbox.
+        let pickup_area = Aabb::new(
+            client_aabb.min - DVec3::new(1.0, 0.5, 1.0),
+            client_aabb.max + DVec3::new(1.0, 0.5, 1.0),
+        );
+
+        if !pickup_area.intersects(Aabb::from_bottom_size(pos.0, [0.5, 0.5, 0.5])) {
+            continue;
+        }
+
+        collected.insert(client);
+        experience.add_points(orb.value);
+
+        instance.write_packet_at(
+            &ItemPickupAnimationS2c {
+                collected_entity_id: VarInt(orb_id.get()),
+                collector_entity_id: VarInt(client_id.get()),
+                pickup_item_count: VarInt(1),
+            },
+            pos.chunk_pos(),
+        );
+
+        instance.play_sound(
+            pos.0,
+            Sound::EntityExperienceOrbPickup,
+            SoundCategory::Player,
+            0.1,
+            0.9,
+            // The sound has a single variant.
+            0,
+        );
+
+        commands.entity(orb_ent).insert(Despawned);
+
+        events.send(ExperiencePickupEvent {
+            client,
+            orb: orb_ent,
+            value: orb.value,
+        });
+    }
+}
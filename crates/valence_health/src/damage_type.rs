@@ -0,0 +1,173 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use valence_core::ident;
+use valence_core::ident::Ident;
+use valence_nbt::serde::CompoundSerializer;
+use valence_registry::codec::{RegistryCodec, RegistryValue};
+use valence_registry::{Registry, RegistryIdx, RegistrySet};
+
+pub(super) fn build(app: &mut App) {
+    app.init_resource::<DamageTypeRegistry>()
+        .add_startup_system(load_default_damage_types.in_base_set(StartupSet::PreStartup))
+        .add_system(
+            update_damage_type_registry
+                .in_base_set(CoreSet::PostUpdate)
+                .before(RegistrySet),
+        );
+}
+
+/// Loads the default damage types from the registry codec.
+fn load_default_damage_types(mut reg: ResMut<DamageTypeRegistry>, codec: Res<RegistryCodec>) {
+    let mut helper = move || -> anyhow::Result<()> {
+        for value in codec.registry(DamageTypeRegistry::KEY) {
+            let damage_type = DamageType::deserialize(value.element.clone())?;
+
+            reg.insert(value.name.clone(), damage_type);
+        }
+
+        Ok(())
+    };
+
+    if let Err(e) = helper() {
+        error!("failed to load default damage types from registry codec: {e:#}");
+    }
+}
+
+/// Updates the registry codec as the damage type registry is modified by
+/// users.
+fn update_damage_type_registry(reg: Res<DamageTypeRegistry>, mut codec: ResMut<RegistryCodec>) {
+    if reg.is_changed() {
+        let damage_types = codec.registry_mut(DamageTypeRegistry::KEY);
+
+        damage_types.clear();
+
+        damage_types.extend(reg.iter().map(|(_, name, damage_type)| {
+            RegistryValue {
+                name: name.into(),
+                element: damage_type
+                    .serialize(CompoundSerializer)
+                    .expect("failed to serialize damage type"),
+            }
+        }));
+    }
+}
+
+/// The damage types known to clients. The ID of a damage type is its position
+/// in this registry.
+#[derive(Resource, Default, Debug)]
+pub struct DamageTypeRegistry {
+    reg: Registry<DamageTypeId, DamageType>,
+}
+
+impl DamageTypeRegistry {
+    pub const KEY: Ident<&str> = ident!("damage_type");
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct DamageTypeId(u16);
+
+impl DamageTypeId {
+    /// Returns the ID sent to clients for this damage type.
+    pub fn to_raw(self) -> u16 {
+        self.0
+    }
+}
+
+impl RegistryIdx for DamageTypeId {
+    const MAX: usize = u16::MAX as _;
+
+    fn to_index(self) -> usize {
+        self.0 as _
+    }
+
+    fn from_index(idx: usize) -> Self {
+        Self(idx as _)
+    }
+}
+
+impl Deref for DamageTypeRegistry {
+    type Target = Registry<DamageTypeId, DamageType>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reg
+    }
+}
+
+impl DerefMut for DamageTypeRegistry {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.reg
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DamageType {
+    /// Used in the translation key of the death message, such as
+    /// `death.attack.<message_id>`.
+    pub message_id: String,
+    pub scaling: DamageScaling,
+    /// The food exhaustion caused by taking damage of this type.
+    pub exhaustion: f32,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub effects: DamageEffects,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub death_message_type: DeathMessageType,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        Self {
+            message_id: "generic".into(),
+            scaling: DamageScaling::default(),
+            exhaustion: 0.0,
+            effects: DamageEffects::default(),
+            death_message_type: DeathMessageType::default(),
+        }
+    }
+}
+
+fn is_default<T: Default + PartialEq>(t: &T) -> bool {
+    *t == T::default()
+}
+
+/// Whether the amount of damage depends on the difficulty.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageScaling {
+    Never,
+    #[default]
+    WhenCausedByLivingNonPlayer,
+    Always,
+}
+
+/// The sound played by the client when damage is taken.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageEffects {
+    #[default]
+    Hurt,
+    Thorns,
+    Drowning,
+    Burning,
+    Poking,
+    Freezing,
+}
+
+/// How the death message for a damage type is built.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathMessageType {
+    /// `death.attack.<message_id>`, or `death.attack.<message_id>.player` if
+    /// there is an attacker.
+    #[default]
+    Default,
+    /// `death.fell.accident.generic`.
+    FallVariants,
+    /// `death.attack.<message_id>.message` followed by a link to the bug
+    /// report.
+    IntentionalGameDesign,
+}
@@ -0,0 +1,412 @@
+//! # Health
+//! Health, food, and damage for living entities.
+//!
+//! The health of a living entity is stored in its [`Health`] component and is
+//! kept between zero and the entity's max health attribute. Clients are also
+//! given [`Food`], [`Saturation`] and [`Exhaustion`] components when they
+//! join. Changes to the health and food of a client are sent to it
+//! automatically.
+//!
+//! Damage is dealt by sending a [`DamageEvent`]. Damaged entities play the
+//! hurt animation and sound for nearby clients, and a [`DeathEvent`] is sent
+//! once an entity's health runs out. Clients that die are shown the vanilla
+//! death message for the type of damage that killed them.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_core::ident;
+//! # use valence_health::{DamageEvent, DamageTypeRegistry};
+//! fn damage_everyone(
+//!     entities: Query<Entity, With<valence_health::Health>>,
+//!     damage_types: Res<DamageTypeRegistry>,
+//!     mut damage: EventWriter<DamageEvent>,
+//! ) {
+//!     let Some(magic) = damage_types.index_of(ident!("magic")) else {
+//!         return
+//!     };
+//!
+//!     for entity in &entities {
+//!         damage.send(DamageEvent::new(entity, 1.0, magic));
+//!     }
+//! }
+//! ```
+//!
+//! Dead entities ignore damage until their health is set above zero again,
+//! such as when a client respawns.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use valence_client::packet::{DamageTiltS2c, HealthUpdateS2c};
+use valence_client::{Client, FlushPacketsSet, UpdateClientsSet, Username};
+use valence_core::game_mode::GameMode;
+use valence_core::ident;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_entity::attributes::{Attributes, EntityAttribute};
+use valence_entity::packet::EntityDamageS2c;
+use valence_entity::{
+    EntityDamages, EntityId, EntityKind, EntityStatus, EntityStatuses, InitEntitiesSet, Location,
+    Look, Position, UpdateTrackedDataSet,
+};
+use valence_instance::WriteUpdatePacketsToInstancesSet;
+use valence_registry::tags::TagsRegistry;
+
+mod damage_type;
+
+pub use damage_type::{
+    DamageEffects, DamageScaling, DamageType, DamageTypeId, DamageTypeRegistry, DeathMessageType,
+};
+pub use valence_entity::living::Health;
+
+pub struct HealthPlugin;
+
+/// When damage is applied and the health and food of entities are updated in
+/// [`CoreSet::PostUpdate`]. Systems that send [`DamageEvent`]s should run
+/// _before_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct HealthSet;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            .configure_set(
+                HealthSet
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(InitEntitiesSet)
+                    .before(UpdateTrackedDataSet)
+                    .before(WriteUpdatePacketsToInstancesSet)
+                    .before(UpdateClientsSet),
+            )
+            .add_system(init_food.in_base_set(CoreSet::PreUpdate))
+            .add_systems(
+                (apply_damage, clamp_health, apply_exhaustion)
+                    .chain()
+                    .in_set(HealthSet),
+            )
+            .add_system(
+                send_health_updates
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(UpdateClientsSet)
+                    .before(FlushPacketsSet),
+            );
+
+        damage_type::build(app);
+    }
+}
+
+/// The food level of a client, from 0 to 20.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Food(pub i32);
+
+impl Default for Food {
+    fn default() -> Self {
+        Self(20)
+    }
+}
+
+/// The food saturation of a client. Saturation is used up before the food
+/// level.
+#[derive(Component, Copy, Clone, PartialEq, Debug)]
+pub struct Saturation(pub f32);
+
+impl Default for Saturation {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+/// The food exhaustion of a client. Every 4 points of exhaustion use up a
+/// point of [`Saturation`], or a point of [`Food`] if there is no saturation
+/// left.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Exhaustion(pub f32);
+
+/// Deals damage to a living entity.
+///
+/// Clients in creative or spectator mode are only damaged by damage types in
+/// the `minecraft:bypasses_invulnerability` tag.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+    pub damage_type: DamageTypeId,
+    /// The entity responsible for the damage, such as the player that shot an
+    /// arrow.
+    pub attacker: Option<Entity>,
+    /// The entity that directly dealt the damage, such as the arrow. Usually
+    /// the same as [`Self::attacker`].
+    pub direct: Option<Entity>,
+    /// The position the damage came from when there is no attacker.
+    pub source_pos: Option<DVec3>,
+}
+
+impl DamageEvent {
+    /// Damage without an attacker.
+    pub fn new(entity: Entity, amount: f32, damage_type: DamageTypeId) -> Self {
+        Self {
+            entity,
+            amount,
+            damage_type,
+            attacker: None,
+            direct: None,
+            source_pos: None,
+        }
+    }
+
+    /// Damage dealt directly by `attacker`, such as a melee attack.
+    pub fn by(mut self, attacker: Entity) -> Self {
+        self.attacker = Some(attacker);
+        self.direct = Some(attacker);
+        self
+    }
+}
+
+/// Sent when the health of a living entity is brought to zero by a
+/// [`DamageEvent`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    /// The type of the damage that killed the entity.
+    pub damage_type: DamageTypeId,
+    /// The entity responsible for the killing blow, if any.
+    pub attacker: Option<Entity>,
+}
+
+fn init_food(clients: Query<Entity, (Added<Client>, Without<Food>)>, mut commands: Commands) {
+    for client in &clients {
+        commands.entity(client).insert((
+            Food::default(),
+            Saturation::default(),
+            Exhaustion::default(),
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut victims: Query<(
+        &mut Health,
+        &EntityId,
+        &Position,
+        Option<&Look>,
+        Option<&GameMode>,
+        Option<&mut Client>,
+        Option<&mut Exhaustion>,
+        Option<&mut EntityStatuses>,
+        Option<&mut EntityDamages>,
+    )>,
+    names: Query<(Option<&Username>, Option<&EntityKind>)>,
+    sources: Query<(&EntityId, &Position)>,
+    damage_types: Res<DamageTypeRegistry>,
+    tags: Res<TagsRegistry>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    for event in events.iter() {
+        let Ok((mut health, id, pos, look, game_mode, mut client, exhaustion, statuses, damages)) =
+            victims.get_mut(event.entity) else {
+            continue
+        };
+
+        if health.0 <= 0.0 {
+            continue;
+        }
+
+        if matches!(game_mode, Some(GameMode::Creative | GameMode::Spectator))
+            && !tags
+                .tag(ident!("damage_type"), ident!("bypasses_invulnerability"))
+                .is_some_and(|tag| tag.contains(event.damage_type.to_raw() as i32))
+        {
+            continue;
+        }
+
+        let Some(damage_type) = damage_types.get_by_index(event.damage_type) else {
+            continue
+        };
+
+        health.0 = (health.0 - event.amount).max(0.0);
+
+        if let Some(mut exhaustion) = exhaustion {
+            exhaustion.0 += damage_type.exhaustion;
+        }
+
+        let source_id = |source: Option<Entity>| {
+            source
+                .and_then(|e| sources.get(e).ok())
+                .map_or(0, |(id, _)| id.get() + 1)
+        };
+
+        let source_pos = event
+            .attacker
+            .and_then(|e| sources.get(e).ok())
+            .map(|(_, pos)| pos.0)
+            .or(event.source_pos);
+
+        let mut pkt = EntityDamageS2c {
+            entity_id: VarInt(id.get()),
+            source_type_id: VarInt(event.damage_type.to_raw() as i32),
+            source_cause_id: VarInt(source_id(event.attacker)),
+            source_direct_id: VarInt(source_id(event.direct)),
+            source_pos: event.source_pos,
+        };
+
+        // Plays the hurt animation and sound for the players in view. This is
+        // sent with the update packets of the entity, which skip the victim.
+        if let Some(mut damages) = damages {
+            damages.0.push(pkt);
+        }
+
+        if let Some(client) = &mut client {
+            // Clients refer to themselves with an entity ID of zero.
+            pkt.entity_id = VarInt(0);
+            client.write_packet(&pkt);
+
+            let yaw = match source_pos {
+                Some(source_pos) => {
+                    let diff = source_pos - pos.0;
+                    diff.z.atan2(diff.x).to_degrees() as f32 - look.map_or(0.0, |l| l.yaw)
+                }
+                None => 0.0,
+            };
+
+            client.write_packet(&DamageTiltS2c {
+                entity_id: VarInt(0),
+                yaw,
+            });
+        }
+
+        if health.0 > 0.0 {
+            continue;
+        }
+
+        if let Some(mut statuses) = statuses {
+            statuses.trigger(EntityStatus::PlayDeathSoundOrAddProjectileHitParticles);
+        }
+
+        if let Some(client) = &mut client {
+            let name = |entity| match names.get(entity) {
+                Ok((Some(username), _)) => Text::text(username.0.clone()),
+                Ok((None, Some(kind))) => match kind.translation_key() {
+                    Some(key) => Text::translate(key, []),
+                    None => Text::text(format!("{kind:?}")),
+                },
+                _ => Text::text(""),
+            };
+
+            client.kill(death_message(
+                damage_type,
+                name(event.entity),
+                event.attacker.map(name),
+            ));
+        }
+
+        deaths.send(DeathEvent {
+            entity: event.entity,
+            damage_type: event.damage_type,
+            attacker: event.attacker,
+        });
+    }
+}
+
+/// Builds the vanilla death message for a victim killed by damage of the given
+/// type.
+pub fn death_message(damage_type: &DamageType, victim: Text, attacker: Option<Text>) -> Text {
+    let id = &damage_type.message_id;
+
+    match damage_type.death_message_type {
+        DeathMessageType::Default => match attacker {
+            Some(attacker) => {
+                Text::translate(format!("death.attack.{id}.player"), [victim, attacker])
+            }
+            None => Text::translate(format!("death.attack.{id}"), [victim]),
+        },
+        DeathMessageType::FallVariants => Text::translate("death.fell.accident.generic", [victim]),
+        DeathMessageType::IntentionalGameDesign => Text::translate(
+            format!("death.attack.{id}.message"),
+            [
+                victim,
+                Text::text("[") + Text::translate("death.attack.badRespawnPoint.link", []) + "]",
+            ],
+        ),
+    }
+}
+
+/// Keeps the health of living entities between zero and their max health.
+fn clamp_health(
+    mut entities: Query<
+        (&mut Health, Option<&Attributes>),
+        Or<(Changed<Health>, Changed<Attributes>)>,
+    >,
+) {
+    for (mut health, attributes) in &mut entities {
+        let max = attributes.map_or(EntityAttribute::MaxHealth.default_value(), |a| {
+            a.get(EntityAttribute::MaxHealth)
+        }) as f32;
+
+        let clamped = health.0.clamp(0.0, max);
+
+        if health.0 != clamped {
+            health.0 = clamped;
+        }
+    }
+}
+
+fn apply_exhaustion(
+    mut clients: Query<(&mut Exhaustion, &mut Saturation, &mut Food), Changed<Exhaustion>>,
+) {
+    for (mut exhaustion, mut saturation, mut food) in &mut clients {
+        while exhaustion.0 >= 4.0 {
+            exhaustion.0 -= 4.0;
+
+            if saturation.0 > 0.0 {
+                saturation.0 = (saturation.0 - 1.0).max(0.0);
+            } else if food.0 > 0 {
+                food.0 -= 1;
+            }
+        }
+    }
+}
+
+/// Sends the health and food to clients when they change, or when the client
+/// respawned and forgot them.
+#[allow(clippy::type_complexity)]
+fn send_health_updates(
+    mut clients: Query<
+        (&mut Client, &Health, &Food, &Saturation),
+        Or<(
+            Changed<Health>,
+            Changed<Food>,
+            Changed<Saturation>,
+            Changed<Location>,
+        )>,
+    >,
+) {
+    for (mut client, health, food, saturation) in &mut clients {
+        client.write_packet(&HealthUpdateS2c {
+            health: health.0,
+            food: VarInt(food.0),
+            food_saturation: saturation.0,
+        });
+    }
+}
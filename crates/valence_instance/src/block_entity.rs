@@ -0,0 +1,485 @@
+//! Typed views over the NBT data of common block entities.
+//!
+//! Block entity data is stored as raw NBT in a [`BlockEntity`]. The types in
+//! this module read and write the parts of that NBT which the vanilla client
+//! cares about, so that signs, chests, skulls, and banners can be manipulated
+//! without hand-assembling compounds.
+//!
+//! ```
+//! use valence_block::BlockState;
+//! use valence_core::text::{Color, TextFormat};
+//! use valence_instance::block_entity::{BlockEntityData, SignText};
+//! use valence_instance::Block;
+//!
+//! let mut sign = SignText::default();
+//! sign.front.lines[0] = "Hello".color(Color::RED);
+//!
+//! let block = Block::with_nbt(BlockState::OAK_SIGN, sign.to_nbt());
+//! ```
+
+use valence_block::BlockEntityKind;
+use valence_core::ident::Ident;
+pub use valence_core::item::SkullProfile;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::text::Text;
+use valence_nbt::{compound, Compound, List, Value};
+
+use crate::BlockEntity;
+
+/// A typed representation of (part of) the NBT data of a block entity.
+pub trait BlockEntityData: Sized {
+    /// Returns `true` if block entities of the given kind can be represented
+    /// by this type.
+    fn is_kind(kind: BlockEntityKind) -> bool;
+
+    /// Reads this type from block entity NBT. Missing or malformed entries are
+    /// replaced with their default values.
+    fn from_nbt(nbt: &Compound) -> Self;
+
+    /// Writes this type into block entity NBT. Only the entries owned by this
+    /// type are overwritten. Other entries in `nbt` are left untouched.
+    fn write_nbt(&self, nbt: &mut Compound);
+
+    /// Writes this type into a new compound.
+    fn to_nbt(&self) -> Compound {
+        let mut nbt = Compound::new();
+        self.write_nbt(&mut nbt);
+        nbt
+    }
+}
+
+impl BlockEntity {
+    /// Reads the NBT of this block entity as `T`. Returns `None` if `T` cannot
+    /// represent block entities of this kind.
+    pub fn data<T: BlockEntityData>(&self) -> Option<T> {
+        T::is_kind(self.kind).then(|| T::from_nbt(&self.nbt))
+    }
+
+    /// Writes `data` into the NBT of this block entity. Returns `false` with no
+    /// effect if `T` cannot represent block entities of this kind.
+    pub fn set_data<T: BlockEntityData>(&mut self, data: &T) -> bool {
+        if T::is_kind(self.kind) {
+            data.write_nbt(&mut self.nbt);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The sixteen dye colors. Used by signs, banners, and other colored blocks.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    #[default]
+    Black,
+}
+
+impl DyeColor {
+    pub const ALL: [Self; 16] = [
+        Self::White,
+        Self::Orange,
+        Self::Magenta,
+        Self::LightBlue,
+        Self::Yellow,
+        Self::Lime,
+        Self::Pink,
+        Self::Gray,
+        Self::LightGray,
+        Self::Cyan,
+        Self::Purple,
+        Self::Blue,
+        Self::Brown,
+        Self::Green,
+        Self::Red,
+        Self::Black,
+    ];
+
+    /// Returns the numeric ID of this color.
+    pub const fn id(self) -> i32 {
+        self as i32
+    }
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.get(usize::try_from(id).ok()?).copied()
+    }
+
+    /// Returns the name of this color as used in NBT data, such as
+    /// `"light_blue"`.
+    pub const fn to_str(self) -> &'static str {
+        match self {
+            Self::White => "white",
+            Self::Orange => "orange",
+            Self::Magenta => "magenta",
+            Self::LightBlue => "light_blue",
+            Self::Yellow => "yellow",
+            Self::Lime => "lime",
+            Self::Pink => "pink",
+            Self::Gray => "gray",
+            Self::LightGray => "light_gray",
+            Self::Cyan => "cyan",
+            Self::Purple => "purple",
+            Self::Blue => "blue",
+            Self::Brown => "brown",
+            Self::Green => "green",
+            Self::Red => "red",
+            Self::Black => "black",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.to_str() == name)
+    }
+}
+
+/// The text on both sides of a sign or hanging sign.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SignText {
+    pub front: SignSide,
+    pub back: SignSide,
+    /// Waxed signs cannot be edited by players.
+    pub is_waxed: bool,
+}
+
+/// The text on one side of a sign.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct SignSide {
+    /// The four lines of text, from top to bottom.
+    pub lines: [Text; 4],
+    pub color: DyeColor,
+    pub has_glowing_text: bool,
+}
+
+impl SignSide {
+    fn from_nbt(nbt: Option<&Value>) -> Self {
+        let mut side = Self::default();
+
+        let Some(Value::Compound(nbt)) = nbt else {
+            return side;
+        };
+
+        if let Some(Value::List(List::String(messages))) = nbt.get("messages") {
+            for (line, msg) in side.lines.iter_mut().zip(messages) {
+                *line = serde_json::from_str(msg).unwrap_or_default();
+            }
+        }
+
+        if let Some(Value::String(color)) = nbt.get("color") {
+            side.color = DyeColor::from_str(color).unwrap_or_default();
+        }
+
+        if let Some(Value::Byte(glowing)) = nbt.get("has_glowing_text") {
+            side.has_glowing_text = *glowing != 0;
+        }
+
+        side
+    }
+
+    fn to_nbt(&self) -> Compound {
+        let messages = self
+            .lines
+            .iter()
+            .map(|line| serde_json::to_string(line).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        compound! {
+            "messages" => List::String(messages),
+            "color" => self.color.to_str(),
+            "has_glowing_text" => self.has_glowing_text,
+        }
+    }
+}
+
+impl BlockEntityData for SignText {
+    fn is_kind(kind: BlockEntityKind) -> bool {
+        matches!(kind, BlockEntityKind::Sign | BlockEntityKind::HangingSign)
+    }
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        Self {
+            front: SignSide::from_nbt(nbt.get("front_text")),
+            back: SignSide::from_nbt(nbt.get("back_text")),
+            is_waxed: matches!(nbt.get("is_waxed"), Some(Value::Byte(b)) if *b != 0),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        nbt.insert("front_text", self.front.to_nbt());
+        nbt.insert("back_text", self.back.to_nbt());
+        nbt.insert("is_waxed", self.is_waxed);
+    }
+}
+
+/// The items stored in a container block entity such as a chest, barrel, or
+/// shulker box.
+///
+/// Note that the vanilla client never receives these items as part of the
+/// block entity. They are used by the server and when saving the chunk.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct ContainerContents {
+    /// Pairs of slot indices and the item stacks in those slots. Empty slots
+    /// are omitted.
+    pub items: Vec<(u8, ItemStack)>,
+}
+
+impl ContainerContents {
+    /// Returns the item in the given slot, if any.
+    pub fn slot(&self, idx: u8) -> Option<&ItemStack> {
+        self.items.iter().find(|(i, _)| *i == idx).map(|(_, s)| s)
+    }
+
+    /// Sets the item in the given slot. Passing `None` empties the slot.
+    /// Returns the previous item in the slot.
+    pub fn set_slot(&mut self, idx: u8, item: impl Into<Option<ItemStack>>) -> Option<ItemStack> {
+        let old = self
+            .items
+            .iter()
+            .position(|(i, _)| *i == idx)
+            .map(|pos| self.items.remove(pos).1);
+
+        if let Some(item) = item.into() {
+            if item.item != ItemKind::Air {
+                self.items.push((idx, item));
+                self.items.sort_by_key(|(i, _)| *i);
+            }
+        }
+
+        old
+    }
+}
+
+impl BlockEntityData for ContainerContents {
+    fn is_kind(kind: BlockEntityKind) -> bool {
+        matches!(
+            kind,
+            BlockEntityKind::Chest
+                | BlockEntityKind::TrappedChest
+                | BlockEntityKind::Barrel
+                | BlockEntityKind::ShulkerBox
+                | BlockEntityKind::Dispenser
+                | BlockEntityKind::Dropper
+                | BlockEntityKind::Hopper
+        )
+    }
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let mut items = vec![];
+
+        if let Some(Value::List(List::Compound(list))) = nbt.get("Items") {
+            for entry in list {
+                let Some(Value::Byte(slot)) = entry.get("Slot") else {
+                    continue
+                };
+
+                let Some(Value::String(id)) = entry.get("id") else {
+                    continue
+                };
+
+                let Some(kind) = Ident::new(id.as_str())
+                    .ok()
+                    .and_then(|id| ItemKind::from_str(id.path()))
+                else {
+                    continue
+                };
+
+                let count = match entry.get("Count") {
+                    Some(Value::Byte(count)) => *count as u8,
+                    _ => 1,
+                };
+
+                let tag = match entry.get("tag") {
+                    Some(Value::Compound(tag)) => Some(tag.clone()),
+                    _ => None,
+                };
+
+                items.push((*slot as u8, ItemStack::new(kind, count, tag)));
+            }
+        }
+
+        items.sort_by_key(|(i, _)| *i);
+
+        Self { items }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        let list = self
+            .items
+            .iter()
+            .map(|(slot, stack)| {
+                let mut entry = compound! {
+                    "Slot" => *slot as i8,
+                    "id" => format!("minecraft:{}", stack.item.to_str()),
+                    "Count" => stack.count() as i8,
+                };
+
+                if let Some(tag) = &stack.nbt {
+                    entry.insert("tag", tag.clone());
+                }
+
+                entry
+            })
+            .collect();
+
+        nbt.insert("Items", List::Compound(list));
+    }
+}
+
+impl BlockEntityData for SkullProfile {
+    fn is_kind(kind: BlockEntityKind) -> bool {
+        kind == BlockEntityKind::Skull
+    }
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        match nbt.get("SkullOwner") {
+            Some(Value::Compound(owner)) => Self::from_compound(owner),
+            _ => Self::default(),
+        }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        nbt.insert("SkullOwner", self.to_compound());
+    }
+}
+
+/// The layered patterns drawn on a banner, from bottom to top.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct BannerPatterns {
+    pub patterns: Vec<BannerPattern>,
+}
+
+/// A single pattern layer of a banner.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BannerPattern {
+    /// The short code of the pattern, such as `"bs"` for a bottom stripe or
+    /// `"cre"` for a creeper charge.
+    pub pattern: String,
+    pub color: DyeColor,
+}
+
+impl BannerPattern {
+    pub fn new(pattern: impl Into<String>, color: DyeColor) -> Self {
+        Self {
+            pattern: pattern.into(),
+            color,
+        }
+    }
+}
+
+impl BlockEntityData for BannerPatterns {
+    fn is_kind(kind: BlockEntityKind) -> bool {
+        kind == BlockEntityKind::Banner
+    }
+
+    fn from_nbt(nbt: &Compound) -> Self {
+        let mut patterns = vec![];
+
+        if let Some(Value::List(List::Compound(list))) = nbt.get("Patterns") {
+            for entry in list {
+                if let (Some(Value::String(pattern)), Some(Value::Int(color))) =
+                    (entry.get("Pattern"), entry.get("Color"))
+                {
+                    patterns.push(BannerPattern {
+                        pattern: pattern.clone(),
+                        color: DyeColor::from_id(*color).unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        Self { patterns }
+    }
+
+    fn write_nbt(&self, nbt: &mut Compound) {
+        let list = self
+            .patterns
+            .iter()
+            .map(|p| {
+                compound! {
+                    "Pattern" => p.pattern.clone(),
+                    "Color" => p.color.id(),
+                }
+            })
+            .collect();
+
+        nbt.insert("Patterns", List::Compound(list));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use valence_core::property::Property;
+    use valence_core::text::{Color, TextFormat};
+
+    use super::*;
+
+    #[test]
+    fn sign_text_round_trip() {
+        let mut sign = SignText::default();
+        sign.front.lines[1] = "foo".color(Color::RED);
+        sign.back.color = DyeColor::LightBlue;
+        sign.back.has_glowing_text = true;
+        sign.is_waxed = true;
+
+        let be = BlockEntity::new(BlockEntityKind::Sign, sign.to_nbt());
+
+        assert_eq!(be.data::<SignText>(), Some(sign));
+        assert_eq!(be.data::<BannerPatterns>(), None);
+    }
+
+    #[test]
+    fn container_contents_round_trip() {
+        let mut contents = ContainerContents::default();
+        contents.set_slot(5, ItemStack::new(ItemKind::Diamond, 3, None));
+        contents.set_slot(1, ItemStack::new(ItemKind::Stone, 64, Some(compound! {})));
+        contents.set_slot(7, ItemStack::new(ItemKind::Air, 1, None));
+
+        assert_eq!(contents.items.len(), 2);
+        assert_eq!(contents.slot(5).map(|s| s.item), Some(ItemKind::Diamond));
+
+        let mut be = BlockEntity::new(BlockEntityKind::Chest, compound! { "Lock" => "abc" });
+        assert!(be.set_data(&contents));
+
+        assert_eq!(be.data::<ContainerContents>(), Some(contents));
+        assert_eq!(be.nbt.get("Lock"), Some(&Value::String("abc".into())));
+    }
+
+    #[test]
+    fn skull_and_banner_round_trip() {
+        let skull = SkullProfile {
+            uuid: Some(Uuid::from_u128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210)),
+            name: Some("jeb_".into()),
+            textures: Some(Property {
+                name: "textures".into(),
+                value: "abc".into(),
+                signature: None,
+            }),
+        };
+
+        assert_eq!(SkullProfile::from_nbt(&skull.to_nbt()), skull);
+
+        let banner = BannerPatterns {
+            patterns: vec![
+                BannerPattern::new("bs", DyeColor::Red),
+                BannerPattern::new("cre", DyeColor::Black),
+            ],
+        };
+
+        assert_eq!(BannerPatterns::from_nbt(&banner.to_nbt()), banner);
+    }
+}
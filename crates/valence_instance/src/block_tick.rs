@@ -0,0 +1,90 @@
+//! Scheduled block ticks.
+//!
+//! A block tick is a request to be notified about a block position some
+//! number of game ticks in the future. Ticks are scheduled with
+//! [`Instance::schedule_block_tick`] or [`Chunk::schedule_tick`] and delivered
+//! as [`BlockTickEvent`]s in [`CoreSet::PreUpdate`]. Pending ticks are stored
+//! in the chunk they belong to, so they are kept when a chunk is unloaded with
+//! [`Chunk::to_unloaded`] and inserted back into an instance later.
+//!
+//! This is what vanilla uses for delayed block behavior such as flowing
+//! fluids, redstone repeaters, and falling blocks.
+//!
+//! [`Chunk::schedule_tick`]: crate::Chunk::schedule_tick
+//! [`Chunk::to_unloaded`]: crate::Chunk::to_unloaded
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_core::block_pos::BlockPos;
+
+use crate::Instance;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<BlockTickEvent>()
+        .add_system(run_scheduled_block_ticks.in_base_set(CoreSet::PreUpdate));
+}
+
+/// A block tick waiting to be delivered, stored in a [`Chunk`].
+///
+/// [`Chunk`]: crate::Chunk
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ScheduledTick {
+    /// Chunk-local X coordinate of the block in `0..16`.
+    pub x: u8,
+    /// Chunk-local Y coordinate of the block, starting from the bottom of the
+    /// chunk.
+    pub y: u32,
+    /// Chunk-local Z coordinate of the block in `0..16`.
+    pub z: u8,
+    /// Number of game ticks until this tick is delivered.
+    pub delay: u32,
+    /// Ticks delivered on the same game tick are sent in ascending order of
+    /// priority. Vanilla uses values from `-3` (extremely high) to `3`
+    /// (extremely low).
+    pub priority: i32,
+}
+
+/// Sent when a tick scheduled for a block position is due.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BlockTickEvent {
+    /// The [`Instance`] containing the block.
+    pub instance: Entity,
+    /// The absolute position of the block.
+    pub position: BlockPos,
+    /// The priority the tick was scheduled with.
+    pub priority: i32,
+}
+
+fn run_scheduled_block_ticks(
+    mut instances: Query<(Entity, &mut Instance)>,
+    mut events: EventWriter<BlockTickEvent>,
+) {
+    let mut due = vec![];
+
+    for (entity, mut instance) in &mut instances {
+        let min_y = instance.info.min_y;
+
+        for (pos, chunk) in instance.bypass_change_detection().chunks_mut() {
+            chunk.scheduled_ticks_mut().retain_mut(|tick| {
+                if tick.delay <= 1 {
+                    due.push(BlockTickEvent {
+                        instance: entity,
+                        position: BlockPos::new(
+                            pos.x * 16 + tick.x as i32,
+                            min_y + tick.y as i32,
+                            pos.z * 16 + tick.z as i32,
+                        ),
+                        priority: tick.priority,
+                    });
+                    false
+                } else {
+                    tick.delay -= 1;
+                    true
+                }
+            });
+        }
+
+        due.sort_by_key(|event| event.priority);
+        events.send_batch(due.drain(..));
+    }
+}
@@ -15,6 +15,7 @@ use valence_core::protocol::Encode;
 use valence_nbt::{compound, Compound};
 use valence_registry::RegistryIdx;
 
+use crate::block_tick::ScheduledTick;
 use crate::packet::{
     BlockEntityUpdateS2c, BlockUpdateS2c, ChunkDataBlockEntity, ChunkDataS2c, ChunkDeltaUpdateS2c,
 };
@@ -40,6 +41,8 @@ pub struct Chunk<const LOADED: bool = false> {
     /// Block entities in this chunk
     block_entities: BTreeMap<u32, BlockEntity>,
     modified_block_entities: BTreeSet<u32>,
+    /// Block ticks waiting to be delivered.
+    scheduled_ticks: Vec<ScheduledTick>,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -199,6 +202,7 @@ impl Chunk<false> {
             viewed: AtomicBool::new(false),
             block_entities: BTreeMap::new(),
             modified_block_entities: BTreeSet::new(),
+            scheduled_ticks: vec![],
         };
 
         chunk.resize(section_count);
@@ -219,6 +223,9 @@ impl Chunk<false> {
                 .resize_with(new_section_count, Section::default);
         } else {
             self.sections.truncate(new_section_count);
+
+            let height = (new_section_count * 16) as u32;
+            self.scheduled_ticks.retain(|tick| tick.y < height);
         }
     }
 
@@ -233,6 +240,7 @@ impl Chunk<false> {
             viewed: AtomicBool::new(false),
             block_entities: self.block_entities,
             modified_block_entities: self.modified_block_entities,
+            scheduled_ticks: self.scheduled_ticks,
         }
     }
 }
@@ -252,6 +260,7 @@ impl Clone for Chunk {
             viewed: AtomicBool::new(false),
             block_entities: self.block_entities.clone(),
             modified_block_entities: BTreeSet::new(),
+            scheduled_ticks: self.scheduled_ticks.clone(),
         }
     }
 }
@@ -266,7 +275,7 @@ impl Chunk<true> {
                 Section {
                     block_states: sect.block_states.clone(),
                     biomes: sect.biomes.clone(),
-                    non_air_count: 0,
+                    non_air_count: sect.non_air_count,
                     section_updates: vec![], // Don't clone the section updates.
                 }
             })
@@ -279,7 +288,70 @@ impl Chunk<true> {
             viewed: AtomicBool::new(false),
             block_entities: self.block_entities.clone(),
             modified_block_entities: BTreeSet::new(),
+            scheduled_ticks: self.scheduled_ticks.clone(),
+        }
+    }
+
+    /// Sets the blocks, biomes, block entities, and scheduled ticks of this
+    /// chunk to those of `other`. Clients in view of the chunk receive updates
+    /// for only the blocks that differ, unless the biomes differ too, in which
+    /// case the whole chunk is resent.
+    ///
+    /// Sections that still share their data with `other` are skipped without
+    /// comparing their blocks.
+    pub(super) fn restore(&mut self, other: &Chunk) {
+        let refresh = self.refresh
+            || self.sections.len() != other.sections.len()
+            || self
+                .sections
+                .iter()
+                .zip(&other.sections)
+                .any(|(sect, other_sect)| {
+                    !sect.biomes.shares_data(&other_sect.biomes)
+                        && (0..SECTION_BIOME_COUNT)
+                            .any(|i| sect.biomes.get(i) != other_sect.biomes.get(i))
+                });
+
+        if refresh {
+            self.refresh = true;
+            self.sections = other.sections.clone();
+            self.modified_block_entities.clear();
+
+            for sect in &mut self.sections {
+                sect.section_updates.clear();
+            }
+        } else {
+            for (sect, other_sect) in self.sections.iter_mut().zip(&other.sections) {
+                if sect.block_states.shares_data(&other_sect.block_states) {
+                    continue;
+                }
+
+                for idx in 0..SECTION_BLOCK_COUNT {
+                    let block = other_sect.block_states.get(idx);
+
+                    if block != sect.block_states.get(idx) {
+                        let (x, z, y) = (idx % 16, idx / 16 % 16, idx / 16 / 16);
+                        let compact = (block.to_raw() as i64) << 12 | (x << 8 | z << 4 | y) as i64;
+                        sect.section_updates.push(VarLong(compact));
+                    }
+                }
+
+                sect.block_states = other_sect.block_states.clone();
+                sect.biomes = other_sect.biomes.clone();
+                sect.non_air_count = other_sect.non_air_count;
+            }
+
+            // Removed block entities are removed by the block updates.
+            for (&idx, block_entity) in &other.block_entities {
+                if self.block_entities.get(&idx) != Some(block_entity) {
+                    self.modified_block_entities.insert(idx);
+                }
+            }
         }
+
+        self.block_entities = other.block_entities.clone();
+        self.scheduled_ticks = other.scheduled_ticks.clone();
+        self.cached_init_packets.get_mut().clear();
     }
 
     pub(super) fn clear_viewed(&mut self) {
@@ -297,6 +369,18 @@ impl Chunk<true> {
         *self.viewed.get_mut()
     }
 
+    /// Returns `true` if this chunk was modified during the current tick, i.e.
+    /// clients in view of the chunk will receive block or block entity
+    /// updates for it at the end of the tick.
+    pub fn is_modified(&self) -> bool {
+        self.refresh
+            || !self.modified_block_entities.is_empty()
+            || self
+                .sections
+                .iter()
+                .any(|sect| !sect.section_updates.is_empty())
+    }
+
     /// Marks this chunk as being seen by a client.
     #[doc(hidden)]
     pub fn mark_viewed(&self) {
@@ -318,6 +402,7 @@ impl Chunk<true> {
             viewed: AtomicBool::new(false),
             block_entities: self.block_entities,
             modified_block_entities: self.modified_block_entities,
+            scheduled_ticks: self.scheduled_ticks,
         }
     }
 
@@ -896,6 +981,43 @@ impl<const LOADED: bool> Chunk<LOADED> {
         self.refresh = true;
     }
 
+    /// Schedules a block tick for the block at the given chunk-local position
+    /// to be delivered in `delay` game ticks. A delay of zero is treated as a
+    /// delay of one.
+    ///
+    /// See [`ScheduledTick::priority`] for the meaning of `priority`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offsets are outside the bounds of the chunk. `x` and `z`
+    /// must be less than 16 while `y` must be less than `section_count() * 16`.
+    #[track_caller]
+    pub fn schedule_tick(&mut self, x: usize, y: usize, z: usize, delay: u32, priority: i32) {
+        assert!(
+            x < 16 && y < self.section_count() * 16 && z < 16,
+            "chunk block offsets of ({x}, {y}, {z}) are out of bounds"
+        );
+
+        self.scheduled_ticks.push(ScheduledTick {
+            x: x as u8,
+            y: y as u32,
+            z: z as u8,
+            delay: delay.max(1),
+            priority,
+        });
+    }
+
+    /// Returns the block ticks waiting to be delivered in this chunk.
+    pub fn scheduled_ticks(&self) -> &[ScheduledTick] {
+        &self.scheduled_ticks
+    }
+
+    /// Returns the block ticks waiting to be delivered in this chunk mutably.
+    /// Removing a tick from the returned list cancels it.
+    pub fn scheduled_ticks_mut(&mut self) -> &mut Vec<ScheduledTick> {
+        &mut self.scheduled_ticks
+    }
+
     /// Optimizes this chunk to use the minimum amount of memory possible. It
     /// has no observable effect on the contents of the chunk.
     ///
@@ -905,6 +1027,7 @@ impl<const LOADED: bool> Chunk<LOADED> {
     pub fn optimize(&mut self) {
         self.sections.shrink_to_fit();
         self.cached_init_packets.get_mut().shrink_to_fit();
+        self.scheduled_ticks.shrink_to_fit();
 
         for sect in &mut self.sections {
             sect.section_updates.shrink_to_fit();
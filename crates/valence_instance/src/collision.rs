@@ -0,0 +1,220 @@
+//! Collision between entities and the blocks of an instance.
+//!
+//! The collision shapes of a block state are given by
+//! [`BlockState::collision_shapes`] relative to the block position.
+//! [`Instance::move_entity`] sweeps a hitbox through the blocks of an instance
+//! the same way vanilla moves entities, so server-driven entities such as
+//! projectiles and NPCs don't pass through walls.
+//!
+//! Blocks in chunks that aren't loaded have no collision.
+//!
+//! [`BlockState::collision_shapes`]: valence_block::BlockState::collision_shapes
+
+use glam::{BVec3, DVec3};
+use valence_core::aabb::Aabb;
+use valence_core::block_pos::BlockPos;
+
+use crate::Instance;
+
+/// Distances smaller than this are considered touching.
+const EPSILON: f64 = 1.0e-7;
+
+/// The result of [`Instance::move_entity`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EntityMove {
+    /// The movement after resolving collisions.
+    pub delta: DVec3,
+    /// If the movement was stopped by a block on each axis.
+    pub collided: BVec3,
+    /// If the hitbox is standing on a block after the movement.
+    pub on_ground: bool,
+}
+
+impl Instance {
+    /// Returns the collision shapes of the block at `pos` in world space.
+    pub fn block_collision_shapes(&self, pos: impl Into<BlockPos>) -> Vec<Aabb> {
+        let pos = pos.into();
+
+        let Some(block) = self.block(pos) else {
+            return vec![]
+        };
+
+        let offset = DVec3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+
+        block
+            .state()
+            .collision_shapes()
+            .map(|shape| shape + offset)
+            .collect()
+    }
+
+    /// Returns the collision shapes of all blocks intersecting `area` in world
+    /// space.
+    pub fn collision_boxes(&self, area: Aabb) -> Vec<Aabb> {
+        let mut boxes = vec![];
+
+        let min = area.min.floor().as_ivec3();
+        let max = area.max.floor().as_ivec3();
+
+        // Some shapes such as fences extend into the block above.
+        for y in min.y - 1..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    boxes.extend(
+                        self.block_collision_shapes([x, y, z])
+                            .into_iter()
+                            .filter(|shape| overlaps(*shape, area)),
+                    );
+                }
+            }
+        }
+
+        boxes
+    }
+
+    /// Returns `true` if `aabb` overlaps the collision shape of any block.
+    /// Shapes that are only touching are not counted.
+    pub fn intersects_blocks(&self, aabb: Aabb) -> bool {
+        !self.collision_boxes(aabb).is_empty()
+    }
+
+    /// Returns `true` if `aabb` is standing on the collision shape of a block.
+    pub fn is_on_ground(&self, aabb: Aabb) -> bool {
+        let below = Aabb {
+            min: DVec3::new(aabb.min.x, aabb.min.y - EPSILON * 10.0, aabb.min.z),
+            max: DVec3::new(aabb.max.x, aabb.min.y, aabb.max.z),
+        };
+
+        !self.collision_boxes(below).is_empty()
+    }
+
+    /// Moves the hitbox `aabb` by `delta` and returns the movement after
+    /// colliding with blocks.
+    ///
+    /// When the hitbox runs into a block horizontally while on the ground, it
+    /// steps up blocks at most `step_height` tall. Players and most mobs have a
+    /// step height of `0.6`.
+    pub fn move_entity(&self, aabb: Aabb, delta: DVec3, step_height: f64) -> EntityMove {
+        let was_on_ground = self.is_on_ground(aabb);
+        let boxes = self.collision_boxes(expand_towards(aabb, delta));
+
+        let mut resolved = collide(aabb, delta, &boxes);
+
+        let collided_horizontally = resolved.x != delta.x || resolved.z != delta.z;
+        let landed = resolved.y != delta.y && delta.y < 0.0;
+
+        if step_height > 0.0 && (landed || was_on_ground) && collided_horizontally {
+            let step_boxes = self.collision_boxes(expand_towards(
+                aabb,
+                DVec3::new(delta.x, step_height, delta.z),
+            ));
+
+            // Try moving up and over the obstacle at once, and moving up first
+            // then over the obstacle. The one that gets further is used.
+            let mut stepped = collide(aabb, DVec3::new(delta.x, step_height, delta.z), &step_boxes);
+
+            let up = collide(
+                expand_towards(aabb, DVec3::new(delta.x, 0.0, delta.z)),
+                DVec3::new(0.0, step_height, 0.0),
+                &step_boxes,
+            );
+
+            if up.y < step_height {
+                let over = collide(aabb + up, DVec3::new(delta.x, 0.0, delta.z), &step_boxes) + up;
+
+                if horizontal_len_sq(over) > horizontal_len_sq(stepped) {
+                    stepped = over;
+                }
+            }
+
+            if horizontal_len_sq(stepped) > horizontal_len_sq(resolved) {
+                // Move back down onto the obstacle.
+                let down = collide(
+                    aabb + stepped,
+                    DVec3::new(0.0, delta.y - stepped.y, 0.0),
+                    &step_boxes,
+                );
+
+                resolved = stepped + down;
+            }
+        }
+
+        let collided = BVec3::new(
+            resolved.x != delta.x,
+            resolved.y != delta.y,
+            resolved.z != delta.z,
+        );
+
+        EntityMove {
+            delta: resolved,
+            collided,
+            on_ground: (collided.y && delta.y < 0.0) || self.is_on_ground(aabb + resolved),
+        }
+    }
+}
+
+/// Resolves `delta` against `boxes` one axis at a time. The vertical axis is
+/// resolved first, followed by the horizontal axis with the larger movement.
+fn collide(aabb: Aabb, delta: DVec3, boxes: &[Aabb]) -> DVec3 {
+    let mut aabb = aabb;
+    let mut res = DVec3::ZERO;
+
+    let order = if delta.x.abs() < delta.z.abs() {
+        [1, 2, 0]
+    } else {
+        [1, 0, 2]
+    };
+
+    for axis in order {
+        let offset = boxes
+            .iter()
+            .fold(delta[axis], |offset, bb| clip_axis(axis, aabb, *bb, offset));
+
+        res[axis] = offset;
+        aabb.min[axis] += offset;
+        aabb.max[axis] += offset;
+    }
+
+    res
+}
+
+/// Limits `offset` along `axis` so that `aabb` doesn't move into `obstacle`.
+fn clip_axis(axis: usize, aabb: Aabb, obstacle: Aabb, offset: f64) -> f64 {
+    if offset.abs() < EPSILON {
+        return 0.0;
+    }
+
+    // The boxes can only collide if they overlap on the other two axes.
+    for other in 0..3 {
+        if other != axis
+            && (aabb.max[other] - EPSILON <= obstacle.min[other]
+                || aabb.min[other] + EPSILON >= obstacle.max[other])
+        {
+            return offset;
+        }
+    }
+
+    if offset > 0.0 && aabb.max[axis] - EPSILON <= obstacle.min[axis] {
+        offset.min(obstacle.min[axis] - aabb.max[axis])
+    } else if offset < 0.0 && aabb.min[axis] + EPSILON >= obstacle.max[axis] {
+        offset.max(obstacle.max[axis] - aabb.min[axis])
+    } else {
+        offset
+    }
+}
+
+/// Returns `true` if the boxes overlap by more than [`EPSILON`].
+fn overlaps(a: Aabb, b: Aabb) -> bool {
+    (0..3).all(|i| a.max[i] - EPSILON > b.min[i] && b.max[i] - EPSILON > a.min[i])
+}
+
+fn expand_towards(aabb: Aabb, delta: DVec3) -> Aabb {
+    Aabb {
+        min: aabb.min + delta.min(DVec3::ZERO),
+        max: aabb.max + delta.max(DVec3::ZERO),
+    }
+}
+
+fn horizontal_len_sq(v: DVec3) -> f64 {
+    v.x * v.x + v.z * v.z
+}
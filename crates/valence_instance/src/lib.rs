@@ -40,26 +40,36 @@ use valence_core::particle::{Particle, ParticleS2c};
 use valence_core::protocol::array::LengthPrefixedArray;
 use valence_core::protocol::byte_angle::ByteAngle;
 use valence_core::protocol::encode::{PacketWriter, WritePacket};
-use valence_core::protocol::packet::sound::{PlaySoundS2c, Sound, SoundCategory};
+use valence_core::protocol::packet::sound::{
+    PlaySoundFromEntityS2c, PlaySoundS2c, SoundCategory, SoundId, StopSoundS2c,
+};
 use valence_core::protocol::var_int::VarInt;
 use valence_core::protocol::{Encode, Packet};
 use valence_core::Server;
 use valence_dimension::DimensionTypeRegistry;
+use valence_entity::attributes::Attributes;
+use valence_entity::effects::ActiveEffects;
+use valence_entity::equipment::Equipment;
 use valence_entity::packet::{
-    EntityAnimationS2c, EntityPositionS2c, EntitySetHeadYawS2c, EntityStatusS2c,
-    EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, MoveRelativeS2c, RotateAndMoveRelativeS2c,
-    RotateS2c,
+    EntityAnimationS2c, EntityAttributesS2c, EntityPositionS2c, EntitySetHeadYawS2c,
+    EntityStatusS2c, EntityTrackerUpdateS2c, EntityVelocityUpdateS2c, MoveRelativeS2c,
+    RotateAndMoveRelativeS2c, RotateS2c,
 };
 use valence_entity::{
-    EntityAnimations, EntityId, EntityKind, EntityStatuses, HeadYaw, InitEntitiesSet, Location,
-    Look, OldLocation, OldPosition, OnGround, PacketByteRange, Position, TrackedData,
-    UpdateTrackedDataSet, Velocity,
+    EntityAnimations, EntityDamages, EntityId, EntityKind, EntityStatuses, HeadYaw,
+    InitEntitiesSet, Location, Look, OldLocation, OldPosition, OnGround, PacketByteRange, Position,
+    TrackedData, UpdateTrackedDataSet, Velocity,
 };
 
+pub mod block_entity;
+pub mod block_tick;
 mod chunk;
 mod chunk_entry;
+pub mod collision;
 pub mod packet;
 mod paletted_container;
+pub mod raycast;
+pub mod snapshot;
 
 pub struct InstancePlugin;
 
@@ -86,10 +96,11 @@ impl Plugin for InstancePlugin {
                 .in_base_set(CoreSet::PostUpdate),
         ))
         .add_system(
-            // This can run at the same time as entity init because we're only looking at position
-            // + location.
+            // Runs after entity init so that entities moved by systems in `PostUpdate` before
+            // `InitEntitiesSet`, such as passengers, end up in the right cell.
             update_entity_cell_positions
                 .in_base_set(CoreSet::PostUpdate)
+                .after(InitEntitiesSet)
                 .before(WriteUpdatePacketsToInstancesSet),
         )
         .add_system(
@@ -99,6 +110,8 @@ impl Plugin for InstancePlugin {
         )
         .add_system(clear_instance_changes.in_set(ClearInstanceChangesSet));
 
+        block_tick::build(app);
+
         #[cfg(debug_assertions)]
         app.add_system(check_instance_invariants.in_base_set(CoreSet::PostUpdate));
     }
@@ -273,6 +286,10 @@ struct UpdateEntityQuery {
     tracked_data: &'static TrackedData,
     statuses: &'static EntityStatuses,
     animations: &'static EntityAnimations,
+    damages: Option<&'static EntityDamages>,
+    attributes: Option<&'static Attributes>,
+    effects: Option<&'static ActiveEffects>,
+    equipment: Option<&'static Equipment>,
     packet_byte_range: &'static mut PacketByteRange,
 }
 
@@ -282,14 +299,18 @@ impl UpdateEntityQueryItem<'_> {
 
         let entity_id = VarInt(self.id.get());
 
-        let position_delta = self.pos.0 - self.old_pos.get();
-        let needs_teleport = position_delta.abs().max_element() >= 8.0;
-        let changed_position = self.pos.0 != self.old_pos.get();
+        // Compute the delta between the positions as the client sees them,
+        // quantized to 1/4096 of a block. Rounding both positions instead of
+        // the difference means the deltas add up to the exact position over
+        // time, and movements too small to be seen don't send a packet.
+        let position_delta = (self.pos.0 * 4096.0).round() - (self.old_pos.get() * 4096.0).round();
+        let needs_teleport = position_delta.abs().max_element() > i16::MAX as f64;
+        let changed_position = position_delta != DVec3::ZERO;
 
         if changed_position && !needs_teleport && self.look.is_changed() {
             writer.write_packet(&RotateAndMoveRelativeS2c {
                 entity_id,
-                delta: (position_delta * 4096.0).to_array().map(|v| v as i16),
+                delta: position_delta.to_array().map(|v| v as i16),
                 yaw: ByteAngle::from_degrees(self.look.yaw),
                 pitch: ByteAngle::from_degrees(self.look.pitch),
                 on_ground: self.on_ground.0,
@@ -298,7 +319,7 @@ impl UpdateEntityQueryItem<'_> {
             if changed_position && !needs_teleport {
                 writer.write_packet(&MoveRelativeS2c {
                     entity_id,
-                    delta: (position_delta * 4096.0).to_array().map(|v| v as i16),
+                    delta: position_delta.to_array().map(|v| v as i16),
                     on_ground: self.on_ground.0,
                 });
             }
@@ -344,6 +365,25 @@ impl UpdateEntityQueryItem<'_> {
             });
         }
 
+        if let Some(attributes) = self.attributes {
+            let properties = attributes.update_properties();
+
+            if !properties.is_empty() {
+                writer.write_packet(&EntityAttributesS2c {
+                    entity_id,
+                    properties,
+                });
+            }
+        }
+
+        if let Some(effects) = self.effects {
+            effects.write_update_packets(entity_id, &mut writer);
+        }
+
+        if let Some(equipment) = self.equipment {
+            equipment.write_update_packets(entity_id, &mut writer);
+        }
+
         if self.statuses.0 != 0 {
             for i in 0..mem::size_of_val(self.statuses) {
                 if (self.statuses.0 >> i) & 1 == 1 {
@@ -365,6 +405,12 @@ impl UpdateEntityQueryItem<'_> {
                 }
             }
         }
+
+        if let Some(damages) = self.damages {
+            for pkt in &damages.0 {
+                writer.write_packet(pkt);
+            }
+        }
     }
 }
 
@@ -499,6 +545,16 @@ impl Instance {
         self.info.section_count
     }
 
+    /// The lowest Y coordinate that blocks can be placed at.
+    pub fn min_y(&self) -> i32 {
+        self.info.min_y
+    }
+
+    /// The total height of the instance in blocks.
+    pub fn height(&self) -> u32 {
+        self.info.section_count as u32 * 16
+    }
+
     /// Get a reference to the chunk at the given position, if it is loaded.
     pub fn chunk(&self, pos: impl Into<ChunkPos>) -> Option<&Chunk<true>> {
         self.partition
@@ -672,6 +728,45 @@ impl Instance {
         ))
     }
 
+    /// Schedules a block tick at an absolute block position in world space to
+    /// be delivered as a [`BlockTickEvent`] in `delay` game ticks. The pending
+    /// tick is stored in the chunk containing `pos`.
+    ///
+    /// Returns `false` with no effect if the position is not within a loaded
+    /// chunk or otherwise out of bounds.
+    ///
+    /// [`BlockTickEvent`]: block_tick::BlockTickEvent
+    pub fn schedule_block_tick(
+        &mut self,
+        pos: impl Into<BlockPos>,
+        delay: u32,
+        priority: i32,
+    ) -> bool {
+        let pos = pos.into();
+
+        let Some(y) = pos.y.checked_sub(self.info.min_y).and_then(|y| y.try_into().ok()) else {
+            return false;
+        };
+
+        if y >= self.info.section_count * 16 {
+            return false;
+        }
+
+        let Some(chunk) = self.chunk_mut(ChunkPos::from_block_pos(pos)) else {
+            return false;
+        };
+
+        chunk.schedule_tick(
+            pos.x.rem_euclid(16) as usize,
+            y,
+            pos.z.rem_euclid(16) as usize,
+            delay,
+            priority,
+        );
+
+        true
+    }
+
     /// Writes a packet to all clients in view of `pos` in this instance. Has no
     /// effect if there is no chunk at `pos`.
     ///
@@ -712,14 +807,23 @@ impl Instance {
     /// Puts a particle effect at the given position in the world. The particle
     /// effect is visible to all players in the instance with the
     /// appropriate chunk in view.
-    pub fn play_particle(
+    ///
+    /// - `offset`: The random offset of each particle from `position`, scaled
+    ///   by a normal distribution.
+    /// - `count`: The number of particles. If `0`, a single particle is moved
+    ///   by `offset` times `speed` instead.
+    /// - `speed`: The speed of the particles. Its meaning depends on the
+    ///   particle.
+    /// - `long_distance`: Shows the particle from 512 blocks away instead of
+    ///   32.
+    pub fn spawn_particle(
         &mut self,
         particle: &Particle,
-        long_distance: bool,
         position: impl Into<DVec3>,
         offset: impl Into<Vec3>,
-        max_speed: f32,
         count: i32,
+        speed: f32,
+        long_distance: bool,
     ) {
         let position = position.into();
 
@@ -729,7 +833,7 @@ impl Instance {
                 long_distance,
                 position,
                 offset: offset.into(),
-                max_speed,
+                max_speed: speed,
                 count,
             },
             ChunkPos::from_dvec3(position),
@@ -739,28 +843,72 @@ impl Instance {
     /// Plays a sound effect at the given position in the world. The sound
     /// effect is audible to all players in the instance with the
     /// appropriate chunk in view.
-    pub fn play_sound(
+    ///
+    /// The sound is either a vanilla [`Sound`] or the [`Ident`] of a custom
+    /// sound, such as one from a resource pack. The `seed` picks which variant
+    /// of the sound is played.
+    ///
+    /// [`Sound`]: valence_core::protocol::packet::sound::Sound
+    pub fn play_sound<'a>(
         &mut self,
-        sound: Sound,
-        category: SoundCategory,
         position: impl Into<DVec3>,
+        sound: impl Into<SoundId<'a>>,
+        category: SoundCategory,
         volume: f32,
         pitch: f32,
+        seed: i64,
     ) {
         let position = position.into();
 
         self.write_packet_at(
             &PlaySoundS2c {
-                id: sound.to_id(),
+                id: sound.into(),
                 category,
                 position: (position * 8.0).as_ivec3(),
                 volume,
                 pitch,
-                seed: rand::random(),
+                seed,
             },
             ChunkPos::from_dvec3(position),
         );
     }
+
+    /// Plays a sound effect that follows an entity. `entity_id` is the
+    /// protocol ID of the entity from its [`EntityId`]. The sound effect is
+    /// sent to all players in the instance, but only those with the entity
+    /// loaded will hear it.
+    ///
+    /// See [`Self::play_sound`] for the other arguments.
+    ///
+    /// [`EntityId`]: valence_entity::EntityId
+    pub fn play_sound_from_entity<'a>(
+        &mut self,
+        entity_id: i32,
+        sound: impl Into<SoundId<'a>>,
+        category: SoundCategory,
+        volume: f32,
+        pitch: f32,
+        seed: i64,
+    ) {
+        self.write_packet(&PlaySoundFromEntityS2c {
+            id: sound.into(),
+            category,
+            entity_id: VarInt(entity_id),
+            volume,
+            pitch,
+            seed,
+        });
+    }
+
+    /// Stops sound effects for all players in the instance. Only sounds in
+    /// `category` are stopped, or all sounds if it is `None`. If `sound` is
+    /// given, only that sound is stopped.
+    pub fn stop_sound(&mut self, category: Option<SoundCategory>, sound: Option<Ident<&str>>) {
+        self.write_packet(&StopSoundS2c {
+            source: category,
+            sound: sound.map(Into::into),
+        });
+    }
 }
 
 /// Writing packets to the instance writes to the instance's global packet
@@ -1,5 +1,6 @@
 use std::array;
 use std::io::Write;
+use std::sync::Arc;
 
 use arrayvec::ArrayVec;
 use num_integer::div_ceil;
@@ -9,11 +10,14 @@ use valence_core::protocol::Encode;
 use crate::bit_width;
 
 /// `HALF_LEN` must be equal to `ceil(LEN / 2)`.
+///
+/// The data of the indirect and direct representations is shared between
+/// clones and copied on write, so cloning a container is cheap.
 #[derive(Clone, Debug)]
 pub(crate) enum PalettedContainer<T, const LEN: usize, const HALF_LEN: usize> {
     Single(T),
-    Indirect(Box<Indirect<T, LEN, HALF_LEN>>),
-    Direct(Box<[T; LEN]>),
+    Indirect(Arc<Indirect<T, LEN, HALF_LEN>>),
+    Direct(Arc<[T; LEN]>),
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +53,17 @@ impl<T: Copy + Eq + Default, const LEN: usize, const HALF_LEN: usize>
         }
     }
 
+    /// Returns `true` if both containers are known to be equal without
+    /// comparing their elements, because they share the same data.
+    pub(crate) fn shares_data(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Single(a), Self::Single(b)) => a == b,
+            (Self::Indirect(a), Self::Indirect(b)) => Arc::ptr_eq(a, b),
+            (Self::Direct(a), Self::Direct(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
     pub(crate) fn set(&mut self, idx: usize, val: T) -> T {
         debug_assert!(idx < LEN);
 
@@ -59,29 +74,33 @@ impl<T: Copy + Eq + Default, const LEN: usize, const HALF_LEN: usize>
                 } else {
                     // Upgrade to indirect.
                     let old = *old_val;
-                    let mut ind = Box::new(Indirect {
+                    let mut ind = Indirect {
                         palette: ArrayVec::from_iter([old, val]),
                         // All indices are initialized to index 0 (the old element).
                         indices: [0; HALF_LEN],
-                    });
+                    };
 
                     ind.indices[idx / 2] = 1 << (idx % 2 * 4);
-                    *self = Self::Indirect(ind);
+                    *self = Self::Indirect(Arc::new(ind));
                     old
                 }
             }
             Self::Indirect(ind) => {
-                if let Some(old) = ind.set(idx, val) {
+                if ind.get(idx) == val {
+                    val
+                } else if let Some(old) = Arc::make_mut(ind).set(idx, val) {
                     old
                 } else {
                     // Upgrade to direct.
-                    *self = Self::Direct(Box::new(array::from_fn(|i| ind.get(i))));
+                    *self = Self::Direct(Arc::new(array::from_fn(|i| ind.get(i))));
                     self.set(idx, val)
                 }
             }
             Self::Direct(vals) => {
                 let old = vals[idx];
-                vals[idx] = val;
+                if old != val {
+                    Arc::make_mut(vals)[idx] = val;
+                }
                 old
             }
         }
@@ -103,7 +122,7 @@ impl<T: Copy + Eq + Default, const LEN: usize, const HALF_LEN: usize>
                 if new_ind.palette.len() == 1 {
                     *self = Self::Single(new_ind.palette[0]);
                 } else {
-                    **ind = new_ind;
+                    *ind = Arc::new(new_ind);
                 }
             }
             Self::Direct(dir) => {
@@ -121,7 +140,7 @@ impl<T: Copy + Eq + Default, const LEN: usize, const HALF_LEN: usize>
                 *self = if ind.palette.len() == 1 {
                     Self::Single(ind.palette[0])
                 } else {
-                    Self::Indirect(Box::new(ind))
+                    Self::Indirect(Arc::new(ind))
                 };
             }
         }
@@ -0,0 +1,184 @@
+//! Raycasting against the blocks of an instance.
+//!
+//! Rays walk the block grid one block at a time and are intersected with the
+//! collision shapes of each block state. Blocks without a collision shape,
+//! such as air, fluids, and flowers, are passed through. Chunks that aren't
+//! loaded are treated as empty.
+
+use glam::DVec3;
+use valence_block::BlockState;
+use valence_core::aabb::Aabb;
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+
+use crate::Instance;
+
+/// An intersection between a ray and the collision shape of a block.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BlockRaycastHit {
+    /// The position of the block that was hit.
+    pub block_pos: BlockPos,
+    /// The state of the block that was hit.
+    pub state: BlockState,
+    /// The face of the block's collision shape the ray entered through.
+    pub face: Direction,
+    /// The point where the ray enters the collision shape.
+    pub position: DVec3,
+    /// The distance from the ray origin to [`Self::position`]. This is zero
+    /// if the origin is inside the collision shape.
+    pub distance: f64,
+}
+
+impl Instance {
+    /// Casts a ray from `origin` in `direction` and returns the first block
+    /// collision shape it hits within `max_dist`.
+    pub fn raycast_blocks(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_dist: f64,
+    ) -> Option<BlockRaycastHit> {
+        self.raycast_blocks_filtered(origin, direction, max_dist, |_, _| true)
+    }
+
+    /// Like [`Self::raycast_blocks`], but blocks for which `f` returns `false`
+    /// are passed through.
+    pub fn raycast_blocks_filtered<F>(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_dist: f64,
+        mut f: F,
+    ) -> Option<BlockRaycastHit>
+    where
+        F: FnMut(BlockPos, BlockState) -> bool,
+    {
+        let direction = direction.normalize_or_zero();
+
+        if direction == DVec3::ZERO || !max_dist.is_finite() || !origin.is_finite() {
+            return None;
+        }
+
+        let mut block_pos = BlockPos::at(origin);
+        let step = direction.signum().as_ivec3();
+
+        // The distance along the ray to the next block boundary on each axis,
+        // and the distance between boundaries.
+        let mut t_max = DVec3::ZERO;
+        let mut t_delta = DVec3::ZERO;
+
+        for i in 0..3 {
+            if direction[i] == 0.0 {
+                t_max[i] = f64::INFINITY;
+                t_delta[i] = f64::INFINITY;
+            } else {
+                let block = origin[i].floor();
+                let boundary = if direction[i] > 0.0 {
+                    block + 1.0
+                } else {
+                    block
+                };
+
+                t_max[i] = (boundary - origin[i]) / direction[i];
+                t_delta[i] = 1.0 / direction[i].abs();
+            }
+        }
+
+        let mut t = 0.0;
+
+        while t <= max_dist {
+            if let Some(block) = self.block(block_pos) {
+                let state = block.state();
+
+                if state.collision_shapes().len() > 0 && f(block_pos, state) {
+                    let offset =
+                        DVec3::new(block_pos.x as f64, block_pos.y as f64, block_pos.z as f64);
+
+                    let closest = state
+                        .collision_shapes()
+                        .filter_map(|shape| ray_aabb_face(origin, direction, shape + offset))
+                        .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+                    if let Some((distance, face)) = closest {
+                        if distance <= max_dist {
+                            return Some(BlockRaycastHit {
+                                block_pos,
+                                state,
+                                face,
+                                position: origin + direction * distance,
+                                distance,
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Step into the next block along the axis with the closest boundary.
+            let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+                0
+            } else if t_max.y < t_max.z {
+                1
+            } else {
+                2
+            };
+
+            t = t_max[axis];
+            t_max[axis] += t_delta[axis];
+
+            match axis {
+                0 => block_pos.x += step.x,
+                1 => block_pos.y += step.y,
+                _ => block_pos.z += step.z,
+            }
+        }
+
+        None
+    }
+}
+
+/// Intersects a ray with an AABB and returns the distance to the entry point
+/// along with the face that was entered.
+fn ray_aabb_face(origin: DVec3, direction: DVec3, aabb: Aabb) -> Option<(f64, Direction)> {
+    const FACES: [(Direction, Direction); 3] = [
+        (Direction::West, Direction::East),
+        (Direction::Down, Direction::Up),
+        (Direction::North, Direction::South),
+    ];
+
+    let mut near = f64::NEG_INFINITY;
+    let mut far = f64::INFINITY;
+    let mut face = None;
+
+    for (i, (min_face, max_face)) in FACES.into_iter().enumerate() {
+        if direction[i] == 0.0 {
+            if origin[i] < aabb.min[i] || origin[i] > aabb.max[i] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t0 = (aabb.min[i] - origin[i]) / direction[i];
+        let t1 = (aabb.max[i] - origin[i]) / direction[i];
+
+        // Rays travelling in the positive direction enter through the min face.
+        let (t_enter, t_exit, enter_face) = if direction[i] > 0.0 {
+            (t0, t1, min_face)
+        } else {
+            (t1, t0, max_face)
+        };
+
+        if t_enter > near {
+            near = t_enter;
+            face = Some(enter_face);
+        }
+
+        far = far.min(t_exit);
+    }
+
+    if near <= far && far >= 0.0 {
+        Some((near.max(0.0), face?))
+    } else {
+        None
+    }
+}
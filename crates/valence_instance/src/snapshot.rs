@@ -0,0 +1,85 @@
+//! Snapshots of the chunks of an instance.
+//!
+//! Taking an [`InstanceSnapshot`] is cheap, because the blocks and biomes of
+//! the snapshot are shared with the instance and only copied when the instance
+//! changes them. Restoring a snapshot only sends clients the blocks that
+//! differ, so a minigame arena can be reset between rounds without setting
+//! every block again.
+
+use rustc_hash::FxHashMap;
+use valence_core::chunk_pos::ChunkPos;
+
+use crate::{Chunk, Instance};
+
+/// A copy of chunks of an instance at some point in time. See
+/// [`Instance::snapshot`] and [`Instance::restore`].
+#[derive(Clone, Debug)]
+pub struct InstanceSnapshot {
+    section_count: usize,
+    chunks: FxHashMap<ChunkPos, Chunk>,
+}
+
+impl InstanceSnapshot {
+    /// Gets the chunk at the given position, if it's in the snapshot.
+    pub fn chunk(&self, pos: impl Into<ChunkPos>) -> Option<&Chunk> {
+        self.chunks.get(&pos.into())
+    }
+
+    /// Gets an iterator over the chunks in the snapshot. The order of the
+    /// chunks is undefined.
+    pub fn chunks(&self) -> impl ExactSizeIterator<Item = (ChunkPos, &Chunk)> + Clone + '_ {
+        self.chunks.iter().map(|(&pos, chunk)| (pos, chunk))
+    }
+}
+
+impl Instance {
+    /// Takes a snapshot of all loaded chunks in the instance.
+    pub fn snapshot(&self) -> InstanceSnapshot {
+        self.snapshot_chunks(|_| true)
+    }
+
+    /// Takes a snapshot of the loaded chunks for which `f` returns `true`, such
+    /// as the chunks of an arena in a larger world.
+    pub fn snapshot_chunks<F>(&self, mut f: F) -> InstanceSnapshot
+    where
+        F: FnMut(ChunkPos) -> bool,
+    {
+        InstanceSnapshot {
+            section_count: self.info.section_count,
+            chunks: self
+                .chunks()
+                .filter(|&(pos, _)| f(pos))
+                .map(|(pos, chunk)| (pos, chunk.to_unloaded()))
+                .collect(),
+        }
+    }
+
+    /// Restores the chunks in `snapshot`. Loaded chunks are updated in place,
+    /// and clients in view of them only receive the blocks that changed since
+    /// the snapshot was taken. Chunks that were unloaded since are inserted
+    /// again.
+    ///
+    /// Chunks that aren't in the snapshot are left alone. To unload them, use
+    /// [`Instance::retain_chunks`] with [`InstanceSnapshot::chunk`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot was taken of an instance with a different
+    /// height.
+    #[track_caller]
+    pub fn restore(&mut self, snapshot: &InstanceSnapshot) {
+        assert_eq!(
+            self.info.section_count, snapshot.section_count,
+            "snapshot of an instance with a different section count"
+        );
+
+        for (&pos, chunk) in &snapshot.chunks {
+            match self.chunk_mut(pos) {
+                Some(loaded) => loaded.restore(chunk),
+                None => {
+                    self.insert_chunk(pos, chunk.clone());
+                }
+            }
+        }
+    }
+}
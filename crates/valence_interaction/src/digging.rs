@@ -0,0 +1,443 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_block::{BlockKind, BlockState, PropName, PropValue};
+use valence_client::action::{DiggingEvent, DiggingState};
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_core::ident::Ident;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::{ident, Server};
+use valence_entity::effects::{ActiveEffects, StatusEffect};
+use valence_entity::entity::Pose;
+use valence_entity::{Location, OnGround, Position};
+use valence_instance::Instance;
+use valence_inventory::{HeldItem, Inventory};
+use valence_registry::tags::TagsRegistry;
+
+use crate::{
+    distance_to_block, eye_position, stack_in_hand, CancelInteraction, Interaction, InteractionSet,
+    InteractionSettings,
+};
+
+/// The player inventory slot of the helmet.
+const HELMET_SLOT: u16 = 5;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<StartDiggingEvent>()
+        .add_event::<AbortDiggingEvent>()
+        .add_event::<BreakBlockEvent>()
+        .add_system(
+            handle_digging
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::Update)
+                .in_set(InteractionSet),
+        );
+}
+
+/// Sent when a client starts breaking a block that doesn't break instantly.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StartDiggingEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    /// The face of the block that is being hit.
+    pub face: Direction,
+    /// The block being broken.
+    pub state: BlockState,
+    /// The number of ticks it takes the client to break the block, or `None`
+    /// if break times aren't validated for this block.
+    pub break_ticks: Option<u32>,
+}
+
+impl StartDiggingEvent {
+    /// Cancels breaking the block. The client can't finish breaking it.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::Dig {
+                position: self.position,
+            },
+        }
+    }
+}
+
+/// Sent when a client stops breaking a block before it is broken.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AbortDiggingEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+}
+
+/// Sent when a client breaks a block. The block is not removed from the
+/// instance automatically.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BreakBlockEvent {
+    pub client: Entity,
+    pub position: BlockPos,
+    /// The face of the block that was hit.
+    pub face: Direction,
+    /// The block that was broken.
+    pub state: BlockState,
+    /// If the block was broken as soon as the client started hitting it, such
+    /// as in creative mode.
+    pub instant: bool,
+}
+
+impl BreakBlockEvent {
+    /// Cancels breaking the block. The block is restored on the client.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::Dig {
+                position: self.position,
+            },
+        }
+    }
+}
+
+/// The block a client is currently breaking.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub struct Digging(pub(crate) Option<ActiveDig>);
+
+impl Digging {
+    pub fn get(&self) -> Option<&ActiveDig> {
+        self.0.as_ref()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ActiveDig {
+    pub position: BlockPos,
+    /// The server tick the client started breaking the block.
+    pub start_tick: i64,
+    /// The fraction of the block broken each tick, as returned by
+    /// [`break_progress`].
+    pub progress_per_tick: Option<f32>,
+}
+
+/// The conditions of a client other than the block and tool that affect how
+/// fast it breaks blocks.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct DiggingConditions {
+    /// The amplifier of haste or conduit power.
+    pub haste: Option<u8>,
+    /// The amplifier of mining fatigue.
+    pub mining_fatigue: Option<u8>,
+    /// If the eyes of the client are in water without aqua affinity.
+    pub underwater: bool,
+    pub on_ground: bool,
+}
+
+/// Returns the fraction of `state` broken each tick when hit with `tool`. The
+/// block breaks once the total reaches `1.0`.
+///
+/// Blocks that can't be broken return `0.0`. Returns `None` if the hardness of
+/// the block is unknown.
+pub fn break_progress(
+    state: BlockState,
+    tool: Option<&ItemStack>,
+    conditions: DiggingConditions,
+    tags: &TagsRegistry,
+) -> Option<f32> {
+    let kind = state.to_kind();
+    let hardness = kind.hardness()?;
+
+    if hardness < 0.0 {
+        return Some(0.0);
+    }
+
+    if hardness == 0.0 {
+        return Some(1.0);
+    }
+
+    let (mut speed, correct_tool) = tool_speed(kind, tool.map(|t| t.item), tags);
+
+    if speed > 1.0 {
+        let efficiency = tool.map_or(0, |t| t.enchantment_level("minecraft:efficiency"));
+
+        if efficiency > 0 {
+            speed += (efficiency as f32).powi(2) + 1.0;
+        }
+    }
+
+    if let Some(haste) = conditions.haste {
+        speed *= 1.0 + 0.2 * (haste as f32 + 1.0);
+    }
+
+    if let Some(fatigue) = conditions.mining_fatigue {
+        speed *= match fatigue {
+            0 => 0.3,
+            1 => 0.09,
+            2 => 0.0027,
+            _ => 0.00081,
+        };
+    }
+
+    if conditions.underwater {
+        speed /= 5.0;
+    }
+
+    if !conditions.on_ground {
+        speed /= 5.0;
+    }
+
+    let divisor = if correct_tool || !kind.requires_tool() {
+        30.0
+    } else {
+        100.0
+    };
+
+    Some(speed / hardness / divisor)
+}
+
+/// Returns the speed `item` breaks `kind` at and whether it is the correct tool
+/// for dropping items.
+fn tool_speed(kind: BlockKind, item: Option<ItemKind>, tags: &TagsRegistry) -> (f32, bool) {
+    let in_tag = |name: Ident<&str>| {
+        tags.tag(ident!("block"), name)
+            .is_some_and(|tag| tag.contains(kind.to_raw() as i32))
+    };
+
+    let Some(item) = item else {
+        return (1.0, false)
+    };
+
+    if item == ItemKind::Shears {
+        let correct = matches!(
+            kind,
+            BlockKind::Cobweb | BlockKind::RedstoneWire | BlockKind::Tripwire
+        );
+
+        let speed = if kind == BlockKind::Cobweb || in_tag(ident!("leaves")) {
+            15.0
+        } else if in_tag(ident!("wool")) {
+            5.0
+        } else if matches!(kind, BlockKind::Vine | BlockKind::GlowLichen) {
+            2.0
+        } else {
+            1.0
+        };
+
+        return (speed, correct);
+    }
+
+    let Some((material, tool)) = item.to_str().rsplit_once('_') else {
+        return (1.0, false)
+    };
+
+    if tool == "sword" {
+        return if kind == BlockKind::Cobweb {
+            (15.0, true)
+        } else if in_tag(ident!("sword_efficient")) {
+            (1.5, false)
+        } else {
+            (1.0, false)
+        };
+    }
+
+    let mineable = match tool {
+        "pickaxe" => ident!("mineable/pickaxe"),
+        "axe" => ident!("mineable/axe"),
+        "shovel" => ident!("mineable/shovel"),
+        "hoe" => ident!("mineable/hoe"),
+        _ => return (1.0, false),
+    };
+
+    let (speed, tier) = match material {
+        "wooden" => (2.0, 0),
+        "stone" => (4.0, 1),
+        "iron" => (6.0, 2),
+        "diamond" => (8.0, 3),
+        "golden" => (12.0, 0),
+        "netherite" => (9.0, 4),
+        _ => return (1.0, false),
+    };
+
+    if !in_tag(mineable) {
+        return (1.0, false);
+    }
+
+    let required_tier = if in_tag(ident!("needs_diamond_tool")) {
+        3
+    } else if in_tag(ident!("needs_iron_tool")) {
+        2
+    } else if in_tag(ident!("needs_stone_tool")) {
+        1
+    } else {
+        0
+    };
+
+    (speed, tier >= required_tier)
+}
+
+fn is_water(state: BlockState) -> bool {
+    state.to_kind() == BlockKind::Water || state.get(PropName::Waterlogged) == Some(PropValue::True)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_digging(
+    mut digs: EventReader<DiggingEvent>,
+    mut clients: Query<(
+        &mut Digging,
+        &Position,
+        &Location,
+        &GameMode,
+        Option<&Pose>,
+        Option<&OnGround>,
+        Option<&ActiveEffects>,
+        Option<&Inventory>,
+        Option<&HeldItem>,
+    )>,
+    instances: Query<&Instance>,
+    server: Res<Server>,
+    tags: Res<TagsRegistry>,
+    settings: Res<InteractionSettings>,
+    mut start_events: EventWriter<StartDiggingEvent>,
+    mut abort_events: EventWriter<AbortDiggingEvent>,
+    mut break_events: EventWriter<BreakBlockEvent>,
+    mut cancel: EventWriter<CancelInteraction>,
+) {
+    for dig in digs.iter() {
+        let Ok((mut digging, pos, loc, game_mode, pose, on_ground, effects, inventory, held)) =
+            clients.get_mut(dig.client) else {
+            continue
+        };
+
+        let cancel_dig = CancelInteraction {
+            client: dig.client,
+            interaction: Interaction::Dig {
+                position: dig.position,
+            },
+        };
+
+        if dig.state == DiggingState::Abort {
+            if digging.get().is_some_and(|d| d.position == dig.position) {
+                digging.0 = None;
+            }
+
+            abort_events.send(AbortDiggingEvent {
+                client: dig.client,
+                position: dig.position,
+            });
+
+            continue;
+        }
+
+        let Ok(instance) = instances.get(loc.0) else {
+            continue
+        };
+
+        let Some(state) = instance.block(dig.position).map(|b| b.state()) else {
+            cancel.send(cancel_dig);
+            continue
+        };
+
+        let eyes = eye_position(pos.0, pose);
+        let tool = stack_in_hand(inventory, held, Hand::Main);
+
+        // Swords can't break blocks in creative mode.
+        let creative_sword = *game_mode == GameMode::Creative
+            && tool.is_some_and(|t| t.item.to_str().ends_with("_sword"));
+
+        if matches!(*game_mode, GameMode::Adventure | GameMode::Spectator)
+            || creative_sword
+            || state.is_air()
+            || distance_to_block(eyes, dig.position) > settings.block_reach
+        {
+            digging.0 = None;
+            cancel.send(cancel_dig);
+            continue;
+        }
+
+        match dig.state {
+            DiggingState::Start => {
+                let progress = if *game_mode == GameMode::Creative {
+                    Some(1.0)
+                } else if settings.validate_break_time {
+                    let effect_amplifier =
+                        |effect| effects.and_then(|e| e.get(effect)).map(|e| e.amplifier);
+
+                    let aqua_affinity = inventory
+                        .and_then(|inv| inv.slot(HELMET_SLOT))
+                        .is_some_and(|helmet| {
+                            helmet.enchantment_level("minecraft:aqua_affinity") > 0
+                        });
+
+                    let conditions = DiggingConditions {
+                        haste: effect_amplifier(StatusEffect::Haste)
+                            .max(effect_amplifier(StatusEffect::ConduitPower)),
+                        mining_fatigue: effect_amplifier(StatusEffect::MiningFatigue),
+                        underwater: !aqua_affinity
+                            && instance
+                                .block(BlockPos::at(eyes))
+                                .is_some_and(|b| is_water(b.state())),
+                        on_ground: on_ground.is_none_or(|g| g.0),
+                    };
+
+                    break_progress(state, tool, conditions, &tags)
+                } else {
+                    None
+                };
+
+                match progress {
+                    Some(p) if p >= 1.0 => {
+                        digging.0 = None;
+
+                        break_events.send(BreakBlockEvent {
+                            client: dig.client,
+                            position: dig.position,
+                            face: dig.direction,
+                            state,
+                            instant: true,
+                        });
+                    }
+                    Some(p) if p <= 0.0 => {
+                        digging.0 = None;
+                        cancel.send(cancel_dig);
+                    }
+                    _ => {
+                        digging.0 = Some(ActiveDig {
+                            position: dig.position,
+                            start_tick: server.current_tick(),
+                            progress_per_tick: progress,
+                        });
+
+                        start_events.send(StartDiggingEvent {
+                            client: dig.client,
+                            position: dig.position,
+                            face: dig.direction,
+                            state,
+                            break_ticks: progress.map(|p| (1.0 / p).ceil() as u32),
+                        });
+                    }
+                }
+            }
+            DiggingState::Stop => {
+                let Some(active) = digging.0.take().filter(|d| d.position == dig.position) else {
+                    cancel.send(cancel_dig);
+                    continue
+                };
+
+                if let Some(progress) = active.progress_per_tick {
+                    let ticks = server.current_tick() - active.start_tick + 1;
+
+                    if progress * (ticks as f32) < settings.break_time_tolerance {
+                        cancel.send(cancel_dig);
+                        continue;
+                    }
+                }
+
+                break_events.send(BreakBlockEvent {
+                    client: dig.client,
+                    position: dig.position,
+                    face: dig.direction,
+                    state,
+                    instant: false,
+                });
+            }
+            DiggingState::Abort => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::Vec3;
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_client::interact_entity::{EntityInteraction, InteractEntityEvent};
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_entity::entity::Pose;
+use valence_entity::hitbox::Hitbox;
+use valence_entity::{Location, Position};
+
+use crate::{eye_position, CancelInteraction, Interaction, InteractionSet, InteractionSettings};
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<AttackEntityEvent>()
+        .add_event::<UseEntityEvent>()
+        .add_system(
+            handle_interact_entity
+                .in_schedule(EventLoopSchedule)
+                .in_base_set(EventLoopSet::Update)
+                .in_set(InteractionSet),
+        );
+}
+
+/// Sent when a client attacks an entity in the same instance and within
+/// reach.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AttackEntityEvent {
+    pub client: Entity,
+    pub target: Entity,
+    /// If the client was sneaking during the attack.
+    pub sneaking: bool,
+}
+
+impl AttackEntityEvent {
+    /// Cancels the attack.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::AttackEntity {
+                target: self.target,
+            },
+        }
+    }
+}
+
+/// Sent when a client right-clicks an entity in the same instance and within
+/// reach.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct UseEntityEvent {
+    pub client: Entity,
+    pub target: Entity,
+    pub hand: Hand,
+    /// The position on the target that was clicked, relative to the target's
+    /// position. This is `None` for entities that are interacted with as a
+    /// whole.
+    pub position: Option<Vec3>,
+    /// If the client was sneaking during the interaction.
+    pub sneaking: bool,
+}
+
+impl UseEntityEvent {
+    /// Cancels the interaction. The held item is restored on the client.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::UseEntity {
+                target: self.target,
+                hand: self.hand,
+            },
+        }
+    }
+}
+
+fn handle_interact_entity(
+    mut interactions: EventReader<InteractEntityEvent>,
+    clients: Query<(&Position, &Location, &GameMode, Option<&Pose>)>,
+    targets: Query<(&Position, &Location, Option<&Hitbox>)>,
+    settings: Res<InteractionSettings>,
+    mut attack_events: EventWriter<AttackEntityEvent>,
+    mut use_events: EventWriter<UseEntityEvent>,
+    mut cancel: EventWriter<CancelInteraction>,
+) {
+    for interaction in interactions.iter() {
+        if interaction.client == interaction.entity {
+            continue;
+        }
+
+        let Ok((pos, loc, game_mode, pose)) = clients.get(interaction.client) else {
+            continue
+        };
+
+        // Attacking as a spectator spectates the entity instead.
+        if *game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        let eyes = eye_position(pos.0, pose);
+
+        let valid =
+            targets
+                .get(interaction.entity)
+                .is_ok_and(|(target_pos, target_loc, hitbox)| {
+                    // Measure to the closest point of the hitbox.
+                    let closest = match hitbox {
+                        Some(hitbox) => eyes.clamp(hitbox.get().min, hitbox.get().max),
+                        None => target_pos.0,
+                    };
+
+                    target_loc.0 == loc.0 && eyes.distance(closest) <= settings.entity_reach
+                });
+
+        match interaction.interact {
+            EntityInteraction::Attack => {
+                let event = AttackEntityEvent {
+                    client: interaction.client,
+                    target: interaction.entity,
+                    sneaking: interaction.sneaking,
+                };
+
+                if valid {
+                    attack_events.send(event);
+                } else {
+                    cancel.send(event.cancel());
+                }
+            }
+            EntityInteraction::Interact(hand) | EntityInteraction::InteractAt { hand, .. } => {
+                let position = match interaction.interact {
+                    EntityInteraction::InteractAt { target, .. } => Some(target),
+                    _ => None,
+                };
+
+                let event = UseEntityEvent {
+                    client: interaction.client,
+                    target: interaction.entity,
+                    hand,
+                    position,
+                    sneaking: interaction.sneaking,
+                };
+
+                if valid {
+                    use_events.send(event);
+                } else {
+                    cancel.send(event.cancel());
+                }
+            }
+        }
+    }
+}
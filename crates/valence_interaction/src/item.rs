@@ -0,0 +1,63 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_client::interact_item::InteractItemEvent;
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_core::item::ItemStack;
+use valence_inventory::{HeldItem, Inventory};
+
+use crate::{stack_in_hand, CancelInteraction, Interaction, InteractionSet};
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<UseItemEvent>().add_system(
+        handle_use_item
+            .in_schedule(EventLoopSchedule)
+            .in_base_set(EventLoopSet::Update)
+            .in_set(InteractionSet),
+    );
+}
+
+/// Sent when a client uses the item in its hand, such as eating food or
+/// throwing an ender pearl.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UseItemEvent {
+    pub client: Entity,
+    pub hand: Hand,
+    /// The item that was used.
+    pub item: ItemStack,
+}
+
+impl UseItemEvent {
+    /// Cancels using the item. The held item is restored on the client.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::UseItem { hand: self.hand },
+        }
+    }
+}
+
+fn handle_use_item(
+    mut interactions: EventReader<InteractItemEvent>,
+    clients: Query<(&GameMode, Option<&Inventory>, Option<&HeldItem>)>,
+    mut events: EventWriter<UseItemEvent>,
+) {
+    for interaction in interactions.iter() {
+        let Ok((game_mode, inventory, held)) = clients.get(interaction.client) else {
+            continue
+        };
+
+        if *game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        if let Some(item) = stack_in_hand(inventory, held, interaction.hand) {
+            events.send(UseItemEvent {
+                client: interaction.client,
+                hand: interaction.hand,
+                item: item.clone(),
+            });
+        }
+    }
+}
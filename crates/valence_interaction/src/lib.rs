@@ -0,0 +1,315 @@
+//! # Interaction
+//! High-level events for clients interacting with the world.
+//!
+//! The packets sent by clients when they break blocks, place blocks, use items,
+//! and interact with entities are turned into these events:
+//!
+//! - [`StartDiggingEvent`], [`AbortDiggingEvent`] and [`BreakBlockEvent`] for
+//!   breaking blocks. How long a block takes to break is determined by the
+//!   server from the hardness of the block, the tool and the status effects of
+//!   the client.
+//! - [`PlaceBlockEvent`] for placing blocks, with build height and reach
+//!   checks.
+//! - [`UseItemEvent`] for using the held item.
+//! - [`AttackEntityEvent`] and [`UseEntityEvent`] for interacting with entities
+//!   in the same instance and within reach.
+//!
+//! Clients predict the outcome of most interactions, so a block broken by a
+//! client disappears on its screen right away. When the server doesn't agree
+//! with the interaction, send a [`CancelInteraction`] and the client is sent
+//! the real state of the affected blocks and held item.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_interaction::{BreakBlockEvent, CancelInteraction};
+//! fn protect_spawn(
+//!     mut breaks: EventReader<BreakBlockEvent>,
+//!     mut cancel: EventWriter<CancelInteraction>,
+//! ) {
+//!     for event in breaks.iter() {
+//!         if event.position.x.abs() < 16 && event.position.z.abs() < 16 {
+//!             cancel.send(event.cancel());
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Interactions that fail a check are cancelled automatically without sending
+//! any of the events. Nothing is changed in the instance by this crate:
+//! breaking and placing the block in response to the events is left to the
+//! server.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use valence_client::{Client, FlushPacketsSet};
+use valence_core::block_pos::BlockPos;
+use valence_core::hand::Hand;
+use valence_core::item::ItemStack;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::entity::Pose;
+use valence_entity::Location;
+use valence_instance::packet::{BlockEntityUpdateS2c, BlockUpdateS2c};
+use valence_instance::Instance;
+use valence_inventory::packet::ScreenHandlerSlotUpdateS2c;
+use valence_inventory::{
+    ClientInventoryState, HeldItem, Inventory, PLAYER_INVENTORY_OFF_HAND_SLOT,
+};
+
+mod digging;
+mod entity;
+mod item;
+mod placement;
+
+pub use digging::{
+    break_progress, AbortDiggingEvent, ActiveDig, BreakBlockEvent, Digging, DiggingConditions,
+    StartDiggingEvent,
+};
+pub use entity::{AttackEntityEvent, UseEntityEvent};
+pub use item::UseItemEvent;
+pub use placement::PlaceBlockEvent;
+
+pub struct InteractionPlugin;
+
+/// When the interaction events are sent in the
+/// [`EventLoopSchedule`](valence_client::event_loop::EventLoopSchedule).
+/// Systems that read them in the event loop should run _after_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct InteractionSet;
+
+/// When cancelled interactions are undone on the client in
+/// [`CoreSet::PostUpdate`]. [`CancelInteraction`]s sent after this arrive one
+/// tick late.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ResyncInteractionsSet;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InteractionSettings>()
+            .add_event::<CancelInteraction>()
+            .configure_set(
+                ResyncInteractionsSet
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(FlushPacketsSet),
+            )
+            .add_system(init_interaction_state.in_base_set(CoreSet::PreUpdate))
+            .add_system(resync_cancelled_interactions.in_set(ResyncInteractionsSet));
+
+        digging::build(app);
+        placement::build(app);
+        item::build(app);
+        entity::build(app);
+    }
+}
+
+/// Limits used to validate client interactions.
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct InteractionSettings {
+    /// The greatest distance from the eyes of a client to the center of a
+    /// block it can break or place against.
+    pub block_reach: f64,
+    /// The greatest distance from the eyes of a client to the hitbox of an
+    /// entity it can interact with.
+    pub entity_reach: f64,
+    /// If clients are prevented from breaking blocks faster than the hardness
+    /// of the block allows.
+    pub validate_break_time: bool,
+    /// The fraction of the break time that must have passed when a client
+    /// finishes breaking a block. This allows for some latency between the
+    /// client and server.
+    pub break_time_tolerance: f32,
+}
+
+impl Default for InteractionSettings {
+    fn default() -> Self {
+        Self {
+            block_reach: 6.0,
+            entity_reach: 6.0,
+            validate_break_time: true,
+            break_time_tolerance: 0.7,
+        }
+    }
+}
+
+/// Send this event to undo an interaction on the client. The blocks and held
+/// item affected by the interaction are sent to the client again.
+///
+/// Use the `cancel` method of the interaction events to create it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CancelInteraction {
+    pub client: Entity,
+    pub interaction: Interaction,
+}
+
+/// An interaction that can be cancelled.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Interaction {
+    /// Breaking or starting to break the block at `position`. The client can't
+    /// finish breaking the block after this is cancelled.
+    Dig {
+        position: BlockPos,
+    },
+    /// Placing a block at `position` against the block at `against`.
+    PlaceBlock {
+        position: BlockPos,
+        against: BlockPos,
+        hand: Hand,
+    },
+    UseItem {
+        hand: Hand,
+    },
+    AttackEntity {
+        target: Entity,
+    },
+    UseEntity {
+        target: Entity,
+        hand: Hand,
+    },
+}
+
+/// Returns the position of the eyes of a client with the given pose.
+pub fn eye_position(position: DVec3, pose: Option<&Pose>) -> DVec3 {
+    let height = match pose.map(|p| p.0) {
+        Some(valence_entity::Pose::Sneaking) => 1.27,
+        Some(
+            valence_entity::Pose::FallFlying
+            | valence_entity::Pose::Swimming
+            | valence_entity::Pose::SpinAttack,
+        ) => 0.4,
+        Some(valence_entity::Pose::Sleeping) => 0.2,
+        _ => 1.62,
+    };
+
+    DVec3::new(position.x, position.y + height, position.z)
+}
+
+/// Returns the distance between `point` and the center of the block at `pos`.
+pub fn distance_to_block(point: DVec3, pos: BlockPos) -> f64 {
+    let center = DVec3::new(pos.x as f64 + 0.5, pos.y as f64 + 0.5, pos.z as f64 + 0.5);
+    point.distance(center)
+}
+
+/// Returns the player inventory slot of the item in `hand`.
+pub(crate) fn hand_slot(held: &HeldItem, hand: Hand) -> u16 {
+    match hand {
+        Hand::Main => held.slot(),
+        Hand::Off => PLAYER_INVENTORY_OFF_HAND_SLOT,
+    }
+}
+
+/// Returns the item stack in `hand`.
+pub(crate) fn stack_in_hand<'a>(
+    inventory: Option<&'a Inventory>,
+    held: Option<&HeldItem>,
+    hand: Hand,
+) -> Option<&'a ItemStack> {
+    inventory?.slot(hand_slot(held?, hand))
+}
+
+fn resync_cancelled_interactions(
+    mut cancellations: EventReader<CancelInteraction>,
+    mut clients: Query<(
+        &mut Client,
+        &Location,
+        &mut Digging,
+        Option<&Inventory>,
+        Option<&HeldItem>,
+        Option<&ClientInventoryState>,
+    )>,
+    instances: Query<&Instance>,
+) {
+    for cancel in cancellations.iter() {
+        let Ok((mut client, loc, mut digging, inventory, held, inv_state)) =
+            clients.get_mut(cancel.client) else {
+            continue
+        };
+
+        let instance = instances.get(loc.0).ok();
+
+        let mut resync_block = |pos: BlockPos| {
+            let Some(block) = instance.and_then(|i| i.block(pos)) else {
+                return
+            };
+
+            client.write_packet(&BlockUpdateS2c {
+                position: pos,
+                block_id: VarInt(block.state().to_raw() as i32),
+            });
+
+            if let (Some(kind), Some(nbt)) = (block.state().block_entity_kind(), block.nbt()) {
+                client.write_packet(&BlockEntityUpdateS2c {
+                    position: pos,
+                    kind: VarInt(kind.id() as i32),
+                    data: Cow::Borrowed(nbt),
+                });
+            }
+        };
+
+        let hand = match cancel.interaction {
+            Interaction::Dig { position } => {
+                resync_block(position);
+
+                if digging.get().is_some_and(|dig| dig.position == position) {
+                    digging.0 = None;
+                }
+
+                None
+            }
+            Interaction::PlaceBlock {
+                position,
+                against,
+                hand,
+            } => {
+                resync_block(position);
+                resync_block(against);
+                Some(hand)
+            }
+            Interaction::UseItem { hand } | Interaction::UseEntity { hand, .. } => Some(hand),
+            Interaction::AttackEntity { .. } => None,
+        };
+
+        if let (Some(hand), Some(inventory), Some(held), Some(inv_state)) =
+            (hand, inventory, held, inv_state)
+        {
+            let slot = hand_slot(held, hand);
+
+            client.write_packet(&ScreenHandlerSlotUpdateS2c {
+                window_id: 0,
+                state_id: VarInt(inv_state.state_id().0),
+                slot_idx: slot as i16,
+                slot_data: Cow::Owned(inventory.slot(slot).cloned()),
+            });
+        }
+    }
+}
+
+/// Adds the components used to track interactions to new clients.
+fn init_interaction_state(
+    clients: Query<Entity, (Added<Client>, Without<Digging>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(Digging::default());
+    }
+}
@@ -0,0 +1,143 @@
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::Vec3;
+use valence_block::{BlockKind, BlockState};
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_client::interact_block::InteractBlockEvent;
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_core::item::ItemStack;
+use valence_entity::entity::Pose;
+use valence_entity::{Location, Position};
+use valence_instance::Instance;
+use valence_inventory::{HeldItem, Inventory};
+
+use crate::{
+    distance_to_block, eye_position, stack_in_hand, CancelInteraction, Interaction, InteractionSet,
+    InteractionSettings,
+};
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<PlaceBlockEvent>().add_system(
+        handle_place_block
+            .in_schedule(EventLoopSchedule)
+            .in_base_set(EventLoopSet::Update)
+            .in_set(InteractionSet),
+    );
+}
+
+/// Sent when a client places the block item in its hand. The block is not
+/// placed in the instance automatically.
+///
+/// This is sent for any click on a block while holding a block item, including
+/// clicks on blocks that open a menu such as chests. Use the
+/// [`InteractBlockEvent`] to handle those.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlaceBlockEvent {
+    pub client: Entity,
+    pub hand: Hand,
+    /// Where the block is placed.
+    pub position: BlockPos,
+    /// The block that was clicked to place the block.
+    pub against: BlockPos,
+    /// The face of [`Self::against`] that was clicked.
+    pub face: Direction,
+    /// The position inside of [`Self::against`] that was clicked.
+    pub cursor_pos: Vec3,
+    /// The item used to place the block.
+    pub item: ItemStack,
+    /// The default state of the block to place. Properties such as the
+    /// direction of stairs are left for the server to decide.
+    pub state: BlockState,
+}
+
+impl PlaceBlockEvent {
+    /// Cancels placing the block. The block and held item are restored on the
+    /// client.
+    pub fn cancel(&self) -> CancelInteraction {
+        CancelInteraction {
+            client: self.client,
+            interaction: Interaction::PlaceBlock {
+                position: self.position,
+                against: self.against,
+                hand: self.hand,
+            },
+        }
+    }
+}
+
+fn handle_place_block(
+    mut interactions: EventReader<InteractBlockEvent>,
+    clients: Query<(
+        &Position,
+        &Location,
+        &GameMode,
+        Option<&Pose>,
+        Option<&Inventory>,
+        Option<&HeldItem>,
+    )>,
+    instances: Query<&Instance>,
+    settings: Res<InteractionSettings>,
+    mut events: EventWriter<PlaceBlockEvent>,
+    mut cancel: EventWriter<CancelInteraction>,
+) {
+    for interaction in interactions.iter() {
+        let Ok((pos, loc, game_mode, pose, inventory, held)) =
+            clients.get(interaction.client) else {
+            continue
+        };
+
+        let Some(item) = stack_in_hand(inventory, held, interaction.hand) else {
+            continue
+        };
+
+        let Some(kind) = BlockKind::from_item_kind(item.item) else {
+            continue
+        };
+
+        // Spectators can't place blocks, even on their own screen.
+        if *game_mode == GameMode::Spectator {
+            continue;
+        }
+
+        let Ok(instance) = instances.get(loc.0) else {
+            continue
+        };
+
+        let against = interaction.position;
+
+        let position = match instance.block(against) {
+            Some(block) if block.state().is_replaceable() => against,
+            _ => against.get_in_direction(interaction.face),
+        };
+
+        let in_build_height = position.y >= instance.min_y()
+            && position.y < instance.min_y() + instance.height() as i32;
+
+        let valid = *game_mode != GameMode::Adventure
+            && in_build_height
+            && distance_to_block(eye_position(pos.0, pose), against) <= settings.block_reach
+            && instance
+                .block(position)
+                .is_some_and(|b| b.state().is_replaceable());
+
+        let event = PlaceBlockEvent {
+            client: interaction.client,
+            hand: interaction.hand,
+            position,
+            against,
+            face: interaction.face,
+            cursor_pos: interaction.cursor_pos,
+            item: item.clone(),
+            state: kind.to_state(),
+        };
+
+        if valid {
+            events.send(event);
+        } else {
+            cancel.send(event.cancel());
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bevy_ecs::prelude::*;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::protocol::Encode;
+
+/// Decides which items clients in creative mode are allowed to put in their
+/// inventory. Creative clients can send any item with any NBT, so items are
+/// passed through [`CreativeItemPolicy::apply`] before they are put in the
+/// inventory.
+///
+/// For custom policies, read the [`CreativeInventoryAction`] events, which
+/// contain the item the client asked for, and set the slot to whatever the
+/// client should get instead.
+///
+/// [`CreativeInventoryAction`]: crate::CreativeInventoryAction
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct CreativeItemPolicy {
+    /// Items that creative clients can't get. These are removed instead.
+    pub denied_items: Vec<ItemKind>,
+    /// Top level NBT tags that are removed from items, such as the contents
+    /// of containers in `BlockEntityTag`.
+    pub stripped_tags: Vec<String>,
+    /// The maximum size of the NBT of an item in bytes. Items with larger NBT
+    /// lose all of it.
+    pub max_nbt_size: usize,
+}
+
+impl Default for CreativeItemPolicy {
+    fn default() -> Self {
+        Self {
+            denied_items: vec![
+                ItemKind::CommandBlock,
+                ItemKind::ChainCommandBlock,
+                ItemKind::RepeatingCommandBlock,
+                ItemKind::CommandBlockMinecart,
+                ItemKind::StructureBlock,
+                ItemKind::Jigsaw,
+                ItemKind::DebugStick,
+            ],
+            stripped_tags: vec!["BlockEntityTag".into(), "EntityTag".into()],
+            max_nbt_size: 65536,
+        }
+    }
+}
+
+impl CreativeItemPolicy {
+    /// Returns the item a creative client gets when it asks for `stack`, or
+    /// `None` if it gets nothing.
+    pub fn apply(&self, mut stack: ItemStack) -> Option<ItemStack> {
+        if stack.item == ItemKind::Air || self.denied_items.contains(&stack.item) {
+            return None;
+        }
+
+        if stack.count() > stack.item.max_stack() {
+            stack.set_count(stack.item.max_stack());
+        }
+
+        if let Some(nbt) = &mut stack.nbt {
+            for tag in &self.stripped_tags {
+                nbt.remove(tag.as_str());
+            }
+
+            let mut buf = vec![];
+            if nbt.is_empty() || nbt.encode(&mut buf).is_err() || buf.len() > self.max_nbt_size {
+                stack.nbt = None;
+            }
+        }
+
+        Some(stack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use valence_nbt::compound;
+
+    use super::*;
+
+    #[test]
+    fn apply_default_policy() {
+        let policy = CreativeItemPolicy::default();
+
+        assert_eq!(
+            policy.apply(ItemStack::new(ItemKind::EnderPearl, 64, None)),
+            Some(ItemStack::new(ItemKind::EnderPearl, 16, None))
+        );
+
+        assert_eq!(
+            policy.apply(ItemStack::new(ItemKind::CommandBlock, 1, None)),
+            None
+        );
+
+        let chest = ItemStack::new(
+            ItemKind::Chest,
+            1,
+            Some(compound! {
+                "BlockEntityTag" => compound! {},
+                "display" => compound! {},
+            }),
+        );
+        assert_eq!(
+            policy.apply(chest),
+            Some(ItemStack::new(
+                ItemKind::Chest,
+                1,
+                Some(compound! { "display" => compound! {} })
+            ))
+        );
+
+        let book = ItemStack::new(
+            ItemKind::WritableBook,
+            1,
+            Some(compound! { "pages" => "a".repeat(100_000) }),
+        );
+        assert_eq!(
+            policy.apply(book),
+            Some(ItemStack::new(ItemKind::WritableBook, 1, None))
+        );
+    }
+}
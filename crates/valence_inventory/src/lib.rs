@@ -25,21 +25,28 @@ use std::ops::Range;
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
+pub use creative::CreativeItemPolicy;
+pub use merchant::{MerchantMenu, Trade, TradeEvent};
 use packet::{
     ClickMode, ClickSlotC2s, CloseHandledScreenC2s, CloseScreenS2c, CreativeInventoryActionC2s,
     InventoryS2c, OpenScreenS2c, ScreenHandlerSlotUpdateS2c, SlotChange, UpdateSelectedSlotC2s,
-    WindowType,
+    UpdateSelectedSlotS2c, WindowType,
 };
 use tracing::{debug, warn};
 use valence_client::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent, RunEventLoopSet};
 use valence_client::packet::{PlayerAction, PlayerActionC2s};
-use valence_client::{Client, FlushPacketsSet, SpawnClientsSet};
+use valence_client::{Client, FlushPacketsSet, SpawnClientsSet, UpdateClientsSet};
 use valence_core::game_mode::GameMode;
 use valence_core::item::ItemStack;
 use valence_core::protocol::encode::WritePacket;
 use valence_core::protocol::var_int::VarInt;
 use valence_core::text::Text;
+use valence_entity::equipment::{Equipment, EquipmentSlot};
+use valence_entity::Location;
+use valence_instance::WriteUpdatePacketsToInstancesSet;
 
+mod creative;
+mod merchant;
 pub mod packet;
 mod validate;
 
@@ -57,11 +64,20 @@ impl Plugin for InventoryPlugin {
             (
                 update_open_inventories,
                 update_client_on_close_inventory.after(update_open_inventories),
+                resync_player_inventories
+                    .after(UpdateClientsSet)
+                    .before(update_player_inventories),
                 update_player_inventories,
+                merchant::send_trade_offers.after(update_open_inventories),
             )
                 .in_base_set(CoreSet::PostUpdate)
                 .before(FlushPacketsSet),
         )
+        .add_system(
+            update_player_equipment
+                .in_base_set(CoreSet::PostUpdate)
+                .before(WriteUpdatePacketsToInstancesSet),
+        )
         .add_systems(
             (
                 handle_update_selected_slot,
@@ -69,15 +85,28 @@ impl Plugin for InventoryPlugin {
                 handle_creative_inventory_action,
                 handle_close_handled_screen,
                 handle_player_actions,
+                merchant::handle_select_merchant_trade,
+                merchant::handle_merchant_close,
             )
                 .in_base_set(EventLoopSet::PreUpdate)
                 .in_schedule(EventLoopSchedule),
         )
+        .add_systems(
+            (
+                merchant::take_trade_results,
+                merchant::update_merchant_results.after(merchant::take_trade_results),
+            )
+                .in_base_set(CoreSet::Update),
+        )
         .init_resource::<InventorySettings>()
+        .init_resource::<CreativeItemPolicy>()
         .add_event::<ClickSlot>()
+        .add_event::<ClickResultSlot>()
         .add_event::<DropItemStack>()
+        .add_event::<TradeEvent>()
         .add_event::<CreativeInventoryAction>()
-        .add_event::<UpdateSelectedSlot>();
+        .add_event::<UpdateSelectedSlot>()
+        .add_event::<SwapHands>();
     }
 }
 
@@ -85,6 +114,13 @@ impl Plugin for InventoryPlugin {
 /// plus the hotbar.
 pub const PLAYER_INVENTORY_MAIN_SLOTS_COUNT: u16 = 36;
 
+/// The slot of the player inventory holding the item in the off hand.
+pub const PLAYER_INVENTORY_OFF_HAND_SLOT: u16 = 45;
+
+/// The bits of the changed slots mask for the main slots of the player
+/// inventory, which are slots 9 to 44.
+const PLAYER_INVENTORY_MAIN_SLOTS_MASK: u64 = ((1 << PLAYER_INVENTORY_MAIN_SLOTS_COUNT) - 1) << 9;
+
 #[derive(Debug, Clone, Component)]
 pub struct Inventory {
     title: Text,
@@ -93,6 +129,11 @@ pub struct Inventory {
     /// Contains a set bit for each modified slot in `slots`.
     #[doc(hidden)]
     pub changed: u64,
+    /// Whether clients are prevented from moving items in and out of this
+    /// inventory when they have it open. Clicks on the slots of a readonly
+    /// inventory are undone for the client, but are still sent as
+    /// [`ClickSlot`] events.
+    pub readonly: bool,
 }
 
 impl Inventory {
@@ -107,6 +148,7 @@ impl Inventory {
             kind,
             slots: vec![None; kind.slot_count()].into(),
             changed: 0,
+            readonly: false,
         }
     }
 
@@ -372,12 +414,20 @@ pub struct CursorItem(pub Option<ItemStack>);
 
 /// Used to indicate that the client with this component is currently viewing
 /// an inventory.
+///
+/// Replacing this component with a new one opens the new inventory in place of
+/// the old one. This can also be used to reopen the same inventory, for
+/// example after changing its title.
 #[derive(Component, Clone, Debug)]
 pub struct OpenInventory {
     /// The entity with the `Inventory` component that the client is currently
     /// viewing.
     pub entity: Entity,
     client_changed: u64,
+    /// Whether the inventory has been sent to the client.
+    opened: bool,
+    /// Whether the inventory was sent to the client in this tick.
+    just_opened: bool,
 }
 
 impl OpenInventory {
@@ -385,6 +435,8 @@ impl OpenInventory {
         OpenInventory {
             entity,
             client_changed: 0,
+            opened: false,
+            just_opened: false,
         }
     }
 }
@@ -550,6 +602,53 @@ fn init_new_client_inventories(clients: Query<Entity, Added<Client>>, mut comman
     }
 }
 
+/// Clients forget their inventory and held item when respawning, so the whole
+/// inventory is sent again to clients that changed instances.
+fn resync_player_inventories(
+    mut clients: Query<(&mut Client, &mut Inventory, &HeldItem), Changed<Location>>,
+) {
+    for (mut client, mut inventory, held_item) in &mut clients {
+        if client.is_added() {
+            // The inventory is sent when joining anyway.
+            continue;
+        }
+
+        inventory.changed = u64::MAX;
+
+        client.write_packet(&UpdateSelectedSlotS2c {
+            slot: (held_item.slot() - 36) as u8,
+        });
+    }
+}
+
+/// Shows the held items and armor of clients to the clients viewing them.
+fn update_player_equipment(
+    mut clients: Query<
+        (&Inventory, &HeldItem, &mut Equipment),
+        (
+            With<Client>,
+            Or<(Changed<Inventory>, Changed<HeldItem>, Added<Equipment>)>,
+        ),
+    >,
+) {
+    for (inventory, held_item, mut equipment) in &mut clients {
+        for (slot, idx) in [
+            (EquipmentSlot::MainHand, held_item.slot()),
+            (EquipmentSlot::OffHand, PLAYER_INVENTORY_OFF_HAND_SLOT),
+            (EquipmentSlot::Head, 5),
+            (EquipmentSlot::Chest, 6),
+            (EquipmentSlot::Legs, 7),
+            (EquipmentSlot::Feet, 8),
+        ] {
+            let item = inventory.slot(idx);
+
+            if equipment.get(slot) != item {
+                equipment.set(slot, item.cloned());
+            }
+        }
+    }
+}
+
 /// Send updates for each client's player inventory.
 fn update_player_inventories(
     mut query: Query<
@@ -634,7 +733,7 @@ fn update_open_inventories(
         Entity,
         &mut Client,
         &mut ClientInventoryState,
-        &CursorItem,
+        Ref<CursorItem>,
         &mut OpenInventory,
     )>,
     mut inventories: Query<&mut Inventory>,
@@ -657,10 +756,13 @@ fn update_open_inventories(
             continue;
         };
 
-        if open_inventory.is_added() {
+        open_inventory.just_opened = !open_inventory.opened;
+
+        if !open_inventory.opened {
             // Send the inventory to the client if the client just opened the inventory.
             inv_state.window_id = inv_state.window_id % 100 + 1;
             open_inventory.client_changed = 0;
+            open_inventory.opened = true;
 
             client.write_packet(&OpenScreenS2c {
                 window_id: VarInt(inv_state.window_id.into()),
@@ -711,10 +813,45 @@ fn update_open_inventories(
             }
         }
 
+        let window_slot_offset = inventory.slot_count();
+        inventory.changed = 0;
+
+        // The main slots of the player's inventory are shown below the open inventory,
+        // so changes to them are sent as part of the window.
+        if let Ok(mut player_inventory) = inventories.get_mut(client_entity) {
+            let main_slots = PLAYER_INVENTORY_MAIN_SLOTS_MASK & !inv_state.slots_changed;
+            let changed_filtered = player_inventory.changed & main_slots;
+
+            if changed_filtered != 0 {
+                inv_state.state_id += 1;
+
+                for (i, slot) in player_inventory.slots.iter().enumerate() {
+                    if (changed_filtered >> i) & 1 == 1 {
+                        client.write_packet(&ScreenHandlerSlotUpdateS2c {
+                            window_id: inv_state.window_id as i8,
+                            state_id: VarInt(inv_state.state_id.0),
+                            slot_idx: (window_slot_offset + i as u16 - 9) as i16,
+                            slot_data: Cow::Borrowed(slot),
+                        });
+                    }
+                }
+            }
+
+            player_inventory.changed &= !PLAYER_INVENTORY_MAIN_SLOTS_MASK;
+        }
+
+        if cursor_item.is_changed() && !inv_state.client_updated_cursor_item {
+            client.write_packet(&ScreenHandlerSlotUpdateS2c {
+                window_id: -1,
+                state_id: VarInt(inv_state.state_id.0),
+                slot_idx: -1,
+                slot_data: Cow::Borrowed(&cursor_item.0),
+            });
+        }
+
         open_inventory.client_changed = 0;
         inv_state.slots_changed = 0;
         inv_state.client_updated_cursor_item = false;
-        inventory.changed = 0;
     }
 }
 
@@ -733,7 +870,9 @@ fn handle_close_handled_screen(mut packets: EventReader<PacketEvent>, mut comman
 /// indicates that the client is no longer viewing an inventory.
 fn update_client_on_close_inventory(
     mut removals: RemovedComponents<OpenInventory>,
-    mut clients: Query<(&mut Client, &ClientInventoryState)>,
+    // Clients that opened another inventory in the same tick don't need to close
+    // the old one.
+    mut clients: Query<(&mut Client, &ClientInventoryState), Without<OpenInventory>>,
 ) {
     for entity in &mut removals {
         if let Ok((mut client, inv_state)) = clients.get_mut(entity) {
@@ -757,6 +896,24 @@ pub struct ClickSlot {
     pub carried_item: Option<ItemStack>,
 }
 
+/// Sent when a client clicks a slot whose contents are computed by the server,
+/// like the result slot of a crafting grid in the player's own inventory or in
+/// an open [`InventoryKind::Crafting`] inventory, or the result slot of an
+/// [`InventoryKind::Merchant`] inventory.
+///
+/// Unlike other clicks, these are not applied to the inventory. It is up to the
+/// handler of this event to fill the cursor or the player's inventory with the
+/// result and to consume the inputs.
+#[derive(Clone, Debug)]
+pub struct ClickResultSlot {
+    pub client: Entity,
+    pub window_id: u8,
+    pub button: i8,
+    /// One of [`ClickMode::Click`], [`ClickMode::ShiftClick`] or
+    /// [`ClickMode::Hotbar`].
+    pub mode: ClickMode,
+}
+
 #[derive(Clone, Debug)]
 pub struct DropItemStack {
     pub client: Entity,
@@ -764,6 +921,57 @@ pub struct DropItemStack {
     pub stack: ItemStack,
 }
 
+/// Returns the slots of an open inventory followed by the main slots of the
+/// player inventory, which is how the client sees the window.
+fn window_slots(
+    open_inventory: &Inventory,
+    player_inventory: &Inventory,
+) -> Vec<Option<ItemStack>> {
+    open_inventory
+        .slots
+        .iter()
+        .chain(&player_inventory.slots[9..45])
+        .cloned()
+        .collect()
+}
+
+/// Returns the slot of an inventory whose contents are computed by the server
+/// from the other slots.
+fn computed_result_slot(kind: InventoryKind) -> Option<i16> {
+    match kind {
+        InventoryKind::Player | InventoryKind::Crafting => Some(0),
+        InventoryKind::Merchant => Some(2),
+        _ => None,
+    }
+}
+
+/// Sends the whole window to the client, replacing whatever the client thinks
+/// the window looks like.
+fn resync_inventory(
+    client: &mut Client,
+    inv_state: &mut ClientInventoryState,
+    player_inventory: &Inventory,
+    open_inventory: Option<&Inventory>,
+    cursor_item: &CursorItem,
+) {
+    inv_state.state_id += 1;
+
+    match open_inventory {
+        Some(open_inventory) => client.write_packet(&InventoryS2c {
+            window_id: inv_state.window_id,
+            state_id: VarInt(inv_state.state_id.0),
+            slots: Cow::Owned(window_slots(open_inventory, player_inventory)),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        }),
+        None => client.write_packet(&InventoryS2c {
+            window_id: 0,
+            state_id: VarInt(inv_state.state_id.0),
+            slots: Cow::Borrowed(player_inventory.slot_slice()),
+            carried_item: Cow::Borrowed(&cursor_item.0),
+        }),
+    }
+}
+
 fn handle_click_slot(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(
@@ -772,10 +980,12 @@ fn handle_click_slot(
         &mut ClientInventoryState,
         Option<&mut OpenInventory>,
         &mut CursorItem,
+        &GameMode,
     )>,
     mut inventories: Query<&mut Inventory, Without<Client>>,
     mut drop_item_stack_events: EventWriter<DropItemStack>,
     mut click_slot_events: EventWriter<ClickSlot>,
+    mut click_result_slot_events: EventWriter<ClickResultSlot>,
 ) {
     for packet in packets.iter() {
         let Some(pkt) = packet.decode::<ClickSlotC2s>() else {
@@ -788,21 +998,140 @@ fn handle_click_slot(
             mut client_inv,
             mut inv_state,
             open_inventory,
-            mut cursor_item
+            mut cursor_item,
+            &game_mode,
         )) = clients.get_mut(packet.client) else {
             // The client does not exist, ignore.
             continue;
         };
 
-        let open_inv = open_inventory
-            .as_ref()
-            .and_then(|open| inventories.get_mut(open.entity).ok());
+        let open_inv = match &open_inventory {
+            Some(open) => {
+                let Ok(inventory) = inventories.get_mut(open.entity) else {
+                    // The inventory does not exist, ignore.
+                    continue;
+                };
+
+                Some(inventory)
+            }
+            None => None,
+        };
+
+        let window_id = if open_inv.is_some() {
+            inv_state.window_id
+        } else {
+            0
+        };
+
+        if pkt.window_id != window_id {
+            // The client is clicking in a window that was closed, or one it never had.
+            debug!(
+                "Client sent a click for window {} while window {} is open, ignoring",
+                pkt.window_id, window_id
+            );
+            continue;
+        }
+
+        if inv_state.state_id.0 != pkt.state_id.0 {
+            // Client is out of sync. Resync and ignore the click.
+
+            debug!("Client state id mismatch, resyncing");
+
+            resync_inventory(
+                &mut client,
+                &mut inv_state,
+                &client_inv,
+                open_inv.as_deref(),
+                &cursor_item,
+            );
+
+            continue;
+        }
+
+        let result_slot = match &open_inv {
+            Some(inv) => computed_result_slot(inv.kind()),
+            None => computed_result_slot(InventoryKind::Player),
+        };
+
+        if result_slot == Some(pkt.slot_idx) {
+            // The client is taking the result of a crafting grid or a trade. The result
+            // depends on state known to the server, so the client's prediction is
+            // discarded and the click is handed to whoever fills the result slot.
+
+            resync_inventory(
+                &mut client,
+                &mut inv_state,
+                &client_inv,
+                open_inv.as_deref(),
+                &cursor_item,
+            );
+
+            if matches!(
+                pkt.mode,
+                ClickMode::Click | ClickMode::ShiftClick | ClickMode::Hotbar
+            ) {
+                click_result_slot_events.send(ClickResultSlot {
+                    client: packet.client,
+                    window_id: pkt.window_id,
+                    button: pkt.button,
+                    mode: pkt.mode,
+                });
+            }
+
+            continue;
+        }
+
+        if let Some(open_inv) = open_inv.as_ref().filter(|inv| inv.readonly) {
+            let target_slots = 0..open_inv.slot_count() as i16;
+
+            if target_slots.contains(&pkt.slot_idx)
+                || pkt
+                    .slot_changes
+                    .iter()
+                    .any(|slot| target_slots.contains(&slot.idx))
+            {
+                // The client is moving items in or out of a readonly inventory. Undo the
+                // click by sending the whole window again.
+
+                resync_inventory(
+                    &mut client,
+                    &mut inv_state,
+                    &client_inv,
+                    Some(open_inv),
+                    &cursor_item,
+                );
+
+                if pkt.mode == ClickMode::Hotbar && pkt.button == 40 {
+                    // The offhand slot isn't part of the window.
+                    client.write_packet(&ScreenHandlerSlotUpdateS2c {
+                        window_id: 0,
+                        state_id: VarInt(inv_state.state_id.0),
+                        slot_idx: 45,
+                        slot_data: Cow::Borrowed(&client_inv.slots[45]),
+                    });
+                }
+
+                click_slot_events.send(ClickSlot {
+                    client: packet.client,
+                    window_id: pkt.window_id,
+                    state_id: pkt.state_id.0,
+                    slot_id: pkt.slot_idx,
+                    button: pkt.button,
+                    mode: pkt.mode,
+                    slot_changes: pkt.slot_changes,
+                    carried_item: pkt.carried_item,
+                });
+
+                continue;
+            }
+        }
 
         if let Err(e) = validate::validate_click_slot_packet(
             &pkt,
             &client_inv,
             open_inv.as_deref(),
             &cursor_item,
+            game_mode,
         ) {
             debug!(
                 "failed to validate click slot packet for client {:#?}: \"{e:#}\" {pkt:#?}",
@@ -811,16 +1140,13 @@ fn handle_click_slot(
 
             // Resync the inventory.
 
-            client.write_packet(&InventoryS2c {
-                window_id: if open_inv.is_some() {
-                    inv_state.window_id
-                } else {
-                    0
-                },
-                state_id: VarInt(inv_state.state_id.0),
-                slots: Cow::Borrowed(open_inv.unwrap_or(client_inv).slot_slice()),
-                carried_item: Cow::Borrowed(&cursor_item.0),
-            });
+            resync_inventory(
+                &mut client,
+                &mut inv_state,
+                &client_inv,
+                open_inv.as_deref(),
+                &cursor_item,
+            );
 
             continue;
         }
@@ -850,23 +1176,6 @@ fn handle_click_slot(
                     continue;
                 };
 
-                if inv_state.state_id.0 != pkt.state_id.0 {
-                    // Client is out of sync. Resync and ignore click.
-
-                    debug!("Client state id mismatch, resyncing");
-
-                    inv_state.state_id += 1;
-
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(target_inventory.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
-
-                    continue;
-                }
-
                 if (0i16..target_inventory.slot_count() as i16).contains(&pkt.slot_idx) {
                     // The player is dropping an item from another inventory.
 
@@ -944,17 +1253,6 @@ fn handle_click_slot(
         } else {
             // The player is clicking a slot in an inventory.
 
-            // Validate the window id.
-            if (pkt.window_id == 0) != open_inventory.is_none() {
-                warn!(
-                    "Client sent a click with an invalid window id for current state: window_id = \
-                     {}, open_inventory present = {}",
-                    pkt.window_id,
-                    open_inventory.is_some()
-                );
-                continue;
-            }
-
             if let Some(mut open_inventory) = open_inventory {
                 // The player is interacting with an inventory that is open.
 
@@ -963,24 +1261,25 @@ fn handle_click_slot(
                     continue;
                 };
 
-                if inv_state.state_id.0 != pkt.state_id.0 {
-                    // Client is out of sync. Resync and ignore click.
-
-                    debug!("Client state id mismatch, resyncing");
-
-                    inv_state.state_id += 1;
-
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(target_inventory.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
+                if pkt.mode == ClickMode::Hotbar && pkt.button == 40 {
+                    // The offhand slot isn't part of the window, so the client doesn't
+                    // tell us that the clicked item was moved there.
+                    let old_slot = if (0i16..target_inventory.slot_count() as i16)
+                        .contains(&pkt.slot_idx)
+                    {
+                        target_inventory.slot(pkt.slot_idx as u16).cloned()
+                    } else {
+                        let slot_id =
+                            convert_to_player_slot_id(target_inventory.kind, pkt.slot_idx as u16);
+                        client_inv.slot(slot_id).cloned()
+                    };
 
-                    continue;
+                    client_inv.set_slot(45, old_slot);
+                    inv_state.slots_changed |= 1 << 45;
                 }
 
                 cursor_item.set_if_neq(CursorItem(pkt.carried_item.clone()));
+                inv_state.client_updated_cursor_item = true;
 
                 for slot in pkt.slot_changes.clone() {
                     if (0i16..target_inventory.slot_count() as i16).contains(&slot.idx) {
@@ -998,23 +1297,6 @@ fn handle_click_slot(
             } else {
                 // The client is interacting with their own inventory.
 
-                if inv_state.state_id.0 != pkt.state_id.0 {
-                    // Client is out of sync. Resync and ignore the click.
-
-                    debug!("Client state id mismatch, resyncing");
-
-                    inv_state.state_id += 1;
-
-                    client.write_packet(&InventoryS2c {
-                        window_id: inv_state.window_id,
-                        state_id: VarInt(inv_state.state_id.0),
-                        slots: Cow::Borrowed(client_inv.slot_slice()),
-                        carried_item: Cow::Borrowed(&cursor_item.0),
-                    });
-
-                    continue;
-                }
-
                 cursor_item.set_if_neq(CursorItem(pkt.carried_item.clone()));
                 inv_state.client_updated_cursor_item = true;
 
@@ -1047,10 +1329,22 @@ fn handle_click_slot(
     }
 }
 
+/// Sent when a client swaps the items in its main and off hand. The items
+/// are already swapped in its inventory.
+#[derive(Clone, Debug)]
+pub struct SwapHands {
+    pub client: Entity,
+    /// The item in the main hand after the swap.
+    pub main_hand: Option<ItemStack>,
+    /// The item in the off hand after the swap.
+    pub off_hand: Option<ItemStack>,
+}
+
 fn handle_player_actions(
     mut packets: EventReader<PacketEvent>,
     mut clients: Query<(&mut Inventory, &mut ClientInventoryState, &HeldItem)>,
     mut drop_item_stack_events: EventWriter<DropItemStack>,
+    mut swap_hands_events: EventWriter<SwapHands>,
 ) {
     for packet in packets.iter() {
         if let Some(pkt) = packet.decode::<PlayerActionC2s>() {
@@ -1091,7 +1385,17 @@ fn handle_player_actions(
                     }
                 }
                 PlayerAction::SwapItemWithOffhand => {
-                    // TODO
+                    if let Ok((mut inv, _, &held)) = clients.get_mut(packet.client) {
+                        // The client doesn't swap the items on its own, so the
+                        // swapped slots are sent back to it.
+                        inv.swap_slot(held.slot(), PLAYER_INVENTORY_OFF_HAND_SLOT);
+
+                        swap_hands_events.send(SwapHands {
+                            client: packet.client,
+                            main_hand: inv.slot(held.slot()).cloned(),
+                            off_hand: inv.slot(PLAYER_INVENTORY_OFF_HAND_SLOT).cloned(),
+                        });
+                    }
                 }
                 _ => {}
             }
@@ -1099,12 +1403,16 @@ fn handle_player_actions(
     }
 }
 
-// TODO: make this event user friendly.
+/// Sent when a client in creative mode puts an item in a slot of its
+/// inventory, or drops an item when the slot is `-1`.
 #[derive(Clone, Debug)]
 pub struct CreativeInventoryAction {
     pub client: Entity,
     pub slot: i16,
+    /// The item put in the slot after the [`CreativeItemPolicy`] was applied.
     pub clicked_item: Option<ItemStack>,
+    /// The item the client asked for.
+    pub requested_item: Option<ItemStack>,
 }
 
 fn handle_creative_inventory_action(
@@ -1117,9 +1425,10 @@ fn handle_creative_inventory_action(
     )>,
     mut inv_action_events: EventWriter<CreativeInventoryAction>,
     mut drop_item_stack_events: EventWriter<DropItemStack>,
+    policy: Res<CreativeItemPolicy>,
 ) {
     for packet in packets.iter() {
-        if let Some(pkt) = packet.decode::<CreativeInventoryActionC2s>() {
+        if let Some(mut pkt) = packet.decode::<CreativeInventoryActionC2s>() {
             let Ok((mut client, mut inventory, mut inv_state, game_mode)) = clients.get_mut(packet.client) else {
                 continue
             };
@@ -1129,6 +1438,9 @@ fn handle_creative_inventory_action(
                 continue;
             }
 
+            let requested_item = pkt.clicked_item.take();
+            pkt.clicked_item = requested_item.clone().and_then(|stack| policy.apply(stack));
+
             if pkt.slot == -1 {
                 if let Some(stack) = pkt.clicked_item.clone() {
                     drop_item_stack_events.send(DropItemStack {
@@ -1137,6 +1449,14 @@ fn handle_creative_inventory_action(
                         stack,
                     });
                 }
+
+                inv_action_events.send(CreativeInventoryAction {
+                    client: packet.client,
+                    slot: pkt.slot,
+                    clicked_item: pkt.clicked_item,
+                    requested_item,
+                });
+
                 continue;
             }
 
@@ -1165,11 +1485,14 @@ fn handle_creative_inventory_action(
                 client: packet.client,
                 slot: pkt.slot,
                 clicked_item: pkt.clicked_item,
+                requested_item,
             });
         }
     }
 }
 
+/// Sent when a client selects another slot of its hotbar. Its [`HeldItem`] is
+/// already updated, and the new item is shown to other clients.
 #[derive(Clone, Debug)]
 pub struct UpdateSelectedSlot {
     pub client: Entity,
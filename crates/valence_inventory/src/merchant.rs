@@ -0,0 +1,580 @@
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
+use valence_client::event_loop::PacketEvent;
+use valence_client::Client;
+use valence_core::item::ItemStack;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+
+use crate::packet::{
+    ClickMode, CloseHandledScreenC2s, SelectMerchantTradeC2s, SetTradeOffersS2c, TradeOffer,
+};
+use crate::{
+    ClickResultSlot, ClientInventoryState, CursorItem, DropItemStack, Inventory, OpenInventory,
+    PLAYER_INVENTORY_MAIN_SLOTS_COUNT,
+};
+
+/// The slots of the player inventory that are shown below a merchant
+/// inventory.
+const PLAYER_MAIN_SLOTS: Range<u16> = 9..9 + PLAYER_INVENTORY_MAIN_SLOTS_COUNT;
+
+/// The slot of a merchant inventory that shows the output of the trade.
+const RESULT_SLOT: u16 = 2;
+
+/// The trades of an [`InventoryKind::Merchant`] inventory. Add this component
+/// to the entity with the merchant [`Inventory`] and open it with
+/// [`OpenInventory`] like any other inventory.
+///
+/// The input slots of merchant inventories hold items the client put there, so
+/// each client should be given its own merchant inventory. The items in the
+/// input slots are returned to the client when the inventory is closed.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use valence_core::item::{ItemKind, ItemStack};
+/// # use valence_inventory::*;
+/// fn open_shop(client: Entity, commands: &mut Commands) {
+///     let mut trade = Trade::new(
+///         ItemStack::new(ItemKind::Emerald, 3, None),
+///         None,
+///         ItemStack::new(ItemKind::Bread, 6, None),
+///     );
+///     trade.max_uses = 16;
+///
+///     let shop = commands
+///         .spawn((
+///             Inventory::with_title(InventoryKind::Merchant, "Baker"),
+///             MerchantMenu::new(vec![trade]),
+///         ))
+///         .id();
+///
+///     commands.entity(client).insert(OpenInventory::new(shop));
+/// }
+/// ```
+///
+/// [`InventoryKind::Merchant`]: crate::InventoryKind::Merchant
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct MerchantMenu {
+    pub trades: Vec<Trade>,
+    /// The level of the merchant, from 1 to 5. Only shown if
+    /// `is_regular_villager` is set.
+    pub level: i32,
+    /// The experience of the merchant, shown as a progress bar towards the
+    /// next level.
+    pub experience: i32,
+    /// Whether the level and experience of the merchant are shown.
+    pub is_regular_villager: bool,
+    /// Whether the client is told that the merchant restocks its trades. This
+    /// only changes the text shown for trades that are out of stock.
+    pub can_restock: bool,
+    selected_trade: Option<usize>,
+}
+
+impl MerchantMenu {
+    pub fn new(trades: Vec<Trade>) -> Self {
+        Self {
+            trades,
+            level: 1,
+            experience: 0,
+            is_regular_villager: false,
+            can_restock: false,
+            selected_trade: None,
+        }
+    }
+
+    /// Returns the index of the trade the client last selected in the list of
+    /// trades.
+    pub fn selected_trade(&self) -> Option<usize> {
+        self.selected_trade
+    }
+
+    /// Returns the index of the trade that can be made with the items in the
+    /// input slots, and whether the items are in the opposite slots. The
+    /// selected trade is preferred.
+    fn trade_for(
+        &self,
+        input_one: Option<&ItemStack>,
+        input_two: Option<&ItemStack>,
+    ) -> Option<(usize, bool)> {
+        let matches = |idx: usize| {
+            let trade = self.trades.get(idx)?;
+
+            if trade.matches(input_one, input_two) {
+                Some((idx, false))
+            } else if trade.matches(input_two, input_one) {
+                Some((idx, true))
+            } else {
+                None
+            }
+        };
+
+        match self.selected_trade {
+            Some(idx) => matches(idx),
+            None => (0..self.trades.len()).find_map(matches),
+        }
+    }
+
+    fn to_packet(&self, window_id: u8) -> SetTradeOffersS2c {
+        SetTradeOffersS2c {
+            window_id: VarInt(window_id.into()),
+            trades: self.trades.iter().map(Trade::to_offer).collect(),
+            villager_level: VarInt(self.level),
+            experience: VarInt(self.experience),
+            is_regular_villager: self.is_regular_villager,
+            can_restock: self.can_restock,
+        }
+    }
+}
+
+/// A trade offered by a [`MerchantMenu`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Trade {
+    /// The first item the client pays with. Its count is adjusted by the
+    /// demand and special price, see [`Trade::price`].
+    pub input_one: ItemStack,
+    /// The second item the client pays with.
+    pub input_two: Option<ItemStack>,
+    pub output: ItemStack,
+    /// The number of times the trade has been made.
+    pub uses: i32,
+    /// The number of times the trade can be made before it is out of stock.
+    pub max_uses: i32,
+    /// The experience the merchant gets when the trade is made. This is only
+    /// used by the client to preview the progress of the merchant.
+    pub xp: i32,
+    /// Added to the count of the first input, like the discount vanilla
+    /// villagers give after being cured.
+    pub special_price: i32,
+    pub price_multiplier: f32,
+    pub demand: i32,
+}
+
+impl Trade {
+    /// Creates a trade that can be made an unlimited number of times.
+    pub fn new(input_one: ItemStack, input_two: Option<ItemStack>, output: ItemStack) -> Self {
+        Self {
+            input_one,
+            input_two,
+            output,
+            uses: 0,
+            max_uses: i32::MAX,
+            xp: 0,
+            special_price: 0,
+            price_multiplier: 0.0,
+            demand: 0,
+        }
+    }
+
+    pub fn is_out_of_stock(&self) -> bool {
+        self.uses >= self.max_uses
+    }
+
+    /// Returns the first input with its count adjusted by the demand, price
+    /// multiplier and special price, which is what the client pays.
+    pub fn price(&self) -> ItemStack {
+        let count = self.input_one.count() as i32;
+        let demand_cost = ((count * self.demand) as f32 * self.price_multiplier)
+            .floor()
+            .max(0.0) as i32;
+
+        let mut price = self.input_one.clone();
+        price.set_count(
+            (count + demand_cost + self.special_price).clamp(1, price.item.max_stack().into())
+                as u8,
+        );
+        price
+    }
+
+    fn matches(&self, input_one: Option<&ItemStack>, input_two: Option<&ItemStack>) -> bool {
+        pays(input_one, Some(&self.price())) && pays(input_two, self.input_two.as_ref())
+    }
+
+    fn to_offer(&self) -> TradeOffer {
+        TradeOffer {
+            input_one: Some(self.input_one.clone()),
+            output_item: Some(self.output.clone()),
+            input_two: self.input_two.clone(),
+            trade_disabled: self.is_out_of_stock(),
+            number_of_trade_uses: self.uses,
+            max_trade_uses: self.max_uses,
+            xp: self.xp,
+            special_price: self.special_price,
+            price_multiplier: self.price_multiplier,
+            demand: self.demand,
+        }
+    }
+}
+
+/// An event sent when a client makes a trade.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TradeEvent {
+    pub client: Entity,
+    /// The entity with the [`MerchantMenu`].
+    pub inventory: Entity,
+    /// The index of the trade in [`MerchantMenu::trades`].
+    pub trade: usize,
+}
+
+/// Returns if the offered item pays for the cost.
+fn pays(offered: Option<&ItemStack>, cost: Option<&ItemStack>) -> bool {
+    match (offered, cost) {
+        (_, None) => offered.is_none(),
+        (None, Some(_)) => false,
+        (Some(offered), Some(cost)) => {
+            offered.item == cost.item
+                && offered.count() >= cost.count()
+                && (cost.nbt.is_none() || offered.nbt == cost.nbt)
+        }
+    }
+}
+
+fn can_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && a.nbt == b.nbt
+}
+
+/// Returns if the stack fits in the main slots of the player inventory.
+fn can_insert(player_inventory: &Inventory, stack: &ItemStack) -> bool {
+    let max = stack.item.max_stack() as u32;
+
+    let space: u32 = PLAYER_MAIN_SLOTS
+        .map(|idx| match player_inventory.slot(idx) {
+            None => max,
+            Some(slot) if can_stack(slot, stack) => max.saturating_sub(slot.count() as u32),
+            Some(_) => 0,
+        })
+        .sum();
+
+    space >= stack.count() as u32
+}
+
+/// Puts a stack in the main slots of the player inventory, starting at the end
+/// of the hotbar like vanilla does. Returns the items that didn't fit.
+fn insert(player_inventory: &mut Inventory, mut stack: ItemStack) -> Option<ItemStack> {
+    let max = stack.item.max_stack();
+
+    for idx in PLAYER_MAIN_SLOTS.rev() {
+        let Some(mut slot) = player_inventory.slot(idx).cloned() else {
+            continue
+        };
+
+        if can_stack(&slot, &stack) && slot.count() < max {
+            let moved = (max - slot.count()).min(stack.count());
+
+            slot.set_count(slot.count() + moved);
+            player_inventory.set_slot(idx, slot);
+
+            if moved == stack.count() {
+                return None;
+            }
+
+            stack.set_count(stack.count() - moved);
+        }
+    }
+
+    for idx in PLAYER_MAIN_SLOTS.rev() {
+        if player_inventory.slot(idx).is_none() {
+            player_inventory.set_slot(idx, stack);
+            return None;
+        }
+    }
+
+    Some(stack)
+}
+
+/// Removes `count` items from a slot, emptying it if none are left.
+fn remove_items(inventory: &mut Inventory, idx: u16, count: u8) {
+    let Some(stack) = inventory.slot(idx) else {
+        return
+    };
+
+    if stack.count() > count {
+        inventory.set_slot_amount(idx, stack.count() - count);
+    } else {
+        inventory.set_slot(idx, None);
+    }
+}
+
+/// Moves the items in the input slots of a merchant inventory to the player.
+/// Returns the items that didn't fit.
+fn return_inputs(merchant: &mut Inventory, player_inventory: &mut Inventory) -> Vec<ItemStack> {
+    let mut leftovers = vec![];
+
+    for idx in 0..RESULT_SLOT {
+        if let Some(stack) = merchant.replace_slot(idx, None) {
+            leftovers.extend(insert(player_inventory, stack));
+        }
+    }
+
+    leftovers
+}
+
+/// Moves items matching the cost from the player inventory to an input slot,
+/// up to a full stack.
+fn fill_input(
+    merchant: &mut Inventory,
+    player_inventory: &mut Inventory,
+    idx: u16,
+    cost: &ItemStack,
+) {
+    let max = cost.item.max_stack();
+
+    for slot_idx in PLAYER_MAIN_SLOTS {
+        let count = merchant.slot(idx).map_or(0, ItemStack::count);
+
+        if count >= max {
+            break;
+        }
+
+        let Some(mut stack) = player_inventory.slot(slot_idx).cloned() else {
+            continue
+        };
+
+        if !pays(
+            Some(&stack),
+            Some(&ItemStack::new(cost.item, 1, cost.nbt.clone())),
+        ) {
+            continue;
+        }
+
+        let moved = (max - count).min(stack.count());
+
+        let mut input = stack.clone();
+        input.set_count(count + moved);
+        merchant.set_slot(idx, input);
+
+        if moved == stack.count() {
+            player_inventory.set_slot(slot_idx, None);
+        } else {
+            stack.set_count(stack.count() - moved);
+            player_inventory.set_slot(slot_idx, stack);
+        }
+    }
+}
+
+/// Sends the trades to clients when they open a merchant inventory or when the
+/// trades change.
+pub(crate) fn send_trade_offers(
+    mut clients: Query<(&mut Client, &ClientInventoryState, &OpenInventory)>,
+    menus: Query<Ref<MerchantMenu>>,
+) {
+    for (mut client, inv_state, open_inventory) in &mut clients {
+        let Ok(menu) = menus.get(open_inventory.entity) else {
+            continue
+        };
+
+        if open_inventory.just_opened || menu.is_changed() {
+            client.write_packet(&menu.to_packet(inv_state.window_id));
+        }
+    }
+}
+
+/// Handles clients selecting a trade, which moves the items needed for the
+/// trade from the player inventory to the input slots.
+pub(crate) fn handle_select_merchant_trade(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Inventory, &OpenInventory), With<Client>>,
+    mut merchants: Query<(&mut Inventory, &mut MerchantMenu), Without<Client>>,
+) {
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<SelectMerchantTradeC2s>() else {
+            continue
+        };
+
+        let Ok((mut player_inventory, open_inventory)) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let Ok((mut merchant, mut menu)) = merchants.get_mut(open_inventory.entity) else {
+            continue
+        };
+
+        let Ok(idx) = usize::try_from(pkt.selected_slot.0) else {
+            continue
+        };
+
+        let Some(trade) = menu.trades.get(idx).cloned() else {
+            continue
+        };
+
+        menu.bypass_change_detection().selected_trade = Some(idx);
+
+        let leftovers = return_inputs(&mut merchant, &mut player_inventory);
+
+        if !leftovers.is_empty() {
+            // The player inventory is full, so the items stay in the input slots.
+            for (idx, stack) in (0..RESULT_SLOT).zip(leftovers) {
+                merchant.set_slot(idx, stack);
+            }
+
+            continue;
+        }
+
+        fill_input(&mut merchant, &mut player_inventory, 0, &trade.price());
+
+        if let Some(input_two) = &trade.input_two {
+            fill_input(&mut merchant, &mut player_inventory, 1, input_two);
+        }
+    }
+}
+
+/// Returns the items in the input slots to clients closing a merchant
+/// inventory.
+pub(crate) fn handle_merchant_close(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Inventory, &OpenInventory), With<Client>>,
+    mut merchants: Query<&mut Inventory, (With<MerchantMenu>, Without<Client>)>,
+    mut drop_item_stack_events: EventWriter<DropItemStack>,
+) {
+    for packet in packets.iter() {
+        if packet.decode::<CloseHandledScreenC2s>().is_none() {
+            continue;
+        }
+
+        let Ok((mut player_inventory, open_inventory)) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let Ok(mut merchant) = merchants.get_mut(open_inventory.entity) else {
+            continue
+        };
+
+        for stack in return_inputs(&mut merchant, &mut player_inventory) {
+            drop_item_stack_events.send(DropItemStack {
+                client: packet.client,
+                from_slot: None,
+                stack,
+            });
+        }
+    }
+}
+
+/// Makes trades when clients take the output of a merchant inventory.
+pub(crate) fn take_trade_results(
+    mut events: EventReader<ClickResultSlot>,
+    mut clients: Query<
+        (
+            &mut Inventory,
+            &mut CursorItem,
+            &ClientInventoryState,
+            &OpenInventory,
+        ),
+        With<Client>,
+    >,
+    mut merchants: Query<(&mut Inventory, &mut MerchantMenu), Without<Client>>,
+    mut trade_events: EventWriter<TradeEvent>,
+) {
+    for event in events.iter() {
+        let Ok((mut player_inventory, mut cursor_item, inv_state, open_inventory)) =
+            clients.get_mut(event.client) else {
+            continue
+        };
+
+        if event.window_id != inv_state.window_id {
+            continue;
+        }
+
+        let Ok((mut merchant, mut menu)) = merchants.get_mut(open_inventory.entity) else {
+            continue
+        };
+
+        // Makes the trade if the inputs still pay for it, returning the output.
+        let mut make_trade = |merchant: &mut Inventory, menu: &mut MerchantMenu| {
+            let (idx, swapped) = menu.trade_for(merchant.slot(0), merchant.slot(1))?;
+            let trade = &mut menu.trades[idx];
+
+            if trade.is_out_of_stock() {
+                return None;
+            }
+
+            let (slot_one, slot_two) = if swapped { (1, 0) } else { (0, 1) };
+
+            remove_items(merchant, slot_one, trade.price().count());
+
+            if let Some(input_two) = &trade.input_two {
+                remove_items(merchant, slot_two, input_two.count());
+            }
+
+            trade.uses += 1;
+
+            trade_events.send(TradeEvent {
+                client: event.client,
+                inventory: open_inventory.entity,
+                trade: idx,
+            });
+
+            Some(trade.output.clone())
+        };
+
+        let Some((idx, _)) = menu.trade_for(merchant.slot(0), merchant.slot(1)) else {
+            continue
+        };
+        let output = menu.trades[idx].output.clone();
+
+        match event.mode {
+            ClickMode::Click => match &mut cursor_item.0 {
+                None => {
+                    if let Some(output) = make_trade(&mut merchant, &mut menu) {
+                        cursor_item.0 = Some(output);
+                    }
+                }
+                Some(held)
+                    if can_stack(held, &output)
+                        && held.count() + output.count() <= held.item.max_stack() =>
+                {
+                    if make_trade(&mut merchant, &mut menu).is_some() {
+                        held.set_count(held.count() + output.count());
+                    }
+                }
+                Some(_) => {}
+            },
+            ClickMode::ShiftClick => {
+                // Trade as many times as possible, as long as the trade doesn't change.
+                while menu
+                    .trade_for(merchant.slot(0), merchant.slot(1))
+                    .is_some_and(|(i, _)| i == idx)
+                    && can_insert(&player_inventory, &output)
+                {
+                    let Some(output) = make_trade(&mut merchant, &mut menu) else {
+                        break
+                    };
+
+                    insert(&mut player_inventory, output);
+                }
+            }
+            ClickMode::Hotbar => {
+                if let Ok(button) = u16::try_from(event.button) {
+                    let slot = 36 + button;
+
+                    if slot < 45 && player_inventory.slot(slot).is_none() {
+                        if let Some(output) = make_trade(&mut merchant, &mut menu) {
+                            player_inventory.set_slot(slot, output);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Shows the output of the trade that can be made with the items in the input
+/// slots of merchant inventories.
+pub(crate) fn update_merchant_results(
+    mut merchants: Query<
+        (&mut Inventory, &MerchantMenu),
+        Or<(Changed<Inventory>, Changed<MerchantMenu>)>,
+    >,
+) {
+    for (mut merchant, menu) in &mut merchants {
+        let output = menu
+            .trade_for(merchant.slot(0), merchant.slot(1))
+            .map(|(idx, _)| &menu.trades[idx])
+            .filter(|trade| !trade.is_out_of_stock())
+            .map(|trade| trade.output.clone());
+
+        if merchant.slot(RESULT_SLOT) != output.as_ref() {
+            merchant.set_slot(RESULT_SLOT, output);
+        }
+    }
+}
@@ -326,8 +326,7 @@ pub mod synchronize_recipes {
     #[derive(Clone, Debug, Encode, Decode, Packet)]
     #[packet(id = packet_id::SYNCHRONIZE_RECIPES_S2C)]
     pub struct SynchronizeRecipesS2c<'a> {
-        // TODO: this should be a Vec<Recipe<'a>>
-        pub recipes: valence_core::protocol::raw::RawBytes<'a>,
+        pub recipes: Vec<Recipe<'a>>,
     }
 
     #[derive(Clone, PartialEq, Debug)]
@@ -347,6 +346,7 @@ pub mod synchronize_recipes {
             category: CraftingCategory,
             ingredients: Vec<Ingredient>,
             result: Option<ItemStack>,
+            show_notification: bool,
         },
         CraftingSpecial {
             kind: SpecialCraftingKind,
@@ -471,6 +471,7 @@ pub mod synchronize_recipes {
                     category,
                     ingredients,
                     result,
+                    show_notification,
                 } => {
                     "crafting_shaped".encode(&mut w)?;
                     recipe_id.encode(&mut w)?;
@@ -488,7 +489,8 @@ pub mod synchronize_recipes {
                         ing.encode(&mut w)?;
                     }
 
-                    result.encode(w)
+                    result.encode(&mut w)?;
+                    show_notification.encode(w)
                 }
                 Recipe::CraftingSpecial {
                     kind,
@@ -667,6 +669,7 @@ pub mod synchronize_recipes {
                         category,
                         ingredients,
                         result: Decode::decode(r)?,
+                        show_notification: Decode::decode(r)?,
                     }
                 }
                 "minecraft:smelting" => Self::Smelting {
@@ -1,5 +1,7 @@
 use anyhow::{bail, ensure};
-use valence_core::item::ItemStack;
+use valence_core::game_mode::GameMode;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_nbt::{Compound, Value};
 
 use super::{CursorItem, Inventory, InventoryWindow, PLAYER_INVENTORY_MAIN_SLOTS_COUNT};
 use crate::packet::{ClickMode, ClickSlotC2s};
@@ -10,6 +12,7 @@ pub(super) fn validate_click_slot_packet(
     player_inventory: &Inventory,
     open_inventory: Option<&Inventory>,
     cursor_item: &CursorItem,
+    game_mode: GameMode,
 ) -> anyhow::Result<()> {
     ensure!(
         (packet.window_id == 0) == open_inventory.is_none(),
@@ -26,7 +29,7 @@ pub(super) fn validate_click_slot_packet(
     // check all slot ids and item counts are valid
     ensure!(
         packet.slot_changes.iter().all(|s| {
-            if !(0..=max_slot).contains(&(s.idx as u16)) {
+            if !(0..max_slot).contains(&(s.idx as u16)) {
                 return false;
             }
             if let Some(slot) = s.item.as_ref() {
@@ -45,6 +48,16 @@ pub(super) fn validate_click_slot_packet(
         "invalid slot ids or item counts"
     );
 
+    // check that no slot is modified twice
+    ensure!(
+        packet
+            .slot_changes
+            .iter()
+            .enumerate()
+            .all(|(i, a)| packet.slot_changes[..i].iter().all(|b| a.idx != b.idx)),
+        "slots must not be modified more than once"
+    );
+
     // check carried item count is valid
     if let Some(carried_item) = &packet.carried_item {
         let max_stack_size = carried_item
@@ -62,43 +75,47 @@ pub(super) fn validate_click_slot_packet(
         ClickMode::Click => {
             ensure!((0..=1).contains(&packet.button), "invalid button");
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
+                (0..max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
                 "invalid slot index"
             )
         }
         ClickMode::ShiftClick => {
             ensure!((0..=1).contains(&packet.button), "invalid button");
             ensure!(
-                packet.carried_item.is_none(),
-                "carried item must be empty for a hotbar swap"
+                packet.carried_item == cursor_item.0,
+                "carried item must not change for a shift click"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)),
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
         ClickMode::Hotbar => {
             ensure!(matches!(packet.button, 0..=8 | 40), "invalid button");
             ensure!(
-                packet.carried_item.is_none(),
-                "carried item must be empty for a hotbar swap"
+                packet.carried_item == cursor_item.0,
+                "carried item must not change for a hotbar swap"
             );
+            ensure!(
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
+                "invalid slot index"
+            )
         }
         ClickMode::CreativeMiddleClick => {
             ensure!(packet.button == 2, "invalid button");
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)),
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
         ClickMode::DropKey => {
             ensure!((0..=1).contains(&packet.button), "invalid button");
             ensure!(
-                packet.carried_item.is_none(),
-                "carried item must be empty for an item drop"
+                packet.carried_item == cursor_item.0,
+                "carried item must not change for an item drop"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)),
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
@@ -108,11 +125,21 @@ pub(super) fn validate_click_slot_packet(
                 "invalid button"
             );
             ensure!(
-                (0..=max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
+                !matches!(packet.button, 8..=10) || game_mode == GameMode::Creative,
+                "middle click drags are only allowed in creative mode"
+            );
+            ensure!(
+                (0..max_slot).contains(&(packet.slot_idx as u16)) || packet.slot_idx == -999,
+                "invalid slot index"
+            )
+        }
+        ClickMode::DoubleClick => {
+            ensure!(packet.button == 0, "invalid button");
+            ensure!(
+                (0..max_slot).contains(&(packet.slot_idx as u16)),
                 "invalid slot index"
             )
         }
-        ClickMode::DoubleClick => ensure!(packet.button == 0, "invalid button"),
     }
 
     // Check that items aren't being duplicated, i.e. conservation of mass.
@@ -131,10 +158,19 @@ pub(super) fn validate_click_slot_packet(
                     "slot modifications must be empty"
                 );
 
+                ensure!(
+                    match (&cursor_item.0, &packet.carried_item) {
+                        (Some(old), Some(new)) => same_item(old, new),
+                        (_, None) => true,
+                        (None, Some(_)) => false,
+                    },
+                    "transmuting items is not allowed"
+                );
+
                 // Clicked outside the window
                 let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
                 let expected_delta = match packet.button {
-                    1 => -1,
+                    1 => -cursor_item.0.as_ref().map_or(0, |_| 1),
                     0 => -cursor_item
                         .0
                         .as_ref()
@@ -154,14 +190,15 @@ pub(super) fn validate_click_slot_packet(
                     "click must modify one slot, got {}",
                     packet.slot_changes.len()
                 );
+                ensure!(
+                    packet.slot_changes[0].idx == packet.slot_idx,
+                    "slot index does not match modified slot"
+                );
 
                 let old_slot = window.slot(packet.slot_changes[0].idx as u16);
-                // TODO: make sure NBT is the same.
-                //       Sometimes, the client will add nbt data to an item if it's missing,
-                // like       "Damage" to a sword.
                 let should_swap = packet.button == 0
                     && match (old_slot, cursor_item.0.as_ref()) {
-                        (Some(old_slot), Some(cursor_item)) => old_slot.item != cursor_item.item,
+                        (Some(old_slot), Some(cursor_item)) => !same_item(old_slot, cursor_item),
                         (Some(_), None) => true,
                         (None, Some(cursor_item)) => {
                             cursor_item.count() <= cursor_item.item.max_stack()
@@ -172,18 +209,16 @@ pub(super) fn validate_click_slot_packet(
                 if should_swap {
                     // assert that a swap occurs
                     ensure!(
-                        old_slot == packet.carried_item.as_ref()
-                            && cursor_item.0 == packet.slot_changes[0].item,
+                        same_stack(old_slot, packet.carried_item.as_ref())
+                            && same_stack(
+                                cursor_item.0.as_ref(),
+                                packet.slot_changes[0].item.as_ref()
+                            ),
                         "swapped items must match"
                     );
                 } else {
                     // assert that a merge occurs
-                    let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
-                    ensure!(
-                        count_deltas == 0,
-                        "invalid item delta for stack merge: {}",
-                        count_deltas
-                    );
+                    ensure_items_conserved(packet, &window, cursor_item)?;
                 }
             }
         }
@@ -194,29 +229,14 @@ pub(super) fn validate_click_slot_packet(
                 packet.slot_changes.len()
             );
 
-            let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
             ensure!(
-                count_deltas == 0,
-                "invalid item delta: expected 0, got {}",
-                count_deltas
+                packet.slot_changes.iter().any(|s| s.idx == packet.slot_idx),
+                "shift click must modify the clicked slot"
             );
 
-            let Some(item_kind) = packet
-                .slot_changes
-                .iter()
-                .filter_map(|s| s.item.as_ref())
-                .next()
-                .map(|s| s.item) else {
-                    bail!("shift click must move an item");
-                };
-
-            let Some(old_slot_kind) = window.slot(packet.slot_idx as u16).map(|s| s.item) else {
+            let Some(old_slot) = window.slot(packet.slot_idx as u16) else {
                 bail!("shift click must move an item");
             };
-            ensure!(
-                old_slot_kind == item_kind,
-                "shift click must move the same item kind as modified slots"
-            );
 
             // assert all moved items are the same kind
             ensure!(
@@ -224,41 +244,85 @@ pub(super) fn validate_click_slot_packet(
                     .slot_changes
                     .iter()
                     .filter_map(|s| s.item.as_ref())
-                    .all(|s| s.item == item_kind),
+                    .all(|s| same_item(s, old_slot)),
                 "shift click must move the same item kind"
             );
+
+            ensure_items_conserved(packet, &window, cursor_item)?;
         }
 
         ClickMode::Hotbar => {
-            ensure!(
-                packet.slot_changes.len() == 2,
-                "hotbar swap must modify two slots, got {}",
-                packet.slot_changes.len()
-            );
+            if open_inventory.is_some() && packet.button == 40 {
+                // The offhand slot is not part of the window, so only the clicked slot is
+                // modified. Its item is swapped with the item in the offhand.
+                ensure!(
+                    packet.slot_changes.len() == 1 && packet.slot_changes[0].idx == packet.slot_idx,
+                    "offhand swap must modify the clicked slot"
+                );
+                ensure!(
+                    same_stack(
+                        packet.slot_changes[0].item.as_ref(),
+                        player_inventory.slot(45)
+                    ),
+                    "swapped items must match"
+                );
+            } else {
+                ensure!(
+                    packet.slot_changes.len() == 2,
+                    "hotbar swap must modify two slots, got {}",
+                    packet.slot_changes.len()
+                );
 
-            let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
-            ensure!(
-                count_deltas == 0,
-                "invalid item delta: expected 0, got {}",
-                count_deltas
-            );
+                let hotbar_slot = match (open_inventory, packet.button) {
+                    (None, 40) => 45,
+                    (None, button) => 36 + button as i16,
+                    (Some(inv), button) => (inv.slot_count() + 27) as i16 + button as i16,
+                };
+
+                let (clicked, hotbar) =
+                    match (packet.slot_changes[0].idx, packet.slot_changes[1].idx) {
+                        (a, b) if a == packet.slot_idx && b == hotbar_slot => (0, 1),
+                        (a, b) if a == hotbar_slot && b == packet.slot_idx => (1, 0),
+                        _ => bail!("hotbar swap must modify the clicked slot and the hotbar slot"),
+                    };
 
-            // assert that a swap occurs
-            let old_slots = [
-                window.slot(packet.slot_changes[0].idx as u16),
-                window.slot(packet.slot_changes[1].idx as u16),
-            ];
+                // assert that a swap occurs
+                ensure!(
+                    same_stack(
+                        packet.slot_changes[clicked].item.as_ref(),
+                        window.slot(hotbar_slot as u16)
+                    ) && same_stack(
+                        packet.slot_changes[hotbar].item.as_ref(),
+                        window.slot(packet.slot_idx as u16)
+                    ),
+                    "swapped items must match"
+                );
+            }
+        }
+        ClickMode::CreativeMiddleClick => {
             ensure!(
-                old_slots
-                    .iter()
-                    .any(|s| s == &packet.slot_changes[0].item.as_ref())
-                    && old_slots
-                        .iter()
-                        .any(|s| s == &packet.slot_changes[1].item.as_ref()),
-                "swapped items must match"
+                packet.slot_changes.is_empty(),
+                "middle click must not modify slots"
             );
+
+            if packet.carried_item != cursor_item.0 {
+                // The client is picking a full stack of the item in the slot.
+                ensure!(
+                    game_mode == GameMode::Creative,
+                    "middle click is only allowed in creative mode"
+                );
+
+                let picked = window.slot(packet.slot_idx as u16);
+                ensure!(
+                    cursor_item.0.is_none()
+                        && match (picked, &packet.carried_item) {
+                            (Some(picked), Some(carried)) => same_item(picked, carried),
+                            _ => false,
+                        },
+                    "middle click must pick the item in the slot"
+                );
+            }
         }
-        ClickMode::CreativeMiddleClick => {}
         ClickMode::DropKey => {
             ensure!(
                 packet.slot_changes.len() == 1,
@@ -272,10 +336,7 @@ pub(super) fn validate_click_slot_packet(
             let old_slot = window.slot(packet.slot_idx as u16);
             let new_slot = packet.slot_changes[0].item.as_ref();
             let is_transmuting = match (old_slot, new_slot) {
-                // TODO: make sure NBT is the same
-                // Sometimes, the client will add nbt data to an item if it's missing, like "Damage"
-                // to a sword
-                (Some(old_slot), Some(new_slot)) => old_slot.item != new_slot.item,
+                (Some(old_slot), Some(new_slot)) => !same_item(old_slot, new_slot),
                 (_, None) => false,
                 (None, Some(_)) => true,
             };
@@ -284,7 +345,7 @@ pub(super) fn validate_click_slot_packet(
             let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
 
             let expected_delta = match packet.button {
-                0 => -1,
+                0 => -old_slot.map_or(0, |_| 1),
                 1 => -old_slot.map(|s| s.count() as i32).unwrap_or(0),
                 _ => unreachable!(),
             };
@@ -296,25 +357,111 @@ pub(super) fn validate_click_slot_packet(
             );
         }
         ClickMode::Drag => {
-            if matches!(packet.button, 2 | 6 | 10) {
-                let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
+            if matches!(packet.button, 2 | 6) {
+                ensure_items_conserved(packet, &window, cursor_item)?;
+            } else if packet.button == 10 {
+                // Middle click drags copy the cursor item to the slots.
                 ensure!(
-                    count_deltas == 0,
-                    "invalid item delta: expected 0, got {}",
-                    count_deltas
+                    packet.carried_item == cursor_item.0,
+                    "carried item must not change for a middle click drag"
+                );
+
+                let Some(cursor_item) = &cursor_item.0 else {
+                    bail!("middle click drag must copy an item");
+                };
+
+                ensure!(
+                    packet
+                        .slot_changes
+                        .iter()
+                        .all(|s| s.item.as_ref().is_some_and(|s| same_item(s, cursor_item))),
+                    "middle click drag must copy the cursor item"
                 );
             } else {
-                ensure!(packet.slot_changes.is_empty() && packet.carried_item == cursor_item.0);
+                ensure!(
+                    packet.slot_changes.is_empty() && packet.carried_item == cursor_item.0,
+                    "drag must not modify slots before it ends"
+                );
             }
         }
         ClickMode::DoubleClick => {
-            let count_deltas = calculate_net_item_delta(packet, &window, cursor_item);
             ensure!(
-                count_deltas == 0,
-                "invalid item delta: expected 0, got {}",
-                count_deltas
+                match (&cursor_item.0, &packet.carried_item) {
+                    (Some(old), Some(new)) => same_item(old, new),
+                    (None, None) => true,
+                    _ => false,
+                },
+                "double click must collect the carried item"
             );
+
+            ensure_items_conserved(packet, &window, cursor_item)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the NBT of an item stack without data the client adds by itself,
+/// like the "Damage" tag of items that can be damaged.
+fn normalized_nbt(stack: &ItemStack) -> Option<Compound> {
+    let mut nbt = stack.nbt.clone()?;
+
+    if matches!(nbt.get("Damage"), Some(Value::Int(0))) {
+        nbt.remove("Damage");
+    }
+
+    (!nbt.is_empty()).then_some(nbt)
+}
+
+/// Returns if two stacks are of the same item and can be stacked together.
+fn same_item(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && normalized_nbt(a) == normalized_nbt(b)
+}
+
+/// Returns if two slots hold the same items.
+fn same_stack(a: Option<&ItemStack>, b: Option<&ItemStack>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => same_item(a, b) && a.count() == b.count(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Checks that the changes in this packet only move items around the window
+/// and cursor. Unlike [`calculate_net_item_delta`], this also checks that no
+/// item is turned into another.
+fn ensure_items_conserved(
+    packet: &ClickSlotC2s,
+    window: &InventoryWindow,
+    cursor_item: &CursorItem,
+) -> anyhow::Result<()> {
+    let mut deltas: Vec<(ItemKind, Option<Compound>, i32)> = vec![];
+
+    let mut add = |stack: Option<&ItemStack>, sign: i32| {
+        let Some(stack) = stack else { return };
+
+        let nbt = normalized_nbt(stack);
+        let count = stack.count() as i32 * sign;
+
+        match deltas
+            .iter_mut()
+            .find(|(item, n, _)| *item == stack.item && *n == nbt)
+        {
+            Some((_, _, delta)) => *delta += count,
+            None => deltas.push((stack.item, nbt, count)),
         }
+    };
+
+    for slot in &packet.slot_changes {
+        add(window.slot(slot.idx as u16), -1);
+        add(slot.item.as_ref(), 1);
+    }
+
+    add(cursor_item.0.as_ref(), -1);
+    add(packet.carried_item.as_ref(), 1);
+
+    if let Some((item, _, delta)) = deltas.iter().find(|(_, _, delta)| *delta != 0) {
+        bail!("invalid item delta for {item:?}: expected 0, got {delta}");
     }
 
     Ok(())
@@ -358,6 +505,7 @@ fn calculate_net_item_delta(
 mod tests {
     use valence_core::item::{ItemKind, ItemStack};
     use valence_core::protocol::var_int::VarInt;
+    use valence_nbt::compound;
 
     use super::*;
     use crate::packet::SlotChange;
@@ -455,8 +603,14 @@ mod tests {
             carried_item: inventory.slot(0).cloned(),
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            Some(&inventory),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -491,11 +645,23 @@ mod tests {
             carried_item: None,
         };
 
-        validate_click_slot_packet(&packet1, &player_inventory, Some(&inventory1), &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet1,
+            &player_inventory,
+            Some(&inventory1),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
 
-        validate_click_slot_packet(&packet2, &player_inventory, Some(&inventory2), &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet2,
+            &player_inventory,
+            Some(&inventory2),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -517,8 +683,14 @@ mod tests {
             carried_item: Some(ItemStack::new(ItemKind::Diamond, 20, None)),
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            Some(&inventory),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -540,8 +712,14 @@ mod tests {
             carried_item: Some(ItemStack::new(ItemKind::IronIngot, 2, None)),
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, Some(&inventory), &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            Some(&inventory),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -594,14 +772,32 @@ mod tests {
             carried_item: None,
         };
 
-        validate_click_slot_packet(&packet1, &player_inventory, Some(&inventory1), &cursor_item)
-            .expect_err("packet 1 should fail item duplication check");
+        validate_click_slot_packet(
+            &packet1,
+            &player_inventory,
+            Some(&inventory1),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet 1 should fail item duplication check");
 
-        validate_click_slot_packet(&packet2, &player_inventory, Some(&inventory2), &cursor_item)
-            .expect_err("packet 2 should fail item duplication check");
+        validate_click_slot_packet(
+            &packet2,
+            &player_inventory,
+            Some(&inventory2),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet 2 should fail item duplication check");
 
-        validate_click_slot_packet(&packet3, &player_inventory, Some(&inventory1), &cursor_item)
-            .expect_err("packet 3 should fail item duplication check");
+        validate_click_slot_packet(
+            &packet3,
+            &player_inventory,
+            Some(&inventory1),
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet 3 should fail item duplication check");
     }
 
     #[test]
@@ -667,9 +863,16 @@ mod tests {
         ];
 
         for (i, packet) in packets.iter().enumerate() {
-            validate_click_slot_packet(packet, &player_inventory, None, &cursor_item).expect_err(
-                &format!("packet {i} passed item duplication check when it should have failed"),
-            );
+            validate_click_slot_packet(
+                packet,
+                &player_inventory,
+                None,
+                &cursor_item,
+                GameMode::Survival,
+            )
+            .expect_err(&format!(
+                "packet {i} passed item duplication check when it should have failed"
+            ));
         }
     }
 
@@ -700,8 +903,14 @@ mod tests {
             carried_item: None,
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -720,8 +929,14 @@ mod tests {
             carried_item: Some(ItemStack::new(ItemKind::Apple, 100, None)),
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
     }
 
     #[test]
@@ -742,7 +957,202 @@ mod tests {
             carried_item: Some(ItemStack::new(ItemKind::Apple, 36, None)),
         };
 
-        validate_click_slot_packet(&packet, &player_inventory, None, &cursor_item)
-            .expect("packet should be valid");
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
+    }
+
+    #[test]
+    fn disallow_transmutation_on_merge() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Dirt, 10, None));
+        player_inventory.set_slot(10, ItemStack::new(ItemKind::Dirt, 10, None));
+        let cursor_item = CursorItem::default();
+
+        // The counts add up, but dirt is turned into diamonds.
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: 9,
+            button: 0,
+            mode: ClickMode::DoubleClick,
+            slot_changes: vec![
+                SlotChange { idx: 9, item: None },
+                SlotChange {
+                    idx: 10,
+                    item: None,
+                },
+            ],
+            carried_item: Some(ItemStack::new(ItemKind::Diamond, 20, None)),
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet should fail item transmutation check");
+    }
+
+    #[test]
+    fn allow_client_added_damage() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::DiamondSword, 1, None));
+        let cursor_item = CursorItem::default();
+
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: 9,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange { idx: 9, item: None }],
+            carried_item: Some(ItemStack::new(
+                ItemKind::DiamondSword,
+                1,
+                Some(compound! { "Damage" => 0 }),
+            )),
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect("packet should be valid");
+    }
+
+    #[test]
+    fn disallow_out_of_range_slot() {
+        let player_inventory = Inventory::new(InventoryKind::Player);
+        let cursor_item = CursorItem(Some(ItemStack::new(ItemKind::Diamond, 1, None)));
+
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: 46,
+            button: 0,
+            mode: ClickMode::Click,
+            slot_changes: vec![SlotChange {
+                idx: 46,
+                item: Some(ItemStack::new(ItemKind::Diamond, 1, None)),
+            }],
+            carried_item: None,
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet should fail slot range check");
+    }
+
+    #[test]
+    fn middle_click_requires_creative() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 1, None));
+        let cursor_item = CursorItem::default();
+
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: 9,
+            button: 2,
+            mode: ClickMode::CreativeMiddleClick,
+            slot_changes: vec![],
+            carried_item: Some(ItemStack::new(ItemKind::Diamond, 64, None)),
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet should fail in survival mode");
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Creative,
+        )
+        .expect("packet should be valid in creative mode");
+    }
+
+    #[test]
+    fn hotbar_swap_must_swap() {
+        let mut player_inventory = Inventory::new(InventoryKind::Player);
+        player_inventory.set_slot(9, ItemStack::new(ItemKind::Diamond, 10, None));
+        player_inventory.set_slot(36, ItemStack::new(ItemKind::Diamond, 10, None));
+        let cursor_item = CursorItem::default();
+
+        // Both slots are filled afterwards, so the counts don't add up.
+        let packet = ClickSlotC2s {
+            window_id: 0,
+            state_id: VarInt(0),
+            slot_idx: 9,
+            button: 0,
+            mode: ClickMode::Hotbar,
+            slot_changes: vec![
+                SlotChange {
+                    idx: 9,
+                    item: Some(ItemStack::new(ItemKind::Diamond, 20, None)),
+                },
+                SlotChange {
+                    idx: 36,
+                    item: Some(ItemStack::new(ItemKind::Diamond, 20, None)),
+                },
+            ],
+            carried_item: None,
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet should fail hotbar swap check");
+
+        // Swapping with a slot that isn't the hotbar slot of the button.
+        let packet = ClickSlotC2s {
+            button: 1,
+            slot_changes: vec![
+                SlotChange {
+                    idx: 9,
+                    item: Some(ItemStack::new(ItemKind::Diamond, 10, None)),
+                },
+                SlotChange {
+                    idx: 36,
+                    item: Some(ItemStack::new(ItemKind::Diamond, 10, None)),
+                },
+            ],
+            ..packet
+        };
+
+        validate_click_slot_packet(
+            &packet,
+            &player_inventory,
+            None,
+            &cursor_item,
+            GameMode::Survival,
+        )
+        .expect_err("packet should fail hotbar slot check");
     }
 }
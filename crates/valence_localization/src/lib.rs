@@ -0,0 +1,305 @@
+//! # Localization
+//! Server-side translations, so every client receives messages in its own
+//! language.
+//!
+//! Translations are registered per locale in the [`Translations`] resource.
+//! Their templates use the format of Minecraft's language files, where `%s`
+//! is replaced by the next argument, `%1$s` by the first argument, and `%%`
+//! is a literal `%`.
+//!
+//! Messages are sent as [`LocalizedText`] through the [`LocalizedMessages`]
+//! component of a client. Each message is translated into the locale in the
+//! client's [`ClientSettings`] when it is sent, falling back to the
+//! [fallback locale] when the client's locale has no translation. Keys
+//! without any translation are sent as translated text for the client to
+//! translate, so vanilla translation keys can be used directly.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_localization::{LocalizedMessages, LocalizedText, Translations};
+//! fn setup(mut translations: ResMut<Translations>) {
+//!     translations.insert("en_us", "welcome", "Welcome, %s!");
+//!     translations.insert("de_de", "welcome", "Willkommen, %s!");
+//! }
+//!
+//! fn greet(mut clients: Query<&mut LocalizedMessages>) {
+//!     for mut messages in &mut clients {
+//!         messages.send_chat_message(LocalizedText::new("welcome", ["Steve".into()]));
+//!     }
+//! }
+//! ```
+//!
+//! [fallback locale]: Translations::fallback_locale
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_client::message::SendMessage;
+use valence_client::settings::ClientSettings;
+use valence_client::{Client, FlushPacketsSet};
+use valence_core::text::Text;
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Translations>()
+            .add_system(init_localized_messages.in_base_set(CoreSet::PreUpdate))
+            .add_system(
+                send_localized_messages
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+/// The translation templates of every locale.
+#[derive(Resource, Clone, Debug)]
+pub struct Translations {
+    locales: BTreeMap<String, BTreeMap<String, String>>,
+    fallback_locale: String,
+}
+
+impl Default for Translations {
+    fn default() -> Self {
+        Self {
+            locales: BTreeMap::new(),
+            fallback_locale: "en_us".into(),
+        }
+    }
+}
+
+impl Translations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The locale used when the locale of a client has no translation for a
+    /// key. `en_us` by default.
+    pub fn fallback_locale(&self) -> &str {
+        &self.fallback_locale
+    }
+
+    pub fn set_fallback_locale(&mut self, locale: impl Into<String>) {
+        self.fallback_locale = locale.into().to_ascii_lowercase();
+    }
+
+    /// Adds the template of a key in a locale, returning the previous
+    /// template. Locales are case-insensitive, like `en_us` or `de_de`.
+    pub fn insert(
+        &mut self,
+        locale: impl Into<String>,
+        key: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Option<String> {
+        self.locales
+            .entry(locale.into().to_ascii_lowercase())
+            .or_default()
+            .insert(key.into(), template.into())
+    }
+
+    /// Adds a bundle of keys and templates to a locale.
+    pub fn insert_bundle<K, V>(
+        &mut self,
+        locale: impl Into<String>,
+        bundle: impl IntoIterator<Item = (K, V)>,
+    ) where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.locales
+            .entry(locale.into().to_ascii_lowercase())
+            .or_default()
+            .extend(bundle.into_iter().map(|(k, v)| (k.into(), v.into())));
+    }
+
+    /// Adds the translations from a JSON object of keys and templates, which
+    /// is the format of Minecraft's language files.
+    pub fn insert_json(&mut self, locale: impl Into<String>, json: &str) -> serde_json::Result<()> {
+        let bundle: BTreeMap<String, String> = serde_json::from_str(json)?;
+        self.insert_bundle(locale, bundle);
+        Ok(())
+    }
+
+    /// Removes all translations of a locale.
+    pub fn remove_locale(&mut self, locale: &str) {
+        self.locales.remove(&locale.to_ascii_lowercase());
+    }
+
+    /// Iterates over the locales with translations.
+    pub fn locales(&self) -> impl Iterator<Item = &str> + '_ {
+        self.locales.keys().map(|l| l.as_str())
+    }
+
+    /// Gets the template of a key in a locale, or in the fallback locale if
+    /// the locale has no translation for the key.
+    pub fn get(&self, locale: &str, key: &str) -> Option<&str> {
+        let get = |locale: &str| {
+            self.locales
+                .get(locale)
+                .and_then(|keys| keys.get(key))
+                .map(|t| t.as_str())
+        };
+
+        get(&locale.to_ascii_lowercase()).or_else(|| get(&self.fallback_locale))
+    }
+
+    /// Translates text into a locale. Keys without any translation become
+    /// translated text, which the client translates itself.
+    pub fn localize(&self, text: &LocalizedText, locale: &str) -> Text {
+        let Some(template) = self.get(locale, &text.key) else {
+            return Text::translate(text.key.clone(), text.args.clone())
+        };
+
+        format_template(template, &text.args)
+    }
+}
+
+/// Replaces the `%s`, `%1$s`, and `%%` in a template. Specifiers pointing past
+/// the arguments are kept as they are.
+fn format_template(template: &str, args: &[Text]) -> Text {
+    let mut res = Text::default();
+    let mut buf = String::new();
+    let mut next_arg = 0;
+    let mut rest = template;
+
+    while let Some(i) = rest.find('%') {
+        buf.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        if let Some(r) = rest.strip_prefix('%') {
+            buf.push('%');
+            rest = r;
+            continue;
+        }
+
+        let spec = match rest.strip_prefix('s') {
+            Some(r) => {
+                next_arg += 1;
+                Some((next_arg - 1, r))
+            }
+            None => indexed_spec(rest),
+        };
+
+        match spec.and_then(|(index, r)| Some((args.get(index)?, r))) {
+            Some((arg, r)) => {
+                if !buf.is_empty() {
+                    res += std::mem::take(&mut buf);
+                }
+
+                res += arg.clone();
+                rest = r;
+            }
+            None => buf.push('%'),
+        }
+    }
+
+    buf.push_str(rest);
+
+    if !buf.is_empty() {
+        res += buf;
+    }
+
+    res
+}
+
+/// Reads the `1$s` of an indexed specifier, returning the index of the
+/// argument and the rest of the template.
+fn indexed_spec(s: &str) -> Option<(usize, &str)> {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let n: usize = s[..digits].parse().ok()?;
+    let rest = s[digits..].strip_prefix("$s")?;
+
+    Some((n.checked_sub(1)?, rest))
+}
+
+/// Text that is translated on the server for each client that receives it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LocalizedText {
+    /// The key of the translation in [`Translations`].
+    pub key: Cow<'static, str>,
+    /// The arguments inserted into the translation template.
+    pub args: Vec<Text>,
+}
+
+impl LocalizedText {
+    pub fn new(key: impl Into<Cow<'static, str>>, args: impl Into<Vec<Text>>) -> Self {
+        Self {
+            key: key.into(),
+            args: args.into(),
+        }
+    }
+}
+
+/// The localized messages waiting to be sent to a client. Automatically
+/// inserted into clients.
+#[derive(Component, Clone, Default, Debug)]
+pub struct LocalizedMessages {
+    /// The messages and whether they are shown in the action bar.
+    pending: Vec<(LocalizedText, bool)>,
+}
+
+impl LocalizedMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends a system message visible in the chat.
+    pub fn send_chat_message(&mut self, msg: LocalizedText) {
+        self.pending.push((msg, false));
+    }
+
+    /// Displays a message in the player's action bar (text above the hotbar).
+    pub fn send_action_bar_message(&mut self, msg: LocalizedText) {
+        self.pending.push((msg, true));
+    }
+}
+
+fn init_localized_messages(
+    clients: Query<Entity, (Added<Client>, Without<LocalizedMessages>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(LocalizedMessages::default());
+    }
+}
+
+fn send_localized_messages(
+    mut clients: Query<
+        (&mut Client, &ClientSettings, &mut LocalizedMessages),
+        Changed<LocalizedMessages>,
+    >,
+    translations: Res<Translations>,
+) {
+    for (mut client, settings, mut messages) in &mut clients {
+        for (msg, overlay) in messages.bypass_change_detection().pending.drain(..) {
+            let txt = translations.localize(&msg, &settings.locale);
+
+            if overlay {
+                client.send_action_bar_message(txt);
+            } else {
+                client.send_chat_message(txt);
+            }
+        }
+    }
+}
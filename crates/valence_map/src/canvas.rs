@@ -0,0 +1,282 @@
+use bevy_ecs::prelude::*;
+use num_integer::div_ceil;
+
+use crate::color::{self, TRANSPARENT};
+use crate::{MapBundle, MapId, MapManager};
+
+/// The width and height of a map in pixels.
+pub const MAP_SIZE: usize = 128;
+
+/// The pixels of a map. Every pixel is a color ID from the [`color`] module.
+///
+/// The canvas keeps track of the pixels that changed since the last update,
+/// so that only the changed region is sent to clients.
+#[derive(Component, Clone, PartialEq, Eq, Debug)]
+pub struct MapCanvas {
+    colors: Box<[u8]>,
+    /// The changed region as the inclusive bounds `[min_x, min_y, max_x,
+    /// max_y]`.
+    dirty: Option<[usize; 4]>,
+}
+
+impl Default for MapCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapCanvas {
+    /// Creates a transparent canvas.
+    pub fn new() -> Self {
+        Self {
+            colors: vec![TRANSPARENT; MAP_SIZE * MAP_SIZE].into(),
+            dirty: None,
+        }
+    }
+
+    /// Returns the color IDs of all pixels, row by row.
+    pub fn colors(&self) -> &[u8] {
+        &self.colors
+    }
+
+    /// Returns the color ID of a pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside the canvas.
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        assert!(x < MAP_SIZE && y < MAP_SIZE, "pixel out of bounds");
+        self.colors[y * MAP_SIZE + x]
+    }
+
+    /// Sets the color ID of a pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside the canvas.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u8) {
+        assert!(x < MAP_SIZE && y < MAP_SIZE, "pixel out of bounds");
+
+        let old = std::mem::replace(&mut self.colors[y * MAP_SIZE + x], color);
+
+        if old != color {
+            self.mark_dirty(x, y, x, y);
+        }
+    }
+
+    /// Sets every pixel to a color ID.
+    pub fn fill(&mut self, color: u8) {
+        self.fill_rect(0, 0, MAP_SIZE, MAP_SIZE, color);
+    }
+
+    /// Sets the pixels of a rectangle to a color ID. The parts of the
+    /// rectangle outside the canvas are ignored.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u8) {
+        for y in y..(y + height).min(MAP_SIZE) {
+            for x in x..(x + width).min(MAP_SIZE) {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+
+    /// Draws an image of color IDs with its top left corner at `x` and `y`.
+    /// The image is given as rows of `width` pixels. Transparent pixels of the
+    /// image are skipped, and the parts of the image outside the canvas are
+    /// ignored.
+    pub fn draw(&mut self, x: usize, y: usize, width: usize, colors: &[u8]) {
+        if width == 0 {
+            return;
+        }
+
+        for (row, line) in colors.chunks(width).enumerate() {
+            for (column, &color) in line.iter().enumerate() {
+                if color != TRANSPARENT && x + column < MAP_SIZE && y + row < MAP_SIZE {
+                    self.set_pixel(x + column, y + row, color);
+                }
+            }
+        }
+    }
+
+    /// Like [`MapCanvas::draw`], but with an RGBA image that is converted to
+    /// the colors of maps with [`color::from_rgba`].
+    pub fn draw_rgba(&mut self, x: usize, y: usize, width: usize, rgba: &[u8], dither: bool) {
+        self.draw(x, y, width, &color::from_rgba(width, rgba, dither));
+    }
+
+    /// Returns whether any pixels changed since the last update.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    fn mark_dirty(&mut self, min_x: usize, min_y: usize, max_x: usize, max_y: usize) {
+        self.dirty = Some(match self.dirty {
+            Some([x0, y0, x1, y1]) => [x0.min(min_x), y0.min(min_y), x1.max(max_x), y1.max(max_y)],
+            None => [min_x, min_y, max_x, max_y],
+        });
+    }
+
+    /// Returns the changed region as `[x, y]`, `[width, height]` and the
+    /// colors of the region, and clears it.
+    pub(crate) fn take_dirty(&mut self) -> Option<([usize; 2], [usize; 2], Vec<u8>)> {
+        let [min_x, min_y, max_x, max_y] = self.dirty.take()?;
+
+        let colors = (min_y..=max_y)
+            .flat_map(|y| &self.colors[y * MAP_SIZE + min_x..=y * MAP_SIZE + max_x])
+            .copied()
+            .collect();
+
+        Some((
+            [min_x, min_y],
+            [max_x - min_x + 1, max_y - min_y + 1],
+            colors,
+        ))
+    }
+}
+
+/// An image split over a grid of maps, for walls of item frames.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use valence_map::{MapManager, MapWall};
+/// fn spawn_poster(
+///     mut commands: Commands,
+///     mut maps: ResMut<MapManager>,
+///     instance: Entity,
+///     image: &[u8],
+/// ) {
+///     // A 256x128 image needs two maps next to each other.
+///     let wall = MapWall::from_rgba(256, image, true);
+///     let ids = wall.spawn(&mut commands, &mut maps, instance);
+///
+///     // Put `ids[0].item()` in the left item frame and `ids[1].item()` in the
+///     // right one...
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapWall {
+    columns: usize,
+    rows: usize,
+    canvases: Vec<MapCanvas>,
+}
+
+impl MapWall {
+    /// Splits an RGBA image over as many maps as needed to fit it. The image
+    /// is converted to the colors of maps as a whole, so dithering is seamless
+    /// across maps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `rgba` is not a multiple of `width * 4`.
+    pub fn from_rgba(width: usize, rgba: &[u8], dither: bool) -> Self {
+        let colors = color::from_rgba(width, rgba, dither);
+        let height = colors.len() / width;
+
+        let columns = div_ceil(width, MAP_SIZE);
+        let rows = div_ceil(height, MAP_SIZE);
+
+        let mut canvases = vec![MapCanvas::new(); columns * rows];
+
+        for (y, line) in colors.chunks(width).enumerate() {
+            for (column, part) in line.chunks(MAP_SIZE).enumerate() {
+                canvases[y / MAP_SIZE * columns + column].draw(0, y % MAP_SIZE, part.len(), part);
+            }
+        }
+
+        Self {
+            columns,
+            rows,
+            canvases,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the canvas of a map in the wall. Column `0` and row `0` is the
+    /// top left map.
+    pub fn canvas(&self, column: usize, row: usize) -> Option<&MapCanvas> {
+        if column < self.columns {
+            self.canvases.get(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the canvases of the wall row by row, starting at the top left.
+    pub fn into_canvases(self) -> Vec<MapCanvas> {
+        self.canvases
+    }
+
+    /// Spawns the maps of the wall in an instance. Returns the IDs of the maps
+    /// row by row, starting at the top left.
+    pub fn spawn(
+        self,
+        commands: &mut Commands,
+        maps: &mut MapManager,
+        instance: Entity,
+    ) -> Vec<MapId> {
+        self.canvases
+            .into_iter()
+            .map(|canvas| {
+                let id = maps.next_id();
+
+                commands.spawn(MapBundle {
+                    canvas,
+                    ..MapBundle::new(id, instance)
+                });
+
+                id
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_region() {
+        let mut canvas = MapCanvas::new();
+        assert_eq!(canvas.take_dirty(), None);
+
+        canvas.set_pixel(5, 10, 34);
+        canvas.set_pixel(2, 12, 34);
+        assert_eq!(
+            canvas.take_dirty(),
+            Some(([2, 10], [4, 3], vec![0, 0, 0, 34, 0, 0, 0, 0, 34, 0, 0, 0]))
+        );
+        assert!(!canvas.is_dirty());
+
+        // Setting a pixel to the same color doesn't change anything.
+        canvas.set_pixel(5, 10, 34);
+        assert!(!canvas.is_dirty());
+
+        canvas.fill_rect(120, 120, 20, 20, 34);
+        assert_eq!(
+            canvas.take_dirty(),
+            Some(([120, 120], [8, 8], vec![34; 64]))
+        );
+    }
+
+    #[test]
+    fn split_wall() {
+        let rgba = [[255, 255, 255, 255]; 200 * 130].concat();
+        let wall = MapWall::from_rgba(200, &rgba, false);
+
+        assert_eq!((wall.columns(), wall.rows()), (2, 2));
+
+        let white = color::nearest([255, 255, 255]);
+        let bottom_right = wall.canvas(1, 1).unwrap();
+
+        assert_eq!(bottom_right.pixel(71, 1), white);
+        assert_eq!(bottom_right.pixel(72, 1), TRANSPARENT);
+        assert_eq!(bottom_right.pixel(71, 2), TRANSPARENT);
+        assert_eq!(wall.canvas(2, 0), None);
+    }
+}
@@ -0,0 +1,217 @@
+//! The colors of maps.
+//!
+//! Maps don't store RGB colors. Every pixel is a color ID made from one of the
+//! base colors below, times four, plus a shade from `0` to `3`. Color IDs `0`
+//! to `3` are transparent.
+
+/// The transparent color ID.
+pub const TRANSPARENT: u8 = 0;
+
+/// The number of color IDs understood by the client.
+pub const COLOR_COUNT: usize = BASE_COLORS.len() * 4;
+
+/// The base colors of maps in the order of their IDs, from `MapColor`.
+const BASE_COLORS: [u32; 62] = [
+    0x000000, // None
+    0x7fb238, // Grass
+    0xf7e9a3, // Sand
+    0xc7c7c7, // Wool
+    0xff0000, // Fire
+    0xa0a0ff, // Ice
+    0xa7a7a7, // Metal
+    0x007c00, // Plant
+    0xffffff, // Snow
+    0xa4a8b8, // Clay
+    0x976d4d, // Dirt
+    0x707070, // Stone
+    0x4040ff, // Water
+    0x8f7748, // Wood
+    0xfffcf5, // Quartz
+    0xd87f33, // Orange
+    0xb24cd8, // Magenta
+    0x6699d8, // Light blue
+    0xe5e533, // Yellow
+    0x7fcc19, // Light green
+    0xf27fa5, // Pink
+    0x4c4c4c, // Gray
+    0x999999, // Light gray
+    0x4c7f99, // Cyan
+    0x7f3fb2, // Purple
+    0x334cb2, // Blue
+    0x664c33, // Brown
+    0x667f33, // Green
+    0x993333, // Red
+    0x191919, // Black
+    0xfaee4d, // Gold
+    0x5cdbd5, // Diamond
+    0x4a80ff, // Lapis
+    0x00d93a, // Emerald
+    0x815631, // Podzol
+    0x700200, // Nether
+    0xd1b1a1, // White terracotta
+    0x9f5224, // Orange terracotta
+    0x95576c, // Magenta terracotta
+    0x706c8a, // Light blue terracotta
+    0xba8524, // Yellow terracotta
+    0x677535, // Light green terracotta
+    0xa04d4e, // Pink terracotta
+    0x392923, // Gray terracotta
+    0x876b62, // Light gray terracotta
+    0x575c5c, // Cyan terracotta
+    0x7a4958, // Purple terracotta
+    0x4c3e5c, // Blue terracotta
+    0x4c3223, // Brown terracotta
+    0x4c522a, // Green terracotta
+    0x8e3c2e, // Red terracotta
+    0x251610, // Black terracotta
+    0xbd3031, // Crimson nylium
+    0x943f61, // Crimson stem
+    0x5c191d, // Crimson hyphae
+    0x167e86, // Warped nylium
+    0x3a8e8c, // Warped stem
+    0x562c3e, // Warped hyphae
+    0x14b485, // Warped wart block
+    0x646464, // Deepslate
+    0xd8af93, // Raw iron
+    0x7fa796, // Glow lichen
+];
+
+/// How much each shade darkens the base color, out of 255.
+const SHADES: [u32; 4] = [180, 220, 255, 135];
+
+/// Returns the RGB color of a color ID, or `None` if the color ID is
+/// transparent or unknown.
+pub fn to_rgb(color: u8) -> Option<[u8; 3]> {
+    let base = *BASE_COLORS.get(usize::from(color / 4))?;
+
+    if color < 4 {
+        return None;
+    }
+
+    let shade = SHADES[usize::from(color % 4)];
+    let channel = |shift: u32| (((base >> shift) & 0xff) * shade / 255) as u8;
+
+    Some([channel(16), channel(8), channel(0)])
+}
+
+/// Returns the opaque color ID closest to an RGB color.
+pub fn nearest(rgb: [u8; 3]) -> u8 {
+    nearest_with_error(rgb.map(i32::from)).0
+}
+
+fn nearest_with_error(rgb: [i32; 3]) -> (u8, [i32; 3]) {
+    let mut best = (TRANSPARENT, [0; 3], i32::MAX);
+
+    for color in 4..COLOR_COUNT as u8 {
+        let [r, g, b] = to_rgb(color).unwrap().map(i32::from);
+        let error = [rgb[0] - r, rgb[1] - g, rgb[2] - b];
+
+        // Weighted by how sensitive eyes are to each channel.
+        let distance = 3 * error[0] * error[0] + 4 * error[1] * error[1] + 2 * error[2] * error[2];
+
+        if distance < best.2 {
+            best = (color, error, distance);
+        }
+    }
+
+    (best.0, best.1)
+}
+
+/// Converts an RGBA image to color IDs. The image is given as rows of `width`
+/// pixels with four bytes each. Pixels that are mostly transparent become
+/// [`TRANSPARENT`].
+///
+/// With `dither`, the error of every pixel is spread to the pixels next to it
+/// (Floyd-Steinberg dithering), which makes gradients look much better than
+/// picking the nearest color of every pixel.
+///
+/// # Panics
+///
+/// Panics if the length of `rgba` is not a multiple of `width * 4`.
+pub fn from_rgba(width: usize, rgba: &[u8], dither: bool) -> Vec<u8> {
+    assert!(
+        width > 0 && rgba.len().is_multiple_of(width * 4),
+        "RGBA image is not {width} pixels wide"
+    );
+
+    let height = rgba.len() / (width * 4);
+
+    let mut colors = vec![TRANSPARENT; width * height];
+    let mut errors = vec![[0; 3]; if dither { width * height } else { 0 }];
+
+    for y in 0..height {
+        for x in 0..width {
+            let idx = y * width + x;
+            let [r, g, b, a] = [0, 1, 2, 3].map(|i| i32::from(rgba[idx * 4 + i]));
+
+            if a < 128 {
+                continue;
+            }
+
+            if !dither {
+                colors[idx] = nearest_with_error([r, g, b]).0;
+                continue;
+            }
+
+            let [er, eg, eb] = errors[idx];
+            let rgb = [r + er / 16, g + eg / 16, b + eb / 16].map(|c| c.clamp(0, 255));
+
+            let (color, error) = nearest_with_error(rgb);
+            colors[idx] = color;
+
+            let mut spread = |x: usize, y: usize, weight: i32| {
+                if x < width && y < height {
+                    for (e, error) in errors[y * width + x].iter_mut().zip(error) {
+                        *e += error * weight;
+                    }
+                }
+            };
+
+            spread(x + 1, y, 7);
+            if x > 0 {
+                spread(x - 1, y + 1, 3);
+            }
+            spread(x, y + 1, 5);
+            spread(x + 1, y + 1, 1);
+        }
+    }
+
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors() {
+        assert_eq!(to_rgb(TRANSPARENT), None);
+        assert_eq!(to_rgb(3), None);
+        assert_eq!(to_rgb(8 * 4 + 2), Some([255, 255, 255]));
+        assert_eq!(to_rgb(COLOR_COUNT as u8), None);
+
+        assert_eq!(nearest([255, 255, 255]), 8 * 4 + 2);
+        assert_eq!(nearest([255, 0, 0]), 4 * 4 + 2);
+
+        for color in 4..COLOR_COUNT as u8 {
+            let rgb = to_rgb(color).unwrap();
+            assert_eq!(to_rgb(nearest(rgb)), Some(rgb));
+        }
+    }
+
+    #[test]
+    fn dither_flat_color() {
+        // A color that isn't in the palette.
+        let rgba = [[150, 70, 200, 255]; 16 * 16].concat();
+
+        let plain = from_rgba(16, &rgba, false);
+        assert_eq!(plain.len(), 16 * 16);
+        assert!(plain.iter().all(|&c| c == plain[0]));
+
+        // Dithering mixes colors to get closer to the color on average.
+        let dithered = from_rgba(16, &rgba, true);
+        assert!(dithered.iter().any(|&c| c != dithered[0]));
+
+        assert_eq!(from_rgba(1, &[255, 255, 255, 0], true), [TRANSPARENT]);
+    }
+}
@@ -0,0 +1,271 @@
+//! # Maps
+//! Custom images on map items, such as image boards and minimaps.
+//!
+//! A map is an entity with a [`MapId`], a [`MapCanvas`] with the pixels of the
+//! map, [`MapIcons`] drawn on top of the pixels, and the [`Location`] of the
+//! instance the map is shown in. Clients in the instance see the map on any
+//! map item with the ID of the map, made with [`MapId::item`].
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_map::{color, MapBundle, MapCanvas, MapManager};
+//! fn spawn_flag(
+//!     mut commands: Commands,
+//!     mut maps: ResMut<MapManager>,
+//!     instance: Entity,
+//! ) -> Entity {
+//!     let id = maps.next_id();
+//!
+//!     let mut canvas = MapCanvas::new();
+//!     canvas.fill_rect(0, 0, 128, 64, color::nearest([255, 255, 255]));
+//!     canvas.fill_rect(0, 64, 128, 64, color::nearest([255, 0, 0]));
+//!
+//!     commands
+//!         .spawn(MapBundle {
+//!             canvas,
+//!             ..MapBundle::new(id, instance)
+//!         })
+//!         .id()
+//! }
+//! ```
+//!
+//! Only the parts of a canvas that changed are sent to clients, so maps can
+//! be redrawn often. Images wider than a single map can be split over a wall
+//! of maps with [`MapWall`].
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+mod canvas;
+pub mod color;
+pub mod packet;
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use canvas::{MapCanvas, MapWall, MAP_SIZE};
+use packet::{Data, Icon, IconType, MapUpdateS2c};
+use valence_client::{Client, FlushPacketsSet};
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_entity::Location;
+use valence_instance::{Instance, WriteUpdatePacketsToInstancesSet};
+use valence_nbt::compound;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapManager>()
+            .add_system(
+                update_maps
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(WriteUpdatePacketsToInstancesSet),
+            )
+            .add_system(
+                init_maps_for_clients
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+#[derive(Bundle)]
+pub struct MapBundle {
+    pub id: MapId,
+    pub canvas: MapCanvas,
+    pub icons: MapIcons,
+    pub location: Location,
+}
+
+impl MapBundle {
+    /// Creates a transparent map without icons in an instance.
+    pub fn new(id: MapId, instance: Entity) -> Self {
+        Self {
+            id,
+            canvas: MapCanvas::new(),
+            icons: MapIcons::default(),
+            location: Location(instance),
+        }
+    }
+}
+
+/// The ID of a map, which connects map items to the map they show. Obtain new
+/// IDs from the [`MapManager`].
+#[derive(Component, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MapId(i32);
+
+impl MapId {
+    pub fn new(id: i32) -> Self {
+        Self(id)
+    }
+
+    /// Returns the underlying map ID as an integer.
+    pub fn get(self) -> i32 {
+        self.0
+    }
+
+    /// Returns a filled map item that shows the map with this ID.
+    pub fn item(self) -> ItemStack {
+        ItemStack::new(ItemKind::FilledMap, 1, Some(compound! { "map" => self.0 }))
+    }
+}
+
+/// Hands out unique [`MapId`]s.
+#[derive(Resource, Default, Debug)]
+pub struct MapManager {
+    next_id: i32,
+}
+
+impl MapManager {
+    /// Returns the next unique map ID and increments the counter.
+    pub fn next_id(&mut self) -> MapId {
+        let id = MapId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+}
+
+/// The icons drawn on top of a map, like the markers of players and banners.
+#[derive(Component, Clone, PartialEq, Default, Debug)]
+pub struct MapIcons(pub Vec<MapIcon>);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MapIcon {
+    pub icon_type: IconType,
+    /// The position of the icon in half pixels, from `-128` on the left or top
+    /// edge to `127` on the right or bottom edge.
+    pub position: [i8; 2],
+    /// The rotation of the icon in steps of 22.5° clockwise, from `0` to `15`.
+    pub direction: i8,
+    pub display_name: Option<Text>,
+}
+
+impl MapIcon {
+    /// Creates an icon that points up at a pixel of the map.
+    pub fn new(icon_type: IconType, x: usize, y: usize) -> Self {
+        let half_pixels = |p: usize| (p.min(MAP_SIZE - 1) as i32 * 2 - MAP_SIZE as i32) as i8;
+
+        Self {
+            icon_type,
+            position: [half_pixels(x), half_pixels(y)],
+            direction: 0,
+            display_name: None,
+        }
+    }
+
+    pub fn with_direction(mut self, direction: i8) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<Text>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+}
+
+fn map_icons(icons: &MapIcons) -> Vec<Icon<'_>> {
+    icons
+        .0
+        .iter()
+        .map(|icon| Icon {
+            icon_type: icon.icon_type,
+            position: icon.position,
+            direction: icon.direction,
+            display_name: icon.display_name.as_ref().map(Cow::Borrowed),
+        })
+        .collect()
+}
+
+fn write_full_map(mut writer: impl WritePacket, id: MapId, canvas: &MapCanvas, icons: &MapIcons) {
+    writer.write_packet(&MapUpdateS2c {
+        map_id: VarInt(id.0),
+        scale: 0,
+        locked: true,
+        icons: Some(map_icons(icons)),
+        data: Some(Data {
+            columns: MAP_SIZE as u8,
+            rows: MAP_SIZE as u8,
+            position: [0, 0],
+            data: canvas.colors(),
+        }),
+    });
+}
+
+/// Sends the changes of maps to the clients in the instance of the map.
+fn update_maps(
+    mut maps: Query<
+        (&MapId, &mut MapCanvas, Ref<MapIcons>, Ref<Location>),
+        Or<(Changed<MapCanvas>, Changed<MapIcons>, Changed<Location>)>,
+    >,
+    mut instances: Query<&mut Instance>,
+) {
+    for (&id, mut canvas, icons, loc) in &mut maps {
+        let Ok(mut instance) = instances.get_mut(loc.0) else {
+            continue
+        };
+
+        // Clients in the instance don't know anything about new maps yet.
+        if canvas.is_added() || loc.is_changed() {
+            let canvas = canvas.bypass_change_detection();
+            canvas.take_dirty();
+
+            write_full_map(&mut instance, id, canvas, &icons);
+            continue;
+        }
+
+        let dirty = canvas.bypass_change_detection().take_dirty();
+
+        if dirty.is_none() && !icons.is_changed() {
+            continue;
+        }
+
+        instance.write_packet(&MapUpdateS2c {
+            map_id: VarInt(id.0),
+            scale: 0,
+            locked: true,
+            icons: icons.is_changed().then(|| map_icons(&icons)),
+            data: dirty
+                .as_ref()
+                .map(|([x, y], [width, height], colors)| Data {
+                    columns: *width as u8,
+                    rows: *height as u8,
+                    position: [*x as i8, *y as i8],
+                    data: colors,
+                }),
+        });
+    }
+}
+
+/// Sends all maps in an instance to clients that joined the instance.
+fn init_maps_for_clients(
+    mut clients: Query<(&mut Client, &Location), Changed<Location>>,
+    maps: Query<(&MapId, &MapCanvas, &MapIcons, &Location)>,
+) {
+    for (mut client, loc) in &mut clients {
+        for (&id, canvas, icons, map_loc) in &maps {
+            if map_loc == loc {
+                write_full_map(&mut client, id, canvas, icons);
+            }
+        }
+    }
+}
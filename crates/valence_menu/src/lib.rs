@@ -0,0 +1,346 @@
+//! # Menus
+//! Menus built from inventories, like the chest GUIs used for server selectors
+//! and shops.
+//!
+//! A [`Menu`] is added to an entity with an [`Inventory`], which is filled with
+//! the items of the menu and made readonly. Clients open the menu like any
+//! other inventory, with [`OpenInventory`]. Items in the menu can't be taken
+//! by clients. Instead, clicking an item runs its click handler and sends a
+//! [`MenuClickEvent`].
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_core::item::{ItemKind, ItemStack};
+//! # use valence_inventory::{Inventory, InventoryKind, OpenInventory};
+//! # use valence_menu::{Menu, MenuItem};
+//! fn open_server_selector(client: Entity, commands: &mut Commands) {
+//!     let lobby = MenuItem::new(ItemStack::new(ItemKind::Compass, 1, None)).on_click(
+//!         |_menu, click, commands| {
+//!             // Send the client to the lobby...
+//!             commands.entity(click.client).remove::<OpenInventory>();
+//!         },
+//!     );
+//!
+//!     let menu = commands
+//!         .spawn((
+//!             Inventory::with_title(InventoryKind::Generic9x3, "Servers"),
+//!             Menu::new().with_item(13, lobby),
+//!         ))
+//!         .id();
+//!
+//!     commands.entity(client).insert(OpenInventory::new(menu));
+//! }
+//! ```
+//!
+//! Changes to a menu are shown to the clients viewing it. Lists of items that
+//! don't fit in the menu can be split over multiple [`Pages`].
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use num_integer::div_ceil;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_inventory::packet::ClickMode;
+use valence_inventory::{ClickSlot, ClientInventoryState, Inventory, OpenInventory};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuClickEvent>().add_systems(
+            (
+                handle_menu_clicks,
+                update_menu_inventories.after(handle_menu_clicks),
+            )
+                .in_base_set(CoreSet::Update),
+        );
+    }
+}
+
+type ClickHandler = Arc<dyn Fn(&mut Menu, &MenuClickEvent, &mut Commands) + Send + Sync>;
+
+/// The items of a menu. Add this component to an entity with an [`Inventory`]
+/// to show the items in the inventory.
+#[derive(Component, Clone, Default, Debug)]
+pub struct Menu {
+    items: BTreeMap<u16, MenuItem>,
+    pub pages: Option<Pages>,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_item(mut self, slot: u16, item: impl Into<MenuItem>) -> Self {
+        self.set_item(slot, item);
+        self
+    }
+
+    pub fn with_pages(mut self, pages: Pages) -> Self {
+        self.pages = Some(pages);
+        self
+    }
+
+    /// Puts an item in a slot of the menu. Slots used by the [`Pages`] of the
+    /// menu show the items of the current page instead.
+    pub fn set_item(&mut self, slot: u16, item: impl Into<MenuItem>) {
+        self.items.insert(slot, item.into());
+    }
+
+    pub fn remove_item(&mut self, slot: u16) -> Option<MenuItem> {
+        self.items.remove(&slot)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns the item shown in a slot of the menu.
+    pub fn item(&self, slot: u16) -> Option<&ItemStack> {
+        match self.slot(slot)? {
+            MenuSlot::Item(item) => Some(&item.stack),
+            MenuSlot::PreviousPage => Some(&self.pages.as_ref()?.previous_item),
+            MenuSlot::NextPage => Some(&self.pages.as_ref()?.next_item),
+        }
+    }
+
+    fn slot(&self, slot: u16) -> Option<MenuSlot<'_>> {
+        if let Some(pages) = &self.pages {
+            if let Some(pos) = pages.slots.iter().position(|&s| s == slot) {
+                return pages
+                    .items
+                    .get(pages.page() * pages.slots.len() + pos)
+                    .map(MenuSlot::Item);
+            }
+
+            if slot == pages.previous && pages.page() > 0 {
+                return Some(MenuSlot::PreviousPage);
+            }
+
+            if slot == pages.next && pages.page() + 1 < pages.page_count() {
+                return Some(MenuSlot::NextPage);
+            }
+        }
+
+        self.items.get(&slot).map(MenuSlot::Item)
+    }
+}
+
+enum MenuSlot<'a> {
+    Item(&'a MenuItem),
+    PreviousPage,
+    NextPage,
+}
+
+/// An item in a [`Menu`] with an optional click handler.
+#[derive(Clone)]
+pub struct MenuItem {
+    pub stack: ItemStack,
+    on_click: Option<ClickHandler>,
+}
+
+impl MenuItem {
+    pub fn new(stack: ItemStack) -> Self {
+        Self {
+            stack,
+            on_click: None,
+        }
+    }
+
+    /// Sets the function that is called when the item is clicked. The function
+    /// is given the menu the item is in, so it can change the menu in response
+    /// to the click.
+    pub fn on_click(
+        mut self,
+        handler: impl Fn(&mut Menu, &MenuClickEvent, &mut Commands) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_click = Some(Arc::new(handler));
+        self
+    }
+}
+
+impl From<ItemStack> for MenuItem {
+    fn from(stack: ItemStack) -> Self {
+        Self::new(stack)
+    }
+}
+
+impl fmt::Debug for MenuItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MenuItem")
+            .field("stack", &self.stack)
+            .field("on_click", &self.on_click.is_some())
+            .finish()
+    }
+}
+
+/// A list of items split over multiple pages of a [`Menu`], with buttons to go
+/// to the previous and next page.
+#[derive(Clone, Debug)]
+pub struct Pages {
+    /// The slots that show the items of the current page, in order.
+    pub slots: Vec<u16>,
+    pub items: Vec<MenuItem>,
+    /// The slot of the button that goes to the previous page. The button is
+    /// only shown if there is a previous page.
+    pub previous: u16,
+    pub previous_item: ItemStack,
+    /// The slot of the button that goes to the next page. The button is only
+    /// shown if there is a next page.
+    pub next: u16,
+    pub next_item: ItemStack,
+    page: usize,
+}
+
+impl Pages {
+    /// Creates pages that show items in the given slots, with arrows to go to
+    /// the previous and next page.
+    pub fn new(slots: impl IntoIterator<Item = u16>, previous: u16, next: u16) -> Self {
+        Self {
+            slots: slots.into_iter().collect(),
+            items: vec![],
+            previous,
+            previous_item: ItemStack::new(ItemKind::Arrow, 1, None),
+            next,
+            next_item: ItemStack::new(ItemKind::Arrow, 1, None),
+            page: 0,
+        }
+    }
+
+    pub fn with_items(mut self, items: impl IntoIterator<Item = impl Into<MenuItem>>) -> Self {
+        self.items = items.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the index of the current page.
+    pub fn page(&self) -> usize {
+        self.page.min(self.page_count() - 1)
+    }
+
+    /// Goes to a page. The page is clamped to the last page.
+    pub fn set_page(&mut self, page: usize) {
+        self.page = page.min(self.page_count() - 1);
+    }
+
+    /// Returns the number of pages, which is at least one.
+    pub fn page_count(&self) -> usize {
+        if self.slots.is_empty() {
+            1
+        } else {
+            div_ceil(self.items.len(), self.slots.len()).max(1)
+        }
+    }
+}
+
+/// Sent when a client clicks a slot of a [`Menu`] it has open. Not sent for
+/// the buttons of [`Pages`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MenuClickEvent {
+    pub client: Entity,
+    /// The entity with the [`Menu`].
+    pub menu: Entity,
+    pub slot: u16,
+    pub button: i8,
+    pub mode: ClickMode,
+}
+
+fn handle_menu_clicks(
+    mut events: EventReader<ClickSlot>,
+    clients: Query<(&ClientInventoryState, &OpenInventory)>,
+    mut menus: Query<(&mut Menu, &Inventory)>,
+    mut click_events: EventWriter<MenuClickEvent>,
+    mut commands: Commands,
+) {
+    for event in events.iter() {
+        let Ok((inv_state, open_inventory)) = clients.get(event.client) else {
+            continue
+        };
+
+        if event.window_id != inv_state.window_id() {
+            continue;
+        }
+
+        let Ok((mut menu, inventory)) = menus.get_mut(open_inventory.entity) else {
+            continue
+        };
+
+        let Ok(slot) = u16::try_from(event.slot_id) else {
+            continue
+        };
+
+        // Dragging and collecting items are sent as multiple clicks.
+        if slot >= inventory.slot_count()
+            || matches!(event.mode, ClickMode::Drag | ClickMode::DoubleClick)
+        {
+            continue;
+        }
+
+        let click = MenuClickEvent {
+            client: event.client,
+            menu: open_inventory.entity,
+            slot,
+            button: event.button,
+            mode: event.mode,
+        };
+
+        let handler = match menu.slot(slot) {
+            Some(MenuSlot::Item(item)) => item.on_click.clone(),
+            Some(MenuSlot::PreviousPage) => {
+                let pages = menu.pages.as_mut().unwrap();
+                pages.set_page(pages.page() - 1);
+                continue;
+            }
+            Some(MenuSlot::NextPage) => {
+                let pages = menu.pages.as_mut().unwrap();
+                pages.set_page(pages.page() + 1);
+                continue;
+            }
+            None => None,
+        };
+
+        if let Some(handler) = handler {
+            handler(&mut menu, &click, &mut commands);
+        }
+
+        click_events.send(click);
+    }
+}
+
+/// Fills the inventories of menus with the items of the menu.
+fn update_menu_inventories(mut menus: Query<(&Menu, &mut Inventory), Changed<Menu>>) {
+    for (menu, mut inventory) in &mut menus {
+        if !inventory.readonly {
+            inventory.readonly = true;
+        }
+
+        for idx in 0..inventory.slot_count() {
+            let item = menu.item(idx);
+
+            if inventory.slot(idx) != item {
+                inventory.set_slot(idx, item.cloned());
+            }
+        }
+    }
+}
@@ -0,0 +1,637 @@
+//! The whitelist and the lists of banned players and IP addresses.
+//!
+//! The lists are checked for every client during login, after the client is
+//! authenticated. Clients that are banned or not on the enabled whitelist are
+//! disconnected with the messages in [`AccessSettings`]. The lists can be
+//! changed at runtime through the [`AccessControl`] resource. Every change is
+//! saved to the [`AccessStorage`] and sent as an [`AccessListEvent`], and
+//! clients that are no longer allowed on the server are disconnected.
+//!
+//! [`JsonFileStorage`] stores the lists in the same files as the vanilla
+//! server, so existing lists can be used.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::OffsetDateTime;
+use tracing::warn;
+use valence_client::{Client, DisconnectClient, Ip};
+use valence_core::translation_key;
+use valence_core::uuid::UniqueId;
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<AccessListEvent>()
+        .add_systems((send_access_list_events, disconnect_denied_clients).chain());
+}
+
+/// Settings for the whitelist and ban lists, part of the [`NetworkSettings`].
+#[derive(Clone)]
+pub struct AccessSettings {
+    /// If only players on the whitelist can join.
+    ///
+    /// # Default Value
+    ///
+    /// `false`
+    pub whitelist_enabled: bool,
+    /// The message shown to players that are not on the enabled whitelist.
+    ///
+    /// # Default Value
+    ///
+    /// The vanilla message.
+    pub whitelist_message: Text,
+    /// The message shown to banned players. If `None`, the vanilla message
+    /// with the reason and expiration date of the ban is shown.
+    ///
+    /// # Default Value
+    ///
+    /// `None`
+    pub ban_message: Option<Text>,
+    /// Where the lists are loaded from when the server starts and saved to
+    /// when they change.
+    ///
+    /// # Default Value
+    ///
+    /// `()`, which keeps the lists in memory.
+    pub storage: Arc<dyn AccessStorage>,
+}
+
+impl Default for AccessSettings {
+    fn default() -> Self {
+        Self {
+            whitelist_enabled: false,
+            whitelist_message: Text::translate(
+                translation_key::MULTIPLAYER_DISCONNECT_NOT_WHITELISTED,
+                [],
+            ),
+            ban_message: None,
+            storage: Arc::new(()),
+        }
+    }
+}
+
+/// A player on the whitelist.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+/// A banned player.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PlayerBan {
+    pub uuid: Uuid,
+    pub name: String,
+    #[serde(with = "date")]
+    pub created: OffsetDateTime,
+    /// Who created the ban.
+    pub source: String,
+    /// When the ban ends, or `None` if it is permanent.
+    #[serde(with = "expiry")]
+    pub expires: Option<OffsetDateTime>,
+    pub reason: String,
+}
+
+impl PlayerBan {
+    /// Creates a permanent ban by the server.
+    pub fn new(uuid: Uuid, name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            uuid,
+            name: name.into(),
+            created: OffsetDateTime::now_utc(),
+            source: "Server".into(),
+            expires: None,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .map_or(false, |expires| expires <= OffsetDateTime::now_utc())
+    }
+}
+
+/// A banned IP address.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct IpBan {
+    pub ip: IpAddr,
+    #[serde(with = "date")]
+    pub created: OffsetDateTime,
+    /// Who created the ban.
+    pub source: String,
+    /// When the ban ends, or `None` if it is permanent.
+    #[serde(with = "expiry")]
+    pub expires: Option<OffsetDateTime>,
+    pub reason: String,
+}
+
+impl IpBan {
+    /// Creates a permanent ban by the server.
+    pub fn new(ip: IpAddr, reason: impl Into<String>) -> Self {
+        Self {
+            ip,
+            created: OffsetDateTime::now_utc(),
+            source: "Server".into(),
+            expires: None,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .map_or(false, |expires| expires <= OffsetDateTime::now_utc())
+    }
+}
+
+/// The contents of the whitelist and ban lists.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct AccessLists {
+    pub whitelist: Vec<WhitelistEntry>,
+    pub banned_players: Vec<PlayerBan>,
+    pub banned_ips: Vec<IpBan>,
+}
+
+/// Loads and saves the [`AccessLists`].
+pub trait AccessStorage: Send + Sync + 'static {
+    fn load(&self) -> anyhow::Result<AccessLists>;
+
+    fn save(&self, lists: &AccessLists) -> anyhow::Result<()>;
+}
+
+/// Keeps the lists in memory only.
+impl AccessStorage for () {
+    fn load(&self) -> anyhow::Result<AccessLists> {
+        Ok(AccessLists::default())
+    }
+
+    fn save(&self, _lists: &AccessLists) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Stores the lists in `whitelist.json`, `banned-players.json`, and
+/// `banned-ips.json` in a directory, in the format of the vanilla server.
+/// Missing files are treated as empty lists.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct JsonFileStorage {
+    pub directory: PathBuf,
+}
+
+impl JsonFileStorage {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(&self, file: &str) -> anyhow::Result<Vec<T>> {
+        let path = self.directory.join(file);
+
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .with_context(|| format!("failed to parse {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    fn write<T: Serialize>(&self, file: &str, entries: &[T]) -> anyhow::Result<()> {
+        let path = self.directory.join(file);
+
+        fs::write(&path, serde_json::to_string_pretty(entries)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+impl AccessStorage for JsonFileStorage {
+    fn load(&self) -> anyhow::Result<AccessLists> {
+        Ok(AccessLists {
+            whitelist: self.read("whitelist.json")?,
+            banned_players: self.read("banned-players.json")?,
+            banned_ips: self.read("banned-ips.json")?,
+        })
+    }
+
+    fn save(&self, lists: &AccessLists) -> anyhow::Result<()> {
+        self.write("whitelist.json", &lists.whitelist)?;
+        self.write("banned-players.json", &lists.banned_players)?;
+        self.write("banned-ips.json", &lists.banned_ips)
+    }
+}
+
+/// Sent when the whitelist or the ban lists change.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AccessListEvent {
+    WhitelistAdded(WhitelistEntry),
+    WhitelistRemoved(WhitelistEntry),
+    WhitelistToggled { enabled: bool },
+    PlayerBanned(PlayerBan),
+    PlayerPardoned(PlayerBan),
+    IpBanned(IpBan),
+    IpPardoned(IpBan),
+}
+
+/// The whitelist and ban lists of the server.
+///
+/// This is shared with the login process, so changes apply to clients that
+/// are logging in right away.
+#[derive(Resource, Clone)]
+pub struct AccessControl(Arc<AccessControlInner>);
+
+struct AccessControlInner {
+    state: RwLock<AccessState>,
+    storage: Arc<dyn AccessStorage>,
+    events_send: Sender<AccessListEvent>,
+    events_recv: Receiver<AccessListEvent>,
+}
+
+struct AccessState {
+    lists: AccessLists,
+    whitelist_enabled: bool,
+    whitelist_message: Text,
+    ban_message: Option<Text>,
+}
+
+impl AccessControl {
+    pub(crate) fn new(settings: &AccessSettings) -> Self {
+        let lists = settings.storage.load().unwrap_or_else(|e| {
+            warn!("failed to load the whitelist and ban lists: {e:#}");
+            AccessLists::default()
+        });
+
+        let (events_send, events_recv) = flume::unbounded();
+
+        Self(Arc::new(AccessControlInner {
+            state: RwLock::new(AccessState {
+                lists,
+                whitelist_enabled: settings.whitelist_enabled,
+                whitelist_message: settings.whitelist_message.clone(),
+                ban_message: settings.ban_message.clone(),
+            }),
+            storage: settings.storage.clone(),
+            events_send,
+            events_recv,
+        }))
+    }
+
+    /// Returns the message to disconnect a player with if they are not
+    /// allowed to join.
+    pub fn check(&self, uuid: Uuid, ip: IpAddr) -> Result<(), Text> {
+        let state = self.0.state.read().unwrap();
+
+        if let Some(ban) = state
+            .lists
+            .banned_players
+            .iter()
+            .find(|ban| ban.uuid == uuid && !ban.is_expired())
+        {
+            return Err(state.ban_message.clone().unwrap_or_else(|| {
+                ban_message(
+                    translation_key::MULTIPLAYER_DISCONNECT_BANNED_REASON,
+                    translation_key::MULTIPLAYER_DISCONNECT_BANNED_EXPIRATION,
+                    &ban.reason,
+                    ban.expires,
+                )
+            }));
+        }
+
+        if let Some(ban) = state
+            .lists
+            .banned_ips
+            .iter()
+            .find(|ban| ban.ip == ip && !ban.is_expired())
+        {
+            return Err(state.ban_message.clone().unwrap_or_else(|| {
+                ban_message(
+                    translation_key::MULTIPLAYER_DISCONNECT_BANNED_IP_REASON,
+                    translation_key::MULTIPLAYER_DISCONNECT_BANNED_IP_EXPIRATION,
+                    &ban.reason,
+                    ban.expires,
+                )
+            }));
+        }
+
+        if state.whitelist_enabled && !state.lists.whitelist.iter().any(|e| e.uuid == uuid) {
+            return Err(state.whitelist_message.clone());
+        }
+
+        Ok(())
+    }
+
+    /// A copy of the current lists.
+    pub fn lists(&self) -> AccessLists {
+        self.0.state.read().unwrap().lists.clone()
+    }
+
+    pub fn is_whitelist_enabled(&self) -> bool {
+        self.0.state.read().unwrap().whitelist_enabled
+    }
+
+    pub fn set_whitelist_enabled(&self, enabled: bool) {
+        let mut state = self.0.state.write().unwrap();
+
+        if state.whitelist_enabled != enabled {
+            state.whitelist_enabled = enabled;
+            self.send_event(AccessListEvent::WhitelistToggled { enabled });
+        }
+    }
+
+    pub fn set_whitelist_message(&self, message: impl Into<Text>) {
+        self.0.state.write().unwrap().whitelist_message = message.into();
+    }
+
+    pub fn set_ban_message(&self, message: Option<Text>) {
+        self.0.state.write().unwrap().ban_message = message;
+    }
+
+    pub fn is_whitelisted(&self, uuid: Uuid) -> bool {
+        let state = self.0.state.read().unwrap();
+        state.lists.whitelist.iter().any(|e| e.uuid == uuid)
+    }
+
+    /// Adds a player to the whitelist. Returns `false` if the player was
+    /// already on it.
+    pub fn add_to_whitelist(&self, uuid: Uuid, name: impl Into<String>) -> bool {
+        self.modify(|lists| {
+            if lists.whitelist.iter().any(|e| e.uuid == uuid) {
+                return None;
+            }
+
+            let entry = WhitelistEntry {
+                uuid,
+                name: name.into(),
+            };
+
+            lists.whitelist.push(entry.clone());
+            Some(AccessListEvent::WhitelistAdded(entry))
+        })
+    }
+
+    /// Removes a player from the whitelist. Returns `false` if the player was
+    /// not on it.
+    pub fn remove_from_whitelist(&self, uuid: Uuid) -> bool {
+        self.modify(|lists| {
+            let index = lists.whitelist.iter().position(|e| e.uuid == uuid)?;
+            Some(AccessListEvent::WhitelistRemoved(
+                lists.whitelist.remove(index),
+            ))
+        })
+    }
+
+    /// Returns the ban of a player if they are banned and the ban hasn't
+    /// expired.
+    pub fn player_ban(&self, uuid: Uuid) -> Option<PlayerBan> {
+        let state = self.0.state.read().unwrap();
+
+        state
+            .lists
+            .banned_players
+            .iter()
+            .find(|ban| ban.uuid == uuid && !ban.is_expired())
+            .cloned()
+    }
+
+    /// Bans a player, replacing their previous ban.
+    pub fn ban_player(&self, ban: PlayerBan) {
+        self.modify(|lists| {
+            lists.banned_players.retain(|b| b.uuid != ban.uuid);
+            lists.banned_players.push(ban.clone());
+            Some(AccessListEvent::PlayerBanned(ban))
+        });
+    }
+
+    /// Removes the ban of a player, returning the removed ban.
+    pub fn pardon_player(&self, uuid: Uuid) -> Option<PlayerBan> {
+        let mut pardoned = None;
+
+        self.modify(|lists| {
+            let index = lists.banned_players.iter().position(|b| b.uuid == uuid)?;
+            let ban = lists.banned_players.remove(index);
+            pardoned = Some(ban.clone());
+            Some(AccessListEvent::PlayerPardoned(ban))
+        });
+
+        pardoned
+    }
+
+    /// Returns the ban of an IP address if it is banned and the ban hasn't
+    /// expired.
+    pub fn ip_ban(&self, ip: IpAddr) -> Option<IpBan> {
+        let state = self.0.state.read().unwrap();
+
+        state
+            .lists
+            .banned_ips
+            .iter()
+            .find(|ban| ban.ip == ip && !ban.is_expired())
+            .cloned()
+    }
+
+    /// Bans an IP address, replacing its previous ban.
+    pub fn ban_ip(&self, ban: IpBan) {
+        self.modify(|lists| {
+            lists.banned_ips.retain(|b| b.ip != ban.ip);
+            lists.banned_ips.push(ban.clone());
+            Some(AccessListEvent::IpBanned(ban))
+        });
+    }
+
+    /// Removes the ban of an IP address, returning the removed ban.
+    pub fn pardon_ip(&self, ip: IpAddr) -> Option<IpBan> {
+        let mut pardoned = None;
+
+        self.modify(|lists| {
+            let index = lists.banned_ips.iter().position(|b| b.ip == ip)?;
+            let ban = lists.banned_ips.remove(index);
+            pardoned = Some(ban.clone());
+            Some(AccessListEvent::IpPardoned(ban))
+        });
+
+        pardoned
+    }
+
+    /// Changes the lists with `f`. If `f` returns an event, the lists are
+    /// saved and the event is sent. Returns if the lists were changed.
+    fn modify(&self, f: impl FnOnce(&mut AccessLists) -> Option<AccessListEvent>) -> bool {
+        let mut state = self.0.state.write().unwrap();
+
+        let Some(event) = f(&mut state.lists) else {
+            return false
+        };
+
+        if let Err(e) = self.0.storage.save(&state.lists) {
+            warn!("failed to save the whitelist and ban lists: {e:#}");
+        }
+
+        self.send_event(event);
+        true
+    }
+
+    fn send_event(&self, event: AccessListEvent) {
+        // The receiver is never dropped.
+        let _ = self.0.events_send.send(event);
+    }
+}
+
+fn ban_message(
+    reason_key: &'static str,
+    expiration_key: &'static str,
+    reason: &str,
+    expires: Option<OffsetDateTime>,
+) -> Text {
+    let message = Text::translate(reason_key, [reason.to_owned().into()]);
+
+    match expires.and_then(|expires| expires.format(DATE_FORMAT).ok()) {
+        Some(expires) => message + Text::translate(expiration_key, [expires.into()]),
+        None => message,
+    }
+}
+
+fn send_access_list_events(access: Res<AccessControl>, mut events: EventWriter<AccessListEvent>) {
+    events.send_batch(access.0.events_recv.try_iter());
+}
+
+/// Disconnects the clients that are not allowed on the server anymore after
+/// they were banned or the whitelist changed.
+fn disconnect_denied_clients(
+    mut events: EventReader<AccessListEvent>,
+    access: Res<AccessControl>,
+    clients: Query<(Entity, &UniqueId, &Ip), With<Client>>,
+    mut commands: Commands,
+) {
+    let denying = events.iter().any(|event| {
+        matches!(
+            event,
+            AccessListEvent::WhitelistRemoved(_)
+                | AccessListEvent::WhitelistToggled { enabled: true }
+                | AccessListEvent::PlayerBanned(_)
+                | AccessListEvent::IpBanned(_)
+        )
+    });
+
+    if !denying {
+        return;
+    }
+
+    for (client, uuid, ip) in &clients {
+        if let Err(reason) = access.check(uuid.0, ip.0) {
+            commands.add(DisconnectClient { client, reason });
+        }
+    }
+}
+
+/// The format of dates in the vanilla ban lists, like
+/// `2023-06-01 12:30:00 +0000`.
+const DATE_FORMAT: &[FormatItem<'static>] = format_description!(
+    "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+);
+
+mod date {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        date: &OffsetDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let date = date.format(DATE_FORMAT).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&date)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error> {
+        let date = String::deserialize(deserializer)?;
+        OffsetDateTime::parse(&date, DATE_FORMAT).map_err(de::Error::custom)
+    }
+}
+
+/// Expiration dates, which are `forever` for permanent bans.
+mod expiry {
+    use super::*;
+
+    pub(super) fn serialize<S: Serializer>(
+        expires: &Option<OffsetDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match expires {
+            Some(date) => super::date::serialize(date, serializer),
+            None => serializer.serialize_str("forever"),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<OffsetDateTime>, D::Error> {
+        let date = String::deserialize(deserializer)?;
+
+        if date == "forever" {
+            return Ok(None);
+        }
+
+        OffsetDateTime::parse(&date, DATE_FORMAT)
+            .map(Some)
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_files_round_trip() {
+        let dir = std::env::temp_dir().join(format!("valence-access-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let storage = JsonFileStorage::new(&dir);
+
+        assert_eq!(storage.load().unwrap(), AccessLists::default());
+
+        // A ban list written by the vanilla server.
+        fs::write(
+            dir.join("banned-players.json"),
+            r#"[
+  {
+    "uuid": "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+    "name": "Notch",
+    "created": "2023-06-01 12:30:00 +0000",
+    "source": "Server",
+    "expires": "forever",
+    "reason": "Banned by an operator."
+  }
+]"#,
+        )
+        .unwrap();
+
+        let mut lists = storage.load().unwrap();
+        let ban = &lists.banned_players[0];
+
+        assert_eq!(ban.name, "Notch");
+        assert_eq!(ban.expires, None);
+        assert_eq!(ban.created.year(), 2023);
+
+        lists.whitelist.push(WhitelistEntry {
+            uuid: ban.uuid,
+            name: "Notch".into(),
+        });
+        lists
+            .banned_ips
+            .push(IpBan::new("127.0.0.1".parse().unwrap(), "Spam"));
+
+        storage.save(&lists).unwrap();
+
+        let mut loaded = storage.load().unwrap();
+
+        // Dates are saved with second precision.
+        loaded.banned_ips[0].created = lists.banned_ips[0].created;
+        assert_eq!(loaded, lists);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,12 @@
 //! A channel specifically for sending/receiving batches of bytes.
+//!
+//! The buffers sent on the channel are queued as they are, so that the
+//! receiver can write all of them at once and give them back to a
+//! [`BufferPool`](valence_core::protocol::encode::BufferPool).
 
 #![allow(dead_code)]
 
+use std::mem;
 use std::sync::{Arc, Mutex};
 
 use bytes::BytesMut;
@@ -11,7 +16,8 @@ use tokio::sync::Notify;
 pub(crate) fn byte_channel(limit: usize) -> (ByteSender, ByteReceiver) {
     let shared = Arc::new(Shared {
         mtx: Mutex::new(Inner {
-            bytes: BytesMut::new(),
+            chunks: vec![],
+            len: 0,
             disconnected: false,
         }),
         notify: Notify::new(),
@@ -42,20 +48,26 @@ struct Shared {
 }
 
 struct Inner {
-    bytes: BytesMut,
+    /// The buffers sent and not yet received, in the order they were sent.
+    chunks: Vec<BytesMut>,
+    /// The total number of bytes in `chunks`.
+    len: usize,
     disconnected: bool,
 }
 
-impl ByteSender {
-    pub(crate) fn take_capacity(&mut self, additional: usize) -> BytesMut {
-        let mut lck = self.shared.mtx.lock().unwrap();
-
-        lck.bytes.reserve(additional);
+impl Inner {
+    fn push(&mut self, bytes: BytesMut) {
+        self.len += bytes.len();
+        self.chunks.push(bytes);
+    }
 
-        let len = lck.bytes.len();
-        lck.bytes.split_off(len)
+    fn take(&mut self) -> Vec<BytesMut> {
+        self.len = 0;
+        mem::take(&mut self.chunks)
     }
+}
 
+impl ByteSender {
     pub(crate) fn try_send(&mut self, mut bytes: BytesMut) -> Result<(), TrySendError> {
         let mut lck = self.shared.mtx.lock().unwrap();
 
@@ -67,18 +79,18 @@ impl ByteSender {
             return Ok(());
         }
 
-        let available = self.shared.limit - lck.bytes.len();
+        let available = self.shared.limit - lck.len;
 
         if bytes.len() > available {
             if available > 0 {
-                lck.bytes.unsplit(bytes.split_to(available));
+                lck.push(bytes.split_to(available));
                 self.shared.notify.notify_waiters();
             }
 
             return Err(TrySendError::Full(bytes));
         }
 
-        lck.bytes.unsplit(bytes);
+        lck.push(bytes);
         self.shared.notify.notify_waiters();
 
         Ok(())
@@ -97,16 +109,16 @@ impl ByteSender {
                     return Ok(());
                 }
 
-                let available = self.shared.limit - lck.bytes.len();
+                let available = self.shared.limit - lck.len;
 
                 if bytes.len() <= available {
-                    lck.bytes.unsplit(bytes);
+                    lck.push(bytes);
                     self.shared.notify.notify_waiters();
                     return Ok(());
                 }
 
                 if available > 0 {
-                    lck.bytes.unsplit(bytes.split_to(available));
+                    lck.push(bytes.split_to(available));
                     self.shared.notify.notify_waiters();
                 }
             }
@@ -144,12 +156,13 @@ impl SendError {
 }
 
 impl ByteReceiver {
-    pub(crate) fn try_recv(&mut self) -> Result<BytesMut, TryRecvError> {
+    /// Receives all the buffers sent so far.
+    pub(crate) fn try_recv(&mut self) -> Result<Vec<BytesMut>, TryRecvError> {
         let mut lck = self.shared.mtx.lock().unwrap();
 
-        if !lck.bytes.is_empty() {
+        if !lck.chunks.is_empty() {
             self.shared.notify.notify_waiters();
-            return Ok(lck.bytes.split());
+            return Ok(lck.take());
         }
 
         if lck.disconnected {
@@ -159,14 +172,15 @@ impl ByteReceiver {
         Err(TryRecvError::Empty)
     }
 
-    pub(crate) async fn recv_async(&mut self) -> Result<BytesMut, RecvError> {
+    /// Waits for buffers to be sent and receives all of them.
+    pub(crate) async fn recv_async(&mut self) -> Result<Vec<BytesMut>, RecvError> {
         loop {
             {
                 let mut lck = self.shared.mtx.lock().unwrap();
 
-                if !lck.bytes.is_empty() {
+                if !lck.chunks.is_empty() {
                     self.shared.notify.notify_waiters();
-                    return Ok(lck.bytes.split());
+                    return Ok(lck.take());
                 }
 
                 if lck.disconnected {
@@ -228,7 +242,18 @@ mod tests {
 
         assert_eq!(
             receiver.try_recv().unwrap(),
-            BytesMut::from("hell".as_bytes())
+            [BytesMut::from("hell".as_bytes())]
+        );
+
+        assert_eq!(sender.try_send("o".as_bytes().into()), Ok(()));
+        assert_eq!(sender.try_send("k".as_bytes().into()), Ok(()));
+
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            [
+                BytesMut::from("o".as_bytes()),
+                BytesMut::from("k".as_bytes())
+            ]
         );
     }
 
@@ -237,10 +262,10 @@ mod tests {
         let (mut sender, mut receiver) = byte_channel(4);
 
         let t = tokio::spawn(async move {
-            let bytes = receiver.recv_async().await.unwrap();
-            assert_eq!(&bytes[..], b"hell");
-            let bytes = receiver.recv_async().await.unwrap();
-            assert_eq!(&bytes[..], b"o");
+            let chunks = receiver.recv_async().await.unwrap();
+            assert_eq!(chunks, [BytesMut::from("hell".as_bytes())]);
+            let chunks = receiver.recv_async().await.unwrap();
+            assert_eq!(chunks, [BytesMut::from("o".as_bytes())]);
 
             assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
         });
@@ -15,7 +15,7 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 use sha1::Sha1;
 use sha2::{Digest, Sha256};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::TcpListener;
 use tracing::{error, info, trace, warn};
 use uuid::Uuid;
 use valence_client::is_valid_username;
@@ -28,6 +28,7 @@ use valence_core::protocol::Decode;
 use valence_core::text::Text;
 use valence_core::{ident, translation_key, MINECRAFT_VERSION, PROTOCOL_VERSION};
 
+use crate::driver::Stream;
 use crate::packet::{
     HandshakeC2s, HandshakeNextState, LoginCompressionS2c, LoginDisconnectS2c, LoginHelloC2s,
     LoginHelloS2c, LoginKeyC2s, LoginQueryRequestS2c, LoginQueryResponseC2s, LoginSuccessS2c,
@@ -67,7 +68,11 @@ pub(super) async fn do_accept_loop(shared: SharedNetworkState) {
     }
 }
 
-async fn handle_connection(shared: SharedNetworkState, stream: TcpStream, remote_addr: SocketAddr) {
+pub(super) async fn handle_connection(
+    shared: SharedNetworkState,
+    stream: impl Stream,
+    remote_addr: SocketAddr,
+) {
     trace!("handling connection");
 
     if let Err(e) = stream.set_nodelay(true) {
@@ -76,7 +81,7 @@ async fn handle_connection(shared: SharedNetworkState, stream: TcpStream, remote
 
     let conn = PacketIo::new(
         stream,
-        PacketEncoder::new(),
+        PacketEncoder::with_pool(shared.0.buffer_pool.clone()),
         PacketDecoder::new(),
         Duration::from_secs(5),
     );
@@ -103,7 +108,7 @@ struct HandshakeData {
 
 async fn handle_handshake(
     shared: SharedNetworkState,
-    mut io: PacketIo,
+    mut io: PacketIo<impl Stream>,
     remote_addr: SocketAddr,
 ) -> anyhow::Result<()> {
     let handshake = io.recv_packet::<HandshakeC2s>().await?;
@@ -149,7 +154,7 @@ async fn handle_handshake(
 
 async fn handle_status(
     shared: SharedNetworkState,
-    mut io: PacketIo,
+    mut io: PacketIo<impl Stream>,
     remote_addr: SocketAddr,
     handshake: HandshakeData,
 ) -> anyhow::Result<()> {
@@ -206,7 +211,7 @@ async fn handle_status(
 /// Handle the login process and return the new client's data if successful.
 async fn handle_login(
     shared: &SharedNetworkState,
-    conn: &mut PacketIo,
+    conn: &mut PacketIo<impl Stream>,
     remote_addr: SocketAddr,
     handshake: HandshakeData,
 ) -> anyhow::Result<Option<(NewClientInfo, CleanupOnDrop)>> {
@@ -233,6 +238,15 @@ async fn handle_login(
         ConnectionMode::Velocity { secret } => login_velocity(conn, username, secret).await?,
     };
 
+    if let Err(reason) = shared.access().check(info.uuid, info.ip) {
+        info!("disconnect at login: \"{reason}\"");
+        conn.send_packet(&LoginDisconnectS2c {
+            reason: reason.into(),
+        })
+        .await?;
+        return Ok(None);
+    }
+
     if let Some(threshold) = shared.0.compression_threshold {
         conn.send_packet(&LoginCompressionS2c {
             threshold: VarInt(threshold as i32),
@@ -267,7 +281,7 @@ async fn handle_login(
 /// Login procedure for online mode.
 async fn login_online(
     shared: &SharedNetworkState,
-    conn: &mut PacketIo,
+    conn: &mut PacketIo<impl Stream>,
     remote_addr: SocketAddr,
     username: String,
 ) -> anyhow::Result<NewClientInfo> {
@@ -429,7 +443,7 @@ fn login_bungeecord(
 
 /// Login procedure for Velocity.
 async fn login_velocity(
-    io: &mut PacketIo,
+    io: &mut PacketIo<impl Stream>,
     username: String,
     velocity_secret: &str,
 ) -> anyhow::Result<NewClientInfo> {
@@ -0,0 +1,169 @@
+//! Abstractions over the I/O driver used for the sockets of connections.
+//!
+//! The handshake and login code is generic over [`Stream`], so connections
+//! can be served by tokio's I/O driver or by another one such as io_uring.
+//! Reads and writes take ownership of their buffers, because completion based
+//! drivers need the buffers to stay alive until the kernel is done with them.
+
+use std::future::Future;
+use std::io;
+use std::io::{ErrorKind, IoSlice};
+use std::pin::Pin;
+
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+use crate::packet_io::{ReadLoop, WriteLoop};
+
+/// The I/O backend used for the sockets of connections. Set with
+/// [`NetworkSettings::io_backend`](crate::NetworkSettings::io_backend).
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+#[non_exhaustive]
+pub enum IoBackend {
+    /// Tokio's default I/O driver, which is based on epoll on Linux.
+    #[default]
+    Tokio,
+    /// io_uring through `tokio-uring`. Every socket operation is submitted to
+    /// a ring instead of being a system call of its own, which reduces the
+    /// overhead of many mostly idle connections.
+    ///
+    /// The connections are served by a dedicated thread running its own
+    /// runtime. Only available on Linux with the `io_uring` feature.
+    #[cfg(all(target_os = "linux", feature = "io_uring"))]
+    IoUring,
+}
+
+/// Reads bytes from a socket.
+pub(crate) trait ReadHalf {
+    type ReadFuture<'a>: Future<Output = (io::Result<usize>, BytesMut)> + 'a
+    where
+        Self: 'a;
+
+    /// Reads bytes into the spare capacity of `buf`, returning the number of
+    /// bytes read and the buffer. Zero bytes are read at EOF.
+    fn read_into(&mut self, buf: BytesMut) -> Self::ReadFuture<'_>;
+}
+
+/// Writes bytes to a socket.
+pub(crate) trait WriteHalf {
+    type WriteFuture<'a>: Future<Output = (io::Result<()>, Vec<BytesMut>)> + 'a
+    where
+        Self: 'a;
+
+    /// Writes all the buffers, with as few operations as possible. The
+    /// buffers are given back so they can be reused.
+    fn write_all_bufs(&mut self, bufs: Vec<BytesMut>) -> Self::WriteFuture<'_>;
+}
+
+/// A connected socket.
+pub(crate) trait Stream: ReadHalf + WriteHalf + Sized + 'static {
+    fn set_nodelay(&self, nodelay: bool) -> io::Result<()>;
+
+    /// Spawns the tasks reading and writing the packets of a client in the
+    /// play state on the runtime of this driver.
+    fn spawn_io_tasks(
+        self,
+        read_loop: ReadLoop,
+        write_loop: WriteLoop,
+    ) -> (JoinHandle<()>, JoinHandle<()>);
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+impl<T: AsyncRead + Unpin + Send> ReadHalf for T {
+    type ReadFuture<'a> = BoxFuture<'a, (io::Result<usize>, BytesMut)> where Self: 'a;
+
+    fn read_into(&mut self, mut buf: BytesMut) -> Self::ReadFuture<'_> {
+        Box::pin(async move {
+            let res = self.read_buf(&mut buf).await;
+            (res, buf)
+        })
+    }
+}
+
+impl<T: AsyncWrite + Unpin + Send> WriteHalf for T {
+    type WriteFuture<'a> = BoxFuture<'a, (io::Result<()>, Vec<BytesMut>)> where Self: 'a;
+
+    fn write_all_bufs(&mut self, bufs: Vec<BytesMut>) -> Self::WriteFuture<'_> {
+        Box::pin(async move {
+            let res = write_all_vectored(self, &bufs).await;
+            (res, bufs)
+        })
+    }
+}
+
+impl Stream for TcpStream {
+    fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        TcpStream::set_nodelay(self, nodelay)
+    }
+
+    fn spawn_io_tasks(
+        self,
+        read_loop: ReadLoop,
+        write_loop: WriteLoop,
+    ) -> (JoinHandle<()>, JoinHandle<()>) {
+        let (reader, writer) = self.into_split();
+
+        (
+            tokio::spawn(read_loop.run(reader)),
+            tokio::spawn(write_loop.run(writer)),
+        )
+    }
+}
+
+/// The maximum number of buffers written with a single call to
+/// `write_vectored`.
+pub(crate) const MAX_IO_SLICES: usize = 64;
+
+/// Writes all the buffers to `writer`, with as few system calls as possible.
+async fn write_all_vectored(
+    writer: &mut (impl AsyncWrite + Unpin),
+    chunks: &[BytesMut],
+) -> io::Result<()> {
+    // The first buffer that isn't completely written, and the number of bytes
+    // of it that are written.
+    let mut idx = 0;
+    let mut offset = 0;
+
+    loop {
+        while idx < chunks.len() && offset == chunks[idx].len() {
+            idx += 1;
+            offset = 0;
+        }
+
+        if idx == chunks.len() {
+            return Ok(());
+        }
+
+        let mut slices = [IoSlice::new(&[]); MAX_IO_SLICES];
+        let mut slice_count = 0;
+
+        for (i, (slice, chunk)) in slices.iter_mut().zip(&chunks[idx..]).enumerate() {
+            let start = if i == 0 { offset } else { 0 };
+            *slice = IoSlice::new(&chunk[start..]);
+            slice_count += 1;
+        }
+
+        let mut written = writer.write_vectored(&slices[..slice_count]).await?;
+
+        if written == 0 {
+            return Err(ErrorKind::WriteZero.into());
+        }
+
+        // Skip past the buffers that were written.
+        while written > 0 {
+            let remaining = chunks[idx].len() - offset;
+
+            if written < remaining {
+                offset += written;
+                break;
+            }
+
+            written -= remaining;
+            idx += 1;
+            offset = 0;
+        }
+    }
+}
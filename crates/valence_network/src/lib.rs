@@ -17,21 +17,29 @@
     clippy::dbg_macro
 )]
 
+pub mod access;
 mod byte_channel;
 mod connect;
+mod driver;
 pub mod packet;
 mod packet_io;
+pub mod profile;
+#[cfg(all(target_os = "linux", feature = "io_uring"))]
+mod uring;
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use access::{AccessControl, AccessSettings};
 use anyhow::Context;
 pub use async_trait::async_trait;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use connect::do_accept_loop;
+pub use driver::IoBackend;
 use flume::{Receiver, Sender};
+use profile::ProfileCache;
 use rand::rngs::OsRng;
 use rsa::{PublicKeyParts, RsaPrivateKey};
 use serde::Serialize;
@@ -39,7 +47,10 @@ use tokio::runtime::{Handle, Runtime};
 use tokio::sync::Semaphore;
 use tracing::error;
 use uuid::Uuid;
-use valence_client::{ClientBundle, ClientBundleArgs, Properties, SpawnClientsSet};
+use valence_client::{
+    is_valid_username, ClientBundle, ClientBundleArgs, Properties, SpawnClientsSet,
+};
+use valence_core::protocol::encode::BufferPool;
 use valence_core::text::Text;
 use valence_core::Server;
 
@@ -83,9 +94,12 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         None => settings.tokio_handle.clone().unwrap(),
     };
 
+    let access = AccessControl::new(&settings.access);
+
     let shared = SharedNetworkState(Arc::new(SharedNetworkStateInner {
         callbacks: settings.callbacks.clone(),
         address: settings.address,
+        io_backend: settings.io_backend,
         incoming_byte_limit: settings.incoming_byte_limit,
         outgoing_byte_limit: settings.outgoing_byte_limit,
         connection_sema: Arc::new(Semaphore::new(
@@ -102,16 +116,29 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
         rsa_key,
         public_key_der,
         http_client: reqwest::Client::new(),
+        profile_cache: ProfileCache::default(),
+        access: access.clone(),
+        buffer_pool: BufferPool::new(),
     }));
 
-    app.insert_resource(shared.clone());
+    app.insert_resource(shared.clone()).insert_resource(access);
 
     // System for starting the accept loop.
     let start_accept_loop = move |shared: Res<SharedNetworkState>| {
-        let _guard = shared.0.tokio_handle.enter();
-
-        // Start accepting new connections.
-        tokio::spawn(do_accept_loop(shared.clone()));
+        match shared.0.io_backend {
+            IoBackend::Tokio => {
+                let _guard = shared.0.tokio_handle.enter();
+
+                // Start accepting new connections.
+                tokio::spawn(do_accept_loop(shared.clone()));
+            }
+            #[cfg(all(target_os = "linux", feature = "io_uring"))]
+            IoBackend::IoUring => {
+                if let Err(e) = uring::start_accept_loop(shared.clone()) {
+                    error!("failed to start io_uring thread: {e}");
+                }
+            }
+        }
     };
 
     // System for spawning new clients.
@@ -135,6 +162,9 @@ fn build_plugin(app: &mut App) -> anyhow::Result<()> {
     // Spawn new clients before the event loop starts.
     app.add_system(spawn_new_clients.in_set(SpawnClientsSet));
 
+    profile::build(app);
+    access::build(app);
+
     Ok(())
 }
 
@@ -153,10 +183,21 @@ impl SharedNetworkState {
     pub fn max_players(&self) -> usize {
         self.0.max_players
     }
+
+    /// The [`Handle`] to the tokio runtime used by the server.
+    pub fn tokio_handle(&self) -> &Handle {
+        &self.0.tokio_handle
+    }
+
+    /// The whitelist and ban lists checked when clients log in.
+    pub fn access(&self) -> &AccessControl {
+        &self.0.access
+    }
 }
 struct SharedNetworkStateInner {
     callbacks: ErasedNetworkCallbacks,
     address: SocketAddr,
+    io_backend: IoBackend,
     incoming_byte_limit: usize,
     outgoing_byte_limit: usize,
     /// Limits the number of simultaneous connections to the server before the
@@ -182,6 +223,12 @@ struct SharedNetworkStateInner {
     public_key_der: Box<[u8]>,
     /// For session server requests.
     http_client: reqwest::Client,
+    /// Profiles fetched from Mojang.
+    profile_cache: ProfileCache,
+    /// The whitelist and ban lists.
+    access: AccessControl,
+    /// The buffers packets are encoded into, shared by all clients.
+    buffer_pool: BufferPool,
 }
 
 /// Contains information about a new client joining the server.
@@ -230,6 +277,12 @@ pub struct NetworkSettings {
     ///
     /// `0.0.0.0:25565`, which will listen on every available network interface.
     pub address: SocketAddr,
+    /// The I/O backend used for the sockets of connections. See [`IoBackend`].
+    ///
+    /// # Default Value
+    ///
+    /// [`IoBackend::Tokio`]
+    pub io_backend: IoBackend,
     /// The connection mode. This determines if client authentication and
     /// encryption should take place and if the server should get the player
     /// data from a proxy.
@@ -262,6 +315,12 @@ pub struct NetworkSettings {
     ///
     /// The default value is left unspecified and may change in future versions.
     pub outgoing_byte_limit: usize,
+    /// The whitelist and ban list settings, and where the lists are stored.
+    ///
+    /// # Default Value
+    ///
+    /// The whitelist is disabled and the lists are kept in memory.
+    pub access: AccessSettings,
 }
 
 impl Default for NetworkSettings {
@@ -272,11 +331,13 @@ impl Default for NetworkSettings {
             max_connections: 1024,
             max_players: 20,
             address: SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 25565).into(),
+            io_backend: IoBackend::default(),
             connection_mode: ConnectionMode::Online {
                 prevent_proxy_connections: false,
             },
             incoming_byte_limit: 2097152, // 2 MiB
             outgoing_byte_limit: 8388608, // 8 MiB
+            access: AccessSettings::default(),
         }
     }
 }
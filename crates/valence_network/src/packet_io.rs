@@ -5,22 +5,21 @@ use std::{io, mem};
 
 use anyhow::bail;
 use bytes::BytesMut;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tracing::{debug, warn};
 use valence_client::{ClientBundleArgs, ClientConnection, ReceivedPacket};
 use valence_core::protocol::decode::{PacketDecoder, PacketFrame};
-use valence_core::protocol::encode::PacketEncoder;
+use valence_core::protocol::encode::{BufferPool, PacketEncoder};
 use valence_core::protocol::{Decode, Encode, Packet};
 
-use crate::byte_channel::{byte_channel, ByteSender, TrySendError};
+use crate::byte_channel::{byte_channel, ByteReceiver, ByteSender, TrySendError};
+use crate::driver::{ReadHalf, Stream, WriteHalf};
 use crate::{CleanupOnDrop, NewClientInfo};
 
-pub(crate) struct PacketIo {
-    stream: TcpStream,
+pub(crate) struct PacketIo<S> {
+    stream: S,
     enc: PacketEncoder,
     dec: PacketDecoder,
     frame: PacketFrame,
@@ -29,9 +28,9 @@ pub(crate) struct PacketIo {
 
 const READ_BUF_SIZE: usize = 4096;
 
-impl PacketIo {
+impl<S: Stream> PacketIo<S> {
     pub(crate) fn new(
-        stream: TcpStream,
+        stream: S,
         enc: PacketEncoder,
         dec: PacketDecoder,
         timeout: Duration,
@@ -54,7 +53,15 @@ impl PacketIo {
     {
         self.enc.append_packet(pkt)?;
         let bytes = self.enc.take();
-        timeout(self.timeout, self.stream.write_all(&bytes)).await??;
+        let (res, bufs) = timeout(self.timeout, self.stream.write_all_bufs(vec![bytes])).await?;
+        res?;
+
+        if let Some(pool) = self.enc.pool() {
+            for buf in bufs {
+                pool.put(buf);
+            }
+        }
+
         Ok(())
     }
 
@@ -71,14 +78,16 @@ impl PacketIo {
                 }
 
                 self.dec.reserve(READ_BUF_SIZE);
-                let mut buf = self.dec.take_capacity();
+                let buf = self.dec.take_capacity();
 
-                if self.stream.read_buf(&mut buf).await? == 0 {
+                let (res, buf) = self.stream.read_into(buf).await;
+
+                if res? == 0 {
                     return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
                 }
 
                 // This should always be an O(1) unsplit because we reserved space earlier and
-                // the call to `read_buf` shouldn't have grown the allocation.
+                // the read shouldn't have grown the allocation.
                 self.dec.queue_bytes(buf);
             }
         })
@@ -97,7 +106,7 @@ impl PacketIo {
     }
 
     pub(crate) fn into_client_args(
-        mut self,
+        self,
         info: NewClientInfo,
         incoming_byte_limit: usize,
         outgoing_byte_limit: usize,
@@ -108,109 +117,151 @@ impl PacketIo {
         let incoming_byte_limit = incoming_byte_limit.min(Semaphore::MAX_PERMITS);
 
         let recv_sem = Arc::new(Semaphore::new(incoming_byte_limit));
-        let recv_sem_clone = recv_sem.clone();
 
-        let (mut reader, mut writer) = self.stream.into_split();
+        let (outgoing_sender, outgoing_receiver) = byte_channel(outgoing_byte_limit);
 
-        let reader_task = tokio::spawn(async move {
-            let mut buf = BytesMut::new();
+        let read_loop = ReadLoop {
+            dec: self.dec,
+            incoming_sender,
+            incoming_byte_limit,
+            recv_sem: recv_sem.clone(),
+        };
 
-            loop {
-                let frame = match self.dec.try_next_packet() {
-                    Ok(Some(frame)) => frame,
-                    Ok(None) => {
-                        // Incomplete packet. Need more data.
-
-                        buf.reserve(READ_BUF_SIZE);
-                        match reader.read_buf(&mut buf).await {
-                            Ok(0) => break, // Reader is at EOF.
-                            Ok(_) => {}
-                            Err(e) => {
-                                debug!("error reading data from stream: {e}");
-                                break;
-                            }
-                        }
+        let write_loop = WriteLoop {
+            outgoing_receiver,
+            pool: self.enc.pool().cloned(),
+        };
 
-                        self.dec.queue_bytes(buf.split());
+        let (reader_task, writer_task) = self.stream.spawn_io_tasks(read_loop, write_loop);
 
-                        continue;
-                    }
-                    Err(e) => {
-                        warn!("error decoding packet frame: {e:#}");
-                        break;
-                    }
-                };
+        ClientBundleArgs {
+            username: info.username,
+            uuid: info.uuid,
+            ip: info.ip,
+            properties: info.properties.0,
+            conn: Box::new(RealClientConnection {
+                send: outgoing_sender,
+                recv: incoming_receiver,
+                recv_sem,
+                reader_task,
+                writer_task,
+                _cleanup: cleanup,
+            }),
+            enc: self.enc,
+        }
+    }
+}
 
-                let timestamp = Instant::now();
+/// Reads the packets of a client in the play state and sends them to the
+/// client's connection.
+pub(crate) struct ReadLoop {
+    dec: PacketDecoder,
+    incoming_sender: flume::Sender<ReceivedPacket>,
+    incoming_byte_limit: usize,
+    recv_sem: Arc<Semaphore>,
+}
 
-                // Estimate memory usage of this packet.
-                let cost = mem::size_of::<ReceivedPacket>() + frame.body.len();
+impl ReadLoop {
+    pub(crate) async fn run(mut self, mut reader: impl ReadHalf) {
+        let mut buf = BytesMut::new();
 
-                if cost > incoming_byte_limit {
-                    debug!(
-                        cost,
-                        incoming_byte_limit,
-                        "cost of received packet is greater than the incoming memory limit"
-                    );
-                    // We would never acquire enough permits, so we should exit instead of getting
-                    // stuck.
-                    break;
-                }
+        loop {
+            let frame = match self.dec.try_next_packet() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    // Incomplete packet. Need more data.
 
-                // Wait until there's enough space for this packet.
-                let Ok(permits) = recv_sem.acquire_many(cost as u32).await else {
-                    // Semaphore closed.
-                    break;
-                };
+                    buf.reserve(READ_BUF_SIZE);
 
-                // The permits will be added back on the other side of the channel.
-                permits.forget();
+                    let (res, read_buf) = reader.read_into(buf).await;
+                    buf = read_buf;
 
-                let packet = ReceivedPacket {
-                    timestamp,
-                    id: frame.id,
-                    body: frame.body.freeze(),
-                };
+                    match res {
+                        Ok(0) => break, // Reader is at EOF.
+                        Ok(_) => {}
+                        Err(e) => {
+                            debug!("error reading data from stream: {e}");
+                            break;
+                        }
+                    }
+
+                    self.dec.queue_bytes(buf.split());
 
-                if incoming_sender.try_send(packet).is_err() {
-                    // Channel closed.
+                    continue;
+                }
+                Err(e) => {
+                    warn!("error decoding packet frame: {e:#}");
                     break;
                 }
+            };
+
+            let timestamp = Instant::now();
+
+            // Estimate memory usage of this packet.
+            let cost = mem::size_of::<ReceivedPacket>() + frame.body.len();
+
+            if cost > self.incoming_byte_limit {
+                debug!(
+                    cost,
+                    incoming_byte_limit = self.incoming_byte_limit,
+                    "cost of received packet is greater than the incoming memory limit"
+                );
+                // We would never acquire enough permits, so we should exit instead of getting
+                // stuck.
+                break;
             }
-        });
 
-        let (outgoing_sender, mut outgoing_receiver) = byte_channel(outgoing_byte_limit);
+            // Wait until there's enough space for this packet.
+            let Ok(permits) = self.recv_sem.acquire_many(cost as u32).await else {
+                // Semaphore closed.
+                break;
+            };
 
-        let writer_task = tokio::spawn(async move {
-            loop {
-                let bytes = match outgoing_receiver.recv_async().await {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        debug!("error receiving packet data: {e}");
-                        break;
-                    }
-                };
+            // The permits will be added back on the other side of the channel.
+            permits.forget();
+
+            let packet = ReceivedPacket {
+                timestamp,
+                id: frame.id,
+                body: frame.body.freeze(),
+            };
+
+            if self.incoming_sender.try_send(packet).is_err() {
+                // Channel closed.
+                break;
+            }
+        }
+    }
+}
+
+/// Writes the packets queued in a client's connection to the socket.
+pub(crate) struct WriteLoop {
+    outgoing_receiver: ByteReceiver,
+    pool: Option<BufferPool>,
+}
 
-                if let Err(e) = writer.write_all(&bytes).await {
-                    debug!("error writing data to stream: {e}");
+impl WriteLoop {
+    pub(crate) async fn run(mut self, mut writer: impl WriteHalf) {
+        loop {
+            let chunks = match self.outgoing_receiver.recv_async().await {
+                Ok(chunks) => chunks,
+                Err(e) => {
+                    debug!("error receiving packet data: {e}");
+                    break;
                 }
+            };
+
+            let (res, chunks) = writer.write_all_bufs(chunks).await;
+
+            if let Err(e) = res {
+                debug!("error writing data to stream: {e}");
             }
-        });
 
-        ClientBundleArgs {
-            username: info.username,
-            uuid: info.uuid,
-            ip: info.ip,
-            properties: info.properties.0,
-            conn: Box::new(RealClientConnection {
-                send: outgoing_sender,
-                recv: incoming_receiver,
-                recv_sem: recv_sem_clone,
-                reader_task,
-                writer_task,
-                _cleanup: cleanup,
-            }),
-            enc: self.enc,
+            if let Some(pool) = &self.pool {
+                for chunk in chunks {
+                    pool.put(chunk);
+                }
+            }
         }
     }
 }
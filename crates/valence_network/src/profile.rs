@@ -0,0 +1,323 @@
+//! Fetching game profiles and skins from Mojang.
+//!
+//! Profiles can be fetched asynchronously with
+//! [`SharedNetworkState::fetch_profile`] and
+//! [`SharedNetworkState::fetch_profile_by_username`]. Successful lookups are
+//! cached for an hour, so repeated requests for the same player don't hit
+//! Mojang's rate limits. The cache holds a limited number of players, and the
+//! oldest entries are removed first when it is full.
+//!
+//! From systems, insert a [`FetchSkin`] component on a client or player
+//! entity instead. Once the profile is fetched, the `textures` property is
+//! copied into the entity's [`Properties`] and the component is removed.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context};
+use flume::TryRecvError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tracing::warn;
+use valence_core::property::Property;
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_systems((start_skin_fetches, apply_fetched_skins).chain());
+}
+
+/// A game profile fetched from Mojang's session server.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+pub struct Profile {
+    #[serde(rename = "id")]
+    pub uuid: Uuid,
+    #[serde(rename = "name")]
+    pub username: String,
+    /// The signed properties of the profile. Typically contains a `textures`
+    /// property with the skin and cape of the player.
+    pub properties: Vec<Property>,
+}
+
+impl Profile {
+    /// Finds the property with the name "textures".
+    pub fn textures(&self) -> Option<&Property> {
+        self.properties.iter().find(|prop| prop.name == "textures")
+    }
+}
+
+/// How long a cached lookup is used before it is fetched again. Players can
+/// change their skin, so profiles aren't cached forever.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The most entries in each map of the [`ProfileCache`].
+const MAX_CACHED: usize = 1024;
+
+#[derive(Default)]
+pub(crate) struct ProfileCache {
+    /// Maps lowercase usernames to UUIDs.
+    uuids: Mutex<ExpiringMap<String, Uuid>>,
+    profiles: Mutex<ExpiringMap<Uuid, Profile>>,
+}
+
+/// A map with at most [`MAX_CACHED`] entries, which expire after
+/// [`CACHE_TTL`].
+struct ExpiringMap<K, V> {
+    /// The values, the time they were inserted, and their insertion order.
+    entries: HashMap<K, (V, Instant, u64)>,
+    /// Counts insertions. Many entries can be inserted at the same `Instant`,
+    /// so this decides which entry is the oldest.
+    insertions: u64,
+}
+
+impl<K, V> Default for ExpiringMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            insertions: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ExpiringMap<K, V> {
+    fn get(&self, key: &K) -> Option<V> {
+        let (value, inserted, _) = self.entries.get(key)?;
+        (inserted.elapsed() < CACHE_TTL).then(|| value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() >= MAX_CACHED && !self.entries.contains_key(&key) {
+            self.entries
+                .retain(|_, (_, inserted, _)| inserted.elapsed() < CACHE_TTL);
+
+            if self.entries.len() >= MAX_CACHED {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, (_, _, order))| *order)
+                    .map(|(key, _)| key.clone());
+
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+
+        self.entries
+            .insert(key, (value, Instant::now(), self.insertions));
+        self.insertions += 1;
+    }
+}
+
+impl SharedNetworkState {
+    /// Fetches the game profile of the player with the given UUID, including
+    /// signed textures.
+    ///
+    /// This must be called from within the tokio runtime. See
+    /// [`SharedNetworkState::tokio_handle`].
+    pub async fn fetch_profile(&self, uuid: Uuid) -> anyhow::Result<Profile> {
+        if let Some(profile) = self.0.profile_cache.profiles.lock().unwrap().get(&uuid) {
+            return Ok(profile);
+        }
+
+        let url = format!(
+            "https://sessionserver.mojang.com/session/minecraft/profile/{}?unsigned=false",
+            uuid.simple()
+        );
+
+        let resp = self.0.http_client.get(url).send().await?;
+
+        match resp.status() {
+            StatusCode::OK => {}
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                bail!("no profile exists for UUID {uuid}")
+            }
+            status => bail!("profile GET request failed (status code {status})"),
+        }
+
+        let profile: Profile = resp.json().await.context("parsing game profile")?;
+
+        self.0
+            .profile_cache
+            .profiles
+            .lock()
+            .unwrap()
+            .insert(uuid, profile.clone());
+
+        Ok(profile)
+    }
+
+    /// Fetches the game profile of the player with the given username,
+    /// including signed textures. Usernames are case insensitive.
+    ///
+    /// This must be called from within the tokio runtime. See
+    /// [`SharedNetworkState::tokio_handle`].
+    pub async fn fetch_profile_by_username(&self, username: &str) -> anyhow::Result<Profile> {
+        let key = username.to_ascii_lowercase();
+
+        let cached = self.0.profile_cache.uuids.lock().unwrap().get(&key);
+
+        let uuid = match cached {
+            Some(uuid) => uuid,
+            None => {
+                if !is_valid_username(username) {
+                    bail!("invalid username {username:?}");
+                }
+
+                let url = format!("https://api.mojang.com/users/profiles/minecraft/{username}");
+
+                let resp = self.0.http_client.get(url).send().await?;
+
+                match resp.status() {
+                    StatusCode::OK => {}
+                    StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                        bail!("no profile exists for username {username:?}")
+                    }
+                    status => bail!("UUID GET request failed (status code {status})"),
+                }
+
+                #[derive(Deserialize)]
+                struct UuidResponse {
+                    id: Uuid,
+                }
+
+                let uuid = resp
+                    .json::<UuidResponse>()
+                    .await
+                    .context("parsing UUID response")?
+                    .id;
+
+                self.0.profile_cache.uuids.lock().unwrap().insert(key, uuid);
+
+                uuid
+            }
+        };
+
+        self.fetch_profile(uuid).await
+    }
+}
+
+/// Fetches the skin of a player from Mojang and applies it to the entity this
+/// component is attached to. The entity's [`Properties`] are updated and this
+/// component is removed once the request is complete.
+///
+/// If the request fails, a warning is logged and the properties are left
+/// unchanged.
+#[derive(Component, Debug)]
+pub struct FetchSkin {
+    source: SkinSource,
+    recv: Option<Receiver<anyhow::Result<Profile>>>,
+}
+
+#[derive(Clone, Debug)]
+enum SkinSource {
+    Uuid(Uuid),
+    Username(String),
+}
+
+impl FetchSkin {
+    /// Fetch the skin of the player with the given UUID.
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self {
+            source: SkinSource::Uuid(uuid),
+            recv: None,
+        }
+    }
+
+    /// Fetch the skin of the player with the given username.
+    pub fn from_username(username: impl Into<String>) -> Self {
+        Self {
+            source: SkinSource::Username(username.into()),
+            recv: None,
+        }
+    }
+}
+
+fn start_skin_fetches(
+    mut fetches: Query<&mut FetchSkin, Added<FetchSkin>>,
+    shared: Res<SharedNetworkState>,
+) {
+    for mut fetch in &mut fetches {
+        let (send, recv) = flume::bounded(1);
+        let source = fetch.source.clone();
+        let shared = shared.clone();
+
+        shared.0.tokio_handle.clone().spawn(async move {
+            let res = match source {
+                SkinSource::Uuid(uuid) => shared.fetch_profile(uuid).await,
+                SkinSource::Username(username) => shared.fetch_profile_by_username(&username).await,
+            };
+
+            let _ = send.send(res);
+        });
+
+        fetch.recv = Some(recv);
+    }
+}
+
+fn apply_fetched_skins(
+    mut fetches: Query<(Entity, &FetchSkin, Option<&mut Properties>)>,
+    mut commands: Commands,
+) {
+    for (entity, fetch, props) in &mut fetches {
+        let Some(recv) = &fetch.recv else {
+            continue
+        };
+
+        let res = match recv.try_recv() {
+            Ok(res) => res,
+            Err(TryRecvError::Empty) => continue,
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("skin fetch task panicked")),
+        };
+
+        match res {
+            Ok(profile) => match (props, profile.textures()) {
+                (Some(mut props), Some(textures)) => {
+                    props.set_textures(textures.value.clone(), textures.signature.clone());
+                }
+                (Some(mut props), None) => {
+                    props.remove_textures();
+                }
+                (None, _) => {
+                    commands
+                        .entity(entity)
+                        .insert(Properties(profile.properties));
+                }
+            },
+            Err(e) => warn!("failed to fetch skin for {entity:?}: {e:#}"),
+        }
+
+        commands.entity(entity).remove::<FetchSkin>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiring_map_limits_entries() {
+        let mut map = ExpiringMap::default();
+
+        for i in 0..MAX_CACHED + 10 {
+            map.insert(i, i);
+        }
+
+        assert_eq!(map.entries.len(), MAX_CACHED);
+
+        // The oldest entries were removed first.
+        assert_eq!(map.get(&0), None);
+        assert_eq!(map.get(&9), None);
+        assert_eq!(map.get(&10), Some(10));
+        assert_eq!(map.get(&(MAX_CACHED + 9)), Some(MAX_CACHED + 9));
+
+        // Expired entries are not returned. The clock may have started less than
+        // an hour ago.
+        if let Some(expired) = Instant::now().checked_sub(CACHE_TTL) {
+            map.entries.get_mut(&10).unwrap().1 = expired;
+            assert_eq!(map.get(&10), None);
+        }
+    }
+}
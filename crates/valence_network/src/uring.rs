@@ -0,0 +1,156 @@
+//! The io_uring backend, built on `tokio-uring`.
+
+use std::future::Future;
+use std::io;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use bytes::{Buf, BytesMut};
+use tokio::task::JoinHandle;
+use tokio_uring::net::{TcpListener, TcpStream};
+use tracing::error;
+
+use crate::connect::handle_connection;
+use crate::driver::{ReadHalf, Stream, WriteHalf, MAX_IO_SLICES};
+use crate::packet_io::{ReadLoop, WriteLoop};
+use crate::SharedNetworkState;
+
+/// Starts the thread running the io_uring runtime, which accepts and serves
+/// all connections.
+pub(super) fn start_accept_loop(shared: SharedNetworkState) -> io::Result<()> {
+    std::thread::Builder::new()
+        .name("valence io_uring".into())
+        .spawn(move || tokio_uring::start(do_accept_loop(shared)))?;
+
+    Ok(())
+}
+
+async fn do_accept_loop(shared: SharedNetworkState) {
+    let listener = match TcpListener::bind(shared.0.address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("failed to start TCP listener: {e}");
+            return;
+        }
+    };
+
+    loop {
+        match shared.0.connection_sema.clone().acquire_owned().await {
+            Ok(permit) => match listener.accept().await {
+                Ok((stream, remote_addr)) => {
+                    let shared = shared.clone();
+
+                    tokio_uring::spawn(async move {
+                        handle_connection(shared, UringStream(Rc::new(stream)), remote_addr).await;
+                        drop(permit);
+                    });
+                }
+                Err(e) => {
+                    error!("failed to accept incoming connection: {e}");
+                }
+            },
+            // Closed semaphore indicates server shutdown.
+            Err(_) => return,
+        }
+    }
+}
+
+/// A socket of the io_uring runtime. Operations on the socket don't need
+/// exclusive access, so the read and write halves share it.
+pub(crate) struct UringStream(Rc<TcpStream>);
+
+type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+impl ReadHalf for UringStream {
+    type ReadFuture<'a> = LocalBoxFuture<'a, (io::Result<usize>, BytesMut)>;
+
+    fn read_into(&mut self, buf: BytesMut) -> Self::ReadFuture<'_> {
+        // Reads into the spare capacity of the buffer and advances its length.
+        Box::pin(self.0.read(buf))
+    }
+}
+
+impl WriteHalf for UringStream {
+    type WriteFuture<'a> = LocalBoxFuture<'a, (io::Result<()>, Vec<BytesMut>)>;
+
+    fn write_all_bufs(&mut self, mut bufs: Vec<BytesMut>) -> Self::WriteFuture<'_> {
+        Box::pin(async move {
+            // Advancing past the written bytes takes them out of the capacity
+            // of the buffers, so the capacity is restored before the buffers
+            // are returned to be reused.
+            let capacities: Vec<_> = bufs.iter().map(|buf| buf.capacity()).collect();
+
+            let res = write_all(&self.0, &mut bufs).await;
+
+            for (buf, capacity) in bufs.iter_mut().zip(capacities) {
+                buf.clear();
+                // Reclaims the memory in front of the buffer without
+                // allocating if the buffer is the only one using it.
+                buf.reserve(capacity);
+            }
+
+            (res, bufs)
+        })
+    }
+}
+
+/// Writes all the buffers to `stream`. Written bytes are advanced past, and
+/// the buffers stay in `bufs` in their original order.
+async fn write_all(stream: &TcpStream, bufs: &mut Vec<BytesMut>) -> io::Result<()> {
+    // The first buffer that isn't completely written.
+    let mut idx = 0;
+
+    loop {
+        while idx < bufs.len() && bufs[idx].is_empty() {
+            idx += 1;
+        }
+
+        if idx == bufs.len() {
+            return Ok(());
+        }
+
+        let mut pending = bufs.split_off(idx);
+        let rest = pending.split_off(pending.len().min(MAX_IO_SLICES));
+
+        let (res, pending) = stream.writev(pending).await;
+
+        bufs.extend(pending);
+        bufs.extend(rest);
+
+        let mut written = match res? {
+            0 => return Err(ErrorKind::WriteZero.into()),
+            written => written,
+        };
+
+        // Skip past the bytes that were written.
+        for buf in &mut bufs[idx..] {
+            let n = written.min(buf.len());
+            buf.advance(n);
+            written -= n;
+
+            if written == 0 {
+                break;
+            }
+        }
+    }
+}
+
+impl Stream for UringStream {
+    fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.0.set_nodelay(nodelay)
+    }
+
+    fn spawn_io_tasks(
+        self,
+        read_loop: ReadLoop,
+        write_loop: WriteLoop,
+    ) -> (JoinHandle<()>, JoinHandle<()>) {
+        let reader = UringStream(self.0.clone());
+
+        (
+            tokio_uring::spawn(read_loop.run(reader)),
+            tokio_uring::spawn(write_loop.run(self)),
+        )
+    }
+}
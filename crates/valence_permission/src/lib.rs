@@ -0,0 +1,371 @@
+//! # Permissions
+//! Permission nodes like `valence.command.give`, granted to clients directly
+//! or through groups.
+//!
+//! Every client has a [`Permissions`] component with the nodes it was granted
+//! or denied and the groups it is in. Groups are defined in the
+//! [`PermissionManager`] resource, and can inherit the nodes of other groups.
+//! Nodes ending in `*` are wildcards that match all nodes that start with the
+//! part before the `*`, so `valence.command.*` matches
+//! `valence.command.give`, and `*` matches every node.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_permission::{PermissionManager, Permissions};
+//! fn setup(mut manager: ResMut<PermissionManager>) {
+//!     manager.group_mut("admin").grant("valence.command.*");
+//!     manager
+//!         .group_mut("moderator")
+//!         .grant("valence.command.kick")
+//!         .deny("valence.command.stop");
+//! }
+//!
+//! fn make_admin(mut clients: Query<&mut Permissions>, manager: Res<PermissionManager>) {
+//!     for mut permissions in &mut clients {
+//!         permissions.add_group("admin");
+//!         assert!(manager.has_permission(&permissions, "valence.command.give"));
+//!     }
+//! }
+//! ```
+//!
+//! Whether a client has a node is decided by the [`PermissionProvider`] of
+//! the manager. The default [`GroupPermissionProvider`] uses the nodes and
+//! groups above, and can be replaced to look up permissions elsewhere.
+//!
+//! A [`PermissionsChangedEvent`] is sent for a client when its permissions or
+//! the groups change, so that other features can update the client.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_client::Client;
+
+pub struct PermissionPlugin;
+
+/// The system set where [`PermissionsChangedEvent`]s are sent. Systems that
+/// read the events should run after this set.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct UpdatePermissionsSet;
+
+impl Plugin for PermissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PermissionManager>()
+            .add_event::<PermissionsChangedEvent>()
+            .configure_set(UpdatePermissionsSet.in_base_set(CoreSet::PostUpdate))
+            .add_system(init_permissions.in_base_set(CoreSet::PreUpdate))
+            .add_system(send_permission_change_events.in_set(UpdatePermissionsSet));
+    }
+}
+
+/// The permission nodes granted to or denied for a client, and the groups it
+/// is in. Nodes set here take priority over the nodes of the groups.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Permissions {
+    nodes: BTreeMap<String, bool>,
+    groups: Vec<String>,
+}
+
+impl Permissions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants a node, which may be a wildcard.
+    pub fn grant(&mut self, node: impl Into<String>) -> &mut Self {
+        self.nodes.insert(node.into(), true);
+        self
+    }
+
+    /// Denies a node, which may be a wildcard. Denied nodes are not granted
+    /// by groups.
+    pub fn deny(&mut self, node: impl Into<String>) -> &mut Self {
+        self.nodes.insert(node.into(), false);
+        self
+    }
+
+    /// Removes a node that was granted or denied, returning if it was granted.
+    pub fn unset(&mut self, node: &str) -> Option<bool> {
+        self.nodes.remove(node)
+    }
+
+    /// Adds the holder to a group. Groups added first take priority.
+    pub fn add_group(&mut self, group: impl Into<String>) -> &mut Self {
+        let group = group.into();
+
+        if !self.groups.contains(&group) {
+            self.groups.push(group);
+        }
+
+        self
+    }
+
+    /// Removes the holder from a group, returning if it was in the group.
+    pub fn remove_group(&mut self, group: &str) -> bool {
+        let len = self.groups.len();
+        self.groups.retain(|g| g != group);
+        self.groups.len() != len
+    }
+
+    pub fn in_group(&self, group: &str) -> bool {
+        self.groups.iter().any(|g| g == group)
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    /// The nodes that were granted (`true`) or denied (`false`).
+    pub fn nodes(&self) -> &BTreeMap<String, bool> {
+        &self.nodes
+    }
+
+    /// Returns if the node is granted or denied by these nodes alone, or
+    /// `None` if no node matches it.
+    pub fn lookup(&self, node: &str) -> Option<bool> {
+        lookup(&self.nodes, node)
+    }
+}
+
+/// A named set of permission nodes that can inherit the nodes of other
+/// groups.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct PermissionGroup {
+    nodes: BTreeMap<String, bool>,
+    parents: Vec<String>,
+}
+
+impl PermissionGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grant(&mut self, node: impl Into<String>) -> &mut Self {
+        self.nodes.insert(node.into(), true);
+        self
+    }
+
+    pub fn deny(&mut self, node: impl Into<String>) -> &mut Self {
+        self.nodes.insert(node.into(), false);
+        self
+    }
+
+    pub fn unset(&mut self, node: &str) -> Option<bool> {
+        self.nodes.remove(node)
+    }
+
+    /// Makes the group inherit the nodes of another group. Nodes of the group
+    /// itself take priority over inherited nodes.
+    pub fn inherit(&mut self, parent: impl Into<String>) -> &mut Self {
+        let parent = parent.into();
+
+        if !self.parents.contains(&parent) {
+            self.parents.push(parent);
+        }
+
+        self
+    }
+
+    pub fn parents(&self) -> &[String] {
+        &self.parents
+    }
+
+    pub fn nodes(&self) -> &BTreeMap<String, bool> {
+        &self.nodes
+    }
+
+    /// Returns if the node is granted or denied by the nodes of this group
+    /// alone, or `None` if no node matches it.
+    pub fn lookup(&self, node: &str) -> Option<bool> {
+        lookup(&self.nodes, node)
+    }
+}
+
+/// Finds the most specific node in `nodes` that matches `node`. An exact
+/// match comes first, followed by wildcards from the longest to the shortest.
+fn lookup(nodes: &BTreeMap<String, bool>, node: &str) -> Option<bool> {
+    if let Some(&value) = nodes.get(node) {
+        return Some(value);
+    }
+
+    node.rmatch_indices('.')
+        .map(|(i, _)| i + 1)
+        .chain([0])
+        .find_map(|i| nodes.get(&format!("{}*", &node[..i])).copied())
+}
+
+/// Decides whether the holder of some [`Permissions`] has a permission node.
+pub trait PermissionProvider: Send + Sync + 'static {
+    /// Returns if the holder of `permissions` has `node`. `manager` has the
+    /// groups defined on the server.
+    fn has_permission(
+        &self,
+        permissions: &Permissions,
+        manager: &PermissionManager,
+        node: &str,
+    ) -> bool;
+}
+
+/// The built-in [`PermissionProvider`]. The nodes of the [`Permissions`] are
+/// checked first, followed by the groups in the order they were added, where
+/// each group is checked before the groups it inherits from. The first of
+/// these with a node that matches decides, and permissions that don't match
+/// any node are denied.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct GroupPermissionProvider;
+
+impl PermissionProvider for GroupPermissionProvider {
+    fn has_permission(
+        &self,
+        permissions: &Permissions,
+        manager: &PermissionManager,
+        node: &str,
+    ) -> bool {
+        fn lookup_group(
+            manager: &PermissionManager,
+            group: &str,
+            node: &str,
+            visited: &mut Vec<String>,
+        ) -> Option<bool> {
+            // Groups can inherit from each other in a cycle.
+            if visited.iter().any(|g| g == group) {
+                return None;
+            }
+
+            visited.push(group.to_owned());
+
+            let group = manager.group(group)?;
+
+            group.lookup(node).or_else(|| {
+                group
+                    .parents
+                    .iter()
+                    .find_map(|parent| lookup_group(manager, parent, node, visited))
+            })
+        }
+
+        permissions
+            .lookup(node)
+            .or_else(|| {
+                let mut visited = vec![];
+
+                permissions
+                    .groups
+                    .iter()
+                    .find_map(|group| lookup_group(manager, group, node, &mut visited))
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// The permission groups of the server and the [`PermissionProvider`] that
+/// decides which permissions clients have.
+///
+/// Changing the manager sends a [`PermissionsChangedEvent`] for every client.
+#[derive(Resource)]
+pub struct PermissionManager {
+    groups: BTreeMap<String, PermissionGroup>,
+    provider: Box<dyn PermissionProvider>,
+}
+
+impl PermissionManager {
+    pub fn new(provider: impl PermissionProvider) -> Self {
+        Self {
+            groups: BTreeMap::new(),
+            provider: Box::new(provider),
+        }
+    }
+
+    /// Returns if the holder of `permissions` has `node`, as decided by the
+    /// provider.
+    pub fn has_permission(&self, permissions: &Permissions, node: &str) -> bool {
+        self.provider.has_permission(permissions, self, node)
+    }
+
+    pub fn set_provider(&mut self, provider: impl PermissionProvider) {
+        self.provider = Box::new(provider);
+    }
+
+    pub fn group(&self, name: &str) -> Option<&PermissionGroup> {
+        self.groups.get(name)
+    }
+
+    /// Gets a group, creating it if it doesn't exist.
+    pub fn group_mut(&mut self, name: impl Into<String>) -> &mut PermissionGroup {
+        self.groups.entry(name.into()).or_default()
+    }
+
+    pub fn remove_group(&mut self, name: &str) -> Option<PermissionGroup> {
+        self.groups.remove(name)
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = (&str, &PermissionGroup)> + '_ {
+        self.groups
+            .iter()
+            .map(|(name, group)| (name.as_str(), group))
+    }
+}
+
+impl Default for PermissionManager {
+    fn default() -> Self {
+        Self::new(GroupPermissionProvider)
+    }
+}
+
+impl fmt::Debug for PermissionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermissionManager")
+            .field("groups", &self.groups)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sent when the permissions of a client may have changed, because its
+/// [`Permissions`] or the [`PermissionManager`] changed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PermissionsChangedEvent {
+    pub client: Entity,
+}
+
+fn init_permissions(
+    clients: Query<Entity, (Added<Client>, Without<Permissions>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(Permissions::default());
+    }
+}
+
+fn send_permission_change_events(
+    clients: Query<(Entity, Ref<Permissions>), With<Client>>,
+    manager: Res<PermissionManager>,
+    mut events: EventWriter<PermissionsChangedEvent>,
+) {
+    for (client, permissions) in &clients {
+        // New clients get their permissions when they join.
+        let changed = permissions.is_changed() && !permissions.is_added();
+
+        if changed || (manager.is_changed() && !manager.is_added()) {
+            events.send(PermissionsChangedEvent { client });
+        }
+    }
+}
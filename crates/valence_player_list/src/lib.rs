@@ -18,13 +18,16 @@
 )]
 #![allow(clippy::type_complexity)]
 
+pub mod npc;
 pub mod packet;
+pub mod tab_list;
 
 use std::borrow::Cow;
 
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use packet::{PlayerListActions, PlayerListHeaderS2c, PlayerListS2c};
+use tab_list::TabList;
 use uuid::Uuid;
 use valence_client::{Client, Ping, Properties, Username};
 use valence_core::despawn::Despawned;
@@ -65,6 +68,9 @@ impl Plugin for PlayerListPlugin {
                     // Needs to happen before player entities are initialized. Otherwise, they will appear invisible.
                     .before(WriteUpdatePacketsToInstancesSet),
             );
+
+        npc::build(app);
+        tab_list::build(app);
     }
 }
 
@@ -75,6 +81,8 @@ pub struct PlayerList {
     header: Text,
     footer: Text,
     changed_header_or_footer: bool,
+    /// If the header and footer were sent to all clients this tick.
+    sent_header_or_footer: bool,
     /// If clients should be automatically added and removed from the player
     /// list with the proper components inserted. Enabled by default.
     pub manage_clients: bool,
@@ -88,6 +96,7 @@ impl PlayerList {
             header: Text::default(),
             footer: Text::default(),
             changed_header_or_footer: false,
+            sent_header_or_footer: false,
             manage_clients: true,
         }
     }
@@ -159,9 +168,11 @@ impl Default for Listed {
 }
 
 fn update_header_footer(player_list: ResMut<PlayerList>, server: Res<Server>) {
-    if player_list.changed_header_or_footer {
-        let player_list = player_list.into_inner();
+    let player_list = player_list.into_inner();
 
+    player_list.sent_header_or_footer = player_list.changed_header_or_footer;
+
+    if player_list.changed_header_or_footer {
         let mut w = PacketWriter::new(
             &mut player_list.cached_update_packets,
             server.compression_threshold(),
@@ -188,6 +199,7 @@ fn add_new_clients_to_player_list(
                 PlayerListEntry,
                 DisplayName::default(),
                 Listed::default(),
+                TabList::default(),
             ));
         }
     }
@@ -0,0 +1,104 @@
+//! Fake players, also known as NPCs.
+//!
+//! An NPC is a player entity that isn't controlled by a client. For clients to
+//! render a player entity, the entity's UUID must be in their player list. The
+//! [`NpcBundle`] takes care of this by combining a player entity with an
+//! unlisted player list entry, so the NPC's skin is shown without the NPC
+//! appearing in the tab list.
+//!
+//! NPCs are removed by inserting the [`Despawned`] component, which removes
+//! both the entity and the player list entry.
+//!
+//! When a client clicks on an NPC, an [`NpcInteractEvent`] is sent.
+//!
+//! [`Despawned`]: valence_core::despawn::Despawned
+
+use valence_client::event_loop::RunEventLoopSet;
+use valence_client::interact_entity::{EntityInteraction, InteractEntityEvent};
+use valence_entity::player::{PlayerEntityBundle, PlayerModelParts};
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_event::<NpcInteractEvent>().add_system(
+        send_npc_interact_events
+            .in_base_set(CoreSet::PreUpdate)
+            .after(RunEventLoopSet),
+    );
+}
+
+/// Bundle for spawning NPCs. The unique ID of the NPC is stored in the player
+/// entity bundle.
+///
+/// The player entity's [`Location`] and [`Position`] must be set for the NPC
+/// to be visible.
+///
+/// [`Location`]: valence_entity::Location
+/// [`Position`]: valence_entity::Position
+#[derive(Bundle, Debug)]
+pub struct NpcBundle {
+    pub npc: Npc,
+    pub player: PlayerEntityBundle,
+    pub player_list_entry: PlayerListEntry,
+    pub username: Username,
+    /// Contains the skin of the NPC in the `textures` property.
+    pub properties: Properties,
+    pub game_mode: GameMode,
+    pub ping: Ping,
+    pub display_name: DisplayName,
+    /// Whether the NPC is shown in the tab list. Defaults to `false`.
+    pub listed: Listed,
+}
+
+impl Default for NpcBundle {
+    fn default() -> Self {
+        Self {
+            npc: Npc,
+            player: PlayerEntityBundle {
+                // Show all skin layers.
+                player_player_model_parts: PlayerModelParts(0x7f),
+                ..Default::default()
+            },
+            player_list_entry: PlayerListEntry,
+            username: Username::default(),
+            properties: Properties::default(),
+            game_mode: GameMode::default(),
+            ping: Ping::default(),
+            display_name: DisplayName::default(),
+            listed: Listed(false),
+        }
+    }
+}
+
+/// Marker component for NPCs.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct Npc;
+
+/// Sent when a client attacks or interacts with an NPC.
+#[derive(Copy, Clone, Debug)]
+pub struct NpcInteractEvent {
+    pub client: Entity,
+    /// The NPC being interacted with.
+    pub npc: Entity,
+    /// If the client was sneaking during the interaction.
+    pub sneaking: bool,
+    /// The kind of interaction that occurred.
+    pub interact: EntityInteraction,
+}
+
+fn send_npc_interact_events(
+    mut interactions: EventReader<InteractEntityEvent>,
+    npcs: Query<(), With<Npc>>,
+    mut events: EventWriter<NpcInteractEvent>,
+) {
+    for event in interactions.iter() {
+        if npcs.contains(event.entity) {
+            events.send(NpcInteractEvent {
+                client: event.client,
+                npc: event.entity,
+                sneaking: event.sneaking,
+                interact: event.interact,
+            });
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Per-client customization of the tab list.
+//!
+//! The [`PlayerList`] resource and the components of player list entries
+//! decide what all clients see. The [`TabList`] component of a client
+//! overrides this for that client alone. With it, you can:
+//!
+//! - Change the header and footer.
+//! - Hide entries, including the entries of real players.
+//! - Change the display names of entries.
+//! - Show entries in a custom order.
+//!
+//! Hidden entries are unlisted rather than removed, so the player entities of
+//! hidden entries are still rendered. Players in the [`HiddenEntities`] of a
+//! client are hidden from its tab list as well.
+//!
+//! Fake entries are spawned like any other entry with a
+//! [`PlayerListEntryBundle`]. Latency is shown with the [`Ping`] component of
+//! an entry, which is kept up to date for clients.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_client::Username;
+//! # use valence_player_list::tab_list::TabList;
+//! # use valence_player_list::{PlayerListEntry, PlayerListEntryBundle};
+//! fn spawn_fake_entry(mut commands: Commands) {
+//!     commands.spawn(PlayerListEntryBundle {
+//!         username: Username("Fake".into()),
+//!         ..Default::default()
+//!     });
+//! }
+//!
+//! fn sort_by_name_descending(
+//!     mut tab_lists: Query<&mut TabList>,
+//!     entries: Query<(Entity, &Username), With<PlayerListEntry>>,
+//! ) {
+//!     let mut order: Vec<_> = entries.iter().collect();
+//!     order.sort_by(|a, b| b.1 .0.cmp(&a.1 .0));
+//!
+//!     for mut tab_list in &mut tab_lists {
+//!         tab_list.set_order(order.iter().map(|(entity, _)| *entity));
+//!     }
+//! }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use valence_client::spectator::HiddenEntities;
+use valence_scoreboard::packet::{
+    CollisionRule, Mode, NameTagVisibility, TeamColor, TeamFlags, TeamS2c,
+};
+
+use super::*;
+
+pub(super) fn build(app: &mut App) {
+    app.add_systems(
+        (remove_despawned_from_tab_lists, update_tab_lists)
+            .chain()
+            .in_set(PlayerListSet)
+            // Overrides are sent after the changes sent to all clients.
+            .after(write_player_list_changes),
+    );
+}
+
+/// The tab list of a client. Automatically inserted into clients when
+/// [`PlayerList::manage_clients`] is enabled.
+#[derive(Component, Default, Debug)]
+pub struct TabList {
+    header: Option<Text>,
+    footer: Option<Text>,
+    changed_header_or_footer: bool,
+    hidden: BTreeSet<Entity>,
+    display_names: BTreeMap<Entity, Text>,
+    order: Vec<Entity>,
+    /// The entries hidden from the client, including its [`HiddenEntities`].
+    sent_hidden: BTreeSet<Entity>,
+    sent_display_names: BTreeMap<Entity, Text>,
+    /// The username in each ordering team sent to the client.
+    sent_order: Vec<String>,
+}
+
+impl TabList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The header of this client, or `None` if the header of the
+    /// [`PlayerList`] is used.
+    pub fn header(&self) -> Option<&Text> {
+        self.header.as_ref()
+    }
+
+    /// The footer of this client, or `None` if the footer of the
+    /// [`PlayerList`] is used.
+    pub fn footer(&self) -> Option<&Text> {
+        self.footer.as_ref()
+    }
+
+    pub fn set_header(&mut self, txt: impl Into<Text>) {
+        let txt = Some(txt.into());
+
+        if txt != self.header {
+            self.changed_header_or_footer = true;
+        }
+
+        self.header = txt;
+    }
+
+    pub fn set_footer(&mut self, txt: impl Into<Text>) {
+        let txt = Some(txt.into());
+
+        if txt != self.footer {
+            self.changed_header_or_footer = true;
+        }
+
+        self.footer = txt;
+    }
+
+    /// Goes back to the header of the [`PlayerList`].
+    pub fn reset_header(&mut self) {
+        if self.header.take().is_some() {
+            self.changed_header_or_footer = true;
+        }
+    }
+
+    /// Goes back to the footer of the [`PlayerList`].
+    pub fn reset_footer(&mut self) {
+        if self.footer.take().is_some() {
+            self.changed_header_or_footer = true;
+        }
+    }
+
+    /// Hides a player list entry from this client. Returns `false` if the entry
+    /// was already hidden.
+    pub fn hide(&mut self, entry: Entity) -> bool {
+        self.hidden.insert(entry)
+    }
+
+    /// Stops hiding a player list entry. The entry is shown again if it is
+    /// [`Listed`]. Returns `false` if the entry wasn't hidden.
+    pub fn show(&mut self, entry: Entity) -> bool {
+        self.hidden.remove(&entry)
+    }
+
+    pub fn is_hidden(&self, entry: Entity) -> bool {
+        self.hidden.contains(&entry)
+    }
+
+    /// The display name of an entry for this client, or `None` if the entry's
+    /// [`DisplayName`] is used.
+    pub fn display_name(&self, entry: Entity) -> Option<&Text> {
+        self.display_names.get(&entry)
+    }
+
+    pub fn set_display_name(&mut self, entry: Entity, name: impl Into<Text>) {
+        self.display_names.insert(entry, name.into());
+    }
+
+    /// Goes back to the [`DisplayName`] of an entry.
+    pub fn reset_display_name(&mut self, entry: Entity) {
+        self.display_names.remove(&entry);
+    }
+
+    /// The entries shown in a custom order.
+    pub fn order(&self) -> &[Entity] {
+        &self.order
+    }
+
+    /// Shows entries in the given order. Entries that aren't in the order are
+    /// shown before them, sorted by name. Spectators are always shown last.
+    ///
+    /// The order is made with scoreboard teams, one for every entry. Entries
+    /// that are in the order must not be on other teams, or the client will be
+    /// disconnected.
+    pub fn set_order(&mut self, entries: impl IntoIterator<Item = Entity>) {
+        let mut seen = BTreeSet::new();
+
+        self.order.clear();
+        self.order
+            .extend(entries.into_iter().filter(|&entry| seen.insert(entry)));
+    }
+}
+
+fn remove_despawned_from_tab_lists(
+    entries: Query<Entity, (Added<Despawned>, With<PlayerListEntry>)>,
+    mut tab_lists: Query<&mut TabList>,
+) {
+    for entry in &entries {
+        for mut tab_list in &mut tab_lists {
+            let tab_list_ref = tab_list.bypass_change_detection();
+
+            // The client already removed the entry.
+            tab_list_ref.hidden.remove(&entry);
+            tab_list_ref.sent_hidden.remove(&entry);
+            tab_list_ref.display_names.remove(&entry);
+            tab_list_ref.sent_display_names.remove(&entry);
+
+            if tab_list_ref.order.contains(&entry) {
+                tab_list_ref.order.retain(|&e| e != entry);
+                tab_list.set_changed();
+            }
+        }
+    }
+}
+
+fn update_tab_lists(
+    mut clients: Query<(&mut Client, &mut TabList, Ref<HiddenEntities>)>,
+    entries: Query<
+        (&UniqueId, &Username, &DisplayName, &Listed),
+        (With<PlayerListEntry>, Without<Despawned>),
+    >,
+    changed_entries: Query<
+        Entity,
+        (
+            With<PlayerListEntry>,
+            Without<Despawned>,
+            Or<(
+                Changed<UniqueId>,
+                Changed<Username>,
+                Changed<Properties>,
+                Changed<DisplayName>,
+                Changed<Listed>,
+            )>,
+        ),
+    >,
+    player_list: Res<PlayerList>,
+) {
+    // Entries updated for all clients, which may have overwritten the overrides.
+    let changed_entries: Vec<_> = changed_entries.iter().collect();
+
+    for (mut client, mut tab_list, hidden_entities) in &mut clients {
+        let tab_list_changed = tab_list.is_changed();
+        let tab_list_added = tab_list.is_added() || client.is_added();
+        let tab_list = tab_list.bypass_change_detection();
+
+        let overrides_header_or_footer = tab_list.header.is_some() || tab_list.footer.is_some();
+
+        if tab_list.changed_header_or_footer
+            || (overrides_header_or_footer && (tab_list_added || player_list.sent_header_or_footer))
+        {
+            client.write_packet(&PlayerListHeaderS2c {
+                header: Cow::Borrowed(tab_list.header.as_ref().unwrap_or(&player_list.header)),
+                footer: Cow::Borrowed(tab_list.footer.as_ref().unwrap_or(&player_list.footer)),
+            });
+
+            tab_list.changed_header_or_footer = false;
+        }
+
+        let mut updates = BTreeMap::new();
+
+        if tab_list_changed || hidden_entities.is_changed() {
+            let hidden: BTreeSet<_> = tab_list
+                .hidden
+                .iter()
+                .copied()
+                .chain(hidden_entities.iter())
+                .collect();
+
+            for &entry in hidden.symmetric_difference(&tab_list.sent_hidden) {
+                updates
+                    .entry(entry)
+                    .or_insert_with(PlayerListActions::new)
+                    .set_update_listed(true);
+            }
+
+            tab_list.sent_hidden = hidden;
+        }
+
+        if tab_list_changed {
+            for (&entry, name) in &tab_list.display_names {
+                if tab_list.sent_display_names.get(&entry) != Some(name) {
+                    updates
+                        .entry(entry)
+                        .or_insert_with(PlayerListActions::new)
+                        .set_update_display_name(true);
+                }
+            }
+
+            for &entry in tab_list.sent_display_names.keys() {
+                if !tab_list.display_names.contains_key(&entry) {
+                    updates
+                        .entry(entry)
+                        .or_insert_with(PlayerListActions::new)
+                        .set_update_display_name(true);
+                }
+            }
+
+            tab_list
+                .sent_display_names
+                .clone_from(&tab_list.display_names);
+        }
+
+        for &entry in &changed_entries {
+            if tab_list.sent_hidden.contains(&entry) {
+                updates
+                    .entry(entry)
+                    .or_insert_with(PlayerListActions::new)
+                    .set_update_listed(true);
+            }
+
+            if tab_list.display_names.contains_key(&entry) {
+                updates
+                    .entry(entry)
+                    .or_insert_with(PlayerListActions::new)
+                    .set_update_display_name(true);
+            }
+        }
+
+        for (entry, actions) in updates {
+            let Ok((uuid, username, display_name, listed)) = entries.get(entry) else {
+                continue
+            };
+
+            let display_name = tab_list
+                .display_names
+                .get(&entry)
+                .or(display_name.0.as_ref());
+
+            client.write_packet(&PlayerListS2c {
+                actions,
+                entries: Cow::Borrowed(&[packet::PlayerListEntry {
+                    player_uuid: uuid.0,
+                    username: &username.0,
+                    listed: listed.0 && !tab_list.sent_hidden.contains(&entry),
+                    display_name: display_name.map(Cow::Borrowed),
+                    ..Default::default()
+                }]),
+            });
+        }
+
+        if tab_list_changed
+            || changed_entries
+                .iter()
+                .any(|entry| tab_list.order.contains(entry))
+        {
+            let mut seen = BTreeSet::new();
+
+            let order: Vec<&str> = tab_list
+                .order
+                .iter()
+                .filter_map(|&entry| entries.get(entry).ok())
+                .map(|(_, username, _, _)| username.0.as_str())
+                // A name can only be on one team.
+                .filter(|&name| seen.insert(name))
+                .collect();
+
+            write_order(&mut client, &mut tab_list.sent_order, &order);
+        }
+    }
+}
+
+/// Moves the names into the ordering teams of their index.
+fn write_order(client: &mut Client, sent: &mut Vec<String>, order: &[&str]) {
+    // Names are removed from their old teams before any are added, since the
+    // client disconnects when removing a name from a team it isn't on.
+    for (i, old_name) in sent.iter().enumerate() {
+        if order.get(i) == Some(&old_name.as_str()) {
+            continue;
+        }
+
+        let mode = if i < order.len() {
+            Mode::RemoveEntities {
+                entities: vec![old_name],
+            }
+        } else {
+            Mode::RemoveTeam
+        };
+
+        client.write_packet(&TeamS2c {
+            team_name: &order_team_name(i),
+            mode,
+        });
+    }
+
+    for (i, &name) in order.iter().enumerate() {
+        let mode = match sent.get(i) {
+            Some(old_name) if old_name == name => continue,
+            Some(_) => Mode::AddEntities {
+                entities: vec![name],
+            },
+            None => Mode::CreateTeam {
+                team_display_name: Cow::Owned(Text::default()),
+                friendly_flags: TeamFlags::new().with_friendly_fire(true),
+                name_tag_visibility: NameTagVisibility::Always,
+                collision_rule: CollisionRule::Always,
+                team_color: TeamColor::Reset,
+                team_prefix: Cow::Owned(Text::default()),
+                team_suffix: Cow::Owned(Text::default()),
+                entities: vec![name],
+            },
+        };
+
+        client.write_packet(&TeamS2c {
+            team_name: &order_team_name(i),
+            mode,
+        });
+    }
+
+    *sent = order.iter().map(|&name| name.to_owned()).collect();
+}
+
+/// The client sorts entries by the names of their teams.
+fn order_team_name(index: usize) -> String {
+    format!("tab_list_{index:05}")
+}
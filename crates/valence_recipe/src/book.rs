@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::*;
+use valence_client::event_loop::PacketEvent;
+use valence_client::Client;
+use valence_core::ident::Ident;
+use valence_core::protocol::encode::WritePacket;
+use valence_inventory::packet::synchronize_recipes::SynchronizeRecipesS2c;
+use valence_inventory::packet::{
+    RecipeBookDataC2s, RecipeBookId, RecipeCategoryOptionsC2s, UnlockRecipesS2c,
+    UpdateRecipeBookAction,
+};
+
+use crate::Recipes;
+
+/// The recipes a client has unlocked and the state of its recipe book.
+/// Clients are given this component when they join.
+///
+/// Only unlocked recipes are shown in the recipe book. Crafting grids accept
+/// all [`Recipes`], unlocked or not.
+#[derive(Component, Default, Debug)]
+pub struct RecipeBook {
+    unlocked: BTreeSet<Ident<String>>,
+    /// Unlocked recipes the client hasn't looked at in the recipe book yet.
+    highlighted: BTreeSet<Ident<String>>,
+    /// Recipes that have been unlocked since the last update.
+    added: Vec<Ident<String>>,
+    /// Recipes that have been locked since the last update.
+    removed: Vec<Ident<String>>,
+    /// The recipe book settings that were last sent to or received from the
+    /// client.
+    sent_settings: [RecipeBookSettings; 4],
+    pub crafting: RecipeBookSettings,
+    pub furnace: RecipeBookSettings,
+    pub blast_furnace: RecipeBookSettings,
+    pub smoker: RecipeBookSettings,
+}
+
+/// The state of the recipe book in one kind of screen.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct RecipeBookSettings {
+    /// Whether the recipe book is open.
+    pub open: bool,
+    /// Whether only recipes that can be crafted with the items in the
+    /// inventory are shown.
+    pub filter_active: bool,
+}
+
+impl RecipeBook {
+    /// Unlocks a recipe. Returns `false` if it was already unlocked.
+    pub fn unlock(&mut self, recipe: impl Into<Ident<String>>) -> bool {
+        let recipe = recipe.into();
+
+        if !self.unlocked.insert(recipe.clone()) {
+            return false;
+        }
+
+        self.removed.retain(|r| *r != recipe);
+        self.highlighted.insert(recipe.clone());
+        self.added.push(recipe);
+        true
+    }
+
+    /// Locks a recipe. Returns `false` if it wasn't unlocked.
+    pub fn lock(&mut self, recipe: &str) -> bool {
+        let Some(recipe) = self.unlocked.take(recipe) else {
+            return false
+        };
+
+        self.highlighted.remove(&recipe);
+
+        if let Some(idx) = self.added.iter().position(|r| *r == recipe) {
+            self.added.remove(idx);
+        } else {
+            self.removed.push(recipe);
+        }
+
+        true
+    }
+
+    pub fn is_unlocked(&self, recipe: &str) -> bool {
+        self.unlocked.contains(recipe)
+    }
+
+    /// Returns if a recipe is unlocked and the client hasn't looked at it in
+    /// the recipe book yet.
+    pub fn is_highlighted(&self, recipe: &str) -> bool {
+        self.highlighted.contains(recipe)
+    }
+
+    /// Returns the unlocked recipes.
+    pub fn iter(&self) -> impl Iterator<Item = &Ident<String>> + '_ {
+        self.unlocked.iter()
+    }
+
+    fn settings(&self) -> [RecipeBookSettings; 4] {
+        [self.crafting, self.furnace, self.blast_furnace, self.smoker]
+    }
+
+    fn settings_mut(&mut self, book: RecipeBookId) -> &mut RecipeBookSettings {
+        match book {
+            RecipeBookId::Crafting => &mut self.crafting,
+            RecipeBookId::Furnace => &mut self.furnace,
+            RecipeBookId::BlastFurnace => &mut self.blast_furnace,
+            RecipeBookId::Smoker => &mut self.smoker,
+        }
+    }
+
+    fn unlock_packet<'a>(
+        &self,
+        action: UpdateRecipeBookAction<'a>,
+        recipe_ids: Vec<Ident<Cow<'a, str>>>,
+    ) -> UnlockRecipesS2c<'a> {
+        UnlockRecipesS2c {
+            action,
+            crafting_recipe_book_open: self.crafting.open,
+            crafting_recipe_book_filter_active: self.crafting.filter_active,
+            smelting_recipe_book_open: self.furnace.open,
+            smelting_recipe_book_filter_active: self.furnace.filter_active,
+            blast_furnace_recipe_book_open: self.blast_furnace.open,
+            blast_furnace_recipe_book_filter_active: self.blast_furnace.filter_active,
+            smoker_recipe_book_open: self.smoker.open,
+            smoker_recipe_book_filter_active: self.smoker.filter_active,
+            recipe_ids,
+        }
+    }
+}
+
+/// Sent when a recipe is unlocked with [`RecipeBook::unlock`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecipeUnlockedEvent {
+    pub client: Entity,
+    pub recipe: Ident<String>,
+}
+
+pub(crate) fn init_recipe_books(
+    clients: Query<Entity, (Added<Client>, Without<RecipeBook>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(RecipeBook::default());
+    }
+}
+
+pub(crate) fn handle_recipe_book_packets(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut RecipeBook>,
+) {
+    for packet in packets.iter() {
+        let Ok(mut book) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        if let Some(pkt) = packet.decode::<RecipeCategoryOptionsC2s>() {
+            let book = book.bypass_change_detection();
+
+            *book.settings_mut(pkt.book_id) = RecipeBookSettings {
+                open: pkt.book_open,
+                filter_active: pkt.filter_active,
+            };
+            book.sent_settings = book.settings();
+        } else if let Some(pkt) = packet.decode::<RecipeBookDataC2s>() {
+            book.bypass_change_detection()
+                .highlighted
+                .remove(pkt.recipe_id.as_str());
+        }
+    }
+}
+
+/// Sends the recipes to clients that just joined or when the recipes change,
+/// and sends changes to recipe books.
+pub(crate) fn update_recipe_books(
+    recipes: Res<Recipes>,
+    mut clients: Query<(Entity, &mut Client, &mut RecipeBook)>,
+    mut events: EventWriter<RecipeUnlockedEvent>,
+) {
+    let mut sync_packet = None;
+
+    for (entity, mut client, mut book) in &mut clients {
+        let resync = recipes.is_changed() || book.is_added();
+
+        if !resync && !book.is_changed() {
+            continue;
+        }
+
+        let book = book.bypass_change_detection();
+
+        for recipe in &book.added {
+            events.send(RecipeUnlockedEvent {
+                client: entity,
+                recipe: recipe.clone(),
+            });
+        }
+
+        if resync {
+            // The recipe book is sent again after the recipes, which is what vanilla does
+            // when the recipes are reloaded.
+            client.write_packet(sync_packet.get_or_insert_with(|| {
+                SynchronizeRecipesS2c {
+                    recipes: recipes
+                        .iter()
+                        .map(|(id, recipe)| recipe.to_packet(id))
+                        .collect(),
+                }
+            }));
+
+            client.write_packet(
+                &book.unlock_packet(
+                    UpdateRecipeBookAction::Init {
+                        recipe_ids: book
+                            .highlighted
+                            .iter()
+                            .map(|id| id.as_str_ident().into())
+                            .collect(),
+                    },
+                    book.unlocked
+                        .iter()
+                        .map(|id| id.as_str_ident().into())
+                        .collect(),
+                ),
+            );
+        } else {
+            if !book.removed.is_empty() {
+                client.write_packet(
+                    &book.unlock_packet(
+                        UpdateRecipeBookAction::Remove,
+                        book.removed
+                            .iter()
+                            .map(|id| id.as_str_ident().into())
+                            .collect(),
+                    ),
+                );
+            }
+
+            if !book.added.is_empty() || book.sent_settings != book.settings() {
+                client.write_packet(
+                    &book.unlock_packet(
+                        UpdateRecipeBookAction::Add,
+                        book.added
+                            .iter()
+                            .map(|id| id.as_str_ident().into())
+                            .collect(),
+                    ),
+                );
+            }
+        }
+
+        book.added.clear();
+        book.removed.clear();
+        book.sent_settings = book.settings();
+    }
+}
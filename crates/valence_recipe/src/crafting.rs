@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bevy_ecs::prelude::*;
+use tracing::debug;
+use valence_client::event_loop::PacketEvent;
+use valence_client::Client;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::protocol::encode::WritePacket;
+use valence_inventory::packet::{ClickMode, CraftFailedResponseS2c, CraftRequestC2s};
+use valence_inventory::{
+    ClickResultSlot, ClientInventoryState, CursorItem, Inventory, InventoryKind,
+    InventoryWindowMut, OpenInventory,
+};
+
+use crate::{Ingredient, RecipeBook, Recipes};
+
+/// Returns the slots of the crafting grid of an inventory and the width of the
+/// grid. The result slot is always slot 0.
+fn crafting_grid(kind: InventoryKind) -> Option<(Range<u16>, usize)> {
+    match kind {
+        InventoryKind::Player => Some((1..5, 2)),
+        InventoryKind::Crafting => Some((1..10, 3)),
+        _ => None,
+    }
+}
+
+/// The item left in a crafting grid slot after an item in it is used.
+fn remainder(item: ItemKind) -> Option<ItemKind> {
+    match item {
+        ItemKind::WaterBucket
+        | ItemKind::LavaBucket
+        | ItemKind::MilkBucket
+        | ItemKind::PowderSnowBucket => Some(ItemKind::Bucket),
+        ItemKind::HoneyBottle | ItemKind::DragonBreath => Some(ItemKind::GlassBottle),
+        _ => None,
+    }
+}
+
+fn can_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    a.item == b.item && a.nbt == b.nbt
+}
+
+/// Fills the result slot of crafting grids with the result of the matching
+/// recipe.
+pub(crate) fn update_crafting_results(
+    recipes: Res<Recipes>,
+    mut inventories: Query<&mut Inventory>,
+) {
+    for mut inventory in &mut inventories {
+        if !inventory.is_changed() && !recipes.is_changed() {
+            continue;
+        }
+
+        let Some((grid, width)) = crafting_grid(inventory.kind()) else {
+            continue
+        };
+
+        let items: Vec<_> = grid.map(|idx| inventory.slot(idx).cloned()).collect();
+
+        let result = recipes
+            .find_crafting(&items, width)
+            .map(|(_, recipe)| recipe.result().clone());
+
+        if inventory.slot(0) != result.as_ref() {
+            inventory.set_slot(0, result);
+        }
+    }
+}
+
+/// The crafting grid a client is looking at, along with the client's main
+/// inventory.
+struct CraftingWindow<'a> {
+    window: InventoryWindowMut<'a>,
+    grid: Range<u16>,
+    width: usize,
+    /// The slots of the player's main inventory in the window.
+    main: Range<u16>,
+}
+
+impl<'a> CraftingWindow<'a> {
+    fn new(player_inventory: &'a mut Inventory, open_inventory: Option<&'a mut Inventory>) -> Self {
+        let (kind, main_start) = match &open_inventory {
+            Some(inv) => (inv.kind(), inv.slot_count()),
+            None => (InventoryKind::Player, 9),
+        };
+
+        let (grid, width) = crafting_grid(kind).expect("inventory has no crafting grid");
+
+        Self {
+            window: InventoryWindowMut::new(player_inventory, open_inventory),
+            grid,
+            width,
+            main: main_start..main_start + 36,
+        }
+    }
+
+    fn hotbar(&self) -> Range<u16> {
+        self.main.end - 9..self.main.end
+    }
+
+    fn grid_items(&self) -> Vec<Option<ItemStack>> {
+        self.grid
+            .clone()
+            .map(|idx| self.window.slot(idx).cloned())
+            .collect()
+    }
+
+    /// Returns the result of the recipe in the grid.
+    fn result(&self, recipes: &Recipes) -> Option<ItemStack> {
+        recipes
+            .find_crafting(&self.grid_items(), self.width)
+            .map(|(_, recipe)| recipe.result().clone())
+    }
+
+    /// Uses one item in each slot of the grid.
+    fn consume_ingredients(&mut self) {
+        for idx in self.grid.clone() {
+            let Some(mut stack) = self.window.slot(idx).cloned() else {
+                continue
+            };
+
+            let remainder = remainder(stack.item).map(|item| ItemStack::new(item, 1, None));
+
+            if stack.count() > 1 {
+                stack.set_count(stack.count() - 1);
+                self.window.set_slot(idx, stack);
+
+                if let Some(remainder) = remainder {
+                    self.insert(remainder);
+                }
+            } else {
+                self.window.set_slot(idx, remainder);
+            }
+        }
+    }
+
+    /// Returns if the stack fits in the player's main inventory.
+    fn can_insert(&self, stack: &ItemStack) -> bool {
+        let max = stack.item.max_stack() as u32;
+
+        let space: u32 = self
+            .main
+            .clone()
+            .map(|idx| match self.window.slot(idx) {
+                None => max,
+                Some(slot) if can_stack(slot, stack) => max.saturating_sub(slot.count() as u32),
+                Some(_) => 0,
+            })
+            .sum();
+
+        space >= stack.count() as u32
+    }
+
+    /// Puts a stack in the player's main inventory, starting at the end of the
+    /// hotbar like vanilla does. Returns the items that didn't fit.
+    fn insert(&mut self, mut stack: ItemStack) -> Option<ItemStack> {
+        let max = stack.item.max_stack();
+
+        for idx in self.main.clone().rev() {
+            let Some(mut slot) = self.window.slot(idx).cloned() else {
+                continue
+            };
+
+            if can_stack(&slot, &stack) && slot.count() < max {
+                let moved = (max - slot.count()).min(stack.count());
+
+                slot.set_count(slot.count() + moved);
+                self.window.set_slot(idx, slot);
+
+                if moved == stack.count() {
+                    return None;
+                }
+
+                stack.set_count(stack.count() - moved);
+            }
+        }
+
+        for idx in self.main.clone().rev() {
+            if self.window.slot(idx).is_none() {
+                self.window.set_slot(idx, stack);
+                return None;
+            }
+        }
+
+        Some(stack)
+    }
+}
+
+/// Crafts the result of crafting grids when clients take it.
+pub(crate) fn take_crafting_results(
+    mut events: EventReader<ClickResultSlot>,
+    recipes: Res<Recipes>,
+    mut clients: Query<
+        (
+            &mut Inventory,
+            &mut CursorItem,
+            &ClientInventoryState,
+            Option<&OpenInventory>,
+        ),
+        With<Client>,
+    >,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+) {
+    for event in events.iter() {
+        let Ok((mut player_inventory, mut cursor_item, inv_state, open_inventory)) =
+            clients.get_mut(event.client) else {
+            continue
+        };
+
+        let mut open_inventory = match open_inventory {
+            Some(open) => {
+                let Ok(inventory) = inventories.get_mut(open.entity) else {
+                    continue
+                };
+
+                if inventory.kind() != InventoryKind::Crafting
+                    || event.window_id != inv_state.window_id()
+                {
+                    continue;
+                }
+
+                Some(inventory)
+            }
+            None if event.window_id == 0 => None,
+            None => continue,
+        };
+
+        let mut window = CraftingWindow::new(&mut player_inventory, open_inventory.as_deref_mut());
+
+        let Some(result) = window.result(&recipes) else {
+            continue
+        };
+
+        match event.mode {
+            ClickMode::Click => match &mut cursor_item.0 {
+                None => {
+                    window.consume_ingredients();
+                    cursor_item.0 = Some(result);
+                }
+                Some(held)
+                    if can_stack(held, &result)
+                        && held.count() + result.count() <= held.item.max_stack() =>
+                {
+                    window.consume_ingredients();
+                    held.set_count(held.count() + result.count());
+                }
+                Some(_) => {}
+            },
+            ClickMode::ShiftClick => {
+                // Craft as many as possible, as long as the result doesn't change.
+                while window.result(&recipes).as_ref() == Some(&result)
+                    && window.can_insert(&result)
+                {
+                    window.consume_ingredients();
+                    window.insert(result.clone());
+                }
+            }
+            ClickMode::Hotbar => {
+                let hotbar = window.hotbar();
+
+                if let Ok(button) = u16::try_from(event.button) {
+                    let idx = hotbar.start + button;
+
+                    if hotbar.contains(&idx) && window.window.slot(idx).is_none() {
+                        window.consume_ingredients();
+                        window.window.set_slot(idx, result);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Places recipes from the recipe book in crafting grids.
+pub(crate) fn handle_craft_request(
+    mut packets: EventReader<PacketEvent>,
+    recipes: Res<Recipes>,
+    mut clients: Query<(
+        &mut Client,
+        &mut Inventory,
+        &ClientInventoryState,
+        &RecipeBook,
+        Option<&OpenInventory>,
+    )>,
+    mut inventories: Query<&mut Inventory, Without<Client>>,
+) {
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<CraftRequestC2s>() else {
+            continue
+        };
+
+        let Ok((mut client, mut player_inventory, inv_state, book, open_inventory)) =
+            clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let mut open_inventory = match open_inventory {
+            Some(open) => {
+                let Ok(inventory) = inventories.get_mut(open.entity) else {
+                    continue
+                };
+
+                if inventory.kind() != InventoryKind::Crafting
+                    || pkt.window_id != inv_state.window_id() as i8
+                {
+                    continue;
+                }
+
+                Some(inventory)
+            }
+            None if pkt.window_id == 0 => None,
+            None => continue,
+        };
+
+        if !book.is_unlocked(pkt.recipe.as_str()) {
+            debug!(
+                "client {:?} tried to place recipe {} which it hasn't unlocked",
+                packet.client, pkt.recipe
+            );
+            continue;
+        }
+
+        let Some(recipe) = recipes.get(pkt.recipe.as_str()) else {
+            continue
+        };
+
+        let mut window = CraftingWindow::new(&mut player_inventory, open_inventory.as_deref_mut());
+
+        let width = window.width;
+        let Some(layout) = recipe.grid_layout(width, window.grid.len() / width) else {
+            continue
+        };
+
+        // Clicking a recipe again adds another set of ingredients to the grid.
+        let grid_items = window.grid_items();
+        let placed = if recipe.matches(&grid_items, width) {
+            grid_items.iter().flatten().map(|stack| stack.count()).min()
+        } else {
+            None
+        };
+
+        // Move the items in the grid to the player's inventory.
+        let mut cleared = true;
+        for idx in window.grid.clone() {
+            if let Some(stack) = window.window.replace_slot(idx, None) {
+                if let Some(rest) = window.insert(stack) {
+                    window.window.set_slot(idx, rest);
+                    cleared = false;
+                }
+            }
+        }
+
+        if !cleared {
+            continue;
+        }
+
+        let mut available = HashMap::<ItemKind, u32>::new();
+        for idx in window.main.clone() {
+            if let Some(stack) = window.window.slot(idx) {
+                if stack.nbt.is_none() {
+                    *available.entry(stack.item).or_default() += stack.count() as u32;
+                }
+            }
+        }
+
+        let target = if pkt.make_all {
+            64
+        } else {
+            placed.map_or(1, |count| count + 1)
+        };
+
+        let Some((times, items)) = (1..=target)
+            .rev()
+            .find_map(|times| Some((times, select_items(&layout, &available, times)?))) else {
+            // Show the recipe in the grid without items.
+            client.write_packet(&CraftFailedResponseS2c {
+                window_id: pkt.window_id as u8,
+                recipe: pkt.recipe,
+            });
+
+            continue;
+        };
+
+        for (idx, item) in window.grid.clone().zip(items) {
+            let Some(item) = item else { continue };
+
+            let mut needed = times;
+
+            for slot_idx in window.main.clone() {
+                let Some(mut stack) = window.window.slot(slot_idx).cloned() else {
+                    continue
+                };
+
+                if stack.item != item || stack.nbt.is_some() {
+                    continue;
+                }
+
+                let taken = stack.count().min(needed);
+                needed -= taken;
+
+                if taken == stack.count() {
+                    window.window.set_slot(slot_idx, None);
+                } else {
+                    stack.set_count(stack.count() - taken);
+                    window.window.set_slot(slot_idx, stack);
+                }
+
+                if needed == 0 {
+                    break;
+                }
+            }
+
+            window
+                .window
+                .set_slot(idx, ItemStack::new(item, times, None));
+        }
+    }
+}
+
+/// Picks an item for each slot of the layout so that the recipe can be placed
+/// `times` times with the available items.
+fn select_items(
+    layout: &[&Ingredient],
+    available: &HashMap<ItemKind, u32>,
+    times: u8,
+) -> Option<Vec<Option<ItemKind>>> {
+    let mut available = available.clone();
+
+    layout
+        .iter()
+        .map(|ingredient| {
+            if ingredient.is_empty() {
+                return Some(None);
+            }
+
+            let item = ingredient.0.iter().copied().find(|item| {
+                item.max_stack() >= times
+                    && available
+                        .get(item)
+                        .is_some_and(|&count| count >= times as u32)
+            })?;
+
+            *available.get_mut(&item).unwrap() -= times as u32;
+
+            Some(Some(item))
+        })
+        .collect()
+}
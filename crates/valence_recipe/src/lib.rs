@@ -0,0 +1,588 @@
+//! # Recipes
+//! Crafting, smelting and other recipes for the vanilla recipe book.
+//!
+//! Recipes are declared in the [`Recipes`] resource, which is sent to clients
+//! when they join and whenever it changes. Clients are given a [`RecipeBook`]
+//! component with the recipes they have unlocked. Unlocked recipes are shown in
+//! the recipe book, from where they can be placed in a crafting grid.
+//!
+//! The result slot of the crafting grid in player inventories and
+//! [`InventoryKind::Crafting`] inventories is filled with the result of the
+//! matching recipe. Taking the result crafts the item on the server and
+//! consumes the ingredients.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_core::ident;
+//! # use valence_core::item::{ItemKind, ItemStack};
+//! # use valence_recipe::{RecipeBook, Recipes, ShapedRecipe};
+//! fn setup(mut recipes: ResMut<Recipes>) {
+//!     recipes.insert(
+//!         ident!("stick"),
+//!         ShapedRecipe::new(
+//!             &["#", "#"],
+//!             [('#', ItemKind::OakPlanks.into())],
+//!             ItemStack::new(ItemKind::Stick, 4, None),
+//!         ),
+//!     );
+//! }
+//!
+//! fn unlock_recipes(mut clients: Query<&mut RecipeBook, Added<RecipeBook>>) {
+//!     for mut book in &mut clients {
+//!         book.unlock(ident!("stick"));
+//!     }
+//! }
+//! ```
+//!
+//! [`InventoryKind::Crafting`]: valence_inventory::InventoryKind::Crafting
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+mod book;
+mod crafting;
+
+use std::collections::BTreeMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use book::{RecipeBook, RecipeBookSettings, RecipeUnlockedEvent};
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
+use valence_client::FlushPacketsSet;
+use valence_core::ident::Ident;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::protocol::var_int::VarInt;
+use valence_inventory::packet::synchronize_recipes;
+pub use valence_inventory::packet::synchronize_recipes::{CraftingCategory, SmeltCategory};
+
+pub struct RecipePlugin;
+
+impl Plugin for RecipePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recipes>()
+            .add_event::<RecipeUnlockedEvent>()
+            .add_system(book::init_recipe_books.in_base_set(CoreSet::PreUpdate))
+            .add_systems(
+                (
+                    book::handle_recipe_book_packets,
+                    crafting::handle_craft_request,
+                )
+                    .in_base_set(EventLoopSet::PreUpdate)
+                    .in_schedule(EventLoopSchedule),
+            )
+            .add_systems(
+                (
+                    crafting::take_crafting_results,
+                    crafting::update_crafting_results.after(crafting::take_crafting_results),
+                )
+                    .in_base_set(CoreSet::Update),
+            )
+            .add_system(
+                book::update_recipe_books
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(FlushPacketsSet),
+            );
+    }
+}
+
+/// All recipes known to the server, by their identifier.
+///
+/// Changing the recipes sends them to all clients again.
+#[derive(Resource, Default, Debug)]
+pub struct Recipes {
+    recipes: BTreeMap<Ident<String>, Recipe>,
+}
+
+impl Recipes {
+    /// Adds a recipe, returning the recipe it replaced.
+    pub fn insert(
+        &mut self,
+        id: impl Into<Ident<String>>,
+        recipe: impl Into<Recipe>,
+    ) -> Option<Recipe> {
+        self.recipes.insert(id.into(), recipe.into())
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Recipe> {
+        self.recipes.remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.recipes.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Ident<String>, &Recipe)> + '_ {
+        self.recipes.iter()
+    }
+
+    /// Returns the crafting recipe that matches the items in a crafting grid.
+    /// The grid is given in row major order with `width` slots per row.
+    pub fn find_crafting(
+        &self,
+        grid: &[Option<ItemStack>],
+        width: usize,
+    ) -> Option<(&Ident<String>, &Recipe)> {
+        if grid.iter().all(Option::is_none) {
+            return None;
+        }
+
+        self.recipes
+            .iter()
+            .find(|(_, recipe)| recipe.matches(grid, width))
+    }
+
+    /// Returns the cooking recipe of the given kind for an item.
+    pub fn find_cooking(
+        &self,
+        kind: CookingKind,
+        item: ItemKind,
+    ) -> Option<(&Ident<String>, &CookingRecipe)> {
+        self.recipes.iter().find_map(|(id, recipe)| match recipe {
+            Recipe::Cooking(recipe)
+                if recipe.kind == kind && recipe.ingredient.0.contains(&item) =>
+            {
+                Some((id, recipe))
+            }
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Recipe {
+    Shaped(ShapedRecipe),
+    Shapeless(ShapelessRecipe),
+    Cooking(CookingRecipe),
+}
+
+impl Recipe {
+    pub fn result(&self) -> &ItemStack {
+        match self {
+            Recipe::Shaped(recipe) => &recipe.result,
+            Recipe::Shapeless(recipe) => &recipe.result,
+            Recipe::Cooking(recipe) => &recipe.result,
+        }
+    }
+
+    /// Returns if this is a crafting recipe that matches the items in a
+    /// crafting grid. The grid is given in row major order with `width` slots
+    /// per row.
+    pub fn matches(&self, grid: &[Option<ItemStack>], width: usize) -> bool {
+        match self {
+            Recipe::Shaped(recipe) => recipe.matches(grid, width),
+            Recipe::Shapeless(recipe) => recipe.matches(grid),
+            Recipe::Cooking(_) => false,
+        }
+    }
+
+    /// Returns the ingredient for each slot of a crafting grid with the given
+    /// dimensions when the recipe is placed in it, or `None` if it doesn't fit.
+    pub(crate) fn grid_layout(&self, width: usize, height: usize) -> Option<Vec<&Ingredient>> {
+        static EMPTY: Ingredient = Ingredient::EMPTY;
+
+        let mut layout = vec![&EMPTY; width * height];
+
+        match self {
+            Recipe::Shaped(recipe) => {
+                if recipe.width > width || recipe.height > height {
+                    return None;
+                }
+
+                for (i, ingredient) in recipe.ingredients.iter().enumerate() {
+                    layout[i / recipe.width * width + i % recipe.width] = ingredient;
+                }
+            }
+            Recipe::Shapeless(recipe) => {
+                if recipe.ingredients.len() > layout.len() {
+                    return None;
+                }
+
+                for (slot, ingredient) in layout.iter_mut().zip(&recipe.ingredients) {
+                    *slot = ingredient;
+                }
+            }
+            Recipe::Cooking(_) => return None,
+        }
+
+        Some(layout)
+    }
+
+    fn to_packet<'a>(&'a self, id: &'a Ident<String>) -> synchronize_recipes::Recipe<'a> {
+        match self {
+            Recipe::Shaped(recipe) => synchronize_recipes::Recipe::CraftingShaped {
+                recipe_id: id.as_str_ident().into(),
+                width: VarInt(recipe.width as i32),
+                height: VarInt(recipe.height as i32),
+                group: &recipe.group,
+                category: recipe.category,
+                ingredients: recipe
+                    .ingredients
+                    .iter()
+                    .map(Ingredient::to_packet)
+                    .collect(),
+                result: Some(recipe.result.clone()),
+                show_notification: recipe.show_notification,
+            },
+            Recipe::Shapeless(recipe) => synchronize_recipes::Recipe::CraftingShapeless {
+                recipe_id: id.as_str_ident().into(),
+                group: &recipe.group,
+                category: recipe.category,
+                ingredients: recipe
+                    .ingredients
+                    .iter()
+                    .map(Ingredient::to_packet)
+                    .collect(),
+                result: Some(recipe.result.clone()),
+            },
+            Recipe::Cooking(recipe) => {
+                let recipe_id = id.as_str_ident().into();
+                let group = recipe.group.as_str();
+                let category = recipe.category;
+                let ingredient = recipe.ingredient.to_packet();
+                let result = Some(recipe.result.clone());
+                let experience = recipe.experience;
+                let cooking_time = VarInt(recipe.cooking_time);
+
+                match recipe.kind {
+                    CookingKind::Smelting => synchronize_recipes::Recipe::Smelting {
+                        recipe_id,
+                        group,
+                        category,
+                        ingredient,
+                        result,
+                        experience,
+                        cooking_time,
+                    },
+                    CookingKind::Blasting => synchronize_recipes::Recipe::Blasting {
+                        recipe_id,
+                        group,
+                        category,
+                        ingredient,
+                        result,
+                        experience,
+                        cooking_time,
+                    },
+                    CookingKind::Smoking => synchronize_recipes::Recipe::Smoking {
+                        recipe_id,
+                        group,
+                        category,
+                        ingredient,
+                        result,
+                        experience,
+                        cooking_time,
+                    },
+                    CookingKind::CampfireCooking => synchronize_recipes::Recipe::CampfireCooking {
+                        recipe_id,
+                        group,
+                        category,
+                        ingredient,
+                        result,
+                        experience,
+                        cooking_time,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl From<ShapedRecipe> for Recipe {
+    fn from(value: ShapedRecipe) -> Self {
+        Self::Shaped(value)
+    }
+}
+
+impl From<ShapelessRecipe> for Recipe {
+    fn from(value: ShapelessRecipe) -> Self {
+        Self::Shapeless(value)
+    }
+}
+
+impl From<CookingRecipe> for Recipe {
+    fn from(value: CookingRecipe) -> Self {
+        Self::Cooking(value)
+    }
+}
+
+/// The items that can be used in a slot of a recipe. An ingredient without
+/// items matches an empty slot.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Ingredient(pub Vec<ItemKind>);
+
+impl Ingredient {
+    pub const EMPTY: Self = Self(vec![]);
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns if the item stack in a slot matches this ingredient. The NBT of
+    /// the stack is ignored.
+    pub fn test(&self, stack: Option<&ItemStack>) -> bool {
+        match stack {
+            Some(stack) => self.0.contains(&stack.item),
+            None => self.is_empty(),
+        }
+    }
+
+    fn to_packet(&self) -> synchronize_recipes::Ingredient {
+        self.0
+            .iter()
+            .map(|&item| Some(ItemStack::new(item, 1, None)))
+            .collect()
+    }
+}
+
+impl From<ItemKind> for Ingredient {
+    fn from(value: ItemKind) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl<const N: usize> From<[ItemKind; N]> for Ingredient {
+    fn from(value: [ItemKind; N]) -> Self {
+        Self(value.into())
+    }
+}
+
+/// A crafting recipe where the ingredients have to be placed in a pattern. The
+/// pattern may be placed anywhere in the grid and may be mirrored
+/// horizontally.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShapedRecipe {
+    pub width: usize,
+    pub height: usize,
+    /// The ingredients of the pattern in row major order.
+    pub ingredients: Vec<Ingredient>,
+    pub result: ItemStack,
+    /// Recipes with the same group are shown together in the recipe book.
+    pub group: String,
+    pub category: CraftingCategory,
+    /// Whether a toast is shown when the recipe is unlocked.
+    pub show_notification: bool,
+}
+
+impl ShapedRecipe {
+    /// Creates a recipe from a pattern in the format of vanilla data packs.
+    /// Each character of the pattern is an ingredient in `key`, and spaces are
+    /// empty slots. Empty rows and columns around the pattern are removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty, larger than 3x3, has rows of different
+    /// lengths or contains a character that is not in `key`.
+    #[track_caller]
+    pub fn new(
+        pattern: &[&str],
+        key: impl IntoIterator<Item = (char, Ingredient)>,
+        result: ItemStack,
+    ) -> Self {
+        let key: BTreeMap<_, _> = key.into_iter().collect();
+
+        let height = pattern.len();
+        let width = pattern.first().map_or(0, |row| row.chars().count());
+
+        assert!(
+            (1..=3).contains(&width) && (1..=3).contains(&height),
+            "pattern must be between 1x1 and 3x3"
+        );
+
+        let mut ingredients = vec![];
+
+        for row in pattern {
+            assert_eq!(
+                row.chars().count(),
+                width,
+                "rows of the pattern must have the same length"
+            );
+
+            for c in row.chars() {
+                ingredients.push(match c {
+                    ' ' => Ingredient::EMPTY,
+                    c => key
+                        .get(&c)
+                        .unwrap_or_else(|| panic!("character '{c}' is not in the key"))
+                        .clone(),
+                });
+            }
+        }
+
+        let (x, y, w, h) = bounds(&ingredients, width, |ing| !ing.is_empty())
+            .expect("pattern must have an ingredient");
+
+        let ingredients = (y..y + h)
+            .flat_map(|row| ingredients[row * width + x..row * width + x + w].to_vec())
+            .collect();
+
+        Self {
+            width: w,
+            height: h,
+            ingredients,
+            result,
+            group: String::new(),
+            category: CraftingCategory::Misc,
+            show_notification: true,
+        }
+    }
+
+    fn matches(&self, grid: &[Option<ItemStack>], width: usize) -> bool {
+        let Some((x, y, w, h)) = bounds(grid, width, Option::is_some) else {
+            return false
+        };
+
+        if w != self.width || h != self.height {
+            return false;
+        }
+
+        let matches_with = |mirrored: bool| {
+            (0..h).all(|dy| {
+                (0..w).all(|dx| {
+                    let ingredient_x = if mirrored { w - 1 - dx } else { dx };
+
+                    self.ingredients[dy * w + ingredient_x]
+                        .test(grid[(y + dy) * width + x + dx].as_ref())
+                })
+            })
+        };
+
+        matches_with(false) || matches_with(true)
+    }
+}
+
+/// A crafting recipe where the ingredients can be placed anywhere in the grid.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShapelessRecipe {
+    /// The ingredients, which must not be empty.
+    pub ingredients: Vec<Ingredient>,
+    pub result: ItemStack,
+    /// Recipes with the same group are shown together in the recipe book.
+    pub group: String,
+    pub category: CraftingCategory,
+}
+
+impl ShapelessRecipe {
+    pub fn new(ingredients: impl IntoIterator<Item = Ingredient>, result: ItemStack) -> Self {
+        Self {
+            ingredients: ingredients.into_iter().collect(),
+            result,
+            group: String::new(),
+            category: CraftingCategory::Misc,
+        }
+    }
+
+    fn matches(&self, grid: &[Option<ItemStack>]) -> bool {
+        let items: Vec<_> = grid.iter().flatten().collect();
+
+        items.len() == self.ingredients.len()
+            && assign_ingredients(&self.ingredients, &items, &mut vec![false; items.len()])
+    }
+}
+
+/// Returns if each ingredient can be matched with a different item.
+fn assign_ingredients(ingredients: &[Ingredient], items: &[&ItemStack], used: &mut [bool]) -> bool {
+    let Some((ingredient, rest)) = ingredients.split_first() else {
+        return true
+    };
+
+    for (i, item) in items.iter().enumerate() {
+        if !used[i] && ingredient.test(Some(item)) {
+            used[i] = true;
+
+            if assign_ingredients(rest, items, used) {
+                return true;
+            }
+
+            used[i] = false;
+        }
+    }
+
+    false
+}
+
+/// Returns the position and size of the smallest rectangle containing all
+/// occupied slots of a grid.
+fn bounds<T>(
+    grid: &[T],
+    width: usize,
+    occupied: impl Fn(&T) -> bool,
+) -> Option<(usize, usize, usize, usize)> {
+    let mut min = (usize::MAX, usize::MAX);
+    let mut max = (0, 0);
+
+    for (i, slot) in grid.iter().enumerate() {
+        if occupied(slot) {
+            let (x, y) = (i % width, i / width);
+
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+
+    (min.0 != usize::MAX).then(|| (min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CookingKind {
+    /// Cooking in a furnace.
+    Smelting,
+    /// Cooking in a blast furnace.
+    Blasting,
+    /// Cooking in a smoker.
+    Smoking,
+    CampfireCooking,
+}
+
+impl CookingKind {
+    /// The vanilla cooking time in ticks.
+    pub const fn default_cooking_time(self) -> i32 {
+        match self {
+            CookingKind::Smelting => 200,
+            CookingKind::Blasting | CookingKind::Smoking => 100,
+            CookingKind::CampfireCooking => 600,
+        }
+    }
+}
+
+/// A recipe for furnaces, blast furnaces, smokers or campfires.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CookingRecipe {
+    pub kind: CookingKind,
+    pub ingredient: Ingredient,
+    pub result: ItemStack,
+    /// The experience gained when taking the result.
+    pub experience: f32,
+    /// The cooking time in ticks.
+    pub cooking_time: i32,
+    /// Recipes with the same group are shown together in the recipe book.
+    pub group: String,
+    pub category: SmeltCategory,
+}
+
+impl CookingRecipe {
+    pub fn new(kind: CookingKind, ingredient: impl Into<Ingredient>, result: ItemStack) -> Self {
+        Self {
+            kind,
+            ingredient: ingredient.into(),
+            result,
+            experience: 0.0,
+            cooking_time: kind.default_cooking_time(),
+            group: String::new(),
+            category: SmeltCategory::Misc,
+        }
+    }
+}
@@ -97,6 +97,11 @@ impl<I: RegistryIdx, V> Registry<I, V> {
         self.items.get_mut(name.as_str())
     }
 
+    /// Returns the item at `idx`, or `None` if it is out of bounds.
+    pub fn get_by_index(&self, idx: I) -> Option<&V> {
+        self.items.get_index(idx.to_index()).map(|(_, v)| v)
+    }
+
     pub fn index_of(&self, name: Ident<&str>) -> Option<I> {
         self.items.get_index_of(name.as_str()).map(I::from_index)
     }
@@ -51,6 +51,126 @@ impl TagsRegistry {
     pub fn sync_tags_packet(&self) -> &Vec<u8> {
         &self.cached_packet
     }
+
+    /// Returns the tag registry with the given name, such as
+    /// `minecraft:block` or `minecraft:item`.
+    pub fn registry(&self, registry: Ident<&str>) -> Option<&Registry> {
+        self.registries
+            .iter()
+            .find(|reg| reg.registry.as_str_ident() == registry)
+    }
+
+    /// Returns the tag registry with the given name mutably.
+    pub fn registry_mut(&mut self, registry: Ident<&str>) -> Option<&mut Registry> {
+        self.registries
+            .iter_mut()
+            .find(|reg| reg.registry.as_str_ident() == registry)
+    }
+
+    /// Returns the tag `name` in the tag registry `registry`.
+    pub fn tag(&self, registry: Ident<&str>, name: Ident<&str>) -> Option<&TagEntry> {
+        self.registry(registry)?.tag(name)
+    }
+
+    /// Adds the raw IDs in `entries` to the tag `name` in the tag registry
+    /// `registry`. The registry and tag are created if they don't already
+    /// exist. IDs already present in the tag are not duplicated.
+    ///
+    /// For instance, adding a block's raw ID to `minecraft:climbable` in
+    /// `minecraft:block` lets clients climb it like a ladder.
+    pub fn insert_entries(
+        &mut self,
+        registry: Ident<&str>,
+        name: Ident<&str>,
+        entries: impl IntoIterator<Item = i32>,
+    ) {
+        let reg = match self
+            .registries
+            .iter()
+            .position(|reg| reg.registry.as_str_ident() == registry)
+        {
+            Some(idx) => &mut self.registries[idx],
+            None => {
+                self.registries.push(Registry {
+                    registry: registry.to_string_ident(),
+                    tags: vec![],
+                });
+                self.registries.last_mut().unwrap()
+            }
+        };
+
+        let tag = match reg
+            .tags
+            .iter()
+            .position(|tag| tag.name.as_str_ident() == name)
+        {
+            Some(idx) => &mut reg.tags[idx],
+            None => {
+                reg.tags.push(TagEntry {
+                    name: name.to_string_ident(),
+                    entries: vec![],
+                });
+                reg.tags.last_mut().unwrap()
+            }
+        };
+
+        for entry in entries {
+            if !tag.entries.contains(&VarInt(entry)) {
+                tag.entries.push(VarInt(entry));
+            }
+        }
+    }
+
+    /// Removes the raw IDs in `entries` from the tag `name` in the tag registry
+    /// `registry`.
+    pub fn remove_entries(
+        &mut self,
+        registry: Ident<&str>,
+        name: Ident<&str>,
+        entries: impl IntoIterator<Item = i32>,
+    ) {
+        if let Some(tag) = self
+            .registry_mut(registry)
+            .and_then(|reg| reg.tag_mut(name))
+        {
+            for entry in entries {
+                tag.entries.retain(|e| e.0 != entry);
+            }
+        }
+    }
+
+    /// Removes the tag `name` from the tag registry `registry`, returning it if
+    /// it existed.
+    pub fn remove_tag(&mut self, registry: Ident<&str>, name: Ident<&str>) -> Option<TagEntry> {
+        let reg = self.registry_mut(registry)?;
+        let idx = reg
+            .tags
+            .iter()
+            .position(|tag| tag.name.as_str_ident() == name)?;
+
+        Some(reg.tags.remove(idx))
+    }
+}
+
+impl Registry {
+    /// Returns the tag with the given name in this registry.
+    pub fn tag(&self, name: Ident<&str>) -> Option<&TagEntry> {
+        self.tags.iter().find(|tag| tag.name.as_str_ident() == name)
+    }
+
+    /// Returns the tag with the given name in this registry mutably.
+    pub fn tag_mut(&mut self, name: Ident<&str>) -> Option<&mut TagEntry> {
+        self.tags
+            .iter_mut()
+            .find(|tag| tag.name.as_str_ident() == name)
+    }
+}
+
+impl TagEntry {
+    /// Returns whether the raw ID `entry` is part of this tag.
+    pub fn contains(&self, entry: i32) -> bool {
+        self.entries.contains(&VarInt(entry))
+    }
 }
 
 pub fn init_tags_registry(mut tags: ResMut<TagsRegistry>) {
@@ -75,6 +195,8 @@ pub(crate) fn cache_tags_packet(server: Res<Server>, tags: ResMut<TagsRegistry>)
 
 #[cfg(test)]
 mod tests {
+    use valence_core::ident;
+
     use super::*;
     use crate::RegistryPlugin;
 
@@ -90,4 +212,36 @@ mod tests {
         assert!(!packet.registries.is_empty());
         assert!(!tags_registry.cached_packet.is_empty());
     }
+
+    #[test]
+    fn user_tags() {
+        let mut app = bevy_app::App::new();
+        app.add_plugin(RegistryPlugin);
+        app.insert_resource(Server::default());
+        app.update();
+
+        let block = ident!("block");
+        let climbable = ident!("climbable");
+        let custom = ident!("my_plugin:custom");
+
+        let mut tags = app.world.resource_mut::<TagsRegistry>();
+        let old_packet = tags.cached_packet.clone();
+
+        assert!(!tags.tag(block, climbable).unwrap().contains(1));
+        tags.insert_entries(block, climbable, [1, 1]);
+        tags.insert_entries(block, custom, [2, 3]);
+        tags.remove_entries(block, custom, [2]);
+
+        let tag = tags.tag(block, climbable).unwrap();
+        assert_eq!(tag.entries.iter().filter(|e| e.0 == 1).count(), 1);
+        assert_eq!(tags.tag(block, custom).unwrap().entries, [VarInt(3)]);
+
+        app.update();
+
+        let mut tags = app.world.resource_mut::<TagsRegistry>();
+        assert_ne!(tags.cached_packet, old_packet);
+
+        assert!(tags.remove_tag(block, custom).is_some());
+        assert!(tags.tag(block, custom).is_none());
+    }
 }
@@ -0,0 +1,501 @@
+//! # Respawn
+//! Death and respawning for clients.
+//!
+//! A client dies when its [`Health`] runs out. Dead clients are shown the
+//! death screen, and a [`PlayerDeathEvent`] is sent. Unless the inventory is
+//! kept, the items and experience of the client are taken away and listed in
+//! the event, so they can be dropped or handed out by the server.
+//!
+//! When a dead client clicks the respawn button, it is moved to its
+//! [`RespawnPoint`] with full health and food, and a [`PlayerRespawnEvent`]
+//! is sent. If its bed or respawn anchor is missing or obstructed, the client
+//! respawns at the world spawn instead, like in vanilla.
+//!
+//! Clients set their respawn point by using beds and respawn anchors, which
+//! can also be charged with glowstone. A [`SleepAttemptEvent`] is sent when a
+//! client uses a bed, and a [`RespawnBlockExplosionEvent`] is sent when a bed
+//! or anchor is used in a dimension where it doesn't work. The [`SpawnPoint`]
+//! query sets the spawn point of clients directly.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_respawn::{PlayerDeathEvent, RespawnPoint};
+//! #[derive(Resource)]
+//! struct Lobby(Entity);
+//!
+//! fn respawn_in_lobby(
+//!     mut events: EventReader<PlayerDeathEvent>,
+//!     mut clients: Query<&mut RespawnPoint>,
+//!     lobby: Res<Lobby>,
+//! ) {
+//!     for event in events.iter() {
+//!         if let Ok(mut point) = clients.get_mut(event.client) {
+//!             *point = RespawnPoint::Custom {
+//!                 instance: lobby.0,
+//!                 position: [0.0, 64.0, 0.0].into(),
+//!                 yaw: 0.0,
+//!                 pitch: 0.0,
+//!             };
+//!         }
+//!     }
+//! }
+//! ```
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::WorldQuery;
+use glam::DVec3;
+use valence_block::{BlockKind, BlockState, PropName, PropValue};
+use valence_client::game_rules::GameRules;
+use valence_client::packet::{GameEventKind, GameStateChangeS2c};
+use valence_client::status::RequestRespawnEvent;
+use valence_client::{
+    Client, CompassAngle, CompassPos, DeathLocation, ResendEntityEvent, Username,
+};
+use valence_core::block_pos::BlockPos;
+use valence_core::item::ItemStack;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::text::Text;
+use valence_entity::attributes::{Attributes, EntityAttribute};
+use valence_entity::effects::ActiveEffects;
+use valence_entity::{Location, Look, Position};
+use valence_experience::Experience;
+use valence_health::{DeathEvent, Exhaustion, Food, Health, HealthSet, Saturation};
+use valence_instance::Instance;
+use valence_inventory::{CursorItem, Inventory};
+
+mod respawn_block;
+
+pub use respawn_block::{RespawnBlockExplosionEvent, SleepAttemptEvent};
+
+pub struct RespawnPlugin;
+
+/// When clients that asked to respawn are moved to their respawn point, in
+/// [`CoreSet::Update`]. Systems that move respawning clients somewhere else
+/// should run _after_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RespawnSet;
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RespawnSettings>()
+            .add_event::<PlayerDeathEvent>()
+            .add_event::<PlayerRespawnEvent>()
+            .configure_set(RespawnSet.in_base_set(CoreSet::Update))
+            .add_system(init_clients.in_base_set(CoreSet::PreUpdate))
+            .add_system(respawn_clients.in_set(RespawnSet))
+            .add_system(
+                handle_deaths
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(HealthSet),
+            );
+
+        respawn_block::build(app);
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Eq, Default, Debug)]
+pub struct RespawnSettings {
+    /// If clients keep their inventory and experience when they die, like the
+    /// `keepInventory` game rule. Can be overridden for an instance with
+    /// [`GameRules::keep_inventory`], and for a client with [`KeepInventory`].
+    pub keep_inventory: bool,
+}
+
+/// Overrides [`RespawnSettings::keep_inventory`] and the game rules of the
+/// instance for a client.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct KeepInventory(pub bool);
+
+/// Where a client respawns after dying. Inserted into clients when they join.
+#[derive(Component, Copy, Clone, PartialEq, Default, Debug)]
+pub enum RespawnPoint {
+    /// The world spawn, which is the [`CompassPos`] of the client in the
+    /// instance it died in. The client faces its [`CompassAngle`].
+    #[default]
+    WorldSpawn,
+    /// Next to a bed. The client respawns at the world spawn if the bed is
+    /// gone or obstructed.
+    Bed { instance: Entity, pos: BlockPos },
+    /// Next to a respawn anchor, using up one of its charges. The client
+    /// respawns at the world spawn if the anchor is gone, obstructed, or out
+    /// of charges.
+    RespawnAnchor { instance: Entity, pos: BlockPos },
+    /// An exact position.
+    Custom {
+        instance: Entity,
+        position: DVec3,
+        yaw: f32,
+        pitch: f32,
+    },
+}
+
+/// A query for setting the spawn point of clients.
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct SpawnPoint {
+    pub compass_pos: &'static mut CompassPos,
+    pub compass_angle: &'static mut CompassAngle,
+    pub respawn_point: &'static mut RespawnPoint,
+}
+
+impl SpawnPointItem<'_> {
+    /// Sets the world spawn of the client to `pos`, facing `angle` in degrees.
+    /// This is where compasses point and where the client respawns, replacing
+    /// its bed or respawn anchor like the `/spawnpoint` command.
+    pub fn set_spawn_point(&mut self, pos: impl Into<BlockPos>, angle: f32) {
+        self.compass_pos.set_if_neq(CompassPos(pos.into()));
+        self.compass_angle.set_if_neq(CompassAngle(angle));
+        self.respawn_point.set_if_neq(RespawnPoint::WorldSpawn);
+    }
+}
+
+/// Marks a client that died and hasn't respawned yet.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Dead;
+
+/// Sent when the health of a client runs out.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerDeathEvent {
+    pub client: Entity,
+    /// The entity responsible for the death, if the client was killed by a
+    /// [`valence_health::DamageEvent`] with an attacker.
+    pub attacker: Option<Entity>,
+    /// The instance the client died in.
+    pub instance: Entity,
+    /// Where the client died.
+    pub position: DVec3,
+    /// The items taken from the inventory of the client. Empty if the
+    /// inventory is kept.
+    pub drops: Vec<ItemStack>,
+    /// The experience points the client would drop in vanilla. Zero if the
+    /// inventory is kept.
+    pub experience: u32,
+}
+
+/// Sent when a dead client respawns.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PlayerRespawnEvent {
+    pub client: Entity,
+    /// The respawn point that was used. This is [`RespawnPoint::WorldSpawn`]
+    /// if the bed or respawn anchor of the client couldn't be used.
+    pub point: RespawnPoint,
+    /// The instance the client respawned in.
+    pub instance: Entity,
+    /// Where the client respawned.
+    pub position: DVec3,
+}
+
+fn init_clients(
+    clients: Query<Entity, (Added<Client>, Without<RespawnPoint>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(RespawnPoint::default());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_deaths(
+    mut clients: Query<
+        (
+            Entity,
+            &mut Client,
+            &Health,
+            &Location,
+            &Position,
+            &mut DeathLocation,
+            Option<&Username>,
+            Option<&KeepInventory>,
+            Option<&mut Inventory>,
+            Option<&mut CursorItem>,
+            Option<&mut Experience>,
+        ),
+        (Changed<Health>, Without<Dead>),
+    >,
+    instances: Query<(&Instance, Option<&GameRules>)>,
+    settings: Res<RespawnSettings>,
+    mut damage_deaths: EventReader<DeathEvent>,
+    mut death_events: EventWriter<PlayerDeathEvent>,
+    mut commands: Commands,
+) {
+    let damage_deaths: Vec<_> = damage_deaths.iter().copied().collect();
+
+    for (
+        entity,
+        mut client,
+        health,
+        loc,
+        pos,
+        mut death_loc,
+        username,
+        keep_inventory,
+        inventory,
+        cursor_item,
+        experience,
+    ) in &mut clients
+    {
+        if health.0 > 0.0 {
+            continue;
+        }
+
+        let damage_death = damage_deaths.iter().find(|death| death.entity == entity);
+
+        // Deaths caused by damage were already shown the death screen with a
+        // death message.
+        if damage_death.is_none() {
+            let name = username.map_or_else(Text::default, |u| Text::text(u.0.clone()));
+            client.kill(Text::translate("death.attack.generic", [name]));
+        }
+
+        let mut keep_rule = settings.keep_inventory;
+
+        if let Ok((instance, rules)) = instances.get(loc.0) {
+            death_loc.0 = Some((instance.dimension_type_name().into(), BlockPos::at(pos.0)));
+
+            if let Some(rules) = rules {
+                keep_rule = rules.keep_inventory;
+            }
+        }
+
+        let mut drops = vec![];
+        let mut dropped_experience = 0;
+
+        if !keep_inventory.map_or(keep_rule, |keep| keep.0) {
+            if let Some(mut inventory) = inventory {
+                // Slot 0 is the crafting result, which isn't a real item.
+                for idx in 1..inventory.slot_count() {
+                    if inventory.slot(idx).is_some() {
+                        drops.extend(inventory.replace_slot(idx, None));
+                    }
+                }
+            }
+
+            if let Some(mut cursor_item) = cursor_item {
+                drops.extend(cursor_item.0.take());
+            }
+
+            if let Some(mut experience) = experience {
+                dropped_experience = (experience.level * 7).min(100);
+                *experience = Experience::default();
+            }
+        }
+
+        commands.entity(entity).insert(Dead);
+
+        death_events.send(PlayerDeathEvent {
+            client: entity,
+            attacker: damage_death.and_then(|death| death.attacker),
+            instance: loc.0,
+            position: pos.0,
+            drops,
+            experience: dropped_experience,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn respawn_clients(
+    mut clients: Query<
+        (
+            &mut Client,
+            &mut Health,
+            &mut Location,
+            &mut Position,
+            &mut Look,
+            &mut RespawnPoint,
+            &CompassPos,
+            &CompassAngle,
+            Option<&Attributes>,
+            Option<&mut ActiveEffects>,
+            Option<(&mut Food, &mut Saturation, &mut Exhaustion)>,
+        ),
+        With<Dead>,
+    >,
+    mut instances: Query<&mut Instance>,
+    mut requests: EventReader<RequestRespawnEvent>,
+    mut respawn_events: EventWriter<PlayerRespawnEvent>,
+    mut resend_events: EventWriter<ResendEntityEvent>,
+    mut commands: Commands,
+) {
+    for request in requests.iter() {
+        let Ok((
+            mut client,
+            mut health,
+            mut loc,
+            mut pos,
+            mut look,
+            mut point,
+            compass_pos,
+            compass_angle,
+            attributes,
+            effects,
+            food,
+        )) = clients.get_mut(request.client) else {
+            continue
+        };
+
+        let (instance, position, yaw, pitch) = match find_respawn_position(*point, &mut instances) {
+            Some(spawn) => spawn,
+            None => {
+                if *point != RespawnPoint::WorldSpawn {
+                    client.write_packet(&GameStateChangeS2c {
+                        kind: GameEventKind::NoRespawnBlockAvailable,
+                        value: 0.0,
+                    });
+
+                    *point = RespawnPoint::WorldSpawn;
+                }
+
+                let spawn = compass_pos.0;
+                let position =
+                    DVec3::new(spawn.x as f64 + 0.5, spawn.y as f64, spawn.z as f64 + 0.5);
+
+                (loc.0, position, compass_angle.0, 0.0)
+            }
+        };
+
+        health.0 = attributes.map_or(EntityAttribute::MaxHealth.default_value(), |a| {
+            a.get(EntityAttribute::MaxHealth)
+        }) as f32;
+
+        if let Some((mut food, mut saturation, mut exhaustion)) = food {
+            *food = Food::default();
+            *saturation = Saturation::default();
+            *exhaustion = Exhaustion::default();
+        }
+
+        if let Some(mut effects) = effects {
+            effects.clear();
+        }
+
+        // The respawn packet is sent when the location changes, so it is sent
+        // even if the client respawns in the same instance.
+        loc.0 = instance;
+        loc.set_changed();
+        pos.0 = position;
+        look.yaw = yaw;
+        look.pitch = pitch;
+
+        commands.entity(request.client).remove::<Dead>();
+
+        // Viewers still see the death animation of the old player entity.
+        resend_events.send(ResendEntityEvent {
+            entity: request.client,
+        });
+
+        respawn_events.send(PlayerRespawnEvent {
+            client: request.client,
+            point: *point,
+            instance,
+            position,
+        });
+    }
+}
+
+/// Returns the instance, position, yaw and pitch to respawn at, or `None` if
+/// the client should respawn at the world spawn.
+fn find_respawn_position(
+    point: RespawnPoint,
+    instances: &mut Query<&mut Instance>,
+) -> Option<(Entity, DVec3, f32, f32)> {
+    match point {
+        RespawnPoint::WorldSpawn => None,
+        RespawnPoint::Bed { instance, pos } => {
+            let inst = instances.get(instance).ok()?;
+
+            if !is_bed(inst.block(pos)?.state()) {
+                return None;
+            }
+
+            let position = find_stand_up_position(inst, pos)?;
+
+            Some((instance, position, 0.0, 0.0))
+        }
+        RespawnPoint::RespawnAnchor { instance, pos } => {
+            let mut inst = instances.get_mut(instance).ok()?;
+
+            let state = inst.block(pos)?.state();
+
+            if state.to_kind() != BlockKind::RespawnAnchor {
+                return None;
+            }
+
+            let charges = state.get(PropName::Charges)?.to_u16()?;
+
+            if charges == 0 {
+                return None;
+            }
+
+            let position = find_stand_up_position(&inst, pos)?;
+
+            inst.set_block(
+                pos,
+                state.set(PropName::Charges, PropValue::from_u16(charges - 1)?),
+            );
+
+            Some((instance, position, 0.0, 0.0))
+        }
+        RespawnPoint::Custom {
+            instance,
+            position,
+            yaw,
+            pitch,
+        } => {
+            instances.get(instance).ok()?;
+
+            Some((instance, position, yaw, pitch))
+        }
+    }
+}
+
+fn is_bed(state: BlockState) -> bool {
+    state.to_kind().to_str().ends_with("_bed")
+}
+
+/// Finds a free spot to stand next to a bed or respawn anchor.
+fn find_stand_up_position(instance: &Instance, pos: BlockPos) -> Option<DVec3> {
+    let is_free = |pos: BlockPos| {
+        instance.block(pos).map_or(false, |block| {
+            block.state().collision_shapes().next().is_none()
+        })
+    };
+
+    for dy in [0, 1] {
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                let feet = BlockPos::new(pos.x + dx, pos.y + dy, pos.z + dz);
+                let head = BlockPos::new(feet.x, feet.y + 1, feet.z);
+                let ground = BlockPos::new(feet.x, feet.y - 1, feet.z);
+
+                if is_free(feet) && is_free(head) && !is_free(ground) {
+                    return Some(DVec3::new(
+                        feet.x as f64 + 0.5,
+                        feet.y as f64,
+                        feet.z as f64 + 0.5,
+                    ));
+                }
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,217 @@
+//! Setting the respawn point with beds and respawn anchors.
+
+use valence_block::BlockKind;
+use valence_client::interact_block::InteractBlockEvent;
+use valence_client::message::SendMessage;
+use valence_core::direction::Direction;
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_core::item::ItemKind;
+use valence_core::translation_key;
+use valence_dimension::DimensionTypeRegistry;
+use valence_entity::entity::Pose;
+use valence_interaction::{distance_to_block, eye_position, InteractionSettings};
+use valence_inventory::{HeldItem, PLAYER_INVENTORY_OFF_HAND_SLOT};
+
+use super::*;
+
+/// The most charges a respawn anchor can hold.
+const MAX_ANCHOR_CHARGES: u16 = 4;
+
+pub(super) fn build(app: &mut App) {
+    app.init_resource::<InteractionSettings>()
+        .add_event::<SleepAttemptEvent>()
+        .add_event::<RespawnBlockExplosionEvent>()
+        .add_system(use_respawn_blocks.in_base_set(CoreSet::Update));
+}
+
+/// Sent when a client uses a bed in a dimension where beds work. The respawn
+/// point of the client is set to the bed before this is sent. Whether the
+/// client falls asleep is left to the server.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SleepAttemptEvent {
+    pub client: Entity,
+    pub instance: Entity,
+    /// The head of the bed.
+    pub position: BlockPos,
+}
+
+/// Sent when a client uses a bed or a charged respawn anchor in a dimension
+/// where it doesn't work, like a bed in the nether. In vanilla the block
+/// explodes, but nothing is changed in the instance by this crate.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RespawnBlockExplosionEvent {
+    pub client: Entity,
+    pub instance: Entity,
+    /// The head of the bed or the respawn anchor.
+    pub position: BlockPos,
+    pub block: BlockKind,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn use_respawn_blocks(
+    mut interactions: EventReader<InteractBlockEvent>,
+    mut clients: Query<(
+        &mut Client,
+        &Location,
+        &Position,
+        Option<&Pose>,
+        &GameMode,
+        &mut RespawnPoint,
+        Option<&mut Inventory>,
+        Option<&HeldItem>,
+    )>,
+    mut instances: Query<&mut Instance>,
+    dimensions: Res<DimensionTypeRegistry>,
+    settings: Res<InteractionSettings>,
+    mut sleep_events: EventWriter<SleepAttemptEvent>,
+    mut explosion_events: EventWriter<RespawnBlockExplosionEvent>,
+) {
+    for interaction in interactions.iter() {
+        let Ok((mut client, loc, pos, pose, game_mode, mut point, inventory, held)) =
+            clients.get_mut(interaction.client) else {
+            continue
+        };
+
+        if *game_mode == GameMode::Spectator
+            || distance_to_block(eye_position(pos.0, pose), interaction.position)
+                > settings.block_reach
+        {
+            continue;
+        }
+
+        // Sneaking clients holding an item use the item instead of the block,
+        // like in vanilla.
+        let holding_item = inventory.as_deref().is_some_and(|inventory| {
+            held.map(|held| held.slot())
+                .into_iter()
+                .chain([PLAYER_INVENTORY_OFF_HAND_SLOT])
+                .any(|slot| inventory.slot(slot).is_some())
+        });
+
+        if holding_item && pose.is_some_and(|p| p.0 == valence_entity::Pose::Sneaking) {
+            continue;
+        }
+
+        let Ok(mut instance) = instances.get_mut(loc.0) else {
+            continue
+        };
+
+        let Some(dim) = dimensions.get(instance.dimension_type_name()) else {
+            continue
+        };
+
+        let mut position = interaction.position;
+
+        let Some(state) = instance.block(position).map(|block| block.state()) else {
+            continue
+        };
+
+        let new_point = if is_bed(state) {
+            if state.get(PropName::Part) == Some(PropValue::Foot) {
+                if let Some(facing) = state.get(PropName::Facing).and_then(horizontal_direction) {
+                    position = position.get_in_direction(facing);
+                }
+            }
+
+            if !dim.bed_works {
+                explosion_events.send(RespawnBlockExplosionEvent {
+                    client: interaction.client,
+                    instance: loc.0,
+                    position,
+                    block: state.to_kind(),
+                });
+                continue;
+            }
+
+            sleep_events.send(SleepAttemptEvent {
+                client: interaction.client,
+                instance: loc.0,
+                position,
+            });
+
+            RespawnPoint::Bed {
+                instance: loc.0,
+                pos: position,
+            }
+        } else if state.to_kind() == BlockKind::RespawnAnchor {
+            let Some(charges) = state.get(PropName::Charges).and_then(|c| c.to_u16()) else {
+                continue
+            };
+
+            let slot = match interaction.hand {
+                Hand::Main => held.map(|held| held.slot()),
+                Hand::Off => Some(PLAYER_INVENTORY_OFF_HAND_SLOT),
+            };
+
+            let glowstone = slot.zip(inventory).filter(|(slot, inventory)| {
+                inventory
+                    .slot(*slot)
+                    .is_some_and(|stack| stack.item == ItemKind::Glowstone)
+            });
+
+            // Using glowstone on an anchor that isn't full charges it instead of
+            // setting the respawn point.
+            if charges < MAX_ANCHOR_CHARGES {
+                if let Some((slot, mut inventory)) = glowstone {
+                    let Some(charged) = PropValue::from_u16(charges + 1) else {
+                        continue
+                    };
+
+                    instance.set_block(position, state.set(PropName::Charges, charged));
+
+                    if *game_mode != GameMode::Creative {
+                        let count = inventory.slot(slot).map_or(0, |stack| stack.count());
+
+                        if count > 1 {
+                            inventory.set_slot_amount(slot, count - 1);
+                        } else {
+                            inventory.set_slot(slot, None);
+                        }
+                    }
+
+                    continue;
+                }
+            }
+
+            if charges == 0 {
+                continue;
+            }
+
+            if !dim.respawn_anchor_works {
+                explosion_events.send(RespawnBlockExplosionEvent {
+                    client: interaction.client,
+                    instance: loc.0,
+                    position,
+                    block: BlockKind::RespawnAnchor,
+                });
+                continue;
+            }
+
+            RespawnPoint::RespawnAnchor {
+                instance: loc.0,
+                pos: position,
+            }
+        } else {
+            continue;
+        };
+
+        if *point != new_point {
+            *point = new_point;
+            client.send_chat_message(Text::translate(
+                translation_key::BLOCK_MINECRAFT_SET_SPAWN,
+                [],
+            ));
+        }
+    }
+}
+
+fn horizontal_direction(facing: PropValue) -> Option<Direction> {
+    match facing {
+        PropValue::North => Some(Direction::North),
+        PropValue::South => Some(Direction::South),
+        PropValue::West => Some(Direction::West),
+        PropValue::East => Some(Direction::East),
+        _ => None,
+    }
+}
@@ -0,0 +1,680 @@
+//! # Scoreboards
+//! Objectives with scores shown in the sidebar, the player list, or below the
+//! names of players, and teams that change how players are shown to each
+//! other.
+//!
+//! Objectives and teams are entities in an instance. Clients in the instance
+//! see all objectives and teams of the instance.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_scoreboard::packet::{ScoreboardPosition, TeamColor};
+//! # use valence_scoreboard::{Objective, ObjectiveBundle, ObjectiveScores, TeamBundle};
+//! fn spawn_sidebar(mut commands: Commands, instance: Entity) {
+//!     commands.spawn(ObjectiveBundle {
+//!         objective: Objective::new("kills")
+//!             .with_display_name("Kills")
+//!             .with_display_slot(ScoreboardPosition::Sidebar),
+//!         scores: ObjectiveScores::new().with_score("Notch", 3),
+//!         ..ObjectiveBundle::new("kills", instance)
+//!     });
+//!
+//!     let mut red = TeamBundle::new("red", instance);
+//!     red.team = red.team.with_color(TeamColor::Red).with_prefix("[Red] ");
+//!     red.members.insert("Notch");
+//!     commands.spawn(red);
+//! }
+//! ```
+//!
+//! Only the scores and team members that changed are sent to clients, so
+//! scores can be updated every tick.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+pub mod packet;
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use packet::*;
+use valence_client::{Client, FlushPacketsSet, UpdateClientsSet};
+use valence_core::despawn::Despawned;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_entity::{ClearEntityChangesSet, Location, OldLocation};
+use valence_instance::{Instance, WriteUpdatePacketsToInstancesSet};
+
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (update_objectives, update_teams)
+                .in_base_set(CoreSet::PostUpdate)
+                .before(WriteUpdatePacketsToInstancesSet)
+                .before(ClearEntityChangesSet),
+        )
+        .add_system(
+            init_scoreboard_for_clients
+                .in_base_set(CoreSet::PostUpdate)
+                .after(UpdateClientsSet)
+                .before(ClearEntityChangesSet)
+                .before(FlushPacketsSet),
+        );
+    }
+}
+
+#[derive(Bundle)]
+pub struct ObjectiveBundle {
+    pub objective: Objective,
+    pub scores: ObjectiveScores,
+    pub location: Location,
+    pub old_location: OldLocation,
+}
+
+impl ObjectiveBundle {
+    /// Creates an objective without scores in an instance.
+    pub fn new(name: impl Into<String>, instance: Entity) -> Self {
+        Self {
+            objective: Objective::new(name),
+            scores: ObjectiveScores::new(),
+            location: Location(instance),
+            old_location: OldLocation::new(instance),
+        }
+    }
+}
+
+/// An objective of a scoreboard. The name of an objective identifies it and
+/// must be unique within the instance.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Objective {
+    name: String,
+    pub display_name: Text,
+    pub render_type: ObjectiveRenderType,
+    /// Where the objective is shown. Each display slot shows at most one
+    /// objective.
+    pub display_slot: Option<ScoreboardPosition>,
+    sent_display_slot: Option<ScoreboardPosition>,
+}
+
+impl Objective {
+    /// Creates an objective that isn't shown anywhere. The display name is the
+    /// name of the objective.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+
+        Self {
+            display_name: name.clone().into(),
+            name,
+            render_type: ObjectiveRenderType::Integer,
+            display_slot: None,
+            sent_display_slot: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<Text>) -> Self {
+        self.display_name = display_name.into();
+        self
+    }
+
+    pub fn with_render_type(mut self, render_type: ObjectiveRenderType) -> Self {
+        self.render_type = render_type;
+        self
+    }
+
+    pub fn with_display_slot(mut self, display_slot: ScoreboardPosition) -> Self {
+        self.display_slot = Some(display_slot);
+        self
+    }
+}
+
+/// The scores of the entries of an [`Objective`]. Entries are usernames for
+/// players, UUIDs for other entities, or any other text, such as the lines of
+/// a sidebar. Sidebars show the entries ordered by score, highest first.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ObjectiveScores {
+    scores: BTreeMap<String, i32>,
+    /// Entries changed since the last update.
+    changed: BTreeSet<String>,
+}
+
+impl ObjectiveScores {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_score(mut self, entry: impl Into<String>, score: i32) -> Self {
+        self.set(entry, score);
+        self
+    }
+
+    pub fn get(&self, entry: &str) -> Option<i32> {
+        self.scores.get(entry).copied()
+    }
+
+    /// Sets the score of an entry and returns the previous score.
+    pub fn set(&mut self, entry: impl Into<String>, score: i32) -> Option<i32> {
+        let entry = entry.into();
+
+        if self.get(&entry) == Some(score) {
+            return Some(score);
+        }
+
+        self.changed.insert(entry.clone());
+        self.scores.insert(entry, score)
+    }
+
+    /// Adds to the score of an entry, which starts at zero, and returns the
+    /// new score.
+    pub fn add(&mut self, entry: impl Into<String>, amount: i32) -> i32 {
+        let entry = entry.into();
+        let score = self.get(&entry).unwrap_or(0).wrapping_add(amount);
+
+        self.set(entry, score);
+        score
+    }
+
+    pub fn remove(&mut self, entry: &str) -> Option<i32> {
+        let score = self.scores.remove(entry)?;
+        self.changed.insert(entry.to_owned());
+        Some(score)
+    }
+
+    pub fn clear(&mut self) {
+        self.changed
+            .extend(std::mem::take(&mut self.scores).into_keys());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i32)> + '_ {
+        self.scores
+            .iter()
+            .map(|(entry, &score)| (entry.as_str(), score))
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+#[derive(Bundle)]
+pub struct TeamBundle {
+    pub team: Team,
+    pub members: TeamMembers,
+    pub location: Location,
+    pub old_location: OldLocation,
+}
+
+impl TeamBundle {
+    /// Creates a team without members in an instance.
+    pub fn new(name: impl Into<String>, instance: Entity) -> Self {
+        Self {
+            team: Team::new(name),
+            members: TeamMembers::new(),
+            location: Location(instance),
+            old_location: OldLocation::new(instance),
+        }
+    }
+}
+
+/// A team of a scoreboard. The name of a team identifies it and must be
+/// unique within the instance.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub struct Team {
+    name: String,
+    pub display_name: Text,
+    /// Shown before the names of members.
+    pub prefix: Text,
+    /// Shown after the names of members.
+    pub suffix: Text,
+    /// The color of the names of members.
+    pub color: TeamColor,
+    /// Whether members can attack each other.
+    pub friendly_fire: bool,
+    /// Whether members see invisible members as translucent.
+    pub see_invisible_teammates: bool,
+    pub name_tag_visibility: NameTagVisibility,
+    pub collision_rule: CollisionRule,
+}
+
+impl Team {
+    /// Creates a team with the same defaults as vanilla. The display name is
+    /// the name of the team.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+
+        Self {
+            display_name: name.clone().into(),
+            name,
+            prefix: Text::default(),
+            suffix: Text::default(),
+            color: TeamColor::Reset,
+            friendly_fire: true,
+            see_invisible_teammates: true,
+            name_tag_visibility: NameTagVisibility::Always,
+            collision_rule: CollisionRule::Always,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<Text>) -> Self {
+        self.display_name = display_name.into();
+        self
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<Text>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: impl Into<Text>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    pub fn with_color(mut self, color: TeamColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_friendly_fire(mut self, friendly_fire: bool) -> Self {
+        self.friendly_fire = friendly_fire;
+        self
+    }
+
+    pub fn with_see_invisible_teammates(mut self, see_invisible_teammates: bool) -> Self {
+        self.see_invisible_teammates = see_invisible_teammates;
+        self
+    }
+
+    pub fn with_name_tag_visibility(mut self, name_tag_visibility: NameTagVisibility) -> Self {
+        self.name_tag_visibility = name_tag_visibility;
+        self
+    }
+
+    pub fn with_collision_rule(mut self, collision_rule: CollisionRule) -> Self {
+        self.collision_rule = collision_rule;
+        self
+    }
+
+    fn friendly_flags(&self) -> TeamFlags {
+        TeamFlags::new()
+            .with_friendly_fire(self.friendly_fire)
+            .with_see_invisible_teammates(self.see_invisible_teammates)
+    }
+}
+
+/// The members of a [`Team`]. Members are usernames for players and UUIDs for
+/// other entities. An entry can only be a member of one team at a time.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct TeamMembers {
+    members: BTreeSet<String>,
+    /// Members changed since the last update, and whether they were members
+    /// before the change.
+    changed: BTreeMap<String, bool>,
+}
+
+impl TeamMembers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, member: &str) -> bool {
+        self.members.contains(member)
+    }
+
+    /// Adds a member. Returns `false` if it was already a member.
+    pub fn insert(&mut self, member: impl Into<String>) -> bool {
+        let member = member.into();
+
+        if self.members.contains(&member) {
+            return false;
+        }
+
+        self.changed.entry(member.clone()).or_insert(false);
+        self.members.insert(member)
+    }
+
+    /// Removes a member. Returns `false` if it wasn't a member.
+    pub fn remove(&mut self, member: &str) -> bool {
+        if !self.members.remove(member) {
+            return false;
+        }
+
+        self.changed.entry(member.to_owned()).or_insert(true);
+        true
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.members.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+fn write_objective(mut writer: impl WritePacket, objective: &Objective, scores: &ObjectiveScores) {
+    writer.write_packet(&ScoreboardObjectiveUpdateS2c {
+        objective_name: &objective.name,
+        mode: ObjectiveMode::Create {
+            objective_display_name: objective.display_name.clone(),
+            render_type: objective.render_type,
+        },
+    });
+
+    for (entry, score) in scores.iter() {
+        writer.write_packet(&ScoreboardPlayerUpdateS2c {
+            entity_name: entry,
+            action: ScoreboardPlayerUpdateAction::Update {
+                objective_name: &objective.name,
+                objective_score: VarInt(score),
+            },
+        });
+    }
+}
+
+fn write_display_slot(mut writer: impl WritePacket, position: ScoreboardPosition, name: &str) {
+    writer.write_packet(&ScoreboardDisplayS2c {
+        position,
+        score_name: name,
+    });
+}
+
+fn remove_objective(mut writer: impl WritePacket, objective: &Objective) {
+    writer.write_packet(&ScoreboardObjectiveUpdateS2c {
+        objective_name: &objective.name,
+        mode: ObjectiveMode::Remove,
+    });
+}
+
+fn write_team(mut writer: impl WritePacket, team: &Team, members: &TeamMembers) {
+    writer.write_packet(&TeamS2c {
+        team_name: &team.name,
+        mode: Mode::CreateTeam {
+            team_display_name: Cow::Borrowed(&team.display_name),
+            friendly_flags: team.friendly_flags(),
+            name_tag_visibility: team.name_tag_visibility,
+            collision_rule: team.collision_rule,
+            team_color: team.color,
+            team_prefix: Cow::Borrowed(&team.prefix),
+            team_suffix: Cow::Borrowed(&team.suffix),
+            entities: members.iter().collect(),
+        },
+    });
+}
+
+fn remove_team(mut writer: impl WritePacket, team: &Team) {
+    writer.write_packet(&TeamS2c {
+        team_name: &team.name,
+        mode: Mode::RemoveTeam,
+    });
+}
+
+/// Sends the changes of objectives to the clients in the instance of the
+/// objective.
+fn update_objectives(
+    mut objectives: Query<
+        (
+            &mut Objective,
+            &mut ObjectiveScores,
+            Ref<Location>,
+            &OldLocation,
+            Option<&Despawned>,
+        ),
+        Or<(
+            Changed<Objective>,
+            Changed<ObjectiveScores>,
+            Changed<Location>,
+            Added<Despawned>,
+        )>,
+    >,
+    mut instances: Query<&mut Instance>,
+) {
+    // Objectives are shown after all objectives are removed from display slots,
+    // so that objectives can trade places.
+    let mut shown = vec![];
+
+    for (mut objective, mut scores, loc, old_loc, despawned) in &mut objectives {
+        if despawned.is_some() {
+            if let Ok(mut instance) = instances.get_mut(loc.0) {
+                remove_objective(&mut instance, &objective);
+            }
+            continue;
+        }
+
+        let moved = loc.is_changed() && !objective.is_added() && *old_loc != *loc;
+
+        if moved {
+            if let Ok(mut old_instance) = instances.get_mut(old_loc.get()) {
+                remove_objective(&mut old_instance, &objective);
+            }
+        }
+
+        let Ok(mut instance) = instances.get_mut(loc.0) else {
+            continue
+        };
+
+        if objective.is_added() || moved {
+            write_objective(&mut instance, &objective, &scores);
+
+            if let Some(slot) = objective.display_slot {
+                shown.push((loc.0, slot, objective.name.clone()));
+            }
+
+            let objective = objective.bypass_change_detection();
+            objective.sent_display_slot = objective.display_slot;
+            scores.bypass_change_detection().changed.clear();
+            continue;
+        }
+
+        if objective.is_changed() {
+            instance.write_packet(&ScoreboardObjectiveUpdateS2c {
+                objective_name: &objective.name,
+                mode: ObjectiveMode::Update {
+                    objective_display_name: objective.display_name.clone(),
+                    render_type: objective.render_type,
+                },
+            });
+
+            if objective.display_slot != objective.sent_display_slot {
+                if let Some(slot) = objective.sent_display_slot {
+                    write_display_slot(&mut instance, slot, "");
+                }
+
+                if let Some(slot) = objective.display_slot {
+                    shown.push((loc.0, slot, objective.name.clone()));
+                }
+
+                let objective = objective.bypass_change_detection();
+                objective.sent_display_slot = objective.display_slot;
+            }
+        }
+
+        if scores.is_changed() {
+            let scores = scores.bypass_change_detection();
+
+            for entry in std::mem::take(&mut scores.changed) {
+                let action = match scores.get(&entry) {
+                    Some(score) => ScoreboardPlayerUpdateAction::Update {
+                        objective_name: &objective.name,
+                        objective_score: VarInt(score),
+                    },
+                    None => ScoreboardPlayerUpdateAction::Remove {
+                        objective_name: &objective.name,
+                    },
+                };
+
+                instance.write_packet(&ScoreboardPlayerUpdateS2c {
+                    entity_name: &entry,
+                    action,
+                });
+            }
+        }
+    }
+
+    for (instance, slot, name) in shown {
+        if let Ok(mut instance) = instances.get_mut(instance) {
+            write_display_slot(&mut instance, slot, &name);
+        }
+    }
+}
+
+/// Sends the changes of teams to the clients in the instance of the team.
+fn update_teams(
+    mut teams: Query<
+        (
+            Ref<Team>,
+            &mut TeamMembers,
+            Ref<Location>,
+            &OldLocation,
+            Option<&Despawned>,
+        ),
+        Or<(
+            Changed<Team>,
+            Changed<TeamMembers>,
+            Changed<Location>,
+            Added<Despawned>,
+        )>,
+    >,
+    mut instances: Query<&mut Instance>,
+) {
+    for (team, mut members, loc, old_loc, despawned) in &mut teams {
+        if despawned.is_some() {
+            if let Ok(mut instance) = instances.get_mut(loc.0) {
+                remove_team(&mut instance, &team);
+            }
+            continue;
+        }
+
+        let moved = loc.is_changed() && !team.is_added() && *old_loc != *loc;
+
+        if moved {
+            if let Ok(mut old_instance) = instances.get_mut(old_loc.get()) {
+                remove_team(&mut old_instance, &team);
+            }
+        }
+
+        let Ok(mut instance) = instances.get_mut(loc.0) else {
+            continue
+        };
+
+        if team.is_added() || moved {
+            write_team(&mut instance, &team, &members);
+            members.bypass_change_detection().changed.clear();
+            continue;
+        }
+
+        if team.is_changed() {
+            instance.write_packet(&TeamS2c {
+                team_name: &team.name,
+                mode: Mode::UpdateTeamInfo {
+                    team_display_name: Cow::Borrowed(&team.display_name),
+                    friendly_flags: team.friendly_flags(),
+                    name_tag_visibility: team.name_tag_visibility,
+                    collision_rule: team.collision_rule,
+                    team_color: team.color,
+                    team_prefix: Cow::Borrowed(&team.prefix),
+                    team_suffix: Cow::Borrowed(&team.suffix),
+                },
+            });
+        }
+
+        if members.is_changed() {
+            let members = members.bypass_change_detection();
+            let changed = std::mem::take(&mut members.changed);
+
+            let mut added = vec![];
+            let mut removed = vec![];
+
+            for (member, was_member) in &changed {
+                match (was_member, members.contains(member)) {
+                    (false, true) => added.push(member.as_str()),
+                    (true, false) => removed.push(member.as_str()),
+                    _ => {}
+                }
+            }
+
+            if !removed.is_empty() {
+                instance.write_packet(&TeamS2c {
+                    team_name: &team.name,
+                    mode: Mode::RemoveEntities { entities: removed },
+                });
+            }
+
+            if !added.is_empty() {
+                instance.write_packet(&TeamS2c {
+                    team_name: &team.name,
+                    mode: Mode::AddEntities { entities: added },
+                });
+            }
+        }
+    }
+}
+
+/// Replaces the objectives and teams of clients that changed instances with
+/// the ones of the new instance.
+fn init_scoreboard_for_clients(
+    mut clients: Query<(&mut Client, &Location, &OldLocation), Changed<Location>>,
+    objectives: Query<(&Objective, &ObjectiveScores, &Location), Without<Despawned>>,
+    teams: Query<(&Team, &TeamMembers, &Location), Without<Despawned>>,
+) {
+    for (mut client, loc, old_loc) in &mut clients {
+        if *old_loc == *loc {
+            continue;
+        }
+
+        for (objective, scores, objective_loc) in &objectives {
+            if objective_loc.0 == old_loc.get() {
+                remove_objective(&mut client, objective);
+            } else if objective_loc == loc {
+                write_objective(&mut client, objective, scores);
+
+                if let Some(slot) = objective.display_slot {
+                    write_display_slot(&mut client, slot, &objective.name);
+                }
+            }
+        }
+
+        for (team, members, team_loc) in &teams {
+            if team_loc.0 == old_loc.get() {
+                remove_team(&mut client, team);
+            } else if team_loc == loc {
+                write_team(&mut client, team, members);
+            }
+        }
+    }
+}
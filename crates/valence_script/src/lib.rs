@@ -0,0 +1,643 @@
+//! # Scripts
+//! Gameplay scripts written in [Rhai](https://rhai.rs).
+//!
+//! With the [`ScriptPlugin`] added, every `.rhai` file in
+//! [`ScriptSettings::directory`] is loaded when the server starts. Scripts
+//! handle events by defining functions with these names:
+//!
+//! | Function | Called when |
+//! |---|---|
+//! | `on_tick()` | Every tick. |
+//! | `on_join(client)` | A client joined. |
+//! | `on_leave(client)` | A client left. |
+//! | `on_chat(client, message)` | A client sent a chat message. |
+//!
+//! ```text
+//! fn on_join(client) {
+//!     send_message(client, "Welcome, " + username(client) + "!");
+//!     give_item(client, "bread", 16);
+//! }
+//!
+//! fn on_chat(client, message) {
+//!     if message == "spawn" {
+//!         teleport(client, 0.5, 65.0, 0.5);
+//!     }
+//! }
+//! ```
+//!
+//! Entities are passed to scripts as values of type `Entity`. Scripts can use
+//! these functions:
+//!
+//! | Function | Description |
+//! |---|---|
+//! | `clients()` | An array of all clients. |
+//! | `username(client)` | The username of a client. |
+//! | `send_message(client, message)` | Sends a chat message to a client. |
+//! | `broadcast(message)` | Sends a chat message to all clients. |
+//! | `instance_of(entity)` | The instance an entity is in. |
+//! | `position(entity)` | The position of an entity as an array `[x, y, z]`. |
+//! | `teleport(entity, x, y, z)` | Moves an entity within its instance. |
+//! | `block(instance, x, y, z)` | The name of a block, like `"stone"`, or `""` if the position isn't loaded. |
+//! | `set_block(instance, x, y, z, name)` | Sets a block to the default state of a block kind. Returns `false` if the position isn't loaded. |
+//! | `give_item(client, name, count)` | Puts items in the first empty slot of a client's inventory. Returns `false` if the inventory is full. |
+//! | `despawn(entity)` | Despawns an entity that isn't a client. |
+//!
+//! `print` and `debug` are logged. Each call into a script is limited to
+//! [`ScriptSettings::max_operations`], so scripts stuck in a loop are stopped.
+//!
+//! Scripts are loaded again with the `/reloadscripts` command, which requires
+//! the `valence.command.reloadscripts` permission.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use parking_lot::Mutex; // Using nonstandard mutex to avoid poisoning API.
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST};
+use tracing::{info, warn};
+use valence_block::BlockKind;
+use valence_client::message::{ChatMessageEvent, SendMessage};
+use valence_client::{Client, Username};
+use valence_command::{literal, CommandExecutionEvent, CommandRegistry};
+use valence_core::block_pos::BlockPos;
+use valence_core::despawn::Despawned;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_entity::{Location, Position};
+use valence_instance::Instance;
+use valence_inventory::Inventory;
+
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(ScriptSettings::default)
+            .clone();
+
+        app.insert_resource(Scripts::new(settings))
+            .add_startup_system(load_scripts)
+            .add_systems((handle_reload_command, collect_script_events, run_scripts).chain());
+    }
+}
+
+/// The command that reloads all scripts.
+const RELOAD_COMMAND: &str = "reloadscripts";
+
+/// Settings for [`ScriptPlugin`]. Changes have no effect after the plugin is
+/// built.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct ScriptSettings {
+    /// The directory scripts are loaded from.
+    ///
+    /// # Default Value
+    ///
+    /// `scripts`
+    pub directory: PathBuf,
+    /// The maximum number of operations a script can run in one call.
+    ///
+    /// # Default Value
+    ///
+    /// `1_000_000`
+    pub max_operations: u64,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("scripts"),
+            max_operations: 1_000_000,
+        }
+    }
+}
+
+/// The loaded scripts.
+#[derive(Resource)]
+pub struct Scripts {
+    engine: Engine,
+    /// The world of the server during calls into scripts, and an empty world
+    /// otherwise.
+    world: Arc<Mutex<World>>,
+    directory: PathBuf,
+    scripts: BTreeMap<String, Script>,
+    /// Events waiting to be sent to scripts.
+    pending: Vec<ScriptEvent>,
+}
+
+struct Script {
+    ast: AST,
+    scope: Scope<'static>,
+    /// If the top level statements of the script were run.
+    initialized: bool,
+}
+
+enum ScriptEvent {
+    Join(Entity),
+    Leave(Entity),
+    Chat(Entity, String),
+}
+
+impl Scripts {
+    pub fn new(settings: ScriptSettings) -> Self {
+        let world = Arc::new(Mutex::new(World::new()));
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(settings.max_operations);
+        engine.on_print(|text| info!("[script] {text}"));
+        engine.on_debug(|text, source, pos| {
+            info!("[script] {} {pos}: {text}", source.unwrap_or("unknown"))
+        });
+
+        register_api(&mut engine, &world);
+
+        Self {
+            engine,
+            world,
+            directory: settings.directory,
+            scripts: BTreeMap::new(),
+            pending: vec![],
+        }
+    }
+
+    /// Loads a script from a file, named after the file without its
+    /// extension. A script with the same name is replaced. The top level
+    /// statements of the script are run in the next tick.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), Box<EvalAltResult>> {
+        let path = path.as_ref();
+
+        let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+            return Err(format!("invalid script file name {}", path.display()).into())
+        };
+
+        let mut ast = self.engine.compile_file(path.to_owned())?;
+        ast.set_source(name);
+
+        self.insert(name.to_owned(), ast);
+        Ok(())
+    }
+
+    /// Loads a script from its source code. A script with the same name is
+    /// replaced.
+    pub fn load_str(
+        &mut self,
+        name: impl Into<String>,
+        source: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let name = name.into();
+
+        let mut ast = self.engine.compile(source)?;
+        ast.set_source(name.as_str());
+
+        self.insert(name, ast);
+        Ok(())
+    }
+
+    /// Unloads all scripts and loads the scripts in the directory again.
+    /// Returns the number of scripts that were loaded and the errors of the
+    /// scripts that failed to load.
+    pub fn reload_all(&mut self) -> (usize, Vec<String>) {
+        self.scripts.clear();
+
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return (0, vec![]),
+            Err(e) => {
+                let msg = format!(
+                    "failed to read script directory {}: {e}",
+                    self.directory.display()
+                );
+                return (0, vec![msg]);
+            }
+        };
+
+        let mut errors = vec![];
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.extension().map_or(false, |ext| ext == "rhai") {
+                if let Err(e) = self.load(&path) {
+                    errors.push(format!("failed to load script {}: {e}", path.display()));
+                }
+            }
+        }
+
+        (self.scripts.len(), errors)
+    }
+
+    pub fn unload(&mut self, name: &str) -> bool {
+        self.scripts.remove(name).is_some()
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.scripts.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.scripts.keys().map(|name| name.as_str())
+    }
+
+    fn insert(&mut self, name: String, ast: AST) {
+        self.scripts.insert(
+            name,
+            Script {
+                ast,
+                scope: Scope::new(),
+                initialized: false,
+            },
+        );
+    }
+
+    /// Initializes new scripts and sends them the pending events.
+    fn run(&mut self, world: &mut World) {
+        let events = std::mem::take(&mut self.pending);
+
+        // The API needs the world, so it is moved into the shared world for
+        // the duration of the calls.
+        std::mem::swap(world, &mut *self.world.lock());
+
+        for (name, script) in &mut self.scripts {
+            if !script.initialized {
+                script.initialized = true;
+
+                if let Err(e) = self
+                    .engine
+                    .run_ast_with_scope(&mut script.scope, &script.ast)
+                {
+                    warn!("script `{name}` failed to run: {e}");
+                }
+            }
+
+            for event in &events {
+                let res = match event {
+                    ScriptEvent::Join(client) => {
+                        script.call(&self.engine, "on_join", vec![Dynamic::from(*client)])
+                    }
+                    ScriptEvent::Leave(client) => {
+                        script.call(&self.engine, "on_leave", vec![Dynamic::from(*client)])
+                    }
+                    ScriptEvent::Chat(client, message) => script.call(
+                        &self.engine,
+                        "on_chat",
+                        vec![Dynamic::from(*client), message.as_str().into()],
+                    ),
+                };
+
+                if let Err(e) = res {
+                    warn!("script `{name}` failed to handle an event: {e}");
+                }
+            }
+
+            if let Err(e) = script.call(&self.engine, "on_tick", vec![]) {
+                warn!("script `{name}` failed to handle a tick: {e}");
+            }
+        }
+
+        std::mem::swap(world, &mut *self.world.lock());
+    }
+}
+
+impl Script {
+    /// Calls a function of the script if it is defined.
+    fn call(
+        &mut self,
+        engine: &Engine,
+        name: &str,
+        args: Vec<Dynamic>,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let defined = self
+            .ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == args.len());
+
+        if !defined {
+            return Ok(());
+        }
+
+        engine.call_fn_with_options::<Dynamic>(
+            CallFnOptions::new().eval_ast(false),
+            &mut self.scope,
+            &self.ast,
+            name,
+            args,
+        )?;
+
+        Ok(())
+    }
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn register_api(engine: &mut Engine, world: &Arc<Mutex<World>>) {
+    engine.register_type_with_name::<Entity>("Entity");
+
+    let w = world.clone();
+    engine.register_fn("clients", move || -> Array {
+        let mut world = w.lock();
+
+        world
+            .query_filtered::<Entity, With<Client>>()
+            .iter(&world)
+            .map(Dynamic::from)
+            .collect()
+    });
+
+    let w = world.clone();
+    engine.register_fn("username", move |client: Entity| -> ScriptResult<String> {
+        let world = w.lock();
+
+        match world.get::<Username>(client) {
+            Some(username) => Ok(username.0.clone()),
+            None => Err(format!("{client:?} is not a client").into()),
+        }
+    });
+
+    let w = world.clone();
+    engine.register_fn("send_message", move |client: Entity, msg: &str| {
+        if let Some(mut client) = w.lock().get_mut::<Client>(client) {
+            client.send_chat_message(msg.to_owned());
+        }
+    });
+
+    let w = world.clone();
+    engine.register_fn("broadcast", move |msg: &str| {
+        let mut world = w.lock();
+
+        for mut client in world.query::<&mut Client>().iter_mut(&mut world) {
+            client.send_chat_message(msg.to_owned());
+        }
+    });
+
+    let w = world.clone();
+    engine.register_fn(
+        "instance_of",
+        move |entity: Entity| -> ScriptResult<Entity> {
+            match w.lock().get::<Location>(entity) {
+                Some(loc) => Ok(loc.0),
+                None => Err(format!("{entity:?} is not in an instance").into()),
+            }
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn("position", move |entity: Entity| -> ScriptResult<Array> {
+        match w.lock().get::<Position>(entity) {
+            Some(pos) => Ok(vec![pos.0.x.into(), pos.0.y.into(), pos.0.z.into()]),
+            None => Err(format!("{entity:?} has no position").into()),
+        }
+    });
+
+    let w = world.clone();
+    engine.register_fn(
+        "teleport",
+        move |entity: Entity, x: Dynamic, y: Dynamic, z: Dynamic| -> ScriptResult<()> {
+            let pos = DVec3::new(number(x)?, number(y)?, number(z)?);
+
+            match w.lock().get_mut::<Position>(entity) {
+                Some(mut position) => {
+                    position.set(pos);
+                    Ok(())
+                }
+                None => Err(format!("{entity:?} has no position").into()),
+            }
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn(
+        "block",
+        move |instance: Entity, x: i64, y: i64, z: i64| -> ScriptResult<String> {
+            let pos = block_pos(x, y, z)?;
+            let world = w.lock();
+
+            let Some(instance) = world.get::<Instance>(instance) else {
+                return Err(format!("{instance:?} is not an instance").into())
+            };
+
+            Ok(instance
+                .block(pos)
+                .map_or("", |block| block.state().to_kind().to_str())
+                .to_owned())
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn(
+        "set_block",
+        move |instance: Entity, x: i64, y: i64, z: i64, name: &str| -> ScriptResult<bool> {
+            let pos = block_pos(x, y, z)?;
+
+            let Some(kind) = BlockKind::from_str(name) else {
+                return Err(format!("unknown block `{name}`").into())
+            };
+
+            let mut world = w.lock();
+
+            let Some(mut instance) = world.get_mut::<Instance>(instance) else {
+                return Err(format!("{instance:?} is not an instance").into())
+            };
+
+            Ok(instance.set_block(pos, kind.to_state()).is_some())
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn(
+        "give_item",
+        move |client: Entity, name: &str, count: i64| -> ScriptResult<bool> {
+            let Some(kind) = ItemKind::from_str(name) else {
+                return Err(format!("unknown item `{name}`").into())
+            };
+
+            let count = count.clamp(ItemStack::STACK_MIN.into(), ItemStack::STACK_MAX.into());
+            let mut world = w.lock();
+
+            let Some(mut inventory) = world.get_mut::<Inventory>(client) else {
+                return Err(format!("{client:?} has no inventory").into())
+            };
+
+            // The hotbar first, then the rest of the main inventory.
+            let slot = inventory
+                .first_empty_slot_in(36..45)
+                .or_else(|| inventory.first_empty_slot_in(9..36));
+
+            let Some(slot) = slot else {
+                return Ok(false)
+            };
+
+            inventory.set_slot(slot, ItemStack::new(kind, count as u8, None));
+            Ok(true)
+        },
+    );
+
+    let w = world.clone();
+    engine.register_fn("despawn", move |entity: Entity| {
+        let mut world = w.lock();
+
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            if !entity.contains::<Client>() {
+                entity.insert(Despawned);
+            }
+        }
+    });
+}
+
+/// Converts an integer or float from a script to a float.
+fn number(value: Dynamic) -> ScriptResult<f64> {
+    match value.as_float() {
+        Ok(f) => Ok(f),
+        Err(_) => match value.as_int() {
+            Ok(i) => Ok(i as f64),
+            Err(ty) => Err(format!("expected a number, found {ty}").into()),
+        },
+    }
+}
+
+fn block_pos(x: i64, y: i64, z: i64) -> ScriptResult<BlockPos> {
+    match (x.try_into(), y.try_into(), z.try_into()) {
+        (Ok(x), Ok(y), Ok(z)) => Ok(BlockPos::new(x, y, z)),
+        _ => Err(format!("block position ({x}, {y}, {z}) is out of range").into()),
+    }
+}
+
+fn load_scripts(mut scripts: ResMut<Scripts>, mut registry: ResMut<CommandRegistry>) {
+    registry.register(
+        literal(RELOAD_COMMAND)
+            .requires("valence.command.reloadscripts")
+            .executable(),
+    );
+
+    let (count, errors) = scripts.reload_all();
+
+    for error in errors {
+        warn!("{error}");
+    }
+
+    if count > 0 {
+        info!("loaded {count} scripts");
+    }
+}
+
+fn handle_reload_command(
+    mut events: EventReader<CommandExecutionEvent>,
+    mut scripts: ResMut<Scripts>,
+    mut clients: Query<&mut Client>,
+) {
+    for event in events.iter() {
+        if event.command != RELOAD_COMMAND {
+            continue;
+        }
+
+        let (count, errors) = scripts.reload_all();
+
+        if let Ok(mut client) = clients.get_mut(event.client) {
+            for error in &errors {
+                client.send_chat_message(error.clone());
+            }
+
+            client.send_chat_message(format!("Reloaded {count} scripts"));
+        }
+    }
+}
+
+fn collect_script_events(
+    mut scripts: ResMut<Scripts>,
+    joined: Query<Entity, Added<Client>>,
+    mut left: RemovedComponents<Client>,
+    mut chat: EventReader<ChatMessageEvent>,
+) {
+    for client in &joined {
+        scripts.pending.push(ScriptEvent::Join(client));
+    }
+
+    for client in left.iter() {
+        scripts.pending.push(ScriptEvent::Leave(client));
+    }
+
+    for event in chat.iter() {
+        scripts
+            .pending
+            .push(ScriptEvent::Chat(event.client, event.message.to_string()));
+    }
+}
+
+fn run_scripts(world: &mut World) {
+    world.resource_scope(|world, mut scripts: Mut<Scripts>| scripts.run(world));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_call_script_functions() {
+        let mut world = World::new();
+        let mut scripts = Scripts::new(ScriptSettings::default());
+
+        scripts
+            .load_str(
+                "test",
+                r#"
+                    fn on_chat(entity, message) {
+                        if message == "bye" {
+                            despawn(entity);
+                        }
+                    }
+                "#,
+            )
+            .unwrap();
+
+        let stays = world.spawn_empty().id();
+        let leaves = world.spawn_empty().id();
+
+        scripts
+            .pending
+            .push(ScriptEvent::Chat(stays, "hello".into()));
+        scripts
+            .pending
+            .push(ScriptEvent::Chat(leaves, "bye".into()));
+
+        scripts.run(&mut world);
+
+        assert!(!world.entity(stays).contains::<Despawned>());
+        assert!(world.entity(leaves).contains::<Despawned>());
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let mut scripts = Scripts::new(ScriptSettings {
+            max_operations: 1000,
+            ..Default::default()
+        });
+
+        scripts
+            .load_str("spin", "fn on_tick() { loop {} }")
+            .unwrap();
+
+        let script = scripts.scripts.get_mut("spin").unwrap();
+        assert!(script.call(&scripts.engine, "on_tick", vec![]).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Spatial indices of the Minecraft entities in each instance.
+//!
+//! Every [`Instance`] is given an [`EntitySpatialIndex`] component containing
+//! the hitboxes of all entities in the instance. The index is rebuilt once per
+//! tick in [`UpdateSpatialIndexSet`], after client packets have been handled.
+//! Entities moved or spawned after that are seen by the index on the next
+//! tick.
+//!
+//! Entities without a [`HitboxShape`] are indexed as a point at their
+//! position.
+
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use rustc_hash::FxHashMap;
+use valence_client::Client;
+use valence_core::aabb::Aabb;
+use valence_core::despawn::Despawned;
+use valence_entity::hitbox::HitboxShape;
+use valence_entity::{EntityKind, Location, Position};
+use valence_instance::raycast::BlockRaycastHit;
+use valence_instance::Instance;
+
+use crate::bvh::Bvh;
+use crate::{ray_box_intersect, SpatialIndex, WithAabb};
+
+/// When the [`EntitySpatialIndex`] of every instance is rebuilt. Systems that
+/// query the index should run _after_ this.
+#[derive(SystemSet, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct UpdateSpatialIndexSet;
+
+/// A bounding volume hierarchy of the entities in an [`Instance`]. See the
+/// [module-level documentation](self) for more.
+#[derive(Component, Default)]
+pub struct EntitySpatialIndex {
+    bvh: Bvh<WithAabb<IndexedEntity>>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct IndexedEntity {
+    entity: Entity,
+    is_client: bool,
+}
+
+/// An intersection between a ray and the hitbox of an entity.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EntityRaycastHit {
+    pub entity: Entity,
+    /// The hitbox of the entity.
+    pub aabb: Aabb,
+    /// The distance from the ray origin to the point where the ray enters the
+    /// hitbox. This is zero if the origin is inside the hitbox.
+    pub near: f64,
+    /// The distance from the ray origin to the point where the ray exits the
+    /// hitbox.
+    pub far: f64,
+    /// The point where the ray enters the hitbox.
+    pub position: DVec3,
+}
+
+/// The closest intersection found by [`EntitySpatialIndex::raycast`].
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InstanceRaycastHit {
+    Block(BlockRaycastHit),
+    Entity(EntityRaycastHit),
+}
+
+impl InstanceRaycastHit {
+    /// Returns the point where the ray hit.
+    pub fn position(&self) -> DVec3 {
+        match self {
+            Self::Block(hit) => hit.position,
+            Self::Entity(hit) => hit.position,
+        }
+    }
+
+    /// Returns the distance from the ray origin to the hit.
+    pub fn distance(&self) -> f64 {
+        match self {
+            Self::Block(hit) => hit.distance,
+            Self::Entity(hit) => hit.near,
+        }
+    }
+}
+
+impl EntitySpatialIndex {
+    /// Returns the number of entities in the index.
+    pub fn len(&self) -> usize {
+        self.bvh.iter().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Invokes `f` with every entity whose hitbox intersects `aabb`, in an
+    /// arbitrary order. If `f` returns `Some`, the query exits early with that
+    /// value.
+    pub fn query_aabb<F, T>(&self, aabb: Aabb, mut f: F) -> Option<T>
+    where
+        F: FnMut(Entity, Aabb) -> Option<T>,
+    {
+        self.bvh.query(
+            |bb| from_vek(bb).intersects(aabb),
+            |leaf| f(leaf.object.entity, from_vek(leaf.aabb)),
+        )
+    }
+
+    /// Returns all entities whose hitbox intersects `aabb`.
+    pub fn entities_in_aabb(&self, aabb: Aabb) -> Vec<Entity> {
+        let mut res = vec![];
+
+        self.query_aabb(aabb, |entity, _| {
+            res.push(entity);
+            None::<()>
+        });
+
+        res
+    }
+
+    /// Returns the entity with the hitbox closest to `pos` for which `f`
+    /// returns `true`. Only entities within `max_dist` are considered.
+    pub fn nearest<F>(&self, pos: DVec3, max_dist: f64, mut f: F) -> Option<Entity>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        self.nearest_leaf(pos, max_dist, |leaf| f(leaf.entity))
+    }
+
+    /// Returns the client entity with the hitbox closest to `pos`. Only clients
+    /// within `max_dist` are considered.
+    pub fn nearest_client(&self, pos: DVec3, max_dist: f64) -> Option<Entity> {
+        self.nearest_leaf(pos, max_dist, |leaf| leaf.is_client)
+    }
+
+    fn nearest_leaf(
+        &self,
+        pos: DVec3,
+        max_dist: f64,
+        mut f: impl FnMut(&IndexedEntity) -> bool,
+    ) -> Option<Entity> {
+        let search = Aabb::new(pos - DVec3::splat(max_dist), pos + DVec3::splat(max_dist));
+        let mut nearest = None;
+        let mut nearest_dist_sq = max_dist * max_dist;
+
+        self.bvh.query(
+            |bb| from_vek(bb).intersects(search),
+            |leaf| {
+                let bb = from_vek(leaf.aabb);
+                let dist_sq = pos.clamp(bb.min, bb.max).distance_squared(pos);
+
+                if dist_sq <= nearest_dist_sq && f(&leaf.object) {
+                    nearest = Some(leaf.object.entity);
+                    nearest_dist_sq = dist_sq;
+                }
+
+                None::<()>
+            },
+        );
+
+        nearest
+    }
+
+    /// Casts a ray from `origin` in `direction` and returns the closest entity
+    /// hitbox it intersects with for which `f` returns `true`. Only hits
+    /// within `max_dist` of the origin are considered.
+    ///
+    /// `f` is typically used to exclude the entity the ray originates from.
+    pub fn raycast_entities<F>(
+        &self,
+        origin: DVec3,
+        direction: DVec3,
+        max_dist: f64,
+        mut f: F,
+    ) -> Option<EntityRaycastHit>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let direction = direction.normalize_or_zero();
+
+        if direction == DVec3::ZERO {
+            return None;
+        }
+
+        let hit = self
+            .bvh
+            .raycast(to_vek_vec(origin), to_vek_vec(direction), |hit| {
+                hit.near <= max_dist && f(hit.object.object.entity)
+            })?;
+
+        Some(EntityRaycastHit {
+            entity: hit.object.object.entity,
+            aabb: from_vek(hit.object.aabb),
+            near: hit.near,
+            far: hit.far,
+            position: origin + direction * hit.near,
+        })
+    }
+
+    /// Casts a ray through both the blocks of `instance` and the entities in
+    /// this index, and returns whichever is hit first. Entities for which `f`
+    /// returns `false` are ignored.
+    ///
+    /// `instance` should be the instance this index belongs to.
+    pub fn raycast<F>(
+        &self,
+        instance: &Instance,
+        origin: DVec3,
+        direction: DVec3,
+        max_dist: f64,
+        f: F,
+    ) -> Option<InstanceRaycastHit>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let block_hit = instance.raycast_blocks(origin, direction, max_dist);
+
+        // Entities behind the block that was hit can be skipped.
+        let entity_max_dist = block_hit.map_or(max_dist, |hit| hit.distance);
+
+        match self.raycast_entities(origin, direction, entity_max_dist, f) {
+            Some(hit) => Some(InstanceRaycastHit::Entity(hit)),
+            None => block_hit.map(InstanceRaycastHit::Block),
+        }
+    }
+}
+
+/// Returns the distances at which the ray from `origin` in the normalized
+/// `direction` enters and exits `aabb`, if it intersects.
+pub fn ray_aabb_intersect(origin: DVec3, direction: DVec3, aabb: Aabb) -> Option<(f64, f64)> {
+    ray_box_intersect(to_vek_vec(origin), to_vek_vec(direction), to_vek(aabb))
+}
+
+pub(crate) fn add_spatial_index(
+    instances: Query<Entity, (With<Instance>, Without<EntitySpatialIndex>)>,
+    mut commands: Commands,
+) {
+    for instance in &instances {
+        commands
+            .entity(instance)
+            .insert(EntitySpatialIndex::default());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn rebuild_spatial_indices(
+    mut instances: Query<(Entity, &mut EntitySpatialIndex)>,
+    entities: Query<
+        (
+            Entity,
+            &Location,
+            &Position,
+            Option<&HitboxShape>,
+            Option<&Client>,
+        ),
+        (With<EntityKind>, Without<Despawned>),
+    >,
+    mut leaves: Local<FxHashMap<Entity, Vec<WithAabb<IndexedEntity>>>>,
+) {
+    for (entity, loc, pos, shape, client) in &entities {
+        // The hitbox component is only updated before the event loop, so it is
+        // computed here from the latest position instead.
+        let aabb = match shape {
+            Some(shape) => shape.get() + pos.0,
+            None => Aabb::new(pos.0, pos.0),
+        };
+
+        leaves.entry(loc.0).or_default().push(WithAabb::new(
+            IndexedEntity {
+                entity,
+                is_client: client.is_some(),
+            },
+            to_vek(aabb),
+        ));
+    }
+
+    for (instance, mut index) in &mut instances {
+        match leaves.get_mut(&instance) {
+            Some(leaves) => index.bvh.rebuild(leaves.drain(..)),
+            None => index.bvh.rebuild([]),
+        }
+    }
+
+    // Remove the buffers of instances that no longer exist.
+    leaves.retain(|instance, _| instances.contains(*instance));
+}
+
+fn to_vek_vec(v: DVec3) -> vek::Vec3<f64> {
+    vek::Vec3::new(v.x, v.y, v.z)
+}
+
+fn to_vek(aabb: Aabb) -> vek::Aabb<f64> {
+    vek::Aabb {
+        min: to_vek_vec(aabb.min),
+        max: to_vek_vec(aabb.max),
+    }
+}
+
+fn from_vek(aabb: vek::Aabb<f64>) -> Aabb {
+    Aabb {
+        min: DVec3::new(aabb.min.x, aabb.min.y, aabb.min.z),
+        max: DVec3::new(aabb.max.x, aabb.max.y, aabb.max.z),
+    }
+}
@@ -17,9 +17,36 @@
     clippy::dbg_macro
 )]
 
+use bevy_app::{App, CoreSet, Plugin};
+use bevy_ecs::prelude::*;
+pub use entity::{EntityRaycastHit, EntitySpatialIndex, InstanceRaycastHit, UpdateSpatialIndexSet};
+use valence_client::event_loop::RunEventLoopSet;
 use vek::{Aabb, Vec3};
 
 pub mod bvh;
+pub mod entity;
+
+/// Maintains an [`EntitySpatialIndex`] for every instance.
+pub struct SpatialIndexPlugin;
+
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_set(
+            UpdateSpatialIndexSet
+                .in_base_set(CoreSet::PreUpdate)
+                .after(RunEventLoopSet),
+        )
+        .add_systems(
+            (
+                entity::add_spatial_index,
+                apply_system_buffers,
+                entity::rebuild_spatial_indices,
+            )
+                .chain()
+                .in_set(UpdateSpatialIndexSet),
+        );
+    }
+}
 
 pub trait SpatialIndex<N = f64> {
     type Object: Bounded3D<N>;
@@ -0,0 +1,123 @@
+macro_rules! custom_stats {
+    ($($variant:ident = $id:literal => $name:literal,)*) => {
+        /// The statistics in the "General" tab of the statistics screen,
+        /// which are in the `minecraft:custom` category.
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum CustomStat {
+            $($variant,)*
+        }
+
+        impl CustomStat {
+            /// All custom statistics in the order of their raw IDs.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)*];
+
+            /// Constructs a custom statistic from its raw ID.
+            pub const fn from_raw(id: i32) -> Option<Self> {
+                match id {
+                    $($id => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the raw ID of this custom statistic.
+            pub const fn to_raw(self) -> i32 {
+                match self {
+                    $(Self::$variant => $id,)*
+                }
+            }
+
+            /// Constructs a custom statistic from its snake_case name.
+            pub fn from_str(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the snake_case name of this custom statistic.
+            pub const fn to_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+custom_stats! {
+    LeaveGame = 0 => "leave_game",
+    PlayTime = 1 => "play_time",
+    TotalWorldTime = 2 => "total_world_time",
+    TimeSinceDeath = 3 => "time_since_death",
+    TimeSinceRest = 4 => "time_since_rest",
+    SneakTime = 5 => "sneak_time",
+    WalkOneCm = 6 => "walk_one_cm",
+    CrouchOneCm = 7 => "crouch_one_cm",
+    SprintOneCm = 8 => "sprint_one_cm",
+    WalkOnWaterOneCm = 9 => "walk_on_water_one_cm",
+    FallOneCm = 10 => "fall_one_cm",
+    ClimbOneCm = 11 => "climb_one_cm",
+    FlyOneCm = 12 => "fly_one_cm",
+    WalkUnderWaterOneCm = 13 => "walk_under_water_one_cm",
+    MinecartOneCm = 14 => "minecart_one_cm",
+    BoatOneCm = 15 => "boat_one_cm",
+    PigOneCm = 16 => "pig_one_cm",
+    HorseOneCm = 17 => "horse_one_cm",
+    AviateOneCm = 18 => "aviate_one_cm",
+    SwimOneCm = 19 => "swim_one_cm",
+    StriderOneCm = 20 => "strider_one_cm",
+    Jump = 21 => "jump",
+    Drop = 22 => "drop",
+    DamageDealt = 23 => "damage_dealt",
+    DamageDealtAbsorbed = 24 => "damage_dealt_absorbed",
+    DamageDealtResisted = 25 => "damage_dealt_resisted",
+    DamageTaken = 26 => "damage_taken",
+    DamageBlockedByShield = 27 => "damage_blocked_by_shield",
+    DamageAbsorbed = 28 => "damage_absorbed",
+    DamageResisted = 29 => "damage_resisted",
+    Deaths = 30 => "deaths",
+    MobKills = 31 => "mob_kills",
+    AnimalsBred = 32 => "animals_bred",
+    PlayerKills = 33 => "player_kills",
+    FishCaught = 34 => "fish_caught",
+    TalkedToVillager = 35 => "talked_to_villager",
+    TradedWithVillager = 36 => "traded_with_villager",
+    EatCakeSlice = 37 => "eat_cake_slice",
+    FillCauldron = 38 => "fill_cauldron",
+    UseCauldron = 39 => "use_cauldron",
+    CleanArmor = 40 => "clean_armor",
+    CleanBanner = 41 => "clean_banner",
+    CleanShulkerBox = 42 => "clean_shulker_box",
+    InteractWithBrewingstand = 43 => "interact_with_brewingstand",
+    InteractWithBeacon = 44 => "interact_with_beacon",
+    InspectDropper = 45 => "inspect_dropper",
+    InspectHopper = 46 => "inspect_hopper",
+    InspectDispenser = 47 => "inspect_dispenser",
+    PlayNoteblock = 48 => "play_noteblock",
+    TuneNoteblock = 49 => "tune_noteblock",
+    PotFlower = 50 => "pot_flower",
+    TriggerTrappedChest = 51 => "trigger_trapped_chest",
+    OpenEnderchest = 52 => "open_enderchest",
+    EnchantItem = 53 => "enchant_item",
+    PlayRecord = 54 => "play_record",
+    InteractWithFurnace = 55 => "interact_with_furnace",
+    InteractWithCraftingTable = 56 => "interact_with_crafting_table",
+    OpenChest = 57 => "open_chest",
+    SleepInBed = 58 => "sleep_in_bed",
+    OpenShulkerBox = 59 => "open_shulker_box",
+    OpenBarrel = 60 => "open_barrel",
+    InteractWithBlastFurnace = 61 => "interact_with_blast_furnace",
+    InteractWithSmoker = 62 => "interact_with_smoker",
+    InteractWithLectern = 63 => "interact_with_lectern",
+    InteractWithCampfire = 64 => "interact_with_campfire",
+    InteractWithCartographyTable = 65 => "interact_with_cartography_table",
+    InteractWithLoom = 66 => "interact_with_loom",
+    InteractWithStonecutter = 67 => "interact_with_stonecutter",
+    BellRing = 68 => "bell_ring",
+    RaidTrigger = 69 => "raid_trigger",
+    RaidWin = 70 => "raid_win",
+    InteractWithAnvil = 71 => "interact_with_anvil",
+    InteractWithGrindstone = 72 => "interact_with_grindstone",
+    TargetHit = 73 => "target_hit",
+    InteractWithSmithingTable = 74 => "interact_with_smithing_table",
+}
@@ -0,0 +1,259 @@
+//! # Statistics
+//! Player statistics like the ones shown in the vanilla statistics screen.
+//!
+//! Clients are given a [`Statistics`] component when they join, which is sent
+//! to the client when it opens the statistics screen. The play time of clients
+//! is counted automatically. Other statistics are changed with
+//! [`Statistics::increment`] and [`Statistics::set`].
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_client::interact_entity::InteractEntityEvent;
+//! # use valence_entity::EntityKind;
+//! # use valence_statistics::{CustomStat, Stat, Statistics};
+//! fn count_villager_talks(
+//!     mut events: EventReader<InteractEntityEvent>,
+//!     entities: Query<&EntityKind>,
+//!     mut clients: Query<&mut Statistics>,
+//! ) {
+//!     for event in events.iter() {
+//!         if entities.get(event.entity) == Ok(&EntityKind::VILLAGER) {
+//!             if let Ok(mut stats) = clients.get_mut(event.client) {
+//!                 stats.increment(Stat::Custom(CustomStat::TalkedToVillager), 1);
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Valence doesn't store player data itself. Use [`Statistics::save`] and
+//! [`Statistics::load`] to keep statistics between sessions.
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+mod custom;
+
+use std::collections::BTreeMap;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+pub use custom::CustomStat;
+use serde::{Deserialize, Serialize};
+use valence_block::BlockKind;
+use valence_client::packet::{Statistic, StatisticsS2c};
+use valence_client::status::RequestStatsEvent;
+use valence_client::Client;
+use valence_core::ident::Ident;
+use valence_core::item::ItemKind;
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::EntityKind;
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(init_statistics.in_base_set(CoreSet::PreUpdate))
+            .add_systems(
+                (count_play_time, send_statistics.after(count_play_time))
+                    .in_base_set(CoreSet::Update),
+            );
+    }
+}
+
+/// A statistic in one of the categories of the statistics screen.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Stat {
+    Mined(BlockKind),
+    Crafted(ItemKind),
+    Used(ItemKind),
+    Broken(ItemKind),
+    PickedUp(ItemKind),
+    Dropped(ItemKind),
+    Killed(EntityKind),
+    KilledBy(EntityKind),
+    Custom(CustomStat),
+}
+
+impl Stat {
+    /// Returns the raw IDs of the category and statistic.
+    pub fn to_raw(self) -> (i32, i32) {
+        match self {
+            Stat::Mined(block) => (0, block.to_raw() as i32),
+            Stat::Crafted(item) => (1, item.to_raw() as i32),
+            Stat::Used(item) => (2, item.to_raw() as i32),
+            Stat::Broken(item) => (3, item.to_raw() as i32),
+            Stat::PickedUp(item) => (4, item.to_raw() as i32),
+            Stat::Dropped(item) => (5, item.to_raw() as i32),
+            Stat::Killed(entity) => (6, entity.get()),
+            Stat::KilledBy(entity) => (7, entity.get()),
+            Stat::Custom(stat) => (8, stat.to_raw()),
+        }
+    }
+
+    /// Returns the names of the category and statistic, without the
+    /// `minecraft:` namespace.
+    pub fn to_str(self) -> (&'static str, &'static str) {
+        match self {
+            Stat::Mined(block) => ("mined", block.to_str()),
+            Stat::Crafted(item) => ("crafted", item.to_str()),
+            Stat::Used(item) => ("used", item.to_str()),
+            Stat::Broken(item) => ("broken", item.to_str()),
+            Stat::PickedUp(item) => ("picked_up", item.to_str()),
+            Stat::Dropped(item) => ("dropped", item.to_str()),
+            Stat::Killed(entity) => ("killed", entity.to_str().unwrap_or_default()),
+            Stat::KilledBy(entity) => ("killed_by", entity.to_str().unwrap_or_default()),
+            Stat::Custom(stat) => ("custom", stat.to_str()),
+        }
+    }
+
+    /// Constructs a statistic from the names of its category and statistic,
+    /// without the `minecraft:` namespace.
+    pub fn from_str(category: &str, name: &str) -> Option<Self> {
+        Some(match category {
+            "mined" => Stat::Mined(BlockKind::from_str(name)?),
+            "crafted" => Stat::Crafted(ItemKind::from_str(name)?),
+            "used" => Stat::Used(ItemKind::from_str(name)?),
+            "broken" => Stat::Broken(ItemKind::from_str(name)?),
+            "picked_up" => Stat::PickedUp(ItemKind::from_str(name)?),
+            "dropped" => Stat::Dropped(ItemKind::from_str(name)?),
+            "killed" => Stat::Killed(EntityKind::from_str(name)?),
+            "killed_by" => Stat::KilledBy(EntityKind::from_str(name)?),
+            "custom" => Stat::Custom(CustomStat::from_str(name)?),
+            _ => return None,
+        })
+    }
+}
+
+/// The statistics of a client.
+#[derive(Component, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Statistics(BTreeMap<Stat, i32>);
+
+impl Statistics {
+    pub fn get(&self, stat: Stat) -> i32 {
+        self.0.get(&stat).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, stat: Stat, value: i32) {
+        if value == 0 {
+            self.0.remove(&stat);
+        } else {
+            self.0.insert(stat, value);
+        }
+    }
+
+    /// Adds `amount` to a statistic, saturating at [`i32::MAX`].
+    pub fn increment(&mut self, stat: Stat, amount: i32) {
+        let value = self.get(stat).saturating_add(amount);
+        self.set(stat, value);
+    }
+
+    /// Returns all statistics that aren't zero.
+    pub fn iter(&self) -> impl Iterator<Item = (Stat, i32)> + '_ {
+        self.0.iter().map(|(&stat, &value)| (stat, value))
+    }
+
+    /// Converts the statistics to the layout of the vanilla statistics file.
+    pub fn save(&self) -> SavedStatistics {
+        let mut saved = SavedStatistics::default();
+
+        for (stat, value) in self.iter() {
+            let (category, name) = stat.to_str();
+
+            let (Ok(category), Ok(name)) = (Ident::new(category), Ident::new(name)) else {
+                continue
+            };
+
+            saved
+                .0
+                .entry(category.to_string_ident())
+                .or_default()
+                .insert(name.to_string_ident(), value);
+        }
+
+        saved
+    }
+
+    /// Restores saved statistics. Statistics that don't exist in this version
+    /// are ignored.
+    pub fn load(saved: &SavedStatistics) -> Self {
+        let mut stats = Self::default();
+
+        for (category, values) in &saved.0 {
+            if category.namespace() != "minecraft" {
+                continue;
+            }
+
+            for (name, &value) in values {
+                if let Some(stat) = Stat::from_str(category.path(), name.path()) {
+                    stats.set(stat, value);
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+/// [`Statistics`] in the layout of the vanilla statistics file, which maps
+/// category identifiers to statistic identifiers and their values.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct SavedStatistics(pub BTreeMap<Ident<String>, BTreeMap<Ident<String>, i32>>);
+
+fn init_statistics(
+    clients: Query<Entity, (Added<Client>, Without<Statistics>)>,
+    mut commands: Commands,
+) {
+    for client in &clients {
+        commands.entity(client).insert(Statistics::default());
+    }
+}
+
+fn count_play_time(mut clients: Query<&mut Statistics>) {
+    for mut stats in &mut clients {
+        stats.increment(Stat::Custom(CustomStat::PlayTime), 1);
+        stats.increment(Stat::Custom(CustomStat::TotalWorldTime), 1);
+    }
+}
+
+fn send_statistics(
+    mut events: EventReader<RequestStatsEvent>,
+    mut clients: Query<(&mut Client, &Statistics)>,
+) {
+    for event in events.iter() {
+        let Ok((mut client, stats)) = clients.get_mut(event.client) else {
+            continue
+        };
+
+        client.write_packet(&StatisticsS2c {
+            statistics: stats
+                .iter()
+                .map(|(stat, value)| {
+                    let (category_id, statistic_id) = stat.to_raw();
+
+                    Statistic {
+                        category_id: VarInt(category_id),
+                        statistic_id: VarInt(statistic_id),
+                        value: VarInt(value),
+                    }
+                })
+                .collect(),
+        });
+    }
+}
@@ -0,0 +1,347 @@
+//! # Text input
+//! Free-form text input from players, using the sign editor or the anvil
+//! rename screen as a text input dialog.
+//!
+//! Insert a [`TextPrompt`] on a client to open the dialog. Once the client is
+//! done typing, a [`TextInputEvent`] is sent with the text and the prompt is
+//! removed. The fake sign or anvil shown to the client is cleaned up
+//! afterwards.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_text_input::{TextInputEvent, TextPrompt};
+//! fn ask_for_name(client: Entity, commands: &mut Commands) {
+//!     commands
+//!         .entity(client)
+//!         .insert(TextPrompt::anvil("Name your pet", "Rex"));
+//! }
+//!
+//! fn read_names(mut events: EventReader<TextInputEvent>) {
+//!     for event in events.iter() {
+//!         if let Some(lines) = &event.lines {
+//!             println!("{:?} named their pet {}", event.client, lines[0]);
+//!         }
+//!     }
+//! }
+//! ```
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::borrow::Cow;
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use valence_block::{BlockEntityKind, BlockState};
+use valence_client::event_loop::{EventLoopSchedule, EventLoopSet, PacketEvent};
+use valence_client::packet::{SignEditorOpenS2c, UpdateSignC2s};
+use valence_client::Client;
+use valence_core::block_pos::BlockPos;
+use valence_core::despawn::Despawned;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_core::protocol::encode::WritePacket;
+use valence_core::protocol::var_int::VarInt;
+use valence_core::text::Text;
+use valence_entity::{Location, Position};
+use valence_instance::block_entity::{BlockEntityData, SignSide, SignText};
+use valence_instance::packet::{BlockEntityUpdateS2c, BlockUpdateS2c};
+use valence_instance::Instance;
+use valence_inventory::packet::RenameItemC2s;
+use valence_inventory::{ClickSlot, ClientInventoryState, Inventory, InventoryKind, OpenInventory};
+
+pub struct TextInputPlugin;
+
+impl Plugin for TextInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TextInputEvent>()
+            .add_systems(
+                (handle_sign_input, handle_anvil_rename)
+                    .in_base_set(EventLoopSet::PreUpdate)
+                    .in_schedule(EventLoopSchedule),
+            )
+            .add_systems(
+                (
+                    handle_anvil_submit,
+                    close_anvil_prompts.after(handle_anvil_submit),
+                    open_text_prompts.after(close_anvil_prompts),
+                )
+                    .in_base_set(CoreSet::Update),
+            );
+    }
+}
+
+/// How far below the client the fake sign is placed. Clients close the sign
+/// editor when they are more than eight blocks away from the sign.
+const SIGN_DEPTH: i32 = 3;
+
+/// A text input dialog shown to a client. Insert this component on a client
+/// to open the dialog. The component is removed once the client is done.
+#[derive(Component, Clone, Debug)]
+pub struct TextPrompt {
+    kind: PromptKind,
+    state: PromptState,
+}
+
+#[derive(Clone, Debug)]
+enum PromptKind {
+    Sign { lines: [Text; 4] },
+    Anvil { title: Text, text: String },
+}
+
+#[derive(Clone, Debug)]
+enum PromptState {
+    Pending,
+    Sign { position: BlockPos },
+    Anvil { inventory: Entity },
+}
+
+impl TextPrompt {
+    /// Opens the sign editor of a fake sign with the given lines. The client
+    /// sends back all four lines, including any that weren't changed.
+    pub fn sign(lines: [Text; 4]) -> Self {
+        Self {
+            kind: PromptKind::Sign { lines },
+            state: PromptState::Pending,
+        }
+    }
+
+    /// Opens the rename screen of a fake anvil, with `text` in the text field.
+    /// The client submits the text by taking the item out of the result slot.
+    pub fn anvil(title: impl Into<Text>, text: impl Into<String>) -> Self {
+        Self {
+            kind: PromptKind::Anvil {
+                title: title.into(),
+                text: text.into(),
+            },
+            state: PromptState::Pending,
+        }
+    }
+}
+
+/// Sent when a client is done with a [`TextPrompt`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TextInputEvent {
+    pub client: Entity,
+    /// The text entered by the client. Signs have four lines and anvils have
+    /// one. `None` if the client closed the prompt without submitting it.
+    pub lines: Option<Vec<String>>,
+}
+
+/// Marks the inventory of an anvil prompt.
+#[derive(Component)]
+struct AnvilPrompt;
+
+fn paper(text: &str) -> ItemStack {
+    ItemStack::new(ItemKind::Paper, 1, None).with_display_name(text.to_owned())
+}
+
+fn open_text_prompts(
+    mut clients: Query<(Entity, &mut Client, &mut TextPrompt, &Position), Changed<TextPrompt>>,
+    mut commands: Commands,
+) {
+    for (entity, mut client, mut prompt, pos) in &mut clients {
+        if !matches!(prompt.state, PromptState::Pending) {
+            continue;
+        }
+
+        match &prompt.kind {
+            PromptKind::Sign { lines } => {
+                let feet = BlockPos::at(pos.0);
+                let position = BlockPos::new(feet.x, feet.y - SIGN_DEPTH, feet.z);
+
+                let sign = SignText {
+                    front: SignSide {
+                        lines: lines.clone(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+
+                client.write_packet(&BlockUpdateS2c {
+                    position,
+                    block_id: VarInt(BlockState::OAK_SIGN.to_raw() as i32),
+                });
+
+                client.write_packet(&BlockEntityUpdateS2c {
+                    position,
+                    kind: VarInt(BlockEntityKind::Sign.id() as i32),
+                    data: Cow::Owned(sign.to_nbt()),
+                });
+
+                client.write_packet(&SignEditorOpenS2c {
+                    location: position,
+                    is_front_text: true,
+                });
+
+                prompt.state = PromptState::Sign { position };
+            }
+            PromptKind::Anvil { title, text } => {
+                let mut inventory = Inventory::with_title(InventoryKind::Anvil, title.clone());
+                inventory.readonly = true;
+                inventory.set_slot(0, paper(text));
+                inventory.set_slot(2, paper(text));
+
+                let inventory = commands.spawn((inventory, AnvilPrompt)).id();
+
+                commands
+                    .entity(entity)
+                    .insert(OpenInventory::new(inventory));
+
+                prompt.state = PromptState::Anvil { inventory };
+            }
+        }
+    }
+}
+
+fn handle_sign_input(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<(&mut Client, &TextPrompt, &Location)>,
+    instances: Query<&Instance>,
+    mut events: EventWriter<TextInputEvent>,
+    mut commands: Commands,
+) {
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<UpdateSignC2s>() else {
+            continue
+        };
+
+        let Ok((mut client, prompt, loc)) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let PromptState::Sign { position } = prompt.state else {
+            continue
+        };
+
+        if pkt.position != position {
+            continue;
+        }
+
+        // Put back the block that was replaced by the sign.
+        if let Some(block) = instances.get(loc.0).ok().and_then(|i| i.block(position)) {
+            client.write_packet(&BlockUpdateS2c {
+                position,
+                block_id: VarInt(block.state().to_raw() as i32),
+            });
+
+            if let (Some(kind), Some(nbt)) = (block.state().block_entity_kind(), block.nbt()) {
+                client.write_packet(&BlockEntityUpdateS2c {
+                    position,
+                    kind: VarInt(kind.id() as i32),
+                    data: Cow::Borrowed(nbt),
+                });
+            }
+        }
+
+        events.send(TextInputEvent {
+            client: packet.client,
+            lines: Some(pkt.lines.iter().map(|line| line.to_string()).collect()),
+        });
+
+        commands.entity(packet.client).remove::<TextPrompt>();
+    }
+}
+
+fn handle_anvil_rename(
+    mut packets: EventReader<PacketEvent>,
+    mut clients: Query<&mut TextPrompt>,
+    mut inventories: Query<&mut Inventory, With<AnvilPrompt>>,
+) {
+    for packet in packets.iter() {
+        let Some(pkt) = packet.decode::<RenameItemC2s>() else {
+            continue
+        };
+
+        let Ok(mut prompt) = clients.get_mut(packet.client) else {
+            continue
+        };
+
+        let TextPrompt {
+            kind: PromptKind::Anvil { text, .. },
+            state: PromptState::Anvil { inventory },
+        } = &mut *prompt else {
+            continue
+        };
+
+        // Vanilla clients don't allow names longer than 50 characters.
+        *text = pkt.item_name.chars().take(50).collect();
+
+        if let Ok(mut inventory) = inventories.get_mut(*inventory) {
+            inventory.set_slot(2, paper(text));
+        }
+    }
+}
+
+fn handle_anvil_submit(
+    mut click_events: EventReader<ClickSlot>,
+    clients: Query<(&TextPrompt, &ClientInventoryState)>,
+    mut events: EventWriter<TextInputEvent>,
+    mut commands: Commands,
+) {
+    for click in click_events.iter() {
+        let Ok((prompt, inv_state)) = clients.get(click.client) else {
+            continue
+        };
+
+        let TextPrompt {
+            kind: PromptKind::Anvil { text, .. },
+            state: PromptState::Anvil { inventory },
+        } = prompt else {
+            continue
+        };
+
+        if click.window_id != inv_state.window_id() || click.slot_id != 2 {
+            continue;
+        }
+
+        events.send(TextInputEvent {
+            client: click.client,
+            lines: Some(vec![text.clone()]),
+        });
+
+        commands.entity(*inventory).insert(Despawned);
+        commands
+            .entity(click.client)
+            .remove::<(TextPrompt, OpenInventory)>();
+    }
+}
+
+/// Cancels anvil prompts whose anvil was closed by the client.
+fn close_anvil_prompts(
+    clients: Query<(Entity, &TextPrompt, Option<&OpenInventory>)>,
+    mut events: EventWriter<TextInputEvent>,
+    mut commands: Commands,
+) {
+    for (entity, prompt, open_inventory) in &clients {
+        let PromptState::Anvil { inventory } = prompt.state else {
+            continue
+        };
+
+        if open_inventory.is_some_and(|open| open.entity == inventory) {
+            continue;
+        }
+
+        events.send(TextInputEvent {
+            client: entity,
+            lines: None,
+        });
+
+        commands.entity(inventory).insert(Despawned);
+        commands.entity(entity).remove::<TextPrompt>();
+    }
+}
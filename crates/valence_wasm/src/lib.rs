@@ -0,0 +1,810 @@
+//! # WebAssembly plugins
+//! Gameplay logic loaded from WebAssembly modules at runtime.
+//!
+//! With the [`WasmPlugin`] added, every `.wasm` file in
+//! [`WasmSettings::directory`] is loaded when the server starts, and plugins
+//! are reloaded when their files change. More plugins can be loaded with the
+//! [`WasmPlugins`] resource.
+//!
+//! # Plugin API
+//!
+//! Plugins import these functions from the `valence` module. Entities are
+//! passed as the `u64` bits of their [`Entity`], and strings as a pointer and
+//! length of UTF-8 in the memory of the plugin.
+//!
+//! | Function | Description |
+//! |---|---|
+//! | `log(ptr: u32, len: u32)` | Logs a message. |
+//! | `subscribe(kind: u32)` | Subscribes to a [`PluginEventKind`]. |
+//! | `send_message(client: u64, ptr: u32, len: u32) -> u32` | Sends a chat message to a client. Returns `0` if the client doesn't exist. |
+//! | `client_instance(client: u64) -> u64` | The instance a client is in, or `u64::MAX` if the client doesn't exist. |
+//! | `set_block(instance: u64, x: i32, y: i32, z: i32, state: u32) -> u32` | Sets a block to the raw ID of a [`BlockState`]. Returns `0` if the instance doesn't exist or the position isn't loaded. |
+//! | `spawn_entity(instance: u64, kind: i32, x: f64, y: f64, z: f64) -> u64` | Spawns an entity of an [`EntityKind`] other than players. Returns the entity, or `u64::MAX` if the instance doesn't exist. |
+//! | `despawn(entity: u64) -> u32` | Despawns an entity that isn't a client. Returns `0` if there is no such entity. |
+//!
+//! Plugins can export these functions, which are all optional.
+//!
+//! | Function | Description |
+//! |---|---|
+//! | `valence_init()` | Called once after the plugin is loaded. |
+//! | `valence_on_event(kind: u32, entity: u64, ptr: u32, len: u32)` | Called for the events the plugin subscribed to. `entity` is `u64::MAX` for events without an entity. |
+//! | `valence_alloc(len: u32) -> u32` | Allocates memory for the data of an event. Required for events with data. |
+//!
+//! The memory of the plugin must be exported as `memory`.
+//!
+//! # Limits
+//!
+//! Each call into a plugin gets [`WasmSettings::fuel_per_call`] fuel, which
+//! is consumed as the plugin runs, and plugins can't grow their memory beyond
+//! [`WasmSettings::max_memory`]. Calls that run out of fuel or trap are
+//! stopped and logged, without affecting the server.
+//!
+//! [`BlockState`]: valence_block::BlockState
+//! [`EntityKind`]: valence_entity::EntityKind
+#![allow(clippy::type_complexity)]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    unreachable_pub,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{bail, ensure, Context};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use glam::DVec3;
+use tracing::{error, info, warn};
+use valence_block::BlockState;
+use valence_client::message::{ChatMessageEvent, SendMessage};
+use valence_client::{Client, Username};
+use valence_core::block_pos::BlockPos;
+use valence_core::despawn::Despawned;
+use valence_core::uuid::UniqueId;
+use valence_entity::{
+    EntityAnimations, EntityId, EntityKind, EntityStatuses, HeadYaw, Location, Look, ObjectData,
+    OldLocation, OldPosition, OnGround, PacketByteRange, Position, TrackedData, Velocity,
+};
+use valence_instance::Instance;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+pub struct WasmPlugin;
+
+impl Plugin for WasmPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(WasmSettings::default)
+            .clone();
+
+        match WasmPlugins::new(settings) {
+            Ok(plugins) => {
+                app.insert_resource(plugins)
+                    .add_startup_system(load_plugin_directory)
+                    .add_systems((collect_plugin_events, run_plugins).chain());
+            }
+            Err(e) => error!("failed to build wasm plugin: {e:#}"),
+        }
+    }
+}
+
+/// Settings for [`WasmPlugin`]. Changes have no effect after the plugin is
+/// built.
+#[derive(Resource, Clone, PartialEq, Eq, Debug)]
+pub struct WasmSettings {
+    /// The directory the plugins are loaded from when the server starts.
+    ///
+    /// # Default Value
+    ///
+    /// `plugins`
+    pub directory: PathBuf,
+    /// The fuel each call into a plugin gets. Roughly one unit of fuel is
+    /// consumed for each WebAssembly instruction.
+    ///
+    /// # Default Value
+    ///
+    /// `10_000_000`
+    pub fuel_per_call: u64,
+    /// The maximum size of the memory of a plugin in bytes.
+    ///
+    /// # Default Value
+    ///
+    /// 64 MiB
+    pub max_memory: usize,
+    /// If plugins loaded from files are reloaded when the files change.
+    ///
+    /// # Default Value
+    ///
+    /// `true`
+    pub hot_reload: bool,
+}
+
+impl Default for WasmSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("plugins"),
+            fuel_per_call: 10_000_000,
+            max_memory: 64 * 1024 * 1024,
+            hot_reload: true,
+        }
+    }
+}
+
+/// The kinds of events plugins can subscribe to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum PluginEventKind {
+    /// Sent every tick, without an entity or data.
+    Tick = 0,
+    /// A client joined. The data is the username of the client.
+    ClientJoin = 1,
+    /// A client left, without data.
+    ClientLeave = 2,
+    /// A client sent a chat message. The data is the message.
+    ChatMessage = 3,
+}
+
+struct PluginEvent {
+    kind: PluginEventKind,
+    entity: Option<Entity>,
+    data: Vec<u8>,
+}
+
+/// The loaded WebAssembly plugins.
+#[derive(Resource)]
+pub struct WasmPlugins {
+    engine: Engine,
+    linker: Linker<HostState>,
+    settings: WasmSettings,
+    plugins: BTreeMap<String, LoadedPlugin>,
+    /// Events waiting to be sent to plugins.
+    pending: Vec<PluginEvent>,
+    ticks: u64,
+}
+
+struct LoadedPlugin {
+    /// The file the plugin was loaded from.
+    path: Option<PathBuf>,
+    /// When the file was last modified, to detect changes.
+    modified: Option<SystemTime>,
+    store: Store<HostState>,
+    memory: Option<Memory>,
+    /// Taken once the plugin is initialized.
+    init: Option<TypedFunc<(), ()>>,
+    on_event: Option<TypedFunc<(u32, u64, u32, u32), ()>>,
+    alloc: Option<TypedFunc<u32, u32>>,
+}
+
+struct HostState {
+    plugin: String,
+    /// The world of the server during calls into the plugin, and an empty
+    /// world otherwise.
+    world: World,
+    /// A bit for each [`PluginEventKind`] the plugin subscribed to.
+    subscriptions: u32,
+    limits: StoreLimits,
+}
+
+impl WasmPlugins {
+    pub fn new(settings: WasmSettings) -> anyhow::Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config)?;
+        let mut linker = Linker::new(&engine);
+        link_host_api(&mut linker)?;
+
+        Ok(Self {
+            engine,
+            linker,
+            settings,
+            plugins: BTreeMap::new(),
+            pending: vec![],
+            ticks: 0,
+        })
+    }
+
+    /// Loads a plugin from a `.wasm` or `.wat` file, named after the file
+    /// without its extension. A plugin with the same name is replaced. The
+    /// plugin is initialized in the next tick.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("invalid plugin file name {}", path.display()))?
+            .to_owned();
+
+        let modified = modified(path);
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+        let mut plugin = self.instantiate(&name, &bytes)?;
+        plugin.path = Some(path.to_owned());
+        plugin.modified = modified;
+
+        self.plugins.insert(name, plugin);
+        Ok(())
+    }
+
+    /// Loads a plugin from the bytes of a WebAssembly module in the binary or
+    /// text format. A plugin with the same name is replaced.
+    pub fn load_bytes(&mut self, name: impl Into<String>, bytes: &[u8]) -> anyhow::Result<()> {
+        let name = name.into();
+        let plugin = self.instantiate(&name, bytes)?;
+
+        self.plugins.insert(name, plugin);
+        Ok(())
+    }
+
+    /// Loads a plugin from its file again.
+    pub fn reload(&mut self, name: &str) -> anyhow::Result<()> {
+        let plugin = self
+            .plugins
+            .get_mut(name)
+            .with_context(|| format!("no plugin named `{name}`"))?;
+
+        let path = plugin
+            .path
+            .clone()
+            .with_context(|| format!("plugin `{name}` was not loaded from a file"))?;
+
+        // Don't retry a failed reload until the file changes again.
+        plugin.modified = modified(&path);
+
+        self.load(path)
+    }
+
+    /// Unloads a plugin, returning if it was loaded. Entities spawned by the
+    /// plugin are not despawned.
+    pub fn unload(&mut self, name: &str) -> bool {
+        self.plugins.remove(name).is_some()
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.plugins.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.plugins.keys().map(|name| name.as_str())
+    }
+
+    fn instantiate(&self, name: &str, bytes: &[u8]) -> anyhow::Result<LoadedPlugin> {
+        let module = Module::new(&self.engine, bytes)
+            .with_context(|| format!("failed to compile plugin `{name}`"))?;
+
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                plugin: name.to_owned(),
+                world: World::new(),
+                subscriptions: 0,
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.settings.max_memory)
+                    .build(),
+            },
+        );
+
+        store.limiter(|state| &mut state.limits);
+        store.add_fuel(self.settings.fuel_per_call)?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &module)
+            .with_context(|| format!("failed to instantiate plugin `{name}`"))?;
+
+        Ok(LoadedPlugin {
+            path: None,
+            modified: None,
+            memory: instance.get_memory(&mut store, "memory"),
+            init: instance.get_typed_func(&mut store, "valence_init").ok(),
+            on_event: instance.get_typed_func(&mut store, "valence_on_event").ok(),
+            alloc: instance.get_typed_func(&mut store, "valence_alloc").ok(),
+            store,
+        })
+    }
+
+    /// Initializes new plugins and sends them the pending events.
+    fn run(&mut self, world: &mut World) {
+        self.ticks += 1;
+
+        // Checking the files once per second is enough.
+        if self.settings.hot_reload && self.ticks % 20 == 0 {
+            self.reload_changed();
+        }
+
+        let fuel = self.settings.fuel_per_call;
+
+        let mut events = std::mem::take(&mut self.pending);
+        events.push(PluginEvent {
+            kind: PluginEventKind::Tick,
+            entity: None,
+            data: vec![],
+        });
+
+        for (name, plugin) in &mut self.plugins {
+            if let Some(init) = plugin.init.take() {
+                if let Err(e) = plugin.call(world, fuel, |store| init.call(store, ())) {
+                    warn!("plugin `{name}` failed to initialize: {e:#}");
+                }
+            }
+
+            for event in &events {
+                if let Err(e) = plugin.dispatch(world, fuel, event) {
+                    warn!("plugin `{name}` failed to handle {:?}: {e:#}", event.kind);
+                }
+            }
+        }
+    }
+
+    fn reload_changed(&mut self) {
+        let changed: Vec<_> = self
+            .plugins
+            .iter()
+            .filter(|(_, plugin)| {
+                plugin
+                    .path
+                    .as_ref()
+                    .map_or(false, |path| modified(path) != plugin.modified)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in changed {
+            match self.reload(&name) {
+                Ok(()) => info!("reloaded plugin `{name}`"),
+                Err(e) => warn!("failed to reload plugin `{name}`: {e:#}"),
+            }
+        }
+    }
+}
+
+impl LoadedPlugin {
+    /// Calls into the plugin with `fuel` and access to the world.
+    fn call<R>(
+        &mut self,
+        world: &mut World,
+        fuel: u64,
+        f: impl FnOnce(&mut Store<HostState>) -> anyhow::Result<R>,
+    ) -> anyhow::Result<R> {
+        let remaining = self.store.consume_fuel(0)?;
+
+        if remaining > fuel {
+            self.store.consume_fuel(remaining - fuel)?;
+        } else {
+            self.store.add_fuel(fuel - remaining)?;
+        }
+
+        // The host API needs the world, so it is moved into the store for the
+        // duration of the call.
+        std::mem::swap(world, &mut self.store.data_mut().world);
+        let res = f(&mut self.store);
+        std::mem::swap(world, &mut self.store.data_mut().world);
+
+        res
+    }
+
+    fn dispatch(
+        &mut self,
+        world: &mut World,
+        fuel: u64,
+        event: &PluginEvent,
+    ) -> anyhow::Result<()> {
+        if self.store.data().subscriptions & (1 << event.kind as u32) == 0 {
+            return Ok(());
+        }
+
+        let Some(on_event) = self.on_event else {
+            bail!("plugin subscribed to events but doesn't export `valence_on_event`")
+        };
+
+        let alloc = self.alloc;
+        let memory = self.memory;
+        let entity = event.entity.map_or(u64::MAX, Entity::to_bits);
+
+        self.call(world, fuel, |store| {
+            let (ptr, len) = if event.data.is_empty() {
+                (0, 0)
+            } else {
+                let (Some(alloc), Some(memory)) = (alloc, memory) else {
+                    bail!("plugin must export `valence_alloc` and `memory` to receive event data")
+                };
+
+                let len = event.data.len() as u32;
+                let ptr = alloc.call(&mut *store, len)?;
+                memory.write(&mut *store, ptr as usize, &event.data)?;
+
+                (ptr, len)
+            };
+
+            on_event.call(store, (event.kind as u32, entity, ptr, len))
+        })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads a UTF-8 string from the memory of the plugin.
+fn read_str(caller: &mut Caller<'_, HostState>, ptr: u32, len: u32) -> anyhow::Result<String> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        bail!("plugin doesn't export `memory`")
+    };
+
+    // The string is sliced from the memory before anything is allocated, so
+    // plugins can't make the server allocate more than their memory size.
+    let start = ptr as usize;
+    let bytes = start
+        .checked_add(len as usize)
+        .and_then(|end| memory.data(&*caller).get(start..end))
+        .with_context(|| format!("string at {ptr} with length {len} is out of bounds"))?;
+
+    Ok(std::str::from_utf8(bytes)?.to_owned())
+}
+
+fn link_host_api(linker: &mut Linker<HostState>) -> anyhow::Result<()> {
+    linker.func_wrap(
+        "valence",
+        "log",
+        |mut caller: Caller<'_, HostState>, ptr: u32, len: u32| -> anyhow::Result<()> {
+            let msg = read_str(&mut caller, ptr, len)?;
+            info!("[{}] {msg}", caller.data().plugin);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "subscribe",
+        |mut caller: Caller<'_, HostState>, kind: u32| -> anyhow::Result<()> {
+            ensure!(
+                kind <= PluginEventKind::ChatMessage as u32,
+                "unknown event kind {kind}"
+            );
+
+            caller.data_mut().subscriptions |= 1 << kind;
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "send_message",
+        |mut caller: Caller<'_, HostState>,
+         client: u64,
+         ptr: u32,
+         len: u32|
+         -> anyhow::Result<u32> {
+            let msg = read_str(&mut caller, ptr, len)?;
+
+            let world = &mut caller.data_mut().world;
+            let Some(mut client) = world.get_mut::<Client>(Entity::from_bits(client)) else {
+                return Ok(0)
+            };
+
+            client.send_chat_message(msg);
+            Ok(1)
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "client_instance",
+        |caller: Caller<'_, HostState>, client: u64| -> u64 {
+            caller
+                .data()
+                .world
+                .get::<Location>(Entity::from_bits(client))
+                .map_or(u64::MAX, |loc| loc.0.to_bits())
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "set_block",
+        |mut caller: Caller<'_, HostState>,
+         instance: u64,
+         x: i32,
+         y: i32,
+         z: i32,
+         state: u32|
+         -> anyhow::Result<u32> {
+            let Some(state) = u16::try_from(state).ok().and_then(BlockState::from_raw) else {
+                bail!("invalid block state {state}")
+            };
+
+            let world = &mut caller.data_mut().world;
+            let Some(mut instance) = world.get_mut::<Instance>(Entity::from_bits(instance)) else {
+                return Ok(0)
+            };
+
+            Ok(instance.set_block(BlockPos::new(x, y, z), state).is_some() as u32)
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "spawn_entity",
+        |mut caller: Caller<'_, HostState>,
+         instance: u64,
+         kind: i32,
+         x: f64,
+         y: f64,
+         z: f64|
+         -> anyhow::Result<u64> {
+            let kind = EntityKind::new(kind);
+            ensure!(
+                kind.to_str().is_some(),
+                "invalid entity kind {}",
+                kind.get()
+            );
+            ensure!(
+                kind != EntityKind::PLAYER,
+                "player entities can't be spawned by plugins"
+            );
+
+            let world = &mut caller.data_mut().world;
+            let instance = Entity::from_bits(instance);
+
+            if world.get::<Instance>(instance).is_none() {
+                return Ok(u64::MAX);
+            }
+
+            let pos = DVec3::new(x, y, z);
+
+            let entity = world
+                .spawn((
+                    (
+                        kind,
+                        EntityId::default(),
+                        UniqueId::default(),
+                        Location(instance),
+                        OldLocation::new(instance),
+                        Position::new(pos),
+                        OldPosition::new(pos),
+                        Look::default(),
+                        HeadYaw::default(),
+                    ),
+                    (
+                        OnGround::default(),
+                        Velocity::default(),
+                        EntityStatuses::default(),
+                        EntityAnimations::default(),
+                        ObjectData::default(),
+                        TrackedData::default(),
+                        PacketByteRange::default(),
+                    ),
+                ))
+                .id();
+
+            Ok(entity.to_bits())
+        },
+    )?;
+
+    linker.func_wrap(
+        "valence",
+        "despawn",
+        |mut caller: Caller<'_, HostState>, entity: u64| -> u32 {
+            let world = &mut caller.data_mut().world;
+
+            match world.get_entity_mut(Entity::from_bits(entity)) {
+                Some(mut entity) if !entity.contains::<Client>() => {
+                    entity.insert(Despawned);
+                    1
+                }
+                _ => 0,
+            }
+        },
+    )?;
+
+    Ok(())
+}
+
+fn load_plugin_directory(mut plugins: ResMut<WasmPlugins>) {
+    let dir = plugins.settings.directory.clone();
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return,
+        Err(e) => {
+            warn!("failed to read plugin directory {}: {e}", dir.display());
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.extension().map_or(false, |ext| ext == "wasm") {
+            match plugins.load(&path) {
+                Ok(()) => info!("loaded plugin {}", path.display()),
+                Err(e) => warn!("failed to load plugin {}: {e:#}", path.display()),
+            }
+        }
+    }
+}
+
+fn collect_plugin_events(
+    mut plugins: ResMut<WasmPlugins>,
+    joined: Query<(Entity, &Username), Added<Client>>,
+    mut left: RemovedComponents<Client>,
+    mut chat: EventReader<ChatMessageEvent>,
+) {
+    for (client, username) in &joined {
+        plugins.pending.push(PluginEvent {
+            kind: PluginEventKind::ClientJoin,
+            entity: Some(client),
+            data: username.0.clone().into_bytes(),
+        });
+    }
+
+    for client in left.iter() {
+        plugins.pending.push(PluginEvent {
+            kind: PluginEventKind::ClientLeave,
+            entity: Some(client),
+            data: vec![],
+        });
+    }
+
+    for event in chat.iter() {
+        plugins.pending.push(PluginEvent {
+            kind: PluginEventKind::ChatMessage,
+            entity: Some(event.client),
+            data: event.message.as_bytes().to_vec(),
+        });
+    }
+}
+
+fn run_plugins(world: &mut World) {
+    world.resource_scope(|world, mut plugins: Mut<WasmPlugins>| plugins.run(world));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESPAWN_ON_JOIN: &str = r#"
+        (module
+            (import "valence" "subscribe" (func $subscribe (param i32)))
+            (import "valence" "despawn" (func $despawn (param i64) (result i32)))
+            (memory (export "memory") 1)
+            (func (export "valence_init")
+                (call $subscribe (i32.const 1)))
+            (func (export "valence_alloc") (param i32) (result i32)
+                (i32.const 16))
+            (func (export "valence_on_event") (param i32 i64 i32 i32)
+                (drop (call $despawn (local.get 1)))))
+    "#;
+
+    #[test]
+    fn events_sent_to_subscribed_plugins() {
+        let mut world = World::new();
+        let mut plugins = WasmPlugins::new(WasmSettings::default()).unwrap();
+
+        plugins
+            .load_bytes("despawn", DESPAWN_ON_JOIN.as_bytes())
+            .unwrap();
+
+        let joined = world.spawn_empty().id();
+        let chatted = world.spawn_empty().id();
+
+        plugins.pending.push(PluginEvent {
+            kind: PluginEventKind::ClientJoin,
+            entity: Some(joined),
+            data: b"Steve".to_vec(),
+        });
+        plugins.pending.push(PluginEvent {
+            kind: PluginEventKind::ChatMessage,
+            entity: Some(chatted),
+            data: b"hello".to_vec(),
+        });
+
+        plugins.run(&mut world);
+
+        assert!(world.entity(joined).contains::<Despawned>());
+        assert!(!world.entity(chatted).contains::<Despawned>());
+    }
+
+    #[test]
+    fn calls_limited_by_fuel() {
+        let mut world = World::new();
+        let mut plugins = WasmPlugins::new(WasmSettings {
+            fuel_per_call: 10_000,
+            ..Default::default()
+        })
+        .unwrap();
+
+        plugins
+            .load_bytes(
+                "spin",
+                br#"(module (func (export "valence_init") (loop $l (br $l))))"#,
+            )
+            .unwrap();
+
+        let plugin = plugins.plugins.get_mut("spin").unwrap();
+        let init = plugin.init.unwrap();
+
+        assert!(plugin
+            .call(&mut world, 10_000, |store| init.call(store, ()))
+            .is_err());
+    }
+
+    #[test]
+    fn out_of_bounds_strings_rejected() {
+        let mut world = World::new();
+        let mut plugins = WasmPlugins::new(WasmSettings::default()).unwrap();
+
+        for (name, ptr, len) in [("huge", 0, u32::MAX), ("past_end", 65535, 2)] {
+            let module = format!(
+                r#"
+                (module
+                    (import "valence" "log" (func $log (param i32 i32)))
+                    (memory (export "memory") 1)
+                    (func (export "valence_init")
+                        (call $log (i32.const {ptr}) (i32.const {len}))))
+                "#
+            );
+
+            plugins.load_bytes(name, module.as_bytes()).unwrap();
+
+            let plugin = plugins.plugins.get_mut(name).unwrap();
+            let init = plugin.init.unwrap();
+
+            let err = plugin
+                .call(&mut world, 10_000, |store| init.call(store, ()))
+                .unwrap_err();
+
+            assert!(format!("{err:#}").contains("out of bounds"), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn spawning_players_rejected() {
+        let mut world = World::new();
+        let mut plugins = WasmPlugins::new(WasmSettings::default()).unwrap();
+
+        let module = format!(
+            r#"
+            (module
+                (import "valence" "spawn_entity"
+                    (func $spawn_entity (param i64 i32 f64 f64 f64) (result i64)))
+                (memory (export "memory") 1)
+                (func (export "valence_init")
+                    (drop (call $spawn_entity
+                        (i64.const 0) (i32.const {}) (f64.const 0) (f64.const 0) (f64.const 0)))))
+            "#,
+            EntityKind::PLAYER.get()
+        );
+
+        plugins.load_bytes("spawner", module.as_bytes()).unwrap();
+
+        let plugin = plugins.plugins.get_mut("spawner").unwrap();
+        let init = plugin.init.unwrap();
+
+        let err = plugin
+            .call(&mut world, 10_000, |store| init.call(store, ()))
+            .unwrap_err();
+
+        assert!(format!("{err:#}").contains("can't be spawned"), "{err:#}");
+    }
+}
@@ -9,9 +9,13 @@
 //! [`WorldBorderBundle`] bundle into a [`Instance`].
 //! Use [`WorldBorderBundle::default()`] to use Minecraft Vanilla border default
 //! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_world_border::*;
+//! # fn example(mut commands: Commands, instance_entity: Entity) {
 //! commands
 //!     .entity(instance_entity)
 //!     .insert(WorldBorderBundle::new([0.0, 0.0], 10.0));
+//! # }
 //! ```
 //!
 //!
@@ -20,8 +24,12 @@
 //! Setting duration to 0 will move the border to `new_diameter` immediately,
 //! otherwise, it will interpolate to `new_diameter` over `duration` time.
 //! ```
+//! # use std::time::Duration;
+//! # use bevy_ecs::prelude::*;
+//! # use valence_world_border::*;
 //! fn change_diameter(
-//!     event_writer: EventWriter<SetWorldBorderSizeEvent>,
+//!     mut event_writer: EventWriter<SetWorldBorderSizeEvent>,
+//!     entity: Entity,
 //!     diameter: f64,
 //!     duration: Duration,
 //! ) {
@@ -42,6 +50,24 @@
 //! diameter size, do not modify the value directly! Use
 //! [`SetWorldBorderSizeEvent`] instead.
 //!
+//! ## Checking if a position is inside the world border
+//! Use the [`WorldBorder`] query to check if a position is within the current
+//! bounds of an instance's world border.
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use valence_entity::{Location, Position};
+//! # use valence_world_border::WorldBorder;
+//! fn check_position(clients: Query<(&Position, &Location)>, borders: Query<WorldBorder>) {
+//!     for (pos, loc) in &clients {
+//!         if let Ok(border) = borders.get(loc.0) {
+//!             if !border.contains(pos.0) {
+//!                 // Outside of the border!
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
 //! ## Access other world border properties.
 //! Access to the rest of the world border properties is fairly straightforward
 //! by querying their respective component. [`WorldBorderBundle`] contains
@@ -71,9 +97,10 @@ pub mod packet;
 use std::time::{Duration, Instant};
 
 use bevy_app::{App, CoreSet, Plugin};
-use glam::DVec2;
+use bevy_ecs::query::WorldQuery;
+use glam::{DVec2, DVec3};
 use packet::*;
-use valence_client::{Client, FlushPacketsSet};
+use valence_client::{Client, FlushPacketsSet, UpdateClientsSet};
 use valence_core::protocol::encode::WritePacket;
 use valence_core::protocol::var_int::VarInt;
 use valence_core::protocol::var_long::VarLong;
@@ -105,6 +132,7 @@ impl Plugin for WorldBorderPlugin {
         .configure_set(
             UpdateWorldBorderPerClientSet
                 .in_base_set(CoreSet::PostUpdate)
+                .after(UpdateClientsSet)
                 .before(FlushPacketsSet),
         )
         .add_event::<SetWorldBorderSizeEvent>()
@@ -187,6 +215,37 @@ impl WorldBorderDiameter {
     }
 }
 
+/// A query for the current bounds of an instance's world border.
+///
+/// The world border is a square centered on [`WorldBorderCenter`] with sides
+/// of length [`WorldBorderDiameter`].
+#[derive(WorldQuery, Copy, Clone)]
+pub struct WorldBorder {
+    pub center: &'static WorldBorderCenter,
+    pub diameter: &'static WorldBorderDiameter,
+}
+
+impl WorldBorderItem<'_> {
+    /// Returns whether the given position is inside the world border. Only
+    /// the X and Z coordinates are considered.
+    pub fn contains(&self, pos: impl Into<DVec3>) -> bool {
+        self.distance_to_edge(pos) > 0.0
+    }
+
+    /// Returns the horizontal distance from the given position to the closest
+    /// edge of the world border. The distance is negative if the position is
+    /// outside the border.
+    pub fn distance_to_edge(&self, pos: impl Into<DVec3>) -> f64 {
+        let pos = pos.into();
+        let radius = self.diameter.0 / 2.0;
+
+        let dx = (pos.x - self.center.0.x).abs();
+        let dz = (pos.z - self.center.0.y).abs();
+
+        (radius - dx).min(radius - dz)
+    }
+}
+
 /// This component represents the `Set Border Lerp Size` packet with timestamp.
 /// It is used for actually lerping the world border diameter.
 /// If you need to set the diameter, it is much better to use the
@@ -220,8 +279,12 @@ impl MovingWorldBorder {
 /// Setting duration to 0 will move the border to `new_diameter` immediately,
 /// otherwise it will interpolate to `new_diameter` over `duration` time.
 /// ```
+/// # use std::time::Duration;
+/// # use bevy_ecs::prelude::*;
+/// # use valence_world_border::*;
 /// fn change_diameter(
-///     event_writer: EventWriter<SetWorldBorderSizeEvent>,
+///     mut event_writer: EventWriter<SetWorldBorderSizeEvent>,
+///     entity: Entity,
 ///     diameter: f64,
 ///     duration: Duration,
 /// ) {
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use valence::advancement::bevy_hierarchy::{BuildChildren, Children, Parent};
+use valence::advancement::progress::{AdvancementProgressStorage, SavedAdvancementProgress};
 use valence::advancement::ForceTabUpdate;
 use valence::prelude::*;
 
@@ -13,14 +14,11 @@ struct Root2Criteria;
 #[derive(Component)]
 struct RootAdvancement;
 
-#[derive(Component)]
-struct RootCriteriaDone(bool);
-
 #[derive(Component)]
 struct TabChangeCount(u8);
 
 #[derive(Resource, Default)]
-struct ClientSave(HashMap<Uuid, (bool, u8)>);
+struct ClientSave(HashMap<Uuid, (SavedAdvancementProgress, u8)>);
 
 fn main() {
     App::new()
@@ -178,90 +176,77 @@ fn load_clients(
     mut commands: Commands,
     clients: Query<(Entity, &UniqueId), Added<Client>>,
     mut client_save: ResMut<ClientSave>,
+    storage: AdvancementProgressStorage,
 ) {
     for (client, uuid) in clients.iter() {
-        let (root_criteria_done, tab_change_count) =
-            client_save.0.entry(uuid.0).or_insert((false, 0));
+        let (progress, tab_change_count) = client_save.0.entry(uuid.0).or_default();
 
-        commands.entity(client).insert((
-            RootCriteriaDone(*root_criteria_done),
-            TabChangeCount(*tab_change_count),
-        ));
+        commands
+            .entity(client)
+            .insert((storage.load(progress), TabChangeCount(*tab_change_count)));
     }
 }
 
 fn init_advancements(
-    mut clients: Query<
-        (
-            &mut AdvancementClientUpdate,
-            &RootCriteriaDone,
-            &TabChangeCount,
-        ),
-        Added<AdvancementClientUpdate>,
-    >,
+    mut clients: Query<&mut AdvancementClientUpdate, Added<AdvancementClientUpdate>>,
     root_advancement_query: Query<Entity, (Without<Parent>, With<Advancement>)>,
     children_query: Query<&Children>,
     advancement_check_query: Query<(), With<Advancement>>,
-    root2_criteria: Query<Entity, With<Root2Criteria>>,
-    root_criteria: Query<Entity, With<RootCriteria>>,
 ) {
-    let root_c = root_criteria.single();
-    let root2_c = root2_criteria.single();
-    for (mut advancement_client_update, root_criteria, tab_change) in clients.iter_mut() {
+    for mut advancement_client_update in clients.iter_mut() {
+        // The saved progress is sent along with the advancements.
         for root_advancement in root_advancement_query.iter() {
             advancement_client_update.send_advancements(
                 root_advancement,
                 &children_query,
                 &advancement_check_query,
             );
-            if root_criteria.0 {
-                advancement_client_update.criteria_done(root_c);
-            }
-            if tab_change.0 > 5 {
-                advancement_client_update.criteria_done(root2_c);
-            }
         }
     }
 }
 
 fn sneak(
     mut sneaking: EventReader<SneakEvent>,
-    mut client: Query<(&mut AdvancementClientUpdate, &mut RootCriteriaDone)>,
+    mut client: Query<&mut AdvancementProgress>,
     root_criteria: Query<Entity, With<RootCriteria>>,
     client_uuid: Query<&UniqueId>,
     mut client_save: ResMut<ClientSave>,
+    storage: AdvancementProgressStorage,
 ) {
     let root_criteria = root_criteria.single();
     for sneaking in sneaking.iter() {
         if sneaking.state == SneakState::Stop {
             continue;
         }
-        let Ok((mut advancement_client_update, mut root_criteria_done)) = client.get_mut(sneaking.client) else { continue; };
-        root_criteria_done.0 = !root_criteria_done.0;
-        match root_criteria_done.0 {
-            true => advancement_client_update.criteria_done(root_criteria),
-            false => advancement_client_update.criteria_undone(root_criteria),
+        let Ok(mut progress) = client.get_mut(sneaking.client) else { continue; };
+        if !progress.revoke(root_criteria) {
+            progress.grant(root_criteria);
         }
         client_save
             .0
             .get_mut(&client_uuid.get(sneaking.client).unwrap().0)
             .unwrap()
-            .0 = root_criteria_done.0;
+            .0 = storage.save(&progress);
     }
 }
 
 fn tab_change(
     mut tab_change: EventReader<AdvancementTabChange>,
-    mut client: Query<(&mut AdvancementClientUpdate, &mut TabChangeCount)>,
+    mut client: Query<(
+        &mut AdvancementClientUpdate,
+        &mut AdvancementProgress,
+        &mut TabChangeCount,
+    )>,
     root2_criteria: Query<Entity, With<Root2Criteria>>,
     root: Query<Entity, With<RootAdvancement>>,
     client_uuid: Query<&UniqueId>,
     mut client_save: ResMut<ClientSave>,
+    storage: AdvancementProgressStorage,
 ) {
     let root2_criteria = root2_criteria.single();
     let root = root.single();
     for tab_change in tab_change.iter() {
-        let Ok((mut advancement_client_update, mut tab_change_count)) = client.get_mut(tab_change.client) else { continue; };
+        let Ok((mut advancement_client_update, mut progress, mut tab_change_count)) = client.get_mut(tab_change.client) else { continue; };
         if let Some(ref opened) = tab_change.opened_tab {
             if opened.as_str() == "custom:root2" {
                 tab_change_count.0 += 1;
@@ -272,14 +257,15 @@ fn tab_change(
             continue;
         }
         if tab_change_count.0 == 5 {
-            advancement_client_update.criteria_done(root2_criteria);
+            progress.grant(root2_criteria);
         } else if tab_change_count.0 >= 10 {
             advancement_client_update.force_tab_update = ForceTabUpdate::Spec(root);
         }
-        client_save
+        let save = client_save
             .0
             .get_mut(&client_uuid.get(tab_change.client).unwrap().0)
-            .unwrap()
-            .1 = tab_change_count.0;
+            .unwrap();
+        save.0 = storage.save(&progress);
+        save.1 = tab_change_count.0;
     }
 }
@@ -1,6 +1,6 @@
 #![allow(clippy::type_complexity)]
 
-use valence::nbt::{compound, List};
+use valence::instance::block_entity::{BlockEntityData, SignText, SkullProfile};
 use valence::prelude::*;
 use valence_client::interact_block::InteractBlockEvent;
 use valence_client::message::ChatMessageEvent;
@@ -43,13 +43,14 @@ fn setup(
         [3, FLOOR_Y + 1, 1],
         BlockState::CHEST.set(PropName::Facing, PropValue::West),
     );
+    let mut sign = SignText::default();
+    sign.front.lines[0] = "Type in chat:".color(Color::RED);
+
     instance.set_block(
         SIGN_POS,
         Block::with_nbt(
             BlockState::OAK_SIGN.set(PropName::Rotation, PropValue::_4),
-            compound! {
-                "Text1" => "Type in chat:".color(Color::RED),
-            },
+            sign.to_nbt(),
         ),
     );
     instance.set_block(
@@ -90,8 +91,10 @@ fn event_handler(
 
         let mut sign = instance.block_mut(SIGN_POS).unwrap();
         let nbt = sign.nbt_mut().unwrap();
-        nbt.insert("Text2", message.to_string().color(Color::DARK_GREEN));
-        nbt.insert("Text3", format!("~{}", username).italic());
+        let mut text = SignText::from_nbt(nbt);
+        text.front.lines[1] = message.to_string().color(Color::DARK_GREEN);
+        text.front.lines[2] = format!("~{}", username).italic();
+        text.write_nbt(nbt);
     }
 
     for InteractBlockEvent {
@@ -112,16 +115,12 @@ fn event_handler(
 
             let mut skull = instance.block_mut(SKULL_POS).unwrap();
             let nbt = skull.nbt_mut().unwrap();
-            *nbt = compound! {
-                "SkullOwner" => compound! {
-                    "Id" => uuid.0,
-                    "Properties" => compound! {
-                        "textures" => List::Compound(vec![compound! {
-                            "Value" => textures.value.clone(),
-                        }])
-                    }
-                }
-            };
+            SkullProfile {
+                uuid: Some(uuid.0),
+                name: None,
+                textures: Some(textures.clone()),
+            }
+            .write_nbt(nbt);
         }
     }
 }
@@ -2,7 +2,6 @@
 
 use valence::prelude::*;
 use valence_client::message::SendMessage;
-use valence_client::status::RequestRespawnEvent;
 
 const SPAWN_Y: i32 = 64;
 
@@ -65,33 +64,34 @@ fn init_clients(
     }
 }
 
-fn squat_and_die(mut clients: Query<&mut Client>, mut events: EventReader<SneakEvent>) {
+fn squat_and_die(mut clients: Query<&mut Health>, mut events: EventReader<SneakEvent>) {
     for event in events.iter() {
         if event.state == SneakState::Start {
-            if let Ok(mut client) = clients.get_mut(event.client) {
-                client.kill("Squatted too hard.");
+            if let Ok(mut health) = clients.get_mut(event.client) {
+                health.0 = 0.0;
             }
         }
     }
 }
 
 fn necromancy(
-    mut clients: Query<(&mut Position, &mut Look, &mut Location)>,
-    mut events: EventReader<RequestRespawnEvent>,
+    mut clients: Query<&mut RespawnPoint>,
+    mut events: EventReader<PlayerDeathEvent>,
     instances: Query<Entity, With<Instance>>,
 ) {
     for event in events.iter() {
-        if let Ok((mut pos, mut look, mut loc)) = clients.get_mut(event.client) {
-            pos.set([0.0, SPAWN_Y as f64 + 1.0, 0.0]);
-            look.yaw = 0.0;
-            look.pitch = 0.0;
-
+        if let Ok(mut point) = clients.get_mut(event.client) {
             // make the client respawn in another instance
-            let idx = instances.iter().position(|i| i == loc.0).unwrap();
+            let idx = instances.iter().position(|i| i == event.instance).unwrap();
 
             let count = instances.iter().len();
 
-            loc.0 = instances.into_iter().nth((idx + 1) % count).unwrap();
+            *point = RespawnPoint::Custom {
+                instance: instances.iter().nth((idx + 1) % count).unwrap(),
+                position: DVec3::new(0.0, SPAWN_Y as f64 + 1.0, 0.0),
+                yaw: 0.0,
+                pitch: 0.0,
+            };
         }
     }
 }
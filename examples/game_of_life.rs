@@ -195,9 +195,9 @@ fn pause_on_crouch(
 
             for mut client in clients.iter_mut() {
                 if board.paused {
-                    client.set_action_bar("Paused".italic().color(Color::RED));
+                    client.action_bar("Paused".italic().color(Color::RED));
                 } else {
-                    client.set_action_bar("Playing".italic().color(Color::GREEN));
+                    client.action_bar("Playing".italic().color(Color::GREEN));
                 }
             }
         }
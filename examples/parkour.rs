@@ -172,15 +172,21 @@ fn manage_blocks(mut clients: Query<(&mut Client, &Position, &mut GameState, &mu
 
                 let pitch = 0.9 + ((state.combo as f32) - 1.0) * 0.05;
                 client.play_sound(
+                    pos.0,
                     Sound::BlockNoteBlockBass,
                     SoundCategory::Master,
-                    pos.0,
                     1.0,
                     pitch,
+                    rand::random(),
                 );
 
-                client.set_title("");
-                client.set_subtitle(state.score.to_string().color(Color::LIGHT_PURPLE).bold());
+                client.set_title(
+                    "",
+                    state.score.to_string().color(Color::LIGHT_PURPLE).bold(),
+                    10,
+                    70,
+                    20,
+                );
             }
         }
     }
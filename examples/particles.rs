@@ -74,8 +74,8 @@ fn manage_particles(
 
     let mut instance = instances.single_mut();
 
-    instance.play_particle(particle, true, pos, offset, 0.1, 100);
-    instance.set_action_bar(name.bold());
+    instance.spawn_particle(particle, pos, offset, 100, 0.1, true);
+    instance.action_bar(name.bold());
 }
 
 fn dbg_name(dbg: &impl fmt::Debug) -> String {
@@ -125,9 +125,7 @@ fn create_particle_vec() -> Vec<Particle> {
         Particle::Firework,
         Particle::Fishing,
         Particle::Flame,
-        Particle::DrippingCherryLeaves,
-        Particle::FallingCherryLeaves,
-        Particle::LandingCherryLeaves,
+        Particle::CherryLeaves,
         Particle::SculkSoul,
         Particle::SculkCharge { roll: 1.0 },
         Particle::SculkChargePop,
@@ -201,5 +199,6 @@ fn create_particle_vec() -> Vec<Particle> {
         Particle::ElectricSpark,
         Particle::Scrape,
         Particle::Shriek { delay: 0 },
+        Particle::EggCrack,
     ]
 }
@@ -28,15 +28,53 @@ mod tests;
 
 #[cfg(feature = "advancement")]
 pub use valence_advancement as advancement;
+#[cfg(feature = "anticheat")]
+pub use valence_anticheat as anticheat;
 #[cfg(feature = "anvil")]
 pub use valence_anvil as anvil;
+#[cfg(feature = "boss_bar")]
+pub use valence_boss_bar as boss_bar;
+#[cfg(feature = "combat")]
+pub use valence_combat as combat;
+#[cfg(feature = "command")]
+pub use valence_command as command;
 pub use valence_core::*;
+#[cfg(feature = "experience")]
+pub use valence_experience as experience;
+#[cfg(feature = "health")]
+pub use valence_health as health;
+#[cfg(feature = "interaction")]
+pub use valence_interaction as interaction;
 #[cfg(feature = "inventory")]
 pub use valence_inventory as inventory;
+#[cfg(feature = "localization")]
+pub use valence_localization as localization;
+#[cfg(feature = "map")]
+pub use valence_map as map;
+#[cfg(feature = "menu")]
+pub use valence_menu as menu;
 #[cfg(feature = "network")]
 pub use valence_network as network;
+#[cfg(feature = "permission")]
+pub use valence_permission as permission;
 #[cfg(feature = "player_list")]
 pub use valence_player_list as player_list;
+#[cfg(feature = "recipe")]
+pub use valence_recipe as recipe;
+#[cfg(feature = "respawn")]
+pub use valence_respawn as respawn;
+#[cfg(feature = "scoreboard")]
+pub use valence_scoreboard as scoreboard;
+#[cfg(feature = "script")]
+pub use valence_script as script;
+#[cfg(feature = "spatial_index")]
+pub use valence_spatial_index as spatial_index;
+#[cfg(feature = "statistics")]
+pub use valence_statistics as statistics;
+#[cfg(feature = "text_input")]
+pub use valence_text_input as text_input;
+#[cfg(feature = "wasm")]
+pub use valence_wasm as wasm;
 #[cfg(feature = "world_border")]
 pub use valence_world_border as world_border;
 pub use {
@@ -65,23 +103,39 @@ pub mod prelude {
     pub use ident::Ident;
     #[cfg(feature = "advancement")]
     pub use valence_advancement::{
-        event::AdvancementTabChange, Advancement, AdvancementBundle, AdvancementClientUpdate,
-        AdvancementCriteria, AdvancementDisplay, AdvancementFrameType, AdvancementRequirements,
+        event::AdvancementTabChange,
+        progress::{AdvancementDone, AdvancementProgress},
+        Advancement, AdvancementBundle, AdvancementClientUpdate, AdvancementCriteria,
+        AdvancementDisplay, AdvancementFrameType, AdvancementRequirements,
     };
     pub use valence_biome::{Biome, BiomeId, BiomeRegistry};
     pub use valence_block::{BlockKind, BlockState, PropName, PropValue};
+    #[cfg(feature = "boss_bar")]
+    pub use valence_boss_bar::{
+        BossBarBundle, BossBarHealth, BossBarStyle, BossBarTitle, BossBarViewers,
+    };
+    pub use valence_client::abilities::{PlayerAbilities, ToggleFlyEvent};
     pub use valence_client::action::{DiggingEvent, DiggingState};
     pub use valence_client::command::{
         ClientCommand, JumpWithHorseEvent, JumpWithHorseState, LeaveBedEvent, SneakEvent,
         SneakState, SprintEvent, SprintState,
     };
+    pub use valence_client::custom_payload::{ClientChannels, CustomPayloadEvent, PluginChannels};
     pub use valence_client::event_loop::{EventLoopSchedule, EventLoopSet};
     pub use valence_client::interact_entity::{EntityInteraction, InteractEntityEvent};
-    pub use valence_client::title::SetTitle as _;
+    pub use valence_client::title::{ActionBarQueue, SetTitle as _};
     pub use valence_client::{
-        despawn_disconnected_clients, Client, CompassPos, DeathLocation, HasRespawnScreen,
-        HashedSeed, Ip, IsDebug, IsFlat, IsHardcore, OldView, OldViewDistance, PrevGameMode,
-        Properties, ReducedDebugInfo, Username, View, ViewDistance,
+        despawn_disconnected_clients, Client, CompassAngle, CompassPos, DeathLocation,
+        HasRespawnScreen, HashedSeed, Ip, IsDebug, IsFlat, IsHardcore, OldView, OldViewDistance,
+        PrevGameMode, Properties, ReducedDebugInfo, Username, View, ViewDistance,
+    };
+    #[cfg(feature = "combat")]
+    pub use valence_combat::{PostAttackEvent, PreAttackEvents};
+    #[cfg(feature = "command")]
+    pub use valence_command::parse::CommandArgs;
+    #[cfg(feature = "command")]
+    pub use valence_command::{
+        argument, literal, CommandExecutionEvent, CommandNode, CommandRegistry,
     };
     pub use valence_core::block_pos::BlockPos;
     pub use valence_core::chunk_pos::{ChunkPos, ChunkView};
@@ -96,24 +150,68 @@ pub mod prelude {
     pub use valence_core::uuid::UniqueId;
     pub use valence_core::{translation_key, CoreSettings, Server};
     pub use valence_dimension::{DimensionType, DimensionTypeRegistry};
+    pub use valence_entity::equipment::{Equipment, EquipmentSlot};
     pub use valence_entity::hitbox::{Hitbox, HitboxShape};
     pub use valence_entity::{
         EntityAnimation, EntityKind, EntityManager, EntityStatus, HeadYaw, Location, Look,
         OldLocation, OldPosition, Position,
     };
+    #[cfg(feature = "experience")]
+    pub use valence_experience::{Experience, ExperienceOrbBundle};
+    #[cfg(feature = "health")]
+    pub use valence_health::{
+        DamageEvent, DamageTypeRegistry, DeathEvent, Food, Health, Saturation,
+    };
     pub use valence_instance::{Block, BlockMut, BlockRef, Chunk, Instance};
+    #[cfg(feature = "interaction")]
+    pub use valence_interaction::{
+        AttackEntityEvent, BreakBlockEvent, CancelInteraction, PlaceBlockEvent, UseEntityEvent,
+        UseItemEvent,
+    };
     #[cfg(feature = "inventory")]
     pub use valence_inventory::{
-        CursorItem, Inventory, InventoryKind, InventoryWindow, InventoryWindowMut, OpenInventory,
+        CursorItem, Inventory, InventoryKind, InventoryWindow, InventoryWindowMut, MerchantMenu,
+        OpenInventory, Trade,
     };
+    #[cfg(feature = "localization")]
+    pub use valence_localization::{LocalizedMessages, LocalizedText, Translations};
+    #[cfg(feature = "map")]
+    pub use valence_map::{MapBundle, MapCanvas, MapIcon, MapIcons, MapId, MapManager};
+    #[cfg(feature = "menu")]
+    pub use valence_menu::{Menu, MenuClickEvent, MenuItem};
     pub use valence_nbt::Compound;
     #[cfg(feature = "network")]
+    pub use valence_network::access::{AccessControl, AccessListEvent};
+    #[cfg(feature = "network")]
     pub use valence_network::{
         ConnectionMode, ErasedNetworkCallbacks, NetworkCallbacks, NetworkSettings, NewClientInfo,
         SharedNetworkState,
     };
+    #[cfg(feature = "permission")]
+    pub use valence_permission::{
+        PermissionGroup, PermissionManager, PermissionProvider, Permissions,
+        PermissionsChangedEvent,
+    };
     #[cfg(feature = "player_list")]
+    pub use valence_player_list::tab_list::TabList;
     pub use valence_player_list::{PlayerList, PlayerListEntry};
+    #[cfg(feature = "recipe")]
+    pub use valence_recipe::{RecipeBook, Recipes, ShapedRecipe, ShapelessRecipe};
+    #[cfg(feature = "respawn")]
+    pub use valence_respawn::{
+        PlayerDeathEvent, PlayerRespawnEvent, RespawnBlockExplosionEvent, RespawnPoint,
+        SleepAttemptEvent, SpawnPoint,
+    };
+    #[cfg(feature = "scoreboard")]
+    pub use valence_scoreboard::{
+        Objective, ObjectiveBundle, ObjectiveScores, Team, TeamBundle, TeamMembers,
+    };
+    #[cfg(feature = "spatial_index")]
+    pub use valence_spatial_index::EntitySpatialIndex;
+    #[cfg(feature = "statistics")]
+    pub use valence_statistics::{CustomStat, Stat, Statistics};
+    #[cfg(feature = "text_input")]
+    pub use valence_text_input::{TextInputEvent, TextPrompt};
 
     pub use super::DefaultPlugins;
 }
@@ -171,6 +269,101 @@ impl PluginGroup for DefaultPlugins {
             group = group.add(valence_world_border::WorldBorderPlugin);
         }
 
+        #[cfg(feature = "anticheat")]
+        {
+            group = group.add(valence_anticheat::AnticheatPlugin);
+        }
+
+        #[cfg(feature = "spatial_index")]
+        {
+            group = group.add(valence_spatial_index::SpatialIndexPlugin);
+        }
+
+        #[cfg(feature = "interaction")]
+        {
+            group = group.add(valence_interaction::InteractionPlugin);
+        }
+
+        #[cfg(feature = "health")]
+        {
+            group = group.add(valence_health::HealthPlugin);
+        }
+
+        #[cfg(feature = "combat")]
+        {
+            group = group.add(valence_combat::CombatPlugin);
+        }
+
+        #[cfg(feature = "experience")]
+        {
+            group = group.add(valence_experience::ExperiencePlugin);
+        }
+
+        #[cfg(feature = "respawn")]
+        {
+            group = group.add(valence_respawn::RespawnPlugin);
+        }
+
+        #[cfg(feature = "statistics")]
+        {
+            group = group.add(valence_statistics::StatisticsPlugin);
+        }
+
+        #[cfg(feature = "recipe")]
+        {
+            group = group.add(valence_recipe::RecipePlugin);
+        }
+
+        #[cfg(feature = "menu")]
+        {
+            group = group.add(valence_menu::MenuPlugin);
+        }
+
+        #[cfg(feature = "text_input")]
+        {
+            group = group.add(valence_text_input::TextInputPlugin);
+        }
+
+        #[cfg(feature = "map")]
+        {
+            group = group.add(valence_map::MapPlugin);
+        }
+
+        #[cfg(feature = "scoreboard")]
+        {
+            group = group.add(valence_scoreboard::ScoreboardPlugin);
+        }
+
+        #[cfg(feature = "boss_bar")]
+        {
+            group = group.add(valence_boss_bar::BossBarPlugin);
+        }
+
+        #[cfg(feature = "localization")]
+        {
+            group = group.add(valence_localization::LocalizationPlugin);
+        }
+
+        #[cfg(feature = "permission")]
+        {
+            group = group.add(valence_permission::PermissionPlugin);
+        }
+
+        #[cfg(feature = "command")]
+        {
+            group = group.add(valence_command::CommandPlugin);
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            group = group.add(valence_wasm::WasmPlugin);
+        }
+
+        #[cfg(feature = "script")]
+        {
+            group = group.add(valence_script::ScriptPlugin);
+        }
+
         group
     }
 }
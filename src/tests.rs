@@ -5,6 +5,7 @@ use std::time::Instant;
 use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bytes::{Buf, BufMut, BytesMut};
+use glam::DVec3;
 use uuid::Uuid;
 use valence_biome::BiomeRegistry;
 use valence_client::ClientBundleArgs;
@@ -14,7 +15,8 @@ use valence_core::protocol::var_int::VarInt;
 use valence_core::protocol::{Encode, Packet};
 use valence_core::{ident, CoreSettings, Server};
 use valence_dimension::DimensionTypeRegistry;
-use valence_entity::Location;
+use valence_entity::zombie::ZombieEntityBundle;
+use valence_entity::{Location, Position};
 use valence_network::{ConnectionMode, NetworkSettings};
 
 use crate::client::{ClientBundle, ClientConnection, ReceivedPacket};
@@ -59,6 +61,41 @@ fn scenario_single_client(app: &mut App) -> (Entity, MockClientHelper) {
     (client_ent, client_helper)
 }
 
+/// Returns the Entity of the instance spawned by [`scenario_single_client`].
+fn find_instance(app: &App) -> Entity {
+    app.world
+        .iter_entities()
+        .find(|e| e.contains::<Instance>())
+        .expect("could not find instance")
+        .id()
+}
+
+/// Like [`scenario_single_client`], but also places the client at the origin
+/// and spawns a zombie two blocks away from it. Returns the Entities of the
+/// client and the zombie, and the MockClientHelper.
+fn scenario_client_and_zombie(app: &mut App) -> (Entity, Entity, MockClientHelper) {
+    let (client_ent, mut client_helper) = scenario_single_client(app);
+
+    let instance_ent = find_instance(app);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(0.0, 0.0, 0.0);
+
+    let zombie_ent = app
+        .world
+        .spawn(ZombieEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([2.0, 0.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    (client_ent, zombie_ent, client_helper)
+}
+
 /// Creates a mock client bundle that can be used for unit testing.
 ///
 /// Returns the client, and a helper to inject packets as if the client sent
@@ -280,8 +317,47 @@ impl_packet_list!(A, B, C, D, E, F, G, H, I);
 impl_packet_list!(A, B, C, D, E, F, G, H, I, J);
 impl_packet_list!(A, B, C, D, E, F, G, H, I, J, K);
 
+mod abilities;
+mod advancement;
+mod anticheat;
+mod attributes;
+mod block_layer;
+mod block_tick;
+mod boss_bar;
 mod client;
+mod collision;
+mod combat;
+mod command;
+mod custom_payload;
+mod effects;
+mod entity;
+mod equipment;
 mod example;
+mod experience;
+mod game_rules;
+mod health;
+mod interaction;
 mod inventory;
+mod keepalive;
+mod localization;
+mod map;
+mod menu;
+mod merchant;
+mod npc;
+mod permission;
+mod recipe;
+mod respawn;
+mod scoreboard;
+mod snapshot;
+mod sound;
+mod spatial_index;
+mod spectator;
+mod statistics;
+mod tab_list;
+mod teleport;
+mod text_input;
+mod time;
+mod title;
+mod vehicle;
 mod weather;
 mod world_border;
@@ -0,0 +1,93 @@
+use bevy_app::App;
+use valence_client::abilities::{PlayerAbilities, ToggleFlyEvent};
+use valence_client::packet::{PlayerAbilitiesS2c, UpdatePlayerAbilitiesC2s};
+use valence_core::game_mode::GameMode;
+
+use super::*;
+
+#[test]
+fn abilities_sent_on_change() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    // The abilities are sent when joining.
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerAbilitiesS2c>(1);
+
+    app.world
+        .get_mut::<PlayerAbilities>(client_ent)
+        .unwrap()
+        .flying_speed = 0.2;
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<PlayerAbilitiesS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == PlayerAbilitiesS2c::ID {
+            let pkt = pkt.decode::<PlayerAbilitiesS2c>().unwrap();
+            assert_eq!(pkt.flying_speed, 0.2);
+            assert!(!pkt.flags.allow_flying());
+        }
+    }
+
+    // Changing the game mode resets the abilities but keeps the speeds.
+    *app.world.get_mut::<GameMode>(client_ent).unwrap() = GameMode::Creative;
+
+    app.update();
+
+    let abilities = app.world.get::<PlayerAbilities>(client_ent).unwrap();
+    assert!(abilities.allow_flying);
+    assert!(abilities.instant_break);
+    assert_eq!(abilities.flying_speed, 0.2);
+
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerAbilitiesS2c>(1);
+}
+
+#[test]
+fn toggle_fly_is_validated() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    // Survival clients can't fly.
+    client_helper.send(&UpdatePlayerAbilitiesC2s::StartFlying);
+    app.update();
+
+    assert!(!app.world.get::<PlayerAbilities>(client_ent).unwrap().flying);
+    let events = app.world.resource::<Events<ToggleFlyEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerAbilitiesS2c>(1);
+
+    app.world
+        .get_mut::<PlayerAbilities>(client_ent)
+        .unwrap()
+        .allow_flying = true;
+
+    app.update();
+    client_helper.clear_sent();
+
+    client_helper.send(&UpdatePlayerAbilitiesC2s::StartFlying);
+    app.update();
+
+    assert!(app.world.get::<PlayerAbilities>(client_ent).unwrap().flying);
+    let events = app.world.resource::<Events<ToggleFlyEvent>>();
+    let toggles: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(toggles.len(), 1);
+    assert!(toggles[0].flying);
+
+    // The client isn't sent its own change back.
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerAbilitiesS2c>(0);
+}
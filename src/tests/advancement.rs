@@ -0,0 +1,159 @@
+use bevy_app::App;
+use bevy_ecs::system::SystemState;
+use valence_advancement::bevy_hierarchy::BuildWorldChildren;
+use valence_advancement::packet::AdvancementUpdateS2c;
+use valence_advancement::progress::{
+    AdvancementDone, AdvancementProgress, AdvancementProgressStorage,
+};
+use valence_advancement::{
+    Advancement, AdvancementBundle, AdvancementClientUpdate, AdvancementCriteria,
+    AdvancementRequirements,
+};
+use valence_core::ident::Ident;
+
+use super::*;
+
+/// Spawns an advancement that requires both of its criteria.
+fn spawn_advancement(app: &mut App) -> (Entity, Entity, Entity) {
+    let first = app
+        .world
+        .spawn(AdvancementCriteria::new(ident!("test:first").into()))
+        .id();
+    let second = app
+        .world
+        .spawn(AdvancementCriteria::new(ident!("test:second").into()))
+        .id();
+
+    let advancement = app
+        .world
+        .spawn(AdvancementBundle {
+            advancement: Advancement::new(ident!("test:root").into()),
+            requirements: AdvancementRequirements(vec![vec![first], vec![second]]),
+            cached_bytes: Default::default(),
+        })
+        .push_children(&[first, second])
+        .id();
+
+    (advancement, first, second)
+}
+
+#[test]
+fn granting_criteria_completes_advancement() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let (advancement, first, second) = spawn_advancement(&mut app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    app.world
+        .get_mut::<AdvancementClientUpdate>(client_ent)
+        .unwrap()
+        .new_advancements
+        .push(advancement);
+    app.update();
+    client_helper.clear_sent();
+
+    let mut progress = app
+        .world
+        .get_mut::<AdvancementProgress>(client_ent)
+        .unwrap();
+    assert!(progress.grant(first));
+    assert!(!progress.grant(first));
+    app.update();
+
+    let progress = app.world.get::<AdvancementProgress>(client_ent).unwrap();
+    assert!(progress.is_obtained(first));
+    assert!(!progress.is_completed(advancement));
+    assert!(app
+        .world
+        .resource::<Events<AdvancementDone>>()
+        .iter_current_update_events()
+        .next()
+        .is_none());
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<AdvancementUpdateS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == AdvancementUpdateS2c::ID {
+            let pkt = pkt.decode::<AdvancementUpdateS2c>().unwrap();
+            assert!(!pkt.reset);
+            assert_eq!(pkt.progress_mapping.len(), 1);
+            assert_eq!(pkt.progress_mapping[0].0.as_str(), "test:root");
+
+            let criteria = &pkt.progress_mapping[0].1;
+            assert_eq!(criteria.len(), 1);
+            assert_eq!(criteria[0].criterion_identifier.as_str(), "test:first");
+            assert!(criteria[0].criterion_progress.is_some());
+        }
+    }
+
+    app.world
+        .get_mut::<AdvancementProgress>(client_ent)
+        .unwrap()
+        .grant(second);
+    app.update();
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<AdvancementDone>>()
+        .iter_current_update_events()
+        .copied()
+        .collect();
+    assert_eq!(
+        events,
+        [AdvancementDone {
+            client: client_ent,
+            advancement,
+        }]
+    );
+
+    let progress = app.world.get::<AdvancementProgress>(client_ent).unwrap();
+    assert!(progress.is_completed(advancement));
+
+    // Revoking a criteria undoes the advancement.
+    app.world
+        .get_mut::<AdvancementProgress>(client_ent)
+        .unwrap()
+        .revoke(first);
+    app.update();
+
+    let progress = app.world.get::<AdvancementProgress>(client_ent).unwrap();
+    assert!(!progress.is_obtained(first));
+    assert!(!progress.is_completed(advancement));
+}
+
+#[test]
+fn advancement_progress_save_and_load() {
+    let mut app = App::new();
+    let (client_ent, _) = scenario_single_client(&mut app);
+    let (advancement, first, second) = spawn_advancement(&mut app);
+
+    app.update();
+
+    let mut progress = app
+        .world
+        .get_mut::<AdvancementProgress>(client_ent)
+        .unwrap();
+    progress.grant_at(first, 10);
+    progress.grant_at(second, 20);
+
+    let mut state = SystemState::<(AdvancementProgressStorage, Query<&AdvancementProgress>)>::new(
+        &mut app.world,
+    );
+    let (storage, clients) = state.get(&app.world);
+    let saved = storage.save(clients.get(client_ent).unwrap());
+
+    let root = Ident::new("test:root").unwrap().to_string_ident();
+    let criteria = &saved.0[&root];
+    assert_eq!(criteria.len(), 2);
+    assert_eq!(criteria.get("test:first"), Some(&10));
+    assert_eq!(criteria.get("test:second"), Some(&20));
+
+    let loaded = storage.load(&saved);
+
+    assert_eq!(loaded.obtained_at(first), Some(10));
+    assert_eq!(loaded.obtained_at(second), Some(20));
+    assert!(loaded.is_completed(advancement));
+}
@@ -0,0 +1,280 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_anticheat::{
+    AnticheatPlugin, AnticheatSettings, MovementViolationEvent, ViolationKind, ViolationScore,
+};
+use valence_block::BlockState;
+use valence_client::abilities::PlayerAbilities;
+use valence_client::movement::{OnGroundOnlyC2s, PositionAndOnGroundC2s};
+use valence_client::teleport::{PlayerPositionLookS2c, TeleportConfirmC2s};
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::Position;
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn invalid_movement_is_rubber_banded() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    if !app.is_plugin_added::<AnticheatPlugin>() {
+        app.add_plugin(AnticheatPlugin);
+    }
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    instance.set_block([10, 1, 8], BlockState::STONE);
+    instance.set_block([10, 2, 8], BlockState::STONE);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 8.5);
+
+    // Process a tick to get past the "on join" logic and confirm the teleport.
+    app.update();
+    client_helper.send(&TeleportConfirmC2s {
+        teleport_id: VarInt(0),
+    });
+    app.update();
+    client_helper.clear_sent();
+
+    // Walking normally is fine.
+    client_helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(8.5, 1.0, 8.8),
+        on_ground: true,
+    });
+
+    app.update();
+
+    let pos = DVec3::new(8.5, 1.0, 8.8);
+    assert_eq!(app.world.get::<Position>(client_ent).unwrap().0, pos);
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerPositionLookS2c>(0);
+
+    // Moving too fast.
+    client_helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(8.5, 1.0, 13.8),
+        on_ground: true,
+    });
+
+    app.update();
+
+    assert_eq!(app.world.get::<Position>(client_ent).unwrap().0, pos);
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerPositionLookS2c>(1);
+
+    let events = app.world.resource::<Events<MovementViolationEvent>>();
+    let violations: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::Speed);
+    assert!(app.world.get::<ViolationScore>(client_ent).unwrap().0 > 0.0);
+
+    // Confirm the position sync.
+    client_helper.send(&TeleportConfirmC2s {
+        teleport_id: VarInt(1),
+    });
+    app.update();
+    client_helper.clear_sent();
+
+    // Walking into the wall.
+    client_helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(9.3, 1.0, 8.8),
+        on_ground: true,
+    });
+    app.update();
+    client_helper.send(&PositionAndOnGroundC2s {
+        position: DVec3::new(10.1, 1.0, 8.8),
+        on_ground: true,
+    });
+    app.update();
+
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(9.3, 1.0, 8.8)
+    );
+
+    let events = app.world.resource::<Events<MovementViolationEvent>>();
+    let violations: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].kind, ViolationKind::NoClip);
+}
+
+#[test]
+fn climbing_ladder_is_allowed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    if !app.is_plugin_added::<AnticheatPlugin>() {
+        app.add_plugin(AnticheatPlugin);
+    }
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    // A wall with ladders facing north.
+    for y in 1..10 {
+        instance.set_block([8, y, 9], BlockState::STONE);
+        instance.set_block([8, y, 8], BlockState::LADDER);
+    }
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 8.5);
+
+    // Process a tick to get past the "on join" logic and confirm the teleport.
+    app.update();
+    client_helper.send(&TeleportConfirmC2s {
+        teleport_id: VarInt(0),
+    });
+    app.update();
+    client_helper.clear_sent();
+
+    // Climb for longer than the client may stay in the air.
+    let mut pos = DVec3::new(8.5, 1.0, 8.5);
+
+    for _ in 0..60 {
+        pos.y += 0.1;
+
+        client_helper.send(&PositionAndOnGroundC2s {
+            position: pos,
+            on_ground: false,
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<MovementViolationEvent>>();
+        assert_eq!(events.iter_current_update_events().count(), 0);
+    }
+
+    assert_eq!(app.world.get::<Position>(client_ent).unwrap().0, pos);
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn flying_with_abilities_is_allowed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    if !app.is_plugin_added::<AnticheatPlugin>() {
+        app.add_plugin(AnticheatPlugin);
+    }
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(0.5, 1.0, 0.5);
+
+    let mut abilities = app.world.get_mut::<PlayerAbilities>(client_ent).unwrap();
+    abilities.allow_flying = true;
+    abilities.flying = true;
+
+    // Process a tick to get past the "on join" logic and confirm the teleport.
+    app.update();
+    client_helper.send(&TeleportConfirmC2s {
+        teleport_id: VarInt(0),
+    });
+    app.update();
+    client_helper.clear_sent();
+
+    // Fly up and then stay in the air for longer than the client may without
+    // flying.
+    let mut pos = DVec3::new(0.5, 1.0, 0.5);
+
+    for i in 0..60 {
+        if i < 10 {
+            pos.y += 1.0;
+        } else {
+            pos.x += 0.2;
+        }
+
+        client_helper.send(&PositionAndOnGroundC2s {
+            position: pos,
+            on_ground: false,
+        });
+
+        app.update();
+
+        let events = app.world.resource::<Events<MovementViolationEvent>>();
+        assert_eq!(events.iter_current_update_events().count(), 0);
+    }
+
+    assert_eq!(app.world.get::<Position>(client_ent).unwrap().0, pos);
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerPositionLookS2c>(0);
+}
+
+#[test]
+fn hovering_in_place_is_caught() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    if !app.is_plugin_added::<AnticheatPlugin>() {
+        app.add_plugin(AnticheatPlugin);
+    }
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 5.0, 8.5);
+
+    // Process a tick to get past the "on join" logic and confirm the teleport.
+    app.update();
+    client_helper.send(&TeleportConfirmC2s {
+        teleport_id: VarInt(0),
+    });
+    app.update();
+    client_helper.clear_sent();
+
+    let max_air_ticks = app.world.resource::<AnticheatSettings>().max_air_ticks;
+
+    for i in 0..=max_air_ticks {
+        client_helper.send(&OnGroundOnlyC2s { on_ground: false });
+
+        app.update();
+
+        let events = app.world.resource::<Events<MovementViolationEvent>>();
+        let violations: Vec<_> = events.iter_current_update_events().collect();
+
+        if i < max_air_ticks {
+            assert!(violations.is_empty());
+        } else {
+            assert_eq!(violations.len(), 1);
+            assert_eq!(violations[0].kind, ViolationKind::Fly);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use bevy_app::App;
+use uuid::Uuid;
+use valence_entity::attributes::{AttributeOperation, Attributes, EntityAttribute};
+use valence_entity::packet::EntityAttributesS2c;
+
+use super::*;
+
+#[test]
+fn attribute_changes_sent_to_client() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    app.update();
+    client_helper.clear_sent();
+
+    let mut attrs = app.world.get_mut::<Attributes>(client_ent).unwrap();
+    assert_eq!(attrs.get(EntityAttribute::MovementSpeed), 0.1);
+
+    attrs.set_modifier(
+        EntityAttribute::MovementSpeed,
+        Uuid::from_u128(1),
+        1.0,
+        AttributeOperation::MultiplyTotal,
+    );
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<EntityAttributesS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == EntityAttributesS2c::ID {
+            let pkt = pkt.decode::<EntityAttributesS2c>().unwrap();
+            assert_eq!(pkt.entity_id.0, 0);
+            assert_eq!(pkt.properties.len(), 1);
+            assert_eq!(pkt.properties[0].modifiers.len(), 1);
+        }
+    }
+
+    // Nothing is sent when the attributes are unchanged.
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityAttributesS2c>(0);
+}
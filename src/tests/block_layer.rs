@@ -0,0 +1,74 @@
+use bevy_app::App;
+use valence_block::BlockState;
+use valence_client::block_layer::BlockLayer;
+use valence_instance::packet::{BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c};
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn block_layer_overrides() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let mut layer = BlockLayer::new();
+    layer.set_block([1, 2, 3], BlockState::GOLD_BLOCK);
+    layer.set_block([1, 3, 3], BlockState::GOLD_BLOCK);
+    app.world.entity_mut(client_ent).insert(layer);
+
+    app.update();
+
+    {
+        let sent_packets = client_helper.collect_sent();
+        sent_packets.assert_count::<BlockUpdateS2c>(2);
+        sent_packets.assert_count::<ChunkDeltaUpdateS2c>(0);
+    }
+
+    // Changing the instance's block must not replace the override.
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.set_block([1, 2, 3], BlockState::STONE);
+
+    app.update();
+
+    {
+        let sent_packets = client_helper.collect_sent();
+        sent_packets.assert_count::<BlockUpdateS2c>(1);
+        sent_packets.assert_count::<ChunkDeltaUpdateS2c>(1);
+        sent_packets.assert_order::<(BlockUpdateS2c, ChunkDeltaUpdateS2c)>();
+    }
+
+    // Removing the override restores the instance's block.
+    let mut layer = app.world.get_mut::<BlockLayer>(client_ent).unwrap();
+    assert_eq!(layer.remove_block([1, 2, 3]), Some(BlockState::GOLD_BLOCK));
+    assert_eq!(layer.len(), 1);
+
+    app.update();
+
+    {
+        let sent_packets = client_helper.collect_sent();
+        sent_packets.assert_count::<BlockUpdateS2c>(1);
+        sent_packets.assert_count::<ChunkDataS2c>(0);
+
+        for pkt in &sent_packets.0 {
+            if pkt.id == BlockUpdateS2c::ID {
+                let pkt = pkt.decode::<BlockUpdateS2c>().unwrap();
+                assert_eq!(pkt.block_id.0, BlockState::STONE.to_raw() as i32);
+            }
+        }
+    }
+}
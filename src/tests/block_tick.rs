@@ -0,0 +1,77 @@
+use bevy_app::App;
+use valence_core::block_pos::BlockPos;
+use valence_instance::block_tick::BlockTickEvent;
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn scheduled_block_ticks() {
+    let mut app = App::new();
+    scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    assert!(instance.schedule_block_tick([1, 2, 3], 2, 0));
+    assert!(instance.schedule_block_tick([4, 5, 6], 2, -1));
+    assert!(instance.schedule_block_tick([7, 8, 9], 1, 0));
+    // Not in a loaded chunk.
+    assert!(!instance.schedule_block_tick([100, 0, 100], 1, 0));
+
+    let mut reader = app
+        .world
+        .resource_mut::<Events<BlockTickEvent>>()
+        .get_reader();
+
+    app.update();
+
+    let events = reader
+        .iter(app.world.resource::<Events<BlockTickEvent>>())
+        .map(|e| (e.position, e.priority))
+        .collect::<Vec<_>>();
+
+    assert_eq!(events, [(BlockPos::new(7, 8, 9), 0)]);
+
+    app.update();
+
+    let events = reader
+        .iter(app.world.resource::<Events<BlockTickEvent>>())
+        .map(|e| (e.position, e.priority))
+        .collect::<Vec<_>>();
+
+    // Ordered by priority.
+    assert_eq!(
+        events,
+        [(BlockPos::new(4, 5, 6), -1), (BlockPos::new(1, 2, 3), 0)]
+    );
+
+    let instance = app.world.get::<Instance>(instance_ent).unwrap();
+    assert!(instance.chunk([0, 0]).unwrap().scheduled_ticks().is_empty());
+}
+
+#[test]
+fn scheduled_ticks_persist_with_chunk() {
+    let mut chunk = Chunk::new(4);
+    chunk.schedule_tick(1, 60, 2, 10, 0);
+
+    let mut app = App::new();
+    scenario_single_client(&mut app);
+
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.insert_chunk([5, 5], chunk);
+
+    let unloaded = instance.remove_chunk([5, 5]).unwrap();
+    assert_eq!(unloaded.scheduled_ticks().len(), 1);
+    assert_eq!(unloaded.scheduled_ticks()[0].delay, 10);
+
+    let mut resized = unloaded;
+    resized.resize(3);
+    assert!(resized.scheduled_ticks().is_empty());
+}
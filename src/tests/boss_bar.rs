@@ -0,0 +1,123 @@
+use bevy_app::App;
+use valence_boss_bar::packet::*;
+use valence_boss_bar::*;
+use valence_client::Client;
+use valence_core::despawn::Despawned;
+
+use super::*;
+
+fn boss_bar_actions(frames: &PacketFrames) -> Vec<BossBarAction> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == BossBarS2c::ID)
+        .map(|f| f.decode::<BossBarS2c>().unwrap().action)
+        .collect()
+}
+
+#[test]
+fn boss_bar_updates_viewers() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let boss_bar_ent = app
+        .world
+        .spawn(BossBarBundle::new(
+            "Ender Dragon",
+            BossBarColor::Purple,
+            BossBarDivision::NoDivision,
+        ))
+        .id();
+
+    app.update();
+
+    client_helper.collect_sent().assert_count::<BossBarS2c>(0);
+
+    app.world
+        .get_mut::<BossBarViewers>(boss_bar_ent)
+        .unwrap()
+        .insert(client_ent);
+
+    app.update();
+
+    let actions = boss_bar_actions(&client_helper.collect_sent());
+    assert!(matches!(
+        actions[..],
+        [BossBarAction::Add {
+            color: BossBarColor::Purple,
+            ..
+        }]
+    ));
+
+    app.world.get_mut::<BossBarHealth>(boss_bar_ent).unwrap().0 = 0.5;
+    app.world
+        .get_mut::<BossBarFlags>(boss_bar_ent)
+        .unwrap()
+        .set_darken_sky(true);
+
+    app.update();
+
+    let actions = boss_bar_actions(&client_helper.collect_sent());
+    assert_eq!(
+        actions,
+        [
+            BossBarAction::UpdateHealth(0.5),
+            BossBarAction::UpdateFlags(BossBarFlags::new().with_darken_sky(true))
+        ]
+    );
+
+    app.world
+        .get_mut::<BossBarViewers>(boss_bar_ent)
+        .unwrap()
+        .remove(client_ent);
+
+    app.update();
+
+    let actions = boss_bar_actions(&client_helper.collect_sent());
+    assert_eq!(actions, [BossBarAction::Remove]);
+}
+
+#[test]
+fn despawned_boss_bar_is_removed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let mut bundle = BossBarBundle::new("Raid", BossBarColor::Red, BossBarDivision::TenNotches);
+    bundle.viewers.insert(client_ent);
+    let boss_bar_ent = app.world.spawn(bundle).id();
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world.entity_mut(boss_bar_ent).insert(Despawned);
+
+    app.update();
+
+    let actions = boss_bar_actions(&client_helper.collect_sent());
+    assert_eq!(actions, [BossBarAction::Remove]);
+}
+
+#[test]
+fn disconnected_clients_stop_viewing() {
+    let mut app = App::new();
+    let (client_ent, _) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let mut bundle = BossBarBundle::new("Raid", BossBarColor::Red, BossBarDivision::TenNotches);
+    bundle.viewers.insert(client_ent);
+    let boss_bar_ent = app.world.spawn(bundle).id();
+
+    app.update();
+
+    app.world.entity_mut(client_ent).remove::<Client>();
+
+    app.update();
+
+    let viewers = app.world.get::<BossBarViewers>(boss_bar_ent).unwrap();
+    assert!(viewers.is_empty());
+}
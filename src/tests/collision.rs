@@ -0,0 +1,64 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_block::BlockState;
+use valence_core::aabb::Aabb;
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn move_entity_through_blocks() {
+    let mut app = App::new();
+    let _ = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    // A floor at y = 0 with a wall and a slab on it.
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    for y in 1..4 {
+        instance.set_block([8, y, 4], BlockState::STONE);
+    }
+
+    instance.set_block([4, 1, 8], BlockState::SMOOTH_STONE_SLAB);
+
+    let instance = app.world.get::<Instance>(instance_ent).unwrap();
+
+    // Player sized hitbox standing on the floor.
+    let hitbox = |x: f64, y: f64, z: f64| Aabb::from_bottom_size([x, y, z], [0.6, 1.8, 0.6]);
+
+    assert!(instance.is_on_ground(hitbox(2.5, 1.0, 2.5)));
+    assert!(!instance.is_on_ground(hitbox(2.5, 1.5, 2.5)));
+    assert!(instance.intersects_blocks(hitbox(2.5, 0.5, 2.5)));
+    assert!(!instance.intersects_blocks(hitbox(2.5, 1.0, 2.5)));
+
+    // Falling onto the floor.
+    let res = instance.move_entity(hitbox(2.5, 3.0, 2.5), DVec3::new(0.0, -5.0, 0.0), 0.6);
+    assert_eq!(res.delta, DVec3::new(0.0, -2.0, 0.0));
+    assert!(res.collided.y);
+    assert!(res.on_ground);
+
+    // Walking into the wall.
+    let res = instance.move_entity(hitbox(6.5, 1.0, 4.5), DVec3::new(2.0, 0.0, 0.0), 0.6);
+    assert!((res.delta.x - 1.2).abs() < 1e-9);
+    assert!(res.collided.x);
+    assert!(res.on_ground);
+
+    // Stepping up onto the slab.
+    let res = instance.move_entity(hitbox(3.0, 1.0, 8.5), DVec3::new(1.0, 0.0, 0.0), 0.6);
+    assert_eq!(res.delta.x, 1.0);
+    assert!((res.delta.y - 0.5).abs() < 1e-9);
+    assert!(res.on_ground);
+
+    // Without a step height, the slab blocks the way.
+    let res = instance.move_entity(hitbox(3.0, 1.0, 8.5), DVec3::new(1.0, 0.0, 0.0), 0.0);
+    assert!((res.delta.x - 0.7).abs() < 1e-9);
+    assert_eq!(res.delta.y, 0.0);
+}
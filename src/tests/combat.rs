@@ -0,0 +1,113 @@
+use bevy_app::App;
+use glam::Vec3;
+use valence_client::interact_entity::{EntityInteraction, PlayerInteractEntityC2s};
+use valence_combat::{ModifyAttacksSet, PostAttackEvent, PreAttackEvents};
+use valence_core::item::{ItemKind, ItemStack};
+use valence_entity::entity::Flags;
+use valence_entity::{EntityId, Velocity};
+use valence_health::Health;
+use valence_inventory::Inventory;
+
+use super::*;
+
+fn attack(app: &App, client_helper: &mut MockClientHelper, target: Entity) {
+    let id = app.world.get::<EntityId>(target).unwrap().get();
+
+    client_helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(id),
+        interact: EntityInteraction::Attack,
+        sneaking: false,
+    });
+}
+
+#[test]
+fn attack_deals_damage_and_knockback() {
+    let mut app = App::new();
+    let (client_ent, zombie_ent, mut client_helper) = scenario_client_and_zombie(&mut app);
+
+    assert_eq!(app.world.get::<Health>(zombie_ent).unwrap().0, 20.0);
+
+    attack(&app, &mut client_helper, zombie_ent);
+    app.update();
+
+    // An empty hand deals the player's base attack damage.
+    assert_eq!(app.world.get::<Health>(zombie_ent).unwrap().0, 19.0);
+
+    let events = app.world.resource::<Events<PostAttackEvent>>();
+    let attacks: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(attacks.len(), 1);
+    assert_eq!(attacks[0].attacker, client_ent);
+    assert_eq!(attacks[0].damage, 1.0);
+    assert!(!attacks[0].critical);
+
+    // The zombie is pushed away from the attacker.
+    let knockback = attacks[0].knockback.unwrap();
+    assert!(knockback.x > 0.0);
+    assert_eq!(knockback.z, 0.0);
+    assert_eq!(app.world.get::<Velocity>(zombie_ent).unwrap().0, knockback);
+
+    // The zombie is still invulnerable and the attack cooldown hasn't
+    // recovered.
+    attack(&app, &mut client_helper, zombie_ent);
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(zombie_ent).unwrap().0, 19.0);
+    let events = app.world.resource::<Events<PostAttackEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    // Wait for the cooldown and invulnerability to wear off.
+    for _ in 0..20 {
+        app.update();
+    }
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::DiamondSword, 1, None));
+
+    attack(&app, &mut client_helper, zombie_ent);
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(zombie_ent).unwrap().0, 12.0);
+}
+
+fn cancel_attacks(mut attacks: ResMut<PreAttackEvents>) {
+    for attack in attacks.iter_mut() {
+        attack.cancel();
+    }
+}
+
+#[test]
+fn cancelled_attack_is_not_applied() {
+    let mut app = App::new();
+    let (_, zombie_ent, mut client_helper) = scenario_client_and_zombie(&mut app);
+
+    app.add_system(cancel_attacks.in_set(ModifyAttacksSet));
+
+    attack(&app, &mut client_helper, zombie_ent);
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(zombie_ent).unwrap().0, 20.0);
+    assert_eq!(app.world.get::<Velocity>(zombie_ent).unwrap().0, Vec3::ZERO);
+
+    let events = app.world.resource::<Events<PostAttackEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+}
+
+#[test]
+fn sprint_attack_stops_sprinting() {
+    let mut app = App::new();
+    let (client_ent, zombie_ent, mut client_helper) = scenario_client_and_zombie(&mut app);
+
+    app.world
+        .get_mut::<Flags>(client_ent)
+        .unwrap()
+        .set_sprinting(true);
+
+    attack(&app, &mut client_helper, zombie_ent);
+    app.update();
+
+    let events = app.world.resource::<Events<PostAttackEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 1);
+    assert!(!app.world.get::<Flags>(client_ent).unwrap().sprinting());
+}
@@ -0,0 +1,367 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_block::{PropName, PropValue};
+use valence_client::message::SendMessage;
+use valence_command::packet::{CommandTreeS2c, NodeData, Parser, StringArg};
+use valence_command::parse::ArgValue;
+use valence_command::selector::{EntitySelector, SelectorKind};
+use valence_command::suggestion::CommandSuggestion;
+use valence_command::{argument, literal, CommandExecutionEvent, CommandRegistry};
+use valence_core::protocol::packet::chat::{
+    CommandExecutionC2s, CommandSuggestionsS2c, GameMessageS2c, RequestCommandCompletionsC2s,
+};
+use valence_core::protocol::var_int::VarInt;
+use valence_permission::Permissions;
+
+use super::*;
+
+fn command_events(app: &App) -> Vec<CommandExecutionEvent> {
+    app.world
+        .resource::<Events<CommandExecutionEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+fn run_command(client_helper: &mut MockClientHelper, command: &str) {
+    client_helper.send(&CommandExecutionC2s {
+        command,
+        timestamp: 0,
+        salt: 0,
+        argument_signatures: vec![],
+        message_count: VarInt(0),
+        acknowledgement: [0; 3],
+    });
+}
+
+fn register_commands(app: &mut App) {
+    app.world.resource_mut::<CommandRegistry>().register(
+        literal("give")
+            .then(
+                argument("item", Parser::String(StringArg::SingleWord))
+                    .executable()
+                    .then(
+                        argument(
+                            "count",
+                            Parser::Integer {
+                                min: Some(1),
+                                max: Some(64),
+                            },
+                        )
+                        .executes(|event, client, _commands| {
+                            client.send_chat_message(format!(
+                                "Gave {} {}",
+                                event.args.get_i32("count").unwrap(),
+                                event.args.get_str("item").unwrap()
+                            ));
+                        }),
+                    ),
+            )
+            .then(literal("all").executable()),
+    );
+}
+
+#[test]
+fn command_tree_sent_to_clients() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    register_commands(&mut app);
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<CommandTreeS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == CommandTreeS2c::ID {
+            let pkt = pkt.decode::<CommandTreeS2c>().unwrap();
+            let nodes = &pkt.commands;
+
+            assert_eq!(nodes.len(), 5);
+            assert!(matches!(
+                nodes[pkt.root_index.0 as usize].data,
+                NodeData::Root
+            ));
+            assert!(matches!(nodes[1].data, NodeData::Literal { name: "give" }));
+            assert!(matches!(
+                nodes[2].data,
+                NodeData::Argument { name: "item", .. }
+            ));
+            assert!(nodes[2].executable);
+            assert_eq!(nodes[2].children, [VarInt(3)]);
+            assert!(matches!(nodes[4].data, NodeData::Literal { name: "all" }));
+        }
+    }
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<CommandTreeS2c>(0);
+
+    app.world
+        .resource_mut::<CommandRegistry>()
+        .register(literal("spawn").executable());
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<CommandTreeS2c>(1);
+}
+
+#[test]
+fn commands_dispatched_with_args() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    register_commands(&mut app);
+    app.update();
+    client_helper.clear_sent();
+
+    run_command(&mut client_helper, "give diamond 5");
+    app.update();
+
+    let events = command_events(&app);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].client, client_ent);
+    assert_eq!(events[0].command, "give");
+    assert_eq!(events[0].input, "give diamond 5");
+    assert_eq!(
+        events[0].args.iter().collect::<Vec<_>>(),
+        [
+            ("item", &ArgValue::String("diamond".into())),
+            ("count", &ArgValue::Integer(5)),
+        ]
+    );
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<GameMessageS2c>(1);
+
+    // Literals that match are preferred over arguments.
+    run_command(&mut client_helper, "give all");
+    app.update();
+
+    let events = command_events(&app);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].args.is_empty());
+}
+
+#[test]
+fn invalid_commands_send_errors() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    register_commands(&mut app);
+    app.update();
+    client_helper.clear_sent();
+
+    for input in ["unknown", "give", "give diamond 100", "give diamond 5x"] {
+        run_command(&mut client_helper, input);
+        app.update();
+
+        assert!(command_events(&app).is_empty(), "{input}");
+        client_helper
+            .collect_sent()
+            .assert_count::<GameMessageS2c>(1);
+    }
+
+    let registry = app.world.resource::<CommandRegistry>();
+
+    assert_eq!(registry.parse("unknown").unwrap_err().pos, 0);
+    assert_eq!(registry.parse("give").unwrap_err().pos, 4);
+    assert_eq!(registry.parse("give diamond 100").unwrap_err().pos, 13);
+    assert_eq!(registry.parse("give diamond 5x").unwrap_err().pos, 14);
+}
+
+#[test]
+fn standard_arguments_parsed() {
+    let mut registry = CommandRegistry::new();
+
+    registry.register(
+        literal("test")
+            .then(
+                literal("entity").then(
+                    argument(
+                        "target",
+                        Parser::Entity {
+                            single: false,
+                            only_players: true,
+                        },
+                    )
+                    .executable(),
+                ),
+            )
+            .then(literal("pos").then(argument("pos", Parser::Vec3).executable()))
+            .then(literal("block").then(argument("block", Parser::BlockState).executable()))
+            .then(literal("time").then(argument("time", Parser::Time).executable())),
+    );
+
+    let (_, args) = registry
+        .parse("test entity @a[distance=..5,limit=2]")
+        .unwrap();
+    let Some(EntitySelector::Selector(selector)) = args.get_selector("target") else {
+        panic!("expected a selector")
+    };
+    assert_eq!(selector.kind, SelectorKind::AllPlayers);
+    assert_eq!(selector.distance.unwrap().max, Some(5.0));
+    assert_eq!(selector.max_results(), 2);
+
+    // Selectors that can select other entities are rejected.
+    assert!(registry.parse("test entity @e").is_err());
+
+    let (_, args) = registry.parse("test pos ~ ~1 10").unwrap();
+    let pos = args
+        .get_coordinates("pos")
+        .unwrap()
+        .resolve(DVec3::new(1.0, 2.0, 3.0), 0.0, 0.0);
+    assert_eq!(pos, DVec3::new(1.0, 3.0, 10.5));
+
+    let (_, args) = registry.parse("test pos ^ ^ ^2").unwrap();
+    let pos = args
+        .get_coordinates("pos")
+        .unwrap()
+        .resolve(DVec3::ZERO, 0.0, 0.0);
+    assert!((pos - DVec3::new(0.0, 0.0, 2.0)).length() < 1e-9);
+
+    let (_, args) = registry
+        .parse("test block oak_stairs[facing=east]")
+        .unwrap();
+    let Some(ArgValue::BlockState(block)) = args.get("block") else {
+        panic!("expected a block state")
+    };
+    assert_eq!(block.state.get(PropName::Facing), Some(PropValue::East));
+    assert!(registry.parse("test block stone[facing=east]").is_err());
+
+    let (_, args) = registry.parse("test time 2s").unwrap();
+    assert_eq!(args.get_time("time"), Some(40));
+}
+
+fn request_suggestions(client_helper: &mut MockClientHelper, transaction_id: i32, text: &str) {
+    client_helper.send(&RequestCommandCompletionsC2s {
+        transaction_id: VarInt(transaction_id),
+        text,
+    });
+}
+
+/// Returns the id, start, length, and suggestions of the first suggestions
+/// packet that was sent.
+fn sent_suggestions(client_helper: &mut MockClientHelper) -> Option<(i32, i32, i32, Vec<String>)> {
+    let sent_packets = client_helper.collect_sent();
+
+    sent_packets
+        .0
+        .iter()
+        .find(|pkt| pkt.id == CommandSuggestionsS2c::ID)
+        .map(|pkt| {
+            let pkt = pkt.decode::<CommandSuggestionsS2c>().unwrap();
+
+            (
+                pkt.id.0,
+                pkt.start.0,
+                pkt.length.0,
+                pkt.matches
+                    .iter()
+                    .map(|m| m.suggested_match.to_owned())
+                    .collect(),
+            )
+        })
+}
+
+#[test]
+fn suggestions_sent_for_partial_input() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world.resource_mut::<CommandRegistry>().register(
+        literal("give").then(literal("all").executable()).then(
+            argument("item", Parser::String(StringArg::SingleWord))
+                .suggests_with(|_ctx| async {
+                    vec![
+                        CommandSuggestion::new("diamond").with_tooltip("Shiny"),
+                        "dirt".into(),
+                        "stone".into(),
+                    ]
+                })
+                .executable(),
+        ),
+    );
+    app.update();
+    client_helper.clear_sent();
+
+    // Suggestions without a provider are sent right away.
+    request_suggestions(&mut client_helper, 1, "/gi");
+    app.update();
+
+    assert_eq!(
+        sent_suggestions(&mut client_helper),
+        Some((1, 1, 2, vec!["give".into()]))
+    );
+
+    // Suggestions of providers are sent when the provider is done.
+    request_suggestions(&mut client_helper, 2, "/give d");
+
+    let mut suggestions = None;
+
+    for _ in 0..100 {
+        app.update();
+        suggestions = sent_suggestions(&mut client_helper);
+
+        if suggestions.is_some() {
+            break;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        suggestions,
+        Some((2, 6, 1, vec!["diamond".into(), "dirt".into()]))
+    );
+}
+
+#[test]
+fn nodes_require_permissions() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world.resource_mut::<CommandRegistry>().register(
+        literal("stop")
+            .requires("valence.command.stop")
+            .executable(),
+    );
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<CommandTreeS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == CommandTreeS2c::ID {
+            let pkt = pkt.decode::<CommandTreeS2c>().unwrap();
+            assert_eq!(pkt.commands.len(), 1, "command should be hidden");
+        }
+    }
+
+    run_command(&mut client_helper, "stop");
+    app.update();
+
+    assert!(command_events(&app).is_empty());
+    client_helper
+        .collect_sent()
+        .assert_count::<GameMessageS2c>(1);
+
+    // Granting the permission sends the command tree again.
+    app.world
+        .get_mut::<Permissions>(client_ent)
+        .unwrap()
+        .grant("valence.command.*");
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<CommandTreeS2c>(1);
+
+    run_command(&mut client_helper, "stop");
+    app.update();
+
+    assert_eq!(command_events(&app).len(), 1);
+}
@@ -0,0 +1,94 @@
+use bevy_app::App;
+use valence_client::custom_payload::{
+    ChannelRegistrationEvent, ClientChannels, CustomPayloadC2s, CustomPayloadEvent,
+    CustomPayloadS2c, PluginChannels, MAX_CLIENT_CHANNELS,
+};
+use valence_core::protocol::raw::RawBytes;
+
+use super::*;
+
+#[test]
+fn plugin_channels_registered_and_brand_exchanged() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world
+        .resource_mut::<PluginChannels>()
+        .register(ident!("valence:test").into());
+
+    app.update();
+
+    let sent = client_helper.collect_sent();
+    let payloads: Vec<_> = sent
+        .0
+        .iter()
+        .filter(|f| f.id == CustomPayloadS2c::ID)
+        .map(|f| {
+            let pkt = f.decode::<CustomPayloadS2c>().unwrap();
+            (pkt.channel.to_string(), pkt.data.0.to_vec())
+        })
+        .collect();
+
+    assert_eq!(
+        payloads,
+        [
+            ("minecraft:brand".into(), b"\x07Valence".to_vec()),
+            ("minecraft:register".into(), b"valence:test".to_vec()),
+        ]
+    );
+
+    let mut brand = vec![];
+    "fabric".encode(&mut brand).unwrap();
+
+    client_helper.send(&CustomPayloadC2s {
+        channel: ident!("minecraft:brand").into(),
+        data: RawBytes(&brand),
+    });
+    client_helper.send(&CustomPayloadC2s {
+        channel: ident!("minecraft:register").into(),
+        data: RawBytes(b"voicechat:a\0voicechat:b"),
+    });
+
+    app.update();
+
+    let channels = app.world.get::<ClientChannels>(client_ent).unwrap();
+    assert_eq!(channels.brand(), Some("fabric"));
+    assert!(channels.is_registered(ident!("voicechat:a")));
+    assert!(channels.is_registered(ident!("voicechat:b")));
+
+    let registrations = app.world.resource::<Events<ChannelRegistrationEvent>>();
+    assert_eq!(registrations.iter_current_update_events().count(), 2);
+
+    let payloads = app.world.resource::<Events<CustomPayloadEvent>>();
+    let brand_event = payloads.iter_current_update_events().next().unwrap();
+    assert_eq!(brand_event.decode::<&str>().unwrap(), "fabric");
+}
+
+#[test]
+fn client_channels_are_limited() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let data = (0..MAX_CLIENT_CHANNELS + 10)
+        .map(|i| format!("test:channel_{i}"))
+        .collect::<Vec<_>>()
+        .join("\0");
+
+    client_helper.send(&CustomPayloadC2s {
+        channel: ident!("minecraft:register").into(),
+        data: RawBytes(data.as_bytes()),
+    });
+
+    app.update();
+
+    let channels = app.world.get::<ClientChannels>(client_ent).unwrap();
+    assert_eq!(channels.iter().count(), MAX_CLIENT_CHANNELS);
+
+    let registrations = app.world.resource::<Events<ChannelRegistrationEvent>>();
+    assert_eq!(
+        registrations.iter_current_update_events().count(),
+        MAX_CLIENT_CHANNELS
+    );
+}
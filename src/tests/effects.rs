@@ -0,0 +1,66 @@
+use bevy_app::App;
+use valence_entity::effects::{ActiveEffect, ActiveEffects, EffectExpiredEvent, StatusEffect};
+use valence_entity::packet::{EntityStatusEffectS2c, RemoveEntityStatusEffectS2c};
+
+use super::*;
+
+#[test]
+fn effect_expires_and_is_removed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(ActiveEffects::default());
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    app.world
+        .get_mut::<ActiveEffects>(client_ent)
+        .unwrap()
+        .add(StatusEffect::Speed, ActiveEffect::new(1, Some(3)));
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<EntityStatusEffectS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == EntityStatusEffectS2c::ID {
+            let pkt = pkt.decode::<EntityStatusEffectS2c>().unwrap();
+            assert_eq!(pkt.entity_id.0, 0);
+            assert_eq!(pkt.effect_id.0, StatusEffect::Speed.to_raw());
+            assert_eq!(pkt.amplifier, 1);
+        }
+    }
+
+    // The effect counts down without sending anything.
+    app.update();
+
+    let effects = app.world.get::<ActiveEffects>(client_ent).unwrap();
+    assert_eq!(effects.get(StatusEffect::Speed).unwrap().duration, Some(1));
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<EntityStatusEffectS2c>(0);
+    sent_packets.assert_count::<RemoveEntityStatusEffectS2c>(0);
+
+    app.update();
+
+    assert!(app
+        .world
+        .get::<ActiveEffects>(client_ent)
+        .unwrap()
+        .is_empty());
+
+    let events = app.world.resource::<Events<EffectExpiredEvent>>();
+    let expired: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].entity, client_ent);
+    assert_eq!(expired[0].effect, StatusEffect::Speed);
+
+    client_helper
+        .collect_sent()
+        .assert_count::<RemoveEntityStatusEffectS2c>(1);
+}
@@ -0,0 +1,123 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_entity::block_display::BlockDisplayEntityBundle;
+use valence_entity::entity::NoGravity;
+use valence_entity::packet::{EntityTrackerUpdateS2c, MoveRelativeS2c};
+use valence_entity::zombie::ZombieEntityBundle;
+use valence_entity::{DisplayTransformation, Location, Position};
+
+use super::*;
+
+fn setup(app: &mut App) -> (Entity, MockClientHelper) {
+    let (client_ent, mut client_helper) = scenario_single_client(app);
+
+    let instance_ent = find_instance(app);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(0.0, 0.0, 0.0);
+
+    let zombie_ent = app
+        .world
+        .spawn(ZombieEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([2.0, 0.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    (zombie_ent, client_helper)
+}
+
+#[test]
+fn unchanged_tracked_data_is_not_resent() {
+    let mut app = App::new();
+    let (zombie_ent, mut client_helper) = setup(&mut app);
+
+    app.world.get_mut::<NoGravity>(zombie_ent).unwrap().0 = true;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityTrackerUpdateS2c>(1);
+
+    // Writing the same value triggers change detection but doesn't change what
+    // the client sees.
+    app.world.get_mut::<NoGravity>(zombie_ent).unwrap().0 = true;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityTrackerUpdateS2c>(0);
+
+    app.world.get_mut::<NoGravity>(zombie_ent).unwrap().0 = false;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityTrackerUpdateS2c>(1);
+}
+
+#[test]
+fn small_movements_accumulate() {
+    let mut app = App::new();
+    let (zombie_ent, mut client_helper) = setup(&mut app);
+
+    // Less than half of the smallest distance the client can see.
+    let step = 1.0 / 4096.0 * 0.4;
+
+    app.world.get_mut::<Position>(zombie_ent).unwrap().0.x += step;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<MoveRelativeS2c>(0);
+
+    // The position has now moved far enough from the last position the client
+    // saw, even though this step alone is too small.
+    app.world.get_mut::<Position>(zombie_ent).unwrap().0.x += step;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<MoveRelativeS2c>(1);
+}
+
+#[test]
+fn repeated_interpolation_is_resent() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    let display_ent = app
+        .world
+        .spawn(BlockDisplayEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([2.0, 0.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    // Each call restarts the interpolation on the client, even with the same
+    // delay and duration.
+    for _ in 0..2 {
+        app.world
+            .query::<DisplayTransformation>()
+            .get_mut(&mut app.world, display_ent)
+            .unwrap()
+            .interpolate(0, 10);
+
+        app.update();
+
+        client_helper
+            .collect_sent()
+            .assert_count::<EntityTrackerUpdateS2c>(1);
+    }
+}
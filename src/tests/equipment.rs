@@ -0,0 +1,206 @@
+use bevy_app::App;
+use valence_client::packet::{PlayerAction, PlayerActionC2s};
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_entity::equipment::{Equipment, EquipmentSlot};
+use valence_entity::packet::{EntityEquipmentUpdateS2c, EquipmentEntry};
+use valence_entity::zombie::ZombieEntityBundle;
+use valence_entity::{Location, Position};
+use valence_instance::Chunk;
+use valence_inventory::packet::{ScreenHandlerSlotUpdateS2c, UpdateSelectedSlotC2s};
+use valence_inventory::{Inventory, SwapHands};
+
+use super::*;
+
+fn equipment_packets(client_helper: &mut MockClientHelper) -> Vec<EntityEquipmentUpdateS2c> {
+    client_helper
+        .collect_sent()
+        .0
+        .iter()
+        .filter(|frame| frame.id == EntityEquipmentUpdateS2c::ID)
+        .map(|frame| frame.decode::<EntityEquipmentUpdateS2c>().unwrap())
+        .collect()
+}
+
+#[test]
+fn equipment_is_sent_to_viewers() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+    let instance_ent = find_instance(&app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let helmet = ItemStack::new(ItemKind::IronHelmet, 1, None);
+
+    let mut equipment = Equipment::new();
+    equipment.set(EquipmentSlot::Head, helmet.clone());
+
+    let zombie_ent = app
+        .world
+        .spawn((
+            ZombieEntityBundle {
+                location: Location(instance_ent),
+                position: Position::new([2.0, 0.0, 0.0]),
+                ..Default::default()
+            },
+            equipment,
+        ))
+        .id();
+
+    app.update();
+
+    // The equipment is sent along with the entity.
+    let packets = equipment_packets(&mut client_helper);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(
+        packets[0].equipment,
+        vec![EquipmentEntry {
+            slot: EquipmentSlot::Head.to_raw(),
+            item: Some(helmet),
+        }]
+    );
+
+    let sword = ItemStack::new(ItemKind::IronSword, 1, None);
+
+    app.world
+        .get_mut::<Equipment>(zombie_ent)
+        .unwrap()
+        .set(EquipmentSlot::MainHand, sword.clone());
+
+    app.update();
+
+    // Only the changed slot is sent.
+    let packets = equipment_packets(&mut client_helper);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(
+        packets[0].equipment,
+        vec![EquipmentEntry {
+            slot: EquipmentSlot::MainHand.to_raw(),
+            item: Some(sword.clone()),
+        }]
+    );
+
+    // Setting the same item again sends nothing.
+    app.world
+        .get_mut::<Equipment>(zombie_ent)
+        .unwrap()
+        .set(EquipmentSlot::MainHand, sword);
+
+    app.update();
+
+    assert!(equipment_packets(&mut client_helper).is_empty());
+}
+
+#[test]
+fn player_equipment_is_shown_to_other_clients() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    let (viewer, mut viewer_helper) = create_mock_client();
+    let viewer_ent = app.world.spawn(viewer).id();
+    app.world.get_mut::<Location>(viewer_ent).unwrap().0 = instance_ent;
+
+    // Process a few ticks to get past the "on join" logic.
+    app.update();
+    app.update();
+    client_helper.clear_sent();
+    viewer_helper.clear_sent();
+
+    let sword = ItemStack::new(ItemKind::IronSword, 1, None);
+    let helmet = ItemStack::new(ItemKind::IronHelmet, 1, None);
+
+    let mut inventory = app.world.get_mut::<Inventory>(client_ent).unwrap();
+    inventory.set_slot(36, sword.clone());
+    inventory.set_slot(5, helmet.clone());
+
+    app.update();
+
+    // The client sees its own inventory, not its equipment.
+    assert!(equipment_packets(&mut client_helper).is_empty());
+
+    let packets = equipment_packets(&mut viewer_helper);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(
+        packets[0].equipment,
+        vec![
+            EquipmentEntry {
+                slot: EquipmentSlot::MainHand.to_raw(),
+                item: Some(sword.clone()),
+            },
+            EquipmentEntry {
+                slot: EquipmentSlot::Head.to_raw(),
+                item: Some(helmet),
+            },
+        ]
+    );
+
+    client_helper.send(&PlayerActionC2s {
+        action: PlayerAction::SwapItemWithOffhand,
+        position: BlockPos::new(0, 0, 0),
+        direction: Direction::Down,
+        sequence: VarInt(0),
+    });
+
+    app.update();
+
+    let events = app.world.resource::<Events<SwapHands>>();
+    let swaps: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(swaps.len(), 1);
+    assert_eq!(swaps[0].main_hand, None);
+    assert_eq!(swaps[0].off_hand, Some(sword.clone()));
+
+    // The client doesn't swap the items by itself.
+    client_helper
+        .collect_sent()
+        .assert_count::<ScreenHandlerSlotUpdateS2c>(2);
+
+    let packets = equipment_packets(&mut viewer_helper);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(
+        packets[0].equipment,
+        vec![
+            EquipmentEntry {
+                slot: EquipmentSlot::MainHand.to_raw(),
+                item: None,
+            },
+            EquipmentEntry {
+                slot: EquipmentSlot::OffHand.to_raw(),
+                item: Some(sword),
+            },
+        ]
+    );
+
+    let diamond = ItemStack::new(ItemKind::Diamond, 1, None);
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(37, diamond.clone());
+
+    app.update();
+    viewer_helper.clear_sent();
+
+    // Selecting another hotbar slot changes the held item.
+    client_helper.send(&UpdateSelectedSlotC2s { slot: 1 });
+
+    app.update();
+
+    let packets = equipment_packets(&mut viewer_helper);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(
+        packets[0].equipment,
+        vec![EquipmentEntry {
+            slot: EquipmentSlot::MainHand.to_raw(),
+            item: Some(diamond),
+        }]
+    );
+}
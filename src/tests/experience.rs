@@ -0,0 +1,93 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_client::packet::ExperienceBarUpdateS2c;
+use valence_entity::packet::ItemPickupAnimationS2c;
+use valence_entity::Position;
+use valence_experience::{
+    points_to_next_level, split_experience, total_points_for_level, Experience,
+    ExperienceOrbBundle, ExperiencePickupEvent,
+};
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn experience_level_curve() {
+    for level in [0, 1, 15, 16, 17, 30, 31, 32, 50] {
+        assert_eq!(
+            total_points_for_level(level) + points_to_next_level(level),
+            total_points_for_level(level + 1),
+            "level {level}"
+        );
+
+        let exp = Experience::from_total(total_points_for_level(level));
+        assert_eq!(exp.level, level);
+        assert_eq!(exp.progress, 0.0);
+    }
+
+    let mut exp = Experience::from_level(15);
+    exp.add_points(points_to_next_level(15) + points_to_next_level(16) / 2);
+    assert_eq!(exp.level, 16);
+    assert!((exp.progress - 0.5).abs() < 1e-4);
+
+    assert_eq!(split_experience(10).collect::<Vec<_>>(), [7, 3]);
+    assert_eq!(split_experience(0).count(), 0);
+}
+
+#[test]
+fn experience_orb_is_picked_up() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::ZERO;
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    app.world.get_mut::<Experience>(client_ent).unwrap().level = 5;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<ExperienceBarUpdateS2c>(1);
+
+    let orb_ent = app
+        .world
+        .spawn(ExperienceOrbBundle::new(
+            instance_ent,
+            DVec3::new(0.5, 1.0, 0.0),
+            3,
+        ))
+        .id();
+
+    app.update();
+
+    let events = app.world.resource::<Events<ExperiencePickupEvent>>();
+    let events: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(
+        events,
+        [&ExperiencePickupEvent {
+            client: client_ent,
+            orb: orb_ent,
+            value: 3,
+        }]
+    );
+
+    assert_eq!(app.world.get::<Experience>(client_ent).unwrap().total, 3);
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<ItemPickupAnimationS2c>(1);
+    sent_packets.assert_count::<ExperienceBarUpdateS2c>(1);
+
+    app.update();
+
+    assert!(app.world.get_entity(orb_ent).is_none());
+}
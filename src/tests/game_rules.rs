@@ -0,0 +1,115 @@
+use bevy_app::App;
+use valence_client::game_rules::GameRules;
+use valence_client::packet::{GameEventKind, GameStateChangeS2c};
+use valence_client::time::{TimeOfDay, WorldAge};
+use valence_client::{HasRespawnScreen, ReducedDebugInfo};
+use valence_entity::packet::EntityStatusS2c;
+use valence_instance::packet::WorldTimeUpdateS2c;
+
+use super::*;
+
+fn setup(app: &mut App) -> (Entity, Entity, MockClientHelper) {
+    let (client_ent, mut client_helper) = scenario_single_client(app);
+
+    let instance_ent = find_instance(app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    (client_ent, instance_ent, client_helper)
+}
+
+#[test]
+fn game_rules_are_synced_to_clients() {
+    let mut app = App::new();
+    let (client_ent, instance_ent, mut client_helper) = setup(&mut app);
+
+    app.world.entity_mut(instance_ent).insert(GameRules {
+        reduced_debug_info: true,
+        immediate_respawn: true,
+        ..Default::default()
+    });
+
+    app.update();
+
+    assert_eq!(
+        app.world.get::<ReducedDebugInfo>(client_ent),
+        Some(&ReducedDebugInfo(true))
+    );
+    assert_eq!(
+        app.world.get::<HasRespawnScreen>(client_ent),
+        Some(&HasRespawnScreen(false))
+    );
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<EntityStatusS2c>(1);
+    frames.assert_count::<GameStateChangeS2c>(1);
+
+    for frame in &frames.0 {
+        if frame.id == EntityStatusS2c::ID {
+            let pkt = frame.decode::<EntityStatusS2c>().unwrap();
+            assert_eq!(pkt.entity_status, 22);
+        } else if frame.id == GameStateChangeS2c::ID {
+            let pkt = frame.decode::<GameStateChangeS2c>().unwrap();
+            assert_eq!(pkt.kind, GameEventKind::EnableRespawnScreen);
+            assert_eq!(pkt.value, 1.0);
+        }
+    }
+
+    // Unrelated rules don't resend anything.
+    app.world
+        .get_mut::<GameRules>(instance_ent)
+        .unwrap()
+        .keep_inventory = true;
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<EntityStatusS2c>(0);
+    frames.assert_count::<GameStateChangeS2c>(0);
+}
+
+#[test]
+fn daylight_cycle_and_world_age() {
+    let mut app = App::new();
+    let (_, instance_ent, mut client_helper) = setup(&mut app);
+
+    app.world.entity_mut(instance_ent).insert((
+        TimeOfDay(1000),
+        WorldAge(500),
+        GameRules {
+            daylight_cycle: false,
+            ..Default::default()
+        },
+    ));
+
+    app.update();
+    app.update();
+
+    assert_eq!(
+        app.world.get::<TimeOfDay>(instance_ent),
+        Some(&TimeOfDay(1000))
+    );
+    assert_eq!(
+        app.world.get::<WorldAge>(instance_ent),
+        Some(&WorldAge(502))
+    );
+
+    app.world.get_mut::<WorldAge>(instance_ent).unwrap().0 = 10;
+    client_helper.clear_sent();
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<WorldTimeUpdateS2c>(1);
+
+    for frame in &frames.0 {
+        if frame.id == WorldTimeUpdateS2c::ID {
+            let pkt = frame.decode::<WorldTimeUpdateS2c>().unwrap();
+            assert_eq!(pkt.world_age, 11);
+            // The time of day is frozen.
+            assert_eq!(pkt.time_of_day, -1000);
+        }
+    }
+}
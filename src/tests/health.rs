@@ -0,0 +1,141 @@
+use bevy_app::App;
+use valence_client::packet::{DamageTiltS2c, DeathMessageS2c, HealthUpdateS2c};
+use valence_core::game_mode::GameMode;
+use valence_entity::packet::EntityDamageS2c;
+use valence_entity::EntityId;
+use valence_health::{DamageEvent, DamageTypeRegistry, DeathEvent, Food, Health};
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn damage_updates_health_and_kills() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    // Clients start with full health and food.
+    assert_eq!(app.world.get::<Health>(client_ent).unwrap().0, 20.0);
+    assert_eq!(*app.world.get::<Food>(client_ent).unwrap(), Food(20));
+
+    client_helper.clear_sent();
+
+    let generic = app
+        .world
+        .resource::<DamageTypeRegistry>()
+        .index_of(ident!("generic"))
+        .unwrap();
+
+    app.world
+        .send_event(DamageEvent::new(client_ent, 5.0, generic));
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(client_ent).unwrap().0, 15.0);
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<EntityDamageS2c>(1);
+    sent_packets.assert_count::<DamageTiltS2c>(1);
+    sent_packets.assert_count::<HealthUpdateS2c>(1);
+    sent_packets.assert_count::<DeathMessageS2c>(0);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == HealthUpdateS2c::ID {
+            let pkt = pkt.decode::<HealthUpdateS2c>().unwrap();
+            assert_eq!(pkt.health, 15.0);
+            assert_eq!(pkt.food.0, 20);
+        }
+    }
+
+    // Creative players are invulnerable to generic damage.
+    *app.world.get_mut::<GameMode>(client_ent).unwrap() = GameMode::Creative;
+    app.world
+        .send_event(DamageEvent::new(client_ent, 100.0, generic));
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(client_ent).unwrap().0, 15.0);
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityDamageS2c>(0);
+
+    *app.world.get_mut::<GameMode>(client_ent).unwrap() = GameMode::Survival;
+    app.world
+        .send_event(DamageEvent::new(client_ent, 100.0, generic));
+    app.update();
+
+    assert_eq!(app.world.get::<Health>(client_ent).unwrap().0, 0.0);
+
+    let deaths = app.world.resource::<Events<DeathEvent>>();
+    let deaths: Vec<_> = deaths.iter_current_update_events().collect();
+    assert_eq!(deaths.len(), 1);
+    assert_eq!(deaths[0].entity, client_ent);
+    assert_eq!(deaths[0].damage_type, generic);
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<DeathMessageS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == DeathMessageS2c::ID {
+            let pkt = pkt.decode::<DeathMessageS2c>().unwrap();
+            assert_eq!(pkt.player_id.0, 0);
+        }
+    }
+
+    // Dead entities can't be damaged again.
+    app.world
+        .send_event(DamageEvent::new(client_ent, 1.0, generic));
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityDamageS2c>(0);
+}
+
+#[test]
+fn damage_is_shown_to_viewers_once() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    let (viewer, mut viewer_helper) = create_mock_client();
+    let viewer_ent = app.world.spawn(viewer).id();
+    app.world.get_mut::<Location>(viewer_ent).unwrap().0 = instance_ent;
+
+    // Process a few ticks to get past the "on join" logic.
+    app.update();
+    app.update();
+    client_helper.clear_sent();
+    viewer_helper.clear_sent();
+
+    let generic = app
+        .world
+        .resource::<DamageTypeRegistry>()
+        .index_of(ident!("generic"))
+        .unwrap();
+
+    app.world
+        .send_event(DamageEvent::new(client_ent, 5.0, generic));
+    app.update();
+
+    let damage_ids = |helper: &mut MockClientHelper| {
+        helper
+            .collect_sent()
+            .0
+            .iter()
+            .filter(|f| f.id == EntityDamageS2c::ID)
+            .map(|f| f.decode::<EntityDamageS2c>().unwrap().entity_id.0)
+            .collect::<Vec<_>>()
+    };
+
+    // The victim only gets the copy with its own ID.
+    assert_eq!(damage_ids(&mut client_helper), [0]);
+
+    let id = app.world.get::<EntityId>(client_ent).unwrap().get();
+    assert_eq!(damage_ids(&mut viewer_helper), [id]);
+}
@@ -0,0 +1,214 @@
+use bevy_app::App;
+use glam::{DVec3, Vec3};
+use valence_block::BlockState;
+use valence_client::interact_block::PlayerInteractBlockC2s;
+use valence_client::packet::{PlayerAction, PlayerActionC2s};
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_core::game_mode::GameMode;
+use valence_core::hand::Hand;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_entity::{OnGround, Position};
+use valence_instance::packet::BlockUpdateS2c;
+use valence_instance::Chunk;
+use valence_interaction::{
+    BreakBlockEvent, CancelInteraction, InteractionSettings, PlaceBlockEvent, StartDiggingEvent,
+};
+use valence_inventory::packet::ScreenHandlerSlotUpdateS2c;
+use valence_inventory::Inventory;
+
+use super::*;
+
+fn setup(app: &mut App) -> (Entity, MockClientHelper) {
+    let (client_ent, mut client_helper) = scenario_single_client(app);
+
+    let instance_ent = find_instance(app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    instance.set_block([8, 319, 8], BlockState::STONE);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 8.5);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    (client_ent, client_helper)
+}
+
+fn cancel_breaks(
+    mut breaks: EventReader<BreakBlockEvent>,
+    mut cancel: EventWriter<CancelInteraction>,
+) {
+    for event in breaks.iter() {
+        cancel.send(event.cancel());
+    }
+}
+
+#[test]
+fn cancelled_break_is_resynced() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    app.add_system(cancel_breaks);
+    *app.world.get_mut::<GameMode>(client_ent).unwrap() = GameMode::Creative;
+
+    client_helper.send(&PlayerActionC2s {
+        action: PlayerAction::StartDestroyBlock,
+        position: BlockPos::new(8, 0, 9),
+        direction: Direction::Up,
+        sequence: VarInt(1),
+    });
+
+    app.update();
+
+    let events = app.world.resource::<Events<BreakBlockEvent>>();
+    let breaks: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(breaks.len(), 1);
+    assert_eq!(breaks[0].position, BlockPos::new(8, 0, 9));
+    assert_eq!(breaks[0].state, BlockState::STONE);
+    assert!(breaks[0].instant);
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<BlockUpdateS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == BlockUpdateS2c::ID {
+            let pkt = pkt.decode::<BlockUpdateS2c>().unwrap();
+            assert_eq!(pkt.position, BlockPos::new(8, 0, 9));
+            assert_eq!(pkt.block_id.0, BlockState::STONE.to_raw() as i32);
+        }
+    }
+
+    // Out of reach breaks are rejected without an event.
+    client_helper.send(&PlayerActionC2s {
+        action: PlayerAction::StartDestroyBlock,
+        position: BlockPos::new(8, 319, 8),
+        direction: Direction::Up,
+        sequence: VarInt(2),
+    });
+
+    app.update();
+
+    let events = app.world.resource::<Events<BreakBlockEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+    client_helper
+        .collect_sent()
+        .assert_count::<BlockUpdateS2c>(1);
+}
+
+#[test]
+fn survival_break_time_is_validated() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    app.world.get_mut::<OnGround>(client_ent).unwrap().0 = true;
+
+    client_helper.send(&PlayerActionC2s {
+        action: PlayerAction::StartDestroyBlock,
+        position: BlockPos::new(8, 0, 9),
+        direction: Direction::Up,
+        sequence: VarInt(1),
+    });
+
+    app.update();
+
+    // Stone needs a pickaxe, so it takes 150 ticks to break by hand.
+    let events = app.world.resource::<Events<StartDiggingEvent>>();
+    let starts: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(starts.len(), 1);
+    assert_eq!(starts[0].break_ticks, Some(150));
+
+    client_helper.clear_sent();
+
+    // Finishing right away is rejected and the block is resynced.
+    client_helper.send(&PlayerActionC2s {
+        action: PlayerAction::StopDestroyBlock,
+        position: BlockPos::new(8, 0, 9),
+        direction: Direction::Up,
+        sequence: VarInt(2),
+    });
+
+    app.update();
+
+    let events = app.world.resource::<Events<BreakBlockEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+    client_helper
+        .collect_sent()
+        .assert_count::<BlockUpdateS2c>(1);
+}
+
+#[test]
+fn place_block_is_validated() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Stone, 1, None));
+
+    app.update();
+    client_helper.clear_sent();
+
+    let place = |client_helper: &mut MockClientHelper, position| {
+        client_helper.send(&PlayerInteractBlockC2s {
+            hand: Hand::Main,
+            position,
+            face: Direction::Up,
+            cursor_pos: Vec3::new(0.5, 1.0, 0.5),
+            head_inside_block: false,
+            sequence: VarInt(1),
+        });
+    };
+
+    place(&mut client_helper, BlockPos::new(9, 0, 8));
+    app.update();
+
+    let events = app.world.resource::<Events<PlaceBlockEvent>>();
+    let places: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(places.len(), 1);
+    assert_eq!(places[0].position, BlockPos::new(9, 1, 8));
+    assert_eq!(places[0].against, BlockPos::new(9, 0, 8));
+    assert_eq!(places[0].state, BlockState::STONE);
+    assert_eq!(places[0].item.item, ItemKind::Stone);
+
+    client_helper
+        .collect_sent()
+        .assert_count::<BlockUpdateS2c>(0);
+
+    // Above the build height.
+    app.world.resource_mut::<InteractionSettings>().block_reach = 1000.0;
+
+    place(&mut client_helper, BlockPos::new(8, 319, 8));
+    app.update();
+
+    let events = app.world.resource::<Events<PlaceBlockEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    // The block clicked and the held item are sent again.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<BlockUpdateS2c>(1);
+    sent_packets.assert_count::<ScreenHandlerSlotUpdateS2c>(1);
+
+    // Out of reach.
+    app.world.resource_mut::<InteractionSettings>().block_reach = 6.0;
+
+    place(&mut client_helper, BlockPos::new(15, 0, 15));
+    app.update();
+
+    let events = app.world.resource::<Events<PlaceBlockEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<BlockUpdateS2c>(2);
+    sent_packets.assert_count::<ScreenHandlerSlotUpdateS2c>(1);
+}
@@ -6,9 +6,10 @@ use valence_inventory::packet::{
     OpenScreenS2c, ScreenHandlerSlotUpdateS2c, SlotChange, UpdateSelectedSlotC2s,
 };
 use valence_inventory::{
-    convert_to_player_slot_id, ClientInventoryState, CursorItem, DropItemStack, HeldItem,
-    Inventory, InventoryKind, OpenInventory,
+    convert_to_player_slot_id, ClickSlot, ClientInventoryState, CreativeInventoryAction,
+    CursorItem, DropItemStack, HeldItem, Inventory, InventoryKind, OpenInventory,
 };
+use valence_nbt::{compound, List};
 
 use super::*;
 
@@ -77,6 +78,46 @@ fn test_should_close_inventory() {
     sent_packets.assert_count::<CloseScreenS2c>(1);
 }
 
+#[test]
+fn test_should_reopen_inventory() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let inventory = Inventory::new(InventoryKind::Generic3x3);
+    let inventory_ent = app.world.spawn(inventory).id();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(OpenInventory::new(inventory_ent));
+
+    app.update();
+    client_helper.clear_sent();
+
+    let window_id = app
+        .world
+        .get::<ClientInventoryState>(client_ent)
+        .unwrap()
+        .window_id();
+
+    // Replacing the open inventory opens it again.
+    app.world
+        .entity_mut(client_ent)
+        .insert(OpenInventory::new(inventory_ent));
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+
+    sent_packets.assert_count::<OpenScreenS2c>(1);
+    sent_packets.assert_count::<CloseScreenS2c>(0);
+
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    assert_ne!(inv_state.window_id(), window_id);
+}
+
 #[test]
 fn test_should_remove_invalid_open_inventory() {
     let mut app = App::new();
@@ -302,6 +343,170 @@ fn test_should_modify_open_inventory_click_slot() {
     );
 }
 
+#[test]
+fn test_should_not_modify_readonly_inventory_click_slot() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let inventory_ent = set_up_open_inventory(&mut app, client_ent);
+    let mut inventory = app
+        .world
+        .get_mut::<Inventory>(inventory_ent)
+        .expect("could not find inventory for client");
+    inventory.set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+    inventory.readonly = true;
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    // Make the client shift click the item into its own inventory.
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    let state_id = inv_state.state_id();
+    let window_id = inv_state.window_id();
+    client_helper.send(&ClickSlotC2s {
+        window_id,
+        state_id: VarInt(state_id.0),
+        slot_idx: 20,
+        button: 0,
+        mode: ClickMode::ShiftClick,
+        slot_changes: vec![
+            SlotChange {
+                idx: 20,
+                item: None,
+            },
+            SlotChange {
+                idx: 27,
+                item: Some(ItemStack::new(ItemKind::Diamond, 2, None)),
+            },
+        ],
+        carried_item: None,
+    });
+
+    app.update();
+
+    // The click is undone.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    let inventory = app
+        .world
+        .get::<Inventory>(inventory_ent)
+        .expect("could not find inventory");
+    assert_eq!(
+        inventory.slot(20),
+        Some(&ItemStack::new(ItemKind::Diamond, 2, None))
+    );
+
+    let player_inventory = app
+        .world
+        .get::<Inventory>(client_ent)
+        .expect("could not find client");
+    assert_eq!(player_inventory.slot(9), None);
+
+    // The click is still sent as an event.
+    let events = app
+        .world
+        .get_resource::<Events<ClickSlot>>()
+        .expect("expected click slot events");
+    assert_eq!(events.iter_current_update_events().count(), 1);
+}
+
+#[test]
+fn test_should_swap_offhand_in_open_inventory() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let inventory_ent = set_up_open_inventory(&mut app, client_ent);
+    app.world
+        .get_mut::<Inventory>(inventory_ent)
+        .unwrap()
+        .set_slot(5, ItemStack::new(ItemKind::Diamond, 2, None));
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    // The offhand isn't part of the window, so the client only reports the
+    // clicked slot.
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    client_helper.send(&ClickSlotC2s {
+        window_id: inv_state.window_id(),
+        state_id: VarInt(inv_state.state_id().0),
+        slot_idx: 5,
+        button: 40,
+        mode: ClickMode::Hotbar,
+        slot_changes: vec![SlotChange { idx: 5, item: None }],
+        carried_item: None,
+    });
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<InventoryS2c>(0);
+    sent_packets.assert_count::<ScreenHandlerSlotUpdateS2c>(0);
+
+    let inventory = app.world.get::<Inventory>(inventory_ent).unwrap();
+    assert_eq!(inventory.slot(5), None);
+
+    let player_inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(
+        player_inventory.slot(45),
+        Some(&ItemStack::new(ItemKind::Diamond, 2, None))
+    );
+}
+
+#[test]
+fn test_should_resync_on_state_id_mismatch() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    let inventory_ent = set_up_open_inventory(&mut app, client_ent);
+    app.world
+        .get_mut::<Inventory>(inventory_ent)
+        .unwrap()
+        .set_slot(20, ItemStack::new(ItemKind::Diamond, 2, None));
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    let state_id = inv_state.state_id();
+    client_helper.send(&ClickSlotC2s {
+        window_id: inv_state.window_id(),
+        state_id: VarInt(state_id.0 - 1),
+        slot_idx: 20,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![SlotChange {
+            idx: 20,
+            item: None,
+        }],
+        carried_item: Some(ItemStack::new(ItemKind::Diamond, 2, None)),
+    });
+
+    app.update();
+
+    // The click is ignored and the client is sent the whole window.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == InventoryS2c::ID {
+            let pkt = pkt.decode::<InventoryS2c>().unwrap();
+            assert_eq!(pkt.slots.len(), 27 + 36);
+            assert_ne!(pkt.state_id.0, state_id.0);
+        }
+    }
+
+    let inventory = app.world.get::<Inventory>(inventory_ent).unwrap();
+    assert_eq!(
+        inventory.slot(20),
+        Some(&ItemStack::new(ItemKind::Diamond, 2, None))
+    );
+
+    let cursor_item = app.world.get::<CursorItem>(client_ent).unwrap();
+    assert_eq!(cursor_item.0, None);
+}
+
 #[test]
 fn test_should_modify_open_inventory_server_side() {
     let mut app = App::new();
@@ -395,6 +600,58 @@ fn test_set_creative_mode_slot_handling() {
     );
 }
 
+#[test]
+fn test_creative_mode_slot_policy() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+    *app.world.get_mut::<GameMode>(client_ent).unwrap() = GameMode::Creative;
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let shulker_box = ItemStack::new(
+        ItemKind::ShulkerBox,
+        1,
+        Some(compound! {
+            "BlockEntityTag" => compound! {
+                "Items" => List::Compound(vec![]),
+            },
+        }),
+    );
+
+    client_helper.send(&CreativeInventoryActionC2s {
+        slot: 36,
+        clicked_item: Some(shulker_box.clone()),
+    });
+    client_helper.send(&CreativeInventoryActionC2s {
+        slot: 37,
+        clicked_item: Some(ItemStack::new(ItemKind::CommandBlock, 1, None)),
+    });
+
+    app.update();
+
+    // The client is told what it got instead.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<ScreenHandlerSlotUpdateS2c>(2);
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(
+        inventory.slot(36),
+        Some(&ItemStack::new(ItemKind::ShulkerBox, 1, None))
+    );
+    assert_eq!(inventory.slot(37), None);
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<CreativeInventoryAction>>()
+        .iter_current_update_events()
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].requested_item, Some(shulker_box));
+    assert_eq!(events[1].clicked_item, None);
+}
+
 #[test]
 fn test_ignore_set_creative_mode_slot_if_not_creative() {
     let mut app = App::new();
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use bevy_app::App;
+use valence_client::keepalive::{
+    ClientLagging, ClientTimedOut, KeepAliveC2s, KeepAliveS2c, KeepaliveSettings,
+};
+use valence_client::{Client, Ping};
+
+use super::*;
+
+fn last_keepalive_id(client_helper: &mut MockClientHelper) -> u64 {
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<KeepAliveS2c>(1);
+
+    sent_packets
+        .0
+        .iter()
+        .find(|f| f.id == KeepAliveS2c::ID)
+        .unwrap()
+        .decode::<KeepAliveS2c>()
+        .unwrap()
+        .id
+}
+
+#[test]
+fn keepalive_updates_ping() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.insert_resource(KeepaliveSettings {
+        period: Duration::ZERO,
+        ..Default::default()
+    });
+
+    app.update();
+
+    assert_eq!(*app.world.get::<Ping>(client_ent).unwrap(), Ping(-1));
+
+    let id = last_keepalive_id(&mut client_helper);
+    client_helper.send(&KeepAliveC2s { id });
+
+    app.update();
+
+    assert!(app.world.get::<Ping>(client_ent).unwrap().0 >= 0);
+
+    let events = app.world.resource::<Events<ClientLagging>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    // The next keepalive is sent right away.
+    client_helper.collect_sent().assert_count::<KeepAliveS2c>(1);
+}
+
+#[test]
+fn unanswered_keepalive_times_out() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.insert_resource(KeepaliveSettings {
+        period: Duration::ZERO,
+        lag_threshold: Duration::ZERO,
+        timeout: Duration::from_millis(50),
+    });
+
+    app.update();
+
+    last_keepalive_id(&mut client_helper);
+
+    app.update();
+
+    // The client is lagging but hasn't timed out yet.
+    let events = app.world.resource::<Events<ClientLagging>>();
+    let lagging: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(lagging.len(), 1);
+    assert_eq!(lagging[0].client, client_ent);
+    assert!(app.world.get::<Client>(client_ent).is_some());
+
+    std::thread::sleep(Duration::from_millis(60));
+
+    app.update();
+
+    let events = app.world.resource::<Events<ClientTimedOut>>();
+    let timed_out: Vec<_> = events.iter_current_update_events().collect();
+    assert_eq!(timed_out.len(), 1);
+    assert_eq!(timed_out[0].client, client_ent);
+    assert!(timed_out[0].delay >= Duration::from_millis(50));
+
+    // Lag is only reported once per keepalive.
+    let events = app.world.resource::<Events<ClientLagging>>();
+    assert_eq!(events.iter_current_update_events().count(), 0);
+
+    assert!(app.world.get::<Client>(client_ent).is_none());
+}
@@ -0,0 +1,85 @@
+use bevy_app::App;
+use valence_client::settings::ClientSettings;
+use valence_core::protocol::packet::chat::GameMessageS2c;
+use valence_core::text::{Text, TextFormat};
+use valence_localization::{LocalizedMessages, LocalizedText, Translations};
+
+use super::*;
+
+fn chat_messages(frames: &PacketFrames) -> Vec<Text> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == GameMessageS2c::ID)
+        .map(|f| f.decode::<GameMessageS2c>().unwrap().chat.into_owned())
+        .collect()
+}
+
+#[test]
+fn localized_messages_use_client_locale() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut translations = app.world.resource_mut::<Translations>();
+    translations.insert("en_us", "greeting", "Hello, %s!");
+    translations.insert("en_us", "farewell", "Bye");
+    translations.insert("de_de", "greeting", "Hallo, %s!");
+
+    app.world
+        .get_mut::<ClientSettings>(client_ent)
+        .unwrap()
+        .locale = "de_de".into();
+
+    let mut messages = app.world.get_mut::<LocalizedMessages>(client_ent).unwrap();
+    messages.send_chat_message(LocalizedText::new("greeting", ["Steve".bold()]));
+    // Falls back to English.
+    messages.send_chat_message(LocalizedText::new("farewell", []));
+    // The client translates unknown keys.
+    messages.send_chat_message(LocalizedText::new(
+        "chat.type.text",
+        ["a".into(), "b".into()],
+    ));
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    assert_eq!(
+        chat_messages(&frames),
+        [
+            Text::default() + "Hallo, " + "Steve".bold() + "!",
+            Text::default() + "Bye",
+            Text::translate("chat.type.text", ["a".into(), "b".into()]),
+        ]
+    );
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<GameMessageS2c>(0);
+}
+
+#[test]
+fn localized_templates() {
+    let mut translations = Translations::new();
+    translations
+        .insert_json(
+            "en_us",
+            r#"{"swap": "%2$s before %1$s, %s%% done", "broken": "%3$s %x"}"#,
+        )
+        .unwrap();
+
+    let args = ["a".into_text(), "b".into_text()];
+
+    assert_eq!(
+        translations.localize(&LocalizedText::new("swap", args.clone()), "en_US"),
+        Text::default() + "b" + " before " + "a" + ", " + "a" + "% done"
+    );
+    assert_eq!(
+        translations.localize(&LocalizedText::new("broken", args), "en_us"),
+        Text::default() + "%3$s %x"
+    );
+}
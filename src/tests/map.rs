@@ -0,0 +1,93 @@
+use bevy_app::App;
+use valence_entity::Location;
+use valence_map::packet::{IconType, MapUpdateS2c};
+use valence_map::{MapBundle, MapCanvas, MapIcon, MapIcons, MapManager};
+
+use super::*;
+
+fn map_updates(frames: &PacketFrames) -> Vec<MapUpdateS2c<'_>> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == MapUpdateS2c::ID)
+        .map(|f| f.decode::<MapUpdateS2c>().unwrap())
+        .collect()
+}
+
+#[test]
+fn map_updates_only_send_changes() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let instance_ent = app.world.get::<Location>(client_ent).unwrap().0;
+    let id = app.world.resource_mut::<MapManager>().next_id();
+    let map_ent = app.world.spawn(MapBundle::new(id, instance_ent)).id();
+
+    app.update();
+
+    // New maps are sent in full.
+    let frames = client_helper.collect_sent();
+    let updates = map_updates(&frames);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].map_id.0, id.get());
+    assert_eq!(updates[0].icons, Some(vec![]));
+    assert_eq!(updates[0].data.unwrap().data.len(), 128 * 128);
+
+    let mut canvas = app.world.get_mut::<MapCanvas>(map_ent).unwrap();
+    canvas.set_pixel(10, 20, 34);
+    canvas.set_pixel(12, 21, 34);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = map_updates(&frames);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].icons, None);
+
+    let data = updates[0].data.unwrap();
+    assert_eq!((data.columns, data.rows), (3, 2));
+    assert_eq!(data.position, [10, 20]);
+    assert_eq!(data.data, [34, 0, 0, 0, 0, 34]);
+
+    app.world.get_mut::<MapIcons>(map_ent).unwrap().0 =
+        vec![MapIcon::new(IconType::RedPointer, 64, 64)];
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = map_updates(&frames);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].data, None);
+    assert_eq!(updates[0].icons.as_ref().unwrap()[0].position, [0, 0]);
+
+    app.update();
+
+    client_helper.collect_sent().assert_count::<MapUpdateS2c>(0);
+}
+
+#[test]
+fn maps_sent_on_join() {
+    let mut app = App::new();
+    let (client_ent, _) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let instance_ent = app.world.get::<Location>(client_ent).unwrap().0;
+    let id = app.world.resource_mut::<MapManager>().next_id();
+    app.world.spawn(MapBundle::new(id, instance_ent));
+
+    app.update();
+
+    let (client, mut client_helper) = create_mock_client();
+    let client_ent = app.world.spawn(client).id();
+    app.world.get_mut::<Location>(client_ent).unwrap().0 = instance_ent;
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = map_updates(&frames);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].map_id.0, id.get());
+}
@@ -0,0 +1,148 @@
+use bevy_app::App;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_inventory::packet::{ClickMode, ClickSlotC2s, InventoryS2c, SlotChange};
+use valence_inventory::{
+    ClientInventoryState, CursorItem, Inventory, InventoryKind, OpenInventory,
+};
+use valence_menu::{Menu, MenuClickEvent, MenuItem, Pages};
+
+use super::*;
+
+fn open_menu(app: &mut App, client_ent: Entity, kind: InventoryKind, menu: Menu) -> Entity {
+    let menu_ent = app.world.spawn((Inventory::new(kind), menu)).id();
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(OpenInventory::new(menu_ent));
+
+    menu_ent
+}
+
+fn click_slot(client_ent: Entity, app: &App, client_helper: &mut MockClientHelper, slot: i16) {
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    let stack = app
+        .world
+        .get::<OpenInventory>(client_ent)
+        .and_then(|open| app.world.get::<Inventory>(open.entity))
+        .and_then(|inv| inv.slot(slot as u16).cloned());
+
+    // The client picks up the item in the slot.
+    client_helper.send(&ClickSlotC2s {
+        window_id: inv_state.window_id(),
+        state_id: VarInt(inv_state.state_id().0),
+        slot_idx: slot,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![SlotChange {
+            idx: slot,
+            item: None,
+        }],
+        carried_item: stack,
+    });
+}
+
+#[test]
+fn menu_items_are_not_taken() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let diamond = ItemStack::new(ItemKind::Diamond, 1, None);
+    let menu = Menu::new().with_item(
+        4,
+        MenuItem::new(diamond.clone()).on_click(|menu, click, _| {
+            menu.set_item(click.slot + 1, ItemStack::new(ItemKind::Emerald, 1, None));
+        }),
+    );
+
+    let menu_ent = open_menu(&mut app, client_ent, InventoryKind::Generic9x1, menu);
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(menu_ent).unwrap();
+    assert!(inventory.readonly);
+    assert_eq!(inventory.slot(4), Some(&diamond));
+
+    client_helper.clear_sent();
+    click_slot(client_ent, &app, &mut client_helper, 4);
+    app.update();
+
+    // The client is told to put the item back.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<InventoryS2c>(1);
+
+    let cursor_item = app.world.get::<CursorItem>(client_ent).unwrap();
+    assert_eq!(cursor_item.0, None);
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<MenuClickEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        [MenuClickEvent {
+            client: client_ent,
+            menu: menu_ent,
+            slot: 4,
+            button: 0,
+            mode: ClickMode::Click,
+        }]
+    );
+
+    // The click handler added an item.
+    let inventory = app.world.get::<Inventory>(menu_ent).unwrap();
+    assert_eq!(inventory.slot(4), Some(&diamond));
+    assert_eq!(
+        inventory.slot(5),
+        Some(&ItemStack::new(ItemKind::Emerald, 1, None))
+    );
+}
+
+#[test]
+fn menu_pages() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let items = (1..=5).map(|count| ItemStack::new(ItemKind::Stone, count, None));
+    let menu = Menu::new().with_pages(Pages::new([0, 1], 7, 8).with_items(items));
+
+    assert_eq!(menu.pages.as_ref().unwrap().page_count(), 3);
+
+    let menu_ent = open_menu(&mut app, client_ent, InventoryKind::Generic9x1, menu);
+    app.update();
+
+    let arrow = ItemStack::new(ItemKind::Arrow, 1, None);
+
+    let inventory = app.world.get::<Inventory>(menu_ent).unwrap();
+    assert_eq!(inventory.slot(0).map(|s| s.count()), Some(1));
+    assert_eq!(inventory.slot(1).map(|s| s.count()), Some(2));
+    assert_eq!(inventory.slot(7), None);
+    assert_eq!(inventory.slot(8), Some(&arrow));
+
+    click_slot(client_ent, &app, &mut client_helper, 8);
+    app.update();
+    click_slot(client_ent, &app, &mut client_helper, 8);
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(menu_ent).unwrap();
+    assert_eq!(inventory.slot(0).map(|s| s.count()), Some(5));
+    assert_eq!(inventory.slot(1), None);
+    assert_eq!(inventory.slot(7), Some(&arrow));
+    assert_eq!(inventory.slot(8), None);
+
+    let menu = app.world.get::<Menu>(menu_ent).unwrap();
+    assert_eq!(menu.pages.as_ref().unwrap().page(), 2);
+
+    // Page buttons don't send click events.
+    assert_eq!(
+        app.world
+            .resource::<Events<MenuClickEvent>>()
+            .iter_current_update_events()
+            .count(),
+        0
+    );
+}
@@ -0,0 +1,252 @@
+use bevy_app::App;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_inventory::packet::{
+    ClickMode, ClickSlotC2s, CloseHandledScreenC2s, SelectMerchantTradeC2s, SetTradeOffersS2c,
+};
+use valence_inventory::{
+    ClientInventoryState, CursorItem, Inventory, InventoryKind, MerchantMenu, OpenInventory, Trade,
+    TradeEvent,
+};
+
+use super::*;
+
+fn emeralds(count: u8) -> ItemStack {
+    ItemStack::new(ItemKind::Emerald, count, None)
+}
+
+fn bread_trade() -> Trade {
+    Trade::new(emeralds(3), None, ItemStack::new(ItemKind::Bread, 6, None))
+}
+
+/// Opens a merchant inventory with the given trades for the client.
+fn open_merchant(app: &mut App, client_ent: Entity, trades: Vec<Trade>) -> Entity {
+    let merchant_ent = app
+        .world
+        .spawn((
+            Inventory::new(InventoryKind::Merchant),
+            MerchantMenu::new(trades),
+        ))
+        .id();
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(OpenInventory::new(merchant_ent));
+
+    merchant_ent
+}
+
+fn click_result(
+    app: &mut App,
+    client_ent: Entity,
+    client_helper: &mut MockClientHelper,
+    mode: ClickMode,
+) {
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+
+    client_helper.send(&ClickSlotC2s {
+        window_id: inv_state.window_id(),
+        state_id: VarInt(inv_state.state_id().0),
+        slot_idx: 2,
+        button: 0,
+        mode,
+        slot_changes: vec![],
+        carried_item: None,
+    });
+}
+
+#[test]
+fn trade_price_follows_demand() {
+    let mut trade = Trade::new(emeralds(10), None, emeralds(1));
+    assert_eq!(trade.price().count(), 10);
+
+    trade.demand = 3;
+    trade.price_multiplier = 0.05;
+    assert_eq!(trade.price().count(), 11);
+
+    trade.special_price = -20;
+    assert_eq!(trade.price().count(), 1);
+
+    trade.demand = -5;
+    trade.special_price = 0;
+    assert_eq!(trade.price().count(), 10);
+}
+
+#[test]
+fn trades_are_sent_when_opened() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let merchant_ent = open_merchant(&mut app, client_ent, vec![bread_trade()]);
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<SetTradeOffersS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == SetTradeOffersS2c::ID {
+            let pkt = pkt.decode::<SetTradeOffersS2c>().unwrap();
+            assert_eq!(pkt.trades.len(), 1);
+            assert_eq!(pkt.trades[0].input_one, Some(emeralds(3)));
+        }
+    }
+
+    // Changing the trades sends them again.
+    app.world
+        .get_mut::<MerchantMenu>(merchant_ent)
+        .unwrap()
+        .trades[0]
+        .max_uses = 1;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<SetTradeOffersS2c>(1);
+}
+
+#[test]
+fn trade_is_made() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, emeralds(10));
+
+    let merchant_ent = open_merchant(&mut app, client_ent, vec![bread_trade()]);
+    app.update();
+
+    // Selecting the trade moves the emeralds to the input slot.
+    client_helper.send(&SelectMerchantTradeC2s {
+        selected_slot: VarInt(0),
+    });
+    app.update();
+
+    let player_inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(player_inventory.slot(36), None);
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(0), Some(&emeralds(10)));
+    assert_eq!(
+        merchant.slot(2),
+        Some(&ItemStack::new(ItemKind::Bread, 6, None))
+    );
+
+    click_result(&mut app, client_ent, &mut client_helper, ClickMode::Click);
+    app.update();
+
+    let cursor_item = app.world.get::<CursorItem>(client_ent).unwrap();
+    assert_eq!(
+        cursor_item.0,
+        Some(ItemStack::new(ItemKind::Bread, 6, None))
+    );
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(0), Some(&emeralds(7)));
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<TradeEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        [TradeEvent {
+            client: client_ent,
+            inventory: merchant_ent,
+            trade: 0,
+        }]
+    );
+
+    // Shift clicking trades as many times as possible.
+    click_result(
+        &mut app,
+        client_ent,
+        &mut client_helper,
+        ClickMode::ShiftClick,
+    );
+    app.update();
+
+    let player_inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(
+        player_inventory.slot(44),
+        Some(&ItemStack::new(ItemKind::Bread, 12, None))
+    );
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(0), Some(&emeralds(1)));
+    assert_eq!(merchant.slot(2), None);
+
+    let menu = app.world.get::<MerchantMenu>(merchant_ent).unwrap();
+    assert_eq!(menu.trades[0].uses, 3);
+    assert_eq!(menu.selected_trade(), Some(0));
+}
+
+#[test]
+fn trade_out_of_stock() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let mut trade = bread_trade();
+    trade.max_uses = 0;
+
+    let merchant_ent = open_merchant(&mut app, client_ent, vec![trade]);
+    app.world
+        .get_mut::<Inventory>(merchant_ent)
+        .unwrap()
+        .set_slot(0, emeralds(3));
+    app.update();
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(2), None);
+
+    click_result(&mut app, client_ent, &mut client_helper, ClickMode::Click);
+    app.update();
+
+    let cursor_item = app.world.get::<CursorItem>(client_ent).unwrap();
+    assert_eq!(cursor_item.0, None);
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(0), Some(&emeralds(3)));
+}
+
+#[test]
+fn inputs_are_returned_on_close() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let merchant_ent = open_merchant(&mut app, client_ent, vec![bread_trade()]);
+    app.world
+        .get_mut::<Inventory>(merchant_ent)
+        .unwrap()
+        .set_slot(1, emeralds(5));
+    app.update();
+
+    let window_id = app
+        .world
+        .get::<ClientInventoryState>(client_ent)
+        .unwrap()
+        .window_id();
+
+    client_helper.send(&CloseHandledScreenC2s {
+        window_id: window_id as i8,
+    });
+    app.update();
+
+    let merchant = app.world.get::<Inventory>(merchant_ent).unwrap();
+    assert_eq!(merchant.slot(1), None);
+
+    let player_inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(player_inventory.slot(44), Some(&emeralds(5)));
+    assert!(app.world.get::<OpenInventory>(client_ent).is_none());
+}
@@ -0,0 +1,98 @@
+use bevy_app::App;
+use valence_client::interact_entity::{EntityInteraction, PlayerInteractEntityC2s};
+use valence_client::packet::PlayerSpawnS2c;
+use valence_client::{Properties, Username};
+use valence_core::hand::Hand;
+use valence_entity::packet::EntitiesDestroyS2c;
+use valence_entity::{EntityId, Location, Position};
+use valence_instance::Chunk;
+use valence_player_list::npc::{NpcBundle, NpcInteractEvent};
+use valence_player_list::packet::PlayerListS2c;
+
+use super::*;
+
+#[test]
+fn npc_spawn_and_interact() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let mut npc = NpcBundle::default();
+    npc.player.location = Location(instance_ent);
+    npc.player.position = Position::new([2.0, 0.0, 2.0]);
+    npc.username = Username("npc".into());
+
+    let npc_ent = app.world.spawn(npc).id();
+
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<PlayerListS2c>(1);
+    sent_packets.assert_count::<PlayerSpawnS2c>(1);
+    // The player list entry must be received before the player can be spawned.
+    sent_packets.assert_order::<(PlayerListS2c, PlayerSpawnS2c)>();
+
+    let entity_id = app.world.get::<EntityId>(npc_ent).unwrap().get();
+
+    client_helper.send(&PlayerInteractEntityC2s {
+        entity_id: VarInt(entity_id),
+        interact: EntityInteraction::Interact(Hand::Main),
+        sneaking: false,
+    });
+
+    app.update();
+
+    let events = app.world.resource::<Events<NpcInteractEvent>>();
+    let mut reader = events.get_reader();
+    let events = reader.iter(events).collect::<Vec<_>>();
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].npc, npc_ent);
+}
+
+#[test]
+fn npc_skin_change_respawns() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    let mut npc = NpcBundle::default();
+    npc.player.location = Location(instance_ent);
+    npc.player.position = Position::new([2.0, 0.0, 2.0]);
+    npc.username = Username("npc".into());
+
+    let npc_ent = app.world.spawn(npc).id();
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world
+        .get_mut::<Properties>(npc_ent)
+        .unwrap()
+        .set_textures("textures", Some("signature".into()));
+
+    app.update();
+
+    // The entry is re-added with the new skin before the player is respawned.
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<PlayerListS2c>(1);
+    sent_packets.assert_count::<EntitiesDestroyS2c>(1);
+    sent_packets.assert_count::<PlayerSpawnS2c>(1);
+    sent_packets.assert_order::<(PlayerListS2c, EntitiesDestroyS2c, PlayerSpawnS2c)>();
+}
@@ -0,0 +1,89 @@
+use bevy_app::App;
+use valence_permission::{PermissionManager, Permissions, PermissionsChangedEvent};
+
+use super::*;
+
+fn change_events(app: &App) -> Vec<PermissionsChangedEvent> {
+    app.world
+        .resource::<Events<PermissionsChangedEvent>>()
+        .iter_current_update_events()
+        .copied()
+        .collect()
+}
+
+#[test]
+fn wildcards_and_groups() {
+    let mut manager = PermissionManager::default();
+
+    manager
+        .group_mut("moderator")
+        .grant("valence.command.kick")
+        .deny("valence.command.stop");
+    manager
+        .group_mut("admin")
+        .inherit("moderator")
+        .grant("valence.command.*");
+
+    let mut permissions = Permissions::new();
+    assert!(!manager.has_permission(&permissions, "valence.command.kick"));
+
+    permissions.add_group("moderator");
+    assert!(manager.has_permission(&permissions, "valence.command.kick"));
+    assert!(!manager.has_permission(&permissions, "valence.command.give"));
+
+    // Nodes of a group take priority over the nodes it inherits, and more
+    // specific nodes take priority over wildcards.
+    permissions.remove_group("moderator");
+    permissions.add_group("admin");
+    assert!(manager.has_permission(&permissions, "valence.command.give"));
+    assert!(manager.has_permission(&permissions, "valence.command.stop"));
+    assert!(!manager.has_permission(&permissions, "valence.other"));
+
+    // Nodes of the holder take priority over groups.
+    permissions.deny("valence.command.give");
+    assert!(!manager.has_permission(&permissions, "valence.command.give"));
+
+    permissions.grant("*");
+    assert!(manager.has_permission(&permissions, "valence.other"));
+
+    // Cycles of inheritance don't loop forever.
+    manager.group_mut("moderator").inherit("admin");
+    let mut permissions = Permissions::new();
+    permissions.add_group("moderator");
+    assert!(!manager.has_permission(&permissions, "valence.other"));
+}
+
+#[test]
+fn permission_changes_send_events() {
+    let mut app = App::new();
+    let (client_ent, _) = scenario_single_client(&mut app);
+
+    app.update();
+    assert!(app.world.get::<Permissions>(client_ent).is_some());
+    assert!(change_events(&app).is_empty());
+
+    app.world
+        .get_mut::<Permissions>(client_ent)
+        .unwrap()
+        .grant("valence.fly");
+    app.update();
+
+    assert_eq!(
+        change_events(&app),
+        [PermissionsChangedEvent { client: client_ent }]
+    );
+
+    app.update();
+    assert!(change_events(&app).is_empty());
+
+    app.world
+        .resource_mut::<PermissionManager>()
+        .group_mut("default")
+        .grant("valence.fly");
+    app.update();
+
+    assert_eq!(
+        change_events(&app),
+        [PermissionsChangedEvent { client: client_ent }]
+    );
+}
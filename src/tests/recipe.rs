@@ -0,0 +1,283 @@
+use bevy_app::App;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_inventory::packet::synchronize_recipes::SynchronizeRecipesS2c;
+use valence_inventory::packet::{
+    ClickMode, ClickSlotC2s, CraftRequestC2s, SlotChange, UnlockRecipesS2c, UpdateRecipeBookAction,
+};
+use valence_inventory::{ClientInventoryState, CursorItem, Inventory};
+use valence_recipe::{RecipeBook, RecipeUnlockedEvent, Recipes, ShapedRecipe, ShapelessRecipe};
+
+use super::*;
+
+fn stick_recipe() -> ShapedRecipe {
+    ShapedRecipe::new(
+        &["#", "#"],
+        [('#', ItemKind::OakPlanks.into())],
+        ItemStack::new(ItemKind::Stick, 4, None),
+    )
+}
+
+fn planks(count: u8) -> Option<ItemStack> {
+    Some(ItemStack::new(ItemKind::OakPlanks, count, None))
+}
+
+#[test]
+fn crafting_recipes_match_grid() {
+    let mut recipes = Recipes::default();
+    recipes.insert(ident!("stick"), stick_recipe());
+    recipes.insert(
+        ident!("stairs"),
+        ShapedRecipe::new(
+            &["#  ", "## ", "###"],
+            [('#', ItemKind::Cobblestone.into())],
+            ItemStack::new(ItemKind::CobblestoneStairs, 4, None),
+        ),
+    );
+    recipes.insert(
+        ident!("flint_and_steel"),
+        ShapelessRecipe::new(
+            [ItemKind::IronIngot.into(), ItemKind::Flint.into()],
+            ItemStack::new(ItemKind::FlintAndSteel, 1, None),
+        ),
+    );
+
+    let find = |grid: &[Option<ItemStack>], width| {
+        recipes
+            .find_crafting(grid, width)
+            .map(|(id, _)| id.as_str().to_owned())
+    };
+
+    // The pattern can be anywhere in the grid.
+    assert_eq!(find(&[planks(1), None, None, planks(5)], 2), None);
+    assert_eq!(
+        find(&[None, planks(1), None, planks(5)], 2).as_deref(),
+        Some("minecraft:stick")
+    );
+
+    let cobble = || Some(ItemStack::new(ItemKind::Cobblestone, 1, None));
+
+    // Mirrored patterns match too.
+    let mirrored = [
+        None,
+        None,
+        cobble(),
+        None,
+        cobble(),
+        cobble(),
+        cobble(),
+        cobble(),
+        cobble(),
+    ];
+    assert_eq!(find(&mirrored, 3).as_deref(), Some("minecraft:stairs"));
+
+    let mut missing = mirrored.clone();
+    missing[4] = None;
+    assert_eq!(find(&missing, 3), None);
+
+    let flint = Some(ItemStack::new(ItemKind::Flint, 1, None));
+    let iron = Some(ItemStack::new(ItemKind::IronIngot, 1, None));
+    assert_eq!(
+        find(&[flint.clone(), None, None, iron.clone()], 2).as_deref(),
+        Some("minecraft:flint_and_steel")
+    );
+    assert_eq!(find(&[flint.clone(), flint, None, iron], 2), None);
+}
+
+#[test]
+fn crafting_result_is_taken() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world
+        .resource_mut::<Recipes>()
+        .insert(ident!("stick"), stick_recipe());
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    let mut inventory = app.world.get_mut::<Inventory>(client_ent).unwrap();
+    inventory.set_slot(1, planks(2));
+    inventory.set_slot(3, planks(2));
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    let result = ItemStack::new(ItemKind::Stick, 4, None);
+    assert_eq!(inventory.slot(0), Some(&result));
+
+    let state_id = app
+        .world
+        .get::<ClientInventoryState>(client_ent)
+        .unwrap()
+        .state_id();
+
+    client_helper.send(&ClickSlotC2s {
+        window_id: 0,
+        state_id: VarInt(state_id.0),
+        slot_idx: 0,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![
+            SlotChange { idx: 0, item: None },
+            SlotChange {
+                idx: 1,
+                item: planks(1),
+            },
+            SlotChange {
+                idx: 3,
+                item: planks(1),
+            },
+        ],
+        carried_item: Some(result.clone()),
+    });
+    app.update();
+
+    let cursor_item = app.world.get::<CursorItem>(client_ent).unwrap();
+    assert_eq!(cursor_item.0, Some(result.clone()));
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(inventory.slot(1), planks(1).as_ref());
+    assert_eq!(inventory.slot(3), planks(1).as_ref());
+    assert_eq!(inventory.slot(0), Some(&result));
+
+    // Taking the result resyncs the inventory, which changes the state id.
+    let state_id = app
+        .world
+        .get::<ClientInventoryState>(client_ent)
+        .unwrap()
+        .state_id();
+
+    // Shift clicking crafts the rest into the inventory.
+    client_helper.send(&ClickSlotC2s {
+        window_id: 0,
+        state_id: VarInt(state_id.0),
+        slot_idx: 0,
+        button: 0,
+        mode: ClickMode::ShiftClick,
+        slot_changes: vec![],
+        carried_item: Some(result.clone()),
+    });
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(inventory.slot(1), None);
+    assert_eq!(inventory.slot(3), None);
+    assert_eq!(inventory.slot(0), None);
+    assert_eq!(inventory.slot(44), Some(&result));
+}
+
+#[test]
+fn recipe_book_is_sent() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let mut recipes = app.world.resource_mut::<Recipes>();
+    recipes.insert(ident!("stick"), stick_recipe());
+    recipes.insert(
+        ident!("flint_and_steel"),
+        ShapelessRecipe::new(
+            [ItemKind::IronIngot.into(), ItemKind::Flint.into()],
+            ItemStack::new(ItemKind::FlintAndSteel, 1, None),
+        ),
+    );
+
+    app.update();
+
+    app.world
+        .get_mut::<RecipeBook>(client_ent)
+        .unwrap()
+        .unlock(ident!("stick"));
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<SynchronizeRecipesS2c>(1);
+    sent_packets.assert_count::<UnlockRecipesS2c>(2);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == SynchronizeRecipesS2c::ID {
+            let pkt = pkt.decode::<SynchronizeRecipesS2c>().unwrap();
+            assert_eq!(pkt.recipes.len(), 2);
+        }
+    }
+
+    let events: Vec<_> = app
+        .world
+        .resource::<Events<RecipeUnlockedEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect();
+    assert_eq!(
+        events,
+        [RecipeUnlockedEvent {
+            client: client_ent,
+            recipe: ident!("stick").into(),
+        }]
+    );
+
+    app.world
+        .get_mut::<RecipeBook>(client_ent)
+        .unwrap()
+        .lock("minecraft:stick");
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<UnlockRecipesS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == UnlockRecipesS2c::ID {
+            let pkt = pkt.decode::<UnlockRecipesS2c>().unwrap();
+            assert_eq!(pkt.action, UpdateRecipeBookAction::Remove);
+            assert_eq!(pkt.recipe_ids.len(), 1);
+            assert_eq!(pkt.recipe_ids[0].as_str(), "minecraft:stick");
+        }
+    }
+}
+
+#[test]
+fn place_recipe_from_book() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world
+        .resource_mut::<Recipes>()
+        .insert(ident!("stick"), stick_recipe());
+
+    app.update();
+
+    app.world
+        .get_mut::<RecipeBook>(client_ent)
+        .unwrap()
+        .unlock(ident!("stick"));
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, planks(5));
+    app.update();
+
+    client_helper.send(&CraftRequestC2s {
+        window_id: 0,
+        recipe: ident!("stick").into(),
+        make_all: false,
+    });
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(inventory.slot(1), planks(1).as_ref());
+    assert_eq!(inventory.slot(3), planks(1).as_ref());
+    assert_eq!(inventory.slot(36), planks(3).as_ref());
+    assert_eq!(
+        inventory.slot(0),
+        Some(&ItemStack::new(ItemKind::Stick, 4, None))
+    );
+
+    client_helper.send(&CraftRequestC2s {
+        window_id: 0,
+        recipe: ident!("stick").into(),
+        make_all: true,
+    });
+    app.update();
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(inventory.slot(1), planks(2).as_ref());
+    assert_eq!(inventory.slot(3), planks(2).as_ref());
+    assert_eq!(inventory.slot(36), planks(1).as_ref());
+}
@@ -0,0 +1,390 @@
+use bevy_app::App;
+use glam::{DVec3, Vec3};
+use valence_block::{BlockKind, BlockState, PropName, PropValue};
+use valence_client::command::{ClientCommand, ClientCommandC2s};
+use valence_client::interact_block::PlayerInteractBlockC2s;
+use valence_client::packet::{
+    DeathMessageS2c, GameEventKind, GameStateChangeS2c, PlayerRespawnS2c, PlayerSpawnPositionS2c,
+};
+use valence_client::status::ClientStatusC2s;
+use valence_client::CompassPos;
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_core::hand::Hand;
+use valence_core::item::{ItemKind, ItemStack};
+use valence_entity::{Look, Position};
+use valence_experience::Experience;
+use valence_health::Health;
+use valence_instance::Chunk;
+use valence_inventory::Inventory;
+use valence_respawn::{
+    Dead, PlayerDeathEvent, PlayerRespawnEvent, RespawnBlockExplosionEvent, RespawnPoint,
+    RespawnSettings, SleepAttemptEvent, SpawnPoint,
+};
+
+use super::*;
+
+fn setup(app: &mut App) -> (Entity, MockClientHelper) {
+    let (client_ent, mut client_helper) = scenario_single_client(app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    app.world.get_mut::<CompassPos>(client_ent).unwrap().0 = BlockPos::new(3, 65, 3);
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Diamond, 1, None));
+    *app.world.get_mut::<Experience>(client_ent).unwrap() = Experience::from_level(10);
+
+    app.update();
+    client_helper.clear_sent();
+
+    (client_ent, client_helper)
+}
+
+fn kill(app: &mut App, client_ent: Entity) {
+    app.world.get_mut::<Health>(client_ent).unwrap().0 = 0.0;
+    app.update();
+}
+
+#[test]
+fn death_and_respawn() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    kill(&mut app, client_ent);
+
+    client_helper
+        .collect_sent()
+        .assert_count::<DeathMessageS2c>(1);
+
+    let deaths = app.world.resource::<Events<PlayerDeathEvent>>();
+    let deaths: Vec<_> = deaths.iter_current_update_events().collect();
+    assert_eq!(deaths.len(), 1);
+    assert_eq!(deaths[0].client, client_ent);
+    assert_eq!(
+        deaths[0].drops,
+        vec![ItemStack::new(ItemKind::Diamond, 1, None)]
+    );
+    assert_eq!(deaths[0].experience, 70);
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(inventory.slot(36), None);
+    assert!(app.world.get::<Dead>(client_ent).is_some());
+
+    client_helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerRespawnS2c>(1);
+
+    assert_eq!(app.world.get::<Health>(client_ent).unwrap().0, 20.0);
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(3.5, 65.0, 3.5)
+    );
+    assert!(app.world.get::<Dead>(client_ent).is_none());
+
+    let respawns = app.world.resource::<Events<PlayerRespawnEvent>>();
+    let respawns: Vec<_> = respawns.iter_current_update_events().collect();
+    assert_eq!(respawns.len(), 1);
+    assert_eq!(respawns[0].point, RespawnPoint::WorldSpawn);
+
+    // Clients that aren't dead can't respawn.
+    client_helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerRespawnS2c>(0);
+}
+
+#[test]
+fn keep_inventory() {
+    let mut app = App::new();
+    let (client_ent, _client_helper) = setup(&mut app);
+
+    app.world.resource_mut::<RespawnSettings>().keep_inventory = true;
+
+    kill(&mut app, client_ent);
+
+    let deaths = app.world.resource::<Events<PlayerDeathEvent>>();
+    let deaths: Vec<_> = deaths.iter_current_update_events().collect();
+    assert_eq!(deaths.len(), 1);
+    assert!(deaths[0].drops.is_empty());
+    assert_eq!(deaths[0].experience, 0);
+
+    let inventory = app.world.get::<Inventory>(client_ent).unwrap();
+    assert_eq!(
+        inventory.slot(36),
+        Some(&ItemStack::new(ItemKind::Diamond, 1, None))
+    );
+    assert_eq!(app.world.get::<Experience>(client_ent).unwrap().level, 10);
+}
+
+#[test]
+fn missing_bed_respawns_at_world_spawn() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    *app.world.get_mut::<RespawnPoint>(client_ent).unwrap() = RespawnPoint::Bed {
+        instance: instance_ent,
+        pos: BlockPos::new(10, 64, 10),
+    };
+
+    kill(&mut app, client_ent);
+    client_helper.clear_sent();
+
+    client_helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<PlayerRespawnS2c>(1);
+
+    let no_respawn_block = frames.0.iter().any(|frame| {
+        frame.id == GameStateChangeS2c::ID
+            && frame.decode::<GameStateChangeS2c>().unwrap().kind
+                == GameEventKind::NoRespawnBlockAvailable
+    });
+    assert!(no_respawn_block);
+
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(3.5, 65.0, 3.5)
+    );
+    assert_eq!(
+        *app.world.get::<RespawnPoint>(client_ent).unwrap(),
+        RespawnPoint::WorldSpawn
+    );
+}
+
+#[test]
+fn set_spawn_point() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+
+    app.world
+        .query::<SpawnPoint>()
+        .get_mut(&mut app.world, client_ent)
+        .unwrap()
+        .set_spawn_point([5, 70, 5], 90.0);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<PlayerSpawnPositionS2c>(1);
+
+    for frame in &frames.0 {
+        if frame.id == PlayerSpawnPositionS2c::ID {
+            let pkt = frame.decode::<PlayerSpawnPositionS2c>().unwrap();
+            assert_eq!(pkt.position, BlockPos::new(5, 70, 5));
+            assert_eq!(pkt.angle, 90.0);
+        }
+    }
+
+    kill(&mut app, client_ent);
+
+    client_helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(5.5, 70.0, 5.5)
+    );
+    assert_eq!(app.world.get::<Look>(client_ent).unwrap().yaw, 90.0);
+}
+
+fn use_block(client_helper: &mut MockClientHelper, position: BlockPos) {
+    client_helper.send(&PlayerInteractBlockC2s {
+        hand: Hand::Main,
+        position,
+        face: Direction::Up,
+        cursor_pos: Vec3::new(0.5, 0.5, 0.5),
+        head_inside_block: false,
+        sequence: VarInt(1),
+    });
+}
+
+fn setup_blocks(app: &mut App, client_ent: Entity) -> Entity {
+    let instance_ent = find_instance(app);
+
+    // Within reach of the blocks used at [8, 1, 8].
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 10.5);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    for x in 0..16 {
+        for z in 0..16 {
+            instance.set_block([x, 0, z], BlockState::STONE);
+        }
+    }
+
+    instance_ent
+}
+
+#[test]
+fn bed_sets_respawn_point() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+    let instance_ent = setup_blocks(&mut app, client_ent);
+
+    let bed = BlockState::RED_BED.set(PropName::Facing, PropValue::North);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.set_block([8, 1, 8], bed.set(PropName::Part, PropValue::Foot));
+    instance.set_block([8, 1, 7], bed.set(PropName::Part, PropValue::Head));
+
+    // Clicking the foot of the bed uses the head.
+    use_block(&mut client_helper, BlockPos::new(8, 1, 8));
+    app.update();
+
+    assert_eq!(
+        *app.world.get::<RespawnPoint>(client_ent).unwrap(),
+        RespawnPoint::Bed {
+            instance: instance_ent,
+            pos: BlockPos::new(8, 1, 7),
+        }
+    );
+
+    let sleeps = app.world.resource::<Events<SleepAttemptEvent>>();
+    let sleeps: Vec<_> = sleeps.iter_current_update_events().collect();
+    assert_eq!(sleeps.len(), 1);
+    assert_eq!(sleeps[0].position, BlockPos::new(8, 1, 7));
+
+    kill(&mut app, client_ent);
+
+    client_helper.send(&ClientStatusC2s::PerformRespawn);
+    app.update();
+
+    let respawns = app.world.resource::<Events<PlayerRespawnEvent>>();
+    let respawns: Vec<_> = respawns.iter_current_update_events().collect();
+    assert_eq!(respawns.len(), 1);
+    assert_eq!(
+        respawns[0].point,
+        RespawnPoint::Bed {
+            instance: instance_ent,
+            pos: BlockPos::new(8, 1, 7),
+        }
+    );
+}
+
+#[test]
+fn respawn_anchor_in_overworld() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+    let instance_ent = setup_blocks(&mut app, client_ent);
+
+    let anchor_pos = BlockPos::new(8, 1, 8);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .set_block(anchor_pos, BlockState::RESPAWN_ANCHOR);
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, ItemStack::new(ItemKind::Glowstone, 2, None));
+
+    // Glowstone charges the anchor.
+    use_block(&mut client_helper, anchor_pos);
+    app.update();
+
+    let charges = |app: &App| {
+        app.world
+            .get::<Instance>(instance_ent)
+            .unwrap()
+            .block(anchor_pos)
+            .unwrap()
+            .state()
+            .get(PropName::Charges)
+    };
+
+    assert_eq!(charges(&app), PropValue::from_u16(1));
+    assert_eq!(
+        app.world.get::<Inventory>(client_ent).unwrap().slot(36),
+        Some(&ItemStack::new(ItemKind::Glowstone, 1, None))
+    );
+
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, None);
+
+    // Using a charged anchor outside of the nether is an explosion, which is
+    // left to the server.
+    use_block(&mut client_helper, anchor_pos);
+    app.update();
+
+    let explosions = app.world.resource::<Events<RespawnBlockExplosionEvent>>();
+    let explosions: Vec<_> = explosions.iter_current_update_events().collect();
+    assert_eq!(explosions.len(), 1);
+    assert_eq!(explosions[0].position, anchor_pos);
+    assert_eq!(explosions[0].block, BlockKind::RespawnAnchor);
+
+    assert_eq!(charges(&app), PropValue::from_u16(1));
+    assert_eq!(
+        *app.world.get::<RespawnPoint>(client_ent).unwrap(),
+        RespawnPoint::WorldSpawn
+    );
+}
+
+#[test]
+fn respawn_blocks_out_of_reach_or_sneaking_are_ignored() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = setup(&mut app);
+    let instance_ent = setup_blocks(&mut app, client_ent);
+
+    let bed = BlockState::RED_BED.set(PropName::Facing, PropValue::North);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.set_block([8, 1, 8], bed.set(PropName::Part, PropValue::Foot));
+    instance.set_block([8, 1, 7], bed.set(PropName::Part, PropValue::Head));
+
+    let point = |app: &App| *app.world.get::<RespawnPoint>(client_ent).unwrap();
+
+    // Too far away.
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 15.5);
+    app.update();
+
+    use_block(&mut client_helper, BlockPos::new(8, 1, 8));
+    app.update();
+
+    assert_eq!(point(&app), RespawnPoint::WorldSpawn);
+
+    // Sneaking while holding the diamond from `setup`.
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.5, 1.0, 10.5);
+    client_helper.send(&ClientCommandC2s {
+        entity_id: VarInt(0),
+        action: ClientCommand::StartSneaking,
+        jump_boost: VarInt(0),
+    });
+    app.update();
+
+    use_block(&mut client_helper, BlockPos::new(8, 1, 8));
+    app.update();
+
+    assert_eq!(point(&app), RespawnPoint::WorldSpawn);
+
+    // Sneaking with empty hands uses the bed.
+    app.world
+        .get_mut::<Inventory>(client_ent)
+        .unwrap()
+        .set_slot(36, None);
+
+    use_block(&mut client_helper, BlockPos::new(8, 1, 8));
+    app.update();
+
+    assert_eq!(
+        point(&app),
+        RespawnPoint::Bed {
+            instance: instance_ent,
+            pos: BlockPos::new(8, 1, 7),
+        }
+    );
+}
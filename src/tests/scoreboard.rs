@@ -0,0 +1,204 @@
+use bevy_app::App;
+use valence_core::despawn::Despawned;
+use valence_core::protocol::Decode;
+use valence_entity::Location;
+use valence_scoreboard::packet::*;
+use valence_scoreboard::*;
+
+use super::*;
+
+fn decode_all<'a, P: Packet + Decode<'a>>(frames: &'a PacketFrames) -> Vec<P> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == P::ID)
+        .map(|f| f.decode::<P>().unwrap())
+        .collect()
+}
+
+#[test]
+fn objective_scores_are_diffed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let instance_ent = app.world.get::<Location>(client_ent).unwrap().0;
+    let objective_ent = app
+        .world
+        .spawn(ObjectiveBundle {
+            objective: Objective::new("kills").with_display_slot(ScoreboardPosition::Sidebar),
+            scores: ObjectiveScores::new()
+                .with_score("Alice", 1)
+                .with_score("Bob", 2),
+            ..ObjectiveBundle::new("kills", instance_ent)
+        })
+        .id();
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(1);
+    frames.assert_count::<ScoreboardPlayerUpdateS2c>(2);
+    frames.assert_count::<ScoreboardDisplayS2c>(1);
+
+    let mut scores = app.world.get_mut::<ObjectiveScores>(objective_ent).unwrap();
+    scores.set("Alice", 1);
+    scores.add("Bob", 5);
+    scores.remove("Alice");
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<ScoreboardObjectiveUpdateS2c>(0);
+
+    let updates = decode_all::<ScoreboardPlayerUpdateS2c>(&frames);
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0].entity_name, "Alice");
+    assert_eq!(
+        updates[0].action,
+        ScoreboardPlayerUpdateAction::Remove {
+            objective_name: "kills"
+        }
+    );
+    assert_eq!(updates[1].entity_name, "Bob");
+    assert_eq!(
+        updates[1].action,
+        ScoreboardPlayerUpdateAction::Update {
+            objective_name: "kills",
+            objective_score: VarInt(7)
+        }
+    );
+
+    // Moving the objective out of the sidebar clears the sidebar.
+    app.world
+        .get_mut::<Objective>(objective_ent)
+        .unwrap()
+        .display_slot = Some(ScoreboardPosition::List);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let displays = decode_all::<ScoreboardDisplayS2c>(&frames);
+    assert_eq!(displays.len(), 2);
+    assert_eq!(displays[0].position, ScoreboardPosition::Sidebar);
+    assert_eq!(displays[0].score_name, "");
+    assert_eq!(displays[1].position, ScoreboardPosition::List);
+    assert_eq!(displays[1].score_name, "kills");
+
+    app.world.entity_mut(objective_ent).insert(Despawned);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let objectives = decode_all::<ScoreboardObjectiveUpdateS2c>(&frames);
+    assert_eq!(objectives.len(), 1);
+    assert_eq!(objectives[0].mode, ObjectiveMode::Remove);
+}
+
+#[test]
+fn team_members_are_diffed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let instance_ent = app.world.get::<Location>(client_ent).unwrap().0;
+
+    let mut bundle = TeamBundle::new("red", instance_ent);
+    bundle.team = bundle.team.with_color(TeamColor::Red);
+    bundle.members.insert("Alice");
+    let team_ent = app.world.spawn(bundle).id();
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 1);
+    assert!(matches!(
+        &teams[0].mode,
+        Mode::CreateTeam { team_color: TeamColor::Red, entities, .. } if entities == &["Alice"]
+    ));
+
+    let mut members = app.world.get_mut::<TeamMembers>(team_ent).unwrap();
+    members.insert("Bob");
+    members.remove("Alice");
+    // Adding and removing an entry in the same tick sends nothing.
+    members.insert("Carol");
+    members.remove("Carol");
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 2);
+    assert_eq!(
+        teams[0].mode,
+        Mode::RemoveEntities {
+            entities: vec!["Alice"]
+        }
+    );
+    assert_eq!(
+        teams[1].mode,
+        Mode::AddEntities {
+            entities: vec!["Bob"]
+        }
+    );
+
+    app.world.get_mut::<Team>(team_ent).unwrap().friendly_fire = false;
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 1);
+    assert!(matches!(
+        teams[0].mode,
+        Mode::UpdateTeamInfo { friendly_flags, .. } if !friendly_flags.friendly_fire()
+    ));
+}
+
+#[test]
+fn scoreboard_follows_client_across_instances() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let first_instance = app.world.get::<Location>(client_ent).unwrap().0;
+    let second_instance = app
+        .world
+        .spawn(Instance::new(
+            ident!("overworld"),
+            app.world.resource::<DimensionTypeRegistry>(),
+            app.world.resource::<BiomeRegistry>(),
+            app.world.resource::<Server>(),
+        ))
+        .id();
+
+    app.world
+        .spawn(ObjectiveBundle::new("first", first_instance));
+    app.world.spawn(TeamBundle::new("first", first_instance));
+    app.world
+        .spawn(ObjectiveBundle::new("second", second_instance));
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world.get_mut::<Location>(client_ent).unwrap().0 = second_instance;
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+
+    let objectives = decode_all::<ScoreboardObjectiveUpdateS2c>(&frames);
+    assert_eq!(objectives.len(), 2);
+    assert_eq!(objectives[0].objective_name, "first");
+    assert_eq!(objectives[0].mode, ObjectiveMode::Remove);
+    assert_eq!(objectives[1].objective_name, "second");
+    assert!(matches!(objectives[1].mode, ObjectiveMode::Create { .. }));
+
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 1);
+    assert_eq!(teams[0].mode, Mode::RemoveTeam);
+}
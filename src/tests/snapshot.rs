@@ -0,0 +1,83 @@
+use bevy_app::App;
+use valence_block::BlockState;
+use valence_instance::packet::{BlockUpdateS2c, ChunkDataS2c, ChunkDeltaUpdateS2c};
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn restore_snapshot_sends_changed_blocks() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.insert_chunk([0, 0], Chunk::default());
+    instance.set_block([0, 64, 0], BlockState::STONE);
+    instance.set_block([1, 64, 0], BlockState::STONE);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    let snapshot = instance.snapshot();
+    assert_eq!(snapshot.chunks().len(), 1);
+
+    instance.set_block([0, 64, 0], BlockState::AIR);
+    instance.set_block([5, 70, 5], BlockState::GOLD_BLOCK);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.restore(&snapshot);
+
+    assert_eq!(
+        instance.block([0, 64, 0]).unwrap().state(),
+        BlockState::STONE
+    );
+    assert_eq!(
+        instance.block([1, 64, 0]).unwrap().state(),
+        BlockState::STONE
+    );
+    assert_eq!(instance.block([5, 70, 5]).unwrap().state(), BlockState::AIR);
+
+    app.update();
+
+    {
+        // Both blocks are in the same section.
+        let sent_packets = client_helper.collect_sent();
+        sent_packets.assert_count::<ChunkDeltaUpdateS2c>(1);
+        sent_packets.assert_count::<BlockUpdateS2c>(0);
+        sent_packets.assert_count::<ChunkDataS2c>(0);
+    }
+
+    // Restoring an unchanged instance sends nothing.
+    let mut instance = app
+        .world
+        .query::<&mut Instance>()
+        .single_mut(&mut app.world);
+
+    instance.restore(&snapshot);
+
+    app.update();
+
+    {
+        let sent_packets = client_helper.collect_sent();
+        sent_packets.assert_count::<ChunkDeltaUpdateS2c>(0);
+        sent_packets.assert_count::<BlockUpdateS2c>(0);
+        sent_packets.assert_count::<ChunkDataS2c>(0);
+    }
+}
@@ -0,0 +1,77 @@
+use bevy_app::App;
+use valence_client::Client;
+use valence_core::ident;
+use valence_core::protocol::packet::sound::*;
+use valence_entity::Location;
+
+use super::*;
+
+#[test]
+fn play_and_stop_sounds() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut client = app.world.get_mut::<Client>(client_ent).unwrap();
+    client.play_sound(
+        [1.0, 2.0, 3.0],
+        ident!("my_pack:jingle"),
+        SoundCategory::Music,
+        1.0,
+        1.0,
+        42,
+    );
+    client.play_sound_from_entity(
+        0,
+        Sound::EntityPlayerLevelup,
+        SoundCategory::Player,
+        1.0,
+        1.0,
+        0,
+    );
+    client.stop_sound(Some(SoundCategory::Music), Some(ident!("my_pack:jingle")));
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_order::<(PlaySoundS2c, PlaySoundFromEntityS2c, StopSoundS2c)>();
+
+    for frame in &frames.0 {
+        if frame.id == PlaySoundS2c::ID {
+            let pkt = frame.decode::<PlaySoundS2c>().unwrap();
+            assert_eq!(pkt.id, SoundId::from(ident!("my_pack:jingle")));
+            assert_eq!(pkt.position, [8, 16, 24].into());
+            assert_eq!(pkt.seed, 42);
+        } else if frame.id == PlaySoundFromEntityS2c::ID {
+            let pkt = frame.decode::<PlaySoundFromEntityS2c>().unwrap();
+            assert_eq!(pkt.id, Sound::EntityPlayerLevelup.to_id());
+            assert_eq!(pkt.entity_id.0, 0);
+        } else if frame.id == StopSoundS2c::ID {
+            let pkt = frame.decode::<StopSoundS2c>().unwrap();
+            assert_eq!(pkt.source, Some(SoundCategory::Music));
+            assert_eq!(pkt.sound, Some(ident!("my_pack:jingle").into()));
+        }
+    }
+}
+
+#[test]
+fn instance_stop_sound() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let instance_ent = app.world.get::<Location>(client_ent).unwrap().0;
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .stop_sound(None, None);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<StopSoundS2c>(1);
+}
@@ -0,0 +1,146 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_block::BlockState;
+use valence_core::aabb::Aabb;
+use valence_core::block_pos::BlockPos;
+use valence_core::direction::Direction;
+use valence_entity::pig::PigEntityBundle;
+use valence_entity::zombie::ZombieEntityBundle;
+use valence_entity::{Location, Position};
+use valence_instance::Chunk;
+use valence_spatial_index::{EntitySpatialIndex, InstanceRaycastHit};
+
+use super::*;
+
+#[test]
+fn spatial_index_queries() {
+    let mut app = App::new();
+    let (client_ent, _client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(0.0, 0.0, 0.0);
+
+    let zombie_ent = app
+        .world
+        .spawn(ZombieEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([10.0, 0.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    let pig_ent = app
+        .world
+        .spawn(PigEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([20.0, 0.0, 0.0]),
+            ..Default::default()
+        })
+        .id();
+
+    // Process some ticks so the entities get their hitboxes and are indexed.
+    app.update();
+    app.update();
+
+    let index = app.world.get::<EntitySpatialIndex>(instance_ent).unwrap();
+    assert_eq!(index.len(), 3);
+
+    let found = index.entities_in_aabb(Aabb::new([8.0, 0.0, -2.0], [22.0, 2.0, 2.0]));
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&zombie_ent) && found.contains(&pig_ent));
+
+    assert_eq!(
+        index.nearest_client(DVec3::new(5.0, 0.0, 0.0), 10.0),
+        Some(client_ent)
+    );
+    assert_eq!(index.nearest_client(DVec3::new(5.0, 0.0, 0.0), 2.0), None);
+    assert_eq!(
+        index.nearest(DVec3::new(18.0, 0.0, 0.0), 10.0, |_| true),
+        Some(pig_ent)
+    );
+
+    let hit = index
+        .raycast_entities(DVec3::new(0.0, 1.0, 0.0), DVec3::X, 50.0, |e| {
+            e != client_ent
+        })
+        .expect("ray should hit the zombie");
+
+    assert_eq!(hit.entity, zombie_ent);
+    assert!((hit.near - 9.7).abs() < 1e-6);
+
+    assert!(index
+        .raycast_entities(DVec3::new(0.0, 1.0, 0.0), DVec3::X, 5.0, |e| {
+            e != client_ent
+        })
+        .is_none());
+}
+
+#[test]
+fn raycast_blocks_and_entities() {
+    let mut app = App::new();
+    let (client_ent, _client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    let mut instance = app.world.get_mut::<Instance>(instance_ent).unwrap();
+    instance.insert_chunk([0, 0], Chunk::default());
+    instance.set_block([5, 0, 0], BlockState::STONE);
+
+    let zombie_ent = app
+        .world
+        .spawn(ZombieEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([8.0, 0.0, 0.5]),
+            ..Default::default()
+        })
+        .id();
+
+    app.update();
+    app.update();
+
+    let instance = app.world.get::<Instance>(instance_ent).unwrap();
+
+    // Air is passed through until the stone block is hit.
+    let hit = instance
+        .raycast_blocks(DVec3::new(0.5, 0.5, 0.5), DVec3::X, 10.0)
+        .expect("ray should hit the stone block");
+
+    assert_eq!(hit.block_pos, BlockPos::new(5, 0, 0));
+    assert_eq!(hit.face, Direction::West);
+    assert_eq!(hit.distance, 4.5);
+    assert_eq!(hit.position, DVec3::new(5.0, 0.5, 0.5));
+
+    assert!(instance
+        .raycast_blocks(DVec3::new(0.5, 0.5, 0.5), DVec3::X, 4.0)
+        .is_none());
+
+    let hit = instance
+        .raycast_blocks(DVec3::new(5.5, 3.0, 0.5), DVec3::NEG_Y, 10.0)
+        .unwrap();
+
+    assert_eq!(hit.face, Direction::Up);
+    assert_eq!(hit.distance, 2.0);
+
+    let index = app.world.get::<EntitySpatialIndex>(instance_ent).unwrap();
+
+    // The zombie is behind the stone block.
+    let hit = index
+        .raycast(instance, DVec3::new(0.5, 0.5, 0.5), DVec3::X, 20.0, |e| {
+            e != client_ent
+        })
+        .unwrap();
+
+    assert!(matches!(hit, InstanceRaycastHit::Block(_)));
+
+    let hit = index
+        .raycast(instance, DVec3::new(0.5, 1.5, 0.5), DVec3::X, 20.0, |e| {
+            e != client_ent
+        })
+        .unwrap();
+
+    match hit {
+        InstanceRaycastHit::Entity(hit) => assert_eq!(hit.entity, zombie_ent),
+        InstanceRaycastHit::Block(_) => panic!("ray should hit the zombie"),
+    }
+}
@@ -0,0 +1,107 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_client::command::{ClientCommand, ClientCommandC2s};
+use valence_client::spectator::{Camera, HiddenEntities};
+use valence_client::teleport::PlayerPositionLookS2c;
+use valence_core::protocol::var_int::VarInt;
+use valence_entity::packet::{
+    EntitiesDestroyS2c, EntitySpawnS2c, MoveRelativeS2c, SetCameraEntityS2c,
+};
+use valence_entity::{EntityId, Position};
+
+use super::*;
+
+#[test]
+fn camera_follows_entity_until_sneaking() {
+    let mut app = App::new();
+    let (client_ent, zombie_ent, mut client_helper) = scenario_client_and_zombie(&mut app);
+
+    let zombie_id = app.world.get::<EntityId>(zombie_ent).unwrap().get();
+    let client_id = app.world.get::<EntityId>(client_ent).unwrap().get();
+
+    app.world
+        .get_mut::<Camera>(client_ent)
+        .unwrap()
+        .set(zombie_ent);
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<SetCameraEntityS2c>(1);
+
+    for frame in &frames.0 {
+        if frame.id == SetCameraEntityS2c::ID {
+            let pkt = frame.decode::<SetCameraEntityS2c>().unwrap();
+            assert_eq!(pkt.entity_id, VarInt(zombie_id));
+        }
+    }
+
+    // The client is moved along with its camera.
+    app.world.get_mut::<Position>(zombie_ent).unwrap().0.x += 1.0;
+    app.update();
+
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(3.0, 0.0, 0.0)
+    );
+
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerPositionLookS2c>(0);
+
+    client_helper.send(&ClientCommandC2s {
+        entity_id: VarInt(client_id),
+        action: ClientCommand::StartSneaking,
+        jump_boost: VarInt(0),
+    });
+
+    app.update();
+
+    assert_eq!(app.world.get::<Camera>(client_ent).unwrap().get(), None);
+
+    let frames = client_helper.collect_sent();
+    frames.assert_count::<SetCameraEntityS2c>(1);
+
+    for frame in &frames.0 {
+        if frame.id == SetCameraEntityS2c::ID {
+            let pkt = frame.decode::<SetCameraEntityS2c>().unwrap();
+            // Clients know themselves as entity 0.
+            assert_eq!(pkt.entity_id, VarInt(0));
+        }
+    }
+}
+
+#[test]
+fn hidden_entities_are_despawned_for_client() {
+    let mut app = App::new();
+    let (client_ent, zombie_ent, mut client_helper) = scenario_client_and_zombie(&mut app);
+
+    app.world
+        .get_mut::<HiddenEntities>(client_ent)
+        .unwrap()
+        .hide(zombie_ent);
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntitiesDestroyS2c>(1);
+
+    // Updates about the hidden entity aren't sent to the client.
+    app.world.get_mut::<Position>(zombie_ent).unwrap().0.x += 1.0;
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<MoveRelativeS2c>(0);
+
+    app.world
+        .get_mut::<HiddenEntities>(client_ent)
+        .unwrap()
+        .show(zombie_ent);
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntitySpawnS2c>(1);
+}
@@ -0,0 +1,77 @@
+use bevy_app::App;
+use valence_block::BlockKind;
+use valence_client::packet::StatisticsS2c;
+use valence_client::status::ClientStatusC2s;
+use valence_core::item::ItemKind;
+use valence_entity::EntityKind;
+use valence_statistics::{CustomStat, Stat, Statistics};
+
+use super::*;
+
+#[test]
+fn statistics_sent_on_request() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+
+    let play_time = app
+        .world
+        .get::<Statistics>(client_ent)
+        .unwrap()
+        .get(Stat::Custom(CustomStat::PlayTime));
+    assert!(play_time > 0);
+
+    app.world
+        .get_mut::<Statistics>(client_ent)
+        .unwrap()
+        .increment(Stat::Mined(BlockKind::Stone), 3);
+
+    client_helper.clear_sent();
+    client_helper.send(&ClientStatusC2s::RequestStats);
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<StatisticsS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == StatisticsS2c::ID {
+            let pkt = pkt.decode::<StatisticsS2c>().unwrap();
+
+            let mined = pkt
+                .statistics
+                .iter()
+                .find(|s| s.category_id.0 == 0)
+                .unwrap();
+            assert_eq!(mined.statistic_id.0, BlockKind::Stone.to_raw() as i32);
+            assert_eq!(mined.value.0, 3);
+
+            let play_time = pkt
+                .statistics
+                .iter()
+                .find(|s| s.category_id.0 == 8 && s.statistic_id.0 == 1)
+                .unwrap();
+            assert_eq!(play_time.value.0, 2);
+        }
+    }
+}
+
+#[test]
+fn statistics_save_and_load() {
+    let mut stats = Statistics::default();
+    stats.increment(Stat::Mined(BlockKind::Stone), 5);
+    stats.increment(Stat::Used(ItemKind::DiamondSword), 2);
+    stats.increment(Stat::Killed(EntityKind::ZOMBIE), 1);
+    stats.increment(Stat::Custom(CustomStat::Jump), 10);
+
+    let saved = stats.save();
+
+    let mined = &saved.0[&ident!("mined").to_string_ident()];
+    assert_eq!(mined[&ident!("stone").to_string_ident()], 5);
+
+    let killed = &saved.0[&ident!("killed").to_string_ident()];
+    assert_eq!(killed[&ident!("zombie").to_string_ident()], 1);
+
+    assert_eq!(Statistics::load(&saved), stats);
+}
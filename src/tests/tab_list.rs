@@ -0,0 +1,197 @@
+use bevy_app::App;
+use valence_client::Username;
+use valence_core::despawn::Despawned;
+use valence_core::protocol::Decode;
+use valence_core::text::Text;
+use valence_player_list::packet::{PlayerListHeaderS2c, PlayerListS2c};
+use valence_player_list::tab_list::TabList;
+use valence_player_list::{Listed, PlayerList, PlayerListEntryBundle};
+use valence_scoreboard::packet::{Mode, TeamS2c};
+
+use super::*;
+
+fn decode_all<'a, P: Packet + Decode<'a>>(frames: &'a PacketFrames) -> Vec<P> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == P::ID)
+        .map(|f| f.decode::<P>().unwrap())
+        .collect()
+}
+
+fn spawn_entry(app: &mut App, name: &str) -> Entity {
+    app.world
+        .spawn(PlayerListEntryBundle {
+            username: Username(name.into()),
+            ..Default::default()
+        })
+        .id()
+}
+
+#[test]
+fn tab_list_overrides_entries() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let entry_ent = spawn_entry(&mut app, "fake");
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut tab_list = app.world.get_mut::<TabList>(client_ent).unwrap();
+    tab_list.hide(entry_ent);
+    tab_list.set_display_name(entry_ent, "Fake");
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = decode_all::<PlayerListS2c>(&frames);
+    assert_eq!(updates.len(), 1);
+    assert!(updates[0].actions.update_listed());
+    assert!(updates[0].actions.update_display_name());
+    assert!(!updates[0].entries[0].listed);
+    assert_eq!(
+        updates[0].entries[0].display_name.as_deref(),
+        Some(&"Fake".into())
+    );
+
+    // Updates sent to all clients are overridden again.
+    app.world.get_mut::<Listed>(entry_ent).unwrap().0 = true;
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = decode_all::<PlayerListS2c>(&frames);
+    assert_eq!(updates.len(), 2);
+    assert!(updates[0].entries[0].listed);
+    assert!(!updates[1].entries[0].listed);
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<PlayerListS2c>(0);
+
+    let mut tab_list = app.world.get_mut::<TabList>(client_ent).unwrap();
+    tab_list.show(entry_ent);
+    tab_list.reset_display_name(entry_ent);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let updates = decode_all::<PlayerListS2c>(&frames);
+    assert_eq!(updates.len(), 1);
+    assert!(updates[0].entries[0].listed);
+    assert_eq!(updates[0].entries[0].display_name, None);
+}
+
+#[test]
+fn tab_list_order() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let a = spawn_entry(&mut app, "a");
+    let b = spawn_entry(&mut app, "b");
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world
+        .get_mut::<TabList>(client_ent)
+        .unwrap()
+        .set_order([b, a]);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 2);
+    assert!(teams[0].team_name < teams[1].team_name);
+    assert!(matches!(&teams[0].mode, Mode::CreateTeam { entities, .. } if entities == &["b"]));
+    assert!(matches!(&teams[1].mode, Mode::CreateTeam { entities, .. } if entities == &["a"]));
+
+    app.world
+        .get_mut::<TabList>(client_ent)
+        .unwrap()
+        .set_order([a, b]);
+
+    app.update();
+
+    // Names are removed from their teams before they are added to new ones.
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 4);
+    assert_eq!(
+        teams[0].mode,
+        Mode::RemoveEntities {
+            entities: vec!["b"]
+        }
+    );
+    assert_eq!(
+        teams[1].mode,
+        Mode::RemoveEntities {
+            entities: vec!["a"]
+        }
+    );
+    assert_eq!(
+        teams[2].mode,
+        Mode::AddEntities {
+            entities: vec!["a"]
+        }
+    );
+    assert_eq!(
+        teams[3].mode,
+        Mode::AddEntities {
+            entities: vec!["b"]
+        }
+    );
+
+    // Despawned entries leave the order.
+    app.world.entity_mut(b).insert(Despawned);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let teams = decode_all::<TeamS2c>(&frames);
+    assert_eq!(teams.len(), 1);
+    assert_eq!(teams[0].mode, Mode::RemoveTeam);
+    assert_eq!(app.world.get::<TabList>(client_ent).unwrap().order(), [a]);
+}
+
+#[test]
+fn tab_list_header_footer() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world
+        .get_mut::<TabList>(client_ent)
+        .unwrap()
+        .set_header("Mine");
+    app.world
+        .resource_mut::<PlayerList>()
+        .set_footer("Everyone's");
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let headers = decode_all::<PlayerListHeaderS2c>(&frames);
+    assert_eq!(headers.len(), 2);
+    assert_eq!(*headers[0].header, Text::default());
+    assert_eq!(*headers[1].header, "Mine".into());
+    assert_eq!(*headers[1].footer, "Everyone's".into());
+
+    app.world
+        .get_mut::<TabList>(client_ent)
+        .unwrap()
+        .reset_header();
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    let headers = decode_all::<PlayerListHeaderS2c>(&frames);
+    assert_eq!(headers.len(), 1);
+    assert_eq!(*headers[0].header, Text::default());
+}
@@ -0,0 +1,64 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_client::packet::PlayerRespawnS2c;
+use valence_client::teleport::{PlayerPositionLookFlags, PlayerPositionLookS2c, Teleport};
+use valence_entity::Position;
+use valence_instance::packet::ChunkDataS2c;
+use valence_instance::Chunk;
+use valence_inventory::packet::InventoryS2c;
+
+use super::*;
+
+#[test]
+fn teleport_to_other_instance() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.world.get_mut::<Position>(client_ent).unwrap().0 = DVec3::new(8.0, 64.0, 8.0);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let mut instance = Instance::new(
+        ident!("overworld"),
+        app.world.resource::<DimensionTypeRegistry>(),
+        app.world.resource::<BiomeRegistry>(),
+        app.world.resource::<Server>(),
+    );
+
+    instance.insert_chunk([0, 0], Chunk::default());
+
+    let instance_ent = app.world.spawn(instance).id();
+
+    // Teleport to the same coordinates in the other instance.
+    app.world
+        .query::<Teleport>()
+        .get_mut(&mut app.world, client_ent)
+        .unwrap()
+        .teleport_to(instance_ent, [8.0, 64.0, 8.0], 90.0, 0.0);
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+
+    frames.assert_count::<PlayerRespawnS2c>(1);
+    frames.assert_count::<PlayerPositionLookS2c>(1);
+    frames.assert_count::<InventoryS2c>(1);
+
+    let index_of = |id| frames.0.iter().position(|frame| frame.id == id).unwrap();
+
+    // The chunks of the new instance must come after the respawn packet, or the
+    // client drops them.
+    assert!(index_of(PlayerRespawnS2c::ID) < index_of(ChunkDataS2c::ID));
+    assert!(index_of(PlayerRespawnS2c::ID) < index_of(PlayerPositionLookS2c::ID));
+
+    for frame in &frames.0 {
+        if frame.id == PlayerPositionLookS2c::ID {
+            let pkt = frame.decode::<PlayerPositionLookS2c>().unwrap();
+            assert_eq!(pkt.position, DVec3::new(8.0, 64.0, 8.0));
+            assert_eq!(pkt.yaw, 90.0);
+            assert_eq!(pkt.flags, PlayerPositionLookFlags::new());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use bevy_app::App;
+use valence_client::packet::{SignEditorOpenS2c, UpdateSignC2s};
+use valence_core::block_pos::BlockPos;
+use valence_core::text::Text;
+use valence_instance::packet::BlockUpdateS2c;
+use valence_inventory::packet::{
+    ClickMode, ClickSlotC2s, CloseHandledScreenC2s, OpenScreenS2c, RenameItemC2s,
+};
+use valence_inventory::{ClientInventoryState, Inventory, OpenInventory};
+use valence_text_input::{TextInputEvent, TextPrompt};
+
+use super::*;
+
+fn text_input_events(app: &App) -> Vec<TextInputEvent> {
+    app.world
+        .resource::<Events<TextInputEvent>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn sign_prompt() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world.entity_mut(client_ent).insert(TextPrompt::sign([
+        Text::default(),
+        "^^^^^^".into(),
+        "Enter a name".into(),
+        Text::default(),
+    ]));
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<BlockUpdateS2c>(1);
+    sent_packets.assert_count::<SignEditorOpenS2c>(1);
+
+    let mut position = BlockPos::new(0, 0, 0);
+    for pkt in &sent_packets.0 {
+        if pkt.id == SignEditorOpenS2c::ID {
+            position = pkt.decode::<SignEditorOpenS2c>().unwrap().location;
+        }
+    }
+
+    client_helper.send(&UpdateSignC2s {
+        position,
+        is_front_text: true,
+        lines: ["Steve", "^^^^^^", "Enter a name", ""],
+    });
+    app.update();
+
+    assert_eq!(
+        text_input_events(&app),
+        [TextInputEvent {
+            client: client_ent,
+            lines: Some(vec![
+                "Steve".into(),
+                "^^^^^^".into(),
+                "Enter a name".into(),
+                "".into()
+            ]),
+        }]
+    );
+    assert!(app.world.get::<TextPrompt>(client_ent).is_none());
+}
+
+#[test]
+fn anvil_prompt() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(TextPrompt::anvil("Name your pet", "Rex"));
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<OpenScreenS2c>(1);
+
+    let anvil_ent = app.world.get::<OpenInventory>(client_ent).unwrap().entity;
+    let anvil = app.world.get::<Inventory>(anvil_ent).unwrap();
+    assert_eq!(
+        anvil.slot(0).and_then(|s| s.display_name()),
+        Some("Rex".into())
+    );
+
+    client_helper.send(&RenameItemC2s { item_name: "Fido" });
+    app.update();
+
+    let anvil = app.world.get::<Inventory>(anvil_ent).unwrap();
+    assert_eq!(
+        anvil.slot(2).and_then(|s| s.display_name()),
+        Some("Fido".into())
+    );
+
+    // Taking the result submits the text.
+    let inv_state = app.world.get::<ClientInventoryState>(client_ent).unwrap();
+    client_helper.send(&ClickSlotC2s {
+        window_id: inv_state.window_id(),
+        state_id: VarInt(inv_state.state_id().0),
+        slot_idx: 2,
+        button: 0,
+        mode: ClickMode::Click,
+        slot_changes: vec![],
+        carried_item: None,
+    });
+    app.update();
+
+    assert_eq!(
+        text_input_events(&app),
+        [TextInputEvent {
+            client: client_ent,
+            lines: Some(vec!["Fido".into()]),
+        }]
+    );
+
+    app.update();
+
+    assert!(app.world.get::<TextPrompt>(client_ent).is_none());
+    assert!(app.world.get::<OpenInventory>(client_ent).is_none());
+    assert!(app.world.get_entity(anvil_ent).is_none());
+}
+
+#[test]
+fn anvil_prompt_closed() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    app.world
+        .entity_mut(client_ent)
+        .insert(TextPrompt::anvil("Name your pet", "Rex"));
+    app.update();
+
+    let window_id = app
+        .world
+        .get::<ClientInventoryState>(client_ent)
+        .unwrap()
+        .window_id();
+
+    client_helper.send(&CloseHandledScreenC2s {
+        window_id: window_id as i8,
+    });
+    app.update();
+
+    assert_eq!(
+        text_input_events(&app),
+        [TextInputEvent {
+            client: client_ent,
+            lines: None,
+        }]
+    );
+    assert!(app.world.get::<TextPrompt>(client_ent).is_none());
+}
@@ -0,0 +1,67 @@
+use bevy_app::App;
+use valence_client::time::{FreezeTime, TimeOfDay};
+use valence_instance::packet::WorldTimeUpdateS2c;
+
+use super::*;
+
+#[test]
+fn test_time_of_day_instance() {
+    let mut app = App::new();
+    let (_, mut client_helper) = scenario_single_client(&mut app);
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    let instance_ent = find_instance(&app);
+
+    app.world.entity_mut(instance_ent).insert(TimeOfDay(6000));
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<WorldTimeUpdateS2c>(1);
+
+    // Time advances by one each tick without sending packets until the next
+    // resync.
+    app.update();
+    assert_eq!(
+        app.world.get::<TimeOfDay>(instance_ent),
+        Some(&TimeOfDay(6002))
+    );
+
+    app.world.entity_mut(instance_ent).insert(FreezeTime(true));
+    app.update();
+    app.update();
+
+    assert_eq!(
+        app.world.get::<TimeOfDay>(instance_ent),
+        Some(&TimeOfDay(6002))
+    );
+}
+
+#[test]
+fn test_time_of_day_client_override() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+
+    let instance_ent = find_instance(&app);
+
+    app.world.entity_mut(instance_ent).insert(TimeOfDay(1000));
+    app.world
+        .entity_mut(client_ent)
+        .insert((TimeOfDay(18000), FreezeTime(true)));
+    app.update();
+
+    let sent_packets = client_helper.collect_sent();
+    sent_packets.assert_count::<WorldTimeUpdateS2c>(1);
+
+    for pkt in &sent_packets.0 {
+        if pkt.id == WorldTimeUpdateS2c::ID {
+            let pkt = pkt.decode::<WorldTimeUpdateS2c>().unwrap();
+            assert_eq!(pkt.time_of_day, -18000);
+        }
+    }
+}
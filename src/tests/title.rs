@@ -0,0 +1,113 @@
+use bevy_app::App;
+use valence_client::title::{
+    ActionBarQueue, ClearTitleS2c, OverlayMessageS2c, SetTitle, SubtitleS2c, TitleFadeS2c, TitleS2c,
+};
+use valence_client::Client;
+use valence_core::text::Text;
+
+use super::*;
+
+fn action_bars(frames: &PacketFrames) -> Vec<Text> {
+    frames
+        .0
+        .iter()
+        .filter(|f| f.id == OverlayMessageS2c::ID)
+        .map(|f| {
+            f.decode::<OverlayMessageS2c>()
+                .unwrap()
+                .action_bar_text
+                .into_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn title_packet_order() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut client = app.world.get_mut::<Client>(client_ent).unwrap();
+    client.set_title("Title", "Subtitle", 10, 70, 20);
+    client.clear_title();
+
+    app.update();
+
+    let frames = client_helper.collect_sent();
+    frames.assert_order::<(TitleFadeS2c, SubtitleS2c, TitleS2c, ClearTitleS2c)>();
+
+    let fade = frames
+        .0
+        .iter()
+        .find(|f| f.id == TitleFadeS2c::ID)
+        .unwrap()
+        .decode::<TitleFadeS2c>()
+        .unwrap();
+    assert_eq!((fade.fade_in, fade.stay, fade.fade_out), (10, 70, 20));
+}
+
+#[test]
+fn action_bar_queue() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    app.update();
+    client_helper.clear_sent();
+
+    let mut queue = app.world.get_mut::<ActionBarQueue>(client_ent).unwrap();
+    queue.push("first", 100);
+    queue.push("second", 2);
+
+    app.update();
+
+    assert_eq!(action_bars(&client_helper.collect_sent()), ["first".into()]);
+
+    // Long messages are sent again before they fade out.
+    for _ in 0..40 {
+        app.update();
+    }
+
+    assert_eq!(action_bars(&client_helper.collect_sent()), ["first".into()]);
+
+    for _ in 0..60 {
+        app.update();
+    }
+
+    assert_eq!(
+        action_bars(&client_helper.collect_sent()),
+        ["first".into(), "second".into()]
+    );
+
+    app.update();
+    app.update();
+
+    // The last message is removed when its time runs out.
+    assert_eq!(
+        action_bars(&client_helper.collect_sent()),
+        [Text::default()]
+    );
+
+    app.update();
+
+    assert!(action_bars(&client_helper.collect_sent()).is_empty());
+
+    let mut queue = app.world.get_mut::<ActionBarQueue>(client_ent).unwrap();
+    queue.push("third", 100);
+    queue.push("fourth", 100);
+
+    app.update();
+
+    app.world
+        .get_mut::<ActionBarQueue>(client_ent)
+        .unwrap()
+        .clear();
+
+    app.update();
+
+    assert_eq!(
+        action_bars(&client_helper.collect_sent()),
+        ["third".into(), Text::default()]
+    );
+}
@@ -0,0 +1,91 @@
+use bevy_app::App;
+use glam::DVec3;
+use valence_client::movement::VehicleMoveC2s;
+use valence_client::packet::{PlayerInputC2s, PlayerInputFlags};
+use valence_client::vehicle::{Passengers, Vehicle, VehicleMoveEvent};
+use valence_entity::boat::BoatEntityBundle;
+use valence_entity::packet::EntityPassengersSetS2c;
+use valence_entity::{Location, Position};
+use valence_instance::Chunk;
+
+use super::*;
+
+#[test]
+fn ride_and_dismount_boat() {
+    let mut app = App::new();
+    let (client_ent, mut client_helper) = scenario_single_client(&mut app);
+
+    let instance_ent = find_instance(&app);
+
+    app.world
+        .get_mut::<Instance>(instance_ent)
+        .unwrap()
+        .insert_chunk([0, 0], Chunk::default());
+
+    let boat_ent = app
+        .world
+        .spawn(BoatEntityBundle {
+            location: Location(instance_ent),
+            position: Position::new([2.0, 0.0, 2.0]),
+            ..Default::default()
+        })
+        .id();
+
+    // Process a tick to get past the "on join" logic.
+    app.update();
+    client_helper.clear_sent();
+
+    // Mount the client on the boat.
+    app.world
+        .entity_mut(boat_ent)
+        .insert(Passengers(vec![client_ent]));
+
+    app.update();
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityPassengersSetS2c>(1);
+
+    assert_eq!(
+        app.world.get::<Vehicle>(client_ent).map(|v| v.get()),
+        Some(boat_ent)
+    );
+    assert_eq!(
+        app.world.get::<Position>(client_ent).unwrap().0,
+        DVec3::new(2.0, 0.0, 2.0)
+    );
+
+    // Move the boat from the client.
+    client_helper.send(&VehicleMoveC2s {
+        position: DVec3::new(5.0, 0.0, 5.0),
+        yaw: 90.0,
+        pitch: 0.0,
+    });
+
+    app.update();
+
+    let new_pos = DVec3::new(5.0, 0.0, 5.0);
+    assert_eq!(app.world.get::<Position>(boat_ent).unwrap().0, new_pos);
+    assert_eq!(app.world.get::<Position>(client_ent).unwrap().0, new_pos);
+
+    let events = app.world.resource::<Events<VehicleMoveEvent>>();
+    assert_eq!(events.iter_current_update_events().count(), 1);
+
+    client_helper.clear_sent();
+
+    // Dismount by sneaking.
+    client_helper.send(&PlayerInputC2s {
+        sideways: 0.0,
+        forward: 0.0,
+        flags: PlayerInputFlags::new().with_unmount(true),
+    });
+
+    app.update();
+
+    assert!(app.world.get::<Passengers>(boat_ent).unwrap().0.is_empty());
+    assert!(app.world.get::<Vehicle>(client_ent).is_none());
+
+    client_helper
+        .collect_sent()
+        .assert_count::<EntityPassengersSetS2c>(1);
+}
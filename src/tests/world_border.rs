@@ -105,6 +105,31 @@ fn test_portal_tp_boundary() {
     frames.assert_count::<WorldBorderInitializeS2c>(1);
 }
 
+#[test]
+fn test_contains() {
+    let mut app = App::new();
+    let (_, instance_ent) = prepare(&mut app);
+
+    let mut ins_mut = app.world.entity_mut(instance_ent);
+    let mut center: Mut<WorldBorderCenter> = ins_mut
+        .get_mut()
+        .expect("Expect world border to be present!");
+    center.0 = [10.0, 10.0].into();
+
+    let border = app
+        .world
+        .query::<WorldBorder>()
+        .get(&app.world, instance_ent)
+        .unwrap();
+
+    assert!(border.contains([10.0, 64.0, 10.0]));
+    assert!(border.contains([14.9, -64.0, 5.1]));
+    assert!(!border.contains([15.1, 0.0, 10.0]));
+    assert!(!border.contains([10.0, 0.0, 4.0]));
+    assert_eq!(border.distance_to_edge([12.0, 0.0, 11.0]), 3.0);
+    assert_eq!(border.distance_to_edge([20.0, 0.0, 10.0]), -5.0);
+}
+
 fn prepare(app: &mut App) -> (MockClientHelper, Entity) {
     let (_, mut client_helper) = scenario_single_client(app);
 
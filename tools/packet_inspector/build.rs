@@ -166,7 +166,18 @@ fn write_transformer(packets: &[Packet]) -> anyhow::Result<()> {
 
                 match_arms.extend(quote! {
                     #name::ID => {
-                        Ok(format!("{:#?}", #name::decode(&mut data)))
+                        let packet = #name::decode(&mut data)?;
+
+                        if !data.is_empty() {
+                            return Err(format!(
+                                "{} bytes left over after decoding {}",
+                                data.len(),
+                                stringify!(#name),
+                            )
+                            .into());
+                        }
+
+                        Ok(format!("{packet:#?}"))
                     }
                 });
             }
@@ -200,6 +211,9 @@ fn write_transformer(packets: &[Packet]) -> anyhow::Result<()> {
     let generated = quote! {
         const NOT_AVAILABLE: &str = "Not yet implemented";
 
+        /// Decodes a packet and formats it with its `Debug` implementation.
+        /// Fails if the packet can't be decoded or has data left over, which
+        /// means the packet definition doesn't match the packet.
         pub fn packet_to_string(packet: &ProxyPacket) -> Result<String, Box<dyn std::error::Error>> {
             let bytes = packet.data.as_ref().unwrap();
             let mut data = &bytes.clone()[..];
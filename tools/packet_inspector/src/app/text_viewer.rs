@@ -1,45 +1,6 @@
-use super::{SharedState, Tab, View};
+use packet_inspector::packet_to_string;
 
-mod utils {
-    use packet_inspector::Packet as ProxyPacket;
-    use packet_inspector::{PacketSide, PacketState};
-    use valence::protocol::{Decode, Packet};
-
-    use valence::advancement::packet::*;
-    use valence::client::action::*;
-    use valence::client::command::*;
-    use valence::client::custom_payload::*;
-    use valence::client::hand_swing::*;
-    use valence::client::interact_block::*;
-    use valence::client::interact_entity::*;
-    use valence::client::interact_item::*;
-    use valence::client::keepalive::*;
-    use valence::client::movement::*;
-    use valence::client::packet::structure_block::*;
-    use valence::client::packet::*;
-    use valence::client::resource_pack::*;
-    use valence::client::settings::*;
-    use valence::client::status::*;
-    use valence::client::teleport::*;
-    use valence::client::title::*;
-    use valence::entity::packet::*;
-    use valence::instance::packet::*;
-    use valence::inventory::packet::synchronize_recipes::*;
-    use valence::inventory::packet::*;
-    use valence::network::packet::*;
-    use valence::particle::*;
-    use valence::player_list::packet::*;
-    use valence::protocol::packet::boss_bar::*;
-    use valence::protocol::packet::chat::*;
-    use valence::protocol::packet::command::*;
-    use valence::protocol::packet::map::*;
-    use valence::protocol::packet::scoreboard::*;
-    use valence::protocol::packet::sound::*;
-    use valence::registry::tags::*;
-    use valence::world_border::packet::*;
-
-    include!(concat!(env!("OUT_DIR"), "/packet_to_string.rs"));
-}
+use super::{SharedState, Tab, View};
 
 pub struct TextView {
     last_packet_id: Option<usize>,
@@ -71,7 +32,7 @@ impl View for TextView {
         if self.last_packet_id != Some(packet_index) {
             self.last_packet_id = Some(packet_index);
 
-            self.packet_str = match utils::packet_to_string(&packets[packet_index]) {
+            self.packet_str = match packet_to_string(&packets[packet_index]) {
                 Ok(str) => str,
                 Err(err) => format!("Error: {}", err),
             };
@@ -1,9 +1,11 @@
+mod packet_decode;
 mod packet_io;
 mod packet_registry;
 
 use std::net::SocketAddr;
 use std::sync::{Arc, OnceLock};
 
+pub use packet_decode::packet_to_string;
 pub use packet_registry::Packet;
 use tokio::net::TcpStream;
 use tokio::sync::RwLock;
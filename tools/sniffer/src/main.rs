@@ -0,0 +1,266 @@
+#![doc = include_str!("../README.md")]
+#![deny(
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    rustdoc::missing_crate_level_docs,
+    rustdoc::invalid_codeblock_attributes,
+    rustdoc::invalid_rust_codeblocks,
+    rustdoc::bare_urls,
+    rustdoc::invalid_html_tags
+)]
+#![warn(
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_lifetimes,
+    unused_import_braces,
+    clippy::dbg_macro
+)]
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use packet_inspector::{packet_to_string, Packet, PacketSide, PacketState, Proxy};
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use tracing::warn;
+
+#[derive(Parser, Clone, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// The socket address to listen for connections on. This is the address
+    /// clients should connect to.
+    listener_addr: SocketAddr,
+    /// The socket address of the server the proxy connects to.
+    server_addr: SocketAddr,
+    /// Only show packets in these states.
+    #[arg(long, value_enum)]
+    state: Vec<State>,
+    /// Only show packets sent in this direction.
+    #[arg(long, value_enum)]
+    side: Option<Side>,
+    /// Only show packets with these IDs. IDs are decimal or hexadecimal with a
+    /// `0x` prefix.
+    #[arg(long, value_parser = parse_id)]
+    id: Vec<i32>,
+    /// Don't show packets with these IDs.
+    #[arg(long, value_parser = parse_id)]
+    exclude_id: Vec<i32>,
+    /// Only show packets that failed to decode.
+    #[arg(long)]
+    errors_only: bool,
+    /// Write packets to this file as JSON lines instead of printing them.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Print packets as JSON lines instead of pretty-printing them.
+    #[arg(long)]
+    json: bool,
+    /// Include the raw data of packets as hexadecimal.
+    #[arg(long)]
+    raw: bool,
+    /// The maximum length of pretty-printed packets. Longer packets are
+    /// truncated. Packets are never truncated in JSON.
+    #[arg(long, default_value_t = 2048)]
+    max_len: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+enum State {
+    Handshaking,
+    Status,
+    Login,
+    Play,
+}
+
+impl State {
+    fn matches(self, state: PacketState) -> bool {
+        matches!(
+            (self, state),
+            (State::Handshaking, PacketState::Handshaking)
+                | (State::Status, PacketState::Status)
+                | (State::Login, PacketState::Login)
+                | (State::Play, PacketState::Play)
+        )
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+enum Side {
+    Clientbound,
+    Serverbound,
+}
+
+impl Side {
+    fn matches(self, side: PacketSide) -> bool {
+        matches!(
+            (self, side),
+            (Side::Clientbound, PacketSide::Clientbound)
+                | (Side::Serverbound, PacketSide::Serverbound)
+        )
+    }
+}
+
+fn parse_id(s: &str) -> Result<i32, String> {
+    let res = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => i32::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+
+    res.map_err(|e| format!("invalid packet ID `{s}`: {e}"))
+}
+
+impl Args {
+    fn filter(&self, packet: &Packet) -> bool {
+        (self.state.is_empty() || self.state.iter().any(|s| s.matches(packet.state)))
+            && self.side.map_or(true, |s| s.matches(packet.side))
+            && (self.id.is_empty() || self.id.contains(&packet.id))
+            && !self.exclude_id.contains(&packet.id)
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Logs go to stderr so that stdout only has packets.
+    tracing_subscriber::fmt().with_writer(io::stderr).init();
+
+    let args = Args::parse();
+
+    let mut out: Box<dyn Write + Send> = match &args.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(io::stdout()),
+    };
+
+    let json = args.json || args.output.is_some();
+
+    let proxy = Proxy::new(args.listener_addr, args.server_addr);
+    let receiver = proxy.subscribe().await;
+
+    tokio::spawn(async move {
+        if let Err(e) = proxy.run().await {
+            warn!("proxy stopped: {e:#}");
+        }
+    });
+
+    // The number of packets that failed to decode, by name.
+    let mut errors = BTreeMap::<String, usize>::new();
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        let packet = tokio::select! {
+            packet = receiver.recv_async() => packet?,
+            _ = &mut ctrl_c => break,
+        };
+
+        let decoded = packet_to_string(&packet).map_err(|e| e.to_string());
+
+        if let Err(e) = &decoded {
+            warn!(
+                "failed to decode {:?} {:?} packet 0x{:02X} \"{}\": {e}",
+                packet.side, packet.state, packet.id, packet.name
+            );
+
+            *errors
+                .entry(format!(
+                    "{:?} {:?} {}",
+                    packet.side, packet.state, packet.name
+                ))
+                .or_default() += 1;
+        }
+
+        if !args.filter(&packet) || (args.errors_only && decoded.is_ok()) {
+            continue;
+        }
+
+        if json {
+            writeln!(out, "{}", to_json(&packet, &decoded, args.raw))?;
+        } else {
+            write_pretty(&mut out, &packet, &decoded, &args)?;
+        }
+    }
+
+    out.flush()?;
+
+    if errors.is_empty() {
+        eprintln!("All packets decoded successfully.");
+    } else {
+        eprintln!("Packets that failed to decode:");
+
+        for (packet, count) in &errors {
+            eprintln!("  {packet}: {count}");
+        }
+    }
+
+    Ok(())
+}
+
+fn to_json(packet: &Packet, decoded: &Result<String, String>, raw: bool) -> serde_json::Value {
+    let mut value = json!({
+        "time": packet.timestamp.and_then(|t| t.format(&Rfc3339).ok()),
+        "side": format!("{:?}", packet.side),
+        "state": format!("{:?}", packet.state),
+        "id": packet.id,
+        "name": packet.name,
+    });
+
+    match decoded {
+        Ok(decoded) => value["packet"] = decoded.as_str().into(),
+        Err(e) => value["error"] = e.as_str().into(),
+    }
+
+    if raw {
+        value["data"] = hex(packet).into();
+    }
+
+    value
+}
+
+fn write_pretty(
+    out: &mut impl Write,
+    packet: &Packet,
+    decoded: &Result<String, String>,
+    args: &Args,
+) -> io::Result<()> {
+    let arrow = match packet.side {
+        PacketSide::Clientbound => "S -> C",
+        PacketSide::Serverbound => "C -> S",
+    };
+
+    writeln!(
+        out,
+        "{arrow} [{:?}] 0x{:02X} \"{}\"",
+        packet.state, packet.id, packet.name
+    )?;
+
+    match decoded {
+        Ok(decoded) => match decoded.char_indices().nth(args.max_len) {
+            Some((i, _)) => writeln!(out, "{}...", &decoded[..i])?,
+            None => writeln!(out, "{decoded}")?,
+        },
+        Err(e) => writeln!(out, "Error: {e}")?,
+    }
+
+    if args.raw {
+        writeln!(out, "{}", hex(packet))?;
+    }
+
+    Ok(())
+}
+
+fn hex(packet: &Packet) -> String {
+    packet
+        .data
+        .iter()
+        .flat_map(|data| data.iter())
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}